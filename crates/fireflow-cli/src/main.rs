@@ -1,8 +1,10 @@
-use fireflow_core::api::*;
+use fireflow_core::compat::Software;
 use fireflow_core::config;
-use fireflow_core::core::AnyCoreDataset;
 use fireflow_core::error::*;
-use fireflow_core::header::Version;
+use fireflow_core::manifest::Manifest;
+use fireflow_core::policy::{ShortnamePattern, SitePolicy};
+use fireflow_core::prelude::*;
+use fireflow_core::scan::{find_fcs_files, scan_files, ScanConfig};
 use fireflow_core::segment::HeaderCorrection;
 use fireflow_core::text::byteord::ByteOrd2_0;
 use fireflow_core::validated::datepattern::DatePattern;
@@ -14,7 +16,10 @@ use serde::ser::Serialize;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
 
 fn main() -> Result<(), ()> {
     let correction_arg = |long: &'static str, help: &'static str| {
@@ -144,6 +149,21 @@ fn main() -> Result<(), ()> {
 
     let trim_value_whitespace = flag_arg(TRIM_VALUE_WHITESPACE, "trim whitespace from all values");
 
+    let control_chars = Arg::new(CONTROL_CHARS)
+        .long(CONTROL_CHARS)
+        .value_name("POLICY")
+        .value_parser(["preserve", "escape", "strip"])
+        .help("how to handle control characters (ie newlines) in values");
+
+    let keyword_allowlist = Arg::new(KEYWORD_ALLOWLIST)
+        .long(KEYWORD_ALLOWLIST)
+        .action(ArgAction::Append)
+        .value_name("KEY")
+        .help(
+            "only keep these keys (including '$' for standard keys) and stop \
+             reading TEXT once all are found",
+        );
+
     let all_raw_args = [
         version_override,
         supp_text_correction_begin,
@@ -163,6 +183,8 @@ fn main() -> Result<(), ()> {
         allow_stext_own_delim,
         allow_missing_nextdata,
         trim_value_whitespace,
+        control_chars,
+        keyword_allowlist,
     ];
 
     // std args
@@ -202,6 +224,16 @@ fn main() -> Result<(), ()> {
 
     let allow_unused_standard = flag_arg(ALLOW_UNUSED_STANDARD, "allow unused standard keywords");
 
+    let absorb_orphan_meas = flag_arg(
+        ABSORB_ORPHAN_MEAS_KEYWORDS,
+        "move $Pn* keywords not indexed by $PAR to nonstandard keywords",
+    );
+
+    let disallow_orphan_meas = flag_arg(
+        DISALLOW_ORPHAN_MEAS_KEYWORDS,
+        "throw error if any $Pn* keywords are not indexed by $PAR",
+    );
+
     let disallow_deprecated = flag_arg(
         DISALLOW_DEPRECATED,
         "throw error if any deprecated keywords are present",
@@ -230,6 +262,16 @@ fn main() -> Result<(), ()> {
              must include '%n' which will be replaced with measurement index",
         );
 
+    let exclude_meas_pattern = Arg::new(EXCLUDE_MEAS_PATTERN)
+        .long(EXCLUDE_MEAS_PATTERN)
+        .value_name("REGEXP")
+        .help("drop measurements whose $PnN matches this pattern");
+
+    let timestamps_from_conventions = flag_arg(
+        TIMESTAMPS_FROM_CONVENTIONS,
+        "fill missing $BTIM/$DATE from vendor epoch timestamp keywords",
+    );
+
     let all_std_args = [
         time_meas_pattern,
         allow_missing_time,
@@ -241,9 +283,13 @@ fn main() -> Result<(), ()> {
         time_pattern,
         allow_pseudostandard,
         allow_unused_standard,
+        absorb_orphan_meas,
+        disallow_orphan_meas,
         disallow_deprecated,
         fix_log_scale_offset,
         ns_meas_pattern,
+        exclude_meas_pattern,
+        timestamps_from_conventions,
     ];
 
     // offset args
@@ -322,10 +368,17 @@ fn main() -> Result<(), ()> {
          dictated by $DATATYPE and $PnB.",
     );
 
+    let disallow_mixed_float_byteord = flag_arg(
+        DISALLOW_MIXED_FLOAT_BYTEORD,
+        "throw error if $BYTEORD is neither big nor little endian for float \
+         layouts; only has effect in 2.0/3.0",
+    );
+
     let all_layout_args = [
         int_widths_from_byteord,
         int_byteord_override,
         disallow_range_truncation,
+        disallow_mixed_float_byteord,
     ];
 
     // dataset args
@@ -346,7 +399,13 @@ fn main() -> Result<(), ()> {
 
     let warnings_are_errors = flag_arg(WARNINGS_ARE_ERRORS, "treat all warnings as fatal errors");
 
-    let all_shared_args = [warnings_are_errors];
+    let max_warnings = Arg::new(MAX_WARNINGS)
+        .long(MAX_WARNINGS)
+        .value_name("N")
+        .help("max number of warnings (and errors) to print (default 10000)")
+        .value_parser(value_parser!(usize));
+
+    let all_shared_args = [warnings_are_errors, max_warnings];
 
     // other args
 
@@ -363,6 +422,76 @@ fn main() -> Result<(), ()> {
         .help("path to FCS file to parse")
         .required(true);
 
+    let sed_key = Arg::new(SED_KEY)
+        .long(SED_KEY)
+        .short('k')
+        .value_name("KEY")
+        .help("key whose value should be replaced (case-insensitive)")
+        .required(true);
+
+    let sed_replace = Arg::new(SED_REPLACE)
+        .long(SED_REPLACE)
+        .short('r')
+        .value_name("VALUE")
+        .help("new value to assign to key")
+        .required(true);
+
+    let sed_match = Arg::new(SED_MATCH)
+        .long(SED_MATCH)
+        .short('m')
+        .value_name("VALUE")
+        .help("only replace if current value is exactly this");
+
+    let dry_run = flag_arg(DRY_RUN, "show changes but don't write anything");
+
+    let estimate_memory = flag_arg(
+        ESTIMATE_MEMORY,
+        "also read TEXT and show bytes needed to hold DATA in memory",
+    );
+
+    let check_compat = Arg::new(CHECK_COMPAT)
+        .long(CHECK_COMPAT)
+        .value_name("SOFTWARE")
+        .value_parser(["flowcore"])
+        .help("only check compatibility with this software");
+
+    let max_threads = Arg::new(MAX_THREADS)
+        .long(MAX_THREADS)
+        .value_name("N")
+        .help("max number of files to process in parallel")
+        .value_parser(value_parser!(NonZeroUsize));
+
+    let max_open_files = Arg::new(MAX_OPEN_FILES)
+        .long(MAX_OPEN_FILES)
+        .value_name("N")
+        .help("max number of files which may be open at once")
+        .value_parser(value_parser!(NonZeroUsize));
+
+    let sed_paths = Arg::new(SED_PATHS)
+        .value_parser(value_parser!(PathBuf))
+        .num_args(1..)
+        .help("FCS files or directories which will be searched recursively for *.fcs files")
+        .required(true);
+
+    let policy = Arg::new(POLICY)
+        .long(POLICY)
+        .value_name("PATH")
+        .value_parser(value_parser!(PathBuf))
+        .help("TOML file with site-specific rules to check in addition to the standard");
+
+    let out_path = Arg::new(OUT_PATH)
+        .long(OUT_PATH)
+        .short('o')
+        .value_name("PATH")
+        .value_parser(value_parser!(PathBuf))
+        .help("write output here instead of stdout");
+
+    let persist_version = flag_arg(
+        PERSIST_VERSION,
+        "if valid, rewrite the version in HEADER to that given by --version-override",
+    )
+    .requires(VERSION_OVERRIDE);
+
     let cmd = Command::new("fireflow")
         .about("read and write FCS files")
         .arg_required_else_help(true)
@@ -370,7 +499,13 @@ fn main() -> Result<(), ()> {
             Command::new(SUBCMD_HEADER)
                 .about("show header as JSON")
                 .arg(&input_arg)
-                .args(&all_header_args),
+                .arg(&estimate_memory)
+                .args(&all_header_args)
+                .args(&all_raw_args)
+                .args(&all_std_args)
+                .args(&all_offset_args)
+                .args(&all_layout_args)
+                .args(&all_shared_args),
         )
         .subcommand(
             Command::new(SUBCMD_RAW)
@@ -427,6 +562,64 @@ fn main() -> Result<(), ()> {
                 .args(&all_dataset_args)
                 .args(&all_shared_args)
                 .arg(&delim_arg),
+        )
+        .subcommand(
+            Command::new(SUBCMD_VALIDATE)
+                .about(
+                    "read an entire file and report all warnings, errors, and \
+                     compatibility with third-party software",
+                )
+                .arg(&input_arg)
+                .arg(&policy)
+                .arg(&persist_version)
+                .args(&all_header_args)
+                .args(&all_raw_args)
+                .args(&all_std_args)
+                .args(&all_offset_args)
+                .args(&all_layout_args)
+                .args(&all_dataset_args)
+                .args(&all_shared_args),
+        )
+        .subcommand(
+            Command::new(SUBCMD_INFO)
+                .about("report whether a file is likely to open in third-party software (flowCore)")
+                .arg(&input_arg)
+                .arg(&check_compat)
+                .args(&all_header_args)
+                .args(&all_raw_args)
+                .args(&all_std_args)
+                .args(&all_offset_args)
+                .args(&all_layout_args)
+                .args(&all_shared_args),
+        )
+        .subcommand(
+            Command::new(SUBCMD_SED)
+                .about("replace a keyword value in TEXT in place across many files")
+                .arg(&sed_key)
+                .arg(&sed_replace)
+                .arg(&sed_match)
+                .arg(&dry_run)
+                .arg(&sed_paths)
+                .arg(&max_threads)
+                .arg(&max_open_files)
+                .args(&all_header_args)
+                .args(&all_raw_args)
+                .args(&all_shared_args),
+        )
+        .subcommand(
+            Command::new(SUBCMD_MANIFEST)
+                .about("summarize and validate many files as JSON, ie for data deposits")
+                .arg(&sed_paths)
+                .arg(&out_path)
+                .arg(&max_threads)
+                .arg(&max_open_files)
+                .args(&all_header_args)
+                .args(&all_raw_args)
+                .args(&all_std_args)
+                .args(&all_offset_args)
+                .args(&all_layout_args)
+                .args(&all_dataset_args)
+                .args(&all_shared_args),
         );
 
     let args = cmd.get_matches();
@@ -435,9 +628,23 @@ fn main() -> Result<(), ()> {
         Some((SUBCMD_HEADER, sargs)) => {
             let conf = parse_header_config(sargs);
             let filepath = parse_input_path(sargs);
-            fcs_read_header(filepath, &conf.into())
-                .map(|h| print_json(&h.inner()))
-                .map_err(handle_failure_nowarn)
+            let header = fcs_read_header(filepath, &conf.into())
+                .map(|h| h.inner())
+                .map_err(handle_failure_nowarn)?;
+            if sargs.get_flag(ESTIMATE_MEMORY) {
+                let std_conf = parse_std_config(sargs);
+                fcs_read_std_text(filepath, &std_conf)
+                    .map(handle_warnings)
+                    .map(|(_, out)| {
+                        let mut j = serde_json::to_value(&header).unwrap();
+                        j["estimated_memory_bytes"] = out.estimated_memory_bytes.into();
+                        print_json(&j)
+                    })
+                    .map_err(handle_failure)
+            } else {
+                print_json(&header);
+                Ok(())
+            }
         }
 
         Some((SUBCMD_RAW, sargs)) => {
@@ -488,6 +695,118 @@ fn main() -> Result<(), ()> {
                 .map_err(handle_failure)
         }
 
+        Some((SUBCMD_VALIDATE, sargs)) => {
+            let conf = parse_dataset_config(sargs);
+            let filepath = parse_input_path(sargs);
+            let persist = conf
+                .raw
+                .version_override
+                .filter(|_| sargs.get_flag(PERSIST_VERSION));
+            let policy = sargs
+                .get_one::<PathBuf>(POLICY)
+                .map(|p| read_policy(p))
+                .transpose()?;
+            fcs_read_std_dataset(filepath, &conf)
+                .map(handle_warnings)
+                .map_err(handle_failure)
+                .and_then(|(core, _)| {
+                    for s in Software::ALL {
+                        println!("{}: {}", filepath.display(), core.check_compat(s));
+                    }
+                    let vs = policy.map(|p| core.check_policy(&p)).unwrap_or_default();
+                    if vs.is_empty() {
+                        println!("{}: valid", filepath.display());
+                        persist.map_or(Ok(()), |v| {
+                            fcs_patch_version(filepath, v, &parse_std_config(sargs), false)
+                                .map(handle_warnings)
+                                .map(|old| {
+                                    let o = old.map_or("invalid".into(), |x| x.to_string());
+                                    println!("{}: version {o} -> {v}", filepath.display())
+                                })
+                                .map_err(handle_failure)
+                        })
+                    } else {
+                        for v in vs {
+                            eprintln!("POLICY VIOLATION: {v}");
+                        }
+                        Err(())
+                    }
+                })
+        }
+
+        Some((SUBCMD_INFO, sargs)) => {
+            let conf = parse_std_config(sargs);
+            let filepath = parse_input_path(sargs);
+            let softwares = sargs
+                .get_one::<String>(CHECK_COMPAT)
+                .and_then(|s| s.parse::<Software>().ok())
+                .map_or(Software::ALL.to_vec(), |s| vec![s]);
+            fcs_read_std_text(filepath, &conf)
+                .map(handle_warnings)
+                .map(|(core, _)| {
+                    for s in softwares {
+                        println!("{}", core.check_compat(s));
+                    }
+                })
+                .map_err(handle_failure)
+        }
+
+        Some((SUBCMD_SED, sargs)) => {
+            let conf = parse_raw_config(sargs);
+            let key = sargs.get_one::<String>(SED_KEY).unwrap().clone();
+            let new = sargs.get_one::<String>(SED_REPLACE).unwrap().clone();
+            let old = sargs.get_one::<String>(SED_MATCH).cloned();
+            let dry = sargs.get_flag(DRY_RUN);
+            let scan_conf = parse_scan_config(sargs);
+            let paths = find_fcs_files(sargs.get_many::<PathBuf>(SED_PATHS).unwrap());
+            let results = scan_files(paths, &scan_conf, move |p| {
+                let replace = |k: &str, v: &str| {
+                    (k.eq_ignore_ascii_case(&key) && old.as_ref().is_none_or(|o| o == v))
+                        .then(|| new.clone())
+                };
+                fcs_patch_text(&p.to_path_buf(), &conf, dry, replace)
+            });
+            let mut res = Ok(());
+            for (filepath, r) in results {
+                let r = r
+                    .map(handle_warnings)
+                    .map(|cs| print_changes(&filepath, &cs, dry))
+                    .map_err(|f| {
+                        eprintln!("{}:", filepath.display());
+                        handle_failure(f)
+                    });
+                res = res.and(r);
+            }
+            res
+        }
+
+        Some((SUBCMD_MANIFEST, sargs)) => {
+            let conf = parse_dataset_config(sargs);
+            let scan_conf = parse_scan_config(sargs);
+            let paths: Vec<_> = sargs
+                .get_many::<PathBuf>(SED_PATHS)
+                .unwrap()
+                .cloned()
+                .collect();
+            let m = Manifest::new(&paths[..], &conf, &scan_conf);
+            let j = serde_json::to_string_pretty(&m).unwrap();
+            if let Some(out) = sargs.get_one::<PathBuf>(OUT_PATH) {
+                fs::write(out, j).map_err(|e| {
+                    eprintln!("TOPLEVEL ERROR: could not write {}: {e}", out.display())
+                })?;
+            } else {
+                println!("{j}");
+            }
+            for e in m.files.iter().filter(|e| !e.errors.is_empty()) {
+                eprintln!("{}: invalid", e.path.display());
+            }
+            if m.is_valid() {
+                Ok(())
+            } else {
+                Err(())
+            }
+        }
+
         _ => Ok(()),
     }
 }
@@ -546,6 +865,13 @@ fn parse_header_and_text_config(sargs: &ArgMatches) -> config::ReadHeaderAndTEXT
         allow_stext_own_delim: sargs.get_flag(ALLOW_STEXT_OWN_DELIM),
         allow_missing_nextdata: sargs.get_flag(ALLOW_MISSING_NEXTDATA),
         trim_value_whitespace: sargs.get_flag(TRIM_VALUE_WHITESPACE),
+        control_chars: sargs
+            .get_one::<String>(CONTROL_CHARS)
+            .map(|s| s.parse().unwrap())
+            .unwrap_or_default(),
+        keyword_allowlist: sargs
+            .get_many::<String>(KEYWORD_ALLOWLIST)
+            .map(|xs| xs.map(|s| s.parse().unwrap()).collect()),
         ignore_standard_keys: KeyPatterns::default(),
         rename_standard_keys: KeyStringPairs::default(),
         promote_to_standard: KeyPatterns::default(),
@@ -565,6 +891,10 @@ fn parse_std_inner_config(sargs: &ArgMatches) -> config::StdTextReadConfig {
         .get_one::<String>(NS_MEAS_PATTERN)
         .cloned()
         .map(|s| s.parse::<NonStdMeasPattern>().unwrap());
+    let exclude_measurement_pattern = sargs
+        .get_one::<String>(EXCLUDE_MEAS_PATTERN)
+        .cloned()
+        .map(|s| s.parse::<config::ExcludeMeasNamePattern>().unwrap());
     let ignore_time_optical_keys = sargs
         .get_many::<String>(IGNORE_TIME_OPTICAL_KEYS)
         .unwrap_or_default()
@@ -589,9 +919,15 @@ fn parse_std_inner_config(sargs: &ArgMatches) -> config::StdTextReadConfig {
         time_pattern,
         allow_pseudostandard: sargs.get_flag(ALLOW_PSEUDOSTANDARD),
         allow_unused_standard: sargs.get_flag(ALLOW_UNUSED_STANDARD),
+        absorb_orphan_measurement_keywords: sargs.get_flag(ABSORB_ORPHAN_MEAS_KEYWORDS),
+        disallow_orphan_measurement_keywords: sargs.get_flag(DISALLOW_ORPHAN_MEAS_KEYWORDS),
         disallow_deprecated: sargs.get_flag(DISALLOW_DEPRECATED),
         fix_log_scale_offsets: sargs.get_flag(FIX_LOG_SCALE_OFFSETS),
         nonstandard_measurement_pattern,
+        exclude_measurement_pattern,
+        instrument_registry: config::InstrumentRegistryRef::default(),
+        keyword_extensions: config::KeywordExtensions::default(),
+        timestamps_from_conventions: sargs.get_flag(TIMESTAMPS_FROM_CONVENTIONS),
     }
 }
 
@@ -651,6 +987,7 @@ fn parse_layout_config(sargs: &ArgMatches) -> config::ReadLayoutConfig {
         integer_widths_from_byteord: sargs.get_flag(INT_WIDTHS_FROM_BYTEORD),
         integer_byteord_override,
         disallow_range_truncation: sargs.get_flag(DISALLOW_RANGE_TRUNCATION),
+        disallow_mixed_float_byteord: sargs.get_flag(DISALLOW_MIXED_FLOAT_BYTEORD),
     }
 }
 
@@ -658,12 +995,35 @@ fn parse_dataset_inner_config(sargs: &ArgMatches) -> config::ReaderConfig {
     config::ReaderConfig {
         allow_tot_mismatch: sargs.get_flag(ALLOW_TOT_MISMATCH),
         allow_uneven_event_width: sargs.get_flag(ALLOW_UNEVEN_EVENT_WIDTH),
+        chunk_size: None,
+        chunk_filter: config::ChunkFilterRef::default(),
+        chunk_time_window: None,
     }
 }
 
 fn parse_shared_config(sargs: &ArgMatches) -> config::SharedConfig {
+    let def = config::SharedConfig::default();
     config::SharedConfig {
         warnings_are_errors: sargs.get_flag(WARNINGS_ARE_ERRORS),
+        max_warnings: sargs
+            .get_one::<usize>(MAX_WARNINGS)
+            .copied()
+            .or(def.max_warnings),
+    }
+}
+
+fn parse_scan_config(sargs: &ArgMatches) -> ScanConfig {
+    let def = ScanConfig::default();
+    ScanConfig {
+        max_threads: sargs
+            .get_one(MAX_THREADS)
+            .copied()
+            .unwrap_or(def.max_threads),
+        max_open_files: sargs
+            .get_one(MAX_OPEN_FILES)
+            .copied()
+            .unwrap_or(def.max_open_files),
+        ..def
     }
 }
 
@@ -675,6 +1035,86 @@ fn parse_delim(sargs: &ArgMatches) -> &String {
     sargs.get_one::<String>(DELIM).unwrap()
 }
 
+fn read_policy(p: &Path) -> Result<SitePolicy, ()> {
+    parse_policy(p).map_err(|e| eprintln!("TOPLEVEL ERROR: could not read policy: {e}"))
+}
+
+/// Read site policy from a TOML file like:
+///
+/// ```toml
+/// versions = ["3.1", "3.2"]
+/// required_keywords = ["$OP", "$INST", "SITE_ID"]
+/// shortname_pattern = "^[A-Za-z0-9-]+$"
+/// max_events = 10000000
+/// ```
+///
+/// All keys are optional.
+fn parse_policy(p: &Path) -> Result<SitePolicy, String> {
+    const KEYS: [&str; 4] = [
+        "versions",
+        "required_keywords",
+        "shortname_pattern",
+        "max_events",
+    ];
+    let s = fs::read_to_string(p).map_err(|e| e.to_string())?;
+    let doc = s.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+    // reject unknown keys so that typos don't silently turn off a rule
+    if let Some((k, _)) = doc.iter().find(|(k, _)| !KEYS.contains(k)) {
+        return Err(format!("unknown key '{k}'"));
+    }
+    let strings = |k: &str| {
+        doc.get(k).map_or(Ok(vec![]), |i| {
+            i.as_array()
+                .and_then(|xs| xs.iter().map(|x| x.as_str().map(String::from)).collect())
+                .ok_or(format!("'{k}' must be a list of strings"))
+        })
+    };
+    let versions = strings("versions")?
+        .into_iter()
+        .map(|v| {
+            Version::from_short(&v)
+                .or_else(|| v.parse().ok())
+                .ok_or(format!("invalid version '{v}'"))
+        })
+        .collect::<Result<_, _>>()?;
+    let required_keywords = strings("required_keywords")?;
+    let shortname_pattern = doc
+        .get("shortname_pattern")
+        .map(|i| {
+            i.as_str()
+                .ok_or("'shortname_pattern' must be a string".to_string())
+                .and_then(|x| x.parse::<ShortnamePattern>().map_err(|e| e.to_string()))
+        })
+        .transpose()?;
+    let max_events = doc
+        .get("max_events")
+        .map(|i| {
+            i.as_integer()
+                .and_then(|x| usize::try_from(x).ok())
+                .ok_or("'max_events' must be a non-negative integer".to_string())
+        })
+        .transpose()?;
+    Ok(SitePolicy {
+        versions,
+        required_keywords,
+        shortname_pattern,
+        max_events,
+    })
+}
+
+fn print_changes(p: &Path, cs: &[KeywordChange], dry_run: bool) {
+    let prefix = if dry_run { "(dry run) " } else { "" };
+    for c in cs {
+        println!(
+            "{prefix}{}: {}: '{}' -> '{}'",
+            p.display(),
+            c.key,
+            c.old,
+            c.new
+        );
+    }
+}
+
 fn print_json<T: Serialize>(j: &T) {
     println!("{}", serde_json::to_string(j).unwrap());
 }
@@ -706,7 +1146,10 @@ fn handle_warnings<X, W>(t: Terminal<X, W>) -> X
 where
     W: Display,
 {
-    t.resolve(print_warnings).0
+    let n = t.suppressed_warnings();
+    let x = t.resolve(print_warnings).0;
+    print_suppressed(n);
+    x
 }
 
 fn print_warnings<W>(ws: Vec<W>)
@@ -718,6 +1161,12 @@ where
     }
 }
 
+fn print_suppressed(n: Option<SuppressedWarnings>) {
+    if let Some(s) = n {
+        eprintln!("WARNING: {s}")
+    }
+}
+
 // TODO use warnings_are_errors flag
 fn handle_failure<W, E, T>(f: TerminalFailure<W, E, T>)
 where
@@ -725,11 +1174,20 @@ where
     T: Display,
     W: Display,
 {
-    f.resolve(print_warnings, |es, r| {
+    let n = f.suppressed_warnings();
+    let m = f.suppressed_errors();
+    let print_all = |ws| {
+        print_warnings(ws);
+        print_suppressed(n);
+    };
+    f.resolve(print_all, |es, r| {
         eprintln!("TOPLEVEL ERROR: {r}");
         for e in es {
             eprintln!("  ERROR: {e}");
         }
+        if let Some(s) = m {
+            eprintln!("  ERROR: {s}");
+        }
     });
 }
 
@@ -739,6 +1197,7 @@ where
     T: Display,
 {
     // TODO not DRY
+    let m = f.suppressed_errors();
     f.resolve(
         |_| (),
         |es, r| {
@@ -746,6 +1205,9 @@ where
             for e in es {
                 eprintln!("  ERROR: {e}");
             }
+            if let Some(s) = m {
+                eprintln!("  ERROR: {s}");
+            }
         },
     );
 }
@@ -762,6 +1224,14 @@ const SUBCMD_MEAS: &str = "measurements";
 
 const SUBCMD_SPILL: &str = "spillover";
 
+const SUBCMD_SED: &str = "sed";
+
+const SUBCMD_INFO: &str = "info";
+
+const SUBCMD_VALIDATE: &str = "validate";
+
+const SUBCMD_MANIFEST: &str = "manifest";
+
 const TEXT_COR_BEGIN: &str = "text-correction-begin";
 const TEXT_COR_END: &str = "text-correction-end";
 
@@ -816,12 +1286,18 @@ const ALLOW_MISSING_NEXTDATA: &str = "allow-missing-nextdata";
 
 const TRIM_VALUE_WHITESPACE: &str = "trim-value-whitespace";
 
+const CONTROL_CHARS: &str = "control-chars";
+
+const KEYWORD_ALLOWLIST: &str = "keyword-allowlist";
+
 const DATE_PATTERN: &str = "date-pattern";
 
 const TIME_PATTERN: &str = "time-pattern";
 
 const WARNINGS_ARE_ERRORS: &str = "warnings-are-errors";
 
+const MAX_WARNINGS: &str = "max-warnings";
+
 const TIME_MEAS_PATTERN: &str = "time-meas-pattern";
 
 const ALLOW_MISSING_TIME: &str = "allow-missing-time";
@@ -838,12 +1314,20 @@ const ALLOW_PSEUDOSTANDARD: &str = "allow-pseudostandard";
 
 const ALLOW_UNUSED_STANDARD: &str = "allow-unused-standard";
 
+const ABSORB_ORPHAN_MEAS_KEYWORDS: &str = "absorb-orphan-measurement-keywords";
+
+const DISALLOW_ORPHAN_MEAS_KEYWORDS: &str = "disallow-orphan-measurement-keywords";
+
 const DISALLOW_DEPRECATED: &str = "disallow-deprecated";
 
 const FIX_LOG_SCALE_OFFSETS: &str = "fix-log-scale-offsets";
 
 const NS_MEAS_PATTERN: &str = "non-std-meas-pattern";
 
+const EXCLUDE_MEAS_PATTERN: &str = "exclude-meas-pattern";
+
+const TIMESTAMPS_FROM_CONVENTIONS: &str = "timestamps-from-conventions";
+
 const TEXT_DATA_COR_BEGIN: &str = "text-data-correction-begin";
 const TEXT_DATA_COR_END: &str = "text-data-correction-end";
 
@@ -866,6 +1350,8 @@ const INT_BYTEORD_OVERRIDE: &str = "integer-byteord-override";
 
 const DISALLOW_RANGE_TRUNCATION: &str = "disallow-range-truncation";
 
+const DISALLOW_MIXED_FLOAT_BYTEORD: &str = "disallow-mixed-float-byteord";
+
 const ALLOW_UNEVEN_EVENT_WIDTH: &str = "allow-uneven-event-width";
 
 const ALLOW_TOT_MISMATCH: &str = "allow-tot-mismatch";
@@ -873,3 +1359,27 @@ const ALLOW_TOT_MISMATCH: &str = "allow-tot-mismatch";
 const DELIM: &str = "delimiter";
 
 const INPUT_PATH: &str = "input-path";
+
+const SED_KEY: &str = "key";
+
+const SED_REPLACE: &str = "replace";
+
+const SED_MATCH: &str = "match";
+
+const SED_PATHS: &str = "paths";
+
+const DRY_RUN: &str = "dry-run";
+
+const PERSIST_VERSION: &str = "persist-version";
+
+const MAX_THREADS: &str = "max-threads";
+
+const MAX_OPEN_FILES: &str = "max-open-files";
+
+const ESTIMATE_MEMORY: &str = "estimate-memory";
+
+const CHECK_COMPAT: &str = "check-compat";
+
+const POLICY: &str = "policy";
+
+const OUT_PATH: &str = "out";
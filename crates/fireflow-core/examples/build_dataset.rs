@@ -0,0 +1,82 @@
+//! Build a 3.1 dataset from scratch and write it to a file.
+//!
+//! ```text
+//! cargo run -p fireflow-core --example build_dataset -- <output.fcs>
+//! ```
+
+mod common;
+
+use fireflow_core::core::{Optical3_1, ScaleTransform, Temporal3_1};
+use fireflow_core::prelude::*;
+use fireflow_core::text::keywords::{AlphaNumType, Mode, Range, Timestep};
+use fireflow_core::text::optional::AlwaysValue;
+use fireflow_core::validated::dataframe::F32Column;
+use fireflow_core::validated::keys::NonStdKeywords;
+
+use std::fs::File;
+use std::io::BufWriter;
+
+const NEVENTS: usize = 1000;
+
+fn main() {
+    let output = common::arg_path(1, "output FCS file");
+
+    // Start with the minimum required metadata and no measurements. All
+    // columns will be stored as 32-bit floats.
+    let text = CoreTEXT3_1::new_def(Mode::List, AlphaNumType::Float);
+    let mut core = text
+        .into_coredataset(FCSDataFrame::default(), Analysis::default(), Others::default())
+        .ok()
+        .unwrap();
+
+    // Add a time measurement, which should be first by convention. Each
+    // measurement needs a name, its metadata, a column, and its range
+    // ($PnR). Set `notrunc` to fail if the range doesn't fit the type rather
+    // than truncating it.
+    let time: Vec<f32> = (0..NEVENTS).map(|i| i as f32 * 0.1).collect();
+    let timestep = Timestep::try_from(0.1_f32).ok().unwrap();
+    let temporal = Temporal3_1::new_3_1(timestep, None, None, None, None, NonStdKeywords::new());
+    common::unwrap_or_exit(core.push_temporal(
+        Shortname::new_unchecked("Time"),
+        temporal,
+        F32Column::from(time).into(),
+        Range::try_from(100.0_f32).unwrap(),
+        true,
+    ));
+
+    // Add two optical measurements with some fake signal.
+    for (name, gain) in [("FSC-A", 2.0), ("FITC-A", 0.5)] {
+        let values: Vec<f32> = (0..NEVENTS).map(|i| (i % 256) as f32 * gain).collect();
+        let optical = Optical3_1::new_3_1(
+            ScaleTransform::default(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            NonStdKeywords::new(),
+        );
+        common::unwrap_or_exit(core.push_optical(
+            // Names are always required in 3.1, but optional in 2.0 and 3.0
+            AlwaysValue(Shortname::new_unchecked(name)),
+            optical,
+            F32Column::from(values).into(),
+            Range::try_from(1024.0_f32).unwrap(),
+            true,
+        ));
+    }
+
+    // See what will be written before committing to it.
+    let plan = common::unwrap_or_exit(core.plan_write(&WriteConfig::default()));
+    println!("DATA will be {} bytes", plan.data.length);
+
+    let mut h = BufWriter::new(File::create(&output).unwrap());
+    common::unwrap_or_exit(core.h_write_dataset(&mut h, &WriteConfig::default()));
+    println!("wrote {}", output.display());
+}
@@ -0,0 +1,69 @@
+//! Helpers shared by the examples.
+//!
+//! Real applications will probably want to handle warnings and errors more
+//! carefully (ie by inspecting each variant), but printing them is enough to
+//! show how the results are structured.
+
+use fireflow_core::prelude::*;
+
+use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process;
+
+/// Return the nth command line argument as a path, or exit if not given.
+pub fn arg_path(n: usize, what: &str) -> PathBuf {
+    env::args().nth(n).map(PathBuf::from).unwrap_or_else(|| {
+        eprintln!("missing argument {n}: {what}");
+        process::exit(2)
+    })
+}
+
+/// Return the value of a result after printing its warnings.
+///
+/// If the result is a failure, print its warnings and errors and exit.
+pub fn unwrap_or_exit<V, W, E, T>(res: TerminalResult<V, W, E, T>) -> V
+where
+    W: Display,
+    E: Display,
+    T: Display,
+{
+    match res {
+        Ok(t) => {
+            let n = t.suppressed_warnings();
+            let x = t.resolve(print_warnings).0;
+            print_suppressed(n);
+            x
+        }
+        Err(f) => {
+            let n = f.suppressed_warnings();
+            let m = f.suppressed_errors();
+            let print_all = |ws| {
+                print_warnings(ws);
+                print_suppressed(n);
+            };
+            f.resolve(print_all, |es, reason| {
+                eprintln!("ERROR: {reason}");
+                for e in es {
+                    eprintln!("  {e}");
+                }
+                if let Some(s) = m {
+                    eprintln!("  {s}");
+                }
+            });
+            process::exit(1)
+        }
+    }
+}
+
+fn print_warnings<W: Display>(ws: Vec<W>) {
+    for w in ws {
+        eprintln!("WARNING: {w}");
+    }
+}
+
+fn print_suppressed(n: Option<SuppressedWarnings>) {
+    if let Some(s) = n {
+        eprintln!("WARNING: {s}");
+    }
+}
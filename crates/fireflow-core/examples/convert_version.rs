@@ -0,0 +1,44 @@
+//! Convert a 2.0 dataset to 3.1.
+//!
+//! ```text
+//! cargo run -p fireflow-core --example convert_version -- <input.fcs> <output.fcs>
+//! ```
+
+mod common;
+
+use fireflow_core::config::ConvertConfig;
+use fireflow_core::core::{AnyCore, InnerMetaroot3_1};
+use fireflow_core::prelude::*;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+
+fn main() {
+    let input = common::arg_path(1, "input FCS 2.0 file");
+    let output = common::arg_path(2, "output FCS file");
+
+    let mut conf = ReadStdDatasetConfig::default();
+    conf.standard.time_meas_pattern = Some(TimeMeasNamePattern::default());
+    let (core, _) = common::unwrap_or_exit(fcs_read_std_dataset(&input, &conf));
+    let AnyCore::FCS2_0(x) = core else {
+        eprintln!("expected FCS 2.0, got {}", core.version());
+        process::exit(1)
+    };
+
+    // Conversion will fail if anything required in 3.1 cannot be derived from
+    // 2.0 (ie $PnE for every measurement). Setting `force` will drop such
+    // keywords instead; `strip_deprecated` removes keywords which 3.1 no
+    // longer allows; `record_conversion` stores what changed in a
+    // nonstandard keyword.
+    let convert_conf = ConvertConfig {
+        strip_deprecated: true,
+        record_conversion: true,
+        ..ConvertConfig::default()
+    };
+    let converted = common::unwrap_or_exit((*x).try_convert::<InnerMetaroot3_1>(&convert_conf));
+
+    let mut h = BufWriter::new(File::create(&output).unwrap());
+    common::unwrap_or_exit(converted.h_write_dataset(&mut h, &WriteConfig::default()));
+    println!("wrote {}", output.display());
+}
@@ -0,0 +1,51 @@
+//! Read a dataset and print summary statistics for each measurement.
+//!
+//! ```text
+//! cargo run -p fireflow-core --example read_stats -- <input.fcs>
+//! ```
+
+mod common;
+
+use fireflow_core::prelude::*;
+
+use std::num::NonZeroUsize;
+
+fn main() {
+    let input = common::arg_path(1, "input FCS file");
+
+    // Read the first dataset in the file, including DATA. The default config
+    // is strict; see the various fields of ReadStdDatasetConfig to repair
+    // common issues (ie wrong offsets or missing keywords). The time
+    // measurement is only treated as such if its name matches a pattern,
+    // which by default is "Time" or "TIME".
+    let mut conf = ReadStdDatasetConfig::default();
+    conf.standard.time_meas_pattern = Some(TimeMeasNamePattern::default());
+    let (core, _) = common::unwrap_or_exit(fcs_read_std_dataset(&input, &conf));
+
+    let groups = core.channel_groups();
+    println!("version: {}", core.version());
+    println!("events: {}", core.as_data().nrows());
+    println!("name\tkind\tmean\tp5\tmedian\tp95");
+
+    // Percentiles are estimated from a fine histogram, which is computed
+    // without copying the column.
+    let bins = NonZeroUsize::new(4096).unwrap();
+    for name in core.shortnames() {
+        // ASSUME these won't fail since each name came from this dataset
+        let kind = groups.kind_of(&name).unwrap();
+        let (n, sum) = core
+            .iter_column(&name)
+            .ok()
+            .unwrap()
+            .fold((0_usize, 0.0), |(n, s), x| (n + 1, s + x));
+        let mean = if n == 0 { f64::NAN } else { sum / n as f64 };
+        let hist = core.channel_histogram(&name, bins, false).ok().unwrap();
+        let [p5, p50, p95] = [5.0, 50.0, 95.0].map(|q| {
+            hist.percentile(q)
+                .ok()
+                .flatten()
+                .map_or("NA".to_string(), |x| format!("{x:.2}"))
+        });
+        println!("{name}\t{kind}\t{mean:.2}\t{p5}\t{p50}\t{p95}");
+    }
+}
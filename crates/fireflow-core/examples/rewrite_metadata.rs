@@ -0,0 +1,49 @@
+//! Change some keywords in a dataset and write it to a new file.
+//!
+//! ```text
+//! cargo run -p fireflow-core --example rewrite_metadata -- <input.fcs> <output.fcs>
+//! ```
+
+mod common;
+
+use fireflow_core::core::{AnyCore, Metaroot};
+use fireflow_core::prelude::*;
+use fireflow_core::text::keywords::Com;
+use fireflow_core::validated::keys::NonStdKey;
+
+use std::fs::File;
+use std::io::BufWriter;
+
+/// Set $COM and add a nonstandard keyword.
+///
+/// Keywords in the metaroot which are common to all versions are plain
+/// fields, so this works the same for any version.
+fn annotate<X>(m: &mut Metaroot<X>) {
+    m.com = Com("reviewed".into()).into();
+    // ASSUME this is a valid key since it doesn't start with '$'
+    let key: NonStdKey = "SITE_REVIEWER".parse().unwrap();
+    m.nonstandard_keywords.insert(key, "jdoe".into());
+}
+
+fn main() {
+    let input = common::arg_path(1, "input FCS file");
+    let output = common::arg_path(2, "output FCS file");
+
+    let mut conf = ReadStdDatasetConfig::default();
+    conf.standard.time_meas_pattern = Some(TimeMeasNamePattern::default());
+    let (mut core, _) = common::unwrap_or_exit(fcs_read_std_dataset(&input, &conf));
+
+    // Version-specific keywords (ie $CYT in 3.1) would need to be changed
+    // inside the matching arm.
+    match &mut core {
+        AnyCore::FCS2_0(x) => annotate(&mut x.metaroot),
+        AnyCore::FCS3_0(x) => annotate(&mut x.metaroot),
+        AnyCore::FCS3_1(x) => annotate(&mut x.metaroot),
+        AnyCore::FCS3_2(x) => annotate(&mut x.metaroot),
+    }
+
+    let mut h = BufWriter::new(File::create(&output).unwrap());
+    let report = common::unwrap_or_exit(core.h_write_dataset(&mut h, &WriteConfig::default()));
+    let lossy: u64 = report.lossy_values.iter().sum();
+    println!("wrote {} ({lossy} lossy values)", output.display());
+}
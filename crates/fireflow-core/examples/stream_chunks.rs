@@ -0,0 +1,57 @@
+//! Compute per-measurement means of a large file without a dataframe.
+//!
+//! ```text
+//! cargo run -p fireflow-core --example stream_chunks -- <input.fcs>
+//! ```
+//!
+//! DATA is read as raw bytes and each event is decoded only when it is
+//! consumed, so memory use is roughly the size of DATA rather than several
+//! times that. Events are processed in fixed-size chunks, which is where an
+//! application would (for instance) send each batch to another thread or
+//! write it elsewhere.
+
+mod common;
+
+use fireflow_core::api::fcs_read_raw_dataset_passthru;
+use fireflow_core::prelude::*;
+
+use itertools::Itertools;
+use std::process;
+
+const CHUNK_SIZE: usize = 100_000;
+
+fn main() {
+    let input = common::arg_path(1, "input FCS file");
+
+    let conf = ReadRawDatasetConfig::default();
+    let out = common::unwrap_or_exit(fcs_read_raw_dataset_passthru(&input, &conf));
+
+    // This will be None if the layout could not be determined or if DATA is
+    // delimited ASCII (which must be parsed all at once).
+    let Some(rows) = out.data.iter_rows() else {
+        eprintln!("DATA cannot be decoded event by event");
+        process::exit(1)
+    };
+
+    let mut sums: Vec<f64> = vec![];
+    let mut n = 0_usize;
+    for (i, chunk) in rows.chunks(CHUNK_SIZE).into_iter().enumerate() {
+        let mut chunk_n = 0;
+        for row in chunk {
+            if sums.is_empty() {
+                sums = vec![0.0; row.len()];
+            }
+            for (s, x) in sums.iter_mut().zip(row) {
+                *s += x;
+            }
+            chunk_n += 1;
+        }
+        n += chunk_n;
+        eprintln!("chunk {i}: {chunk_n} events");
+    }
+
+    println!("events: {n}");
+    for (i, s) in sums.iter().enumerate() {
+        println!("P{}\t{:.2}", i + 1, s / n as f64);
+    }
+}
@@ -5,18 +5,23 @@ use crate::error::*;
 use crate::header::*;
 use crate::macros::def_failure;
 use crate::segment::*;
+use crate::text::index::{MeasIndex, MeasIndexed};
 use crate::text::keywords::*;
 use crate::text::parser::*;
+use crate::text::provenance::Provenance;
+use crate::text::tokenizer::{self, TextToken, TextTokenIssue, TextTokenizer};
 use crate::validated::ascii_uint::UintSpacePad20;
 use crate::validated::dataframe::FCSDataFrame;
 use crate::validated::keys::*;
+use crate::validated::shortname::Shortname;
 
 use derive_more::{Display, From};
 use itertools::Itertools;
+use nonempty::NonEmpty;
 use std::convert::Infallible;
 use std::fmt;
 use std::fs;
-use std::io::{BufReader, Read, Seek};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
 use std::num::ParseIntError;
 use std::path;
 
@@ -45,9 +50,10 @@ pub fn fcs_read_raw_text(
     p: &path::PathBuf,
     conf: &ReadRawTEXTConfig,
 ) -> IOTerminalResult<RawTEXTOutput, ParseRawTEXTWarning, HeaderOrRawError, RawTEXTFailure> {
+    let _limit = IssueLimit::new(&conf.shared);
     read_fcs_raw_text_inner(p, conf)
         .def_map_value(|(x, _, _)| x)
-        .def_terminate_maybe_warn(RawTEXTFailure, conf.shared.warnings_are_errors, |w| {
+        .def_terminate_shared(RawTEXTFailure, &conf.shared, |w| {
             ImpureError::Pure(w.into())
         })
 }
@@ -57,13 +63,20 @@ pub fn fcs_read_std_text(
     p: &path::PathBuf,
     conf: &ReadStdTEXTConfig,
 ) -> IOTerminalResult<(AnyCoreTEXT, StdTEXTOutput), StdTEXTWarning, StdTEXTError, StdTEXTFailure> {
+    let _limit = IssueLimit::new(&conf.shared);
     read_fcs_raw_text_inner(p, conf)
         .def_map_value(|(x, _, st)| (x, st))
         .def_io_into()
         .def_and_maybe(|(raw, st)| raw.into_std_text(&st).def_inner_into().def_errors_liftio())
-        .def_terminate_maybe_warn(StdTEXTFailure, conf.shared.warnings_are_errors, |w| {
+        .def_terminate_shared(StdTEXTFailure, &conf.shared, |w| {
             ImpureError::Pure(StdTEXTError::from(w))
         })
+        .map(|t| {
+            t.map_with_warnings(|(core, mut out), ws| {
+                out.meas_warnings = MeasWarnings::group(ws, &core.shortnames());
+                (core, out)
+            })
+        })
 }
 
 /// Read dataset from FCS file using standardized TEXT.
@@ -71,6 +84,7 @@ pub fn fcs_read_raw_dataset(
     p: &path::PathBuf,
     conf: &ReadRawDatasetConfig,
 ) -> IOTerminalResult<RawDatasetOutput, RawDatasetWarning, RawDatasetError, RawDatasetFailure> {
+    let _limit = IssueLimit::new(&conf.shared);
     read_fcs_raw_text_inner(p, conf)
         .def_io_into()
         .def_and_maybe(|(raw, mut h, st)| {
@@ -86,11 +100,68 @@ pub fn fcs_read_raw_dataset(
             .def_map_value(|dataset| RawDatasetOutput { text: raw, dataset })
             .def_io_into()
         })
-        .def_terminate_maybe_warn(RawDatasetFailure, conf.shared.warnings_are_errors, |w| {
+        .def_terminate_shared(RawDatasetFailure, &conf.shared, |w| {
             ImpureError::Pure(RawDatasetError::from(w))
         })
 }
 
+/// Read dataset from FCS file without interpreting DATA.
+///
+/// Unlike [`fcs_read_raw_dataset`], DATA will be returned as raw bytes and
+/// will not be parsed. If the layout cannot be determined from TEXT (for
+/// instance, due to an unknown $DATATYPE or unsupported widths), this will be
+/// reported as a warning and the layout will be omitted from the output, along
+/// with whichever layout keywords could be read. This allows such files to be
+/// inspected, copied, or repaired.
+pub fn fcs_read_raw_dataset_passthru(
+    p: &path::PathBuf,
+    conf: &ReadRawDatasetConfig,
+) -> IOTerminalResult<
+    RawDatasetPassthruOutput,
+    RawDatasetWarning,
+    RawDatasetError,
+    RawDatasetPassthruFailure,
+> {
+    let _limit = IssueLimit::new(&conf.shared);
+    read_fcs_raw_text_inner(p, conf)
+        .def_io_into()
+        .def_and_maybe(|(raw, mut h, st)| {
+            let header = &raw.parse.header_segments;
+            kws_to_passthru_analysis(
+                raw.version,
+                &mut h,
+                &raw.keywords.std,
+                header.data,
+                header.analysis,
+                &st,
+            )
+            .def_and_maybe(|(data, analysis, data_seg, analysis_seg)| {
+                let or = OthersReader {
+                    segs: &raw.parse.header_segments.other[..],
+                };
+                or.h_read(&mut h)
+                    .into_deferred()
+                    .def_map_value(|others| (data, analysis, others, data_seg, analysis_seg))
+            })
+            .def_map_value(|(data, analysis, others, data_seg, analysis_seg)| {
+                RawDatasetPassthruOutput {
+                    text: raw,
+                    data,
+                    analysis,
+                    others,
+                    data_seg,
+                    analysis_seg,
+                }
+            })
+            .def_io_into()
+        })
+        .def_terminate_shared(
+            RawDatasetPassthruFailure,
+            &conf.shared,
+            |w| ImpureError::Pure(RawDatasetError::from(w)),
+        )
+}
+
 /// Read dataset from FCS file using raw key/value pairs from TEXT.
 pub fn fcs_read_std_dataset(
     p: &path::PathBuf,
@@ -101,12 +172,87 @@ pub fn fcs_read_std_dataset(
     StdDatasetError,
     StdDatasetFailure,
 > {
-    read_fcs_raw_text_inner(p, conf)
-        .def_io_into()
-        .def_and_maybe(|(raw, mut h, st)| raw.into_std_dataset(&mut h, &st).def_io_into())
-        .def_terminate_maybe_warn(StdDatasetFailure, conf.shared.warnings_are_errors, |w| {
-            ImpureError::Pure(StdDatasetError::from(w))
-        })
+    read_std_dataset_at(p, 0, conf).0
+}
+
+/// Read all datasets from FCS file using raw key/value pairs from TEXT.
+///
+/// Datasets after the first are found using $NEXTDATA, which is the offset of
+/// the next dataset relative to the beginning of the current one. Each dataset
+/// is read independently, so failing to read one will not prevent reading the
+/// others. The exception is if TEXT cannot be read, in which case $NEXTDATA
+/// cannot be found and the failure for that dataset will be the last result.
+pub fn fcs_read_std_datasets(
+    p: &path::PathBuf,
+    conf: &ReadStdDatasetConfig,
+) -> Vec<
+    IOTerminalResult<
+        (AnyCoreDataset, StdDatasetOutput),
+        StdDatasetWarning,
+        StdDatasetError,
+        StdDatasetFailure,
+    >,
+> {
+    let mut ret = vec![];
+    let mut base = 0;
+    loop {
+        let (res, _) = read_std_dataset_at(p, base, conf);
+        let nextdata = match &res {
+            Ok(t) => t.value().1.parse.nextdata,
+            // if the dataset could not be read, try again with just TEXT
+            Err(_) => read_fcs_raw_text_at(p, base, conf)
+                .ok()
+                .and_then(|t| t.value().0.parse.nextdata),
+        };
+        ret.push(res);
+        match nextdata {
+            // ASSUME $NEXTDATA is always positive, which guarantees that this
+            // won't loop forever
+            Some(n) if n > 0 => base += u64::from(n),
+            _ => break,
+        }
+    }
+    ret
+}
+
+/// Read all datasets from a file made by concatenating FCS files.
+///
+/// Some pipelines join complete FCS files end-to-end rather than linking them
+/// with $NEXTDATA. After each dataset is read, the file will be searched for
+/// the next valid HEADER, starting after the last byte of any segment in the
+/// current dataset. Since this does not depend on $NEXTDATA, datasets linked
+/// by $NEXTDATA will also be found.
+///
+/// Each result is paired with the offset of its dataset in the file. As with
+/// [`fcs_read_std_datasets`], failing to read one dataset will not prevent
+/// reading the others. If TEXT cannot be read for a dataset, the search will
+/// start from the byte after its HEADER.
+#[allow(clippy::type_complexity)]
+pub fn fcs_read_std_datasets_concatenated(
+    p: &path::PathBuf,
+    conf: &ReadStdDatasetConfig,
+) -> Vec<(
+    u64,
+    IOTerminalResult<
+        (AnyCoreDataset, StdDatasetOutput),
+        StdDatasetWarning,
+        StdDatasetError,
+        StdDatasetFailure,
+    >,
+)> {
+    let mut ret = vec![];
+    let mut base = 0;
+    loop {
+        let (res, end) = read_std_dataset_at(p, base, conf);
+        ret.push((base, res));
+        // ASSUME the search always starts after the current HEADER, which
+        // guarantees that this won't loop forever
+        match find_next_header(p, base + end.unwrap_or(0) + 1) {
+            Ok(Some(next)) => base = next,
+            _ => break,
+        }
+    }
+    ret
 }
 
 /// Read DATA/ANALYSIS in FCS file using provided keywords.
@@ -124,6 +270,7 @@ pub fn fcs_read_raw_dataset_with_keywords(
     LookupAndReadDataAnalysisError,
     RawDatasetWithKwsFailure,
 > {
+    let _limit = IssueLimit::new(&conf.shared);
     ReadState::open(p, conf)
         .into_deferred()
         .def_and_maybe(|(st, file)| {
@@ -138,9 +285,9 @@ pub fn fcs_read_raw_dataset_with_keywords(
                 &st,
             )
         })
-        .def_terminate_maybe_warn(
+        .def_terminate_shared(
             RawDatasetWithKwsFailure,
-            conf.shared.warnings_are_errors,
+            &conf.shared,
             |w| ImpureError::Pure(LookupAndReadDataAnalysisError::from(w)),
         )
 }
@@ -160,6 +307,7 @@ pub fn fcs_read_std_dataset_with_keywords(
     StdDatasetFromRawError,
     StdDatasetWithKwsFailure,
 > {
+    let _limit = IssueLimit::new(&conf.shared);
     ReadState::open(p, conf)
         .into_deferred()
         .def_and_maybe(|(st, file)| {
@@ -186,13 +334,94 @@ pub fn fcs_read_std_dataset_with_keywords(
                 )
             })
         })
-        .def_terminate_maybe_warn(
+        .def_terminate_shared(
             StdDatasetWithKwsFailure,
-            conf.shared.warnings_are_errors,
+            &conf.shared,
             |w| ImpureError::Pure(StdDatasetFromRawError::from(w)),
         )
 }
 
+/// Replace values in primary TEXT of an FCS file without rewriting DATA.
+///
+/// `f` will be called with each key/value pair in primary TEXT and should
+/// return a new value if the pair is to be changed. The new TEXT will be
+/// written in place along with its end offset in HEADER; no other segments
+/// will be moved. This will fail if the new TEXT does not fit before the next
+/// segment in the file. Keywords in supplemental TEXT will not be touched.
+///
+/// If `dry_run` is true, nothing will be written. In either case, the
+/// changes (which were or would be made) are returned.
+pub fn fcs_patch_text<F>(
+    p: &path::PathBuf,
+    conf: &ReadRawTEXTConfig,
+    dry_run: bool,
+    f: F,
+) -> IOTerminalResult<Vec<KeywordChange>, ParseRawTEXTWarning, PatchTEXTError, PatchTEXTFailure>
+where
+    F: Fn(&str, &str) -> Option<String>,
+{
+    let _limit = IssueLimit::new(&conf.shared);
+    read_fcs_raw_text_inner(p, conf)
+        .def_io_into()
+        .def_and_maybe(|(raw, mut h, _)| h_patch_text(p, &mut h, &raw, dry_run, f).into_deferred())
+        .def_terminate_shared(PatchTEXTFailure, &conf.shared, |w| {
+            ImpureError::Pure(PatchTEXTError::from(w))
+        })
+}
+
+/// Rewrite the version in HEADER of an FCS file in place.
+///
+/// This is meant for repairing files whose HEADER claims the wrong (or an
+/// invalid) version. TEXT will first be read and standardized as if
+/// `version_override` were `version`, and nothing will be written unless this
+/// succeeds without errors. Since TEXT which is valid for one version may
+/// still contain keywords which are only standard in another, any
+/// pseudostandard keywords are also errors here. Only the first 6 bytes of the
+/// file will be changed.
+///
+/// If `dry_run` is true, nothing will be written. In either case, return the
+/// version that was originally in HEADER, or `None` if it was invalid.
+pub fn fcs_patch_version(
+    p: &path::PathBuf,
+    version: Version,
+    conf: &ReadStdTEXTConfig,
+    dry_run: bool,
+) -> IOTerminalResult<Option<Version>, StdTEXTWarning, StdTEXTError, StdTEXTFailure> {
+    let _limit = IssueLimit::new(&conf.shared);
+    let mut c = conf.clone();
+    c.raw.version_override = Some(version);
+    read_fcs_raw_text_inner(p, &c)
+        .def_io_into()
+        .def_and_maybe(|(raw, h, st)| {
+            raw.into_std_text(&st)
+                .def_inner_into()
+                .def_errors_liftio()
+                .def_and_maybe(|(_, out)| {
+                    let es = out
+                        .extra
+                        .pseudostandard
+                        .into_keys()
+                        .map(|k| {
+                            ImpureError::Pure(StdTEXTError::Std(PseudostandardError(k).into()))
+                        })
+                        .collect();
+                    NonEmpty::from_vec(es)
+                        .map_or(Ok(Tentative::new1(h)), |xs| Err(DeferredFailure::new2(xs)))
+                })
+        })
+        .def_terminate_shared(StdTEXTFailure, &conf.shared, |w| {
+            ImpureError::Pure(StdTEXTError::from(w))
+        })
+        // only write once TEXT is known to have no errors
+        .and_then(|t| {
+            t.and_finally(|mut h| {
+                h_patch_version(p, &mut h, version, dry_run)
+                    .map_err(ImpureError::IO)
+                    .terminate(StdTEXTFailure)
+            })
+        })
+}
+
 /// Output from parsing the TEXT segment.
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -224,6 +453,15 @@ pub struct StdTEXTOutput {
     /// Keywords that start with '$' that are not part of the standard
     pub extra: ExtraStdKeywords,
 
+    /// Estimated number of bytes needed to read DATA into a dataframe
+    ///
+    /// This will be None if the number of events cannot be determined, which
+    /// may happen for delimited ASCII without $TOT.
+    pub estimated_memory_bytes: Option<u64>,
+
+    /// Warnings which pertain to specific measurements
+    pub meas_warnings: Vec<MeasWarnings>,
+
     /// Miscellaneous data from parsing TEXT
     pub parse: RawTEXTParseData,
 }
@@ -238,16 +476,85 @@ pub struct RawDatasetOutput {
     pub dataset: RawDatasetWithKwsOutput,
 }
 
+/// Output of reading one raw dataset (TEXT+DATA) without parsing DATA.
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct RawDatasetPassthruOutput {
+    /// Output from parsing HEADER+TEXT
+    pub text: RawTEXTOutput,
+
+    /// DATA output
+    pub data: PassthruData,
+
+    /// ANALYSIS output
+    pub analysis: Analysis,
+
+    /// OTHER output(s)
+    pub others: Others,
+
+    /// offsets used to read DATA
+    pub data_seg: AnyDataSegment,
+
+    /// offsets used to read ANALYSIS
+    pub analysis_seg: AnyAnalysisSegment,
+}
+
 /// Output of parsing one standardized dataset (TEXT+DATA) from an FCS file.
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 pub struct StdDatasetOutput {
     /// Standardized data from one FCS dataset
     pub dataset: StdDatasetWithKwsOutput,
 
+    /// Warnings which pertain to specific measurements
+    pub meas_warnings: Vec<MeasWarnings>,
+
     /// Miscellaneous data from parsing TEXT
     pub parse: RawTEXTParseData,
 }
 
+/// Warnings which pertain to one measurement.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct MeasWarnings {
+    /// Index of the measurement
+    pub index: MeasIndex,
+
+    /// Name of the measurement ($PnN or its default)
+    pub shortname: Shortname,
+
+    /// Warning messages in the order they were encountered
+    pub warnings: Vec<String>,
+}
+
+impl MeasWarnings {
+    /// Group warnings by measurement index.
+    ///
+    /// Warnings which do not pertain to a measurement, or which refer to a
+    /// measurement index that doesn't exist, will be skipped. The output will
+    /// be sorted by index.
+    pub fn group<W>(ws: &[W], names: &[Shortname]) -> Vec<Self>
+    where
+        W: MeasIndexed + fmt::Display,
+    {
+        let mut ret: Vec<Self> = vec![];
+        for w in ws {
+            if let Some(index) = w.meas_index() {
+                if let Some(x) = ret.iter_mut().find(|x| x.index == index) {
+                    x.warnings.push(w.to_string());
+                } else if let Some(shortname) = names.get(usize::from(index)) {
+                    ret.push(Self {
+                        index,
+                        shortname: shortname.clone(),
+                        warnings: vec![w.to_string()],
+                    });
+                }
+            }
+        }
+        ret.sort_by_key(|x| usize::from(x.index));
+        ret
+    }
+}
+
 /// Output of using keywords to read raw TEXT+DATA
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 pub struct RawDatasetWithKwsOutput {
@@ -305,12 +612,36 @@ pub struct RawTEXTParseData {
     pub byte_pairs: BytesPairs,
 }
 
+/// A value in TEXT which was changed in place.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct KeywordChange {
+    /// The key whose value was changed
+    pub key: String,
+
+    /// The value before the change
+    pub old: String,
+
+    /// The value after the change
+    pub new: String,
+}
+
 #[derive(From, Display)]
 pub enum StdTEXTWarning {
     Raw(ParseRawTEXTWarning),
     Std(StdTEXTFromRawWarning),
 }
 
+impl MeasIndexed for StdTEXTWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::Raw(_) => None,
+            Self::Std(x) => x.meas_index(),
+        }
+    }
+}
+
 #[derive(From, Display)]
 pub enum StdTEXTError {
     Raw(HeaderOrRawError),
@@ -324,6 +655,15 @@ pub enum StdDatasetWarning {
     Std(StdDatasetFromRawWarning),
 }
 
+impl MeasIndexed for StdDatasetWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::Raw(_) => None,
+            Self::Std(x) => x.meas_index(),
+        }
+    }
+}
+
 #[derive(From, Display)]
 pub enum StdDatasetError {
     Raw(HeaderOrRawError),
@@ -360,6 +700,22 @@ pub enum HeaderOrRawError {
     Warn(ParseRawTEXTWarning),
 }
 
+#[derive(From, Display)]
+pub enum PatchTEXTError {
+    Raw(HeaderOrRawError),
+    Patch(TEXTPatchError),
+    Warn(ParseRawTEXTWarning),
+}
+
+pub enum TEXTPatchError {
+    /// New value is empty or begins/ends with the delimiter
+    InvalidValue { key: String, value: String },
+    /// New TEXT runs into the next segment
+    NoSpace { required: u64, available: u64 },
+    /// New TEXT ends beyond what can be stored in HEADER
+    EndTooLarge(u64),
+}
+
 #[derive(From, Display)]
 pub enum RawToReaderError {
     Layout(RawToLayoutError),
@@ -376,6 +732,7 @@ pub enum RawToReaderWarning {
 pub enum STextSegmentError {
     ReqSegment(ReqSegmentError),
     Dup(DuplicatedSuppTEXT),
+    Cyclic(CyclicSuppTEXT),
 }
 
 #[derive(From, Display)]
@@ -387,6 +744,12 @@ pub enum STextSegmentWarning {
 
 pub struct DuplicatedSuppTEXT;
 
+pub struct CyclicSuppTEXT {
+    begin: u64,
+    end: u64,
+    visited: &'static str,
+}
+
 #[derive(From, Display)]
 pub enum ParseRawTEXTError {
     Delim(DelimVerifyError),
@@ -438,6 +801,7 @@ pub enum ParseKeywordsIssue {
     Final(FinalDelimError),
     Insert(KeywordInsertError),
     Bound(DelimBoundError),
+    ControlChar(ControlCharWarning),
     // this is only for supp TEXT but seems less wasteful/convoluted to put here
     Mismatch(DelimMismatch),
 }
@@ -483,6 +847,150 @@ where
         })
 }
 
+/// Read HEADER and TEXT for the dataset starting at `base`.
+#[allow(clippy::type_complexity)]
+fn read_fcs_raw_text_at<C>(
+    p: &path::PathBuf,
+    base: u64,
+    conf: C,
+) -> DeferredResult<
+    (RawTEXTOutput, BufReader<DatasetFile>, ReadState<C>),
+    ParseRawTEXTWarning,
+    ImpureError<HeaderOrRawError>,
+>
+where
+    C: AsRef<ReadHeaderAndTEXTConfig> + AsRef<HeaderConfigInner>,
+{
+    ReadState::open(p, conf)
+        .and_then(|(mut st, file)| {
+            st.file_len = st.file_len.saturating_sub(base);
+            DatasetFile::new(file, base).map(|f| (st, f))
+        })
+        .into_deferred()
+        .def_and_maybe(|(st, file)| {
+            let mut h = BufReader::new(file);
+            RawTEXTOutput::h_read(&mut h, &st).def_map_value(|x| (x, h, st))
+        })
+}
+
+/// Read standardized dataset starting at `base`.
+///
+/// Also return the last byte of any segment in the dataset relative to `base`,
+/// which will be None if TEXT could not be read. This is needed since the
+/// standardized output doesn't keep all segment offsets, and won't exist at
+/// all if the dataset failed.
+#[allow(clippy::type_complexity)]
+fn read_std_dataset_at(
+    p: &path::PathBuf,
+    base: u64,
+    conf: &ReadStdDatasetConfig,
+) -> (
+    IOTerminalResult<
+        (AnyCoreDataset, StdDatasetOutput),
+        StdDatasetWarning,
+        StdDatasetError,
+        StdDatasetFailure,
+    >,
+    Option<u64>,
+) {
+    let _limit = IssueLimit::new(&conf.shared);
+    let mut end = None;
+    let res = read_fcs_raw_text_at(p, base, conf)
+        .def_io_into()
+        .def_and_maybe(|(raw, mut h, st)| {
+            end = Some(dataset_end(&raw));
+            raw.into_std_dataset(&mut h, &st).def_io_into()
+        })
+        .def_map_value(|(mut core, out)| {
+            // a filtered dataset is derived from this file, so record it
+            let filter = &conf.data.chunk_filter;
+            if conf.data.chunk_size.is_some() && filter.is_some() {
+                let op = "chunk_filter".into();
+                core.set_provenance(Some(Provenance::derived(op, filter.parent())));
+            }
+            (core, out)
+        });
+    (terminate_std_dataset(res, conf), end)
+}
+
+/// Read dataset from an FCS file which is already in memory.
+pub(crate) fn read_std_dataset_from_bytes(
+    bs: &[u8],
+    conf: &ReadStdDatasetConfig,
+) -> IOTerminalResult<
+    (AnyCoreDataset, StdDatasetOutput),
+    StdDatasetWarning,
+    StdDatasetError,
+    StdDatasetFailure,
+> {
+    let _limit = IssueLimit::new(&conf.shared);
+    let st = ReadState {
+        file_len: bs.len() as u64,
+        conf,
+    };
+    let mut h = BufReader::new(io::Cursor::new(bs));
+    let res = RawTEXTOutput::h_read(&mut h, &st)
+        .def_io_into()
+        .def_and_maybe(|raw| raw.into_std_dataset(&mut h, &st).def_io_into());
+    terminate_std_dataset(res, conf)
+}
+
+fn terminate_std_dataset(
+    res: DeferredResult<
+        (AnyCoreDataset, StdDatasetOutput),
+        StdDatasetWarning,
+        ImpureError<StdDatasetError>,
+    >,
+    conf: &ReadStdDatasetConfig,
+) -> IOTerminalResult<
+    (AnyCoreDataset, StdDatasetOutput),
+    StdDatasetWarning,
+    StdDatasetError,
+    StdDatasetFailure,
+> {
+    res.def_terminate_shared(StdDatasetFailure, &conf.shared, |w| {
+        ImpureError::Pure(StdDatasetError::from(w))
+    })
+    .map(|t| {
+        t.map_with_warnings(|(core, mut out), ws| {
+            out.meas_warnings = MeasWarnings::group(ws, &core.shortnames());
+            (core, out)
+        })
+    })
+}
+
+/// A file whose beginning is the HEADER of one of its datasets.
+///
+/// All offsets in a dataset are relative to the beginning of its HEADER, so
+/// seeking from the start of this will skip all datasets before it.
+struct DatasetFile {
+    file: fs::File,
+    base: u64,
+}
+
+impl DatasetFile {
+    fn new(mut file: fs::File, base: u64) -> std::io::Result<Self> {
+        file.seek(SeekFrom::Start(base))?;
+        Ok(Self { file, base })
+    }
+}
+
+impl Read for DatasetFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Seek for DatasetFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let p = match pos {
+            SeekFrom::Start(x) => SeekFrom::Start(self.base + x),
+            _ => pos,
+        };
+        self.file.seek(p).map(|x| x.saturating_sub(self.base))
+    }
+}
+
 fn h_read_dataset_from_kws<C, R>(
     h: &mut BufReader<R>,
     version: Version,
@@ -525,14 +1033,11 @@ impl RawTEXTOutput {
         R: Read + Seek,
         C: AsRef<ReadHeaderAndTEXTConfig> + AsRef<HeaderConfigInner>,
     {
-        Header::h_read(h, st)
+        let conf: &ReadHeaderAndTEXTConfig = st.conf.as_ref();
+        Header::h_read_as(h, st, conf.version_override)
             .mult_to_deferred()
             .def_map_errors(|e: ImpureError<HeaderError>| e.inner_into())
-            .def_and_maybe(|mut header| {
-                let conf: &ReadHeaderAndTEXTConfig = st.conf.as_ref();
-                if let Some(v) = conf.version_override {
-                    header.version = v
-                }
+            .def_and_maybe(|header| {
                 h_read_raw_text_from_header(h, header, st).def_map_errors(|e| e.inner_into())
             })
     }
@@ -553,6 +1058,8 @@ impl RawTEXTOutput {
             st,
         )
         .def_map_value(|(standardized, extra, offsets)| {
+            let estimated_memory_bytes =
+                standardized.estimated_memory_bytes(offsets.tot, offsets.data);
             (
                 standardized,
                 StdTEXTOutput {
@@ -561,6 +1068,8 @@ impl RawTEXTOutput {
                     data: offsets.data,
                     analysis: offsets.analysis,
                     extra,
+                    estimated_memory_bytes,
+                    meas_warnings: vec![],
                 },
             )
         })
@@ -602,6 +1111,7 @@ impl RawTEXTOutput {
                         },
                         extra,
                     },
+                    meas_warnings: vec![],
                     parse: self.parse,
                 },
             )
@@ -633,6 +1143,30 @@ where
     }
 }
 
+fn kws_to_passthru_analysis<C, R>(
+    version: Version,
+    h: &mut BufReader<R>,
+    kws: &StdKeywords,
+    data: HeaderDataSegment,
+    analysis: HeaderAnalysisSegment,
+    st: &ReadState<C>,
+) -> IODeferredResult<
+    (PassthruData, Analysis, AnyDataSegment, AnyAnalysisSegment),
+    LookupAndReadDataAnalysisWarning,
+    LookupAndReadDataAnalysisError,
+>
+where
+    R: Read + Seek,
+    C: AsRef<ReadLayoutConfig> + AsRef<ReadTEXTOffsetsConfig>,
+{
+    match version {
+        Version::FCS2_0 => Version2_0::h_lookup_and_read_passthru(h, kws, data, analysis, st),
+        Version::FCS3_0 => Version3_0::h_lookup_and_read_passthru(h, kws, data, analysis, st),
+        Version::FCS3_1 => Version3_1::h_lookup_and_read_passthru(h, kws, data, analysis, st),
+        Version::FCS3_2 => Version3_2::h_lookup_and_read_passthru(h, kws, data, analysis, st),
+    }
+}
+
 fn h_read_raw_text_from_header<C, R>(
     h: &mut BufReader<R>,
     header: Header,
@@ -645,6 +1179,10 @@ where
     let conf = st.conf.as_ref();
     let mut buf = vec![];
     let ptext_seg = header.segments.text;
+    let mut allow = AllowlistProgress::new(
+        conf.keyword_allowlist.as_deref(),
+        required_text_keys(header.version, conf),
+    );
     ptext_seg
         .inner
         .h_read_contents(h, &mut buf)
@@ -657,7 +1195,7 @@ where
     let kws_res = tnt_delim
         .and_maybe(|(delim, bytes)| {
             let kws = ParsedKeywords::default();
-            split_raw_primary_text(kws, delim, bytes, conf)
+            split_raw_primary_text(kws, delim, bytes, conf, &mut allow)
                 .def_inner_into()
                 .def_errors_liftio()
                 .def_map_value(|_kws| (delim, _kws))
@@ -676,12 +1214,16 @@ where
                     .warnings_into()
                     .map(|s| (s, kws))
                     .and_maybe(|(maybe_supp_seg, _kws)| {
-                        let tnt_supp_kws = if let Some(seg) = maybe_supp_seg {
+                        // skip STEXT entirely if everything we want was
+                        // already found in primary TEXT
+                        let tnt_supp_kws = if let Some(seg) =
+                            maybe_supp_seg.filter(|_| !allow.is_done())
+                        {
                             buf.clear();
                             seg.inner
                                 .h_read_contents(h, &mut buf)
                                 .map_err(|e| DeferredFailure::new1(e.into()))?;
-                            split_raw_supp_text(_kws, delim, &buf, conf)
+                            split_raw_supp_text(_kws, delim, &buf, conf, &mut allow)
                                 .inner_into()
                                 .errors_liftio()
                         } else {
@@ -693,6 +1235,11 @@ where
         });
 
     let repair_res = kws_res.def_and_tentatively(|(delim, mut kws, supp_text_seg)| {
+        let ctrl_ws: Vec<_> = kws
+            .apply_control_chars(conf.control_chars)
+            .into_iter()
+            .map(|w| ParseRawTEXTWarning::Keywords(w.into()))
+            .collect();
         append_keywords(&mut kws, conf)
             .map_or_else(
                 |es| {
@@ -708,6 +1255,7 @@ where
                 |_| Tentative::default(),
             )
             .map(|_| (delim, kws, supp_text_seg))
+            .and_tentatively(|x| Tentative::new(x, ctrl_ws, vec![]))
     });
 
     repair_res.def_and_tentatively(|(delimiter, kws, supp_text_seg)| {
@@ -782,6 +1330,17 @@ where
     })
 }
 
+/// Return keys which must be kept to read TEXT when using an allowlist.
+fn required_text_keys(version: Version, conf: &ReadHeaderAndTEXTConfig) -> &'static [&'static str] {
+    match version {
+        Version::FCS3_0 | Version::FCS3_1 if !conf.ignore_supp_text => {
+            &["$NEXTDATA", "$BEGINSTEXT", "$ENDSTEXT"]
+        }
+        // STEXT offsets are optional in 3.2 so don't wait for them
+        _ => &["$NEXTDATA"],
+    }
+}
+
 fn split_first_delim<'a>(
     bytes: &'a [u8],
     conf: &ReadHeaderAndTEXTConfig,
@@ -802,11 +1361,12 @@ fn split_raw_primary_text(
     delim: u8,
     bytes: &[u8],
     conf: &ReadHeaderAndTEXTConfig,
+    allow: &mut AllowlistProgress,
 ) -> DeferredResult<ParsedKeywords, ParseKeywordsIssue, ParsePrimaryTEXTError> {
     if bytes.is_empty() {
         Err(DeferredFailure::new1(NoTEXTWordsError.into()))
     } else {
-        Ok(split_raw_text_inner(kws, delim, bytes, conf).errors_into())
+        Ok(split_raw_text_inner(kws, delim, bytes, conf, allow).errors_into())
     }
 }
 
@@ -815,9 +1375,10 @@ fn split_raw_supp_text(
     delim: u8,
     bytes: &[u8],
     conf: &ReadHeaderAndTEXTConfig,
+    allow: &mut AllowlistProgress,
 ) -> Tentative<ParsedKeywords, ParseKeywordsIssue, ParseSupplementalTEXTError> {
     if let Some((byte0, rest)) = bytes.split_first() {
-        let mut tnt = split_raw_text_inner(kws, *byte0, rest, conf).errors_into();
+        let mut tnt = split_raw_text_inner(kws, *byte0, rest, conf, allow).errors_into();
         if *byte0 != delim {
             let x = DelimMismatch {
                 delim,
@@ -837,23 +1398,11 @@ fn split_raw_supp_text(
 }
 
 fn split_raw_text_inner(
-    kws: ParsedKeywords,
-    delim: u8,
-    bytes: &[u8],
-    conf: &ReadHeaderAndTEXTConfig,
-) -> Tentative<ParsedKeywords, ParseKeywordsIssue, ParseKeywordsIssue> {
-    if conf.use_literal_delims {
-        split_raw_text_literal_delim(kws, delim, bytes, conf)
-    } else {
-        split_raw_text_escaped_delim(kws, delim, bytes, conf)
-    }
-}
-
-fn split_raw_text_literal_delim(
     mut kws: ParsedKeywords,
     delim: u8,
     bytes: &[u8],
     conf: &ReadHeaderAndTEXTConfig,
+    allow: &mut AllowlistProgress,
 ) -> Tentative<ParsedKeywords, ParseKeywordsIssue, ParseKeywordsIssue> {
     let mut errors = vec![];
     let mut warnings = vec![];
@@ -867,178 +1416,250 @@ fn split_raw_text_literal_delim(
     };
 
     // ASSUME input slice does not start with delim
-    let mut it = bytes.split(|x| *x == delim);
-    let mut prev_was_blank = false;
-    let mut prev_was_key = false;
-
-    while let Some(key) = it.next() {
-        prev_was_key = true;
-        prev_was_blank = key.is_empty();
-        if key.is_empty() {
-            if let Some(value) = it.next() {
-                prev_was_key = false;
-                prev_was_blank = value.is_empty();
-                push_issue(conf.allow_empty, BlankKeyError.into());
-            } else {
-                // if everything is correct, we should exit here since the
-                // last word will be the blank slice after the final delim
-                break;
-            }
-        } else if let Some(value) = it.next() {
-            prev_was_key = false;
-            prev_was_blank = value.is_empty();
-            if value.is_empty() {
-                push_issue(conf.allow_empty, BlankValueError(key.to_vec()).into());
-            } else if let Err(lvl) = kws.insert(key, value, conf) {
-                match lvl.inner_into() {
-                    Leveled::Error(e) => push_issue(false, e),
-                    Leveled::Warning(w) => push_issue(true, w),
+    let tokens = if conf.use_literal_delims {
+        TextTokenizer::new_literal(bytes, delim)
+    } else {
+        TextTokenizer::new(bytes, delim)
+    };
+
+    for token in tokens {
+        match token {
+            TextToken::Pair(p) => {
+                let key = p.key.unescape(delim);
+                if allow.check(&key) {
+                    if let Err(lvl) = kws.insert(&key, &p.value.unescape(delim), conf) {
+                        match lvl.inner_into() {
+                            Leveled::Error(e) => push_issue(false, e),
+                            Leveled::Warning(w) => push_issue(true, w),
+                        }
+                    }
+                    if allow.is_done() {
+                        // the rest of TEXT is unchecked so don't complain about it
+                        return Tentative::new(kws, warnings, errors);
+                    }
                 }
             }
-        } else {
-            // exiting here means we found a key without a value and also didn't
-            // end with a delim
-            break;
+            TextToken::Issue(i) => match i {
+                TextTokenIssue::DelimAtBoundary => {
+                    push_issue(conf.allow_delim_at_boundary, DelimBoundError.into());
+                }
+                TextTokenIssue::BlankKey => push_issue(conf.allow_empty, BlankKeyError.into()),
+                TextTokenIssue::BlankValue(k) => {
+                    push_issue(conf.allow_empty, BlankValueError(k.raw.to_vec()).into());
+                }
+                TextTokenIssue::Uneven => push_issue(conf.allow_odd, UnevenWordsError.into()),
+                TextTokenIssue::MissingFinalDelim => {
+                    push_issue(conf.allow_missing_final_delim, FinalDelimError.into());
+                }
+            },
         }
     }
 
-    if !prev_was_key {
-        push_issue(conf.allow_odd, UnevenWordsError.into());
-    }
-
-    if !prev_was_blank {
-        push_issue(conf.allow_missing_final_delim, FinalDelimError.into());
-    }
-
     Tentative::new(kws, warnings, errors)
 }
 
-fn split_raw_text_escaped_delim(
-    mut kws: ParsedKeywords,
-    delim: u8,
-    bytes: &[u8],
-    conf: &ReadHeaderAndTEXTConfig,
-) -> Tentative<ParsedKeywords, ParseKeywordsIssue, ParseKeywordsIssue> {
-    let mut ews = (vec![], vec![]);
-
-    let push_issue = |_ews: &mut (Vec<_>, Vec<_>), is_warning, error| {
-        let warnings = &mut _ews.0;
-        let errors = &mut _ews.1;
-        if is_warning {
-            warnings.push(error);
-        } else {
-            errors.push(error);
-        }
+fn h_patch_text<F>(
+    p: &path::PathBuf,
+    h: &mut BufReader<fs::File>,
+    raw: &RawTEXTOutput,
+    dry_run: bool,
+    f: F,
+) -> IOResult<Vec<KeywordChange>, PatchTEXTError>
+where
+    F: Fn(&str, &str) -> Option<String>,
+{
+    let header = &raw.parse.header_segments;
+    let Some((begin, end)) = header
+        .text
+        .inner
+        .try_coords()
+        .map(|(b, e)| (b.into(), e.into()))
+    else {
+        return Ok(vec![]);
     };
-
-    let mut push_pair = |_ews: &mut (Vec<_>, Vec<_>), kb: &Vec<_>, vb: &Vec<_>| {
-        if let Err(lvl) = kws.insert(kb, vb, conf) {
-            match lvl.inner_into() {
-                Leveled::Error(e) => push_issue(_ews, false, e),
-                Leveled::Warning(w) => push_issue(_ews, true, w),
-            }
+    let mut buf = vec![];
+    header.text.inner.h_read_contents(h, &mut buf)?;
+    let (new_text, changes) =
+        patch_primary_text(&buf[..], f).map_err(|e| ImpureError::Pure(e.into()))?;
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+    let file_len = h.get_ref().metadata()?.len();
+    let available = next_segment_begin(raw, begin).unwrap_or(file_len) - begin;
+    let required = new_text.len() as u64;
+    if required > available {
+        let e = TEXTPatchError::NoSpace {
+            required,
+            available,
+        };
+        return Err(ImpureError::Pure(e.into()));
+    }
+    let new_end = begin + required - 1;
+    if new_end > u64::from(MAX_HEADER_OFFSET) {
+        return Err(ImpureError::Pure(
+            TEXTPatchError::EndTooLarge(new_end).into(),
+        ));
+    }
+    if !dry_run {
+        let mut w = fs::OpenOptions::new().write(true).open(p)?;
+        w.seek(SeekFrom::Start(begin))?;
+        w.write_all(&new_text[..])?;
+        // pad any leftover bytes from the old TEXT with spaces so they aren't
+        // mistaken for anything meaningful
+        if new_end < end {
+            w.write_all(&vec![b' '; (end - new_end) as usize][..])?;
         }
-    };
-
-    let push_delim = |kb: &mut Vec<_>, vb: &mut Vec<_>, k: usize| {
-        let n = k.div_ceil(2);
-        let buf = if vb.is_empty() { kb } else { vb };
-        for _ in 0..n {
-            buf.push(delim);
+        if new_end != end {
+            w.seek(SeekFrom::Start(HEADER_TEXT_END_POS))?;
+            w.write_all(format!("{new_end:>8}").as_bytes())?;
         }
+    }
+    Ok(changes)
+}
+
+fn h_patch_version(
+    p: &path::PathBuf,
+    h: &mut BufReader<fs::File>,
+    version: Version,
+    dry_run: bool,
+) -> io::Result<Option<Version>> {
+    h.seek(SeekFrom::Start(0))?;
+    let old = match Version::h_read(h) {
+        Ok(v) => Some(v),
+        Err(ImpureError::Pure(_)) => None,
+        Err(ImpureError::IO(e)) => return Err(e),
     };
+    if !dry_run && old != Some(version) {
+        let mut w = fs::OpenOptions::new().write(true).open(p)?;
+        w.write_all(version.to_string().as_bytes())?;
+    }
+    Ok(old)
+}
 
-    // ASSUME input slice does not start with delim
-    let mut consec_blanks = 0;
-    let mut keybuf: Vec<u8> = vec![];
-    let mut valuebuf: Vec<u8> = vec![];
+/// Return the first offset after `begin` which belongs to some other segment.
+fn next_segment_begin(raw: &RawTEXTOutput, begin: u64) -> Option<u64> {
+    let header = &raw.parse.header_segments;
+    let from_header = [
+        header.data.inner.as_u64().try_coords(),
+        header.analysis.inner.as_u64().try_coords(),
+    ]
+    .into_iter()
+    .chain(header.other.iter().map(|o| o.inner.as_u64().try_coords()))
+    .chain([raw
+        .parse
+        .supp_text
+        .map(|s| s.inner.as_u64())
+        .and_then(|s| s.try_coords())])
+    .flatten()
+    .map(|(b, _)| b);
+    let from_text = [Begindata::std(), Beginanalysis::std(), Beginstext::std()]
+        .into_iter()
+        .flat_map(|k| raw.keywords.std.get(&k))
+        .flat_map(|v| v.trim().parse::<u64>().ok());
+    from_header.chain(from_text).filter(|x| *x > begin).min()
+}
 
-    for segment in bytes.split(|x| *x == delim) {
-        if segment.is_empty() {
-            consec_blanks += 1;
+/// Return the last offset which belongs to any segment in a dataset.
+///
+/// Offsets are relative to the start of the dataset.
+fn dataset_end(raw: &RawTEXTOutput) -> u64 {
+    let header = &raw.parse.header_segments;
+    let from_header = [
+        header.text.inner.as_u64().try_coords(),
+        header.data.inner.as_u64().try_coords(),
+        header.analysis.inner.as_u64().try_coords(),
+    ]
+    .into_iter()
+    .chain(header.other.iter().map(|o| o.inner.as_u64().try_coords()))
+    .chain([raw
+        .parse
+        .supp_text
+        .map(|s| s.inner.as_u64())
+        .and_then(|s| s.try_coords())])
+    .flatten()
+    .map(|(_, e)| e);
+    let from_text = [Enddata::std(), Endanalysis::std(), Endstext::std()]
+        .into_iter()
+        .flat_map(|k| raw.keywords.std.get(&k))
+        .flat_map(|v| v.trim().parse::<u64>().ok());
+    from_header
+        .chain(from_text)
+        .max()
+        .unwrap_or(0)
+        .max(u64::from(HEADER_LEN) - 1)
+}
+
+/// Return the offset of the first valid HEADER at or after `from`.
+///
+/// A HEADER is valid if it starts with "FCS" and can be parsed, which should
+/// rule out most chance occurrences of "FCS" in other segments.
+fn find_next_header(p: &path::PathBuf, from: u64) -> std::io::Result<Option<u64>> {
+    let len = usize::from(HEADER_LEN);
+    let mut h = fs::File::open(p)?;
+    h.seek(SeekFrom::Start(from))?;
+    let mut chunk = vec![0; 0x10000];
+    let mut buf = vec![];
+    let mut start = from;
+    loop {
+        let n = h.read(&mut chunk)?;
+        buf.extend_from_slice(&chunk[..n]);
+        // keep enough bytes to parse a HEADER at the end of this chunk
+        let last = if n == 0 {
+            buf.len()
         } else {
-            if consec_blanks & 1 == 0 {
-                // Previous number of delimiters is odd, treat this as a word
-                // boundary
-                if !valuebuf.is_empty() {
-                    push_pair(&mut ews, &keybuf, &valuebuf);
-                    keybuf.clear();
-                    valuebuf.clear();
-                    keybuf.extend_from_slice(segment);
-                } else if !keybuf.is_empty() {
-                    valuebuf.extend_from_slice(segment);
-                } else {
-                    // this should only be reached on first iteration
-                    keybuf.extend_from_slice(segment);
-                }
-                if consec_blanks > 0 {
-                    push_issue(
-                        &mut ews,
-                        conf.allow_delim_at_boundary,
-                        DelimBoundError.into(),
-                    );
-                }
-            } else {
-                // Previous consecutive delimiter sequence was even. Push n / 2
-                // delimiters to whatever the current word is. Then push to
-                // key or value
-                push_delim(&mut keybuf, &mut valuebuf, consec_blanks);
-                if !valuebuf.is_empty() {
-                    valuebuf.extend_from_slice(segment);
-                } else {
-                    keybuf.extend_from_slice(segment);
-                }
-            }
-            consec_blanks = 0;
+            buf.len().saturating_sub(len - 1)
+        };
+        let found =
+            (0..last).find(|&i| buf[i..].starts_with(b"FCS") && parse_header(&buf[i..]).is_ok());
+        if let Some(i) = found {
+            return Ok(Some(start + i as u64));
         }
-    }
-
-    // If all went perfectly, we should have one consecutive blank at this point
-    // since the space between the last delim and the end will show up as a
-    // blank.
-    //
-    // If we have 0, then there was no delim at the end, which is an error.
-    //
-    // If number of blanks is even and not 0, then the last word ended with one
-    // or more escaped delimiters, but the TEXT didn't (2 errors, delim at
-    // boundary and no delim ending TEXT). Note that here, blanks = number of
-    // literal delimiters, whereas in the loop, this corresponded to blanks + 1
-    // delimiters.
-    //
-    // If number of blanks is odd but not 1, the last word ended with one or
-    // more escaped delimiters (error: on a boundary) and the TEXT ended with a
-    // delimiter (not an error).
-
-    if consec_blanks == 0 {
-        push_issue(
-            &mut ews,
-            conf.allow_missing_final_delim,
-            FinalDelimError.into(),
-        );
-    } else if consec_blanks > 1 {
-        push_issue(
-            &mut ews,
-            conf.allow_delim_at_boundary,
-            DelimBoundError.into(),
-        );
-        push_delim(&mut keybuf, &mut valuebuf, consec_blanks);
-
-        if consec_blanks & 1 == 1 {
-            push_issue(
-                &mut ews,
-                conf.allow_missing_final_delim,
-                FinalDelimError.into(),
-            );
+        if n == 0 {
+            return Ok(None);
         }
+        buf.drain(..last);
+        start += last as u64;
     }
+}
 
-    if valuebuf.is_empty() {
-        push_issue(&mut ews, conf.allow_odd, UnevenWordsError.into());
-    } else {
-        push_pair(&mut ews, &keybuf, &valuebuf);
+/// Replace values in primary TEXT.
+///
+/// Words are assumed to be separated by the delimiter (the first byte) with
+/// delimiters within words escaped by doubling them. Any word that is not
+/// changed will be copied verbatim.
+fn patch_primary_text<F>(
+    bytes: &[u8],
+    f: F,
+) -> Result<(Vec<u8>, Vec<KeywordChange>), TEXTPatchError>
+where
+    F: Fn(&str, &str) -> Option<String>,
+{
+    let Some(tokens) = TextTokenizer::from_text(bytes) else {
+        return Ok((vec![], vec![]));
+    };
+    let delim = tokens.delim();
+    let mut changes = vec![];
+    let mut out = vec![];
+    let mut copied = 0;
+    for p in tokens.pairs() {
+        let k = p.key.unescape(delim).into_owned();
+        let v = p.value.unescape(delim).into_owned();
+        let (Ok(key), Ok(old)) = (String::from_utf8(k), String::from_utf8(v)) else {
+            continue;
+        };
+        if let Some(new) = f(&key, &old).filter(|x| *x != old) {
+            let d = char::from(delim);
+            if new.is_empty() || new.starts_with(d) || new.ends_with(d) {
+                return Err(TEXTPatchError::InvalidValue { key, value: new });
+            }
+            out.extend_from_slice(&bytes[copied..p.value.offset]);
+            out.extend(tokenizer::escape(delim, new.as_bytes()));
+            copied = p.value.end();
+            changes.push(KeywordChange { key, old, new });
+        }
     }
-
-    Tentative::new(kws, ews.0, ews.1)
+    out.extend_from_slice(&bytes[copied..]);
+    Ok((out, changes))
 }
 
 fn append_keywords(
@@ -1073,8 +1694,12 @@ where
     }
     .and_tentatively(|x| {
         x.map(|seg| {
+            let supp_coords = seg.inner.as_u64().try_coords();
+            let text_coords = text_segment.inner.as_u64().try_coords();
             if seg.inner.as_u64() == text_segment.inner.as_u64() {
                 Tentative::new_either(None, vec![DuplicatedSuppTEXT], !conf.allow_duplicated_stext)
+            } else if let Some(e) = supp_coords.and_then(|c| find_stext_cycle(c, text_coords)) {
+                Tentative::new(None, vec![], vec![e.into()])
             } else {
                 Tentative::new1(Some(seg))
             }
@@ -1083,6 +1708,28 @@ where
     })
 }
 
+/// Return error if STEXT points back into any segment already read.
+///
+/// Only one level of STEXT is ever followed (the standard has no provision for
+/// STEXT to point to yet another STEXT), so the only segments which could have
+/// been visited are HEADER and primary TEXT. Exact duplicates of primary TEXT
+/// are handled separately since these are common and (optionally) allowed.
+fn find_stext_cycle(supp: (u64, u64), text: Option<(u64, u64)>) -> Option<CyclicSuppTEXT> {
+    let (begin, end) = supp;
+    [
+        Some(("HEADER", (0, u64::from(HEADER_LEN) - 1))),
+        text.map(|t| ("primary TEXT", t)),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|(_, (b, e))| begin <= *e && *b <= end)
+    .map(|(visited, _)| CyclicSuppTEXT {
+        begin,
+        end,
+        visited,
+    })
+}
+
 // TODO the reason we use get instead of remove here is because we don't want to
 // mess up the keyword list for raw mode, but in standardized mode we are
 // consuming the hash table as a way to test for pseudostandard keywords (ie
@@ -1110,6 +1757,16 @@ impl fmt::Display for DuplicatedSuppTEXT {
     }
 }
 
+impl fmt::Display for CyclicSuppTEXT {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "supplemental TEXT at {}-{} points back into {}",
+            self.begin, self.end, self.visited
+        )
+    }
+}
+
 impl fmt::Display for DelimCharError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(
@@ -1191,8 +1848,32 @@ impl fmt::Display for NonstandardError {
     }
 }
 
+impl fmt::Display for TEXTPatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::InvalidValue { key, value } => write!(
+                f,
+                "new value for {key} is empty or starts/ends with delimiter: '{value}'"
+            ),
+            Self::NoSpace {
+                required,
+                available,
+            } => write!(
+                f,
+                "new TEXT requires {required} bytes but only {available} are \
+                 available before next segment",
+            ),
+            Self::EndTooLarge(x) => {
+                write!(f, "new TEXT would end at {x} which cannot fit in HEADER")
+            }
+        }
+    }
+}
+
 def_failure!(HeaderFailure, "could not parse HEADER");
 
+def_failure!(PatchTEXTFailure, "could not patch TEXT in place");
+
 def_failure!(RawTEXTFailure, "could not parse TEXT segment");
 
 def_failure!(StdTEXTFailure, "could not standardize TEXT segment");
@@ -1204,14 +1885,72 @@ def_failure!(
 
 def_failure!(RawDatasetFailure, "could not read DATA with raw TEXT");
 
+def_failure!(
+    RawDatasetPassthruFailure,
+    "could not read DATA verbatim with raw TEXT"
+);
+
 def_failure!(
     RawDatasetWithKwsFailure,
     "could not read raw dataset from keywords"
 );
 
+#[cfg(feature = "python")]
+mod python {
+    use super::{RawDatasetOutput, RawDatasetWithKwsOutput};
+
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    impl RawDatasetOutput {
+        /// Convert to a dict with DATA as a pyarrow Table.
+        pub fn into_pyobject_arrow<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+            let d = PyDict::new(py);
+            d.set_item("text", self.text)?;
+            d.set_item("dataset", self.dataset.into_pyobject_arrow(py)?)?;
+            Ok(d)
+        }
+    }
+
+    impl RawDatasetWithKwsOutput {
+        /// Convert to a dict with DATA as a pyarrow Table.
+        ///
+        /// This mirrors the derived conversion except for DATA, which
+        /// would otherwise be a polars DataFrame.
+        pub fn into_pyobject_arrow<'py>(self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+            let d = PyDict::new(py);
+            d.set_item("data", self.data.into_pyarrow(py, None)?)?;
+            d.set_item("analysis", self.analysis)?;
+            d.set_item("others", self.others)?;
+            d.set_item("data_seg", self.data_seg)?;
+            d.set_item("analysis_seg", self.analysis_seg)?;
+            Ok(d)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channels::{DisplayNameConfig, DisplayNameFormat};
+    use crate::demux::Threshold;
+    use crate::error::{SuppressedErrors, SuppressedWarnings};
+    use crate::text::compensation::Compensation;
+    use crate::text::gating::{AppliedGates3_2, Region, UnivariateRegion};
+    use crate::text::index::RegionIndex;
+    use crate::text::named_vec::{Element, NonCenterElement};
+    use crate::text::optional::AlwaysValue;
+    use crate::text::spillover::Spillover;
+    use crate::text::unstainedcenters::UnstainedCenters;
+    use crate::text::provenance::ParentFile;
+    use crate::validated::dataframe::{AnyFCSColumn, F32Column};
+    use chrono::{NaiveDate, NaiveTime, TimeDelta};
+    use nalgebra::DMatrix;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs::File;
+    use std::io::BufWriter;
+    use std::num::NonZeroUsize;
 
     #[test]
     fn test_split_text_escape() {
@@ -1220,7 +1959,8 @@ mod tests {
         // NOTE should not start with delim
         let bytes = "$P4F/700//75 BP/".as_bytes();
         let delim = 47;
-        let out = split_raw_text_escaped_delim(kws, delim, bytes, &conf);
+        let mut allow = AllowlistProgress::new(None, &[]);
+        let out = split_raw_text_inner(kws, delim, bytes, &conf, &mut allow);
         let v = out
             .value()
             .std
@@ -1234,4 +1974,1053 @@ mod tests {
         assert!(es.is_empty(), "errors: {:?}", es);
         assert!(ws.is_empty(), "warnings: {:?}", ws);
     }
+
+    #[test]
+    fn test_split_text_allowlist() {
+        let allowlist = ["$PAR".parse().unwrap(), "Vendor".parse().unwrap()];
+        // NOTE the last word has a single delim at the end so this would fail
+        // if it were scanned
+        let bytes = "$P1F/7/$par/1/$NEXTDATA/0/vendor/x/$TOT/5/oops".as_bytes();
+        for literal in [false, true] {
+            let conf = ReadHeaderAndTEXTConfig {
+                use_literal_delims: literal,
+                ..ReadHeaderAndTEXTConfig::default()
+            };
+            let mut allow = AllowlistProgress::new(Some(&allowlist), &["$NEXTDATA"]);
+            let out = split_raw_text_inner(ParsedKeywords::default(), 47, bytes, &conf, &mut allow);
+            assert!(allow.is_done());
+            assert!(out.errors().is_empty());
+            assert!(out.warnings().is_empty());
+            let kws = out.value();
+            let mut std: Vec<_> = kws.std.keys().map(|k| k.to_string()).collect();
+            std.sort();
+            assert_eq!(vec!["$NEXTDATA", "$par"], std);
+            assert_eq!(1, kws.nonstd.len());
+        }
+    }
+
+    #[test]
+    fn test_split_text_matches_tokenizer() {
+        // the reader and the public tokenizer should agree on pairs and issues
+        // in both delimiter modes, including pathological inputs
+        let cases = [
+            "$P4F/700//75 BP/",
+            "A/1///B/2/",
+            "A/1/B/2//",
+            "A/1/B/2///",
+            "//A/1/",
+            "A/1//B/2/",
+            "A//1/B",
+            "/A/1/",
+            "A/1/B",
+            "A/1",
+        ];
+        for literal in [false, true] {
+            let conf = ReadHeaderAndTEXTConfig {
+                use_literal_delims: literal,
+                ..ReadHeaderAndTEXTConfig::default()
+            };
+            for c in cases {
+                let bytes = c.as_bytes();
+                let mut allow = AllowlistProgress::new(None, &[]);
+                let out =
+                    split_raw_text_inner(ParsedKeywords::default(), b'/', bytes, &conf, &mut allow);
+                let kws = out.value();
+                let mut read: Vec<_> = kws
+                    .std
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.clone()))
+                    .chain(kws.nonstd.iter().map(|(k, v)| (k.to_string(), v.clone())))
+                    .collect();
+                read.sort();
+                let tokens = if literal {
+                    TextTokenizer::new_literal(bytes, b'/')
+                } else {
+                    TextTokenizer::new(bytes, b'/')
+                };
+                let (pairs, issues): (Vec<_>, Vec<_>) =
+                    tokens.partition(|t| matches!(t, TextToken::Pair(_)));
+                let mut split: Vec<_> = pairs
+                    .into_iter()
+                    .filter_map(|t| match t {
+                        TextToken::Pair(p) => Some((
+                            String::from_utf8(p.key.unescape(b'/').into_owned()).unwrap(),
+                            String::from_utf8(p.value.unescape(b'/').into_owned()).unwrap(),
+                        )),
+                        TextToken::Issue(_) => None,
+                    })
+                    .collect();
+                split.sort();
+                assert_eq!(split, read, "pairs differ for '{c}' (literal={literal})");
+                assert_eq!(
+                    issues.len(),
+                    out.errors().len(),
+                    "issues differ for '{c}' (literal={literal})"
+                );
+                assert!(out.warnings().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_text_allowlist_missing() {
+        let allowlist = ["$PAR".parse().unwrap(), "$NOPE".parse().unwrap()];
+        let bytes = "$P1F/7//5/$PAR/1/".as_bytes();
+        let conf = ReadHeaderAndTEXTConfig::default();
+        let mut allow = AllowlistProgress::new(Some(&allowlist), &[]);
+        let out = split_raw_text_inner(ParsedKeywords::default(), 47, bytes, &conf, &mut allow);
+        // nothing is skipped if a key is never found, so the whole segment is
+        // still checked
+        assert!(!allow.is_done());
+        assert!(out.errors().is_empty());
+        assert_eq!(1, out.value().std.len());
+    }
+
+    #[test]
+    fn test_find_stext_cycle() {
+        let text = Some((58, 200));
+        assert!(find_stext_cycle((201, 300), text).is_none());
+        assert!(find_stext_cycle((201, 300), None).is_none());
+        let e = find_stext_cycle((150, 300), text).unwrap();
+        assert_eq!("primary TEXT", e.visited);
+        let h = find_stext_cycle((10, 20), text).unwrap();
+        assert_eq!("HEADER", h.visited);
+    }
+
+    #[test]
+    fn test_patch_primary_text() {
+        let bytes = "/$PROJ/old//name/$P4F/700//75 BP/".as_bytes();
+        let (out, changes) =
+            patch_primary_text(bytes, |k, _| (k == "$PROJ").then(|| "new/name".to_string()))
+                .ok()
+                .unwrap();
+        assert_eq!("/$PROJ/new//name/$P4F/700//75 BP/".as_bytes(), &out[..]);
+        assert_eq!(1, changes.len());
+        assert_eq!("old/name", changes[0].old);
+        assert_eq!("new/name", changes[0].new);
+    }
+
+    #[test]
+    fn test_patch_primary_text_unchanged() {
+        let bytes = "/$PROJ/x/$P4F/700//75 BP".as_bytes();
+        let (out, changes) = patch_primary_text(bytes, |_, _| None).ok().unwrap();
+        assert_eq!(bytes, &out[..]);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_group_meas_warnings() {
+        struct W(Option<usize>, &'static str);
+        impl MeasIndexed for W {
+            fn meas_index(&self) -> Option<MeasIndex> {
+                self.0.map(MeasIndex::from)
+            }
+        }
+        impl fmt::Display for W {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+                f.write_str(self.1)
+            }
+        }
+        let names: Vec<_> = ["FSC", "SSC"].map(Shortname::new_unchecked).into();
+        let ws = [
+            W(Some(1), "a"),
+            W(None, "b"),
+            W(Some(0), "c"),
+            W(Some(1), "d"),
+            W(Some(5), "e"),
+        ];
+        let gs = MeasWarnings::group(&ws, &names);
+        assert_eq!(2, gs.len());
+        assert_eq!(0, usize::from(gs[0].index));
+        assert_eq!("FSC", gs[0].shortname.as_ref());
+        assert_eq!(vec!["c"], gs[0].warnings);
+        assert_eq!("SSC", gs[1].shortname.as_ref());
+        assert_eq!(vec!["a", "d"], gs[1].warnings);
+    }
+
+    #[test]
+    fn test_passthru_iter_rows() {
+        let layout = |byteord: &str, widths: [&str; 2], datatypes| PassthruLayout {
+            datatype: AlphaNumType::Integer,
+            byteord: byteord.into(),
+            widths: widths.map(String::from).into(),
+            ranges: vec![],
+            datatypes,
+        };
+        let mut bytes = vec![];
+        for (x, y) in [(1_u16, 1.5_f32), (258, -2.0)] {
+            bytes.extend(x.to_be_bytes());
+            bytes.extend(y.to_be_bytes());
+        }
+        // trailing partial event should be ignored
+        bytes.push(0);
+        let mut data = PassthruData {
+            bytes: RawData(bytes),
+            layout: Some(layout(
+                "4,3,2,1",
+                ["16", "32"],
+                vec![AlphaNumType::Integer, AlphaNumType::Float],
+            )),
+            partial_layout: None,
+        };
+        let rows0: Vec<_> = data.iter_rows().unwrap().collect();
+        assert_eq!(vec![vec![1.0, 1.5], vec![258.0, -2.0]], rows0);
+
+        data.bytes = RawData(vec![0, 1, 0, 0, b' ', b'7']);
+        data.layout = Some(layout(
+            "3,4,1,2",
+            ["32", "2"],
+            vec![AlphaNumType::Integer, AlphaNumType::Ascii],
+        ));
+        let rows1: Vec<_> = data.iter_rows().unwrap().collect();
+        assert_eq!(vec![vec![16_777_216.0, 7.0]], rows1);
+
+        // mixed byte order which doesn't match the width
+        data.layout = Some(layout(
+            "3,4,1,2",
+            ["16", "2"],
+            vec![AlphaNumType::Integer, AlphaNumType::Ascii],
+        ));
+        assert!(data.iter_rows().is_none());
+    }
+
+    /// Make an FCS file with `n` measurements and 2 events.
+    ///
+    /// Value for the ith measurement in the jth event is i + 2000 * j.
+    fn wide_fcs(version: &str, n: usize) -> Vec<u8> {
+        wide_fcs_range(version, n, 4096)
+    }
+
+    /// Like [`wide_fcs`] but with $PnR set to `range` for all measurements.
+    fn wide_fcs_range(version: &str, n: usize, range: u64) -> Vec<u8> {
+        let (datatype, byteord, width) = if version == "FCS2.0" {
+            ("I", "1,2", 16)
+        } else {
+            ("F", "1,2,3,4", 32)
+        };
+        let mut text = format!("/$BYTEORD/{byteord}/$DATATYPE/{datatype}/$MODE/L/$PAR/{n}/$TOT/2/");
+        for i in 1..=n {
+            text += &format!("$P{i}B/{width}/$P{i}E/0,0/$P{i}N/ch{i}/$P{i}R/{range}/");
+        }
+        let data: Vec<u8> = (0..2)
+            .flat_map(|j| (1..=n).map(move |i| i + 2000 * j))
+            .flat_map(|x| {
+                if width == 16 {
+                    (x as u16).to_le_bytes().to_vec()
+                } else {
+                    (x as f32).to_le_bytes().to_vec()
+                }
+            })
+            .collect();
+        // the length of TEXT depends on the number of digits in the DATA
+        // offsets which depends on the length of TEXT, so iterate until stable
+        let mut full = text.clone();
+        loop {
+            let db = 58 + full.len();
+            let de = db + data.len() - 1;
+            let next = if version == "FCS2.0" {
+                text.clone()
+            } else {
+                format!(
+                    "{text}$BEGINDATA/{db}/$ENDDATA/{de}/$BEGINANALYSIS/0/\
+                     $ENDANALYSIS/0/$BEGINSTEXT/0/$ENDSTEXT/0/$NEXTDATA/0/"
+                )
+            };
+            let done = next.len() == full.len();
+            full = next;
+            if done {
+                break;
+            }
+        }
+        let te = 57 + full.len();
+        let (db, de) = (te + 1, te + data.len());
+        let mut ret = format!(
+            "{version}    {:>8}{te:>8}{db:>8}{de:>8}{:>8}{:>8}",
+            58, 0, 0
+        )
+        .into_bytes();
+        ret.extend(full.into_bytes());
+        ret.extend(data);
+        ret
+    }
+
+    #[test]
+    fn test_max_warnings() {
+        // every $PnR is too big for 16 bits, so each measurement will warn
+        let p = env::temp_dir().join("fireflow_max_warnings.fcs");
+        fs::write(&p, wide_fcs_range("FCS2.0", 5, 1_000_000)).unwrap();
+        let mut conf = ReadStdDatasetConfig::default();
+        conf.shared.max_warnings = None;
+        let t = fcs_read_std_dataset(&p, &conf).ok().unwrap();
+        assert!(t.suppressed_warnings().is_none());
+        assert_eq!(5, t.resolve(|ws| ws.len()).1);
+        conf.shared.max_warnings = Some(2);
+        let t2 = fcs_read_std_dataset(&p, &conf).ok().unwrap();
+        let n = t2.suppressed_warnings();
+        assert_eq!(Some(SuppressedWarnings(3)), n);
+        assert_eq!("3 further warnings suppressed", n.unwrap().to_string());
+        assert_eq!(2, t2.resolve(|ws| ws.len()).1);
+        // warnings which become errors are limited the same way
+        conf.shared.warnings_are_errors = true;
+        let f = fcs_read_std_dataset(&p, &conf).err().unwrap();
+        assert!(f.suppressed_warnings().is_none());
+        let m = f.suppressed_errors();
+        assert_eq!(Some(SuppressedErrors(3)), m);
+        assert_eq!("3 further errors suppressed", m.unwrap().to_string());
+        assert_eq!(2, f.resolve(|_| (), |es, _| es.len()).1);
+        // errors are always kept if the limit is zero
+        conf.shared.max_warnings = Some(0);
+        let f2 = fcs_read_std_dataset(&p, &conf).err().unwrap();
+        assert_eq!(Some(SuppressedErrors(4)), f2.suppressed_errors());
+        assert_eq!(1, f2.resolve(|_| (), |es, _| es.len()).1);
+        fs::remove_file(p).unwrap();
+    }
+
+    #[test]
+    fn test_read_write_wide() {
+        let n = 1500;
+        let conf = ReadStdDatasetConfig::default();
+        for version in ["FCS2.0", "FCS3.1"] {
+            let p = env::temp_dir().join(format!("fireflow_wide_{version}.fcs"));
+            fs::write(&p, wide_fcs(version, n)).unwrap();
+            let res = fcs_read_std_dataset(&p, &conf).ok().unwrap();
+            let ((core, _), ws) = res.resolve(|ws| ws.len());
+            assert_eq!(0, ws, "{version}");
+            let names = core.shortnames();
+            assert_eq!(n, names.len());
+            assert_eq!("ch1000", names[999].as_ref());
+            assert_eq!("ch1500", names[1499].as_ref());
+            let last: Vec<f64> = core.iter_column(&names[1499]).ok().unwrap().collect();
+            assert_eq!(vec![1500.0, 3500.0], last);
+            let kws = core.standard_keywords();
+            assert_eq!(Some("ch1234"), kws.get("$P1234N").map(String::as_str));
+
+            // write and read back
+            let out = env::temp_dir().join(format!("fireflow_wide_{version}_out.fcs"));
+            {
+                let mut h = BufWriter::new(File::create(&out).unwrap());
+                let w = match &core {
+                    AnyCore::FCS2_0(x) => x.h_write_dataset(&mut h, &WriteConfig::default()),
+                    AnyCore::FCS3_1(x) => x.h_write_dataset(&mut h, &WriteConfig::default()),
+                    _ => unreachable!(),
+                };
+                assert!(w.is_ok(), "{version}");
+            }
+            let res2 = fcs_read_std_dataset(&out, &conf).ok().unwrap();
+            let ((core2, _), _) = res2.resolve(|_| ());
+            assert_eq!(names, core2.shortnames());
+            assert!(core.as_data() == core2.as_data(), "{version}");
+            fs::remove_file(p).unwrap();
+            fs::remove_file(out).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_patch_version() {
+        let conf = ReadStdTEXTConfig::default();
+        let p = env::temp_dir().join("fireflow_patch_version.fcs");
+        let orig = wide_fcs("FCS3.1", 3);
+        fs::write(&p, &orig).unwrap();
+        let patch = |v, dry_run| {
+            fcs_patch_version(&p, v, &conf, dry_run)
+                .ok()
+                .map(|t| t.resolve(|_| ()).0)
+        };
+        let read_version = || {
+            fcs_read_std_text(&p, &conf)
+                .ok()
+                .map(|t| t.resolve(|_| ()).0.0.version())
+        };
+        // dry run changes nothing
+        assert_eq!(Some(Some(Version::FCS3_1)), patch(Version::FCS3_0, true));
+        assert!(orig == fs::read(&p).unwrap());
+        // real write changes only the version
+        assert_eq!(Some(Some(Version::FCS3_1)), patch(Version::FCS3_0, false));
+        assert_eq!(Some(Version::FCS3_0), read_version());
+        let patched = fs::read(&p).unwrap();
+        assert_eq!(b"FCS3.0", &patched[..6]);
+        assert!(orig[6..] == patched[6..]);
+        // and back again
+        assert_eq!(Some(Some(Version::FCS3_0)), patch(Version::FCS3_1, false));
+        assert!(orig == fs::read(&p).unwrap());
+        // TEXT is incompatible with these versions ($BEGINDATA and friends are
+        // pseudostandard in 2.0 and $CYT is missing for 3.2)
+        for v in [Version::FCS2_0, Version::FCS3_2] {
+            assert_eq!(None, patch(v, true));
+            assert_eq!(None, patch(v, false));
+            assert!(orig == fs::read(&p).unwrap());
+        }
+        fs::remove_file(&p).unwrap();
+    }
+
+    #[test]
+    fn test_patch_version_invalid() {
+        let conf = ReadStdTEXTConfig::default();
+        let p = env::temp_dir().join("fireflow_patch_version_invalid.fcs");
+        let mut orig = wide_fcs("FCS2.0", 2);
+        orig[..6].copy_from_slice(b"FCS9.9");
+        fs::write(&p, &orig).unwrap();
+        assert!(fcs_read_std_text(&p, &conf).is_err());
+        let patch = |dry_run| {
+            fcs_patch_version(&p, Version::FCS2_0, &conf, dry_run)
+                .ok()
+                .map(|t| t.resolve(|_| ()).0)
+        };
+        assert_eq!(Some(None), patch(true));
+        assert!(orig == fs::read(&p).unwrap());
+        assert_eq!(Some(None), patch(false));
+        let t = fcs_read_std_text(&p, &conf).ok().unwrap();
+        assert_eq!(Version::FCS2_0, t.resolve(|_| ()).0.0.version());
+        // now that HEADER is valid, patching again does nothing
+        assert_eq!(Some(Some(Version::FCS2_0)), patch(false));
+        fs::remove_file(&p).unwrap();
+    }
+
+    #[test]
+    fn test_read_concatenated() {
+        let conf = ReadStdDatasetConfig::default();
+        let p = env::temp_dir().join("fireflow_concatenated.fcs");
+        let first = wide_fcs("FCS3.1", 3);
+        // junk between files, including a partial magic token
+        let mut bytes = first.clone();
+        bytes.extend_from_slice(b"\0\0FCS9\0");
+        let second_begin = bytes.len() as u64;
+        bytes.extend(wide_fcs("FCS2.0", 2));
+        fs::write(&p, bytes).unwrap();
+        assert_eq!(1, fcs_read_std_datasets(&p, &conf).len());
+        let res = fcs_read_std_datasets_concatenated(&p, &conf);
+        assert_eq!(
+            vec![0, second_begin],
+            res.iter().map(|(o, _)| *o).collect::<Vec<_>>()
+        );
+        let cores: Vec<_> = res
+            .into_iter()
+            .map(|(_, r)| r.ok().unwrap().resolve(|_| ()).0 .0)
+            .collect();
+        assert_eq!(3, cores[0].shortnames().len());
+        assert_eq!(2, cores[1].shortnames().len());
+        assert!(matches!(cores[1], AnyCore::FCS2_0(_)));
+        fs::remove_file(p).unwrap();
+    }
+
+    #[test]
+    fn test_read_chunked() {
+        // FL1 is 1,2,3 and FL2 is 256,512,1000
+        let p = path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join("fcs3_1_uint16_little.fcs");
+        let read = |conf: &ReadStdDatasetConfig| {
+            fcs_read_std_dataset(&p, conf)
+                .ok()
+                .map(|t| t.resolve(|_| ()).0.0)
+        };
+        let columns = |core: &AnyCoreDataset| -> Vec<Vec<f64>> {
+            core.shortnames()
+                .iter()
+                .map(|n| core.iter_column(n).ok().unwrap().collect())
+                .collect()
+        };
+        let mut conf = ReadStdDatasetConfig::default();
+        let full = columns(&read(&conf).unwrap());
+        conf.data.chunk_size = NonZeroUsize::new(2);
+        assert_eq!(full, columns(&read(&conf).unwrap()));
+
+        // drop the second event, which is in the first chunk
+        let parent = ParentFile::from_path(&p).unwrap();
+        conf.data.chunk_filter = ChunkFilterRef::new(|df: FCSDataFrame| {
+            let keep: Vec<_> = df
+                .iter_rows()
+                .enumerate()
+                .filter(|(_, r)| r[0] != 2.0)
+                .map(|(i, _)| i)
+                .collect();
+            df.take_rows(&keep)
+        })
+        .with_parent(parent.clone());
+        let filtered = read(&conf).unwrap();
+        assert_eq!(
+            vec![vec![1.0, 3.0], vec![256.0, 1000.0]],
+            columns(&filtered)
+        );
+
+        // filtered datasets are derived from the file they were read from
+        let prov = filtered.provenance().unwrap();
+        assert_eq!(Some("chunk_filter".into()), prov.operation);
+        assert_eq!(Some("fcs3_1_uint16_little.fcs".into()), prov.parent);
+        assert_eq!(Some(parent.hash), prov.parent_hash);
+        assert_eq!(
+            None,
+            read(&ReadStdDatasetConfig::default()).unwrap().provenance()
+        );
+
+        // filters can't change the columns
+        conf.data.chunk_filter =
+            ChunkFilterRef::new(|_| FCSDataFrame::new1(AnyFCSColumn::F64(vec![].into())));
+        assert!(read(&conf).is_none());
+    }
+
+    #[test]
+    fn test_read_chunked_time() {
+        let fl = F32Column::from(vec![1.0_f32; 7]);
+        let time = F32Column::from(vec![0.0_f32, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let df = FCSDataFrame::try_new(vec![fl.into(), time.into()])
+            .ok()
+            .unwrap();
+        let mut text = CoreTEXT3_1::example(2);
+        assert!(
+            text.set_temporal_at(1.into(), Timestep::default(), false)
+                .is_ok()
+        );
+        let dataset = text
+            .into_coredataset(df, Analysis::default(), Others::default())
+            .ok()
+            .unwrap();
+        let mut h = BufWriter::new(vec![]);
+        assert!(
+            dataset
+                .h_write_dataset(&mut h, &WriteConfig::default())
+                .is_ok()
+        );
+        let bs = h.into_inner().ok().unwrap();
+        let read = |conf: &ReadStdDatasetConfig| {
+            let res = read_std_dataset_from_bytes(&bs, conf).ok().unwrap();
+            let ((core, _), ws) = res.resolve(|ws| ws);
+            (core, ws)
+        };
+        let read_times = |conf: &ReadStdDatasetConfig| -> Vec<f64> {
+            let n = Shortname::new_unchecked("FL2");
+            let (core, ws) = read(conf);
+            assert!(ws.is_empty());
+            core.iter_column(&n).ok().unwrap().collect()
+        };
+
+        // keep only the first event passed to the filter each time
+        let mut conf = ReadStdDatasetConfig::default();
+        conf.standard.time_meas_pattern = Some("^FL2$".parse().ok().unwrap());
+        conf.data.chunk_size = NonZeroUsize::new(3);
+        conf.data.chunk_filter = ChunkFilterRef::new(|x: FCSDataFrame| x.slice_rows(0..1));
+        assert_eq!(vec![0.0, 3.0, 6.0], read_times(&conf));
+
+        // windows should not depend on where chunks start and end
+        conf.data.chunk_time_window = Some(2.0);
+        assert_eq!(vec![0.0, 2.0, 4.0, 6.0], read_times(&conf));
+        conf.data.chunk_size = NonZeroUsize::new(1);
+        assert_eq!(vec![0.0, 2.0, 4.0, 6.0], read_times(&conf));
+
+        // excluding a measurement before time should not change the windows
+        conf.standard.exclude_measurement_pattern = Some("^FL1$".parse().unwrap());
+        assert_eq!(vec![0.0, 2.0, 4.0, 6.0], read_times(&conf));
+
+        // without a time measurement, fall back to chunks of events
+        conf.standard.exclude_measurement_pattern = Some("^FL2$".parse().unwrap());
+        conf.data.chunk_size = NonZeroUsize::new(3);
+        let (nu_core, ws) = read(&conf);
+        assert_eq!(3, nu_core.as_data().nrows());
+        assert_eq!(1, ws.len());
+
+        // windows must be positive and finite
+        for w in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            conf.data.chunk_time_window = Some(w);
+            assert!(read_std_dataset_from_bytes(&bs, &conf).is_err());
+        }
+    }
+
+    #[test]
+    fn test_read_exclude() {
+        // FL1 is 1,2,3 and FL2 is 256,512,1000
+        let p = path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join("fcs3_1_uint16_little.fcs");
+        let pat: ExcludeMeasNamePattern = "^FL1$".parse().unwrap();
+
+        let mut text_conf = ReadStdTEXTConfig::default();
+        text_conf.standard.exclude_measurement_pattern = Some(pat.clone());
+        let text = fcs_read_std_text(&p, &text_conf)
+            .ok()
+            .map(|t| t.resolve(|_| ()).0.0)
+            .unwrap();
+        let names =
+            |xs: Vec<Shortname>| -> Vec<String> { xs.iter().map(|n| n.to_string()).collect() };
+        assert_eq!(vec!["FL2"], names(text.shortnames()));
+
+        let mut conf = ReadStdDatasetConfig::default();
+        conf.standard.exclude_measurement_pattern = Some(pat);
+        let core = fcs_read_std_dataset(&p, &conf)
+            .ok()
+            .map(|t| t.resolve(|_| ()).0.0)
+            .unwrap();
+        let shortnames = core.shortnames();
+        assert_eq!(vec!["FL2"], names(shortnames.clone()));
+        let col: Vec<f64> = core.iter_column(&shortnames[0]).ok().unwrap().collect();
+        assert_eq!(vec![256.0, 512.0, 1000.0], col);
+    }
+
+    #[test]
+    fn test_plan_write() {
+        let conf = ReadStdDatasetConfig::default();
+        let p = env::temp_dir().join("fireflow_plan_write.fcs");
+        fs::write(&p, wide_fcs("FCS3.1", 3)).unwrap();
+        let res = fcs_read_std_dataset(&p, &conf).ok().unwrap();
+        let ((core, _), _) = res.resolve(|_| ());
+        let wconf = WriteConfig::default();
+        let (plan, _) = core.plan_write(&wconf).ok().unwrap().resolve(|_| ());
+        let mut h = BufWriter::new(vec![]);
+        let AnyCore::FCS3_1(x) = &core else {
+            unreachable!()
+        };
+        assert!(x.h_write_dataset(&mut h, &wconf).is_ok());
+        let out = h.into_inner().ok().unwrap();
+        assert_eq!(out.len() as u64, plan.nbytes);
+        assert_eq!(58, plan.text.begin);
+        let text_end = (plan.text.begin + plan.text.length) as usize;
+        assert_eq!(text_end, plan.data.begin as usize);
+        // TEXT starts and ends with the delimiter
+        assert_eq!(out[58], out[text_end - 1]);
+        assert_eq!(24, plan.data.length);
+        assert_eq!(0, plan.analysis.length);
+        let data = &out[plan.data.begin as usize..];
+        assert_eq!(1.0, f32::from_le_bytes(data[..4].try_into().unwrap()));
+        assert!(plan
+            .keywords
+            .iter()
+            .any(|(k, v)| k == "$P3N" && v == "ch3"));
+        assert!(plan.rounding.iter().all(Option::is_none));
+        fs::remove_file(p).unwrap();
+    }
+
+    #[test]
+    fn test_set_data_tot() {
+        let p = env::temp_dir().join("fireflow_set_data_tot.fcs");
+        fs::write(&p, wide_fcs("FCS3.1", 2)).unwrap();
+        let res = fcs_read_std_dataset(&p, &ReadStdDatasetConfig::default());
+        let ((core, _), _) = res.ok().unwrap().resolve(|_| ());
+        let AnyCore::FCS3_1(mut x) = core else {
+            unreachable!()
+        };
+        let col = |v: f32| AnyFCSColumn::from(F32Column::from(vec![v; 5]));
+        let df = FCSDataFrame::try_new(vec![col(1.0), col(2.0)]).ok().unwrap();
+        assert!(x.set_data(df).is_ok());
+        let wconf = WriteConfig::default();
+        let (plan, _) = AnyCore::FCS3_1(x).plan_write(&wconf).ok().unwrap().resolve(|_| ());
+        assert!(plan.keywords.iter().any(|(k, v)| k == "$TOT" && v == "5"));
+        fs::remove_file(p).unwrap();
+    }
+
+    #[test]
+    fn test_control_chars_round_trip() {
+        let p = env::temp_dir().join("fireflow_control_chars.fcs");
+        let out = env::temp_dir().join("fireflow_control_chars_out.fcs");
+        fs::write(&p, wide_fcs("FCS3.1", 2)).unwrap();
+        let mut conf = ReadStdDatasetConfig::default();
+        let res = fcs_read_std_dataset(&p, &conf).ok().unwrap();
+        let ((core, _), _) = res.resolve(|_| ());
+        let AnyCore::FCS3_1(mut x) = core else {
+            unreachable!()
+        };
+        let key: NonStdKey = "NOTE".parse().unwrap();
+        // the default delimiter is also a control character
+        let value = "line1\nline2\x1eend";
+        x.metaroot
+            .nonstandard_keywords
+            .insert(key.clone(), value.into());
+        let write = |wconf: &WriteConfig| {
+            let mut h = BufWriter::new(File::create(&out).unwrap());
+            let (_, ws) = x.h_write_dataset(&mut h, wconf).ok().unwrap().resolve(|ws| ws.len());
+            ws
+        };
+        let read = |c: &ReadStdDatasetConfig| {
+            let ((c2, _), ws) = fcs_read_std_dataset(&out, c)
+                .ok()
+                .unwrap()
+                .resolve(|ws| ws.len());
+            let AnyCore::FCS3_1(y) = c2 else {
+                unreachable!()
+            };
+            (y.metaroot.nonstandard_keywords.get(&key).cloned(), ws)
+        };
+
+        // preserved values round trip
+        assert_eq!(0, write(&WriteConfig::default()));
+        assert_eq!((Some(value.into()), 0), read(&conf));
+        conf.raw.control_chars = ControlCharPolicy::Strip;
+        assert_eq!((Some("line1line2end".into()), 1), read(&conf));
+
+        // escaped values are sanitized with a warning
+        let wconf = WriteConfig {
+            control_chars: ControlCharPolicy::Escape,
+            ..WriteConfig::default()
+        };
+        assert_eq!(1, write(&wconf));
+        conf.raw.control_chars = ControlCharPolicy::Preserve;
+        assert_eq!((Some("line1\\nline2\\x1Eend".into()), 0), read(&conf));
+
+        fs::remove_file(p).unwrap();
+        fs::remove_file(out).unwrap();
+    }
+
+    #[test]
+    fn test_common_metadata() {
+        let t0 = NaiveTime::from_hms_milli_opt(10, 0, 0, 123).unwrap();
+        let t1 = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let d = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let tr = Trigger {
+            measurement: Shortname::new_unchecked("FL1"),
+            threshold: 5,
+        };
+        let cores: [AnyCoreTEXT; 4] = [
+            CoreTEXT2_0::example(1).into(),
+            CoreTEXT3_0::example(1).into(),
+            CoreTEXT3_1::example(1).into(),
+            CoreTEXT3_2::example(1).into(),
+        ];
+        for mut c in cores {
+            assert_eq!(1, c.par().0);
+            assert_eq!(vec![Shortname::new_unchecked("FL1")], c.shortnames());
+            c.set_cyt(Cyt::from("Symphony".to_string()));
+            assert_eq!("Symphony", c.cyt().unwrap().to_string());
+            // 2.0 has no subsecond precision, 3.0 has 1/60, and 3.1+ has 1/100
+            assert!(c.set_btim(Some(t0)).is_ok());
+            let b = c.btim().unwrap();
+            assert!(t0 - b <= TimeDelta::milliseconds(123));
+            assert!(c.set_etim(Some(t1)).is_ok());
+            assert!(c.set_date(Some(d)).is_err());
+            assert!(c.set_etim(None).is_ok());
+            assert!(c.set_date(Some(d)).is_ok());
+            assert_eq!(Some(d), c.date());
+            assert!(c.trigger().is_none());
+            assert!(c.set_trigger(Some(tr.clone())).is_ok());
+            assert!(c.trigger() == Some(&tr));
+        }
+    }
+
+    #[test]
+    fn test_remove_measurement_and_links() {
+        let n = |x: &str| Shortname::new_unchecked(x);
+        let mut c = CoreTEXT3_2::example(3);
+        let spill = Spillover::try_new(vec![n("FL1"), n("FL2")], DMatrix::identity(2, 2));
+        assert!(c.set_spillover(spill.ok()).is_ok());
+        let us = UnstainedCenters::try_from(vec![(n("FL1"), 1.0), (n("FL3"), 2.0)]);
+        assert!(c.set_unstained_centers(us.ok()).is_ok());
+        let tr = Trigger {
+            measurement: n("FL1"),
+            threshold: 5,
+        };
+        assert!(c.set_trigger(Some(tr)).is_ok());
+        let region = |i: usize| {
+            Region::Univariate(UnivariateRegion {
+                gate: UniGate {
+                    lower: 0.into(),
+                    upper: 1.into(),
+                },
+                index: PrefixedMeasIndex(i.into()),
+            })
+        };
+        let regions = [(RegionIndex::from(0), region(0)), (1.into(), region(2))].into();
+        let gating = "R1".parse().ok();
+        let ag = AppliedGates3_2::try_new(gating, regions).ok().unwrap();
+        assert!(c.set_applied_gates_3_2(ag).is_ok());
+
+        // the old API refuses to remove anything with links
+        assert!(c.remove_measurement_by_name(&n("FL1")).is_err());
+        assert!(c.remove_measurement_and_links(&n("nope")).is_err());
+        let r = c.remove_measurement_and_links(&n("FL1")).ok().unwrap();
+        assert_eq!(MeasIndex::from(0), r.index);
+        assert_eq!(vec![n("FL2"), n("FL3")], c.all_shortnames());
+        assert!(c.spillover().is_none());
+        assert!(c.trigger().is_none());
+        let removed: Vec<_> = r.links.removed.keys().map(String::as_str).collect();
+        assert_eq!(vec!["$GATING", "$R1I", "$R1W", "$SPILLOVER", "$TR"], removed);
+        assert_eq!("1,FL3,2", r.links.changed["$UNSTAINEDCENTERS"].new);
+        assert_eq!("P2", r.links.changed["$R2I"].new);
+        assert!(r.links.added.is_empty());
+    }
+
+    #[test]
+    fn test_remove_measurement_and_links_comp() {
+        let mut c = CoreTEXT3_0::example(3);
+        let comp = Compensation::try_from(DMatrix::identity(3, 3)).ok();
+        assert!(c.set_compensation(comp).is_ok());
+        let r = c
+            .remove_measurement_and_links(&Shortname::new_unchecked("FL2"))
+            .ok()
+            .unwrap();
+        assert_eq!("2,1,0,0,1", r.links.changed["$COMP"].new);
+        assert_eq!(2, c.par().0);
+    }
+
+    #[test]
+    fn test_display_names() {
+        let mut c = CoreTEXT3_1::example(3);
+        let ls = vec![Some(Longname("CD4".into())), None, Some(Longname("FL3".into()))];
+        assert!(c.set_meas(ls).is_ok());
+        let mut conf = DisplayNameConfig::default();
+        assert_eq!(vec!["CD4", "FL2", "FL3"], c.display_names(&conf));
+        conf.format = DisplayNameFormat::Both;
+        assert_eq!(vec!["FL1 :: CD4", "FL2", "FL3"], c.display_names(&conf));
+        assert_eq!(Some("FL2".into()), c.display_name(1.into(), &conf));
+        assert_eq!(None, c.display_name(3.into(), &conf));
+    }
+
+    #[test]
+    fn test_example_round_trip() {
+        let conf = ReadStdDatasetConfig::default();
+        let df = || {
+            let c = F32Column::from(vec![1.0_f32, 2.0, 3.0]);
+            FCSDataFrame::try_new(vec![c.clone().into(), c.into()])
+                .ok()
+                .unwrap()
+        };
+        let check = |core: AnyCoreDataset| {
+            let mut h = BufWriter::new(vec![]);
+            assert!(
+                core.h_write_dataset(&mut h, &WriteConfig::default())
+                    .is_ok()
+            );
+            let bs = h.into_inner().ok().unwrap();
+            let res = read_std_dataset_from_bytes(&bs, &conf).ok().unwrap();
+            let ((read, _), ws) = res.resolve(|ws| ws.len());
+            assert_eq!(0, ws);
+            assert_eq!(core.version(), read.version());
+            assert!(core.shortnames() == read.shortnames());
+            assert!(core.as_data() == read.as_data());
+        };
+        macro_rules! go {
+            ($t:ident) => {
+                check(
+                    $t::example(2)
+                        .into_coredataset(df(), Analysis::default(), Others::default())
+                        .ok()
+                        .unwrap()
+                        .into(),
+                )
+            };
+        }
+        go!(CoreTEXT2_0);
+        go!(CoreTEXT3_0);
+        go!(CoreTEXT3_1);
+        go!(CoreTEXT3_2);
+    }
+
+    #[test]
+    fn test_push_optical_like() {
+        let filters = |core: &CoreTEXT3_1| -> Vec<Option<String>> {
+            core.optical_opt::<Filter>()
+                .map(|e| e.0.non_center().flatten().map(|f| f.to_string()))
+                .collect()
+        };
+        let name = |s: &str| AlwaysValue(Shortname::new_unchecked(s));
+        let r = Range::from(1024_u64);
+        let mut core = CoreTEXT3_1::example(3);
+        let fs = [Some("red"), None, Some("blue")]
+            .map(|f| NonCenterElement(Element::NonCenter(f.map(|x| Filter(x.into())))))
+            .into();
+        assert!(core.set_optical(fs).is_ok());
+        let ls = [Some("template"), None, None]
+            .map(|l| NonCenterElement(Element::NonCenter(l.map(|x| Longname(x.into())))))
+            .into();
+        assert!(core.set_optical(ls).is_ok());
+        // make FL2 the time measurement so FL3 is to the right of the center
+        assert!(
+            core.set_temporal_at(1.into(), Timestep::default(), false)
+                .is_ok()
+        );
+
+        // $PnS is not copied
+        assert!(
+            core.push_optical_like(name("copy1"), 0.into(), r.clone(), false)
+                .is_ok()
+        );
+        assert!(
+            core.push_optical_like(name("copy3"), 2.into(), r.clone(), false)
+                .is_ok()
+        );
+        assert_eq!(
+            vec![
+                Some("red".into()),
+                None,
+                Some("blue".into()),
+                Some("red".into()),
+                Some("blue".into())
+            ],
+            filters(&core)
+        );
+        let longnames: Vec<_> = core
+            .optical_opt::<Longname>()
+            .map(|e| e.0.non_center().flatten().is_some())
+            .collect();
+        assert_eq!(vec![true, false, false, false, false], longnames);
+
+        // time, out of bounds, and duplicate names are errors
+        assert!(
+            core.push_optical_like(name("t"), 1.into(), r.clone(), false)
+                .is_err()
+        );
+        assert!(
+            core.push_optical_like(name("oob"), 5.into(), r.clone(), false)
+                .is_err()
+        );
+        assert!(
+            core.push_optical_like(name("FL1"), 0.into(), r.clone(), false)
+                .is_err()
+        );
+        assert_eq!(5, core.par().0);
+
+        // datasets also get the new column
+        let c = F32Column::from(vec![1.0_f32, 2.0]);
+        let df = FCSDataFrame::try_new(vec![c.clone().into(); 5])
+            .ok()
+            .unwrap();
+        let mut dataset = core
+            .into_coredataset(df, Analysis::default(), Others::default())
+            .ok()
+            .unwrap();
+        assert!(
+            dataset
+                .push_optical_like(name("copy4"), 3.into(), c.into(), r, false)
+                .is_ok()
+        );
+        assert_eq!(6, dataset.data().ncols());
+    }
+
+    #[test]
+    fn test_write_provenance() {
+        let conf = ReadStdDatasetConfig::default();
+        let c = F32Column::from(vec![1.0_f32]);
+        let df = FCSDataFrame::try_new(vec![c.into()]).ok().unwrap();
+        let mut core = CoreTEXT3_1::example(1)
+            .into_coredataset(df, Analysis::default(), Others::default())
+            .ok()
+            .unwrap();
+        let write_read = |x: &CoreDataset3_1, wconf: &WriteConfig| {
+            let mut h = BufWriter::new(vec![]);
+            assert!(x.h_write_dataset(&mut h, wconf).is_ok());
+            let bs = h.into_inner().ok().unwrap();
+            let res = read_std_dataset_from_bytes(&bs, &conf).ok().unwrap();
+            res.resolve(|_| ()).0.0.provenance()
+        };
+        let software = Some(format!("fireflow {}", env!("CARGO_PKG_VERSION")));
+
+        let wconf = WriteConfig {
+            record_provenance: true,
+            ..WriteConfig::default()
+        };
+
+        // nothing is recorded by default
+        assert_eq!(None, write_read(&core, &WriteConfig::default()));
+
+        // software is recorded if asked
+        let p0 = write_read(&core, &wconf).unwrap();
+        assert_eq!(software, p0.software);
+        assert_eq!(None, p0.operation);
+
+        // ...and replaces whatever was there before, leaving the rest
+        core.set_provenance(Some(Provenance {
+            operation: Some("subsample".into()),
+            software: Some("other 1.0".into()),
+            ..Provenance::default()
+        }));
+        let p1 = write_read(&core, &wconf).unwrap();
+        assert_eq!(software, p1.software);
+        assert_eq!(Some("subsample".into()), p1.operation);
+
+        // existing provenance is written as-is by default
+        assert_eq!(
+            Some("other 1.0".into()),
+            write_read(&core, &WriteConfig::default()).unwrap().software
+        );
+    }
+
+    #[test]
+    fn test_derived_provenance() {
+        let p = path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join("fcs3_1_float32.fcs");
+        let parent = ParentFile::from_path(&p).unwrap();
+        let conf = ReadStdDatasetConfig::default();
+        let time = F32Column::from(vec![0.0_f32, 1.0, 2.0, 3.0]);
+        let fl = F32Column::from(vec![1.0_f32, 5.0, 1.0, 5.0]);
+        let df = FCSDataFrame::try_new(vec![time.into(), fl.into()])
+            .ok()
+            .unwrap();
+        let mut text = CoreTEXT3_1::example(2);
+        assert!(
+            text.set_temporal_at(0.into(), Timestep::default(), false)
+                .is_ok()
+        );
+        let core = text
+            .into_coredataset(df, Analysis::default(), Others::default())
+            .ok()
+            .unwrap();
+        let write_read = |x: &CoreDataset3_1| {
+            let mut h = BufWriter::new(vec![]);
+            assert!(x.h_write_dataset(&mut h, &WriteConfig::default()).is_ok());
+            let bs = h.into_inner().ok().unwrap();
+            let res = read_std_dataset_from_bytes(&bs, &conf).ok().unwrap();
+            res.resolve(|_| ()).0.0.provenance().unwrap()
+        };
+        let assert_derived = |x: &CoreDataset3_1, op: &str| {
+            let prov = write_read(x);
+            assert_eq!(Some(op.into()), prov.operation);
+            assert_eq!(Some("fcs3_1_float32.fcs".into()), prov.parent);
+            assert_eq!(Some(parent.hash.clone()), prov.parent_hash);
+        };
+
+        let xs = core.split_by_time(2.0, Some(&parent)).unwrap();
+        assert_eq!(2, xs.len());
+        assert_derived(&xs[0], "split_by_time interval=2 slice=0");
+        assert_derived(&xs[1], "split_by_time interval=2 slice=1");
+
+        let rules = [(Shortname::new_unchecked("FL2"), Threshold(5.0))];
+        let ys = core.demultiplex(&rules, Some(&parent)).ok().unwrap();
+        assert_derived(&ys[0], "demultiplex channel=FL2");
+
+        // operation is still recorded if the parent is unknown
+        let zs = core.demultiplex(&rules, None).ok().unwrap();
+        let prov = write_read(&zs[0]);
+        assert_eq!(Some("demultiplex channel=FL2".into()), prov.operation);
+        assert_eq!(None, prov.parent);
+        assert_eq!(None, prov.parent_hash);
+    }
+
+    #[test]
+    fn test_grouped_keywords() {
+        let mut c = CoreTEXT3_1::example(2);
+        let k = |s: &str| s.parse::<NonStdKey>().unwrap();
+        let ms = vec![
+            [(k("P1FOO"), "x".into())].into_iter().collect(),
+            HashMap::new(),
+        ];
+        assert!(c.set_meas_nonstandard(ms).is_ok());
+        c.metaroot.nonstandard_keywords.insert(k("NOTE"), "y".into());
+        let g = c.grouped_keywords();
+        assert_eq!(Some("y"), g.keywords.get("NOTE").map(String::as_str));
+        assert_eq!(Some("FL1"), g.keywords.get("$P1N").map(String::as_str));
+        assert!(!g.keywords.contains_key("P1FOO"));
+        assert_eq!(2, g.measurements.len());
+        assert_eq!(Some("x"), g.measurements[0].get("P1FOO").map(String::as_str));
+        assert!(g.measurements[1].is_empty());
+        // together these have the same keywords as the flat version
+        let n = g.keywords.len() + g.measurements.iter().map(HashMap::len).sum::<usize>();
+        assert_eq!(c.standard_keywords(false, false, false, false).len(), n);
+    }
+
+    #[test]
+    fn test_layout_templates() {
+        let l0 = DataLayout2_0::uint16_big_endian(2, 1024);
+        assert_eq!(("$BYTEORD".into(), "2,1".into()), l0.byteord_keyword());
+        assert_eq!(
+            [("$P2B".into(), "16".into()), ("$P2R".into(), "1024".into())],
+            l0.req_meas_keywords()[1]
+        );
+        // clamped to 16 bits
+        let l1 = DataLayout3_0::uint16_little_endian(1, 100000);
+        assert_eq!("65536", l1.ranges()[0].to_string());
+        let l2 = DataLayout3_1::standard_float32(3);
+        assert!(l2.datatype() == AlphaNumType::Float);
+        assert_eq!(("$BYTEORD".into(), "1,2,3,4".into()), l2.byteord_keyword());
+        let mut c = CoreTEXT3_2::example(3);
+        assert!(c.set_layout(DataLayout3_2::standard_float64(3)).is_ok());
+        assert!(c.set_layout(DataLayout3_2::standard_float64(2)).is_err());
+        assert!(c.layout().datatype() == AlphaNumType::Double);
+    }
 }
@@ -0,0 +1,331 @@
+//! Infer what kind of signal each measurement holds.
+//!
+//! Most analyses only care about some of the measurements; compensation only
+//! makes sense for fluorescence, and plots or summary statistics usually
+//! exclude the time channel and any bookkeeping channels (event counters,
+//! classifiers, etc). FCS 3.2 states this directly with $PnTYPE, but other
+//! versions only have $PnN, which by convention (and usually only by
+//! convention) starts with something like "FSC" or "SSC" for scatter.
+//!
+//! This is a best-effort guess meant to pick sensible defaults; it will never
+//! change any keywords.
+//!
+//! This also includes the common convention for naming measurements in plots,
+//! which most downstream programs otherwise implement themselves.
+
+use crate::text::keywords::OpticalType;
+use crate::validated::shortname::Shortname;
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// The kind of signal held by a measurement.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ChannelKind {
+    /// Forward, side, or back scatter
+    Scatter,
+
+    /// Fluorescence (raw or unmixed) or mass (for mass cytometry)
+    Fluorescence,
+
+    /// The time measurement
+    Time,
+
+    /// Anything else (event counters, classifiers, electronic volume, etc)
+    Other,
+}
+
+/// Prefixes of $PnN (before the first non-letter) for scatter channels.
+const SCATTER_PREFIXES: [&str; 7] = ["FSC", "SSC", "FS", "SS", "BSC", "VSSC", "SCATTER"];
+
+/// Prefixes of $PnN (before the first non-letter) for non-signal channels.
+const OTHER_PREFIXES: [&str; 10] = [
+    "EVENT",
+    "EVENTS",
+    "EVENTCOUNT",
+    "WIDTH",
+    "CENTER",
+    "OFFSET",
+    "RESIDUAL",
+    "INDEX",
+    "SAMPLEID",
+    "BEADDIST",
+];
+
+impl ChannelKind {
+    /// Infer the kind of an optical (non-time) measurement.
+    ///
+    /// $PnTYPE takes precedence if given. Otherwise, guess from $PnN using
+    /// its leading letters, so "FSC-A", "SS Log", and "Event_length" will be
+    /// scatter, scatter, and other respectively. Names which don't look like
+    /// scatter, time, or bookkeeping channels are assumed to be fluorescence
+    /// since there are far too many dye/marker/detector names to match.
+    pub fn infer(name: &Shortname, optical_type: Option<&OpticalType>) -> Self {
+        match optical_type {
+            Some(OpticalType::ForwardScatter | OpticalType::SideScatter) => Self::Scatter,
+            Some(
+                OpticalType::RawFluorescence | OpticalType::UnmixedFluorescence | OpticalType::Mass,
+            ) => Self::Fluorescence,
+            Some(
+                OpticalType::ElectronicVolume
+                | OpticalType::Classification
+                | OpticalType::Index
+                | OpticalType::Other(_),
+            ) => Self::Other,
+            None => Self::infer_from_name(name.as_ref()),
+        }
+    }
+
+    fn infer_from_name(name: &str) -> Self {
+        let n = name.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let prefix = name[..n].to_ascii_uppercase();
+        let rest = &name[n..];
+        if prefix == "TIME" {
+            Self::Time
+        } else if SCATTER_PREFIXES.contains(&prefix.as_str()) {
+            Self::Scatter
+        } else if OTHER_PREFIXES.contains(&prefix.as_str())
+            // "Event_length", "Cell_length", etc
+            || (prefix == "CELL" && rest.starts_with(['_', ' ', '-']))
+        {
+            Self::Other
+        } else {
+            Self::Fluorescence
+        }
+    }
+}
+
+impl fmt::Display for ChannelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = match self {
+            Self::Scatter => "scatter",
+            Self::Fluorescence => "fluorescence",
+            Self::Time => "time",
+            Self::Other => "other",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Measurement names grouped by the kind of signal they hold.
+///
+/// Each group is in the same order as the measurements.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct ChannelGroups {
+    pub scatter: Vec<Shortname>,
+    pub fluorescence: Vec<Shortname>,
+    pub time: Vec<Shortname>,
+    pub other: Vec<Shortname>,
+}
+
+impl ChannelGroups {
+    pub(crate) fn from_kinds<I>(xs: I) -> Self
+    where
+        I: IntoIterator<Item = (Shortname, ChannelKind)>,
+    {
+        let mut ret = Self::default();
+        for (n, k) in xs {
+            match k {
+                ChannelKind::Scatter => ret.scatter.push(n),
+                ChannelKind::Fluorescence => ret.fluorescence.push(n),
+                ChannelKind::Time => ret.time.push(n),
+                ChannelKind::Other => ret.other.push(n),
+            }
+        }
+        ret
+    }
+
+    /// Return the kind of the given measurement if it is in any group.
+    pub fn kind_of(&self, name: &Shortname) -> Option<ChannelKind> {
+        [
+            (&self.scatter, ChannelKind::Scatter),
+            (&self.fluorescence, ChannelKind::Fluorescence),
+            (&self.time, ChannelKind::Time),
+            (&self.other, ChannelKind::Other),
+        ]
+        .into_iter()
+        .find(|(xs, _)| xs.contains(name))
+        .map(|(_, k)| k)
+    }
+
+    /// Measurements which should be compensated or unmixed by default.
+    pub fn compensable(&self) -> &[Shortname] {
+        &self.fluorescence[..]
+    }
+
+    /// Measurements which should be plotted or summarized by default.
+    ///
+    /// This is scatter followed by fluorescence.
+    pub fn plottable(&self) -> Vec<Shortname> {
+        self.scatter
+            .iter()
+            .chain(&self.fluorescence)
+            .cloned()
+            .collect()
+    }
+}
+
+/// How to name a measurement for display, ie in plot axes or legends.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum DisplayNameFormat {
+    /// $PnS if present, otherwise $PnN
+    #[default]
+    Longname,
+
+    /// $PnN only
+    Shortname,
+
+    /// $PnN followed by $PnS if present, ie "FL1-A :: CD4"
+    Both,
+}
+
+impl std::str::FromStr for DisplayNameFormat {
+    type Err = ParseDisplayNameFormatError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "longname" => Ok(Self::Longname),
+            "shortname" => Ok(Self::Shortname),
+            "both" => Ok(Self::Both),
+            _ => Err(ParseDisplayNameFormatError),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseDisplayNameFormatError;
+
+impl fmt::Display for ParseDisplayNameFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str("must be one of 'longname', 'shortname', or 'both'")
+    }
+}
+
+/// Options for naming measurements for display.
+#[derive(Clone)]
+pub struct DisplayNameConfig {
+    /// Which of $PnN and $PnS to use
+    pub format: DisplayNameFormat,
+
+    /// Text between $PnN and $PnS when using [`DisplayNameFormat::Both`]
+    pub separator: String,
+}
+
+impl Default for DisplayNameConfig {
+    fn default() -> Self {
+        Self {
+            format: DisplayNameFormat::default(),
+            separator: " :: ".into(),
+        }
+    }
+}
+
+impl DisplayNameConfig {
+    /// Return the display name for a measurement given $PnN and $PnS.
+    ///
+    /// $PnS will be treated as missing if it is blank or identical to $PnN,
+    /// which avoids names like "FSC-A :: FSC-A".
+    pub fn name(&self, shortname: &Shortname, longname: Option<&str>) -> String {
+        let n = shortname.as_ref();
+        let long = longname.map(str::trim).filter(|s| !s.is_empty() && *s != n);
+        match (self.format, long) {
+            (DisplayNameFormat::Longname, Some(s)) => s.into(),
+            (DisplayNameFormat::Both, Some(s)) => format!("{n}{}{s}", self.separator),
+            _ => n.into(),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::{DisplayNameFormat, ParseDisplayNameFormatError};
+    use crate::python::macros::{impl_from_py_via_fromstr, impl_value_err};
+
+    impl_from_py_via_fromstr!(DisplayNameFormat);
+    impl_value_err!(ParseDisplayNameFormatError);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infer(name: &str) -> ChannelKind {
+        ChannelKind::infer(&Shortname::new_unchecked(name), None)
+    }
+
+    #[test]
+    fn test_infer_from_name() {
+        assert_eq!(ChannelKind::Scatter, infer("FSC-A"));
+        assert_eq!(ChannelKind::Scatter, infer("ssc-h"));
+        assert_eq!(ChannelKind::Scatter, infer("FS Lin"));
+        assert_eq!(ChannelKind::Scatter, infer("BSC-A"));
+        assert_eq!(ChannelKind::Time, infer("Time"));
+        assert_eq!(ChannelKind::Time, infer("TIME"));
+        assert_eq!(ChannelKind::Other, infer("Event_length"));
+        assert_eq!(ChannelKind::Other, infer("Cell_length"));
+        assert_eq!(ChannelKind::Other, infer("Width"));
+        assert_eq!(ChannelKind::Fluorescence, infer("FL1-H"));
+        assert_eq!(ChannelKind::Fluorescence, infer("PE-Cy7-A"));
+        assert_eq!(ChannelKind::Fluorescence, infer("B530-A"));
+        assert_eq!(ChannelKind::Fluorescence, infer("Sm152Di"));
+        // not a scatter prefix even though it starts with one
+        assert_eq!(ChannelKind::Fluorescence, infer("SSEA4"));
+        assert_eq!(ChannelKind::Fluorescence, infer("Cells"));
+    }
+
+    #[test]
+    fn test_infer_from_type() {
+        let n = Shortname::new_unchecked("FSC-A");
+        let f = |t| ChannelKind::infer(&n, Some(&t));
+        assert_eq!(ChannelKind::Fluorescence, f(OpticalType::RawFluorescence));
+        assert_eq!(ChannelKind::Fluorescence, f(OpticalType::Mass));
+        assert_eq!(ChannelKind::Scatter, f(OpticalType::SideScatter));
+        assert_eq!(ChannelKind::Other, f(OpticalType::Index));
+    }
+
+    #[test]
+    fn test_display_name() {
+        let n = Shortname::new_unchecked("FL1-A");
+        let mut conf = DisplayNameConfig::default();
+        assert_eq!("CD4", conf.name(&n, Some("CD4")));
+        assert_eq!("FL1-A", conf.name(&n, None));
+        assert_eq!("FL1-A", conf.name(&n, Some("  ")));
+        conf.format = DisplayNameFormat::Both;
+        assert_eq!("FL1-A :: CD4", conf.name(&n, Some("CD4")));
+        assert_eq!("FL1-A", conf.name(&n, Some("FL1-A")));
+        conf.separator = " / ".into();
+        assert_eq!("FL1-A / CD4", conf.name(&n, Some("CD4")));
+        conf.format = DisplayNameFormat::Shortname;
+        assert_eq!("FL1-A", conf.name(&n, Some("CD4")));
+    }
+
+    #[test]
+    fn test_groups() {
+        let n = Shortname::new_unchecked;
+        let gs = ChannelGroups::from_kinds([
+            (n("FSC-A"), ChannelKind::Scatter),
+            (n("FL1-A"), ChannelKind::Fluorescence),
+            (n("Time"), ChannelKind::Time),
+            (n("SSC-A"), ChannelKind::Scatter),
+            (n("FL2-A"), ChannelKind::Fluorescence),
+        ]);
+        assert_eq!(&[n("FL1-A"), n("FL2-A")], gs.compensable());
+        assert_eq!(
+            vec![n("FSC-A"), n("SSC-A"), n("FL1-A"), n("FL2-A")],
+            gs.plottable()
+        );
+        assert_eq!(Some(ChannelKind::Time), gs.kind_of(&n("Time")));
+        assert_eq!(None, gs.kind_of(&n("FL3-A")));
+    }
+}
@@ -0,0 +1,291 @@
+//! Compatibility checks against third-party software.
+//!
+//! Only limitations which can be traced to the source code or documentation
+//! of each tool are encoded here (see [`Software::limits`]), so a file which
+//! passes may still fail to open. The intent is to flag the usual suspects
+//! (newer versions, wide integers, mixed datatypes) and suggest a version to
+//! convert to.
+//!
+//! For now this only covers flowCore, whose reader is open source. Other
+//! tools (ie FlowJo, FCS Express) will be added once their limits can be cited
+//! the same way; until then they are deliberately left out rather than
+//! guessed.
+
+use crate::header::Version;
+use crate::text::byteord::BitsOrChars;
+use crate::text::index::MeasIndex;
+use crate::text::keywords::AlphaNumType;
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Third-party software which may be checked for compatibility
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Software {
+    FlowCore,
+}
+
+/// Known limitations of a given software
+struct Limits {
+    /// The newest version which can be read
+    max_version: Version,
+
+    /// The widest $PnB which can be read for integer measurements
+    max_int_bits: u8,
+
+    /// True if $PnDATATYPE may differ from $DATATYPE (3.2 only)
+    mixed: bool,
+}
+
+impl Software {
+    pub const ALL: [Self; 1] = [Self::FlowCore];
+
+    /// Return the known limitations of this software.
+    ///
+    /// Each limit must cite where it comes from.
+    fn limits(self) -> Limits {
+        match self {
+            // flowCore (Bioconductor), R/IO.R:
+            // - readFCSheader stops unless the version is FCS2.0, FCS3.0, or
+            //   FCS3.1
+            // - readFCSdata reads integers with readBin(what = "integer"),
+            //   and R integers are 32-bit
+            // - mixed datatypes only exist in 3.2, which is rejected above
+            Self::FlowCore => Limits {
+                max_version: Version::FCS3_1,
+                max_int_bits: 32,
+                mixed: false,
+            },
+        }
+    }
+}
+
+impl FromStr for Software {
+    type Err = SoftwareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "flowcore" => Ok(Self::FlowCore),
+            _ => Err(SoftwareError),
+        }
+    }
+}
+
+impl fmt::Display for Software {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = match self {
+            Self::FlowCore => "flowcore",
+        };
+        f.write_str(s)
+    }
+}
+
+pub struct SoftwareError;
+
+impl fmt::Display for SoftwareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let xs: Vec<_> = Software::ALL.iter().map(|x| x.to_string()).collect();
+        write!(f, "software must be one of {}", xs.join(", "))
+    }
+}
+
+/// A feature of a dataset which may prevent it from being read
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum CompatIssue {
+    /// Version is newer than what the software can read
+    Version { version: Version, max: Version },
+
+    /// $PnB of an integer measurement is wider than what the software can read
+    WideInteger { index: MeasIndex, bits: u8, max: u8 },
+
+    /// Any $PnDATATYPE differs from $DATATYPE
+    MixedDatatypes,
+}
+
+impl fmt::Display for CompatIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Version { version, max } => {
+                write!(f, "{version} is newer than the latest supported ({max})")
+            }
+            Self::WideInteger { index, bits, max } => write!(
+                f,
+                "$P{index}B is {bits} bits but integers wider than {max} bits are not supported"
+            ),
+            Self::MixedDatatypes => f.write_str("mixed $PnDATATYPE values are not supported"),
+        }
+    }
+}
+
+/// Result of checking a dataset against one software
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CompatReport {
+    /// The software which was checked
+    pub software: Software,
+
+    /// Version of the dataset which was checked
+    pub version: Version,
+
+    /// Problems which will likely prevent the file from opening
+    pub issues: Vec<CompatIssue>,
+
+    /// Version to convert to which should maximize compatibility.
+    ///
+    /// This is None if the current version is already the best choice. Note
+    /// that converting will not fix issues with the data layout; in
+    /// particular, 3.2 files with mixed datatypes cannot be converted to older
+    /// versions without first making the layout uniform.
+    pub recommended_version: Option<Version>,
+}
+
+impl CompatReport {
+    /// Check a dataset given its version, datatypes, and widths.
+    ///
+    /// `datatype` is $DATATYPE and `datatypes` has the datatype of each
+    /// measurement (which will only differ from $DATATYPE in 3.2). `widths`
+    /// has $PnB for each measurement in the same order, or None if variable.
+    pub(crate) fn new(
+        software: Software,
+        version: Version,
+        datatype: AlphaNumType,
+        datatypes: &[AlphaNumType],
+        widths: &[Option<BitsOrChars>],
+    ) -> Self {
+        let limits = software.limits();
+        let mut issues = vec![];
+        if version > limits.max_version {
+            issues.push(CompatIssue::Version {
+                version,
+                max: limits.max_version,
+            });
+        }
+        for (i, (dt, w)) in datatypes.iter().zip(widths).enumerate() {
+            let wide = w.map(u8::from).filter(|b| *b > limits.max_int_bits);
+            if let (AlphaNumType::Integer, Some(b)) = (dt, wide) {
+                issues.push(CompatIssue::WideInteger {
+                    index: i.into(),
+                    bits: b,
+                    max: limits.max_int_bits,
+                });
+            }
+        }
+        if !limits.mixed && datatypes.iter().any(|dt| *dt != datatype) {
+            issues.push(CompatIssue::MixedDatatypes);
+        }
+        let recommended_version = (version > limits.max_version).then_some(limits.max_version);
+        Self {
+            software,
+            version,
+            issues,
+            recommended_version,
+        }
+    }
+
+    /// Return true if no issues were found.
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = if self.is_compatible() {
+            "likely compatible"
+        } else {
+            "likely incompatible"
+        };
+        write!(f, "{}: {s}", self.software)?;
+        for i in &self.issues {
+            write!(f, "\n  - {i}")?;
+        }
+        if let Some(v) = self.recommended_version {
+            write!(f, "\n  suggestion: convert to {v}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::byteord::Bytes::{self, *};
+    use AlphaNumType::*;
+
+    fn bits(x: Bytes) -> Option<BitsOrChars> {
+        Some(x.into())
+    }
+
+    #[test]
+    fn test_compatible() {
+        for s in Software::ALL {
+            for (dt, w) in [(Integer, B4), (Float, B4), (Double, B8), (Ascii, B1)] {
+                let r = CompatReport::new(s, Version::FCS3_1, dt, &[dt, dt], &[bits(w), bits(w)]);
+                assert!(r.is_compatible());
+                assert_eq!(r.recommended_version, None);
+            }
+            let r = CompatReport::new(s, Version::FCS3_1, Ascii, &[Ascii], &[None]);
+            assert!(r.is_compatible());
+        }
+    }
+
+    #[test]
+    fn test_wide_integer() {
+        let r = CompatReport::new(
+            Software::FlowCore,
+            Version::FCS3_1,
+            Integer,
+            &[Integer, Integer],
+            &[bits(B2), bits(B8)],
+        );
+        assert_eq!(
+            r.issues,
+            [CompatIssue::WideInteger {
+                index: MeasIndex::from(1_usize),
+                bits: 64,
+                max: 32
+            }]
+        );
+        // converting will not make the column narrower
+        assert_eq!(r.recommended_version, None);
+    }
+
+    #[test]
+    fn test_mixed_3_2() {
+        let r = CompatReport::new(
+            Software::FlowCore,
+            Version::FCS3_2,
+            Integer,
+            &[Integer, Double],
+            &[bits(B4), bits(B8)],
+        );
+        assert_eq!(
+            r.issues,
+            [
+                CompatIssue::Version {
+                    version: Version::FCS3_2,
+                    max: Version::FCS3_1
+                },
+                CompatIssue::MixedDatatypes,
+            ]
+        );
+        assert_eq!(r.recommended_version, Some(Version::FCS3_1));
+    }
+
+    #[test]
+    fn test_software_from_str() {
+        assert_eq!(
+            "FlowCore".parse::<Software>().ok(),
+            Some(Software::FlowCore)
+        );
+        assert!("excel".parse::<Software>().is_err());
+    }
+}
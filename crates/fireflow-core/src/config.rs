@@ -9,24 +9,33 @@
 /// a warning or error should be "does this adhere to the standard." If not, its
 /// an error. This will work in most cases, with a few exceptions where the
 /// standard is unclear.
+use crate::error::Tentative;
 use crate::header::Version;
 use crate::segment::*;
 use crate::text::byteord::ByteOrd2_0;
 use crate::text::index::MeasIndex;
 use crate::text::keywords as kws;
+use crate::text::namespace::KeyNamespace;
+use crate::text::parser::{LookupKeysWarning, LookupTentative};
+use crate::text::provenance::ParentFile;
 use crate::validated::ascii_range::OtherWidth;
+use crate::validated::dataframe::FCSDataFrame;
 use crate::validated::datepattern::DatePattern;
 use crate::validated::keys;
-use crate::validated::keys::IndexedKey;
+use crate::validated::keys::{IndexedKey, Key};
 use crate::validated::textdelim::TEXTDelim;
 use crate::validated::timepattern::TimePattern;
 
+use chrono::FixedOffset;
 use derive_more::{AsRef, Display, From, FromStr};
 use regex::Regex;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fs::File;
+use std::marker::PhantomData;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -180,6 +189,36 @@ pub struct ReaderConfig {
     /// all $PnB. If $TOT does not match this, it may indicate an issue. If
     /// `false`, throw an error on mismatch, and warning otherwise.
     pub allow_tot_mismatch: bool,
+
+    /// If given, read DATA in chunks of at most this many events.
+    ///
+    /// Each chunk will be passed through `chunk_filter` and appended to the
+    /// dataframe, so only the events which are kept will be held in memory.
+    /// Does not apply to delimited ASCII, which must be read all at once.
+    #[cfg_attr(feature = "python", pyo3(default))]
+    pub chunk_size: Option<NonZeroUsize>,
+
+    /// Function applied to each chunk if `chunk_size` is given.
+    ///
+    /// This may (for example) drop events or change values, but must return
+    /// the same columns (with the same types) it was given. The dataset will
+    /// have provenance recording the file it was read from as its parent (see
+    /// [`crate::text::provenance`]).
+    #[cfg_attr(feature = "python", pyo3(default))]
+    pub chunk_filter: ChunkFilterRef,
+
+    /// If given with `chunk_size`, pass events to `chunk_filter` in windows of
+    /// this many seconds of acquisition time rather than `chunk_size` events.
+    ///
+    /// DATA will still be read `chunk_size` events at a time, but events will
+    /// be held until their window is complete, so one window (rather than one
+    /// chunk) must fit in memory. Windows are split as in
+    /// [`crate::core::AnyCoreDataset::time_chunks`]. This only applies when
+    /// reading a standardized dataset with a time measurement (which is not
+    /// excluded); otherwise events will be passed in chunks of `chunk_size`
+    /// with a warning. Reading will fail if this is not finite and positive.
+    #[cfg_attr(feature = "python", pyo3(default))]
+    pub chunk_time_window: Option<f64>,
 }
 
 /// Configuration for writing an FCS file
@@ -210,6 +249,213 @@ pub struct WriteConfig {
 
     /// If ``true`` use 20 chars for OTHER offset width, otherwise 8.
     pub big_other: bool,
+
+    /// If given, write exactly this many OTHER offset pairs in HEADER.
+    ///
+    /// Pairs beyond the number of OTHER segments will be written as 0,0, and
+    /// writing will fail if there are more OTHER segments than this. Some
+    /// legacy readers assume a fixed HEADER length (usually with no OTHER
+    /// offsets at all) and will not parse anything else. If None, write one
+    /// pair for each OTHER segment.
+    pub other_pairs: Option<usize>,
+
+    /// If given, start each segment at an offset which is a multiple of this.
+    ///
+    /// Some software requires segments to start at even offsets or on some
+    /// other boundary. This applies to TEXT, supplemental TEXT, DATA,
+    /// ANALYSIS, and OTHER; empty segments will not be moved.
+    pub segment_alignment: Option<NonZeroU64>,
+
+    /// Byte used to fill gaps between segments created by alignment.
+    ///
+    /// The gap between HEADER and TEXT will always be filled with spaces since
+    /// anything else may be confused for OTHER offsets.
+    pub padding: u8,
+
+    /// Namespaces for nonstandard keywords to check before writing.
+    ///
+    /// Writing will fail if any two namespaces overlap or if any key in a
+    /// namespace appears more than once in TEXT.
+    pub namespaces: Vec<KeyNamespace>,
+
+    /// How to handle fractional values when writing float columns as integers.
+    ///
+    /// This applies to any dataframe column with floats whose measurement is
+    /// stored as an integer (ie $DATATYPE=I or A).
+    pub float_rounding: FloatRounding,
+
+    /// Rounding to use for specific measurements instead of `float_rounding`.
+    pub float_rounding_overrides: HashMap<MeasIndex, FloatRounding>,
+
+    /// How to handle control characters (ie newlines) in keyword values.
+    ///
+    /// If preserved, values will be written as-is, and any which contain the
+    /// delimiter will have it escaped by doubling it. Otherwise, control
+    /// characters will be escaped or stripped with a warning for each value.
+    /// Values which are entirely control characters will be written as-is
+    /// since they cannot be blank.
+    pub control_chars: ControlCharPolicy,
+
+    /// If true, record this library (with its version) as FIREFLOW_SOFTWARE.
+    ///
+    /// This will replace any existing value. Other provenance keywords (ie
+    /// those on derived datasets) are always written as-is. See
+    /// [`crate::text::provenance`].
+    pub record_provenance: bool,
+}
+
+impl WriteConfig {
+    pub(crate) fn align(&self) -> u64 {
+        self.segment_alignment.map_or(1, NonZeroU64::get)
+    }
+
+    pub(crate) fn float_rounding_for(&self, i: MeasIndex) -> FloatRounding {
+        self.float_rounding_overrides
+            .get(&i)
+            .copied()
+            .unwrap_or(self.float_rounding)
+    }
+}
+
+/// Configuration for converting between FCS versions
+#[derive(Clone, Copy, Default)]
+pub struct ConvertConfig {
+    /// If `true`, drop keywords which cannot be converted rather than failing.
+    pub force: bool,
+
+    /// If `true`, remove or replace keywords deprecated in the target version.
+    ///
+    /// Each change will be reported as a warning.
+    pub strip_deprecated: bool,
+
+    /// The UTC offset at which $DATE, $BTIM, and $ETIM were recorded.
+    ///
+    /// These keywords have no timezone, so they are only replaced by
+    /// $BEGINDATETIME and $ENDDATETIME (which require one) if this is given.
+    /// Otherwise they are kept as-is. Only used with `strip_deprecated`.
+    pub timestamp_offset: Option<FixedOffset>,
+
+    /// If `true`, store a summary of the changed standard keywords in a
+    /// nonstandard keyword.
+    #[cfg(feature = "serde")]
+    pub record_conversion: bool,
+}
+
+/// How to handle fractional values when writing floats to integer columns.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Display)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FloatRounding {
+    /// Drop the fractional part, rounding toward zero
+    #[display("truncate")]
+    Truncate,
+
+    /// Round to the nearest integer, with halves rounded away from zero
+    #[display("round")]
+    Round,
+
+    /// Leave values as-is, so any fractional part is a conversion loss.
+    ///
+    /// This will fail writing unless the conversion check is skipped, in which
+    /// case values will be truncated with a warning.
+    #[default]
+    #[display("error")]
+    Error,
+}
+
+impl FloatRounding {
+    /// Apply this to a value, returning it unchanged for [`Self::Error`].
+    pub fn apply(&self, x: f64) -> f64 {
+        match self {
+            Self::Truncate => x.trunc(),
+            Self::Round => x.round(),
+            Self::Error => x,
+        }
+    }
+}
+
+impl std::str::FromStr for FloatRounding {
+    type Err = ParseFloatRoundingError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "truncate" => Ok(Self::Truncate),
+            "round" => Ok(Self::Round),
+            "error" => Ok(Self::Error),
+            _ => Err(ParseFloatRoundingError),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseFloatRoundingError;
+
+impl fmt::Display for ParseFloatRoundingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str("must be one of 'truncate', 'round', or 'error'")
+    }
+}
+
+/// How to handle control characters in keyword values.
+///
+/// Control characters are ASCII 0-31 and 127, which include newlines and tabs.
+/// These are allowed by the standard but may break naive consumers of TEXT.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Display, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ControlCharPolicy {
+    /// Keep values as-is
+    #[default]
+    #[display("preserve")]
+    Preserve,
+
+    /// Replace each with an escape sequence (ie "\n", "\t", or "\x1E")
+    #[display("escape")]
+    Escape,
+
+    /// Remove them
+    #[display("strip")]
+    Strip,
+}
+
+impl ControlCharPolicy {
+    /// Apply this to a value, returning None if it would be unchanged.
+    pub fn apply(&self, s: &str) -> Option<String> {
+        if *self == Self::Preserve || !s.chars().any(|c| c.is_ascii_control()) {
+            return None;
+        }
+        let mut ret = String::with_capacity(s.len());
+        for c in s.chars() {
+            match (self, c) {
+                _ if !c.is_ascii_control() => ret.push(c),
+                (Self::Escape, '\n') => ret.push_str("\\n"),
+                (Self::Escape, '\r') => ret.push_str("\\r"),
+                (Self::Escape, '\t') => ret.push_str("\\t"),
+                (Self::Escape, _) => ret.push_str(&format!("\\x{:02X}", u32::from(c))),
+                _ => (),
+            }
+        }
+        Some(ret)
+    }
+}
+
+impl std::str::FromStr for ControlCharPolicy {
+    type Err = ParseControlCharPolicyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preserve" => Ok(Self::Preserve),
+            "escape" => Ok(Self::Escape),
+            "strip" => Ok(Self::Strip),
+            _ => Err(ParseControlCharPolicyError),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseControlCharPolicyError;
+
+impl fmt::Display for ParseControlCharPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str("must be one of 'preserve', 'escape', or 'strip'")
+    }
 }
 
 #[derive(Default, Clone)]
@@ -295,6 +541,9 @@ pub struct ReadHeaderAndTEXTConfig {
     pub header: HeaderConfigInner,
 
     /// Override the version
+    ///
+    /// The version in HEADER will not be parsed if this is given, so this may
+    /// also be used to read files whose version is invalid.
     pub version_override: Option<Version>,
 
     /// Corrections for supplemental TEXT segment
@@ -422,6 +671,32 @@ pub struct ReadHeaderAndTEXTConfig {
     /// that these will result in errors if ['allow_empty'] is false.
     pub trim_value_whitespace: bool,
 
+    /// How to handle control characters (ie newlines) in values.
+    ///
+    /// If escaped or stripped, a warning will be emitted for each value which
+    /// was changed. Values which are entirely control characters will be
+    /// removed if stripped. This will be applied after
+    /// ['trim_value_whitespace'] and before ['append_standard_keywords'].
+    pub control_chars: ControlCharPolicy,
+
+    /// If given, only keep these keys and stop splitting TEXT once all are
+    /// found.
+    ///
+    /// Keys are matched case-insensitively exactly as they appear in TEXT, so
+    /// standard keys must include the leading "$". Values for all other keys
+    /// will not be validated or stored, and STEXT will not be read if all keys
+    /// are found in primary TEXT. This is meant for quickly pulling a few
+    /// keywords out of many files with [`fcs_read_raw_text`]; reading
+    /// standardized TEXT or DATA will almost certainly fail since most
+    /// required keywords will be missing.
+    ///
+    /// $NEXTDATA and the STEXT offsets (if required by the version) are
+    /// always kept since they are needed to read TEXT. Matching is done before
+    /// any keys are renamed, promoted, or demoted.
+    ///
+    /// [`fcs_read_raw_text`]: crate::api::fcs_read_raw_text
+    pub keyword_allowlist: Option<Vec<keys::KeyString>>,
+
     /// Remove standard keys from TEXT.
     ///
     /// Comparisons will be case-insensitive. Members of this list should not
@@ -588,6 +863,23 @@ pub struct StdTextReadConfig {
     /// if $TIMESTEP is present but no time measurement is present.
     pub allow_unused_standard: bool,
 
+    /// If true, move orphaned $Pn* keywords into nonstandard keywords.
+    ///
+    /// Orphaned keywords are those which look like $PnX but whose index does
+    /// not refer to a measurement, either because it is 0, exceeds $PAR, or has
+    /// leading zeros (ie $P01N, which is not the same as $P1N). These will be
+    /// stored without the leading '$' (ie $P99N will become P99N). Keywords
+    /// which collide with an existing nonstandard keyword will not be moved
+    /// and will be treated as if this was false.
+    pub absorb_orphan_measurement_keywords: bool,
+
+    /// If true, throw an error if orphaned $Pn* keywords are found.
+    ///
+    /// If false, merely throw a warning and keep these keywords with the
+    /// other unused standard keywords. Has no effect on orphans which are
+    /// absorbed by ['absorb_orphan_measurement_keywords'].
+    pub disallow_orphan_measurement_keywords: bool,
+
     /// If true, throw an error if TEXT includes any deprecated features.
     ///
     /// If false, merely throw a warning.
@@ -616,6 +908,42 @@ pub struct StdTextReadConfig {
     /// measurement 7. These may be used when converting between different
     /// FCS versions.
     pub nonstandard_measurement_pattern: Option<keys::NonStdMeasPattern>,
+
+    /// If supplied, drop all measurements whose $PnN matches this pattern.
+    ///
+    /// Matching measurements will be removed from the standardized TEXT, and
+    /// their columns will be skipped when reading DATA. Measurements which
+    /// are referenced by other keywords (ie $TR or $SPILLOVER) will not be
+    /// removed; a warning will be emitted instead.
+    pub exclude_measurement_pattern: Option<ExcludeMeasNamePattern>,
+
+    /// If supplied, a registry used to look up site-specific instrument data.
+    ///
+    /// The registry will be given the values of $CYT and $CYTSN (if present)
+    /// and may return any number of key/value pairs which will be added to the
+    /// nonstandard keywords of the standardized TEXT. Pairs already present in
+    /// TEXT take precedence over those returned by the registry. Failures
+    /// to look up an instrument will be reported as warnings.
+    pub instrument_registry: InstrumentRegistryRef,
+
+    /// Additional keywords which should be treated as standard.
+    ///
+    /// These are only consulted for keys starting with '$' which are not
+    /// defined by the version being read (ie those which would otherwise be
+    /// pseudostandard). Valid values will be stored alongside the standard
+    /// keywords and written back as such. Invalid values will trigger a
+    /// warning and be treated as if no extension matched.
+    pub keyword_extensions: KeywordExtensions,
+
+    /// If true, fill in missing start of acquisition from vendor timestamps.
+    ///
+    /// Some instruments store the start of acquisition as an epoch timestamp
+    /// in a nonstandard keyword (ie "#ACQUISITIONTIMEMILLIS"). If $BTIM and
+    /// $DATE (and $BEGINDATETIME for 3.2) are all missing, these will be set
+    /// from the first such keyword found using the built-in conventions in
+    /// [`crate::text::conventions`]. The key used will be recorded in the
+    /// "FIREFLOW_TIMESTAMP_SOURCE" nonstandard keyword.
+    pub timestamps_from_conventions: bool,
 }
 
 #[derive(Default, Clone)]
@@ -671,22 +999,129 @@ pub struct ReadLayoutConfig {
     /// Note: this flag has nothing to do with the bitmask being applied to the
     /// actual data being read. This will happen regardless.
     pub disallow_range_truncation: bool,
+
+    /// If true, throw error if $BYTEORD is mixed for float layouts.
+    ///
+    /// FCS 2.0-3.0 allow $BYTEORD to be any permutation of bytes, but for
+    /// floats and doubles anything other than big or little endian (ie
+    /// 1,2,3,4 or 4,3,2,1) almost certainly indicates a bug in whatever wrote
+    /// the file and will likely produce garbage values. By default, such
+    /// orders will be used as given with a warning. Setting this will make
+    /// this an error.
+    ///
+    /// Regardless of this flag, the length of $BYTEORD must match the width of
+    /// the float type (4 for $DATATYPE=F and 8 for $DATATYPE=D).
+    pub disallow_mixed_float_byteord: bool,
 }
 
 /// Configuration options for both reading and writing
-#[derive(Default, Clone)]
+#[derive(Clone)]
 #[cfg_attr(feature = "python", derive(FromPyObject), pyo3(from_item_all))]
 pub struct SharedConfig {
     /// If true, all warnings are considered to be fatal errors.
     pub warnings_are_errors: bool,
+
+    /// Maximum number of warnings (and separately, errors) to return.
+    ///
+    /// Pathological files may produce a warning or error for every event,
+    /// which may use more memory than the file itself. Warnings and errors
+    /// past this limit will be dropped as they are found and only their number
+    /// will be reported. At least one error is always kept. If `None`, return
+    /// all warnings and errors.
+    pub max_warnings: Option<usize>,
 }
 
+/// Default for [`SharedConfig::max_warnings`]
+pub const DEFAULT_MAX_WARNINGS: usize = 10_000;
+
+impl Default for SharedConfig {
+    fn default() -> Self {
+        Self {
+            warnings_are_errors: false,
+            max_warnings: Some(DEFAULT_MAX_WARNINGS),
+        }
+    }
+}
+
+/// A configuration which can turn warnings into errors.
+///
+/// Since all configurations are strict-by-default, the `allow_*` and repair
+/// flags are already off unless explicitly set, and this will not change them.
+/// Rather, this sets the flags which make otherwise-compliant but suspicious
+/// input fatal. For each layer these are:
+///
+/// - [`SharedConfig`] sets `warnings_are_errors`
+/// - [`StdTextReadConfig`] sets `disallow_deprecated` and
+///   `disallow_orphan_measurement_keywords`
+/// - [`ReadLayoutConfig`] sets `disallow_range_truncation` and
+///   `disallow_mixed_float_byteord`
+///
+/// Configurations made of several layers apply this to each of them, so a
+/// custom preset may be made by calling this on the whole configuration and
+/// then unsetting individual flags, or by calling this only on some layers.
+pub trait Strict {
+    /// Set (or unset) all flags listed above for this configuration.
+    fn set_strict(&mut self, strict: bool);
+
+    /// Return this configuration with all flags listed above set.
+    fn strict(mut self) -> Self
+    where
+        Self: Sized,
+    {
+        self.set_strict(true);
+        self
+    }
+}
+
+impl Strict for SharedConfig {
+    fn set_strict(&mut self, strict: bool) {
+        self.warnings_are_errors = strict;
+    }
+}
+
+impl Strict for StdTextReadConfig {
+    fn set_strict(&mut self, strict: bool) {
+        self.disallow_deprecated = strict;
+        self.disallow_orphan_measurement_keywords = strict;
+    }
+}
+
+impl Strict for ReadLayoutConfig {
+    fn set_strict(&mut self, strict: bool) {
+        self.disallow_range_truncation = strict;
+        self.disallow_mixed_float_byteord = strict;
+    }
+}
+
+macro_rules! impl_strict {
+    ($t:ident, [$($field:ident),*]) => {
+        impl Strict for $t {
+            fn set_strict(&mut self, strict: bool) {
+                $(self.$field.set_strict(strict);)*
+            }
+        }
+    };
+}
+
+impl_strict!(ReadRawTEXTConfig, [shared]);
+impl_strict!(ReadStdTEXTConfig, [standard, layout, shared]);
+impl_strict!(ReadRawDatasetConfig, [layout, shared]);
+impl_strict!(NewCoreTEXTConfig, [standard, layout, shared]);
+impl_strict!(ReadStdDatasetConfig, [standard, layout, shared]);
+impl_strict!(ReadRawDatasetFromKeywordsConfig, [layout, shared]);
+impl_strict!(ReadStdDatasetFromKeywordsConfig, [standard, layout, shared]);
+impl_strict!(DataReadConfig, [standard]);
+
 /// A pattern to match the $PnN for the time measurement.
 ///
 /// Defaults to matching "TIME" or "Time".
 #[derive(Clone, FromStr, Display)]
 pub struct TimeMeasNamePattern(pub Regex);
 
+/// A pattern to match the $PnN of measurements to be excluded when reading.
+#[derive(Clone, FromStr, Display)]
+pub struct ExcludeMeasNamePattern(pub Regex);
+
 /// Measurement keywords which are not allowed for temporal measurements.
 ///
 /// These can optionally be ignored via config.
@@ -776,6 +1211,261 @@ impl TemporalOpticalKey {
     }
 }
 
+/// A site-specific lookup keyed by instrument model and serial number.
+///
+/// Implement this to map $CYT and $CYTSN to arbitrary nonstandard keywords,
+/// such as a normalized instrument name or a facility asset tag.
+pub trait InstrumentRegistry: Send + Sync {
+    fn lookup(
+        &self,
+        cyt: Option<&str>,
+        cytsn: Option<&str>,
+    ) -> Result<keys::NonStdPairs, InstrumentRegistryError>;
+}
+
+/// A shared handle to an instrument registry, which may be empty.
+#[derive(Clone, Default)]
+pub struct InstrumentRegistryRef(Option<Arc<dyn InstrumentRegistry>>);
+
+/// Error returned when an instrument registry lookup fails.
+#[derive(Debug)]
+pub struct InstrumentRegistryError(pub String);
+
+impl fmt::Display for InstrumentRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "instrument registry lookup failed: {}", self.0)
+    }
+}
+
+impl InstrumentRegistryRef {
+    pub fn new<R: InstrumentRegistry + 'static>(r: R) -> Self {
+        Self(Some(Arc::new(r)))
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Query the registry with $CYT/$CYTSN without consuming either key.
+    pub(crate) fn lookup<E>(
+        &self,
+        kws: &keys::StdKeywords,
+    ) -> LookupTentative<keys::NonStdPairs, E> {
+        let Some(r) = self.0.as_ref() else {
+            return Tentative::default();
+        };
+        let cyt = kws.get(&kws::Cyt::std()).map(String::as_str);
+        let cytsn = kws.get(&kws::Cytsn::std()).map(String::as_str);
+        match r.lookup(cyt, cytsn) {
+            Ok(xs) => Tentative::new1(xs),
+            Err(e) => Tentative::new(vec![], vec![LookupKeysWarning::Registry(e)], vec![]),
+        }
+    }
+}
+
+/// A function applied to each chunk of DATA as it is read.
+///
+/// This is implemented for any function from dataframe to dataframe.
+pub trait ChunkFilter: Send + Sync {
+    fn filter(&self, df: FCSDataFrame) -> FCSDataFrame;
+}
+
+impl<F> ChunkFilter for F
+where
+    F: Fn(FCSDataFrame) -> FCSDataFrame + Send + Sync,
+{
+    fn filter(&self, df: FCSDataFrame) -> FCSDataFrame {
+        self(df)
+    }
+}
+
+/// A shared handle to a chunk filter, which may be empty.
+///
+/// If a parent file is given, datasets read with this filter will record it
+/// in their provenance. The parent is not computed here since hashing the
+/// file is expensive; see [`ParentFile::from_path`].
+#[derive(Clone, Default)]
+pub struct ChunkFilterRef {
+    filter: Option<Arc<dyn ChunkFilter>>,
+    parent: Option<ParentFile>,
+}
+
+impl ChunkFilterRef {
+    pub fn new<F: ChunkFilter + 'static>(f: F) -> Self {
+        Self {
+            filter: Some(Arc::new(f)),
+            parent: None,
+        }
+    }
+
+    /// Record `parent` as the source of datasets read with this filter.
+    #[must_use]
+    pub fn with_parent(self, parent: ParentFile) -> Self {
+        Self {
+            parent: Some(parent),
+            ..self
+        }
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    pub fn parent(&self) -> Option<&ParentFile> {
+        self.parent.as_ref()
+    }
+
+    /// Apply the filter, or return the chunk unchanged if there is none.
+    pub(crate) fn filter(&self, df: FCSDataFrame) -> FCSDataFrame {
+        match self.filter.as_ref() {
+            Some(f) => f.filter(df),
+            None => df,
+        }
+    }
+}
+
+/// A keyword not defined by the FCS standard which should be treated as such.
+///
+/// Implement this to experiment with draft or third-party extensions to the
+/// standard (ie a future version or something proposed by ISAC) without
+/// changing the built-in keyword tables. For simple keywords whose value maps
+/// to a single type, see [`TypedKeyword`].
+pub trait KeywordExtension: Send + Sync {
+    /// Return true if `key` (without leading '$') belongs to this extension.
+    fn matches(&self, version: Version, key: &str) -> bool;
+
+    /// Check a value, returning it as it should be stored.
+    fn parse(&self, key: &str, value: &str) -> Result<String, String>;
+}
+
+/// An extension keyword whose value is parsed as `T`.
+///
+/// Values will be stored as they are formatted by `T`, so for example an
+/// integer "007" will become "7".
+pub struct TypedKeyword<T> {
+    /// Key without leading '$' (case-insensitive)
+    pub key: String,
+
+    /// Versions to which this applies; all if empty
+    pub versions: Vec<Version>,
+
+    _type: PhantomData<fn() -> T>,
+}
+
+/// A shared list of keyword extensions.
+#[derive(Clone, Default)]
+pub struct KeywordExtensions(Vec<Arc<dyn KeywordExtension>>);
+
+/// Error returned when a value is rejected by a keyword extension.
+pub struct KeywordExtensionError {
+    pub key: keys::StdKey,
+    pub value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for KeywordExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "invalid value '{}' for extension keyword {}: {}",
+            self.value, self.key, self.reason
+        )
+    }
+}
+
+impl<T> TypedKeyword<T> {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.strip_prefix('$').unwrap_or(key).to_string(),
+            versions: vec![],
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T> KeywordExtension for TypedKeyword<T>
+where
+    T: std::str::FromStr + fmt::Display,
+    <T as std::str::FromStr>::Err: fmt::Display,
+{
+    fn matches(&self, version: Version, key: &str) -> bool {
+        (self.versions.is_empty() || self.versions.contains(&version))
+            && key.eq_ignore_ascii_case(&self.key)
+    }
+
+    fn parse(&self, _: &str, value: &str) -> Result<String, String> {
+        value
+            .parse::<T>()
+            .map(|x| x.to_string())
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl KeywordExtensions {
+    pub fn push<E: KeywordExtension + 'static>(&mut self, e: E) {
+        self.0.push(Arc::new(e));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the first extension which matches `key`.
+    pub fn find(&self, version: Version, key: &keys::StdKey) -> Option<&dyn KeywordExtension> {
+        self.0
+            .iter()
+            .find(|e| e.matches(version, key.as_ref()))
+            .map(|e| e.as_ref())
+    }
+
+    /// Check a value against the extension for its key.
+    ///
+    /// Return None if no extension matches.
+    pub fn parse(
+        &self,
+        version: Version,
+        key: &keys::StdKey,
+        value: &str,
+    ) -> Option<Result<String, KeywordExtensionError>> {
+        self.find(version, key).map(|e| {
+            e.parse(key.as_ref(), value)
+                .map_err(|reason| KeywordExtensionError {
+                    key: key.clone(),
+                    value: value.to_string(),
+                    reason,
+                })
+        })
+    }
+
+    /// Remove all keywords with a matching extension and a valid value.
+    pub(crate) fn lookup<E>(
+        &self,
+        version: Version,
+        kws: &mut keys::StdKeywords,
+    ) -> LookupTentative<keys::StdKeywords, E> {
+        if self.is_empty() {
+            return Tentative::default();
+        }
+        let mut found = keys::StdKeywords::new();
+        let mut ws = vec![];
+        let mut ks: Vec<_> = kws.keys().cloned().collect();
+        ks.sort();
+        for k in ks {
+            let Some(res) = kws.get(&k).and_then(|v| self.parse(version, &k, v)) else {
+                continue;
+            };
+            match res {
+                Ok(v) => {
+                    let _ = kws.remove(&k);
+                    let _ = found.insert(k, v);
+                }
+                Err(e) => ws.push(LookupKeysWarning::Extension(e)),
+            }
+        }
+        Tentative::new(found, ws, vec![])
+    }
+}
+
 impl Default for TimeMeasNamePattern {
     fn default() -> Self {
         Self(Regex::new("^(TIME|Time)$").unwrap())
@@ -806,18 +1496,31 @@ impl<C> ReadState<C> {
 
 #[cfg(feature = "python")]
 mod python {
-    use crate::python::macros::{impl_from_py_via_fromstr, impl_value_err};
+    use crate::python::macros::{impl_from_py_via_fromstr, impl_to_py_via_display, impl_value_err};
 
     use super::{
-        OffsetCorrection, ParseTemporalOpticalKeyError, TemporalOpticalKey, TimeMeasNamePattern,
+        ChunkFilterRef, ControlCharPolicy, ExcludeMeasNamePattern, FloatRounding,
+        InstrumentRegistry, InstrumentRegistryError, InstrumentRegistryRef, KeywordExtension,
+        KeywordExtensions, OffsetCorrection, ParseControlCharPolicyError, ParseFloatRoundingError,
+        ParseTemporalOpticalKeyError, TemporalOpticalKey, TimeMeasNamePattern,
     };
+    use crate::header::Version;
+    use crate::validated::keys::{NonStdKey, NonStdPairs};
 
-    use pyo3::exceptions::PyValueError;
+    use pyo3::exceptions::{PyTypeError, PyValueError};
     use pyo3::prelude::*;
+    use std::collections::HashMap;
 
     impl_from_py_via_fromstr!(TemporalOpticalKey);
     impl_value_err!(ParseTemporalOpticalKeyError);
 
+    impl_from_py_via_fromstr!(FloatRounding);
+    impl_to_py_via_display!(FloatRounding);
+    impl_value_err!(ParseFloatRoundingError);
+
+    impl_from_py_via_fromstr!(ControlCharPolicy);
+    impl_value_err!(ParseControlCharPolicyError);
+
     impl<'py> FromPyObject<'py> for TimeMeasNamePattern {
         fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
             let s: String = ob.extract()?;
@@ -829,6 +1532,104 @@ mod python {
         }
     }
 
+    impl<'py> FromPyObject<'py> for ExcludeMeasNamePattern {
+        fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+            let s: String = ob.extract()?;
+            let n = s
+                .parse::<ExcludeMeasNamePattern>()
+                // this should be an error from regexp parsing
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            Ok(n)
+        }
+    }
+
+    // registries are python callables like (str | None, str | None) -> dict[str, str]
+    struct PyInstrumentRegistry(Py<PyAny>);
+
+    impl InstrumentRegistry for PyInstrumentRegistry {
+        fn lookup(
+            &self,
+            cyt: Option<&str>,
+            cytsn: Option<&str>,
+        ) -> Result<NonStdPairs, InstrumentRegistryError> {
+            Python::with_gil(|py| {
+                self.0
+                    .call1(py, (cyt, cytsn))
+                    .and_then(|ret| ret.extract::<HashMap<NonStdKey, String>>(py))
+                    .map(|xs| xs.into_iter().collect())
+                    .map_err(|e| InstrumentRegistryError(e.to_string()))
+            })
+        }
+    }
+
+    impl<'py> FromPyObject<'py> for InstrumentRegistryRef {
+        fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+            if ob.is_none() {
+                Ok(Self::default())
+            } else if ob.is_callable() {
+                Ok(Self::new(PyInstrumentRegistry(ob.clone().unbind())))
+            } else {
+                Err(PyTypeError::new_err("instrument registry must be callable"))
+            }
+        }
+    }
+
+    // extensions are a dict of keys to python callables like (str) -> str
+    struct PyKeywordExtension {
+        key: String,
+        parse: Py<PyAny>,
+    }
+
+    impl KeywordExtension for PyKeywordExtension {
+        fn matches(&self, _: Version, key: &str) -> bool {
+            key.eq_ignore_ascii_case(&self.key)
+        }
+
+        fn parse(&self, _: &str, value: &str) -> Result<String, String> {
+            Python::with_gil(|py| {
+                self.parse
+                    .call1(py, (value,))
+                    .and_then(|ret| ret.extract::<String>(py))
+                    .map_err(|e| e.to_string())
+            })
+        }
+    }
+
+    impl<'py> FromPyObject<'py> for KeywordExtensions {
+        fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+            let mut ret = Self::default();
+            if ob.is_none() {
+                return Ok(ret);
+            }
+            let xs: HashMap<String, Bound<'py, PyAny>> = ob.extract()?;
+            let mut ks: Vec<_> = xs.into_iter().collect();
+            ks.sort_by(|a, b| a.0.cmp(&b.0));
+            for (k, f) in ks {
+                if !f.is_callable() {
+                    return Err(PyTypeError::new_err(format!(
+                        "keyword extension for '{k}' must be callable"
+                    )));
+                }
+                ret.push(PyKeywordExtension {
+                    key: k.strip_prefix('$').unwrap_or(&k).to_string(),
+                    parse: f.unbind(),
+                });
+            }
+            Ok(ret)
+        }
+    }
+
+    // chunk filters operate on rust dataframes, so they can't be set here
+    impl<'py> FromPyObject<'py> for ChunkFilterRef {
+        fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+            if ob.is_none() {
+                Ok(Self::default())
+            } else {
+                Err(PyTypeError::new_err("chunk filter must be None"))
+            }
+        }
+    }
+
     // offset corrections will be tuples like (i32, i32)
     impl<'py, I, S> FromPyObject<'py> for OffsetCorrection<I, S> {
         fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
@@ -837,3 +1638,62 @@ mod python {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strict() {
+        let mut conf = ReadStdDatasetConfig::default().strict();
+        assert!(conf.shared.warnings_are_errors);
+        assert!(conf.standard.disallow_deprecated);
+        assert!(conf.standard.disallow_orphan_measurement_keywords);
+        assert!(conf.layout.disallow_range_truncation);
+        assert!(conf.layout.disallow_mixed_float_byteord);
+        assert!(!conf.raw.allow_odd);
+        // compose a preset which is strict except for deprecated keywords
+        conf.standard.disallow_deprecated = false;
+        assert!(conf.shared.warnings_are_errors);
+        conf.set_strict(false);
+        assert!(!conf.shared.warnings_are_errors);
+        assert!(!conf.layout.disallow_range_truncation);
+    }
+
+    #[test]
+    fn test_keyword_extensions_lookup() {
+        let mut exts = KeywordExtensions::default();
+        let mut only_3_2 = TypedKeyword::<u32>::new("$PLATEROWS");
+        only_3_2.versions = vec![Version::FCS3_2];
+        exts.push(only_3_2);
+        exts.push(TypedKeyword::<f32>::new("DRAFTGAIN"));
+        let mut kws: keys::StdKeywords =
+            [("$platerows", "008"), ("$DRAFTGAIN", "x"), ("$FOO", "1")]
+                .into_iter()
+                .map(|(k, v)| (k.parse().unwrap(), v.to_string()))
+                .collect();
+        let mut kws1 = kws.clone();
+        let tnt = exts.lookup::<()>(Version::FCS3_2, &mut kws);
+        let ext = tnt.value();
+        assert_eq!(1, ext.len());
+        assert_eq!(Some(&"8".to_string()), ext.values().next());
+        assert_eq!(1, tnt.warnings().len());
+        // invalid and unmatched keywords are left alone
+        assert_eq!(2, kws.len());
+        let tnt1 = exts.lookup::<()>(Version::FCS3_1, &mut kws1);
+        assert!(tnt1.value().is_empty());
+        assert_eq!(3, kws1.len());
+    }
+
+    #[test]
+    fn test_control_char_policy() {
+        let s = "a\nb\tc\x1e";
+        assert_eq!(None, ControlCharPolicy::Preserve.apply(s));
+        assert_eq!(None, ControlCharPolicy::Strip.apply("abc"));
+        assert_eq!(Some("abc".into()), ControlCharPolicy::Strip.apply(s));
+        assert_eq!(
+            Some("a\\nb\\tc\\x1E".into()),
+            ControlCharPolicy::Escape.apply(s)
+        );
+    }
+}
@@ -1,29 +1,46 @@
+use crate::channels::{ChannelGroups, ChannelKind, DisplayNameConfig};
+use crate::compat::{CompatReport, Software};
 use crate::config::*;
 use crate::data::*;
+use crate::demux::{self, Threshold};
+use crate::duplicates::{self, DuplicateReport};
 use crate::error::*;
 use crate::header::*;
+use crate::histogram::Histogram;
 use crate::macros::{def_failure, match_many_to_one};
 use crate::nonempty::FCSNonEmpty;
+use crate::policy::{PolicyViolation, SitePolicy};
 use crate::segment::*;
 use crate::text::byteord::*;
+use crate::text::carrier::{Carrier, CarrierError, CarrierIssue};
+#[cfg(feature = "serde")]
+use crate::text::changelog::{ConversionLog, ParseConversionLogError};
 use crate::text::compensation::*;
+use crate::text::conventions::{
+    Environment, EnvironmentConvention, TimestampConvention, VendorTimestamp,
+};
 use crate::text::datetimes::*;
+use crate::text::diff::{DiffConflict, KeywordDiff};
 use crate::text::gating::{self, AppliedGates2_0, AppliedGates3_0, AppliedGates3_2};
 use crate::text::index::*;
 use crate::text::keywords::*;
+use crate::text::legacy::LegacyKeywords;
 use crate::text::named_vec::*;
+use crate::text::namespace::{check_collisions, KeyNamespace, NamespaceCollision};
 use crate::text::optional::*;
 use crate::text::parser::*;
+use crate::text::provenance::{ParentFile, Provenance, PROVENANCE_TIMESTAMP_SOURCE};
 use crate::text::ranged_float::PositiveFloat;
 use crate::text::scale::*;
 use crate::text::spillover::*;
 use crate::text::timestamps::*;
 use crate::text::unstainedcenters::*;
+use crate::validated::ascii_range::Chars;
 use crate::validated::ascii_uint::{
     HeaderString, Uint8DigitOverflow, UintSpacePad20, UintSpacePad8,
 };
 use crate::validated::dataframe as df;
-use crate::validated::dataframe::{AnyFCSColumn, FCSDataFrame};
+use crate::validated::dataframe::{AnyFCSColumn, ColumnIter, FCSDataFrame, RowIter};
 use crate::validated::keys::*;
 use crate::validated::shortname::*;
 use crate::validated::textdelim::TEXTDelim;
@@ -34,6 +51,7 @@ use derive_new::new;
 use itertools::Itertools;
 use nalgebra::DMatrix;
 use nonempty::NonEmpty;
+use num_traits::cast::ToPrimitive;
 use num_traits::identities::{One, Zero};
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
@@ -41,7 +59,10 @@ use std::fmt;
 use std::fs::File;
 use std::io;
 use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::iter;
 use std::marker::PhantomData;
+use std::mem;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[cfg(feature = "serde")]
@@ -105,6 +126,63 @@ pub struct Core<A, D, O, M, T, P, N, W, L> {
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 pub struct Analysis(pub Vec<u8>);
 
+/// The DATA segment as uninterpreted bytes
+#[derive(Clone, From, PartialEq, Default)]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct RawData(pub Vec<u8>);
+
+/// DATA which was copied verbatim rather than parsed into a dataframe.
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct PassthruData {
+    /// The contents of DATA exactly as they appear in the file
+    pub bytes: RawData,
+
+    /// The layout of DATA if it could be determined from TEXT
+    pub layout: Option<PassthruLayout>,
+
+    /// Layout keywords which could be read if the layout could not be
+    /// determined
+    pub partial_layout: Option<PartialPassthruLayout>,
+}
+
+/// Summary of a layout which may be used to interpret raw DATA.
+#[derive(Clone)]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct PassthruLayout {
+    /// Value for $DATATYPE
+    pub datatype: AlphaNumType,
+
+    /// Value for $BYTEORD
+    pub byteord: String,
+
+    /// Values for $PnB (as they would be written)
+    pub widths: Vec<String>,
+
+    /// Values for $PnR
+    pub ranges: Vec<Range>,
+
+    /// Datatype of each column ($PnDATATYPE or $DATATYPE)
+    pub datatypes: Vec<AlphaNumType>,
+}
+
+/// Keywords for a layout which could not be fully determined.
+///
+/// Each field is whatever could be read from TEXT, so a broken layout may be
+/// inspected and repaired.
+#[derive(Clone)]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct PartialPassthruLayout {
+    /// Value for $DATATYPE if present and valid
+    pub datatype: Option<AlphaNumType>,
+
+    /// Value for $BYTEORD if present
+    pub byteord: Option<String>,
+
+    /// Values for $PnB exactly as they appear in TEXT, one for each
+    /// measurement in $PAR (none if $PAR is missing or invalid)
+    pub widths: Vec<Option<String>>,
+}
+
 /// An OTHER segment, which is just a string of bytes
 #[derive(Clone, From, PartialEq)]
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -115,6 +193,38 @@ pub struct Other(pub Vec<u8>);
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 pub struct Others(pub Vec<Other>);
 
+/// Keywords in TEXT with nonstandard measurement keywords grouped separately.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct GroupedKeywords {
+    /// Standard keywords and nonstandard keywords not linked to a measurement
+    pub keywords: HashMap<String, String>,
+
+    /// Nonstandard keywords linked to each measurement, in measurement order
+    pub measurements: Vec<HashMap<String, String>>,
+}
+
+/// Where the value of a standard keyword came from.
+#[derive(Clone, Copy, PartialEq, Eq, Display)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum KeywordOrigin {
+    /// Keyword was present in TEXT when this was read
+    #[display("present")]
+    Present,
+
+    /// Keyword has a value which did not come from TEXT.
+    ///
+    /// This may have been filled in by this library (ie when converting
+    /// versions) or set afterwards.
+    #[display("defaulted")]
+    Defaulted,
+
+    /// Keyword has no value
+    #[display("absent")]
+    Absent,
+}
+
 /// Root of the metadata hierarchy.
 ///
 /// Explicit fields are common to all FCS versions.
@@ -213,6 +323,27 @@ pub struct Metaroot<X> {
     /// will also be used to write FCS-compliant files (which do not allow
     /// nonstandard keywords starting with '$')
     pub nonstandard_keywords: NonStdKeywords,
+
+    /// Optional standard keywords which could not be parsed.
+    ///
+    /// These will be written as-is unless the corresponding field above is
+    /// set, in which case the parsed value takes precedence.
+    #[new(default)]
+    pub raw_values: RawValues,
+
+    /// Keywords defined by extensions to the standard.
+    ///
+    /// These are written as standard keywords. See [`KeywordExtension`].
+    #[new(default)]
+    pub extension_keywords: StdKeywords,
+
+    /// Standard keys (other than $Pn*) which were present in TEXT.
+    ///
+    /// Used to tell values read from TEXT apart from those filled in by this
+    /// library or set afterwards.
+    #[new(default)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub present_keys: HashSet<StdKey>,
 }
 
 #[derive(Clone, Default, AsRef, AsMut, PartialEq, new)]
@@ -230,6 +361,22 @@ pub struct CommonMeasurement {
     #[as_ref(NonStdKeywords)]
     #[as_mut(NonStdKeywords)]
     pub nonstandard_keywords: NonStdKeywords,
+
+    /// Optional standard keywords which could not be parsed.
+    ///
+    /// Keys are the part after "$Pn" (ie "V" for $PnV) so these stay with
+    /// this measurement if it is moved. These will be written as-is unless
+    /// the corresponding field is set.
+    #[new(default)]
+    pub raw_values: HashMap<String, String>,
+
+    /// Suffixes of $Pn* keys for this measurement which were present in TEXT.
+    ///
+    /// Keys are uppercase and are the part after "$Pn" like
+    /// [`raw_values`](Self::raw_values).
+    #[new(default)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub present_keys: HashSet<String>,
 }
 
 /// Structured data for time keywords.
@@ -317,6 +464,13 @@ macro_rules! match_anycore {
 }
 
 impl<A, D, O> AnyCore<A, D, O> {
+    pub(crate) fn exclude_measurements(
+        &mut self,
+        pat: &ExcludeMeasNamePattern,
+    ) -> Result<Vec<ExcludeMeasWarning>, RemoveMeasByIndexError> {
+        match_anycore!(self, x, { x.exclude_measurements(pat) })
+    }
+
     pub fn version(&self) -> Version {
         match_many_to_one!(self, Self, [FCS2_0, FCS3_0, FCS3_1, FCS3_2], x, {
             (*x).fcs_version()
@@ -327,6 +481,81 @@ impl<A, D, O> AnyCore<A, D, O> {
         match_anycore!(self, x, { x.all_shortnames() })
     }
 
+    /// Group measurements by the kind of signal they hold.
+    ///
+    /// See [`Core::channel_groups`].
+    pub fn channel_groups(&self) -> ChannelGroups {
+        match_anycore!(self, x, { x.channel_groups() })
+    }
+
+    /// Return names of all measurements for display.
+    ///
+    /// See [`Core::display_names`].
+    pub fn display_names(&self, conf: &DisplayNameConfig) -> Vec<String> {
+        match_anycore!(self, x, { x.display_names(conf) })
+    }
+
+    /// Return provenance stored in nonstandard keywords if present.
+    ///
+    /// See [`Core::provenance`].
+    pub fn provenance(&self) -> Option<Provenance> {
+        match_anycore!(self, x, { x.provenance() })
+    }
+
+    /// Set provenance for this dataset.
+    ///
+    /// See [`Core::set_provenance`].
+    pub fn set_provenance(&mut self, p: Option<Provenance>) {
+        match_anycore!(self, x, { x.set_provenance(p) })
+    }
+
+    /// Return carrier keywords as a [`Carrier`].
+    ///
+    /// These keywords only exist in 3.2, so return None for other versions.
+    /// See [`Core::carrier`].
+    pub fn carrier(&self) -> Result<Option<Carrier>, CarrierError> {
+        match self {
+            Self::FCS3_2(x) => x.carrier(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Check carriers of several datasets from the same experiment.
+    ///
+    /// Report each invalid carrier and each group of datasets which share the
+    /// same $CARRIERID and $LOCATIONID. Indices refer to positions in `xs`.
+    pub fn check_carriers(xs: &[Self]) -> Vec<CarrierIssue> {
+        Carrier::check_all(xs.iter().map(Self::carrier).collect())
+    }
+
+    /// Check if this is likely to be readable by a third-party software.
+    pub fn check_compat(&self, software: Software) -> CompatReport {
+        let (datatype, datatypes, widths) = match_anycore!(self, x, {
+            let l = &x.layout;
+            (l.datatype(), l.datatypes(), l.fixed_widths())
+        });
+        CompatReport::new(software, self.version(), datatype, &datatypes, &widths)
+    }
+
+    /// Return all standard keywords as they would be written.
+    pub fn standard_keywords(&self) -> RawKeywords {
+        match_anycore!(self, x, { x.standard_keywords(false, false, false, false) })
+    }
+
+    /// See [`Core::grouped_keywords`].
+    pub fn grouped_keywords(&self) -> GroupedKeywords {
+        match_anycore!(self, x, { x.grouped_keywords() })
+    }
+
+    fn check_policy_inner(
+        &self,
+        policy: &SitePolicy,
+        events: Option<usize>,
+    ) -> Vec<PolicyViolation> {
+        let kws = match_anycore!(self, x, { x.standard_keywords(false, false, false, false) });
+        policy.check(self.version(), &kws, &self.shortnames(), events)
+    }
+
     pub fn print_meas_table(&self, delim: &str) {
         match_anycore!(self, x, { x.print_meas_table(delim) })
     }
@@ -356,7 +585,133 @@ impl<A, D, O> AnyCore<A, D, O> {
     }
 }
 
+impl<A, D, O> CommonMetadata for AnyCore<A, D, O> {
+    fn version(&self) -> Version {
+        match_anycore!(self, x, { CommonMetadata::version(&**x) })
+    }
+
+    fn par(&self) -> Par {
+        match_anycore!(self, x, { x.par() })
+    }
+
+    fn shortnames(&self) -> Vec<Shortname> {
+        match_anycore!(self, x, { x.all_shortnames() })
+    }
+
+    fn cyt(&self) -> Option<&Cyt> {
+        match_anycore!(self, x, { x.cyt() })
+    }
+
+    fn set_cyt(&mut self, cyt: Cyt) {
+        match_anycore!(self, x, { x.set_cyt(cyt) })
+    }
+
+    fn btim(&self) -> Option<NaiveTime> {
+        match_anycore!(self, x, { x.btim() })
+    }
+
+    fn set_btim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps> {
+        match_anycore!(self, x, { x.set_btim(time) })
+    }
+
+    fn etim(&self) -> Option<NaiveTime> {
+        match_anycore!(self, x, { x.etim() })
+    }
+
+    fn set_etim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps> {
+        match_anycore!(self, x, { x.set_etim(time) })
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        match_anycore!(self, x, { x.date() })
+    }
+
+    fn set_date(&mut self, date: Option<NaiveDate>) -> Result<(), ReversedTimestamps> {
+        match_anycore!(self, x, { CommonMetadata::set_date(&mut **x, date) })
+    }
+
+    fn trigger(&self) -> Option<&Trigger> {
+        match_anycore!(self, x, { x.trigger() })
+    }
+
+    fn set_trigger(&mut self, tr: Option<Trigger>) -> Result<(), TriggerLinkError> {
+        match_anycore!(self, x, { x.set_trigger(tr) })
+    }
+}
+
+macro_rules! impl_common_metadata {
+    ($core:ident, $time:ident, |$m:ident| $cyt:expr) => {
+        impl<A, D, O> CommonMetadata for $core<A, D, O> {
+            fn version(&self) -> Version {
+                self.fcs_version()
+            }
+
+            fn par(&self) -> Par {
+                self.par()
+            }
+
+            fn shortnames(&self) -> Vec<Shortname> {
+                self.all_shortnames()
+            }
+
+            fn cyt(&self) -> Option<&Cyt> {
+                let $m = &self.metaroot.specific;
+                $cyt
+            }
+
+            fn set_cyt(&mut self, cyt: Cyt) {
+                self.metaroot.specific.cyt = cyt.into();
+            }
+
+            fn btim(&self) -> Option<NaiveTime> {
+                self.btim_naive::<$time>()
+            }
+
+            fn set_btim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps> {
+                self.set_btim_naive::<$time>(time)
+            }
+
+            fn etim(&self) -> Option<NaiveTime> {
+                self.etim_naive::<$time>()
+            }
+
+            fn set_etim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps> {
+                self.set_etim_naive::<$time>(time)
+            }
+
+            fn date(&self) -> Option<NaiveDate> {
+                self.date_naive()
+            }
+
+            fn set_date(&mut self, date: Option<NaiveDate>) -> Result<(), ReversedTimestamps> {
+                self.set_date_naive::<$time>(date)
+            }
+
+            fn trigger(&self) -> Option<&Trigger> {
+                self.metaroot.tr.0.as_ref()
+            }
+
+            fn set_trigger(&mut self, tr: Option<Trigger>) -> Result<(), TriggerLinkError> {
+                self.set_trigger(tr)
+            }
+        }
+    };
+}
+
+impl_common_metadata!(Core2_0, FCSTime, |m| m.cyt.0.as_ref());
+impl_common_metadata!(Core3_0, FCSTime60, |m| m.cyt.0.as_ref());
+impl_common_metadata!(Core3_1, FCSTime100, |m| m.cyt.0.as_ref());
+impl_common_metadata!(Core3_2, FCSTime100, |m| Some(&m.cyt));
+
 impl AnyCoreTEXT {
+    /// Check if this satisfies site-specific rules.
+    ///
+    /// The maximum number of events will not be checked since there is no
+    /// DATA.
+    pub fn check_policy(&self, policy: &SitePolicy) -> Vec<PolicyViolation> {
+        self.check_policy_inner(policy, None)
+    }
+
     pub(crate) fn parse_raw<C>(
         version: Version,
         kws: ValidKeywords,
@@ -371,7 +726,8 @@ impl AnyCoreTEXT {
     where
         C: AsRef<StdTextReadConfig> + AsRef<ReadLayoutConfig> + AsRef<ReadTEXTOffsetsConfig>,
     {
-        match version {
+        let conf: &StdTextReadConfig = st.conf.as_ref();
+        let res: DeferredResult<(Self, _, _), _, _> = match version {
             Version::FCS2_0 => CoreTEXT2_0::new_from_keywords_with_offsets(kws, data, analysis, st)
                 .def_map_value(|(x, y, z)| (x.into(), y, z.into_common())),
             Version::FCS3_0 => CoreTEXT3_0::new_from_keywords_with_offsets(kws, data, analysis, st)
@@ -380,7 +736,25 @@ impl AnyCoreTEXT {
                 .def_map_value(|(x, y, z)| (x.into(), y, z.into_common())),
             Version::FCS3_2 => CoreTEXT3_2::new_from_keywords_with_offsets(kws, data, analysis, st)
                 .def_map_value(|(x, y, z)| (x.into(), y, z.into_common())),
-        }
+        };
+        res.def_and_maybe(|(mut core, extra, offsets)| {
+            conf.exclude_measurement_pattern
+                .as_ref()
+                .map_or(Ok(vec![]), |p| core.exclude_measurements(p))
+                .map_err(StdTEXTFromRawError::Exclude)
+                .into_deferred()
+                .def_and_tentatively(|ws| {
+                    let ws_into = ws.into_iter().map(StdTEXTFromRawWarning::Exclude).collect();
+                    Tentative::new((core, extra, offsets), ws_into, vec![])
+                })
+        })
+    }
+
+    /// Estimate the number of bytes needed to read DATA into a dataframe.
+    ///
+    /// See [`VersionedCoreTEXT::estimated_memory_bytes`].
+    pub fn estimated_memory_bytes(&self, tot: Option<Tot>, data: AnyDataSegment) -> Option<u64> {
+        match_anycore!(self, x, { x.estimated_memory_bytes(tot, data) })
     }
 }
 
@@ -389,6 +763,116 @@ impl AnyCoreDataset {
         match_anycore!(self, x, { &x.data })
     }
 
+    /// Return copy of this dataset without any events in DATA.
+    ///
+    /// See [`CoreDataset::to_metadata_only`].
+    pub fn to_metadata_only(&self, keep_analysis: bool) -> Self {
+        match_anycore!(self, x, { x.to_metadata_only(keep_analysis).into() })
+    }
+
+    /// Return true if this dataset has measurements but no events.
+    pub fn is_metadata_only(&self) -> bool {
+        match_anycore!(self, x, { x.is_metadata_only() })
+    }
+
+    /// Return the byte layout which would be used to write this dataset.
+    ///
+    /// See [`CoreDataset::plan_write`].
+    pub fn plan_write(
+        &self,
+        conf: &WriteConfig,
+    ) -> TerminalResult<WritePlan, StdWriterWarning, StdWriterError, WriteDatasetFailure> {
+        match_anycore!(self, x, { x.plan_write(conf) })
+    }
+
+    /// Write this dataset to a handle.
+    ///
+    /// See [`CoreDataset::h_write_dataset`].
+    pub fn h_write_dataset<W: Write>(
+        &self,
+        h: &mut BufWriter<W>,
+        conf: &WriteConfig,
+    ) -> IOTerminalResult<WriteReport, StdWriterWarning, StdWriterError, WriteDatasetFailure> {
+        match_anycore!(self, x, { x.h_write_dataset(h, conf) })
+    }
+
+    /// Compute a histogram of one measurement in DATA.
+    ///
+    /// See [`CoreDataset::channel_histogram`].
+    pub fn channel_histogram(
+        &self,
+        name: &Shortname,
+        bins: NonZeroUsize,
+        scaled: bool,
+    ) -> Result<Histogram, KeyNotFoundError> {
+        match_anycore!(self, x, { x.channel_histogram(name, bins, scaled) })
+    }
+
+    /// Return iterator over each event in DATA.
+    ///
+    /// See [`CoreDataset::iter_rows`].
+    pub fn iter_rows(&self) -> RowIter<'_> {
+        self.as_data().iter_rows()
+    }
+
+    /// Return iterator over the values of one measurement in DATA.
+    ///
+    /// See [`CoreDataset::iter_column`].
+    pub fn iter_column(&self, name: &Shortname) -> Result<ColumnIter<'_>, KeyNotFoundError> {
+        match_anycore!(self, x, { x.iter_column(name) })
+    }
+
+    /// Check if this satisfies site-specific rules.
+    pub fn check_policy(&self, policy: &SitePolicy) -> Vec<PolicyViolation> {
+        self.check_policy_inner(policy, Some(self.as_data().nrows()))
+    }
+
+    /// Split events into one dataset per barcode channel.
+    ///
+    /// See [`CoreDataset::demultiplex`].
+    pub fn demultiplex(
+        &self,
+        rules: &[(Shortname, Threshold)],
+        parent: Option<&ParentFile>,
+    ) -> Result<Vec<Self>, KeyNotFoundError> {
+        match_anycore!(self, x, {
+            x.demultiplex(rules, parent)
+                .map(|ys| ys.into_iter().map(Self::from).collect())
+        })
+    }
+
+    /// Find events which repeat earlier events in the given channels.
+    ///
+    /// See [`CoreDataset::find_duplicate_events`].
+    pub fn find_duplicate_events(
+        &self,
+        channels: &[Shortname],
+    ) -> Result<DuplicateReport, KeyNotFoundError> {
+        match_anycore!(self, x, { x.find_duplicate_events(channels) })
+    }
+
+    /// Return row ranges which split DATA into windows of acquisition time.
+    ///
+    /// Each range will span `window` seconds according to the time
+    /// measurement multiplied by $TIMESTEP (which is assumed to be 1 for 2.0).
+    /// A new range will also be started whenever time decreases, which may
+    /// happen if the time counter rolls over. Return None if there is no time
+    /// measurement. To filter events in these windows without holding all of
+    /// DATA in memory, see [`ReaderConfig::chunk_time_window`].
+    pub fn time_chunks(&self, window: f64) -> Option<Vec<std::ops::Range<usize>>> {
+        match_anycore!(self, x, { x.time_chunks(window) })
+    }
+
+    /// Split this dataset into several datasets by windows of acquisition time.
+    ///
+    /// See [`CoreDataset::split_by_time`].
+    pub fn split_by_time(&self, interval: f64, parent: Option<&ParentFile>) -> Option<Vec<Self>> {
+        match_anycore!(self, x, {
+            x.split_by_time(interval, parent)
+                .map(|ys| ys.into_iter().map(Self::from).collect())
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new_from_keywords<C, R>(
         h: &mut BufReader<R>,
@@ -651,8 +1135,8 @@ pub struct InnerMetaroot3_2 {
     pub vol: MaybeValue<Vol>,
 
     /// Values of $CARRIERID/$CARRIERTYPE/$LOCATIONID
-    #[as_ref(Option<Carrierid>, Option<Carriertype>, Option<Locationid>)]
-    #[as_mut(Option<Carrierid>, Option<Carriertype>, Option<Locationid>)]
+    #[as_ref(Option<Carrierid>, Option<Carriertype>, Option<Locationid>, CarrierData)]
+    #[as_mut(Option<Carrierid>, Option<Carriertype>, Option<Locationid>, CarrierData)]
     pub carrier: CarrierData,
 
     /// Values of $UNSTAINEDINFO/$UNSTAINEDCENTERS
@@ -669,6 +1153,13 @@ pub struct InnerMetaroot3_2 {
     /// Values of $RnI/$RnW/$GATING
     #[as_ref(AppliedGates3_2)]
     applied_gates: AppliedGates3_2,
+
+    /// Keywords from previous versions which were found in TEXT.
+    ///
+    /// These are not written.
+    #[as_ref(LegacyKeywords)]
+    #[new(default)]
+    pub legacy: LegacyKeywords,
 }
 
 /// Temporal measurement fields specific to version 2.0
@@ -937,6 +1428,28 @@ pub enum ScaleTransform {
     Log(LogScale),
 }
 
+/// Metadata describing one column in DATA.
+///
+/// This summarizes the measurement and layout keywords needed to interpret a
+/// column without looking them up separately.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct ColumnMetadata {
+    /// Value of $PnS
+    pub longname: Option<Longname>,
+
+    /// Type of the column as given by $DATATYPE or $PnDATATYPE
+    pub datatype: AlphaNumType,
+
+    /// Value of $PnR
+    pub range: Range,
+
+    /// Value of $PnE/$PnG, where 2.0 $PnE is converted to a transform and the
+    /// temporal measurement is always linear
+    pub transform: ScaleTransform,
+}
+
 /// A bundle for $PKn and $PKNn (2.0-3.1)
 ///
 /// It makes little sense to have only one of these since they both collectively
@@ -1233,6 +1746,81 @@ pub trait AsScaleTransform {
     fn as_transform(&self) -> ScaleTransform;
 }
 
+pub trait AsOpticalType {
+    fn as_optical_type(&self) -> Option<&OpticalType>;
+}
+
+/// Metadata which is present in every FCS version.
+///
+/// This is implemented for [`Core`] in all versions as well as [`AnyCore`],
+/// so generic code can use these without matching on each version. Values
+/// whose representation differs between versions are converted to a common
+/// type; for instance, $BTIM and $ETIM are returned as [`NaiveTime`]
+/// regardless of their sub-second precision.
+///
+/// ```
+/// use fireflow_core::prelude::*;
+///
+/// fn describe<C: CommonMetadata>(core: &C) -> String {
+///     let cyt = core.cyt().map_or("unknown".into(), |c| c.to_string());
+///     format!("{} measurements from {cyt}", core.par())
+/// }
+///
+/// assert_eq!("2 measurements from unknown", describe(&CoreTEXT3_1::example(2)));
+/// assert_eq!("3 measurements from example", describe(&CoreTEXT3_2::example(3)));
+/// ```
+pub trait CommonMetadata {
+    /// Return the FCS version.
+    fn version(&self) -> Version;
+
+    /// Return $PAR, which is the number of measurements.
+    fn par(&self) -> Par;
+
+    /// Return all $PnN, using "Pn" for any which are missing.
+    fn shortnames(&self) -> Vec<Shortname>;
+
+    /// Return $CYT, which is always present in 3.2.
+    fn cyt(&self) -> Option<&Cyt>;
+
+    /// Set $CYT.
+    ///
+    /// This cannot remove $CYT since it is required in 3.2.
+    fn set_cyt(&mut self, cyt: Cyt);
+
+    /// Return $BTIM
+    fn btim(&self) -> Option<NaiveTime>;
+
+    /// Set $BTIM.
+    ///
+    /// Return error if $BTIM would be after $ETIM and $DATE is present.
+    /// Sub-second precision will be reduced to what the version supports.
+    fn set_btim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps>;
+
+    /// Return $ETIM
+    fn etim(&self) -> Option<NaiveTime>;
+
+    /// Set $ETIM.
+    ///
+    /// See [`CommonMetadata::set_btim`].
+    fn set_etim(&mut self, time: Option<NaiveTime>) -> Result<(), ReversedTimestamps>;
+
+    /// Return $DATE
+    fn date(&self) -> Option<NaiveDate>;
+
+    /// Set $DATE.
+    ///
+    /// Return error if $BTIM would be after $ETIM.
+    fn set_date(&mut self, date: Option<NaiveDate>) -> Result<(), ReversedTimestamps>;
+
+    /// Return $TR
+    fn trigger(&self) -> Option<&Trigger>;
+
+    /// Set $TR.
+    ///
+    /// Return error if the trigger does not refer to a measurement.
+    fn set_trigger(&mut self, tr: Option<Trigger>) -> Result<(), TriggerLinkError>;
+}
+
 pub trait Versioned {
     type Layout: VersionedDataLayout;
     type Offsets: VersionedTEXTOffsets<TotDef = <Self::Layout as VersionedDataLayout>::TotDef>;
@@ -1269,7 +1857,7 @@ pub trait Versioned {
                 };
                 let read_conf: &ReaderConfig = st.conf.as_ref();
                 let data_res = layout
-                    .h_read_df(h, offsets.tot(), *offsets.as_ref(), read_conf)
+                    .h_read_df(h, offsets.tot(), *offsets.as_ref(), read_conf, &[], None)
                     .def_warnings_into()
                     .def_map_errors(|e| e.inner_into());
                 let analysis_res = ar.h_read(h).into_deferred();
@@ -1280,11 +1868,221 @@ pub trait Versioned {
                     })
             })
     }
+
+    /// Like h_lookup_and_read but copy DATA verbatim.
+    ///
+    /// Failure to determine the layout will be reported as a warning rather
+    /// than an error. Failure to locate DATA and ANALYSIS is still an error.
+    fn h_lookup_and_read_passthru<C, R>(
+        h: &mut BufReader<R>,
+        kws: &StdKeywords,
+        data_seg: HeaderDataSegment,
+        analysis_seg: HeaderAnalysisSegment,
+        st: &ReadState<C>,
+    ) -> IODeferredResult<
+        (PassthruData, Analysis, AnyDataSegment, AnyAnalysisSegment),
+        LookupAndReadDataAnalysisWarning,
+        LookupAndReadDataAnalysisError,
+    >
+    where
+        R: Read + Seek,
+        Self::Offsets: AsRef<AnyDataSegment> + AsRef<AnyAnalysisSegment>,
+        C: AsRef<ReadLayoutConfig> + AsRef<ReadTEXTOffsetsConfig>,
+    {
+        let layout_tnt = match Self::Layout::lookup_ro(kws, st.conf.as_ref()) {
+            Ok(tnt) => tnt.map(|layout| (Some(PassthruLayout::from_layout(&layout)), None)),
+            Err(f) => f.unfail_with((None, Some(PartialPassthruLayout::from_keywords(kws)))),
+        }
+        .warnings_into()
+        .errors_to_warnings(|e| PassthruLayoutWarning(e).into());
+        let offset_res = Self::Offsets::lookup_ro(kws, data_seg, analysis_seg, st)
+            .def_inner_into()
+            .def_errors_liftio();
+        Ok(layout_tnt)
+            .def_zip(offset_res)
+            .def_and_maybe(|((layout, partial_layout), offsets)| {
+                let d_seg: AnyDataSegment = *offsets.as_ref();
+                let a_seg: AnyAnalysisSegment = *offsets.as_ref();
+                let mut buf = vec![];
+                d_seg
+                    .inner
+                    .h_read_contents(h, &mut buf)
+                    .and_then(|()| AnalysisReader { seg: a_seg }.h_read(h))
+                    .into_deferred()
+                    .def_map_value(|analysis| {
+                        let data = PassthruData {
+                            bytes: RawData(buf),
+                            layout,
+                            partial_layout,
+                        };
+                        (data, analysis, d_seg, a_seg)
+                    })
+            })
+    }
 }
 
-pub trait LookupMetaroot: Sized + VersionedMetaroot {
-    fn lookup_shortname(
-        kws: &mut StdKeywords,
+impl PassthruLayout {
+    fn from_layout<L: VersionedDataLayout>(layout: &L) -> Self {
+        Self {
+            datatype: layout.datatype(),
+            byteord: layout.byteord_keyword().1,
+            widths: layout
+                .req_meas_keywords()
+                .into_iter()
+                .map(|[(_, b), _]| b)
+                .collect(),
+            ranges: layout.ranges(),
+            datatypes: layout.datatypes(),
+        }
+    }
+}
+
+impl PartialPassthruLayout {
+    fn from_keywords(kws: &StdKeywords) -> Self {
+        let widths = Par::get_metaroot_req(kws).map_or(vec![], |par| {
+            (0..par.0)
+                .map(|i| kws.get(&Width::std(i.into())).cloned())
+                .collect()
+        });
+        Self {
+            datatype: AlphaNumType::get_metaroot_req(kws).ok(),
+            // the key is the same for all versions, only the value differs
+            byteord: kws.get(&ByteOrd3_1::std()).cloned(),
+            widths,
+        }
+    }
+}
+
+impl PassthruData {
+    /// Return iterator which decodes each event directly from raw DATA.
+    ///
+    /// Each event is one float per measurement. Nothing is decoded until
+    /// requested, so this may be used to stream values from a large file
+    /// without also holding a dataframe in memory. Integers are returned
+    /// exactly as they are stored and are not masked using $PnR. Fixed-width
+    /// ASCII values which are not numbers will be NaN. Any trailing bytes which
+    /// do not make a complete event are ignored.
+    ///
+    /// Return None if the layout is unknown or cannot be decoded without
+    /// parsing all of DATA (ie delimited ASCII).
+    pub fn iter_rows(&self) -> Option<PassthruRows<'_>> {
+        let layout = self.layout.as_ref()?;
+        let order = parse_byteord(&layout.byteord)?;
+        let mut offset = 0;
+        let mut columns = vec![];
+        for (w, t) in layout.widths.iter().zip(layout.datatypes.iter()) {
+            let n = w.parse::<usize>().ok()?;
+            let nbytes = match t {
+                AlphaNumType::Ascii => n,
+                AlphaNumType::Integer if n > 0 && n % 8 == 0 && n <= 64 => n / 8,
+                AlphaNumType::Float if n == 32 => 4,
+                AlphaNumType::Double if n == 64 => 8,
+                _ => return None,
+            };
+            let col_order = RawByteOrder::for_width(&order, nbytes, *t)?;
+            columns.push((offset..offset + nbytes, *t, col_order));
+            offset += nbytes;
+        }
+        let nrows = self.bytes.0.len().checked_div(offset).unwrap_or(0);
+        Some(PassthruRows {
+            bytes: &self.bytes.0,
+            columns,
+            row_width: offset,
+            rows: 0..nrows,
+        })
+    }
+}
+
+/// Iterator over events decoded from raw DATA.
+///
+/// See [`PassthruData::iter_rows`].
+pub struct PassthruRows<'a> {
+    bytes: &'a [u8],
+    columns: Vec<(std::ops::Range<usize>, AlphaNumType, RawByteOrder)>,
+    row_width: usize,
+    rows: std::ops::Range<usize>,
+}
+
+/// Byte order used to decode one column from raw DATA.
+#[derive(Clone)]
+enum RawByteOrder {
+    Little,
+    Big,
+    /// Significance (0 is least) of each byte in the order it appears
+    Mixed(Vec<usize>),
+}
+
+impl RawByteOrder {
+    fn for_width(order: &[usize], nbytes: usize, t: AlphaNumType) -> Option<Self> {
+        let n = order.len();
+        if t == AlphaNumType::Ascii || order.iter().enumerate().all(|(i, x)| *x == i) {
+            Some(Self::Little)
+        } else if order.iter().enumerate().all(|(i, x)| *x == n - 1 - i) {
+            Some(Self::Big)
+        } else if n == nbytes {
+            Some(Self::Mixed(order.to_vec()))
+        } else {
+            None
+        }
+    }
+
+    /// Rearrange bytes so they are little endian.
+    fn to_le(&self, xs: &[u8]) -> [u8; 8] {
+        let mut buf = [0; 8];
+        match self {
+            Self::Little => buf[..xs.len()].copy_from_slice(xs),
+            Self::Big => xs.iter().rev().enumerate().for_each(|(i, x)| buf[i] = *x),
+            Self::Mixed(o) => xs.iter().zip(o).for_each(|(x, i)| buf[*i] = *x),
+        }
+        buf
+    }
+}
+
+/// Parse $BYTEORD into zero-based significance of each byte.
+fn parse_byteord(s: &str) -> Option<Vec<usize>> {
+    s.split(',')
+        .map(|x| x.trim().parse::<usize>().ok()?.checked_sub(1))
+        .collect()
+}
+
+impl Iterator for PassthruRows<'_> {
+    type Item = Vec<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.rows.next()? * self.row_width;
+        let row = &self.bytes[start..start + self.row_width];
+        let ret = self
+            .columns
+            .iter()
+            .map(|(r, t, o)| {
+                let xs = &row[r.clone()];
+                match t {
+                    AlphaNumType::Ascii => std::str::from_utf8(xs)
+                        .ok()
+                        .and_then(|x| x.trim().parse().ok())
+                        .unwrap_or(f64::NAN),
+                    AlphaNumType::Integer => u64::from_le_bytes(o.to_le(xs)) as f64,
+                    AlphaNumType::Float => {
+                        let b = o.to_le(xs);
+                        f64::from(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    }
+                    AlphaNumType::Double => f64::from_le_bytes(o.to_le(xs)),
+                }
+            })
+            .collect();
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl ExactSizeIterator for PassthruRows<'_> {}
+
+pub trait LookupMetaroot: Sized + VersionedMetaroot {
+    fn lookup_shortname(
+        kws: &mut StdKeywords,
         n: MeasIndex,
     ) -> LookupResult<<Self::Name as MightHave>::Wrapper<Shortname>>;
 
@@ -1353,10 +2151,32 @@ pub trait VersionedMetaroot: Sized {
     /// Everything after `index` must be incremented by 1.
     fn insert_meas_index_inner(&mut self, index: MeasIndex);
 
+    /// Update or remove keywords which link to a measurement being removed.
+    ///
+    /// Everything after `index` must be decremented by 1. Anything which
+    /// would be invalid without this measurement must be removed.
+    fn remove_meas_index_inner(&mut self, index: MeasIndex, name: &Shortname);
+
     fn keywords_req_inner(&self) -> impl Iterator<Item = (String, String)>;
 
     fn keywords_opt_inner(&self) -> impl Iterator<Item = (String, String)>;
 
+    /// Remove or replace keywords which are deprecated in this version.
+    fn strip_deprecated_inner(&mut self, _: Option<FixedOffset>) -> Vec<DeprecatedChange> {
+        vec![]
+    }
+
+    /// Shift timestamps to cover one slice of the acquisition.
+    ///
+    /// `begin` and `end` are the number of seconds after the current start
+    /// of acquisition at which the slice starts and ends.
+    fn slice_time_inner(&mut self, begin: f64, end: f64);
+
+    /// Set the start of acquisition if it is not already known.
+    ///
+    /// Return true if anything was set.
+    fn fill_start_inner(&mut self, start: DateTime<FixedOffset>) -> bool;
+
     /// Swap convert a temporal and optical channel into the other.
     ///
     /// This is necessary to have in one function since we may want to recover
@@ -1436,6 +2256,12 @@ pub trait VersionedOptical: Sized {
     ) -> impl Iterator<Item = (MeasHeader, String, Option<String>)>;
 
     fn can_convert_to_temporal(&self, i: MeasIndex) -> MultiResult<(), OpticalToTemporalError>;
+
+    /// Remove or replace measurement keywords which are deprecated in this
+    /// version.
+    fn strip_deprecated_optical(_: &mut Optical<Self>, _: MeasIndex) -> Vec<DeprecatedChange> {
+        vec![]
+    }
 }
 
 pub trait LookupOptical: Sized + VersionedOptical {
@@ -1460,6 +2286,9 @@ pub trait VersionedTemporal: Sized {
         &self,
         i: MeasIndex,
     ) -> MultiResult<(), SwapOpticalTemporalError>;
+
+    /// Return the number of seconds in one unit of the time measurement.
+    fn timestep_seconds(&self) -> f64;
 }
 
 pub trait LookupTemporal: VersionedTemporal {
@@ -1596,8 +2425,40 @@ impl CommonMeasurement {
         Longname::lookup_opt(kws, i.into()).map(|longname| Self {
             longname,
             nonstandard_keywords: nonstd.into_iter().collect(),
+            raw_values: HashMap::new(),
+            present_keys: HashSet::new(),
+        })
+    }
+
+    /// Keep values of any measurement keys which failed to parse.
+    fn with_unparsed<X, E>(i: MeasIndex, tnt: LookupTentative<X, E>) -> LookupTentative<X, E>
+    where
+        X: AsMut<Self>,
+    {
+        let raw: HashMap<_, _> = unparsed_values(&tnt)
+            .filter(|(k, _)| k.meas_index() == Some(i))
+            .filter_map(|(k, v)| k.split_meas().map(|(_, x)| (x.to_string(), v.clone())))
+            .collect();
+        tnt.map(|mut x| {
+            x.as_mut().raw_values.extend(raw);
+            x
         })
     }
+
+    /// Drop unparsed values, which may not be valid keys in another version.
+    fn into_converted(self) -> Self {
+        Self {
+            raw_values: HashMap::new(),
+            ..self
+        }
+    }
+
+    fn raw_keywords(&self, i: MeasIndex) -> impl Iterator<Item = (String, String)> {
+        let n = IndexFromOne::from(i);
+        self.raw_values
+            .iter()
+            .map(move |(k, v)| (format!("$P{n}{k}"), v.clone()))
+    }
 }
 
 impl<T> Temporal<T> {
@@ -1617,9 +2478,12 @@ impl<T> Temporal<T> {
     where
         T: LookupTemporal,
     {
-        CommonMeasurement::lookup(kws, i, nonstd).and_maybe(|common| {
-            T::lookup_specific(kws, i, conf).def_map_value(|specific| Temporal { common, specific })
-        })
+        CommonMeasurement::lookup(kws, i, nonstd)
+            .and_maybe(|common| {
+                T::lookup_specific(kws, i, conf)
+                    .def_map_value(|specific| Temporal { common, specific })
+            })
+            .map(|tnt| CommonMeasurement::with_unparsed(i, tnt))
     }
 
     fn convert<ToT>(self, i: MeasIndex, force: bool) -> TemporalConvertTentative<Temporal<ToT>>
@@ -1627,7 +2491,7 @@ impl<T> Temporal<T> {
         ToT: ConvertFromTemporal<T>,
     {
         ToT::convert_from_temporal(self.specific, i, force).map(|specific| Temporal {
-            common: self.common,
+            common: self.common.into_converted(),
             specific,
         })
     }
@@ -1680,7 +2544,7 @@ impl<O> Optical<O> {
         force: bool,
     ) -> OpticalConvertResult<Optical<ToP>> {
         ToP::convert_from_optical(self.specific, i, force).def_map_value(|specific| Optical {
-            common: self.common,
+            common: self.common.into_converted(),
             detector_type: self.detector_type,
             detector_voltage: self.detector_voltage,
             filter: self.filter,
@@ -1711,21 +2575,24 @@ impl<O> Optical<O> {
             PercentEmitted::lookup_opt(kws, i.into())
         };
         let v = DetectorVoltage::lookup_opt(kws, i.into());
-        f.zip5(p, d, e, v).errors_into().and_maybe(
-            |(filter, power, detector_type, percent_emitted, detector_voltage)| {
-                CommonMeasurement::lookup(kws, i, nonstd).and_maybe(|common| {
-                    O::lookup_specific(kws, i, conf).def_map_value(|specific| Optical {
-                        common,
-                        filter,
-                        power,
-                        detector_type,
-                        percent_emitted,
-                        detector_voltage,
-                        specific,
+        f.zip5(p, d, e, v)
+            .errors_into()
+            .and_maybe(
+                |(filter, power, detector_type, percent_emitted, detector_voltage)| {
+                    CommonMeasurement::lookup(kws, i, nonstd).and_maybe(|common| {
+                        O::lookup_specific(kws, i, conf).def_map_value(|specific| Optical {
+                            common,
+                            filter,
+                            power,
+                            detector_type,
+                            percent_emitted,
+                            detector_voltage,
+                            specific,
+                        })
                     })
-                })
-            },
-        )
+                },
+            )
+            .map(|tnt| CommonMeasurement::with_unparsed(i, tnt))
     }
 
     fn req_keywords(&self, i: MeasIndex) -> impl Iterator<Item = (MeasHeader, String, String)>
@@ -1869,6 +2736,9 @@ where
             sys: None.into(),
             tr: None.into(),
             nonstandard_keywords: HashMap::new(),
+            raw_values: HashMap::new(),
+            extension_keywords: HashMap::new(),
+            present_keys: HashSet::new(),
             specific,
         }
     }
@@ -1893,6 +2763,10 @@ where
             src: self.src,
             tr: self.tr,
             nonstandard_keywords: self.nonstandard_keywords,
+            // unparsed values may not be valid keys in the new version
+            raw_values: HashMap::new(),
+            extension_keywords: self.extension_keywords,
+            present_keys: self.present_keys,
             specific,
         })
     }
@@ -1922,11 +2796,16 @@ where
         let sr = Src::lookup_opt(kws);
         let sy = Sys::lookup_opt(kws);
         let t = Trigger::lookup_opt(kws, &names);
+        let rg = conf.instrument_registry.lookup(kws);
         a.zip5(co, ce, e, f)
             .zip5(i, l, o, p)
             .zip5(sm, sr, sy, t)
+            .zip(rg)
             .and_maybe(
-                |(((abrt, com, cells, exp, fil), inst, lost, op, proj), smno, src, sys, tr)| {
+                |(
+                    (((abrt, com, cells, exp, fil), inst, lost, op, proj), smno, src, sys, tr),
+                    reg,
+                )| {
                     M::lookup_specific(kws, par, &names, &ordered_names, conf).def_map_value(
                         |specific| Metaroot {
                             abrt,
@@ -1942,12 +2821,25 @@ where
                             src,
                             sys,
                             tr,
-                            nonstandard_keywords: nonstd.into_iter().collect(),
+                            // chain TEXT values last so they win on collision
+                            nonstandard_keywords: reg.into_iter().chain(nonstd).collect(),
+                            raw_values: HashMap::new(),
+                            extension_keywords: HashMap::new(),
+                            present_keys: HashSet::new(),
                             specific,
                         },
                     )
                 },
             )
+            .map(|tnt| {
+                let raw: RawValues = unparsed_values(&tnt)
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                tnt.map(|mut m| {
+                    m.raw_values = raw;
+                    m
+                })
+            })
     }
 
     fn all_req_keywords(&self, par: Par) -> impl Iterator<Item = (String, String)> {
@@ -1975,6 +2867,11 @@ where
         .into_iter()
         .flat_map(|(k, v)| v.map(|x| (k, x)))
         .chain(self.specific.keywords_opt_inner())
+        .chain(
+            self.extension_keywords
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone())),
+        )
         .map(|(k, v)| (k.to_string(), v))
         .chain(
             self.nonstandard_keywords
@@ -1992,6 +2889,13 @@ where
         self.specific.rename_meas_links_inner(mapping);
     }
 
+    fn remove_meas_links(&mut self, index: MeasIndex, name: &Shortname) {
+        if self.tr.0.as_ref().is_some_and(|tr| &tr.measurement == name) {
+            self.tr = None.into();
+        }
+        self.specific.remove_meas_index_inner(index, name);
+    }
+
     fn check_meas_named_links(
         &self,
         names: &HashSet<&Shortname>,
@@ -2078,6 +2982,28 @@ where
     M: VersionedMetaroot,
     M::Name: Clone,
 {
+    /// Remove or replace keywords which are deprecated in this version.
+    ///
+    /// This currently only has an effect on 3.2. $MODE, $PLATENAME, $PnP,
+    /// and all gating keywords will be removed. $PLATEID and $WELLID will be
+    /// moved to $CARRIERID and $LOCATIONID if these are not set. $DATE,
+    /// $BTIM, and $ETIM will be removed if $BEGINDATETIME is set, or otherwise
+    /// used to make $BEGINDATETIME and $ENDDATETIME if `offset` is given;
+    /// since these have no timezone they are kept if neither is true. Return
+    /// a description of each change made.
+    pub fn strip_deprecated(&mut self, offset: Option<FixedOffset>) -> Vec<DeprecatedChange> {
+        let ms = self.measurements.alter_values(
+            |x| M::Optical::strip_deprecated_optical(x.value, x.index),
+            |_| vec![],
+        );
+        self.metaroot
+            .specific
+            .strip_deprecated_inner(offset)
+            .into_iter()
+            .chain(ms.into_iter().flatten())
+            .collect()
+    }
+
     /// Show FCS version.
     pub fn fcs_version(&self) -> Version
     where
@@ -2112,29 +3038,64 @@ where
         Version: From<M::Ver>,
         T: Zero + TryFrom<u64, Error = Uint8DigitOverflow> + HeaderString,
     {
-        self.h_write_text_inner::<_, T>(h, delim, Tot(0), 0, 0, &[])
+        self.h_write_text_inner::<_, T>(
+            h,
+            delim,
+            ControlCharPolicy::Preserve,
+            false,
+            Tot(0),
+            0,
+            0,
+            &[],
+            0,
+            1,
+            0,
+        )
+            .map(|_| ())
             .terminate(WriteTEXTFailure)
     }
 
+    /// Write HEADER, TEXT, and OTHER.
+    ///
+    /// Return the gaps which must be written before DATA and ANALYSIS.
+    #[allow(clippy::too_many_arguments)]
     fn h_write_text_inner<W: Write, T>(
         &self,
         h: &mut BufWriter<W>,
         delim: TEXTDelim,
+        control_chars: ControlCharPolicy,
+        provenance: bool,
         tot: Tot,
         data_len: u64,
         analysis_len: u64,
         other_segs: &[Other],
-    ) -> IOResult<(), Uint8DigitOverflow>
+        other_pairs: usize,
+        align: u64,
+        pad: u8,
+    ) -> IOResult<SegmentGaps, Uint8DigitOverflow>
     where
         Version: From<M::Ver>,
         T: Zero + TryFrom<u64, Error = Uint8DigitOverflow> + HeaderString,
     {
         // TODO do something useful with $NEXTDATA
-        let other_lens: Vec<_> = other_segs.iter().map(|s| s.0.len() as u64).collect();
-        self.header_and_raw_keywords(tot, data_len, analysis_len, other_lens, false)
-            .map_err(ImpureError::Pure)
+        let mut other_lens: Vec<_> = other_segs.iter().map(|s| s.0.len() as u64).collect();
+        // extra pairs are empty segments, which are written as 0,0
+        other_lens.resize(other_lens.len().max(other_pairs), 0);
+        self.header_and_raw_keywords(
+            tot,
+            data_len,
+            analysis_len,
+            other_lens,
+            false,
+            align,
+            delim,
+            control_chars,
+            provenance,
+        )
+        .map_err(ImpureError::Pure)
             .and_then(|hdr_kws: HeaderKeywordsToWrite<T>| {
-                Ok(hdr_kws.h_write(h, M::Ver::fcs_version().into(), delim, other_segs)?)
+                hdr_kws.h_write(h, M::Ver::fcs_version().into(), delim, other_segs, pad)?;
+                Ok(hdr_kws.gaps)
             })
     }
 
@@ -2164,6 +3125,385 @@ where
             .collect()
     }
 
+    /// Return all keywords with nonstandard measurement keywords grouped.
+    ///
+    /// This is like [`Core::standard_keywords`] except that nonstandard
+    /// keywords linked to a measurement (ie those which matched the nonstandard
+    /// measurement pattern when reading) are returned separately for each
+    /// measurement. This includes the time measurement, so together these
+    /// cover all keywords in TEXT other than $TOT, $NEXTDATA, and offsets.
+    pub fn grouped_keywords(&self) -> GroupedKeywords {
+        let measurements: Vec<HashMap<String, String>> = self
+            .get_meas_nonstandard()
+            .into_iter()
+            .map(|xs| xs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+            .collect();
+        let mut keywords = self.standard_keywords(false, false, false, false);
+        for k in measurements.iter().flat_map(|m| m.keys()) {
+            keywords.remove(k);
+        }
+        GroupedKeywords {
+            keywords,
+            measurements,
+        }
+    }
+
+    /// Return provenance stored in nonstandard keywords if present.
+    pub fn provenance(&self) -> Option<Provenance> {
+        Provenance::from_keywords(&self.metaroot.nonstandard_keywords)
+    }
+
+    /// Set provenance for this dataset.
+    ///
+    /// This will replace any existing provenance keywords, or remove them all
+    /// if None.
+    pub fn set_provenance(&mut self, p: Option<Provenance>) {
+        Provenance::replace_keywords(p.as_ref(), &mut self.metaroot.nonstandard_keywords);
+    }
+
+    /// Return environmental conditions stored in nonstandard keywords.
+    ///
+    /// This is best-effort using the given conventions. The keywords
+    /// themselves are left as-is.
+    pub fn environment(&self, conventions: &[&dyn EnvironmentConvention]) -> Environment {
+        Environment::from_keywords(&self.metaroot.nonstandard_keywords, conventions)
+    }
+
+    /// Return start of acquisition stored in vendor epoch keywords.
+    ///
+    /// This uses the built-in timestamp conventions. The keywords and the
+    /// standard date/time keywords are left as-is.
+    pub fn vendor_timestamp(&self) -> Option<VendorTimestamp> {
+        VendorTimestamp::from_keywords_builtin(&self.metaroot.nonstandard_keywords)
+    }
+
+    /// Return start of acquisition using the given timestamp conventions.
+    pub fn vendor_timestamp_with(
+        &self,
+        conventions: &[&dyn TimestampConvention],
+    ) -> Option<VendorTimestamp> {
+        VendorTimestamp::from_keywords(&self.metaroot.nonstandard_keywords, conventions)
+    }
+
+    /// Fill in start of acquisition from vendor epoch keywords.
+    ///
+    /// This uses the built-in timestamp conventions. See
+    /// [`fill_timestamps_with`](Self::fill_timestamps_with).
+    pub fn fill_timestamps(&mut self) -> Option<VendorTimestamp> {
+        let ts = self.vendor_timestamp()?;
+        self.fill_timestamps_from(ts)
+    }
+
+    /// Fill in start of acquisition using the given timestamp conventions.
+    ///
+    /// $BTIM and $DATE (or $BEGINDATETIME for 3.2) will only be set if no
+    /// start of acquisition is present already. Since epoch timestamps are
+    /// UTC, so will be the filled values. If anything was set, the key from
+    /// which it came will be stored in the "FIREFLOW_TIMESTAMP_SOURCE"
+    /// nonstandard keyword (see [`timestamp_source`](Self::timestamp_source))
+    /// and the timestamp will be returned.
+    pub fn fill_timestamps_with(
+        &mut self,
+        conventions: &[&dyn TimestampConvention],
+    ) -> Option<VendorTimestamp> {
+        let ts = self.vendor_timestamp_with(conventions)?;
+        self.fill_timestamps_from(ts)
+    }
+
+    /// Return the key from which the start of acquisition was filled.
+    ///
+    /// Return None if the start of acquisition was not filled from a vendor
+    /// timestamp.
+    pub fn timestamp_source(&self) -> Option<&str> {
+        PROVENANCE_TIMESTAMP_SOURCE
+            .parse::<NonStdKey>()
+            .ok()
+            .and_then(|k| self.metaroot.nonstandard_keywords.get(&k))
+            .map(String::as_str)
+    }
+
+    fn fill_timestamps_from(&mut self, ts: VendorTimestamp) -> Option<VendorTimestamp> {
+        if !self.metaroot.specific.fill_start_inner(ts.datetime) {
+            return None;
+        }
+        // ASSUME this will never fail since the key is a constant which is a
+        // valid nonstandard key
+        if let Ok(k) = PROVENANCE_TIMESTAMP_SOURCE.parse::<NonStdKey>() {
+            self.metaroot.nonstandard_keywords.insert(k, ts.key.clone());
+        }
+        Some(ts)
+    }
+
+    /// Return the origin of each standard keyword which has a value.
+    ///
+    /// This covers the same keywords as
+    /// [`standard_keywords`](Self::standard_keywords). Keywords which were
+    /// present in TEXT when this was read are [`KeywordOrigin::Present`] and
+    /// all others are [`KeywordOrigin::Defaulted`]. Note that a keyword may
+    /// still be "present" if its value was changed after reading.
+    pub fn keyword_origins(&self) -> HashMap<String, KeywordOrigin> {
+        let present = self.present_key_strings();
+        self.standard_keywords(false, false, false, false)
+            .into_keys()
+            .map(|k| {
+                let o = if present.contains(&k.to_ascii_uppercase()) {
+                    KeywordOrigin::Present
+                } else {
+                    KeywordOrigin::Defaulted
+                };
+                (k, o)
+            })
+            .collect()
+    }
+
+    /// Return the origin of one standard keyword.
+    ///
+    /// Key must include the leading "$" and is case-insensitive. Any key which
+    /// has no value will be [`KeywordOrigin::Absent`].
+    pub fn keyword_origin(&self, key: &str) -> KeywordOrigin {
+        self.keyword_origins()
+            .into_iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map_or(KeywordOrigin::Absent, |(_, o)| o)
+    }
+
+    /// Return keywords defined by extensions to the standard.
+    ///
+    /// See [`StdTextReadConfig::keyword_extensions`].
+    pub fn extension_keywords(&self) -> &StdKeywords {
+        &self.metaroot.extension_keywords
+    }
+
+    /// Set a keyword defined by an extension to the standard.
+    ///
+    /// The value will be checked with the first extension matching `key`.
+    /// Return the previous value if any.
+    pub fn insert_extension_keyword(
+        &mut self,
+        exts: &KeywordExtensions,
+        key: StdKey,
+        value: &str,
+    ) -> Result<Option<String>, InsertExtensionError>
+    where
+        Version: From<M::Ver>,
+    {
+        let v = exts
+            .parse(Version::from(M::Ver::fcs_version()), &key, value)
+            .ok_or_else(|| InsertExtensionError::NoMatch(key.clone()))??;
+        Ok(self.metaroot.extension_keywords.insert(key, v))
+    }
+
+    /// Remove a keyword defined by an extension to the standard.
+    pub fn remove_extension_keyword(&mut self, key: &StdKey) -> Option<String> {
+        self.metaroot.extension_keywords.remove(key)
+    }
+
+    /// Record standard keys which were present in TEXT.
+    ///
+    /// $Pn* keys will be stored with their measurement so they follow it if
+    /// measurements are moved; all other keys are stored with the metaroot.
+    fn record_present_keys(&mut self, keys: Vec<StdKey>) {
+        let mut meas_keys = vec![HashSet::new(); self.measurements.len()];
+        for k in keys {
+            let suffix = k.split_meas().map(|(_, x)| x.to_ascii_uppercase());
+            match (k.meas_index().map(usize::from), suffix) {
+                (Some(i), Some(x)) if i < meas_keys.len() => {
+                    meas_keys[i].insert(x);
+                }
+                _ => {
+                    self.metaroot.present_keys.insert(k);
+                }
+            }
+        }
+        // ASSUME this will never fail since the lengths are equal
+        let _ = self.measurements.alter_values_zip(
+            meas_keys,
+            |m, x| m.value.common.present_keys = x,
+            |m, x| m.value.common.present_keys = x,
+        );
+    }
+
+    fn present_key_strings(&self) -> HashSet<String> {
+        let meas = self.measurements.iter_with(
+            &|i, x| Self::meas_key_strings(i, &x.value.common),
+            &|i, x| Self::meas_key_strings(i, &x.value.common),
+        );
+        self.metaroot
+            .present_keys
+            .iter()
+            .map(|k| k.to_string().to_ascii_uppercase())
+            .chain(meas.flatten())
+            .collect()
+    }
+
+    fn meas_key_strings(i: MeasIndex, m: &CommonMeasurement) -> Vec<String> {
+        let n = IndexFromOne::from(i);
+        m.present_keys.iter().map(|x| format!("$P{n}{x}")).collect()
+    }
+
+    /// Return the changes needed to turn keywords in this into `other`.
+    ///
+    /// This considers all keywords which would be written to TEXT (standard
+    /// and nonstandard) except for $TOT and offsets.
+    pub fn keyword_diff(&self, other: &HashMap<String, String>) -> KeywordDiff {
+        KeywordDiff::new(&self.standard_keywords(false, false, false, false), other)
+    }
+
+    /// Apply a diff to keywords and standardize the result.
+    ///
+    /// Nonstandard keywords belonging to each measurement are set aside so
+    /// they stay with their measurement, and the temporal measurement is kept
+    /// so long as its name does not change.
+    fn apply_diff_text(
+        &self,
+        diff: &KeywordDiff,
+    ) -> DeferredResult<VersionedCoreTEXT<M>, StdTEXTFromRawWarning, ApplyDiffError>
+    where
+        M: LookupMetaroot,
+        M::Temporal: LookupTemporal,
+        M::Optical: LookupOptical,
+        Version: From<M::Ver>,
+        <M::Ver as Versioned>::Layout: VersionedDataLayout,
+    {
+        let mut kws = self.standard_keywords(false, false, false, false);
+        diff.apply(&mut kws)
+            .map_err(|es| DeferredFailure::new2(es.map(ApplyDiffError::Conflict)))?;
+        let meas_nonstd: Vec<HashMap<_, _>> = self
+            .get_meas_nonstandard()
+            .into_iter()
+            .map(|m| {
+                m.keys()
+                    .flat_map(|k| kws.remove(&k.to_string()).map(|v| (k.clone(), v)))
+                    .collect()
+            })
+            .collect();
+        let mut valid = ValidKeywords::default();
+        for (k, v) in kws {
+            if k.starts_with('$') {
+                let sk = k
+                    .parse()
+                    .map_err(|e| DeferredFailure::new1(ApplyDiffError::StdKey(e)))?;
+                let _ = valid.std.insert(sk, v);
+            } else {
+                let nk = k
+                    .parse()
+                    .map_err(|e| DeferredFailure::new1(ApplyDiffError::NonStdKey(e)))?;
+                let _ = valid.nonstd.insert(nk, v);
+            }
+        }
+        let time_meas_pattern = self.temporal().map(|t| {
+            let r = format!("^{}$", regex::escape(t.key.as_ref()));
+            // ASSUME escaped string is always a valid regex
+            TimeMeasNamePattern(regex::Regex::new(&r).unwrap())
+        });
+        let conf = NewCoreTEXTConfig {
+            standard: StdTextReadConfig {
+                time_meas_pattern,
+                ..StdTextReadConfig::default()
+            },
+            ..NewCoreTEXTConfig::default()
+        };
+        VersionedCoreTEXT::<M>::lookup_inner(valid, &conf)
+            .def_errors_into()
+            .def_map_value(|(mut core, _)| {
+                let n = core.par().0;
+                let xs = meas_nonstd
+                    .into_iter()
+                    .chain(iter::repeat_with(HashMap::new))
+                    .take(n)
+                    .collect();
+                // ASSUME this won't fail since the length matches $PAR
+                let _ = core.set_meas_nonstandard(xs);
+                core
+            })
+    }
+
+    /// Return keywords in a namespace with their prefix removed.
+    ///
+    /// This only includes nonstandard keywords not belonging to any
+    /// measurement.
+    pub fn namespace_keywords(&self, ns: &KeyNamespace) -> HashMap<String, String> {
+        ns.select(&self.metaroot.nonstandard_keywords)
+    }
+
+    /// Replace all keywords in a namespace.
+    ///
+    /// Names will be prefixed with the namespace. Nonstandard keywords outside
+    /// the namespace will not be changed. Return error and do nothing if any
+    /// name would not make a valid key.
+    pub fn set_namespace_keywords(
+        &mut self,
+        ns: &KeyNamespace,
+        xs: HashMap<String, String>,
+    ) -> Result<(), NonStdKeyError> {
+        ns.replace(&mut self.metaroot.nonstandard_keywords, xs)
+    }
+
+    /// Check namespaces and their keywords for collisions.
+    ///
+    /// Return error if any two namespaces overlap or if any key in a namespace
+    /// appears more than once in TEXT, which may happen if it is present for
+    /// more than one measurement.
+    pub fn check_namespaces(&self, nss: &[KeyNamespace]) -> MultiResult<(), NamespaceCollision> {
+        let ms = self.get_meas_nonstandard();
+        let ks = self
+            .metaroot
+            .nonstandard_keywords
+            .keys()
+            .chain(ms.iter().flat_map(|m| m.keys()));
+        check_collisions(nss, ks)
+    }
+
+    /// Return summary of the last version conversion if present.
+    ///
+    /// Return error if the summary is present but cannot be parsed.
+    #[cfg(feature = "serde")]
+    pub fn conversion_log(&self) -> Option<Result<ConversionLog, ParseConversionLogError>> {
+        ConversionLog::from_keywords(&self.metaroot.nonstandard_keywords)
+    }
+
+    /// Set summary of the last version conversion, or remove it if None.
+    #[cfg(feature = "serde")]
+    pub fn set_conversion_log(&mut self, log: Option<ConversionLog>) {
+        ConversionLog::replace_keywords(log.as_ref(), &mut self.metaroot.nonstandard_keywords);
+    }
+
+    /// Return keywords from previous versions which were found in TEXT.
+    ///
+    /// These are not part of this version and will not be written, but are
+    /// kept (rather than treated as pseudostandard) so they may be migrated
+    /// explicitly.
+    pub fn legacy(&self) -> &LegacyKeywords
+    where
+        Metaroot<M>: AsRef<LegacyKeywords>,
+    {
+        self.metaroot.as_ref()
+    }
+
+    /// Return $CARRIERID, $CARRIERTYPE, and $LOCATIONID as a [`Carrier`].
+    ///
+    /// Return None if all are missing. Return error if any is blank or if
+    /// $CARRIERTYPE or $LOCATIONID is present without $CARRIERID.
+    pub fn carrier(&self) -> Result<Option<Carrier>, CarrierError>
+    where
+        Metaroot<M>: AsRef<CarrierData>,
+    {
+        AsRef::<CarrierData>::as_ref(&self.metaroot).to_carrier()
+    }
+
+    /// Set $CARRIERID, $CARRIERTYPE, and $LOCATIONID from a [`Carrier`].
+    ///
+    /// All three will be removed if None. Return error and leave these
+    /// unchanged if any value is blank.
+    pub fn set_carrier(&mut self, c: Option<Carrier>) -> Result<(), CarrierError>
+    where
+        Metaroot<M>: AsMut<CarrierData>,
+    {
+        c.as_ref().map_or(Ok(()), Carrier::validate)?;
+        *AsMut::<CarrierData>::as_mut(&mut self.metaroot) = CarrierData::from_carrier(c);
+        Ok(())
+    }
+
     /// Set the $TR keyword.
     ///
     /// Return error if supplied name is not a measurement name (a $PnN).
@@ -2889,22 +4229,94 @@ where
         })
     }
 
-    /// Return $PnE/$PnG (3.0+)
+    /// Return $PnE/$PnG as scale transforms
+    ///
+    /// For 2.0, $PnE is optional and missing values will be returned as
+    /// linear transforms.
     pub fn transforms(&self) -> impl Iterator<Item = ScaleTransform>
     where
-        Optical<M::Optical>: AsRef<ScaleTransform>,
+        M::Optical: AsScaleTransform,
     {
+        self.measurements.iter().map(|(_, x)| {
+            x.both(
+                |_| ScaleTransform::default(),
+                |m| m.value.specific.as_transform(),
+            )
+        })
+    }
+
+    /// Return the name of a measurement for display, ie in a plot axis.
+    ///
+    /// Return None if the index is out of bounds. See
+    /// [`DisplayNameConfig::name`].
+    pub fn display_name(&self, index: MeasIndex, conf: &DisplayNameConfig) -> Option<String> {
         self.measurements
-            .iter()
-            .map(|(_, x)| x.both(|_| ScaleTransform::default(), |m| *m.value.as_ref()))
+            .iter_all_names()
+            .zip(self.meas_opt::<Longname>())
+            .nth(index.into())
+            .map(|(n, s)| conf.name(&n, s.map(|x| x.0.as_str())))
     }
 
-    /// Set $PnE (2.0)
-    pub fn set_scales(
-        &mut self,
-        scales: Vec<Option<Scale>>,
-    ) -> TerminalResult<(), Infallible, SetScalesError, SetScalesFailure>
-    where
+    /// Return names of all measurements for display.
+    ///
+    /// Names will be in the same order as the measurements. See
+    /// [`DisplayNameConfig::name`].
+    pub fn display_names(&self, conf: &DisplayNameConfig) -> Vec<String> {
+        self.measurements
+            .iter_all_names()
+            .zip(self.meas_opt::<Longname>())
+            .map(|(n, s)| conf.name(&n, s.map(|x| x.0.as_str())))
+            .collect()
+    }
+
+    /// Group measurements by the kind of signal they hold.
+    ///
+    /// The time measurement will always be in the time group. Others will be
+    /// inferred from $PnTYPE (3.2) and $PnN. See [`ChannelKind::infer`].
+    pub fn channel_groups(&self) -> ChannelGroups
+    where
+        M::Optical: AsOpticalType,
+    {
+        let kinds = self.measurements.iter_with(&|_, _| ChannelKind::Time, &|i, m| {
+            let name = M::Name::as_opt(&m.key).cloned().unwrap_or(i.into());
+            ChannelKind::infer(&name, m.value.specific.as_optical_type())
+        });
+        ChannelGroups::from_kinds(self.all_shortnames().into_iter().zip(kinds))
+    }
+
+    /// Return metadata for each column in the layout.
+    ///
+    /// Order will match that of the measurements and DATA.
+    pub fn column_metadata(&self) -> Vec<ColumnMetadata>
+    where
+        M::Optical: AsScaleTransform,
+    {
+        self.meas_opt::<Longname>()
+            .zip(
+                self.measurements
+                    .iter_with(&|_, t| t.value.as_transform(), &|_, m| {
+                        m.value.as_transform()
+                    }),
+            )
+            .zip(self.layout.datatypes())
+            .zip(self.layout.ranges())
+            .map(
+                |(((longname, transform), datatype), range)| ColumnMetadata {
+                    longname: longname.cloned(),
+                    datatype,
+                    range,
+                    transform,
+                },
+            )
+            .collect()
+    }
+
+    /// Set $PnE (2.0)
+    pub fn set_scales(
+        &mut self,
+        scales: Vec<Option<Scale>>,
+    ) -> TerminalResult<(), Infallible, SetScalesError, SetScalesFailure>
+    where
         M::Optical: HasScale,
     {
         let go = || {
@@ -2936,6 +4348,33 @@ where
         go().mult_terminate(SetScalesFailure)
     }
 
+    /// Set $PnE from scale transforms (2.0)
+    ///
+    /// Since 2.0 has no $PnG, linear transforms must have a gain of 1.0.
+    pub fn set_scales_from_transforms(
+        &mut self,
+        xforms: Vec<ScaleTransform>,
+    ) -> TerminalResult<(), Infallible, SetScalesError, SetScalesFailure>
+    where
+        M::Optical: HasScale,
+    {
+        let scales = xforms
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| match x {
+                ScaleTransform::Lin(g) if g == PositiveFloat::one() => Ok(Some(Scale::Linear)),
+                ScaleTransform::Lin(gain) => Err(ScaleGainError {
+                    index: i.into(),
+                    gain,
+                }
+                .into()),
+                ScaleTransform::Log(l) => Ok(Some(Scale::Log(l))),
+            })
+            .gather()
+            .mult_terminate(SetScalesFailure)?;
+        self.set_scales(scales.inner())
+    }
+
     /// Set $PnE/$PnG (3.0+)
     pub fn set_transforms(
         &mut self,
@@ -3008,10 +4447,14 @@ where
     ///
     /// Conversion may fail if some required keywords in the target version
     /// are not present in current version.
+    ///
+    /// See [`ConvertConfig`] for options; deprecated keywords are removed
+    /// with [`Self::strip_deprecated`] and the conversion summary is available
+    /// with [`Self::conversion_log`].
     #[allow(clippy::type_complexity)]
     pub fn try_convert<ToM>(
         self,
-        force: bool,
+        conf: &ConvertConfig,
     ) -> TerminalResult<
         VersionedCore<A, D, O, ToM>,
         MetarootConvertWarning,
@@ -3034,6 +4477,11 @@ where
         <ToM::Name as MightHave>::Wrapper<Shortname>:
             TryFrom<<M::Name as MightHave>::Wrapper<Shortname>>,
     {
+        let force = conf.force;
+        #[cfg(feature = "serde")]
+        let before = conf
+            .record_conversion
+            .then(|| self.standard_keywords(false, false, false, false));
         let m = self
             .metaroot
             .try_convert(force)
@@ -3065,6 +4513,25 @@ where
                 analysis: self.analysis,
                 others: self.others,
             })
+            .def_and_tentatively(|mut core| {
+                let ws = if conf.strip_deprecated {
+                    core.strip_deprecated(conf.timestamp_offset)
+                } else {
+                    vec![]
+                };
+                #[cfg(feature = "serde")]
+                if let Some(b) = before {
+                    let after = core.standard_keywords(false, false, false, false);
+                    let log = ConversionLog::new(
+                        M::Ver::fcs_version().into(),
+                        ToM::Ver::fcs_version().into(),
+                        &b,
+                        &after,
+                    );
+                    core.set_conversion_log(Some(log));
+                }
+                Tentative::new(core, ws.into_iter().map(|w| w.into()).collect(), vec![])
+            })
             .def_map_errors(|error| ConvertError {
                 from: M::Ver::fcs_version().into(),
                 to: ToM::Ver::fcs_version().into(),
@@ -3142,6 +4609,151 @@ where
         Ok(ret)
     }
 
+    fn remove_measurement_and_links_inner(
+        &mut self,
+        name: &Shortname,
+    ) -> Result<MeasRemoval, KeyNotFoundError> {
+        let index = self.measurements.find_name(name)?;
+        let before: RawKeywords = self.opt_root_keywords().collect();
+        self.metaroot.remove_meas_links(index, name);
+        // ASSUME this won't fail since we just found the name
+        let _ = self.measurements.remove_name(name)?;
+        self.layout.remove_nocheck(index);
+        let after: RawKeywords = self.opt_root_keywords().collect();
+        Ok(MeasRemoval {
+            index,
+            links: KeywordDiff::new(&before, &after),
+        })
+    }
+
+    /// Reorder measurements and layout to match a reference list of names.
+    ///
+    /// Return the new order such that the ith measurement was previously at
+    /// the ith index in this vector.
+    fn align_to_panel_inner(
+        &mut self,
+        reference: &[Shortname],
+        allow_missing: bool,
+        allow_extra: bool,
+    ) -> Result<Vec<MeasIndex>, AlignError> {
+        let names = self.all_shortnames();
+        let mut seen = HashSet::new();
+        let dups: Vec<_> = reference
+            .iter()
+            .filter(|n| !seen.insert(*n))
+            .cloned()
+            .collect();
+        if let Some(ds) = NonEmpty::from_vec(dups) {
+            return Err(AlignDuplicateError(ds).into());
+        }
+        let positions: HashMap<_, _> = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n, MeasIndex::from(i)))
+            .collect();
+        let mut order = vec![];
+        let mut missing = vec![];
+        for n in reference {
+            if let Some(i) = positions.get(n) {
+                order.push(*i);
+            } else {
+                missing.push(n.clone());
+            }
+        }
+        if let Some(ms) = NonEmpty::from_vec(missing).filter(|_| !allow_missing) {
+            return Err(AlignMissingError(ms).into());
+        }
+        let extra: Vec<_> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !reference.contains(n))
+            .collect();
+        if let Some(es) = NonEmpty::from_vec(extra.iter().map(|(_, n)| (*n).clone()).collect())
+            .filter(|_| !allow_extra)
+        {
+            return Err(AlignExtraError(es).into());
+        }
+        order.extend(extra.into_iter().map(|(i, _)| MeasIndex::from(i)));
+        let moved: HashSet<_> = order
+            .iter()
+            .enumerate()
+            .filter(|(i, j)| MeasIndex::from(*i) != **j)
+            .map(|(_, j)| *j)
+            .collect();
+        if !moved.is_empty() {
+            self.metaroot
+                .specific
+                .check_meas_index_links_inner(&moved)?;
+        }
+        self.measurements.reorder_nocheck(&order);
+        self.layout.reorder_nocheck(&order);
+        Ok(order)
+    }
+
+    /// Find measurements whose $PnN match a pattern.
+    ///
+    /// Return a mask which is true for each measurement to be excluded.
+    /// Matching measurements which are referenced by other keywords cannot be
+    /// removed and will be returned as warnings instead.
+    fn excluded_measurements(
+        &self,
+        pat: &ExcludeMeasNamePattern,
+    ) -> (Vec<bool>, Vec<ExcludeMeasWarning>) {
+        let mut mask = vec![false; self.par().0];
+        let mut ws = vec![];
+        for (i, name) in self.measurement_indexed_names() {
+            if pat.0.is_match(name.as_ref()) {
+                match self.metaroot.check_meas_links(&[(i, name)]) {
+                    Ok(()) => mask[usize::from(i)] = true,
+                    Err(error) => ws.push(ExcludeMeasWarning {
+                        name: name.clone(),
+                        error,
+                    }),
+                }
+            }
+        }
+        (mask, ws)
+    }
+
+    /// Return the column of the time measurement in DATA and its timestep.
+    ///
+    /// The column is its index after removing any excluded measurements
+    /// (see [`Self::excluded_measurements`]). Return None if there is no time
+    /// measurement or it is excluded.
+    fn time_column_position(&self, exclude: &[bool]) -> Option<(usize, f64)> {
+        let t = self.temporal()?;
+        let i = usize::from(t.index);
+        if exclude.get(i).copied().unwrap_or(false) {
+            return None;
+        }
+        let skipped = exclude.iter().take(i).filter(|x| **x).count();
+        Some((i - skipped, t.value.specific.timestep_seconds()))
+    }
+
+    /// Remove all measurements flagged in a mask.
+    ///
+    /// The mask should be made with [`Self::excluded_measurements`], in which
+    /// case this should not fail.
+    fn remove_excluded_measurements(
+        &mut self,
+        mask: &[bool],
+    ) -> Result<(), RemoveMeasByIndexError> {
+        for (i, _) in mask.iter().enumerate().rev().filter(|(_, x)| **x) {
+            self.remove_measurement_by_index_inner(i.into())?;
+        }
+        Ok(())
+    }
+
+    /// Remove measurements whose $PnN match a pattern.
+    pub(crate) fn exclude_measurements(
+        &mut self,
+        pat: &ExcludeMeasNamePattern,
+    ) -> Result<Vec<ExcludeMeasWarning>, RemoveMeasByIndexError> {
+        let (mask, ws) = self.excluded_measurements(pat);
+        self.remove_excluded_measurements(&mask)?;
+        Ok(ws)
+    }
+
     fn push_temporal_inner(
         &mut self,
         n: Shortname,
@@ -3189,6 +4801,20 @@ where
             .def_and_tentatively(|ret| self.layout.push(r, notrunc).errors_into().map(|_| ret))
     }
 
+    /// Return copy of optical measurement at index to use as a template.
+    ///
+    /// $PnS, nonstandard keywords, and unparsed keywords will not be copied
+    /// since these usually only describe one measurement.
+    fn optical_template(&self, index: MeasIndex) -> Result<Optical<M::Optical>, ElementIndexError>
+    where
+        M::Optical: Clone,
+    {
+        self.measurements.get_non_center(index).map(|m| Optical {
+            common: CommonMeasurement::default(),
+            ..m.clone()
+        })
+    }
+
     fn insert_optical_inner(
         &mut self,
         i: MeasIndex,
@@ -3306,6 +4932,7 @@ where
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn header_and_raw_keywords<T>(
         &self,
         tot: Tot,
@@ -3313,19 +4940,35 @@ where
         analysis_len: u64,
         other_lens: Vec<u64>,
         has_nextdata: bool,
+        align: u64,
+        delim: TEXTDelim,
+        control_chars: ControlCharPolicy,
+        provenance: bool,
     ) -> Result<HeaderKeywordsToWrite<T>, Uint8DigitOverflow>
     where
         Version: From<M::Ver>,
         T: TryFrom<u64, Error = Uint8DigitOverflow> + HeaderString,
     {
+        let encode = |(k, v)| (k, encode_value(v, delim, control_chars));
+        // replace whatever software was recorded before with this library
+        let software = provenance.then(Provenance::software_pair);
+        let is_software = |k: &String| {
+            software
+                .as_ref()
+                .is_some_and(|(sk, _)| k.eq_ignore_ascii_case(sk))
+        };
         let req: Vec<_> = self
             .req_root_keywords()
             .chain([ReqMetarootKey::pair(&tot)])
             .chain(self.req_meas_keywords())
+            .map(encode)
             .collect();
         let opt: Vec<_> = self
             .opt_root_keywords()
+            .filter(|(k, _)| !is_software(k))
             .chain(self.opt_meas_keywords())
+            .chain(software.clone())
+            .map(encode)
             .collect();
         if Version::from(M::Ver::fcs_version()) == Version::FCS2_0 {
             HeaderKeywordsToWrite::new_2_0(
@@ -3335,6 +4978,7 @@ where
                 analysis_len,
                 other_lens,
                 has_nextdata,
+                align,
             )
         } else {
             HeaderKeywordsToWrite::new_3_0(
@@ -3344,6 +4988,7 @@ where
                 analysis_len,
                 other_lens,
                 has_nextdata,
+                align,
             )
         }
     }
@@ -3355,7 +5000,8 @@ where
             None
         };
         let lv = self.layout.opt_meas_keywords();
-        self.measurements
+        let xs: Vec<_> = self
+            .measurements
             .iter_with(
                 &|i, x| Temporal::opt_meas_keywords(&x.value, i).collect::<Vec<_>>(),
                 &|i, x| Optical::all_opt_keywords(&x.value, i).collect(),
@@ -3367,6 +5013,18 @@ where
                     .flatten()
                     .flat_map(|(k, v)| v.map(|x| (k, x))),
             )
+            .collect();
+        let raw: Vec<_> = self
+            .measurements
+            .iter()
+            .flat_map(|(i, x)| {
+                x.both(
+                    |t| t.value.common.raw_keywords(i).collect::<Vec<_>>(),
+                    |o| o.value.common.raw_keywords(i).collect(),
+                )
+            })
+            .collect();
+        with_raw_keywords(xs, raw)
     }
 
     fn req_meas_keywords(&self) -> impl Iterator<Item = (String, String)> {
@@ -3398,7 +5056,13 @@ where
     }
 
     fn opt_root_keywords(&self) -> impl Iterator<Item = (String, String)> {
-        Metaroot::all_opt_keywords(&self.metaroot)
+        let raw: Vec<_> = self
+            .metaroot
+            .raw_values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        with_raw_keywords(Metaroot::all_opt_keywords(&self.metaroot).collect(), raw)
     }
 
     fn shortname_keywords(&self) -> impl Iterator<Item = (String, String)> {
@@ -3698,16 +5362,41 @@ where
         C: AsRef<StdTextReadConfig> + AsRef<ReadLayoutConfig> + AsRef<SharedConfig>,
     {
         let sconf: &SharedConfig = conf.as_ref();
+        let _limit = IssueLimit::new(sconf);
         Self::lookup_inner(kws, conf)
             .def_errors_into()
             .def_map_value(|(x, _)| x)
-            .def_terminate_maybe_warn(
+            .def_terminate_shared(
                 CoreTEXTFromKeywordsFailure,
-                sconf.warnings_are_errors,
+                sconf,
                 |w| w.into(),
             )
     }
 
+    /// Apply changes to keywords.
+    ///
+    /// The diff is applied to all keywords which would be written to TEXT
+    /// (see [`VersionedCore::keyword_diff`]) and the result is standardized as
+    /// if read from a file of the same version. Nonstandard keywords belonging
+    /// to measurements will stay with their measurements. If the diff
+    /// conflicts with the current keywords or the result cannot be
+    /// standardized, nothing will change.
+    pub fn apply_diff(
+        &mut self,
+        diff: &KeywordDiff,
+    ) -> TerminalResult<(), StdTEXTFromRawWarning, ApplyDiffError, ApplyDiffFailure>
+    where
+        M: LookupMetaroot,
+        M::Temporal: LookupTemporal,
+        M::Optical: LookupOptical,
+        Version: From<M::Ver>,
+        <M::Ver as Versioned>::Layout: VersionedDataLayout,
+    {
+        self.apply_diff_text(diff)
+            .def_map_value(|new| *self = new)
+            .def_terminate(ApplyDiffFailure)
+    }
+
     fn lookup_inner<C>(
         mut kws: ValidKeywords,
         conf: &C,
@@ -3727,6 +5416,9 @@ where
         let _ = kws.std.remove(&Beginstext::std());
         let _ = kws.std.remove(&Endstext::std());
 
+        // Remember which keys were in TEXT before they are consumed below
+        let present: Vec<_> = kws.std.keys().cloned().collect();
+
         // Lookup $PAR first since we need this to get the measurements
         let par_res = Par::lookup_req(&mut kws.std).def_inner_into();
 
@@ -3734,6 +5426,19 @@ where
         let std_conf = conf.as_ref();
 
         par_res.def_and_maybe(|par| {
+            // Pull out any $Pn* keywords which cannot belong to a measurement
+            // (index is 0, exceeds $PAR, or is zero-padded) so that they are
+            // dealt with according to their own policy below
+            let orphans: Vec<_> = kws
+                .std
+                .keys()
+                .filter_map(|k| OrphanMeasKeyError::from_key(k, par, &kws.std))
+                .collect();
+            let orphan_pairs: Vec<_> = orphans
+                .into_iter()
+                .filter_map(|e| kws.std.remove(&e.key).map(|v| (e, v)))
+                .collect();
+
             // Lookup measurements/layout/metaroot with $PAR
             let ns: Vec<_> = kws.nonstd.into_iter().collect();
             let meas_res =
@@ -3746,7 +5451,14 @@ where
                 .def_zip(layout_res)
                 .def_and_maybe(|((ms, meta_ns), layout)| {
                     Metaroot::lookup_metaroot(&mut kws.std, &ms, meta_ns, std_conf)
-                        .def_map_value(|metaroot| CoreTEXT::new_unchecked(metaroot, ms, layout))
+                        .def_map_value(|metaroot| {
+                            let mut core = CoreTEXT::new_unchecked(metaroot, ms, layout);
+                            core.record_present_keys(present);
+                            if std_conf.timestamps_from_conventions {
+                                let _ = core.fill_timestamps();
+                            }
+                            core
+                        })
                         .def_inner_into()
                 })
                 .map(|mut tnt_core| {
@@ -3764,7 +5476,18 @@ where
                         None
                     });
 
-                    let esks = match version {
+                    // Extensions may only claim keys which are not defined
+                    // by this version, so look these up last
+                    let ext = std_conf
+                        .keyword_extensions
+                        .lookup(version, &mut kws.std)
+                        .warnings_into();
+                    tnt_core = tnt_core.zip_with(ext, |mut core, e| {
+                        core.metaroot.extension_keywords = e;
+                        core
+                    });
+
+                    let mut esks = match version {
                         Version::FCS2_0 => ExtraStdKeywords::split_2_0(kws.std),
                         Version::FCS3_0 => ExtraStdKeywords::split_3_0(kws.std),
                         Version::FCS3_1 => ExtraStdKeywords::split_3_1(kws.std),
@@ -3777,11 +5500,75 @@ where
                     let us = esks.unused.keys().cloned().map(UnusedStandardError);
                     tnt_core.extend_errors_or_warnings(us, std_conf.allow_unused_standard);
 
-                    tnt_core.map(|x| (x, esks))
+                    let absorb = std_conf.absorb_orphan_measurement_keywords;
+                    let disallow = std_conf.disallow_orphan_measurement_keywords;
+                    tnt_core.and_tentatively(|mut core| {
+                        let mut tnt = Tentative::new1(());
+                        for (e, v) in orphan_pairs {
+                            if absorb {
+                                let s: &str = e.key.as_ref();
+                                // ASSUME this will never fail since the key is
+                                // already valid and only has its '$' removed
+                                if let Ok(k) = s.parse::<NonStdKey>()
+                                    && !core.metaroot.nonstandard_keywords.contains_key(&k)
+                                {
+                                    core.metaroot.nonstandard_keywords.insert(k, v);
+                                    continue;
+                                }
+                            }
+                            esks.unused.insert(e.key.clone(), v);
+                            tnt.push_error_or_warning(e, disallow);
+                        }
+                        tnt.map(|()| (core, esks))
+                    })
                 })
         })
     }
 
+    /// Add `n` optical measurements named 'FL1', 'FL2', etc with range 1024.
+    fn push_example_measurements<F>(&mut self, n: usize, f: F)
+    where
+        F: Fn() -> Optical<M::Optical>,
+    {
+        for i in 1..=n {
+            let name = Shortname::new_unchecked(format!("FL{i}"));
+            // ASSUME this will never fail since names are unique and the range
+            // can be represented by any datatype
+            self.push_optical_inner(M::Name::wrap(name), f(), Range::from(1024_u64), false)
+                .ok()
+                .unwrap();
+        }
+    }
+
+    /// Estimate the number of bytes needed to read DATA into a dataframe.
+    ///
+    /// This is the number of events times the sum of the native widths of
+    /// each column (ie a 3-byte integer will be read as a u32 and take 4
+    /// bytes). The number of events is taken from `tot` if given and
+    /// otherwise computed from the length of `data`. Return None if the
+    /// number of events cannot be determined (delimited ASCII without $TOT).
+    pub fn estimated_memory_bytes(&self, tot: Option<Tot>, data: AnyDataSegment) -> Option<u64> {
+        self.layout
+            .estimated_memory_bytes(tot.map(|t| t.0 as u64), data)
+    }
+
+    /// Reorder measurements to match a reference list of names.
+    ///
+    /// Measurements not in the reference will be appended in their original
+    /// order if `allow_extra` is true, and names in the reference which are
+    /// not present will be skipped if `allow_missing` is true; otherwise
+    /// either will be an error. Fail if any keywords refer to measurements by
+    /// index (ie $COMP) and these measurements would be moved.
+    pub fn align_to_panel(
+        &mut self,
+        reference: &[Shortname],
+        allow_missing: bool,
+        allow_extra: bool,
+    ) -> Result<(), AlignError> {
+        self.align_to_panel_inner(reference, allow_missing, allow_extra)
+            .map(|_| ())
+    }
+
     /// Remove a measurement matching the given name.
     ///
     /// Return removed measurement and its index if found.
@@ -3813,6 +5600,26 @@ where
         self.remove_measurement_by_index_inner(index)
     }
 
+    /// Remove a measurement and update everything which refers to it.
+    ///
+    /// Unlike [`Self::remove_measurement_by_name`], this will not fail if
+    /// other keywords link to this measurement. Instead, it will be dropped
+    /// from $SPILLOVER/$COMP (removing these entirely if they would have less
+    /// than two measurements), $UNSTAINEDCENTERS, and $TR. Gating regions
+    /// which refer to it will be removed (along with $GATING if it refers to
+    /// these regions), and all other regions will be shifted to point to the
+    /// same measurements as before. The layout is updated as well.
+    ///
+    /// Return the index of the removed measurement and changes to all
+    /// non-measurement keywords. Return error if `name` is not found, in
+    /// which case nothing will be changed.
+    pub fn remove_measurement_and_links(
+        &mut self,
+        name: &Shortname,
+    ) -> Result<MeasRemoval, KeyNotFoundError> {
+        self.remove_measurement_and_links_inner(name)
+    }
+
     /// Add time measurement to the end of the measurement vector.
     ///
     /// Return error if time measurement already exists or name is non-unique.
@@ -3857,6 +5664,28 @@ where
             .def_terminate(PushOpticalFailure)
     }
 
+    /// Add optical measurement to the end using another as a template.
+    ///
+    /// The new measurement will be a copy of the optical measurement at
+    /// `template` (ie scale, display, detector voltage, etc) except for $PnS
+    /// and nonstandard keywords. Return error if `template` is out of bounds
+    /// or refers to the time measurement, or if name is non-unique.
+    pub fn push_optical_like(
+        &mut self,
+        n: <M::Name as MightHave>::Wrapper<Shortname>,
+        template: MeasIndex,
+        r: Range,
+        notrunc: bool,
+    ) -> TerminalResult<Shortname, AnyRangeError, PushOpticalLikeError, PushOpticalFailure>
+    where
+        M::Optical: Clone,
+    {
+        self.optical_template(template)
+            .into_deferred()
+            .def_and_maybe(|m| self.push_optical_inner(n, m, r, notrunc).def_errors_into())
+            .def_terminate(PushOpticalFailure)
+    }
+
     /// Add optical measurement at a given position
     ///
     /// Return error if name is non-unique, or index is out of bounds.
@@ -3918,6 +5747,37 @@ where
     M::Name: Clone,
     <M::Ver as Versioned>::Layout: VersionedDataLayout,
 {
+    /// Apply changes to keywords.
+    ///
+    /// This is like [`VersionedCoreTEXT::apply_diff`] except the result must
+    /// have the same number of measurements as there are columns in DATA.
+    /// DATA, ANALYSIS, and OTHER will not be changed.
+    pub fn apply_diff(
+        &mut self,
+        diff: &KeywordDiff,
+    ) -> TerminalResult<(), StdTEXTFromRawWarning, ApplyDiffError, ApplyDiffFailure>
+    where
+        M: LookupMetaroot,
+        M::Temporal: LookupTemporal,
+        M::Optical: LookupOptical,
+        Version: From<M::Ver>,
+    {
+        self.apply_diff_text(diff)
+            .def_and_maybe(|new| {
+                let data_n = self.data.ncols();
+                let meas_n = new.par().0;
+                if data_n != meas_n && !self.data.is_empty() {
+                    let e = MeasDataMismatchError { meas_n, data_n };
+                    return Err(DeferredFailure::new1(e.into()));
+                }
+                self.metaroot = new.metaroot;
+                self.measurements = new.measurements;
+                self.layout = new.layout;
+                Ok(Tentative::default())
+            })
+            .def_terminate(ApplyDiffFailure)
+    }
+
     pub fn new_from_keywords<C>(
         p: PathBuf,
         kws: ValidKeywords,
@@ -3944,6 +5804,7 @@ where
             + AsRef<SharedConfig>,
     {
         let sconf: &SharedConfig = conf.as_ref();
+        let _limit = IssueLimit::new(sconf);
         File::options()
             .read(true)
             .open(p)
@@ -3965,7 +5826,7 @@ where
                         )
                     })
             })
-            .def_terminate_maybe_warn(StdDatasetWithKwsFailure, sconf.warnings_are_errors, |w| {
+            .def_terminate_shared(StdDatasetWithKwsFailure, sconf, |w| {
                 ImpureError::Pure(StdDatasetFromRawError::from(w))
             })
     }
@@ -3999,21 +5860,42 @@ where
             .def_map_errors(Box::new)
             .def_inner_into()
             .def_errors_liftio()
-            .def_and_maybe(|(text, extra, offsets)| {
+            .def_and_maybe(|(mut text, extra, offsets)| {
                 let or = OthersReader { segs: other_segs };
                 let ar = AnalysisReader {
                     seg: *offsets.as_ref(),
                 };
                 let read_conf: &ReaderConfig = st.conf.as_ref();
-                let data_res = text
+                let std_conf: &StdTextReadConfig = st.conf.as_ref();
+                // Find measurements to exclude before reading DATA so their
+                // columns can be skipped, then remove them from TEXT after
+                // since the full layout is needed to read DATA.
+                let (exclude, exclude_ws) = std_conf
+                    .exclude_measurement_pattern
+                    .as_ref()
+                    .map(|p| text.excluded_measurements(p))
+                    .unwrap_or_default();
+                let time = text.time_column_position(&exclude);
+                let mut data_res = text
                     .layout
-                    .h_read_df(h, offsets.tot(), *offsets.as_ref(), read_conf)
+                    .h_read_df(h, offsets.tot(), *offsets.as_ref(), read_conf, &exclude, time)
                     .def_warnings_into()
                     .def_map_errors(|e| e.inner_into());
+                for w in exclude_ws {
+                    data_res.def_push_warning(StdTEXTFromRawWarning::Exclude(w).into());
+                }
+                let exclude_res = text
+                    .remove_excluded_measurements(&exclude)
+                    .map_err(|e| {
+                        ImpureError::Pure(StdDatasetFromRawError::TEXT(Box::new(e.into())))
+                    })
+                    .into_deferred();
                 let analysis_res = ar.h_read(h).into_deferred();
                 let others_res = or.h_read(h).into_deferred();
-                data_res.def_zip3(analysis_res, others_res).def_map_value(
-                    |(data, analysis, others)| {
+                data_res
+                    .def_zip(exclude_res)
+                    .def_zip3(analysis_res, others_res)
+                    .def_map_value(|((data, ()), analysis, others)| {
                         let c = Core {
                             metaroot: text.metaroot,
                             measurements: text.measurements,
@@ -4029,71 +5911,463 @@ where
     }
 
     /// Write this dataset (HEADER+TEXT+DATA+ANALYSIS+OTHER) to a handle
+    ///
+    /// Return the number of values in each column which were changed to fit
+    /// their measurement type. This is most useful when
+    /// `skip_conversion_check` is true, since otherwise any such loss would
+    /// be caught before writing.
     pub fn h_write_dataset<W: Write>(
         &self,
         h: &mut BufWriter<W>,
         conf: &WriteConfig,
-    ) -> IOTerminalResult<(), StdWriterWarning, StdWriterError, WriteDatasetFailure>
+    ) -> IOTerminalResult<WriteReport, StdWriterWarning, StdWriterError, WriteDatasetFailure>
     where
         Version: From<M::Ver>,
     {
-        let df = &self.data;
         let layout = &self.layout;
+        let (rounded, rounding) = self.round_floats(conf);
+        let df = rounded.as_ref().unwrap_or(&self.data);
         let delim = conf.delim;
         let tot = Tot(df.nrows());
         let analysis_len = self.analysis.0.len() as u64;
         let others = &self.others.0[..];
 
-        let check_res = if conf.skip_conversion_check {
-            Ok(Tentative::default())
-        } else {
-            layout
-                .check_writer(df)
-                .map_err(DeferredFailure::new2)
-                .map(|()| Tentative::default())
-                .def_errors_into()
-                .def_errors_liftio()
-        };
+        let other_pairs = conf.other_pairs.unwrap_or(others.len());
 
-        check_res
+        self.check_writable(df, conf)
+            .def_errors_liftio()
             .def_and_maybe(|()| {
                 let data_len = layout.nbytes(df);
-                if conf.big_other {
+                let align = conf.align();
+                let pad = conf.padding;
+                let gaps = if conf.big_other {
                     self.h_write_text_inner::<_, UintSpacePad20>(
                         h,
                         delim,
+                        conf.control_chars,
+                        conf.record_provenance,
                         tot,
                         data_len,
                         analysis_len,
                         others,
+                        other_pairs,
+                        align,
+                        pad,
                     )
                 } else {
                     self.h_write_text_inner::<_, UintSpacePad8>(
                         h,
                         delim,
+                        conf.control_chars,
+                        conf.record_provenance,
                         tot,
                         data_len,
                         analysis_len,
                         others,
+                        other_pairs,
+                        align,
+                        pad,
                     )
                 }
                 .map_err(|e| e.inner_into())
                 .map_err(DeferredFailure::new1)?;
 
+                gaps.h_write_data(h, pad).into_deferred()?;
+
                 // write DATA; conversion check flag is flipped from above since
                 // we want to emit warnings as we are writing if we did not run
                 // through the data once at the beginning and check for
                 // conversion loss.
-                layout
+                let report = layout
                     .h_write_df(h, df, !conf.skip_conversion_check)
-                    .def_warnings_into()?;
+                    .def_warnings_into()?
+                    .map(|r| WriteReport { rounding, ..r });
 
                 // write ANALYSIS
-                h.write_all(&self.analysis.0).into_deferred()
+                gaps.h_write_analysis(h, pad).into_deferred()?;
+                h.write_all(&self.analysis.0).into_deferred()?;
+                Ok(report)
+            })
+            .def_terminate(WriteDatasetFailure)
+    }
+
+    /// Return the byte layout which would be used to write this dataset.
+    ///
+    /// This performs the same checks as [`Self::h_write_dataset`] (including
+    /// the conversion check unless it is skipped) but writes nothing. The
+    /// returned plan has the offset and length of each segment, the total
+    /// number of bytes, all keywords to be written, and any rounding which
+    /// would be applied to DATA. This is useful for reserving storage or
+    /// rejecting outputs which are too large before committing to a write.
+    pub fn plan_write(
+        &self,
+        conf: &WriteConfig,
+    ) -> TerminalResult<WritePlan, StdWriterWarning, StdWriterError, WriteDatasetFailure>
+    where
+        Version: From<M::Ver>,
+    {
+        let (rounded, rounding) = self.round_floats(conf);
+        let df = rounded.as_ref().unwrap_or(&self.data);
+        let tot = Tot(df.nrows());
+        let data_len = self.layout.nbytes(df);
+        let analysis_len = self.analysis.0.len() as u64;
+        let mut other_lens: Vec<_> = self.others.0.iter().map(|s| s.0.len() as u64).collect();
+        other_lens.resize(other_lens.len().max(conf.other_pairs.unwrap_or(0)), 0);
+        let version = M::Ver::fcs_version().into();
+        let align = conf.align();
+        self.check_writable(df, conf)
+            .def_and_maybe(|()| {
+                if conf.big_other {
+                    self.header_and_raw_keywords::<UintSpacePad20>(
+                        tot,
+                        data_len,
+                        analysis_len,
+                        other_lens,
+                        false,
+                        align,
+                        conf.delim,
+                        conf.control_chars,
+                        conf.record_provenance,
+                    )
+                    .map(|x| x.into_plan(version, data_len, analysis_len, rounding))
+                } else {
+                    self.header_and_raw_keywords::<UintSpacePad8>(
+                        tot,
+                        data_len,
+                        analysis_len,
+                        other_lens,
+                        false,
+                        align,
+                        conf.delim,
+                        conf.control_chars,
+                        conf.record_provenance,
+                    )
+                    .map(|x| x.into_plan(version, data_len, analysis_len, rounding))
+                }
+                .map_err(|e| DeferredFailure::new1(e.into()))
+                .map(Tentative::new1)
             })
             .def_terminate(WriteDatasetFailure)
     }
 
+    /// Check that this dataset can be written with the given configuration.
+    ///
+    /// `df` is DATA after any rounding has been applied.
+    fn check_writable(
+        &self,
+        df: &FCSDataFrame,
+        conf: &WriteConfig,
+    ) -> DeferredResult<(), StdWriterWarning, StdWriterError> {
+        let nothers = self.others.0.len();
+        let other_pairs = conf.other_pairs.unwrap_or(nothers);
+        let policy = conf.control_chars;
+        let ctrl_ws: Vec<_> = if policy == ControlCharPolicy::Preserve {
+            vec![]
+        } else {
+            self.standard_keywords(false, false, false, false)
+                .into_iter()
+                .filter(|(_, v)| policy.apply(v).is_some_and(|x| !x.is_empty()))
+                .map(|(key, _)| ControlCharWarning { key, policy }.into())
+                .collect()
+        };
+        if let Err(es) = self.check_namespaces(&conf.namespaces) {
+            Err(DeferredFailure::new2(es.map(StdWriterError::Namespace)))
+        } else if nothers > other_pairs {
+            let e = TooManyOtherError {
+                segments: nothers,
+                pairs: other_pairs,
+            };
+            Err(DeferredFailure::new1(StdWriterError::from(e)))
+        } else if conf.skip_conversion_check {
+            Ok(Tentative::new((), ctrl_ws, vec![]))
+        } else {
+            self.layout
+                .check_writer(df)
+                .map_err(DeferredFailure::new2)
+                .map(|()| Tentative::new((), ctrl_ws, vec![]))
+                .def_errors_into()
+        }
+    }
+
+    /// Apply rounding to each float column which will be written as integers.
+    ///
+    /// Return a new dataframe if any column was changed and the rounding used
+    /// for each column.
+    fn round_floats(
+        &self,
+        conf: &WriteConfig,
+    ) -> (Option<FCSDataFrame>, Vec<Option<FloatRounding>>) {
+        if self.data.is_empty() {
+            return (None, vec![]);
+        }
+        let rounding: Vec<_> = self
+            .layout
+            .datatypes()
+            .into_iter()
+            .zip(self.data.iter_columns())
+            .enumerate()
+            .map(|(i, (dt, c))| {
+                let is_int = matches!(dt, AlphaNumType::Ascii | AlphaNumType::Integer);
+                (is_int && c.is_float()).then(|| conf.float_rounding_for(i.into()))
+            })
+            .collect();
+        let rounded = rounding
+            .iter()
+            .any(|r| r.is_some_and(|x| x != FloatRounding::Error))
+            .then(|| {
+                let cs = self
+                    .data
+                    .iter_columns()
+                    .zip(&rounding)
+                    .map(|(c, r)| match r {
+                        Some(x) if *x != FloatRounding::Error => c.map_floats(|y| x.apply(y)),
+                        _ => c.clone(),
+                    })
+                    .collect();
+                // ASSUME this won't fail since all columns are the same length
+                FCSDataFrame::try_new(cs).unwrap()
+            });
+        (rounded, rounding)
+    }
+
+    /// Return copy of this dataset without any events in DATA.
+    ///
+    /// All measurements and TEXT keywords will be kept, and $TOT will be 0
+    /// when written. This is useful for sharing panel or instrument setup
+    /// without sharing any measurement data. ANALYSIS will be removed unless
+    /// `keep_analysis` is true; OTHER segments are always kept.
+    pub fn to_metadata_only(&self, keep_analysis: bool) -> Self
+    where
+        Self: Clone,
+    {
+        let mut ret = self.clone();
+        ret.data.clear_rows();
+        if !keep_analysis {
+            ret.analysis = Analysis::default();
+        }
+        ret
+    }
+
+    /// Return row ranges which split DATA into windows of acquisition time.
+    ///
+    /// See [`AnyCoreDataset::time_chunks`].
+    pub fn time_chunks(&self, window: f64) -> Option<Vec<std::ops::Range<usize>>> {
+        self.time_column()
+            .map(|(c, timestep)| c.window_bounds(window, timestep))
+    }
+
+    /// Split this dataset into several datasets by windows of acquisition time.
+    ///
+    /// Events will be split as in [`Self::time_chunks`]. Each new dataset will
+    /// otherwise be a copy of this one, except that $BTIM, $ETIM, and $DATE
+    /// (and $BEGINDATETIME/$ENDDATETIME for 3.2) will be shifted to match the
+    /// times of the first and last event in each slice. If the time counter
+    /// rolls over, acquisition time is assumed to continue from the end of the
+    /// previous slice.
+    ///
+    /// Each slice will also have provenance recording `parent` (if given) and
+    /// the interval and position of the slice. Return None if there is no
+    /// time measurement.
+    pub fn split_by_time(&self, interval: f64, parent: Option<&ParentFile>) -> Option<Vec<Self>>
+    where
+        Self: Clone,
+    {
+        let (c, timestep) = self.time_column()?;
+        let xs: Vec<_> = c.to_f64s().into_iter().map(|x| x * timestep).collect();
+        let mut offset = 0.0;
+        let mut base = xs.first().copied().unwrap_or_default();
+        let mut prev: Option<f64> = None;
+        let ret = c
+            .window_bounds(interval, timestep)
+            .into_iter()
+            .enumerate()
+            .map(|(i, rows)| {
+                let first = xs[rows.start];
+                let last = xs[rows.end - 1];
+                if let Some(p) = prev.filter(|&p| first < p) {
+                    offset += p - base;
+                    base = first;
+                }
+                prev = Some(last);
+                let mut y = self.clone();
+                y.data = self.data.slice_rows(rows);
+                y.metaroot
+                    .specific
+                    .slice_time_inner(offset + first - base, offset + last - base);
+                let op = format!("split_by_time interval={interval} slice={i}");
+                y.set_provenance(Some(Provenance::derived(op, parent)));
+                y
+            })
+            .collect();
+        Some(ret)
+    }
+
+    /// Split events into one dataset per barcode channel.
+    ///
+    /// Each rule is the name of a barcode channel and the threshold at or
+    /// above which an event is considered positive for that channel, compared
+    /// against raw values in DATA. Events will be assigned to the dataset for
+    /// the only rule they are positive for; events which are positive for no
+    /// rules or for more than one rule will be dropped.
+    ///
+    /// Each new dataset will otherwise be a copy of this one, except that the
+    /// channel name will be appended to $SMNO and $SRC (or used as $SMNO if it
+    /// is missing) to tell the samples apart, and provenance will record
+    /// `parent` (if given) and the channel. Return error if any channel does
+    /// not exist.
+    pub fn demultiplex(
+        &self,
+        rules: &[(Shortname, Threshold)],
+        parent: Option<&ParentFile>,
+    ) -> Result<Vec<Self>, KeyNotFoundError>
+    where
+        Self: Clone,
+    {
+        let columns = rules
+            .iter()
+            .map(|(n, _)| {
+                let i = usize::from(self.measurements.find_name(n)?);
+                // DATA may have no columns if this is metadata-only
+                Ok(self
+                    .data
+                    .iter_columns()
+                    .nth(i)
+                    .map(AnyFCSColumn::to_f64s)
+                    .unwrap_or_default())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let thresholds: Vec<_> = rules.iter().map(|(_, t)| *t).collect();
+        let ret = demux::assign_rows(&columns, &thresholds)
+            .into_iter()
+            .zip(rules)
+            .map(|(rows, (n, _))| {
+                let mut y = self.clone();
+                y.data = self.data.take_rows(&rows);
+                let m = &mut y.metaroot;
+                let smno = m
+                    .smno
+                    .0
+                    .as_ref()
+                    .map_or(n.to_string(), |x| format!("{x}-{n}"));
+                m.smno = Some(Smno(smno)).into();
+                m.src = m.src.0.as_ref().map(|x| Src(format!("{x}-{n}"))).into();
+                let op = format!("demultiplex channel={n}");
+                y.set_provenance(Some(Provenance::derived(op, parent)));
+                y
+            })
+            .collect();
+        Ok(ret)
+    }
+
+    /// Find events which repeat earlier events in the given channels.
+    ///
+    /// Two events are duplicates if their raw values in DATA are identical
+    /// for every channel in `channels`, or for every measurement if
+    /// `channels` is empty. Events are hashed rather than sorted or copied,
+    /// so memory usage only grows with the number of distinct events. Runs of
+    /// consecutive duplicates are also reported since these usually indicate
+    /// an aborted and restarted acquisition.
+    ///
+    /// Return error if any channel does not exist.
+    pub fn find_duplicate_events(
+        &self,
+        channels: &[Shortname],
+    ) -> Result<DuplicateReport, KeyNotFoundError> {
+        let indices = if channels.is_empty() {
+            (0..self.par().0).collect()
+        } else {
+            channels
+                .iter()
+                .map(|n| self.measurements.find_name(n).map(usize::from))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        // DATA may have no columns if this is metadata-only
+        let columns: Vec<_> = self
+            .data
+            .iter_columns()
+            .enumerate()
+            .filter(|(i, _)| indices.contains(i))
+            .map(|(_, c)| c)
+            .collect();
+        Ok(duplicates::find_duplicates(&columns))
+    }
+
+    /// Return the time measurement column and its $TIMESTEP in seconds.
+    fn time_column(&self) -> Option<(&AnyFCSColumn, f64)> {
+        let t = self.temporal()?;
+        self.data
+            .iter_columns()
+            .nth(usize::from(t.index))
+            .map(|c| (c, t.value.specific.timestep_seconds()))
+    }
+
+    /// Return true if this dataset has measurements but no events.
+    ///
+    /// Files made with [`Self::to_metadata_only`] will satisfy this when read.
+    pub fn is_metadata_only(&self) -> bool {
+        self.par().0 > 0 && self.data.nrows() == 0
+    }
+
+    /// Compute a histogram of one measurement in DATA.
+    ///
+    /// Values will be split into `bins` equally-spaced bins between the
+    /// minimum and maximum of the column. If `scaled` is true, $PnE/$PnG will
+    /// be applied to each value first, which will only change integer
+    /// measurements with a log scale or gain.
+    ///
+    /// Return error if `name` is not a measurement.
+    pub fn channel_histogram(
+        &self,
+        name: &Shortname,
+        bins: NonZeroUsize,
+        scaled: bool,
+    ) -> Result<Histogram, KeyNotFoundError>
+    where
+        M::Optical: AsScaleTransform,
+    {
+        let i = usize::from(self.measurements.find_name(name)?);
+        // ASSUME these are all the same length as the measurements
+        let xform = self.transforms().nth(i).unwrap_or_default();
+        let range = self.layout.ranges()[i].0.to_f64().unwrap_or(f64::MAX);
+        // DATA may have no columns if this is metadata-only
+        let col = self.data.iter_columns().nth(i);
+        Ok(col.map_or_else(
+            || Histogram::from_values(iter::empty(), bins),
+            |c| {
+                if scaled {
+                    c.histogram(bins, |x| xform.apply(x, range))
+                } else {
+                    c.histogram(bins, |x| x)
+                }
+            },
+        ))
+    }
+
+    /// Return iterator over each event in DATA.
+    ///
+    /// Each event is one float per measurement and is built from DATA as it is
+    /// requested, so only one event is allocated at a time. DATA itself must
+    /// already be in memory; to decode events straight from the raw bytes of a
+    /// file, read it with [`crate::api::fcs_read_raw_dataset_passthru`] and use
+    /// [`PassthruData::iter_rows`].
+    pub fn iter_rows(&self) -> RowIter<'_> {
+        self.data.iter_rows()
+    }
+
+    /// Return iterator over the values of one measurement in DATA.
+    ///
+    /// Each value will be converted to a float as it is requested. This will
+    /// be empty if this is metadata-only. Return error if `name` is not a
+    /// measurement.
+    pub fn iter_column(&self, name: &Shortname) -> Result<ColumnIter<'_>, KeyNotFoundError> {
+        let i = usize::from(self.measurements.find_name(name)?);
+        Ok(self
+            .data
+            .iter_columns()
+            .nth(i)
+            .map_or_else(ColumnIter::empty, AnyFCSColumn::iter_f64))
+    }
+
     /// Return DATA
     pub fn data(&self) -> &FCSDataFrame {
         &self.data
@@ -4103,6 +6377,10 @@ where
     ///
     /// Return error if columns are not all the same length or number of columns
     /// doesn't match the number of measurement.
+    ///
+    /// The number of rows may differ from the current dataframe. $TOT is not
+    /// stored with the dataset and is always computed from the number of rows
+    /// when writing, so it cannot diverge from DATA.
     pub fn set_data(&mut self, df: FCSDataFrame) -> Result<(), ColumnsToDataframeError> {
         let data_n = df.ncols();
         let meas_n = self.par().0;
@@ -4146,6 +6424,40 @@ where
             .into_terminal()
     }
 
+    /// Return the number of digits in the widest value of each column.
+    ///
+    /// This only applies to delimited ASCII layouts ($PnB is "*"), where column
+    /// widths are not stored in TEXT and can only be found from DATA. Return
+    /// `None` for all other layouts. Will be empty if DATA has no columns.
+    pub fn delimited_widths(&self) -> Option<Vec<Chars>> {
+        self.layout.delimited_ranges()?;
+        let ws = self
+            .data
+            .iter_columns()
+            .map(|c| Chars::from_u64(c.ascii_max()))
+            .collect();
+        Some(ws)
+    }
+
+    /// Convert a delimited ASCII layout to a fixed-width layout.
+    ///
+    /// Each column will be given the smallest width which can hold both its
+    /// $PnR and every value in DATA, so the data can be written without loss.
+    /// If `endian` is given, the new layout will use integers with this byte
+    /// order, otherwise it will use fixed-width ASCII. For 2.0 and 3.0, all
+    /// integer columns will have the width of the widest column.
+    ///
+    /// Return `false` and do nothing if the layout is not delimited ASCII.
+    pub fn solidify_delimited(&mut self, endian: Option<Endian>) -> bool {
+        let maxima: Vec<_> = self.data.iter_columns().map(|c| c.ascii_max()).collect();
+        self.layout
+            .solidify_delimited(&maxima, endian)
+            .map(|layout| {
+                self.layout = layout;
+            })
+            .is_some()
+    }
+
     // TODO add function to append event(s)
 
     /// Remove a measurement matching the given name.
@@ -4168,6 +6480,21 @@ where
         })
     }
 
+    /// Reorder measurements and DATA columns to match a reference list.
+    ///
+    /// See [`VersionedCoreTEXT::align_to_panel`] for rules on how missing and
+    /// extra measurements are handled.
+    pub fn align_to_panel(
+        &mut self,
+        reference: &[Shortname],
+        allow_missing: bool,
+        allow_extra: bool,
+    ) -> Result<(), AlignError> {
+        let order = self.align_to_panel_inner(reference, allow_missing, allow_extra)?;
+        self.data.reorder_columns_nocheck(&order);
+        Ok(())
+    }
+
     /// Remove a measurement at a given position
     ///
     /// Return removed measurement and its name if found.
@@ -4184,6 +6511,21 @@ where
         Ok(res)
     }
 
+    /// Remove a measurement, its column, and everything which refers to it.
+    ///
+    /// See [`VersionedCoreTEXT::remove_measurement_and_links`].
+    pub fn remove_measurement_and_links(
+        &mut self,
+        name: &Shortname,
+    ) -> Result<MeasRemoval, KeyNotFoundError> {
+        let res = self.remove_measurement_and_links_inner(name)?;
+        // DATA may have no columns if this is metadata-only
+        if self.data.ncols() > 0 {
+            self.data.drop_in_place(res.index.into()).unwrap();
+        }
+        Ok(res)
+    }
+
     /// Add time measurement to the end of the measurement vector.
     ///
     /// Return error if time measurement already exists or name is non-unique.
@@ -4249,6 +6591,36 @@ where
             .def_terminate(PushOpticalFailure)
     }
 
+    /// Add measurement to the end using another as a template.
+    ///
+    /// See [`VersionedCoreTEXT::push_optical_like`].
+    pub fn push_optical_like(
+        &mut self,
+        n: <M::Name as MightHave>::Wrapper<Shortname>,
+        template: MeasIndex,
+        col: AnyFCSColumn,
+        r: Range,
+        notrunc: bool,
+    ) -> TerminalResult<Shortname, AnyRangeError, PushOpticalLikeToDatasetError, PushOpticalFailure>
+    where
+        M::Optical: Clone,
+    {
+        self.optical_template(template)
+            .into_deferred()
+            .def_and_maybe(|m| {
+                self.push_optical_inner(n, m, r, notrunc)
+                    .def_errors_into::<PushOpticalToDatasetError>()
+                    .def_and_maybe(|k| {
+                        self.data
+                            .push_column(col)
+                            .into_deferred()
+                            .def_map_value(|_| k)
+                    })
+                    .def_errors_into()
+            })
+            .def_terminate(PushOpticalFailure)
+    }
+
     /// Add measurement at a given position
     ///
     /// Return error if name is non-unique, or index is out of bounds.
@@ -4511,6 +6883,17 @@ impl CoreTEXT2_0 {
         let metaroot = Metaroot::new_def(specific);
         CoreTEXT::new_nomeas(metaroot, datatype)
     }
+
+    /// Make a minimal valid instance with `n` optical measurements.
+    ///
+    /// Measurements will be named 'FL1', 'FL2', etc and will each have a
+    /// range of 1024 with a float layout. This is meant for unit tests and
+    /// documentation examples where reading an actual file is unnecessary.
+    pub fn example(n: usize) -> Self {
+        let mut core = Self::new_def(Mode::List, AlphaNumType::Float);
+        core.push_example_measurements(n, Optical2_0::default);
+        core
+    }
 }
 
 impl CoreTEXT3_0 {
@@ -4582,6 +6965,17 @@ impl CoreTEXT3_0 {
         let metaroot = Metaroot::new_def(specific);
         CoreTEXT::new_nomeas(metaroot, datatype)
     }
+
+    /// Make a minimal valid instance with `n` optical measurements.
+    ///
+    /// See [`CoreTEXT2_0::example`].
+    pub fn example(n: usize) -> Self {
+        let mut core = Self::new_def(Mode::List, AlphaNumType::Float);
+        core.push_example_measurements(n, || {
+            Optical::new_common(InnerOptical3_0::new_def(ScaleTransform::default()))
+        });
+        core
+    }
 }
 
 impl CoreTEXT3_1 {
@@ -4661,6 +7055,17 @@ impl CoreTEXT3_1 {
         let metaroot = Metaroot::new_def(specific);
         CoreTEXT::new_nomeas(metaroot, datatype)
     }
+
+    /// Make a minimal valid instance with `n` optical measurements.
+    ///
+    /// See [`CoreTEXT2_0::example`].
+    pub fn example(n: usize) -> Self {
+        let mut core = Self::new_def(Mode::List, AlphaNumType::Float);
+        core.push_example_measurements(n, || {
+            Optical::new_common(InnerOptical3_1::new_def(ScaleTransform::default()))
+        });
+        core
+    }
 }
 
 impl CoreTEXT3_2 {
@@ -4748,6 +7153,17 @@ impl CoreTEXT3_2 {
         let metaroot = Metaroot::new_def(specific);
         CoreTEXT::new_nomeas(metaroot, datatype)
     }
+
+    /// Make a minimal valid instance with `n` optical measurements.
+    ///
+    /// See [`CoreTEXT2_0::example`].
+    pub fn example(n: usize) -> Self {
+        let mut core = Self::new_def("example".into(), AlphaNumType::Float);
+        core.push_example_measurements(n, || {
+            Optical::new_common(InnerOptical3_2::new_def(ScaleTransform::default()))
+        });
+        core
+    }
 }
 
 impl UnstainedData {
@@ -4923,6 +7339,25 @@ impl CarrierData {
         .flat_map(|(k, v)| v.map(|x| (k, x)))
     }
 
+    /// Return these keywords as a validated [`Carrier`].
+    pub fn to_carrier(&self) -> Result<Option<Carrier>, CarrierError> {
+        Carrier::from_keywords(
+            self.carrierid.0.clone().map(|x| x.0),
+            self.carriertype.0.clone().map(|x| x.0),
+            self.locationid.0.clone().map(|x| x.0),
+        )
+    }
+
+    fn from_carrier(c: Option<Carrier>) -> Self {
+        c.map_or_else(Self::default, |x| {
+            Self::new(
+                Some(Carrierid(x.carrierid)),
+                x.carriertype.map(Carriertype),
+                x.locationid.map(Locationid),
+            )
+        })
+    }
+
     fn check_loss(self, lossless: bool) -> BiTentative<(), AnyMetarootKeyLossError> {
         let i = self.carrierid.check_key_transfer(lossless);
         let t = self.carriertype.check_key_transfer(lossless);
@@ -5065,16 +7500,62 @@ impl From<Calibration3_1> for Calibration3_2 {
     }
 }
 
-impl From<Calibration3_2> for Calibration3_1 {
-    fn from(value: Calibration3_2) -> Self {
-        Self {
-            unit: value.unit,
-            slope: value.slope,
-        }
-    }
+impl From<Calibration3_2> for Calibration3_1 {
+    fn from(value: Calibration3_2) -> Self {
+        Self {
+            unit: value.unit,
+            slope: value.slope,
+        }
+    }
+}
+
+/// Append unparsed keywords to those derived from parsed values.
+///
+/// Unparsed keywords will be skipped if the key is already present, which
+/// will happen if its value was set after reading.
+fn with_raw_keywords(
+    xs: Vec<(String, String)>,
+    raw: Vec<(String, String)>,
+) -> impl Iterator<Item = (String, String)> {
+    let ks: HashSet<_> = xs.iter().map(|(k, _)| k.to_uppercase()).collect();
+    let ys: Vec<_> = raw
+        .into_iter()
+        .filter(|(k, _)| !ks.contains(&k.to_uppercase()))
+        .collect();
+    xs.into_iter().chain(ys)
+}
+
+// TODO this is awkward
+/// Remove a deprecated keyword value.
+fn remove_deprecated<X>(x: &MaybeValue<X>) -> Option<DeprecatedChange>
+where
+    X: OptMetarootKey + fmt::Display,
+{
+    let (k, v) = OptMetarootKey::pair_opt(x);
+    v.map(|y| DeprecatedChange::new(k, y, None))
+}
+
+/// Move a deprecated keyword value to its replacement.
+///
+/// The value will only be moved if the replacement is not already set,
+/// otherwise it will be dropped.
+fn move_deprecated<X, Y>(old: MaybeValue<X>, new: &mut MaybeValue<Y>) -> Option<DeprecatedChange>
+where
+    X: OptMetarootKey + fmt::Display + Into<String>,
+    Y: OptMetarootKey + From<String>,
+{
+    let (k, v) = OptMetarootKey::pair_opt(&old);
+    old.0.map(|x| {
+        let r = if new.0.is_none() {
+            *new = Some(Y::from(x.into())).into();
+            Some(Y::std().to_string())
+        } else {
+            None
+        };
+        DeprecatedChange::new(k, v.unwrap_or_default(), r)
+    })
 }
 
-// TODO this is awkward
 fn convert_wavelengths(
     w: MaybeValue<Wavelengths>,
     force: bool,
@@ -5520,6 +8001,7 @@ impl_ref_specific_rw!(
     Option<Carrierid>,
     Option<Carriertype>,
     Option<Locationid>,
+    CarrierData,
     Option<Vol>,
     Option<Flowrate>,
     Option<UnstainedInfo>,
@@ -5615,7 +8097,8 @@ impl_ref_specific_ro!(
     Option<BeginDateTime>,
     Option<EndDateTime>,
     Option<UnstainedCenters>,
-    AppliedGates3_2
+    AppliedGates3_2,
+    LegacyKeywords
 );
 
 impl_ref_specific_ro!(Optical, InnerOptical2_0, Option<Scale>);
@@ -5901,6 +8384,7 @@ impl ConvertFromMetaroot<InnerMetaroot2_0> for InnerMetaroot3_2 {
                         vol: None.into(),
                         flowrate: None.into(),
                         carrier: CarrierData::default(),
+                        legacy: LegacyKeywords::default(),
                         unstained: UnstainedData::default(),
                         datetimes: Datetimes::default(),
                         applied_gates: AppliedGates3_2::default(),
@@ -5951,6 +8435,7 @@ impl ConvertFromMetaroot<InnerMetaroot3_0> for InnerMetaroot3_2 {
                                     vol: None.into(),
                                     flowrate: None.into(),
                                     carrier: CarrierData::default(),
+                                    legacy: LegacyKeywords::default(),
                                     unstained: UnstainedData::default(),
                                     datetimes: Datetimes::default(),
                                     applied_gates,
@@ -5993,6 +8478,7 @@ impl ConvertFromMetaroot<InnerMetaroot3_1> for InnerMetaroot3_2 {
                             vol: value.vol,
                             flowrate: None.into(),
                             carrier: CarrierData::default(),
+                            legacy: LegacyKeywords::default(),
                             unstained: UnstainedData::default(),
                             datetimes: Datetimes::default(),
                             applied_gates,
@@ -6056,6 +8542,21 @@ impl ScaleTransform {
     pub(crate) fn is_noop(&self) -> bool {
         *self == Self::default()
     }
+
+    /// Apply this transform to a value from DATA.
+    ///
+    /// Linear transforms will divide by $PnG. Log transforms will compute
+    /// 10^(decades * x / range) * offset, where `range` is $PnR.
+    pub fn apply(&self, x: f64, range: f64) -> f64 {
+        match self {
+            Self::Lin(g) => x / f64::from(f32::from(*g)),
+            Self::Log(l) => {
+                let decades = f64::from(f32::from(l.decades));
+                let offset = f64::from(f32::from(l.offset));
+                10_f64.powf(decades * x / range) * offset
+            }
+        }
+    }
 }
 
 impl From<Scale> for ScaleTransform {
@@ -6447,6 +8948,30 @@ impl AsScaleTransform for InnerOptical3_2 {
     }
 }
 
+impl AsOpticalType for InnerOptical2_0 {
+    fn as_optical_type(&self) -> Option<&OpticalType> {
+        None
+    }
+}
+
+impl AsOpticalType for InnerOptical3_0 {
+    fn as_optical_type(&self) -> Option<&OpticalType> {
+        None
+    }
+}
+
+impl AsOpticalType for InnerOptical3_1 {
+    fn as_optical_type(&self) -> Option<&OpticalType> {
+        None
+    }
+}
+
+impl AsOpticalType for InnerOptical3_2 {
+    fn as_optical_type(&self) -> Option<&OpticalType> {
+        self.measurement_type.0.as_ref()
+    }
+}
+
 impl LookupOptical for InnerOptical2_0 {
     fn lookup_specific(
         kws: &mut StdKeywords,
@@ -6740,6 +9265,15 @@ impl VersionedOptical for InnerOptical3_1 {
 
 impl VersionedOptical for InnerOptical3_2 {
     type Ver = Version3_2;
+
+    fn strip_deprecated_optical(o: &mut Optical<Self>, i: MeasIndex) -> Vec<DeprecatedChange> {
+        let (k, v) = OptIndexedKey::pair_opt(&o.percent_emitted, i.into());
+        o.percent_emitted = None.into();
+        v.map(|x| DeprecatedChange::new(k, x, None))
+            .into_iter()
+            .collect()
+    }
+
     fn req_suffixes_inner(
         &self,
         i: MeasIndex,
@@ -6816,6 +9350,11 @@ impl VersionedTemporal for InnerTemporal2_0 {
         let Ok(ret) = self.can_convert_to_optical(i);
         Ok(ret)
     }
+
+    fn timestep_seconds(&self) -> f64 {
+        // $TIMESTEP does not exist in 2.0
+        1.0
+    }
 }
 
 impl VersionedTemporal for InnerTemporal3_0 {
@@ -6844,6 +9383,10 @@ impl VersionedTemporal for InnerTemporal3_0 {
         let Ok(ret) = self.can_convert_to_optical(i);
         Ok(ret)
     }
+
+    fn timestep_seconds(&self) -> f64 {
+        f64::from(f32::from(self.timestep))
+    }
 }
 
 impl VersionedTemporal for InnerTemporal3_1 {
@@ -6874,6 +9417,10 @@ impl VersionedTemporal for InnerTemporal3_1 {
         let Ok(ret) = self.can_convert_to_optical(i);
         Ok(ret)
     }
+
+    fn timestep_seconds(&self) -> f64 {
+        f64::from(f32::from(self.timestep))
+    }
 }
 
 impl VersionedTemporal for InnerTemporal3_2 {
@@ -6904,6 +9451,10 @@ impl VersionedTemporal for InnerTemporal3_2 {
     ) -> MultiResult<(), SwapOpticalTemporalError> {
         self.can_convert_to_optical(i).mult_errors_into()
     }
+
+    fn timestep_seconds(&self) -> f64 {
+        f64::from(f32::from(self.timestep))
+    }
 }
 
 impl VersionedTEXTOffsets for TEXTOffsets2_0 {
@@ -7560,9 +10111,10 @@ impl LookupMetaroot for InnerMetaroot3_2 {
         let u = UnstainedData::lookup(kws, names);
         let v = Vol::lookup_opt(kws);
         let g = AppliedGates3_2::lookup(kws, par, dd);
+        let l = LegacyKeywords::lookup(kws, dd);
         ca.zip6(d, f, md, mo, sp)
             .zip6(sn, p, t, u, v)
-            .zip(g)
+            .zip3(g, l)
             .errors_into()
             .and_maybe(
                 |(
@@ -7575,6 +10127,7 @@ impl LookupMetaroot for InnerMetaroot3_2 {
                         vol,
                     ),
                     applied_gates,
+                    legacy,
                 )| {
                     Cyt::lookup_req(kws).def_map_value(|cyt| Self {
                         cyt,
@@ -7590,6 +10143,7 @@ impl LookupMetaroot for InnerMetaroot3_2 {
                         flowrate,
                         unstained,
                         applied_gates,
+                        legacy,
                     })
                 },
             )
@@ -7630,6 +10184,27 @@ impl VersionedMetaroot for InnerMetaroot2_0 {
         }
     }
 
+    fn remove_meas_index_inner(&mut self, index: MeasIndex, _: &Shortname) {
+        if self
+            .comp
+            .0
+            .as_mut()
+            .is_some_and(|x| x.0.remove_by_index_unchecked(index))
+        {
+            self.comp = None.into();
+        }
+    }
+
+    fn slice_time_inner(&mut self, begin: f64, end: f64) {
+        if let Some(ts) = self.timestamps.slice(begin, end) {
+            self.timestamps = ts;
+        }
+    }
+
+    fn fill_start_inner(&mut self, start: DateTime<FixedOffset>) -> bool {
+        self.timestamps.fill_start(start.naive_utc())
+    }
+
     fn keywords_req_inner(&self) -> impl Iterator<Item = (String, String)> {
         [self.mode.pair()].into_iter()
     }
@@ -7697,6 +10272,28 @@ impl VersionedMetaroot for InnerMetaroot3_0 {
         self.applied_gates.shift_meas_indices_after_insert(index);
     }
 
+    fn remove_meas_index_inner(&mut self, index: MeasIndex, _: &Shortname) {
+        if self
+            .comp
+            .0
+            .as_mut()
+            .is_some_and(|x| x.0.remove_by_index_unchecked(index))
+        {
+            self.comp = None.into();
+        }
+        self.applied_gates.remove_meas_index(index);
+    }
+
+    fn slice_time_inner(&mut self, begin: f64, end: f64) {
+        if let Some(ts) = self.timestamps.slice(begin, end) {
+            self.timestamps = ts;
+        }
+    }
+
+    fn fill_start_inner(&mut self, start: DateTime<FixedOffset>) -> bool {
+        self.timestamps.fill_start(start.naive_utc())
+    }
+
     fn keywords_req_inner(&self) -> impl Iterator<Item = (String, String)> {
         [self.mode.pair()].into_iter()
     }
@@ -7775,6 +10372,28 @@ impl VersionedMetaroot for InnerMetaroot3_1 {
         self.applied_gates.shift_meas_indices_after_insert(index);
     }
 
+    fn remove_meas_index_inner(&mut self, index: MeasIndex, name: &Shortname) {
+        if self
+            .spillover
+            .0
+            .as_mut()
+            .is_some_and(|x| x.remove_by_name(name))
+        {
+            self.spillover = None.into();
+        }
+        self.applied_gates.remove_meas_index(index);
+    }
+
+    fn slice_time_inner(&mut self, begin: f64, end: f64) {
+        if let Some(ts) = self.timestamps.slice(begin, end) {
+            self.timestamps = ts;
+        }
+    }
+
+    fn fill_start_inner(&mut self, start: DateTime<FixedOffset>) -> bool {
+        self.timestamps.fill_start(start.naive_utc())
+    }
+
     fn keywords_req_inner(&self) -> impl Iterator<Item = (String, String)> {
         [self.mode.pair()].into_iter()
     }
@@ -7821,6 +10440,58 @@ impl VersionedMetaroot for InnerMetaroot3_2 {
     type Temporal = InnerTemporal3_2;
     type Name = AlwaysFamily;
 
+    fn strip_deprecated_inner(&mut self, offset: Option<FixedOffset>) -> Vec<DeprecatedChange> {
+        let mut cs = vec![];
+
+        // $MODE may only be L in 3.2, which is implied if missing
+        cs.extend(remove_deprecated(&self.mode));
+        self.mode = None.into();
+
+        // $PLATEID and $WELLID have direct replacements, $PLATENAME does not
+        let plate = mem::take(&mut self.plate);
+        cs.extend(move_deprecated(plate.plateid, &mut self.carrier.carrierid));
+        cs.extend(move_deprecated(plate.wellid, &mut self.carrier.locationid));
+        cs.extend(remove_deprecated(&plate.platename));
+
+        // $DATE/$BTIM/$ETIM are redundant if $BEGINDATETIME/$ENDDATETIME are
+        // already given, and otherwise may only be converted to the latter if
+        // we know which timezone they are in; keep them if neither is true
+        let has_datetimes = self.datetimes != Datetimes::default();
+        let new_datetimes = offset
+            .filter(|_| !has_datetimes)
+            .and_then(|tz| self.timestamps.to_datetimes(tz));
+        if has_datetimes || new_datetimes.is_some() {
+            let timestamps = mem::take(&mut self.timestamps);
+            let begin = new_datetimes
+                .as_ref()
+                .map(|_| BeginDateTime::std().to_string());
+            let end = new_datetimes
+                .as_ref()
+                .and_then(|d| AsRef::<Option<EndDateTime>>::as_ref(d).as_ref())
+                .map(|_| EndDateTime::std().to_string());
+            for (tk, tv) in timestamps.opt_keywords() {
+                let r = if tk == Etim::<FCSTime100>::std().to_string() {
+                    end.clone()
+                } else {
+                    begin.clone()
+                };
+                cs.push(DeprecatedChange::new(tk, tv, r));
+            }
+        }
+        if let Some(d) = new_datetimes {
+            self.datetimes = d;
+        }
+
+        // gating keywords have no replacement
+        let gates = mem::take(&mut self.applied_gates);
+        cs.extend(
+            gates
+                .opt_keywords()
+                .map(|(gk, gv)| DeprecatedChange::new(gk, gv, None)),
+        );
+        cs
+    }
+
     fn check_meas_named_links_inner(
         &self,
         names: &HashSet<&Shortname>,
@@ -7869,6 +10540,43 @@ impl VersionedMetaroot for InnerMetaroot3_2 {
         self.applied_gates.shift_meas_indices_after_insert(index);
     }
 
+    fn remove_meas_index_inner(&mut self, index: MeasIndex, name: &Shortname) {
+        if self
+            .spillover
+            .0
+            .as_mut()
+            .is_some_and(|x| x.remove_by_name(name))
+        {
+            self.spillover = None.into();
+        }
+        if self
+            .unstained
+            .unstainedcenters
+            .0
+            .as_mut()
+            .is_some_and(|x| x.remove_by_name(name))
+        {
+            self.unstained.unstainedcenters = None.into();
+        }
+        self.applied_gates.remove_meas_index(index);
+    }
+
+    fn slice_time_inner(&mut self, begin: f64, end: f64) {
+        if let Some(ts) = self.timestamps.slice(begin, end) {
+            self.timestamps = ts;
+        }
+        if let Some(dt) = self.datetimes.slice(begin, end) {
+            self.datetimes = dt;
+        }
+    }
+
+    fn fill_start_inner(&mut self, start: DateTime<FixedOffset>) -> bool {
+        let b: &Option<BeginDateTime> = self.datetimes.as_ref();
+        b.is_none()
+            && !self.timestamps.has_start()
+            && self.datetimes.set_begin(Some(start.into())).is_ok()
+    }
+
     fn keywords_req_inner(&self) -> impl Iterator<Item = (String, String)> {
         [ReqMetarootKey::pair(&self.cyt)].into_iter()
     }
@@ -7975,6 +10683,45 @@ impl InnerMetaroot3_2 {
             spillover: None.into(),
             vol: None.into(),
             applied_gates: AppliedGates3_2::default(),
+            legacy: LegacyKeywords::default(),
+        }
+    }
+}
+
+impl InnerOptical3_0 {
+    pub(crate) fn new_def(scale: ScaleTransform) -> Self {
+        Self {
+            scale,
+            wavelength: None.into(),
+            peak: PeakData::default(),
+        }
+    }
+}
+
+impl InnerOptical3_1 {
+    pub(crate) fn new_def(scale: ScaleTransform) -> Self {
+        Self {
+            scale,
+            wavelengths: None.into(),
+            calibration: None.into(),
+            display: None.into(),
+            peak: PeakData::default(),
+        }
+    }
+}
+
+impl InnerOptical3_2 {
+    pub(crate) fn new_def(scale: ScaleTransform) -> Self {
+        Self {
+            scale,
+            wavelengths: None.into(),
+            calibration: None.into(),
+            display: None.into(),
+            analyte: None.into(),
+            feature: None.into(),
+            measurement_type: None.into(),
+            tag: None.into(),
+            detector_name: None.into(),
         }
     }
 }
@@ -8308,12 +11055,15 @@ pub enum StdWriterError {
     Layout(NewDataLayoutError),
     Check(ColumnError<AnyLossError>),
     Overflow(Uint8DigitOverflow),
+    Namespace(NamespaceCollision),
+    Other(TooManyOtherError),
 }
 
 #[derive(From, Display)]
 pub enum StdWriterWarning {
     Column(ColumnError<IntRangeError<()>>),
     Check(ColumnError<AnyLossError>),
+    ControlChar(ControlCharWarning),
 }
 
 #[derive(From, Display)]
@@ -8406,6 +11156,7 @@ pub enum SetMeasurementsError {
 pub enum SetScalesError {
     Layout(MeasLayoutMismatchError),
     Temporal(NonLinearTemporalScaleError),
+    Gain(ScaleGainError),
 }
 
 #[derive(From, Display)]
@@ -8444,6 +11195,50 @@ pub enum RemoveMeasByIndexError {
     Index(ElementIndexError),
 }
 
+#[derive(From, Display)]
+pub enum AlignError {
+    Missing(AlignMissingError),
+    Extra(AlignExtraError),
+    Duplicate(AlignDuplicateError),
+    Link(ExistingIndexLinkError),
+}
+
+pub struct AlignMissingError(NonEmpty<Shortname>);
+
+pub struct AlignExtraError(NonEmpty<Shortname>);
+
+pub struct AlignDuplicateError(NonEmpty<Shortname>);
+
+impl fmt::Display for AlignMissingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "reference panel has measurements not in dataset: {}",
+            self.0.iter().join(", ")
+        )
+    }
+}
+
+impl fmt::Display for AlignExtraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "dataset has measurements not in reference panel: {}",
+            self.0.iter().join(", ")
+        )
+    }
+}
+
+impl fmt::Display for AlignDuplicateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "reference panel has duplicated names: {}",
+            self.0.iter().join(", ")
+        )
+    }
+}
+
 #[derive(From, Display)]
 pub enum InsertTemporalError {
     Center(InsertCenterError),
@@ -8456,6 +11251,18 @@ pub enum PushOpticalError {
     Layout(AnyRangeError),
 }
 
+#[derive(From, Display)]
+pub enum PushOpticalLikeError {
+    Template(ElementIndexError),
+    Push(PushOpticalError),
+}
+
+#[derive(From, Display)]
+pub enum PushOpticalLikeToDatasetError {
+    Template(ElementIndexError),
+    Push(PushOpticalToDatasetError),
+}
+
 #[derive(From, Display)]
 pub enum InsertOpticalError {
     Insert(InsertError),
@@ -8517,6 +11324,22 @@ impl fmt::Display for NonLinearTemporalScaleError {
     }
 }
 
+pub struct ScaleGainError {
+    index: MeasIndex,
+    gain: PositiveFloat,
+}
+
+impl fmt::Display for ScaleGainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "linear transform for measurement {} has gain {}, \
+             but gain ($PnG) is not supported in FCS 2.0",
+            self.index, self.gain,
+        )
+    }
+}
+
 pub struct NonLinearTemporalTransformError;
 
 impl fmt::Display for NonLinearTemporalTransformError {
@@ -8525,6 +11348,15 @@ impl fmt::Display for NonLinearTemporalTransformError {
     }
 }
 
+#[derive(From, Display)]
+pub enum ApplyDiffError {
+    Conflict(DiffConflict),
+    StdKey(StdKeyError),
+    NonStdKey(NonStdKeyError),
+    Standardize(StdTEXTFromRawError),
+    Data(MeasDataMismatchError),
+}
+
 #[derive(From, Display)]
 pub enum StdTEXTFromKeywordsError {
     Error(StdTEXTFromRawError),
@@ -8538,6 +11370,24 @@ pub enum StdTEXTFromRawError {
     Offsets(LookupTEXTOffsetsError),
     Pseudostandard(PseudostandardError),
     Unused(UnusedStandardError),
+    Orphan(OrphanMeasKeyError),
+    Exclude(RemoveMeasByIndexError),
+}
+
+/// Error when setting a keyword defined by an extension to the standard
+#[derive(From)]
+pub enum InsertExtensionError {
+    NoMatch(StdKey),
+    Invalid(KeywordExtensionError),
+}
+
+impl fmt::Display for InsertExtensionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::NoMatch(k) => write!(f, "no keyword extension matches {k}"),
+            Self::Invalid(e) => e.fmt(f),
+        }
+    }
 }
 
 #[derive(From, Display)]
@@ -8548,6 +11398,34 @@ pub enum StdTEXTFromRawWarning {
     Offsets(LookupTEXTOffsetsWarning),
     Pseudostandard(PseudostandardError),
     Unused(UnusedStandardError),
+    Orphan(OrphanMeasKeyError),
+    Exclude(ExcludeMeasWarning),
+}
+
+impl MeasIndexed for StdTEXTFromRawWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::Metaroot(x) => x.meas_index(),
+            Self::Meas(x) => x.meas_index(),
+            Self::Layout(x) => x.meas_index(),
+            _ => None,
+        }
+    }
+}
+
+pub struct ExcludeMeasWarning {
+    name: Shortname,
+    error: ExistingLinkError,
+}
+
+impl fmt::Display for ExcludeMeasWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "could not exclude measurement '{}' since it is referenced elsewhere: {}",
+            self.name, self.error
+        )
+    }
 }
 
 #[derive(From, Display)]
@@ -8567,12 +11445,30 @@ pub enum StdDatasetFromRawWarning {
     // Mismatch(DataSegmentMismatchError),
 }
 
+impl MeasIndexed for StdDatasetFromRawWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::TEXT(x) => x.meas_index(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(From, Display)]
 pub enum LookupMeasWarning {
     Parse(LookupKeysWarning),
     Pattern(NonStdMeasRegexError),
 }
 
+impl MeasIndexed for LookupMeasWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::Parse(x) => x.meas_index(),
+            Self::Pattern(_) => None,
+        }
+    }
+}
+
 // for now this just means $PnE isn't set and should be to convert
 pub struct NoScaleError(MeasIndex);
 
@@ -8651,6 +11547,7 @@ pub enum MetarootConvertError {
 #[derive(From, Display)]
 pub enum MetarootConvertWarning {
     Mode(ModeUpgradeError),
+    Deprecated(DeprecatedChange),
     Gates3_0To2_0(gating::AppliedGates3_0To2_0Error),
     Gates3_0To3_2(gating::AppliedGates3_0To3_2Error),
     Gates3_2To2_0(gating::AppliedGates3_2To2_0Error),
@@ -8661,6 +11558,49 @@ pub enum MetarootConvertWarning {
     Comp2_0(Comp2_0TransferError),
 }
 
+/// A measurement which was removed along with everything linking to it.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct MeasRemoval {
+    /// Position of the removed measurement
+    pub index: MeasIndex,
+
+    /// Changes to non-measurement keywords caused by the removal
+    pub links: KeywordDiff,
+}
+
+/// A deprecated keyword which was removed or replaced.
+#[derive(Clone, new)]
+pub struct DeprecatedChange {
+    /// The deprecated key
+    pub key: String,
+
+    /// The value of the deprecated key
+    pub value: String,
+
+    /// The key which now holds the value if applicable
+    pub replacement: Option<String>,
+}
+
+impl fmt::Display for DeprecatedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if let Some(r) = &self.replacement {
+            write!(
+                f,
+                "replaced deprecated key {} with {r}, value was '{}'",
+                self.key, self.value
+            )
+        } else {
+            write!(
+                f,
+                "removed deprecated key {}, value was '{}'",
+                self.key, self.value
+            )
+        }
+    }
+}
+
 /// Error when a metaroot keyword will be lost when converting versions
 #[derive(From, Display)]
 pub enum AnyMetarootKeyLossError {
@@ -8752,9 +11692,23 @@ pub enum LookupAndReadDataAnalysisWarning {
     Offsets(LookupTEXTOffsetsWarning),
     Layout(RawToLayoutWarning),
     Data(ReadDataframeWarning),
+    Passthru(PassthruLayoutWarning),
     // Mismatch(DataSegmentMismatchError),
 }
 
+/// Layout could not be determined, so DATA was copied verbatim
+pub struct PassthruLayoutWarning(pub RawToLayoutError);
+
+impl fmt::Display for PassthruLayoutWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "could not determine layout, DATA copied verbatim: {}",
+            self.0
+        )
+    }
+}
+
 #[derive(From, Display)]
 pub enum LookupTEXTOffsetsWarning {
     Tot(ParseKeyError<std::num::ParseIntError>),
@@ -8991,6 +11945,8 @@ def_failure!(
     "could not create new CoreTEXT from keywords"
 );
 
+def_failure!(ApplyDiffFailure, "could not apply keyword diff");
+
 def_failure!(
     StdDatasetWithKwsFailure,
     "could not read standardized dataset from keywords"
@@ -9043,22 +11999,35 @@ mod python {
     use crate::validated::dataframe::python::SeriesToColumnError;
 
     use super::{
-        Analysis, CSVFlags, ColumnsToDataframeError, CompParMismatchError, ExistingLinkError,
-        GatingMeasLinkError, MeasDataMismatchError, MissingMeasurementNameError, NewCoreTEXTError,
-        Other, Others, RemoveMeasByIndexError, RemoveMeasByNameError, ScaleTransform,
-        SetMeasurementsError, SpilloverLinkError, TriggerLinkError,
+        AlignError, Analysis, CSVFlags, ColumnsToDataframeError, CompParMismatchError,
+        ExistingLinkError, GatingMeasLinkError, KeywordOrigin, MeasDataMismatchError,
+        MissingMeasurementNameError, NewCoreTEXTError, Other, Others, RemoveMeasByIndexError,
+        RemoveMeasByNameError, ScaleTransform, SetMeasurementsError, SpilloverLinkError,
+        TriggerLinkError,
     };
 
     use derive_more::{Display, From};
     use pyo3::exceptions::{PyIndexError, PyValueError};
     use pyo3::prelude::*;
+    use pyo3::types::PyString;
     use pyo3::IntoPyObjectExt;
+    use std::convert::Infallible;
 
     impl_from_py_transparent!(Analysis);
     impl_from_py_transparent!(Other);
     impl_from_py_transparent!(Others);
     impl_from_py_transparent!(CSVFlags);
 
+    impl<'py> IntoPyObject<'py> for KeywordOrigin {
+        type Target = PyString;
+        type Output = Bound<'py, PyString>;
+        type Error = Infallible;
+
+        fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+            self.to_string().into_pyobject(py)
+        }
+    }
+
     // $PnE/$PnG (3.0+) as a tuple like (f32) or (f32, f32) in python
     impl<'py> FromPyObject<'py> for ScaleTransform {
         fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
@@ -9116,6 +12085,8 @@ mod python {
         }
     }
 
+    impl_pyreflow_err!(AlignError);
+
     impl From<RemoveMeasByNameError> for PyErr {
         fn from(value: RemoveMeasByNameError) -> Self {
             match value {
@@ -9125,3 +12096,83 @@ mod python {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::fcs_read_raw_dataset_passthru;
+    use crate::validated::dataframe::F32Column;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_passthru_partial_layout() {
+        let c = F32Column::from(vec![1.0_f32, 2.0]);
+        let df = FCSDataFrame::try_new(vec![c.clone().into(), c.into()])
+            .ok()
+            .unwrap();
+        let core = CoreTEXT3_1::example(2)
+            .into_coredataset(df, Analysis::default(), Others::default())
+            .ok()
+            .unwrap();
+        let mut h = BufWriter::new(vec![]);
+        assert!(core.h_write_dataset(&mut h, &WriteConfig::default()).is_ok());
+        let bs = h.into_inner().ok().unwrap();
+
+        // replace $P1B with garbage of the same length so offsets still work
+        let good = b"$P1B\x1e32\x1e";
+        let i = bs.windows(good.len()).position(|x| x == good).unwrap();
+        let mut bad = bs.clone();
+        bad[i + 5..i + 7].copy_from_slice(b"xx");
+
+        let p = env::temp_dir().join("fireflow_passthru_partial_layout.fcs");
+        fs::write(&p, &bad).unwrap();
+        let res = fcs_read_raw_dataset_passthru(&p, &ReadRawDatasetConfig::default());
+        let (out, nwarn) = res.ok().unwrap().resolve(|ws| ws.len());
+        fs::remove_file(p).unwrap();
+
+        // DATA is still copied, but the layout can only be partially read
+        assert_eq!(1, nwarn);
+        assert!(out.data.layout.is_none());
+        assert_eq!(8 * 2, out.data.bytes.0.len());
+        let partial = out.data.partial_layout.unwrap();
+        assert!(partial.datatype == Some(AlphaNumType::Float));
+        assert_eq!(Some("1,2,3,4".into()), partial.byteord);
+        assert_eq!(vec![Some("xx".into()), Some("32".into())], partial.widths);
+    }
+
+    #[test]
+    fn test_strip_deprecated_timestamps() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2);
+        let btim = NaiveTime::from_hms_opt(12, 0, 0);
+        let convert = |timestamp_offset: Option<FixedOffset>| {
+            let mut core = CoreTEXT3_1::example(1);
+            // $CYT is required in 3.2
+            core.set_cyt(Cyt::from("Symphony".to_string()));
+            assert!(core.set_date_naive::<FCSTime100>(date).is_ok());
+            assert!(core.set_btim_naive::<FCSTime100>(btim).is_ok());
+            let conf = ConvertConfig {
+                strip_deprecated: true,
+                timestamp_offset,
+                ..ConvertConfig::default()
+            };
+            let res = core.try_convert::<InnerMetaroot3_2>(&conf);
+            res.ok().unwrap().resolve(|_| ()).0
+        };
+
+        // without a timezone the old keywords cannot be converted
+        let kept = convert(None);
+        assert_eq!(date, kept.date_naive());
+        assert_eq!(btim, kept.btim_naive::<FCSTime100>());
+        assert_eq!(None, kept.begindatetime());
+
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let stripped = convert(Some(tz));
+        assert_eq!(None, stripped.date_naive());
+        assert_eq!(None, stripped.btim_naive::<FCSTime100>());
+        let begin = date
+            .zip(btim)
+            .and_then(|(d, t)| d.and_time(t).and_local_timezone(tz).single());
+        assert_eq!(begin, stripped.begindatetime());
+    }
+}
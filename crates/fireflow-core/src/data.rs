@@ -14,7 +14,11 @@
 //! Layouts can first be classified by column width, where "fixed" layouts have
 //! a single width per column and "delimited" layouts have a variable width. The
 //! latter only corresponds to one layout: the case where $DATATYPE=A and all
-//! $PnB=*. Values in such layouts will always be read as u64.
+//! $PnB=*. Values in such layouts will always be read as u64. Any number of
+//! consecutive delimiters (tab, newline, carriage return, space, or comma) count
+//! as one, so the original widths are lost once read. Since the values are
+//! integers, the width needed for each column can be recomputed from the data
+//! itself, which allows converting to an equivalent fixed layout.
 //!
 //! Fixed layouts can further be classified by the type in each column:
 //! 1) Single-type numeric layouts (aka "matrices")
@@ -48,7 +52,7 @@
 //! DATA, hoping that all columns have the same length. For fixed layouts, we
 //! can compute $TOT using $PnB and the length of DATA.
 
-use crate::config::{ReadLayoutConfig, ReaderConfig};
+use crate::config::{FloatRounding, ReadLayoutConfig, ReaderConfig};
 use crate::core::*;
 use crate::error::*;
 use crate::macros::match_many_to_one;
@@ -56,7 +60,7 @@ use crate::nonempty::FCSNonEmpty;
 use crate::segment::*;
 use crate::text::byteord::*;
 use crate::text::float_decimal::{DecimalToFloatError, FloatDecimal, HasFloatBounds};
-use crate::text::index::{IndexFromOne, MeasIndex};
+use crate::text::index::{permute, IndexFromOne, MeasIndex, MeasIndexed};
 use crate::text::keywords::*;
 use crate::text::optional::MightHave;
 use crate::text::parser::*;
@@ -80,15 +84,20 @@ use std::convert::Infallible;
 use std::fmt;
 use std::io;
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::iter;
 use std::marker::PhantomData;
 use std::mem;
 use std::num::NonZeroU8;
+use std::num::NonZeroUsize;
 use std::num::ParseIntError;
 use std::str;
 
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
 /// All possible byte layouts for the DATA segment in 2.0.
 ///
 /// This is identical to 3.0 in every way except that the $TOT keyword in 2.0
@@ -273,6 +282,40 @@ struct ColumnWriter<'a, C, T, S> {
     data: AnySource<'a, T>,
     byte_layout: PhantomData<S>,
     loss: Option<AnyLossError>,
+    nlossy: u64,
+}
+
+/// Summary of values which were changed when writing DATA.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct WriteReport {
+    /// Number of values in each column which could not be written exactly.
+    ///
+    /// These were truncated, clamped, or rounded to fit the type of their
+    /// measurement. Columns are in the same order as measurements, and this
+    /// will be empty if DATA has no columns.
+    pub lossy_values: Vec<u64>,
+
+    /// Rounding applied to each column of floats written as integers.
+    ///
+    /// Columns are in the same order as measurements and will be None if they
+    /// were not floats written as integers. This will be empty if DATA has no
+    /// columns.
+    pub rounding: Vec<Option<FloatRounding>>,
+}
+
+impl WriteReport {
+    /// Total number of lossy values in all columns
+    pub fn total(&self) -> u64 {
+        self.lossy_values.iter().sum()
+    }
+
+    /// Return true if all values were written exactly.
+    pub fn is_lossless(&self) -> bool {
+        self.total() == 0
+    }
 }
 
 impl<C, T, S> ColumnWriter<'_, C, T, S> {
@@ -462,12 +505,23 @@ pub trait LayoutOps<'a, T>: Sized {
 
     fn nbytes(&self, df: &FCSDataFrame) -> u64;
 
+    /// Estimate the number of bytes needed to hold DATA in a dataframe.
+    ///
+    /// The number of events should be taken from $TOT if given and will
+    /// otherwise be computed from the length of DATA. Return None if this is
+    /// not possible, which will be the case for delimited ASCII without $TOT.
+    fn estimated_memory_bytes(&self, total_events: Option<u64>, seg: AnyDataSegment)
+        -> Option<u64>;
+
     fn ranges(&self) -> Vec<Range>;
 
     fn datatype(&self) -> AlphaNumType;
 
     fn datatypes(&self) -> Vec<AlphaNumType>;
 
+    /// Return $PnB for each measurement, or None if it is variable.
+    fn fixed_widths(&self) -> Vec<Option<BitsOrChars>>;
+
     fn byteord_keyword(&self) -> (String, String);
 
     fn req_keywords(&self) -> [(String, String); 2] {
@@ -481,6 +535,10 @@ pub trait LayoutOps<'a, T>: Sized {
     // more more complex as we would need an associated type
     fn remove_nocheck(&mut self, index: MeasIndex);
 
+    /// Reorder columns such that the ith column is currently at `order[i]`.
+    fn reorder_nocheck(&mut self, order: &[MeasIndex]);
+
+    #[allow(clippy::too_many_arguments)]
     fn h_read_df_inner<R: Read>(
         &self,
         h: &mut BufReader<R>,
@@ -488,6 +546,8 @@ pub trait LayoutOps<'a, T>: Sized {
         tot: <T as TotDefinition>::Tot,
         seg: AnyDataSegment,
         conf: &ReaderConfig,
+        exclude: &[bool],
+        time: Option<(usize, f64)>,
     ) -> IODeferredResult<FCSDataFrame, ReadDataframeWarning, ReadDataframeError>
     where
         T: TotDefinition;
@@ -499,7 +559,7 @@ pub trait LayoutOps<'a, T>: Sized {
         h: &mut BufWriter<W>,
         df: &'a FCSDataFrame,
         skip_conv_check: bool,
-    ) -> IODeferredResult<(), ColumnError<AnyLossError>, E>;
+    ) -> IODeferredResult<WriteReport, ColumnError<AnyLossError>, E>;
 
     fn check_transforms_and_len(
         &self,
@@ -605,12 +665,36 @@ where
         conf: &ReadLayoutConfig,
     ) -> DeferredResult<Self, ColumnError<NewMixedTypeWarning>, NewDataLayoutError>;
 
+    /// Return $PnR for each column if this is delimited ASCII.
+    fn delimited_ranges(&self) -> Option<&[u64]>;
+
+    /// Return an equivalent fixed-width layout if this is delimited ASCII.
+    ///
+    /// `maxima` is the largest value in each column, which along with $PnR
+    /// will be used to pick the smallest width which can hold every value
+    /// without loss. If `endian` is given, the new layout will use integers
+    /// with this byte order, otherwise it will use fixed-width ASCII.
+    ///
+    /// Return `None` if this is not delimited ASCII.
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self>;
+
+    /// Read DATA into a dataframe.
+    ///
+    /// Columns for which `exclude` is true will be skipped and not included
+    /// in the returned dataframe. Columns beyond the length of `exclude` will
+    /// always be read. If given, `time` is the index of the time column in
+    /// the returned dataframe and its timestep, which is needed to read in
+    /// chunks of acquisition time (see [`ReaderConfig::chunk_time_window`]).
+    /// If a window is given with `chunk_size` but `time` is None, events will
+    /// be read in chunks of `chunk_size` with a warning.
     fn h_read_df<R: Read + Seek>(
         &self,
         h: &mut BufReader<R>,
         tot: <Self::TotDef as TotDefinition>::Tot,
         seg: AnyDataSegment,
         conf: &ReaderConfig,
+        exclude: &[bool],
+        time: Option<(usize, f64)>,
     ) -> IODeferredResult<FCSDataFrame, ReadDataframeWarning, ReadDataframeError> {
         // The only purpose of this buffer is to read ASCII since we don't
         // hardcode the buffer width into the type (unlike integers and floats).
@@ -619,13 +703,26 @@ where
         // more complex. Good enough to pass the buffer and only use it when
         // needed.
         let mut buf = vec![];
-        seg.inner.as_u64().try_coords().map_or(
+        if let Some(w) = conf
+            .chunk_time_window
+            .filter(|w| !(w.is_finite() && *w > 0.0))
+        {
+            let e = ReadDataframeError::TimeWindow(ChunkTimeWindowError(w));
+            return Err(ImpureError::Pure(e)).into_deferred();
+        }
+        let no_time =
+            conf.chunk_size.is_some() && conf.chunk_time_window.is_some() && time.is_none();
+        let mut res = seg.inner.as_u64().try_coords().map_or(
             Ok(Tentative::new1(FCSDataFrame::default())),
             |(begin, _)| {
                 h.seek(SeekFrom::Start(begin)).into_deferred()?;
-                self.h_read_df_inner(h, &mut buf, tot, seg, conf)
+                self.h_read_df_inner(h, &mut buf, tot, seg, conf, exclude, time)
             },
-        )
+        );
+        if no_time {
+            res.def_push_warning(NoChunkTimeWarning.into());
+        }
+        res
     }
 
     fn h_write_df<W, E>(
@@ -633,17 +730,21 @@ where
         h: &mut BufWriter<W>,
         df: &FCSDataFrame,
         skip_conv_check: bool,
-    ) -> IODeferredResult<(), ColumnError<AnyLossError>, E>
+    ) -> IODeferredResult<WriteReport, ColumnError<AnyLossError>, E>
     where
         W: Write,
     {
         // The dataframe should be encapsulated such that a) the column number
         // matches the number of measurements. If these are not true, the code
-        // is wrong.
+        // is wrong, but bail with an error rather than taking down the caller.
+        // The exception is a dataframe with no columns, which is what we get
+        // when reading an empty DATA segment, and which has nothing to write.
         let par = self.ncols();
         let ncols = df.ncols();
-        if ncols != par {
-            panic!("datafame columns ({ncols}) unequal to number of measurements ({par})");
+        if ncols != par && !df.is_empty() {
+            let msg =
+                format!("dataframe columns ({ncols}) unequal to number of measurements ({par})");
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg)).into_deferred();
         }
         self.h_write_df_inner(h, df, skip_conv_check)
     }
@@ -701,6 +802,9 @@ trait FromRange: Sized {
 pub trait IsFixed {
     fn nbytes(&self) -> NonZeroU8;
 
+    /// Number of bytes used by this column once read into a dataframe
+    fn native_nbytes(&self) -> usize;
+
     fn fixed_width(&self) -> BitsOrChars;
 
     fn range(&self) -> Range;
@@ -762,6 +866,7 @@ where
             data: AnySource::new(c),
             byte_layout: PhantomData,
             loss: None,
+            nlossy: 0,
         }
     }
 
@@ -816,6 +921,9 @@ trait Writable<'a, S> {
     fn truncate(self, skip_conv_check: bool) -> (AnyFCSColumn, Option<AnyLossError>);
 
     fn as_err(&self, i: MeasIndex) -> Option<ColumnError<AnyLossError>>;
+
+    /// Number of values written so far which were lossy
+    fn nlossy(&self) -> u64;
 }
 
 trait Castable: Sized + HasNativeType {
@@ -874,12 +982,14 @@ trait OrderedFromBytes<const OLEN: usize>: NumProps {
         h: &mut BufWriter<W>,
         order: [u8; OLEN],
     ) -> io::Result<()> {
+        // inverse of reading: the ith byte written is the jth byte of the
+        // little-endian representation
         let tmp = Self::to_little(self);
         let mut buf = [0; OLEN];
         for (i, j) in order.iter().enumerate() {
-            buf[usize::from(*j)] = tmp.as_ref()[i];
+            buf[i] = tmp.as_ref()[usize::from(*j)];
         }
-        h.write_all(tmp.as_ref())
+        h.write_all(&buf)
     }
 }
 
@@ -1585,7 +1695,7 @@ impl<const ORD: bool> NativeWritable<NoByteOrd<ORD>> for AsciiRange {
             // if string less than allocated chars, pad left side with zero before
             // writing number
             for _ in 0..(w - s.len()) {
-                h.write_all(&[30])?;
+                h.write_all(b"0")?;
             }
             h.write_all(s.as_bytes())?;
             None
@@ -1655,8 +1765,11 @@ where
     AnyFCSColumn: From<FCSColumn<T>>,
 {
     fn h_write<W: Write>(&mut self, h: &mut BufWriter<W>, byte_layout: S) -> io::Result<()> {
-        let x = self.data.next().unwrap();
+        let x = self.data.next().ok_or_else(column_exhausted)?;
         let loss = self.column_type.h_write(h, x, byte_layout)?;
+        if loss.is_some() {
+            self.nlossy += 1;
+        }
         self.loss = std::mem::take(&mut self.loss).or(loss);
         Ok(())
     }
@@ -1678,6 +1791,10 @@ where
     fn as_err(&self, i: MeasIndex) -> Option<ColumnError<AnyLossError>> {
         self.as_err(i)
     }
+
+    fn nlossy(&self) -> u64 {
+        self.nlossy
+    }
 }
 
 impl<'a> Writable<'a, Endian> for WriterMixedType<'a> {
@@ -1697,6 +1814,10 @@ impl<'a> Writable<'a, Endian> for WriterMixedType<'a> {
     fn as_err(&self, i: MeasIndex) -> Option<ColumnError<AnyLossError>> {
         match_any_mixed!(self, x, { x.as_err(i) })
     }
+
+    fn nlossy(&self) -> u64 {
+        match_any_mixed!(self, x, { x.nlossy() })
+    }
 }
 
 impl<'a> Writable<'a, Endian> for AnyWriterBitmask<'a> {
@@ -1711,6 +1832,10 @@ impl<'a> Writable<'a, Endian> for AnyWriterBitmask<'a> {
     fn as_err(&self, i: MeasIndex) -> Option<ColumnError<AnyLossError>> {
         match_any_uint!(self, Self, x, { x.as_err(i) })
     }
+
+    fn nlossy(&self) -> u64 {
+        match_any_uint!(self, Self, x, { x.nlossy() })
+    }
 }
 
 impl<T, const LEN: usize> ToNativeWriter for Bitmask<T, LEN>
@@ -2096,8 +2221,30 @@ impl From<u64> for AnyNullBitmask {
     /// The width is determined by the magnitude of the range; the smallest
     /// possible will be used.
     fn from(value: u64) -> Self {
+        Self::from_u64_bytes(value, Bytes::from_u64(value))
+    }
+}
+
+impl From<AnyNullBitmask> for u64 {
+    /// Convert bitmask range (not bitmask itself) to u64.
+    fn from(value: AnyNullBitmask) -> Self {
+        match_any_uint!(value, AnyNullBitmask, x, { u64::from(x) })
+    }
+}
+
+impl AnyNullBitmask {
+    /// Make a new bitmask from a u64 with at least the given width.
+    ///
+    /// The width will be increased if `bytes` is not enough to hold `value`.
+    fn from_u64_bytes(value: u64, bytes: Bytes) -> Self {
+        let needed = Bytes::from_u64(value);
+        let b = if u8::from(needed) > u8::from(bytes) {
+            needed
+        } else {
+            bytes
+        };
         // ASSUME these will never truncate because we check the width first
-        match Bytes::from_u64(value) {
+        match b {
             Bytes::B1 => Self::Uint08(Bitmask::from_u64(value).0),
             Bytes::B2 => Self::Uint16(Bitmask::from_u64(value).0),
             Bytes::B3 => Self::Uint24(Bitmask::from_u64(value).0),
@@ -2108,16 +2255,7 @@ impl From<u64> for AnyNullBitmask {
             Bytes::B8 => Self::Uint64(Bitmask::from_u64(value).0),
         }
     }
-}
 
-impl From<AnyNullBitmask> for u64 {
-    /// Convert bitmask range (not bitmask itself) to u64.
-    fn from(value: AnyNullBitmask) -> Self {
-        match_any_uint!(value, AnyNullBitmask, x, { u64::from(x) })
-    }
-}
-
-impl AnyNullBitmask {
     /// Make a new bitmask from $PnB and PnR values.
     ///
     /// Will return an error if $PnB (in bits) cannot be converted into a width
@@ -2203,6 +2341,12 @@ where
         df.ascii_nbytes()
     }
 
+    fn estimated_memory_bytes(&self, total_events: Option<u64>, _: AnyDataSegment) -> Option<u64> {
+        // ASSUME all delimited ASCII columns are read as u64
+        let width = (self.ncols() * size_of::<u64>()) as u64;
+        total_events.map(|n| n * width)
+    }
+
     fn ranges(&self) -> Vec<Range> {
         self.ranges.iter().map(|x| Range::from(*x)).collect()
     }
@@ -2215,6 +2359,10 @@ where
         self.ranges.iter().map(|_| self.datatype()).collect()
     }
 
+    fn fixed_widths(&self) -> Vec<Option<BitsOrChars>> {
+        self.ranges.iter().map(|_| None).collect()
+    }
+
     fn byteord_keyword(&self) -> (String, String) {
         // NOTE BYTEORD is meaningless for delimited ASCII so use a dummy
         <NoByteOrd<ORD> as HasByteOrd>::ByteOrd::from(NoByteOrd).pair()
@@ -2236,6 +2384,11 @@ where
         self.ranges.remove(index.into());
     }
 
+    fn reorder_nocheck(&mut self, order: &[MeasIndex]) {
+        self.ranges = permute(mem::take(&mut self.ranges), order);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn h_read_df_inner<R: Read>(
         &self,
         h: &mut BufReader<R>,
@@ -2243,14 +2396,16 @@ where
         tot: T::Tot,
         seg: AnyDataSegment,
         _: &ReaderConfig,
+        exclude: &[bool],
+        _: Option<(usize, f64)>,
     ) -> IODeferredResult<FCSDataFrame, ReadDataframeWarning, ReadDataframeError> {
         let rs = &self.ranges;
         let nbytes = seg.inner.len() as usize;
         T::with_tot(
             h,
             tot,
-            |_h, t| h_read_delim_with_rows(rs, _h, t, nbytes).map_err(|e| e.inner_into()),
-            |_h| h_read_delim_without_rows(rs, _h, nbytes).map_err(|e| e.inner_into()),
+            |_h, t| h_read_delim_with_rows(rs, _h, t, nbytes, exclude).map_err(|e| e.inner_into()),
+            |_h| h_read_delim_without_rows(rs, _h, nbytes, exclude).map_err(|e| e.inner_into()),
         )
         .into_deferred()
     }
@@ -2274,17 +2429,24 @@ where
         h: &mut BufWriter<W>,
         df: &FCSDataFrame,
         skip_conv_check: bool,
-    ) -> IODeferredResult<(), ColumnError<AnyLossError>, E> {
+    ) -> IODeferredResult<WriteReport, ColumnError<AnyLossError>, E> {
         let ncols = df.ncols();
         let nrows = df.nrows();
         // ASSUME dataframe has correct number of columns
         let mut column_srcs: Vec<_> = df.iter_columns().map(AnySource::<'_, u64>::new).collect();
         let mut loss_ws = vec![None; column_srcs.len()];
+        let mut lossy_values = vec![0; column_srcs.len()];
         for row in 0..nrows {
             for (col, xs) in column_srcs.iter_mut().enumerate() {
-                let x = xs.next().unwrap();
+                let Some(x) = xs.next() else {
+                    return Err(column_exhausted()).into_deferred();
+                };
                 let s = x.new.to_string();
-                loss_ws[col] = std::mem::take(&mut loss_ws[col]).or(x.as_err());
+                let loss = x.as_err();
+                if loss.is_some() {
+                    lossy_values[col] += 1;
+                }
+                loss_ws[col] = std::mem::take(&mut loss_ws[col]).or(loss);
                 let buf = s.as_bytes();
                 h.write_all(buf).into_deferred()?;
                 // write delimiter after all but last value
@@ -2298,12 +2460,20 @@ where
         } else {
             loss_ws
                 .into_iter()
-                .flatten()
                 .enumerate()
-                .map(|(i, w)| ColumnError::new(i.into(), AnyLossError::Ascii(LossError::Cast(w))))
+                .flat_map(|(i, w)| {
+                    w.map(|x| ColumnError::new(i.into(), AnyLossError::Ascii(LossError::Cast(x))))
+                })
                 .collect()
         };
-        Ok(Tentative::new((), ws, vec![]))
+        Ok(Tentative::new(
+            WriteReport {
+                lossy_values,
+                rounding: vec![],
+            },
+            ws,
+            vec![],
+        ))
     }
 
     fn truncate_df<E>(
@@ -2317,7 +2487,7 @@ where
             .enumerate()
             .map(|(i, c)| {
                 let mut w = None;
-                let mut cs = vec![0; nrows];
+                let mut cs = Vec::with_capacity(nrows);
                 for x in AnySource::<'_, u64>::new(c) {
                     cs.push(x.new);
                     if !skip_conv_check {
@@ -2331,7 +2501,8 @@ where
             })
             .unzip();
         let ws = warnings.into_iter().flatten().collect();
-        Tentative::new(FCSDataFrame::try_new(columns).unwrap(), ws, vec![])
+        // each column was converted element-wise, so none changed length
+        Tentative::new(FCSDataFrame::new_nocheck(columns, nrows), ws, vec![])
     }
 }
 
@@ -2382,6 +2553,31 @@ impl<T, D, const ORD: bool> DelimAsciiLayout<T, D, ORD> {
             .gather()
             .void()
     }
+
+    /// Return ranges for fixed-width ASCII columns which hold all values.
+    fn solid_ascii_ranges(&self, maxima: &[u64]) -> Vec<AsciiRange> {
+        self.zip_maxima(maxima)
+            .map(|(r, m)| AsciiRange::new_covering(r, m))
+            .collect()
+    }
+
+    /// Return ranges for integer columns which hold all values.
+    ///
+    /// The bitmask will be one less than $PnR unless the data has a value
+    /// larger than this.
+    fn solid_uint_ranges(&self, maxima: &[u64]) -> Vec<u64> {
+        self.zip_maxima(maxima)
+            .map(|(r, m)| r.saturating_sub(1).max(m))
+            .collect()
+    }
+
+    fn zip_maxima(&self, maxima: &[u64]) -> impl Iterator<Item = (u64, u64)> {
+        // DATA may be empty, in which case only $PnR matters
+        self.ranges
+            .iter()
+            .copied()
+            .zip(maxima.iter().copied().chain(iter::repeat(0)))
+    }
 }
 
 fn h_read_delim_with_rows<R: Read>(
@@ -2389,6 +2585,7 @@ fn h_read_delim_with_rows<R: Read>(
     h: &mut BufReader<R>,
     tot: Tot,
     nbytes: usize,
+    exclude: &[bool],
 ) -> IOResult<FCSDataFrame, ReadDelimWithRowsAsciiError> {
     let mut buf = Vec::new();
     let mut last_was_delim = false;
@@ -2399,8 +2596,9 @@ fn h_read_delim_with_rows<R: Read>(
     if (nrows == 0 || ncols == 0) && nbytes > 0 {
         return Ok(FCSDataFrame::default());
     }
+    let keep = kept_columns(ncols, exclude);
     // Here we have $TOT so initialize vectors to required length
-    let mut data = vec![vec![0; nrows]; ncols];
+    let mut data = vec![vec![0; nrows]; keep.iter().flatten().count()];
     // let mut data = self.0.columns;
     // let nrows = data.head.len();
     // let ncols = data.len();
@@ -2418,9 +2616,11 @@ fn h_read_delim_with_rows<R: Read>(
         if is_ascii_delim(byte) {
             if !last_was_delim {
                 last_was_delim = true;
-                data[col][row] = ascii_to_uint(&buf)
-                    .map_err(ReadDelimWithRowsAsciiError::Parse)
-                    .map_err(ImpureError::Pure)?;
+                if let Some(j) = keep[col] {
+                    data[j][row] = ascii_to_uint(&buf)
+                        .map_err(ReadDelimWithRowsAsciiError::Parse)
+                        .map_err(ImpureError::Pure)?;
+                }
                 buf.clear();
                 if col == ncols - 1 {
                     col = 0;
@@ -2434,39 +2634,53 @@ fn h_read_delim_with_rows<R: Read>(
             last_was_delim = false;
         }
     }
+    // The spec isn't clear if the last value should be a delim or
+    // not, so flush the buffer if it has anything in it since we
+    // only try to parse if we hit a delim above.
+    if !buf.is_empty() {
+        if row == nrows {
+            let e = ReadDelimWithRowsAsciiError::RowsExceeded(RowsExceededError(nrows));
+            return Err(ImpureError::Pure(e));
+        }
+        if let Some(j) = keep[col] {
+            data[j][row] = ascii_to_uint(&buf)
+                .map_err(ReadDelimWithRowsAsciiError::Parse)
+                .map_err(ImpureError::Pure)?;
+        }
+        if col == ncols - 1 {
+            col = 0;
+            row += 1;
+        } else {
+            col += 1;
+        }
+    }
     if !(col == 0 && row == nrows) {
         let e = DelimIncompleteError { col, row, nrows };
         return Err(ImpureError::Pure(ReadDelimWithRowsAsciiError::Incomplete(
             e,
         )));
     }
-    // The spec isn't clear if the last value should be a delim or
-    // not, so flush the buffer if it has anything in it since we
-    // only try to parse if we hit a delim above.
-    if !buf.is_empty() {
-        data[col][row] = ascii_to_uint(&buf)
-            .map_err(ReadDelimWithRowsAsciiError::Parse)
-            .map_err(ImpureError::Pure)?;
-    }
     let cs: Vec<_> = data
         .into_iter()
         .map(FCSColumn::from)
         .map(AnyFCSColumn::from)
         .collect();
-    // ASSUME this will never fail because all columns should be the same
-    // length
-    Ok(FCSDataFrame::try_new(cs).unwrap())
+    FCSDataFrame::try_new(cs)
+        .map_err(ReadDelimWithRowsAsciiError::Columns)
+        .map_err(ImpureError::Pure)
 }
 
 fn h_read_delim_without_rows<R: Read>(
     ranges: &[u64],
     h: &mut BufReader<R>,
     nbytes: usize,
+    exclude: &[bool],
 ) -> IOResult<FCSDataFrame, ReadDelimAsciiWithoutRowsError> {
     let mut buf = Vec::new();
+    let ncols = ranges.len();
+    let keep = kept_columns(ncols, exclude);
     // Here we don't have $TOT so init to empty vectors
-    let mut data: Vec<_> = ranges.iter().map(|_| vec![]).collect();
-    let ncols = data.len();
+    let mut data: Vec<_> = keep.iter().flatten().map(|_| vec![]).collect();
     // TODO emit a real error here since this means something is probably
     // screwy with the file
     if ncols == 0 && nbytes > 0 {
@@ -2475,10 +2689,14 @@ fn h_read_delim_without_rows<R: Read>(
     let mut col = 0;
     let mut last_was_delim = false;
     let go = |_data: &mut Vec<Vec<u64>>, _col: usize, _buf: &[u8]| {
-        ascii_to_uint(_buf)
-            .map_err(ReadDelimAsciiWithoutRowsError::Parse)
-            .map_err(ImpureError::Pure)
-            .map(|x| _data[_col].push(x))
+        if let Some(j) = keep[_col] {
+            ascii_to_uint(_buf)
+                .map_err(ReadDelimAsciiWithoutRowsError::Parse)
+                .map_err(ImpureError::Pure)
+                .map(|x| _data[j].push(x))
+        } else {
+            Ok(())
+        }
     };
     // Delimiters are tab, newline, carriage return, space, or comma. Any
     // consecutive delimiter counts as one, and delimiters can be mixed.
@@ -2491,8 +2709,8 @@ fn h_read_delim_without_rows<R: Read>(
         if is_ascii_delim(byte) {
             if !last_was_delim {
                 last_was_delim = true;
-                buf.clear();
                 go(&mut data, col, &buf)?;
+                buf.clear();
                 if col == ncols - 1 {
                     col = 0;
                 } else {
@@ -2504,9 +2722,6 @@ fn h_read_delim_without_rows<R: Read>(
             last_was_delim = false;
         }
     }
-    if data.iter().map(|c| c.len()).unique().count() > 1 {
-        return Err(ImpureError::Pure(ReadDelimAsciiWithoutRowsError::Unequal));
-    }
     // The spec isn't clear if the last value should be a delim or
     // not, so flush the buffer if it has anything in it since we
     // only try to parse if we hit a delim above.
@@ -2518,9 +2733,8 @@ fn h_read_delim_without_rows<R: Read>(
         .map(FCSColumn::from)
         .map(AnyFCSColumn::from)
         .collect();
-    // ASSUME this will never fail because all columns should be the same
-    // length
-    Ok(FCSDataFrame::try_new(cs).unwrap())
+    FCSDataFrame::try_new(cs)
+        .map_err(|_| ImpureError::Pure(ReadDelimAsciiWithoutRowsError::Unequal))
 }
 
 impl<C, S: Default, T, D> Default for FixedLayout<C, S, T, D> {
@@ -2553,6 +2767,16 @@ where
         self.event_width() * (df.nrows() as u64)
     }
 
+    fn estimated_memory_bytes(
+        &self,
+        total_events: Option<u64>,
+        seg: AnyDataSegment,
+    ) -> Option<u64> {
+        let nrows = total_events.or_else(|| seg.inner.len().checked_div(self.event_width()))?;
+        let width: usize = self.columns.iter().map(|c| c.native_nbytes()).sum();
+        Some(nrows * (width as u64))
+    }
+
     fn datatype(&self) -> AlphaNumType {
         C::datatype_from_columns(&self.columns)
     }
@@ -2561,6 +2785,10 @@ where
         self.columns.iter().map(|c| c.datatype()).collect()
     }
 
+    fn fixed_widths(&self) -> Vec<Option<BitsOrChars>> {
+        self.columns.iter().map(|c| Some(c.fixed_width())).collect()
+    }
+
     fn byteord_keyword(&self) -> (String, String) {
         S::ByteOrd::from(self.byte_layout).pair()
     }
@@ -2577,6 +2805,11 @@ where
         self.columns.remove(index.into());
     }
 
+    fn reorder_nocheck(&mut self, order: &[MeasIndex]) {
+        self.columns = permute(mem::take(&mut self.columns), order);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn h_read_df_inner<R: Read>(
         &self,
         h: &mut BufReader<R>,
@@ -2584,6 +2817,8 @@ where
         tot: T::Tot,
         seg: AnyDataSegment,
         conf: &ReaderConfig,
+        exclude: &[bool],
+        time: Option<(usize, f64)>,
     ) -> IODeferredResult<FCSDataFrame, ReadDataframeWarning, ReadDataframeError>
     where
         T: TotDefinition,
@@ -2597,9 +2832,15 @@ where
                         .inner_into()
                         .errors_liftio()
                         .and_maybe(|_| {
-                            self.h_read_unchecked_df(h, n as usize, buf)
-                                .map_err(|e| e.inner_into())
-                                .into_deferred()
+                            let m = n as usize;
+                            match conf.chunk_size {
+                                Some(c) => {
+                                    self.h_read_chunked_df(h, m, c, conf, buf, exclude, time)
+                                }
+                                None => self.h_read_unchecked_df(h, m, buf, exclude),
+                            }
+                            .map_err(|e| e.inner_into())
+                            .into_deferred()
                         })
                 } else {
                     Ok(Tentative::new1(FCSDataFrame::default()))
@@ -2630,7 +2871,7 @@ where
         h: &mut BufWriter<W>,
         df: &'a FCSDataFrame,
         skip_conv_check: bool,
-    ) -> IODeferredResult<(), ColumnError<AnyLossError>, E> {
+    ) -> IODeferredResult<WriteReport, ColumnError<AnyLossError>, E> {
         let nrows = df.nrows();
         // ASSUME df has same number of columns as layout
         let mut cs: Vec<_> = self
@@ -2658,7 +2899,15 @@ where
                 .flat_map(|(i, c)| c.as_err(i.into()))
                 .collect()
         };
-        Ok(Tentative::new((), ws, vec![]))
+        let lossy_values = cs.iter().map(Writable::nlossy).collect();
+        Ok(Tentative::new(
+            WriteReport {
+                lossy_values,
+                rounding: vec![],
+            },
+            ws,
+            vec![],
+        ))
     }
 
     fn truncate_df<E>(
@@ -2683,7 +2932,12 @@ where
             .enumerate()
             .flat_map(|(i, e)| e.map(|f| ColumnError::new(i.into(), f)))
             .collect();
-        Tentative::new(FCSDataFrame::try_new(new_columns).unwrap(), ws, vec![])
+        // each column was converted element-wise, so none changed length
+        Tentative::new(
+            FCSDataFrame::new_nocheck(new_columns, df.nrows()),
+            ws,
+            vec![],
+        )
     }
 }
 
@@ -2793,34 +3047,114 @@ impl<C, S, T, D> FixedLayout<C, S, T, D> {
             .def_map_value(|columns| Self::new(columns, byte_layout))
     }
 
+    /// Read DATA in chunks, passing each through the chunk filter.
+    ///
+    /// The first chunk (sliced to zero rows) is used as a template so that
+    /// any filter which changes the columns will be caught. If `time` is given
+    /// along with a time window, each window will be passed to the filter
+    /// rather than each chunk.
+    #[allow(clippy::too_many_arguments)]
+    fn h_read_chunked_df<R: Read>(
+        &self,
+        h: &mut BufReader<R>,
+        nrows: usize,
+        chunk_size: NonZeroUsize,
+        conf: &ReaderConfig,
+        buf: &mut Vec<u8>,
+        exclude: &[bool],
+        time: Option<(usize, f64)>,
+    ) -> IOResult<FCSDataFrame, ReadDataframeError>
+    where
+        S: Copy,
+        C: IsFixed + Clone + IntoReader<S>,
+        <C as IntoReader<S>>::Target: Readable<S>,
+    {
+        let size = chunk_size.get();
+        let window = conf.chunk_time_window.zip(time);
+        let append = |acc: &mut FCSDataFrame, x: &FCSDataFrame| {
+            acc.append_rows(x)
+                .map(|_| ())
+                .map_err(ReadDataframeError::Append)
+                .map_err(ImpureError::Pure)
+        };
+        let mut df: Option<FCSDataFrame> = None;
+        // events in the last window, which may continue into the next chunk
+        let mut pending: Option<FCSDataFrame> = None;
+        // read at least once so that the columns are correct if DATA is empty
+        for start in (0..nrows.max(1)).step_by(size) {
+            let chunk = self.h_read_unchecked_df(h, size.min(nrows - start), buf, exclude)?;
+            let acc = df.get_or_insert_with(|| chunk.slice_rows(0..0));
+            if let Some((w, (i, timestep))) = window {
+                let p = pending.get_or_insert_with(|| chunk.slice_rows(0..0));
+                append(p, &chunk)?;
+                let mut bounds = p
+                    .iter_columns()
+                    .nth(i)
+                    .map(|c| c.window_bounds(w, timestep))
+                    .unwrap_or_default();
+                // the last window is only complete once the next one starts
+                if let Some(last) = bounds.pop() {
+                    for r in bounds {
+                        append(acc, &conf.chunk_filter.filter(p.slice_rows(r)))?;
+                    }
+                    *p = p.slice_rows(last);
+                }
+            } else {
+                append(acc, &conf.chunk_filter.filter(chunk))?;
+            }
+        }
+        if let (Some(acc), Some(p)) = (df.as_mut(), pending)
+            && p.nrows() > 0
+        {
+            append(acc, &conf.chunk_filter.filter(p))?;
+        }
+        Ok(df.unwrap_or_default())
+    }
+
     fn h_read_unchecked_df<R: Read>(
         &self,
         h: &mut BufReader<R>,
         nrows: usize,
         buf: &mut Vec<u8>,
+        exclude: &[bool],
     ) -> IOResult<FCSDataFrame, ReadDataframeError>
     where
         S: Copy,
         C: IsFixed + Clone + IntoReader<S>,
         <C as IntoReader<S>>::Target: Readable<S>,
     {
+        // Excluded columns don't get a reader (and thus don't allocate
+        // anything), but their bytes still need to be consumed to get to the
+        // next column.
         // TODO to clone
         let mut col_readers: Vec<_> = self
             .columns
             .iter()
-            .map(|c| c.clone().into_reader(nrows))
+            .zip(kept_columns(self.columns.len(), exclude))
+            .map(|(c, k)| {
+                k.map(|_| c.clone().into_reader(nrows))
+                    .ok_or(usize::from(u8::from(c.nbytes())))
+            })
             .collect();
+        let mut skip_buf = [0_u8; 255];
         for row in 0..nrows {
             for c in col_readers.iter_mut() {
-                c.h_read(h, row, self.byte_layout, buf)
-                    .map_err(|e| e.inner_into())?;
+                match c {
+                    Ok(r) => r
+                        .h_read(h, row, self.byte_layout, buf)
+                        .map_err(|e| e.inner_into())?,
+                    Err(n) => h.read_exact(&mut skip_buf[..*n])?,
+                }
             }
         }
         let data = col_readers
             .into_iter()
+            .flatten()
             .map(|c| c.into_dataframe_column())
             .collect();
-        Ok(FCSDataFrame::try_new(data).unwrap())
+        FCSDataFrame::try_new(data)
+            .map_err(ReadDataframeError::Columns)
+            .map_err(ImpureError::Pure)
     }
 
     fn insert_column(&mut self, index: MeasIndex, col: C) {
@@ -2882,10 +3216,7 @@ impl<C, S, T, D> FixedLayout<C, S, T, D> {
     {
         let n = seg.inner.len();
         let w = self.event_width();
-        let (t, e) = if w == 0 {
-            (None, Some(UnevenEventWidth::ZeroWidth(n)))
-        } else {
-            let total_events = n / w;
+        let (t, e) = if let Some(total_events) = n.checked_div(w) {
             let remainder = n % w;
             let e = if remainder > 0 {
                 Some(UnevenEventWidth::Remainder {
@@ -2897,6 +3228,8 @@ impl<C, S, T, D> FixedLayout<C, S, T, D> {
                 None
             };
             (Some(total_events), e)
+        } else {
+            (None, Some(UnevenEventWidth::ZeroWidth(n)))
         };
         let mut tnt = Tentative::new1(t);
         if let Some(err) = e {
@@ -3145,6 +3478,10 @@ where
         Self::BYTES.into()
     }
 
+    fn native_nbytes(&self) -> usize {
+        Self::LEN
+    }
+
     fn fixed_width(&self) -> BitsOrChars {
         Self::BYTES.into()
     }
@@ -3164,6 +3501,10 @@ where
         Self::BYTES.into()
     }
 
+    fn native_nbytes(&self) -> usize {
+        Self::LEN
+    }
+
     fn fixed_width(&self) -> BitsOrChars {
         Self::BYTES.into()
     }
@@ -3179,6 +3520,10 @@ impl IsFixed for AsciiRange {
         self.chars().into()
     }
 
+    fn native_nbytes(&self) -> usize {
+        size_of::<<Self as HasNativeType>::Native>()
+    }
+
     fn fixed_width(&self) -> BitsOrChars {
         self.chars().into()
     }
@@ -3193,6 +3538,10 @@ impl IsFixed for AnyNullBitmask {
         match_any_uint!(self, Self, x, { x.nbytes() })
     }
 
+    fn native_nbytes(&self) -> usize {
+        match_any_uint!(self, Self, x, { x.native_nbytes() })
+    }
+
     fn fixed_width(&self) -> BitsOrChars {
         match_any_uint!(self, Self, x, { x.fixed_width() })
     }
@@ -3207,6 +3556,10 @@ impl IsFixed for NullMixedType {
         match_any_mixed!(self, x, { x.nbytes() })
     }
 
+    fn native_nbytes(&self) -> usize {
+        match_any_mixed!(self, x, { x.native_nbytes() })
+    }
+
     fn fixed_width(&self) -> BitsOrChars {
         match_any_mixed!(self, x, { x.fixed_width() })
     }
@@ -3403,6 +3756,14 @@ impl<T, D, const ORD: bool> AnyAsciiLayout<T, D, ORD> {
     fn new_delim(ranges: Vec<u64>) -> Self {
         Self::Delimited(DelimAsciiLayout::new(ranges))
     }
+
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        if let Self::Delimited(x) = self {
+            Some(&x.ranges[..])
+        } else {
+            None
+        }
+    }
 }
 
 impl<T, D, const ORD: bool> FixedAsciiLayout<T, D, ORD> {
@@ -3465,6 +3826,14 @@ impl VersionedDataLayout for DataLayout2_0 {
             .def_map_value(|x| x.into())
             .def_map_warnings(|e| e.inner_into())
     }
+
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        self.0.delimited_ranges()
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        self.0.solidify_delimited(maxima, endian).map(Self)
+    }
 }
 
 impl VersionedDataLayout for DataLayout3_0 {
@@ -3498,6 +3867,14 @@ impl VersionedDataLayout for DataLayout3_0 {
             .def_map_value(|x| x.into())
             .def_map_warnings(|e| e.inner_into())
     }
+
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        self.0.delimited_ranges()
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        self.0.solidify_delimited(maxima, endian).map(Self)
+    }
 }
 
 impl VersionedDataLayout for DataLayout3_1 {
@@ -3531,6 +3908,14 @@ impl VersionedDataLayout for DataLayout3_1 {
             .def_map_value(|x| x.into())
             .def_map_warnings(|e| e.inner_into())
     }
+
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        self.0.delimited_ranges()
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        self.0.solidify_delimited(maxima, endian).map(Self)
+    }
 }
 
 impl VersionedDataLayout for DataLayout3_2 {
@@ -3612,6 +3997,20 @@ impl VersionedDataLayout for DataLayout3_2 {
             .def_map_value(Self::Mixed),
         }
     }
+
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        match self {
+            Self::NonMixed(x) => x.delimited_ranges(),
+            Self::Mixed(_) => None,
+        }
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        match self {
+            Self::NonMixed(x) => x.solidify_delimited(maxima, endian).map(Self::NonMixed),
+            Self::Mixed(_) => None,
+        }
+    }
 }
 
 impl InterLayoutOps<HasMeasDatatype> for DataLayout3_2 {
@@ -3747,6 +4146,61 @@ impl DataLayout3_2 {
     }
 }
 
+/// $PnR used for float columns in layout templates.
+///
+/// This is 2^18, which is what most digital cytometers use for their
+/// floating point channels. $PnR is only advisory for floats, so this only
+/// matters to downstream programs that use it for scaling.
+const TEMPLATE_FLOAT_RANGE: u32 = 262144;
+
+macro_rules! impl_layout_templates {
+    ($layout:ident, $inner:ident) => {
+        impl $layout {
+            /// Make a layout with `n` little-endian 32-bit float columns.
+            ///
+            /// Each $PnR will be 262144. This is the layout written by most
+            /// modern instruments.
+            pub fn standard_float32(n: usize) -> Self {
+                let r = FloatRange::new(FloatDecimal::from_u32(TEMPLATE_FLOAT_RANGE));
+                $inner::new_f32(vec![r; n], Endian::Little.into()).into()
+            }
+
+            /// Make a layout with `n` little-endian 64-bit float columns.
+            ///
+            /// Each $PnR will be 262144.
+            pub fn standard_float64(n: usize) -> Self {
+                let r = FloatRange::new(FloatDecimal::from_u32(TEMPLATE_FLOAT_RANGE));
+                $inner::new_f64(vec![r; n], Endian::Little.into()).into()
+            }
+
+            /// Make a layout with `n` big-endian 16-bit integer columns.
+            ///
+            /// `range` is $PnR for each column, which is one more than the
+            /// maximum value. Ranges above 65536 will be clamped to 65536.
+            pub fn uint16_big_endian(n: usize, range: u64) -> Self {
+                Self::uint16(n, range, Endian::Big)
+            }
+
+            /// Make a layout with `n` little-endian 16-bit integer columns.
+            ///
+            /// See [`Self::uint16_big_endian`].
+            pub fn uint16_little_endian(n: usize, range: u64) -> Self {
+                Self::uint16(n, range, Endian::Little)
+            }
+
+            fn uint16(n: usize, range: u64, endian: Endian) -> Self {
+                let (b, _) = Bitmask16::from_u64(range.saturating_sub(1));
+                $inner::new_uint(vec![b.into(); n], endian.into()).into()
+            }
+        }
+    };
+}
+
+impl_layout_templates!(DataLayout2_0, AnyOrderedLayout);
+impl_layout_templates!(DataLayout3_0, AnyOrderedLayout);
+impl_layout_templates!(DataLayout3_1, NonMixedEndianLayout);
+impl_layout_templates!(DataLayout3_2, NonMixedEndianLayout);
+
 impl<T> Default for AnyOrderedLayout<T> {
     fn default() -> Self {
         Self::Integer(AnyOrderedUintLayout::default())
@@ -3764,6 +4218,7 @@ impl<T> AnyOrderedLayout<T> {
         let b = ByteOrd2_0::lookup_req(kws);
         d.def_zip3(b, cs)
             .def_inner_into()
+            .def_and_tentatively(|x| MixedFloatByteOrdError::check(x, conf))
             .def_and_maybe(|(datatype, byteord, columns)| {
                 Self::try_new(datatype, byteord, columns, conf).def_inner_into()
             })
@@ -3775,6 +4230,7 @@ impl<T> AnyOrderedLayout<T> {
         let b = ByteOrd2_0::get_metaroot_req(kws).into_deferred();
         d.def_zip3(b, cs)
             .def_inner_into()
+            .def_and_tentatively(|x| MixedFloatByteOrdError::check(x, conf))
             .def_and_maybe(|(datatype, byteord, columns)| {
                 Self::try_new(datatype, byteord, columns, conf).def_inner_into()
             })
@@ -3807,6 +4263,42 @@ impl<T> AnyOrderedLayout<T> {
         FixedLayout::new(ranges, byte_layout).into()
     }
 
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        if let Self::Ascii(x) = self {
+            x.delimited_ranges()
+        } else {
+            None
+        }
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        let Self::Ascii(AnyAsciiLayout::Delimited(d)) = self else {
+            return None;
+        };
+        let ret = if let Some(e) = endian {
+            // 2.0 and 3.0 only allow one width for all columns, so use the
+            // widest needed by any of them
+            let xs = d.solid_uint_ranges(maxima);
+            let bytes = xs
+                .iter()
+                .map(|x| Bytes::from_u64(*x))
+                .max_by_key(|b| u8::from(*b))
+                .unwrap_or(Bytes::B1);
+            let cs = xs
+                .into_iter()
+                .map(|x| AnyNullBitmask::from_u64_bytes(x, bytes))
+                .collect();
+            // ASSUME this won't fail since all columns have the same width
+            let u = EndianLayout::<_, NoMeasDatatype>::new(cs, e)
+                .uint_try_into_ordered()
+                .ok()?;
+            Self::Integer(u)
+        } else {
+            Self::new_ascii_fixed(d.solid_ascii_ranges(maxima))
+        };
+        Some(ret)
+    }
+
     fn new_empty(datatype: AlphaNumType) -> Self {
         match datatype {
             AlphaNumType::Ascii => AnyAsciiLayout::default().into(),
@@ -3961,6 +4453,31 @@ impl<D> NonMixedEndianLayout<D> {
         FixedLayout::new(ranges, endian).into()
     }
 
+    fn delimited_ranges(&self) -> Option<&[u64]> {
+        if let Self::Ascii(x) = self {
+            x.delimited_ranges()
+        } else {
+            None
+        }
+    }
+
+    fn solidify_delimited(&self, maxima: &[u64], endian: Option<Endian>) -> Option<Self> {
+        let Self::Ascii(AnyAsciiLayout::Delimited(d)) = self else {
+            return None;
+        };
+        let ret = if let Some(e) = endian {
+            let cs = d
+                .solid_uint_ranges(maxima)
+                .into_iter()
+                .map(AnyNullBitmask::from)
+                .collect();
+            Self::new_uint(cs, e)
+        } else {
+            Self::new_ascii_fixed(d.solid_ascii_ranges(maxima))
+        };
+        Some(ret)
+    }
+
     pub(crate) fn into_ordered<T>(self) -> LayoutConvertResult<AnyOrderedLayout<T>> {
         match self {
             Self::Ascii(x) => Ok(x.phantom_into().into()),
@@ -4047,6 +4564,37 @@ pub enum FloatWidthError {
     Range(DecimalToFloatError),
 }
 
+pub struct MixedFloatByteOrdError {
+    datatype: AlphaNumType,
+    byteord: ByteOrd2_0,
+}
+
+impl MixedFloatByteOrdError {
+    /// Check that byte order is not mixed if datatype is F or D.
+    ///
+    /// This will be a warning unless the config says otherwise.
+    fn check<X, W, E>(
+        x: (AlphaNumType, ByteOrd2_0, X),
+        conf: &ReadLayoutConfig,
+    ) -> Tentative<(AlphaNumType, ByteOrd2_0, X), W, E>
+    where
+        W: From<Self>,
+        E: From<Self>,
+    {
+        let (datatype, byteord, _) = &x;
+        let is_float = matches!(datatype, AlphaNumType::Float | AlphaNumType::Double);
+        let e = (is_float && byteord.is_mixed()).then_some(Self {
+            datatype: *datatype,
+            byteord: *byteord,
+        });
+        let mut tnt = Tentative::new1(x);
+        if let Some(ee) = e {
+            tnt.push_error_or_warning(ee, conf.disallow_mixed_float_byteord);
+        }
+        tnt
+    }
+}
+
 pub struct WrongFloatWidth {
     pub width: Bytes,
     pub expected: usize,
@@ -4077,6 +4625,13 @@ pub struct TotEventMismatch {
     total_events: u64,
 }
 
+/// Error when [`ReaderConfig::chunk_time_window`] is not finite and positive
+pub struct ChunkTimeWindowError(f64);
+
+/// Warning when [`ReaderConfig::chunk_time_window`] cannot be used since there
+/// is no time measurement
+pub struct NoChunkTimeWarning;
+
 pub enum UnevenEventWidth {
     Remainder {
         event_width: u64,
@@ -4128,26 +4683,40 @@ type LookupLayoutResult<T> = DeferredResult<T, LookupLayoutWarning, LookupLayout
 
 #[derive(From, Display)]
 pub enum LookupLayoutError {
+    MixedFloat(MixedFloatByteOrdError),
     New(NewDataLayoutError),
     Raw(LookupKeysError),
 }
 
 #[derive(From, Display)]
 pub enum LookupLayoutWarning {
+    MixedFloat(MixedFloatByteOrdError),
     New(ColumnError<NewMixedTypeWarning>),
     Raw(LookupKeysWarning),
 }
 
+impl MeasIndexed for LookupLayoutWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::MixedFloat(_) => None,
+            Self::New(x) => Some(x.index.into()),
+            Self::Raw(x) => x.meas_index(),
+        }
+    }
+}
+
 type FromRawResult<T> = DeferredResult<T, RawToLayoutWarning, RawToLayoutError>;
 
 #[derive(From, Display)]
 pub enum RawToLayoutError {
+    MixedFloat(MixedFloatByteOrdError),
     New(NewDataLayoutError),
     Raw(RawParsedError),
 }
 
 #[derive(From, Display)]
 pub enum RawToLayoutWarning {
+    MixedFloat(MixedFloatByteOrdError),
     New(ColumnError<NewMixedTypeWarning>),
     Raw(ParseKeyError<NumTypeError>),
 }
@@ -4169,6 +4738,9 @@ pub enum ReadDataframeError {
     Delim(ReadDelimWithRowsAsciiError),
     DelimNoRows(ReadDelimAsciiWithoutRowsError),
     AlphaNum(AsciiToUintError),
+    Columns(NewDataframeError),
+    Append(AppendRowsError),
+    TimeWindow(ChunkTimeWindowError),
 }
 
 #[derive(From, Display)]
@@ -4188,6 +4760,7 @@ pub enum ReadFixedAsciiError {
 pub enum ReadDataframeWarning {
     Uneven(UnevenEventWidth),
     Tot(TotEventMismatch),
+    NoTime(NoChunkTimeWarning),
 }
 
 #[derive(From, Display)]
@@ -4201,6 +4774,29 @@ pub enum ReadDelimWithRowsAsciiError {
     RowsExceeded(RowsExceededError),
     Incomplete(DelimIncompleteError),
     Parse(AsciiToUintError),
+    Columns(NewDataframeError),
+}
+
+/// Map each column to its index in the output dataframe, or None if excluded.
+fn kept_columns(ncols: usize, exclude: &[bool]) -> Vec<Option<usize>> {
+    let mut j = 0;
+    (0..ncols)
+        .map(|i| {
+            if exclude.get(i).copied().unwrap_or(false) {
+                None
+            } else {
+                j += 1;
+                Some(j - 1)
+            }
+        })
+        .collect()
+}
+
+fn column_exhausted() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "column ended before expected number of rows",
+    )
 }
 
 // signify that parsing exceeded max rows
@@ -4289,6 +4885,17 @@ impl fmt::Display for WidthMismatchError {
     }
 }
 
+impl fmt::Display for MixedFloatByteOrdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "$BYTEORD is {} for $DATATYPE={}, which is neither big nor \
+             little endian and may produce garbage values",
+            self.byteord, self.datatype,
+        )
+    }
+}
+
 impl fmt::Display for WrongFloatWidth {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(
@@ -4299,6 +4906,25 @@ impl fmt::Display for WrongFloatWidth {
     }
 }
 
+impl fmt::Display for ChunkTimeWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "time window for chunks must be finite and greater than zero, got {}",
+            self.0
+        )
+    }
+}
+
+impl fmt::Display for NoChunkTimeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.write_str(
+            "time window for chunks was given but no time measurement was found, \
+             filtering DATA in chunks of events instead",
+        )
+    }
+}
+
 impl fmt::Display for TotEventMismatch {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(
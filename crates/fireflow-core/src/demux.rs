@@ -0,0 +1,70 @@
+//! Split events from barcoded (sample-multiplexed) acquisitions.
+//!
+//! When several samples are stained with distinct barcode markers and run
+//! together, each event can be assigned back to its sample by checking which
+//! barcode channel is positive. Here "positive" simply means the raw value is
+//! at or above a fixed threshold for that channel.
+
+use derive_more::{Display, From, FromStr, Into};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Minimum raw value (inclusive) for an event to be positive in a channel.
+#[derive(Clone, Copy, PartialEq, Display, FromStr, From, Into)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Threshold(pub f64);
+
+impl Threshold {
+    pub fn is_positive(&self, x: f64) -> bool {
+        x >= self.0
+    }
+}
+
+/// Assign each row to the one rule for which it is positive.
+///
+/// `columns` holds the values of the barcode channel for each rule and must be
+/// the same length as `thresholds`. Return row indices for each rule. Rows
+/// which are positive for no rules or for more than one rule are dropped.
+pub(crate) fn assign_rows(columns: &[Vec<f64>], thresholds: &[Threshold]) -> Vec<Vec<usize>> {
+    let nrows = columns.first().map_or(0, Vec::len);
+    let mut ret = vec![vec![]; thresholds.len()];
+    for r in 0..nrows {
+        let mut hits = columns
+            .iter()
+            .zip(thresholds)
+            .enumerate()
+            .filter(|(_, (xs, t))| t.is_positive(xs[r]))
+            .map(|(i, _)| i);
+        if let (Some(i), None) = (hits.next(), hits.next()) {
+            ret[i].push(r);
+        }
+    }
+    ret
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::Threshold;
+    use crate::python::macros::impl_from_py_transparent;
+
+    impl_from_py_transparent!(Threshold);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_rows() {
+        let columns = [vec![0.0, 10.0, 10.0, 1.0], vec![5.0, 1.0, 8.0, 2.0]];
+        let thresholds = [Threshold(5.0), Threshold(5.0)];
+        // row 2 is positive for both and row 3 for neither
+        assert_eq!(vec![vec![1], vec![0]], assign_rows(&columns, &thresholds));
+        assert_eq!(
+            vec![Vec::<usize>::new()],
+            assign_rows(&[vec![]], &thresholds[..1])
+        );
+    }
+}
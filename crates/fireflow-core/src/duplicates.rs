@@ -0,0 +1,186 @@
+//! Find events which repeat earlier events.
+//!
+//! Some instruments will write the same events more than once if an
+//! acquisition is aborted and restarted, or if a buffer is flushed twice. Real
+//! events will almost never have identical values across several channels,
+//! so an exact repeat is a good sign something went wrong.
+//!
+//! Rows are compared by hashing the values of the selected channels, so only
+//! one hash and index is stored per distinct event and DATA is never copied.
+
+use crate::validated::dataframe::AnyFCSColumn;
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher, RandomState};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// An event which repeats an earlier event.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct DuplicateEvent {
+    /// Index of this event
+    pub index: usize,
+
+    /// Index of the first event with the same values
+    pub original: usize,
+}
+
+/// Consecutive events which repeat consecutive earlier events.
+///
+/// A long run usually means a block of events was written twice, as would
+/// happen with an aborted re-acquisition.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct DuplicateRun {
+    /// Index of the first duplicated event in this run
+    pub start: usize,
+
+    /// Index of the event repeated by the first event in this run
+    pub original_start: usize,
+
+    /// Number of events in this run
+    pub len: usize,
+}
+
+/// Duplicated events found in DATA.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct DuplicateReport {
+    /// Number of events checked
+    pub events: usize,
+
+    /// Each event which repeats an earlier event, in order
+    pub duplicates: Vec<DuplicateEvent>,
+
+    /// Duplicates grouped into runs, in order
+    pub runs: Vec<DuplicateRun>,
+}
+
+impl DuplicateReport {
+    /// Return the fraction of events which are duplicates.
+    pub fn fraction(&self) -> f64 {
+        if self.events == 0 {
+            0.0
+        } else {
+            self.duplicates.len() as f64 / self.events as f64
+        }
+    }
+
+    /// Return true if no duplicates were found.
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty()
+    }
+}
+
+/// Find rows whose values in `columns` are identical to an earlier row.
+///
+/// All columns must be the same length. Values are compared as floats where
+/// -0.0 and 0.0 are equal and NaN is equal to itself. Rows with matching
+/// hashes are compared directly, so a hash collision will never flag two
+/// different rows; in the astronomically unlikely event of a collision, a
+/// later repeat of the second row may be missed.
+pub(crate) fn find_duplicates(columns: &[&AnyFCSColumn]) -> DuplicateReport {
+    let nrows = columns.first().map_or(0, |c| c.len());
+    let get = |r: usize| columns.iter().map(move |c| normalize(c.get_as::<f64>(r)));
+    let same = |r0: usize, r1: usize| get(r0).zip(get(r1)).all(|(x, y)| x == y);
+    let state = RandomState::new();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    let mut ret = DuplicateReport {
+        events: nrows,
+        ..DuplicateReport::default()
+    };
+    // no channels means there is nothing to compare
+    if columns.is_empty() {
+        return ret;
+    }
+    for r in 0..nrows {
+        let mut h = state.build_hasher();
+        for x in get(r) {
+            h.write_u64(x);
+        }
+        let original = *seen.entry(h.finish()).or_insert(r);
+        if original == r || !same(original, r) {
+            continue;
+        }
+        ret.duplicates.push(DuplicateEvent { index: r, original });
+        match ret.runs.last_mut() {
+            Some(run)
+                if run.start + run.len == r && run.original_start + run.len == original =>
+            {
+                run.len += 1;
+            }
+            _ => ret.runs.push(DuplicateRun {
+                start: r,
+                original_start: original,
+                len: 1,
+            }),
+        }
+    }
+    ret
+}
+
+/// Return bits of a float such that equal values have equal bits.
+fn normalize(x: f64) -> u64 {
+    if x == 0.0 {
+        0
+    } else if x.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        x.to_bits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validated::dataframe::{F32Column, U16Column};
+
+    fn run(start: usize, original_start: usize, len: usize) -> DuplicateRun {
+        DuplicateRun {
+            start,
+            original_start,
+            len,
+        }
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let a: AnyFCSColumn = U16Column::from(vec![1, 2, 3, 1, 2, 3, 9, 2]).into();
+        let b: AnyFCSColumn = F32Column::from(vec![0.5, 0.0, 1.0, 0.5, -0.0, 1.0, 0.0, 7.0]).into();
+        let r = find_duplicates(&[&a, &b]);
+        assert_eq!(8, r.events);
+        assert_eq!(
+            vec![3, 4, 5],
+            r.duplicates.iter().map(|d| d.index).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 1, 2],
+            r.duplicates.iter().map(|d| d.original).collect::<Vec<_>>()
+        );
+        assert_eq!(vec![run(3, 0, 3)], r.runs);
+        assert_eq!(0.375, r.fraction());
+        // with only one channel, the last row repeats row 1
+        let r1 = find_duplicates(&[&a]);
+        assert_eq!(vec![run(3, 0, 3), run(7, 1, 1)], r1.runs);
+    }
+
+    #[test]
+    fn test_find_duplicates_empty() {
+        let a: AnyFCSColumn = U16Column::from(vec![1, 1]).into();
+        assert!(find_duplicates(&[]).is_clean());
+        let r = find_duplicates(&[&a]);
+        assert_eq!(vec![run(1, 0, 1)], r.runs);
+        assert!(!r.is_clean());
+    }
+}
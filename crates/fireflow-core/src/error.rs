@@ -14,15 +14,15 @@
 //! to the user at the API boundary. The only way to get the value out of
 //! such a result is to run a function to process the errors/warnings.
 
+use crate::config::SharedConfig;
+
 use itertools::Itertools;
 use nonempty::NonEmpty;
+use std::cell::Cell;
 use std::convert::Infallible;
 use std::fmt;
 use std::io;
 
-// TODO add a cap to the error buffer so the user doesn't DOS themselves if
-// their file is particularly screwed up
-
 /// Final result which may be passing or not passing
 pub type TerminalResult<V, W, E, T> = Result<Terminal<V, W>, TerminalFailure<W, E, T>>;
 
@@ -33,6 +33,7 @@ pub type IOTerminalResult<V, W, E, T> = TerminalResult<V, W, ImpureError<E>, T>;
 pub struct Terminal<V, W> {
     value: V,
     warnings: Vec<W>,
+    suppressed: Suppressed,
 }
 
 /// Final failed result with either one error or multiple errors with a summary.
@@ -40,6 +41,43 @@ pub struct TerminalFailure<W, E, T> {
     warnings: Vec<W>,
     errors: Box<NonEmpty<E>>,
     reason: T,
+    suppressed: Suppressed,
+}
+
+/// Number of warnings dropped after reaching the limit in [`SharedConfig`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SuppressedWarnings(pub usize);
+
+/// Number of errors dropped after reaching the limit in [`SharedConfig`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SuppressedErrors(pub usize);
+
+/// Number of warnings and errors dropped while collecting them.
+#[derive(Clone, Copy, Default)]
+struct Suppressed {
+    warnings: usize,
+    errors: usize,
+}
+
+thread_local! {
+    /// Maximum number of warnings and errors to keep in one collection.
+    static ISSUE_LIMIT: Cell<IssueLimits> = const { Cell::new((None, None)) };
+}
+
+type IssueLimits = (Option<usize>, Option<usize>);
+
+/// Guard which limits the number of warnings and errors kept on this thread.
+///
+/// While this is alive, any warning or error pushed to a [`Tentative`] or
+/// [`DeferredFailure`] after it already has `max` of them is dropped and
+/// counted rather than stored, so that a particularly broken file cannot make
+/// us collect millions of messages. At least one error is always kept (as well
+/// as one warning if warnings will become errors). The previous limit is
+/// restored when the guard is dropped.
+///
+/// The limit is per-thread; work spawned on other threads needs its own guard.
+pub(crate) struct IssueLimit {
+    prev: IssueLimits,
 }
 
 /// Result which may have at least one error
@@ -58,6 +96,7 @@ pub struct Tentative<V, W, E> {
     value: V,
     warnings: Vec<W>,
     errors: Vec<E>,
+    suppressed: Suppressed,
 }
 
 /// A type that may be either an error or warning.
@@ -84,6 +123,7 @@ pub struct DeferredFailure<P, W, E> {
     passthru: P,
     warnings: Vec<W>,
     errors: Box<NonEmpty<E>>,
+    suppressed: Suppressed,
 }
 
 /// Result for which failure can have multiple errors
@@ -133,17 +173,35 @@ impl<V, W> Terminal<V, W> {
         Self {
             value,
             warnings: vec![],
+            suppressed: Suppressed::default(),
         }
     }
 
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Return number of warnings dropped after reaching the limit.
+    pub fn suppressed_warnings(&self) -> Option<SuppressedWarnings> {
+        SuppressedWarnings::new(self.suppressed.warnings)
+    }
+
     pub fn warnings_to_errors<T, E, F>(self, reason: T, f: F) -> TerminalResult<V, W, E, T>
     where
         F: Fn(W) -> E,
     {
+        let n = self.suppressed.warnings_to_errors();
         match NonEmpty::from_vec(self.warnings) {
             None => Ok(Terminal::new(self.value)),
             Some(ws) => Err(TerminalFailure::new(ws.map(f), reason)),
         }
+        .map(|t| t.add_suppressed(n))
+        .map_err(|e| e.add_suppressed(n))
+    }
+
+    fn add_suppressed(mut self, n: Suppressed) -> Self {
+        self.suppressed = self.suppressed.add(n);
+        self
     }
 
     pub fn value_into<U>(self) -> Terminal<U, W>
@@ -160,6 +218,19 @@ impl<V, W> Terminal<V, W> {
         Terminal {
             value: f(self.value),
             warnings: self.warnings,
+            suppressed: self.suppressed,
+        }
+    }
+
+    /// Map value with access to warnings without consuming them.
+    pub fn map_with_warnings<F, X>(self, f: F) -> Terminal<X, W>
+    where
+        F: FnOnce(V, &[W]) -> X,
+    {
+        Terminal {
+            value: f(self.value, &self.warnings[..]),
+            warnings: self.warnings,
+            suppressed: self.suppressed,
         }
     }
 
@@ -170,6 +241,7 @@ impl<V, W> Terminal<V, W> {
         Terminal {
             value: self.value,
             warnings: self.warnings.into_iter().map(f).collect(),
+            suppressed: self.suppressed,
         }
     }
 
@@ -186,18 +258,22 @@ impl<V, W> Terminal<V, W> {
     {
         match f(self.value) {
             Ok(s) => {
-                self.warnings.extend(s.warnings);
+                let mut n = self.suppressed.add(s.suppressed);
+                n.extend_warnings(&mut self.warnings, s.warnings);
                 Ok(Terminal {
                     value: s.value,
                     warnings: self.warnings,
+                    suppressed: n,
                 })
             }
             Err(e) => {
-                self.warnings.extend(e.warnings);
+                let mut n = self.suppressed.add(e.suppressed);
+                n.extend_warnings(&mut self.warnings, e.warnings);
                 Err(TerminalFailure {
                     warnings: self.warnings,
                     errors: e.errors,
                     reason: e.reason,
+                    suppressed: n,
                 })
             }
         }
@@ -207,42 +283,51 @@ impl<V, W> Terminal<V, W> {
     where
         F: FnOnce(V) -> DeferredResult<X, W, E>,
     {
+        let n = self.suppressed;
         match f(self.value) {
-            Ok(s) => {
-                self.warnings.extend(s.warnings);
+            Ok(mut s) => {
+                s.suppressed.extend_warnings(&mut self.warnings, s.warnings);
                 Tentative {
                     value: s.value,
                     warnings: self.warnings,
                     errors: s.errors,
+                    suppressed: s.suppressed,
                 }
                 .terminate(reason)
             }
-            Err(e) => {
-                self.warnings.extend(e.warnings);
+            Err(mut e) => {
+                e.suppressed.extend_warnings(&mut self.warnings, e.warnings);
                 Err(DeferredFailure {
                     // termination will throw away the passthru value so this
                     // only needs to be a dummy
                     passthru: (),
                     warnings: self.warnings,
                     errors: e.errors,
+                    suppressed: e.suppressed,
                 }
                 .terminate(reason))
             }
         }
+        .map(|t| t.add_suppressed(n))
+        .map_err(|e| e.add_suppressed(n))
     }
 
     pub fn and_tentatively<F, X, E, T>(mut self, reason: T, f: F) -> TerminalResult<X, W, E, T>
     where
         F: FnOnce(V) -> Tentative<X, W, E>,
     {
-        let s = f(self.value);
-        self.warnings.extend(s.warnings);
+        let n = self.suppressed;
+        let mut s = f(self.value);
+        s.suppressed.extend_warnings(&mut self.warnings, s.warnings);
         Tentative {
             value: s.value,
             warnings: self.warnings,
             errors: s.errors,
+            suppressed: s.suppressed,
         }
         .terminate(reason)
+        .map(|t| t.add_suppressed(n))
+        .map_err(|e| e.add_suppressed(n))
     }
 
     pub fn resolve<F, X>(self, f: F) -> (V, X)
@@ -265,9 +350,25 @@ impl<W, E, T> TerminalFailure<W, E, T> {
             warnings: vec![],
             errors: Box::new(errors),
             reason,
+            suppressed: Suppressed::default(),
         }
     }
 
+    /// Return number of warnings dropped after reaching the limit.
+    pub fn suppressed_warnings(&self) -> Option<SuppressedWarnings> {
+        SuppressedWarnings::new(self.suppressed.warnings)
+    }
+
+    /// Return number of errors dropped after reaching the limit.
+    pub fn suppressed_errors(&self) -> Option<SuppressedErrors> {
+        SuppressedErrors::new(self.suppressed.errors)
+    }
+
+    fn add_suppressed(mut self, n: Suppressed) -> Self {
+        self.suppressed = self.suppressed.add(n);
+        self
+    }
+
     // pub fn map_warnings<F, X>(self, f: F) -> TerminalFailure<X, E, T>
     // where
     //     F: Fn(W) -> X,
@@ -326,11 +427,13 @@ impl<W, E, T> TerminalFailure<W, E, T> {
     where
         F: Fn(W) -> E,
     {
-        self.errors.extend(self.warnings.into_iter().map(f));
+        let mut n = self.suppressed.warnings_to_errors();
+        n.extend_errors(self.errors.as_mut(), self.warnings.into_iter().map(f));
         Self {
             warnings: vec![],
             errors: self.errors,
             reason: self.reason,
+            suppressed: n,
         }
     }
 
@@ -344,11 +447,15 @@ impl<W, E, T> TerminalFailure<W, E, T> {
 }
 
 impl<V, W, E> Tentative<V, W, E> {
-    pub fn new(value: V, warnings: Vec<W>, errors: Vec<E>) -> Self {
+    pub fn new(value: V, mut warnings: Vec<W>, mut errors: Vec<E>) -> Self {
+        let mut suppressed = Suppressed::default();
+        suppressed.truncate_warnings(&mut warnings);
+        suppressed.truncate_errors(&mut errors);
         Self {
             value,
             warnings,
             errors,
+            suppressed,
         }
     }
 
@@ -369,11 +476,11 @@ impl<V, W, E> Tentative<V, W, E> {
     }
 
     pub fn push_warning(&mut self, x: W) {
-        self.warnings.push(x)
+        self.suppressed.push_warning(&mut self.warnings, x)
     }
 
     pub fn push_error(&mut self, x: E) {
-        self.errors.push(x)
+        self.suppressed.push_error(&mut self.errors, x)
     }
 
     pub fn push_error_or_warning<X>(&mut self, x: X, is_error: bool)
@@ -401,11 +508,11 @@ impl<V, W, E> Tentative<V, W, E> {
     }
 
     pub fn extend_warnings(&mut self, xs: impl Iterator<Item = W>) {
-        self.warnings.extend(xs)
+        self.suppressed.extend_warnings(&mut self.warnings, xs)
     }
 
     pub fn extend_errors(&mut self, xs: impl Iterator<Item = E>) {
-        self.errors.extend(xs)
+        self.suppressed.extend_errors(&mut self.errors, xs)
     }
 
     pub fn map<F, X>(self, f: F) -> Tentative<X, W, E>
@@ -416,6 +523,7 @@ impl<V, W, E> Tentative<V, W, E> {
             value: f(self.value),
             warnings: self.warnings,
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
@@ -423,20 +531,29 @@ impl<V, W, E> Tentative<V, W, E> {
     where
         F: FnMut(V) -> TerminalResult<X, W, E, T>,
     {
+        // errors in self are dropped here, so don't count any suppressed
+        let mut n = Suppressed {
+            errors: 0,
+            ..self.suppressed
+        };
         match f(self.value) {
             Ok(s) => {
-                self.warnings.extend(s.warnings);
+                n = n.add(s.suppressed);
+                n.extend_warnings(&mut self.warnings, s.warnings);
                 Ok(Terminal {
                     value: s.value,
                     warnings: self.warnings,
+                    suppressed: n,
                 })
             }
             Err(e) => {
-                self.warnings.extend(e.warnings);
+                n = n.add(e.suppressed);
+                n.extend_warnings(&mut self.warnings, e.warnings);
                 Err(TerminalFailure {
                     warnings: self.warnings,
                     errors: e.errors,
                     reason: e.reason,
+                    suppressed: n,
                 })
             }
         }
@@ -448,21 +565,25 @@ impl<V, W, E> Tentative<V, W, E> {
     {
         match f(self.value) {
             Ok(s) => {
-                self.warnings.extend(s.warnings);
-                self.errors.extend(s.errors);
+                let mut n = self.suppressed.add(s.suppressed);
+                n.extend_warnings(&mut self.warnings, s.warnings);
+                n.extend_errors(&mut self.errors, s.errors);
                 Ok(Tentative {
                     value: s.value,
                     warnings: self.warnings,
                     errors: self.errors,
+                    suppressed: n,
                 })
             }
             Err(e) => {
-                self.warnings.extend(e.warnings);
-                self.errors.extend(*e.errors);
+                let mut n = self.suppressed.add(e.suppressed);
+                n.extend_warnings(&mut self.warnings, e.warnings);
+                n.extend_errors(&mut self.errors, *e.errors);
                 Err(DeferredFailure {
                     passthru: e.passthru,
                     warnings: self.warnings,
                     errors: Box::new(NonEmpty::from_vec(self.errors).unwrap()),
+                    suppressed: n,
                 })
             }
         }
@@ -473,12 +594,14 @@ impl<V, W, E> Tentative<V, W, E> {
         F: FnOnce(V) -> Tentative<X, W, E>,
     {
         let s = f(self.value);
-        self.warnings.extend(s.warnings);
-        self.errors.extend(s.errors);
+        let mut n = self.suppressed.add(s.suppressed);
+        n.extend_warnings(&mut self.warnings, s.warnings);
+        n.extend_errors(&mut self.errors, s.errors);
         Tentative {
             value: s.value,
             warnings: self.warnings,
             errors: self.errors,
+            suppressed: n,
         }
     }
 
@@ -487,7 +610,7 @@ impl<V, W, E> Tentative<V, W, E> {
         F: FnOnce(&V) -> Option<E>,
     {
         if let Some(e) = f(&self.value) {
-            self.errors.push(e);
+            self.push_error(e);
         }
     }
 
@@ -496,7 +619,7 @@ impl<V, W, E> Tentative<V, W, E> {
         F: FnOnce(&V) -> Option<W>,
     {
         if let Some(e) = f(&self.value) {
-            self.warnings.push(e);
+            self.push_warning(e);
         }
     }
 
@@ -504,7 +627,8 @@ impl<V, W, E> Tentative<V, W, E> {
     where
         F: FnOnce(&V) -> Vec<E>,
     {
-        self.errors.extend(f(&self.value));
+        let es = f(&self.value);
+        self.extend_errors(es.into_iter());
     }
 
     pub fn map_warnings<F, X>(self, f: F) -> Tentative<V, X, E>
@@ -515,6 +639,7 @@ impl<V, W, E> Tentative<V, W, E> {
             value: self.value,
             warnings: self.warnings.into_iter().map(f).collect(),
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
@@ -526,6 +651,7 @@ impl<V, W, E> Tentative<V, W, E> {
             value: self.value,
             warnings: self.warnings,
             errors: self.errors.into_iter().map(f).collect(),
+            suppressed: self.suppressed,
         }
     }
 
@@ -555,12 +681,26 @@ impl<V, W, E> Tentative<V, W, E> {
         self.map_errors(ImpureError::Pure)
     }
 
+    /// Demote all errors to warnings.
+    pub fn errors_to_warnings<F, X>(mut self, f: F) -> Tentative<V, W, X>
+    where
+        F: Fn(E) -> W,
+    {
+        let mut n = self.suppressed.errors_to_warnings();
+        n.extend_warnings(&mut self.warnings, self.errors.into_iter().map(f));
+        Tentative {
+            value: self.value,
+            warnings: self.warnings,
+            errors: vec![],
+            suppressed: n,
+        }
+    }
+
     pub fn mconcat(xs: Vec<Self>) -> Tentative<Vec<V>, W, E> {
         let mut ret = Tentative::new1(vec![]);
         for x in xs {
             ret.value.push(x.value);
-            ret.warnings.extend(x.warnings);
-            ret.errors.extend(x.errors);
+            ret.append_issues(x.warnings, x.errors, x.suppressed);
         }
         ret
     }
@@ -570,11 +710,11 @@ impl<V, W, E> Tentative<V, W, E> {
             value: NonEmpty::new(xs.head.value),
             warnings: xs.head.warnings,
             errors: xs.head.errors,
+            suppressed: xs.head.suppressed,
         };
         for x in xs.tail {
             ret.value.push(x.value);
-            ret.warnings.extend(x.warnings);
-            ret.errors.extend(x.errors);
+            ret.append_issues(x.warnings, x.errors, x.suppressed);
         }
         ret
     }
@@ -593,11 +733,13 @@ impl<V, W, E> Tentative<V, W, E> {
                 warnings: self.warnings,
                 errors: Box::new(errors),
                 reason,
+                suppressed: self.suppressed,
             }),
             None => Ok((
                 Terminal {
                     value: self.value,
                     warnings: self.warnings,
+                    suppressed: self.suppressed,
                 },
                 reason,
             )),
@@ -666,20 +808,25 @@ impl<V, W, E> Tentative<V, W, E> {
     where
         F: Fn(V, X) -> Y,
     {
-        self.warnings.extend(other.warnings);
-        self.errors.extend(other.errors);
+        self.append_issues(other.warnings, other.errors, other.suppressed);
         Tentative {
             value: f(self.value, other.value),
             warnings: self.warnings,
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
     pub fn void(self) -> Tentative<(), W, E> {
-        Tentative::new((), self.warnings, self.errors)
+        self.map(|_| ())
+    }
+
+    fn append_issues(&mut self, ws: Vec<W>, es: Vec<E>, n: Suppressed) {
+        self.suppressed = self.suppressed.add(n);
+        self.extend_warnings(ws.into_iter());
+        self.extend_errors(es.into_iter());
     }
 
-    #[cfg(test)]
     pub(crate) fn value(&self) -> &V {
         &self.value
     }
@@ -689,7 +836,6 @@ impl<V, W, E> Tentative<V, W, E> {
         &self.errors[..]
     }
 
-    #[cfg(test)]
     pub(crate) fn warnings(&self) -> &[W] {
         &self.warnings[..]
     }
@@ -714,6 +860,7 @@ impl<V, W> Tentative<V, W, Infallible> {
         Terminal {
             value: self.value,
             warnings: self.warnings,
+            suppressed: self.suppressed,
         }
     }
 }
@@ -735,6 +882,7 @@ impl<V, W, E> Tentative<Option<V>, W, E> {
                 value,
                 warnings: self.warnings,
                 errors: self.errors,
+                suppressed: self.suppressed,
             })
         } else {
             None
@@ -754,21 +902,192 @@ impl<V: Default, W> Default for Terminal<V, W> {
     }
 }
 
+impl SuppressedWarnings {
+    fn new(n: usize) -> Option<Self> {
+        if n == 0 {
+            None
+        } else {
+            Some(Self(n))
+        }
+    }
+}
+
+impl fmt::Display for SuppressedWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} further warnings suppressed", self.0)
+    }
+}
+
+impl SuppressedErrors {
+    fn new(n: usize) -> Option<Self> {
+        if n == 0 { None } else { Some(Self(n)) }
+    }
+}
+
+impl fmt::Display for SuppressedErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} further errors suppressed", self.0)
+    }
+}
+
+impl IssueLimit {
+    pub(crate) fn new(conf: &SharedConfig) -> Self {
+        let max = conf.max_warnings;
+        // if warnings are errors, dropping all of them would make a failing
+        // result pass, so keep at least one
+        let w = if conf.warnings_are_errors {
+            max.map(|m| m.max(1))
+        } else {
+            max
+        };
+        let prev = ISSUE_LIMIT.with(|x| x.replace((w, max.map(|m| m.max(1)))));
+        Self { prev }
+    }
+}
+
+impl Drop for IssueLimit {
+    fn drop(&mut self) {
+        ISSUE_LIMIT.with(|x| x.set(self.prev));
+    }
+}
+
+/// A collection of warnings or errors which may be capped.
+trait IssueBuf<T> {
+    fn len(&self) -> usize;
+
+    fn push(&mut self, x: T);
+}
+
+impl<T> IssueBuf<T> for Vec<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn push(&mut self, x: T) {
+        self.push(x)
+    }
+}
+
+impl<T> IssueBuf<T> for NonEmpty<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn push(&mut self, x: T) {
+        self.push(x)
+    }
+}
+
+impl<T> IssueBuf<T> for Box<NonEmpty<T>> {
+    fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    fn push(&mut self, x: T) {
+        self.as_mut().push(x)
+    }
+}
+
+impl Suppressed {
+    /// Return the current limit for warnings and errors respectively.
+    fn limits() -> IssueLimits {
+        ISSUE_LIMIT.with(Cell::get)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            warnings: self.warnings + other.warnings,
+            errors: self.errors + other.errors,
+        }
+    }
+
+    fn warnings_to_errors(self) -> Self {
+        Self {
+            warnings: 0,
+            errors: self.errors + self.warnings,
+        }
+    }
+
+    fn errors_to_warnings(self) -> Self {
+        Self {
+            warnings: self.warnings + self.errors,
+            errors: 0,
+        }
+    }
+
+    fn push_warning<W>(&mut self, ws: &mut Vec<W>, x: W) {
+        self.warnings += push_capped(ws, x, Self::limits().0);
+    }
+
+    fn push_error<E>(&mut self, es: &mut impl IssueBuf<E>, x: E) {
+        self.errors += push_capped(es, x, Self::limits().1);
+    }
+
+    fn extend_warnings<W>(&mut self, ws: &mut Vec<W>, xs: impl IntoIterator<Item = W>) {
+        let max = Self::limits().0;
+        self.warnings += xs
+            .into_iter()
+            .map(|x| push_capped(ws, x, max))
+            .sum::<usize>();
+    }
+
+    fn extend_errors<E>(&mut self, es: &mut impl IssueBuf<E>, xs: impl IntoIterator<Item = E>) {
+        let max = Self::limits().1;
+        self.errors += xs
+            .into_iter()
+            .map(|x| push_capped(es, x, max))
+            .sum::<usize>();
+    }
+
+    fn truncate_warnings<W>(&mut self, ws: &mut Vec<W>) {
+        self.warnings += truncate_capped(ws, Self::limits().0);
+    }
+
+    fn truncate_errors<E>(&mut self, es: &mut Vec<E>) {
+        self.errors += truncate_capped(es, Self::limits().1);
+    }
+}
+
+/// Push `x` if `xs` is below `max`, returning 1 if it was dropped instead.
+fn push_capped<T>(xs: &mut impl IssueBuf<T>, x: T, max: Option<usize>) -> usize {
+    if max.is_some_and(|m| xs.len() >= m) {
+        1
+    } else {
+        xs.push(x);
+        0
+    }
+}
+
+/// Drop everything in `xs` after the first `max`, returning the number dropped.
+fn truncate_capped<T>(xs: &mut Vec<T>, max: Option<usize>) -> usize {
+    let n = max.map_or(0, |m| xs.len().saturating_sub(m));
+    if let Some(m) = max {
+        xs.truncate(m);
+    }
+    n
+}
+
 impl<P, W, E> DeferredFailure<P, W, E> {
-    pub fn new(warnings: Vec<W>, errors: NonEmpty<E>, passthru: P) -> Self {
+    pub fn new(mut warnings: Vec<W>, mut errors: NonEmpty<E>, passthru: P) -> Self {
+        let mut suppressed = Suppressed::default();
+        suppressed.truncate_warnings(&mut warnings);
+        // the head doesn't count toward the limit of the tail
+        let max = Suppressed::limits().1;
+        suppressed.errors += truncate_capped(&mut errors.tail, max.map(|m| m - 1));
         Self {
             warnings,
             errors: Box::new(errors),
             passthru,
+            suppressed,
         }
     }
 
     pub fn push_warning(&mut self, x: W) {
-        self.warnings.push(x)
+        self.suppressed.push_warning(&mut self.warnings, x)
     }
 
     pub fn push_error(&mut self, x: E) {
-        self.errors.push(x)
+        self.suppressed.push_error(&mut self.errors, x)
     }
 
     pub fn push_error_or_warning<X>(&mut self, x: X, is_error: bool)
@@ -791,6 +1110,7 @@ impl<P, W, E> DeferredFailure<P, W, E> {
             passthru: f(self.passthru),
             warnings: self.warnings,
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
@@ -802,6 +1122,7 @@ impl<P, W, E> DeferredFailure<P, W, E> {
             passthru: self.passthru,
             warnings: self.warnings.into_iter().map(f).collect(),
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
@@ -813,6 +1134,7 @@ impl<P, W, E> DeferredFailure<P, W, E> {
             passthru: self.passthru,
             warnings: self.warnings,
             errors: Box::new(self.errors.map(f)),
+            suppressed: self.suppressed,
         }
     }
 
@@ -831,15 +1153,16 @@ impl<P, W, E> DeferredFailure<P, W, E> {
     }
 
     pub fn unfail(self) -> Tentative<P, W, E> {
-        Tentative::new(
-            self.passthru,
-            self.warnings,
-            self.errors.into_iter().collect(),
-        )
+        Tentative {
+            value: self.passthru,
+            warnings: self.warnings,
+            errors: self.errors.into_iter().collect(),
+            suppressed: self.suppressed,
+        }
     }
 
     pub fn drop(self) -> DeferredFailure<(), W, E> {
-        DeferredFailure::new(self.warnings, *self.errors, ())
+        self.void()
     }
 
     pub fn zip<P1>(self, a: DeferredFailure<P1, W, E>) -> DeferredFailure<(P, P1), W, E> {
@@ -898,21 +1221,23 @@ impl<P, W, E> DeferredFailure<P, W, E> {
     where
         F: Fn(P, P1) -> X,
     {
-        self.warnings.extend(other.warnings);
-        self.errors.extend(*other.errors);
+        self.append_issues(other.warnings, *other.errors, other.suppressed);
         DeferredFailure {
             passthru: f(self.passthru, other.passthru),
             warnings: self.warnings,
             errors: self.errors,
+            suppressed: self.suppressed,
         }
     }
 
     pub fn void(self) -> DeferredFailure<(), W, E> {
-        DeferredFailure {
-            passthru: (),
-            warnings: self.warnings,
-            errors: self.errors,
-        }
+        self.map_passthru(|_| ())
+    }
+
+    fn append_issues(&mut self, ws: Vec<W>, es: NonEmpty<E>, n: Suppressed) {
+        self.suppressed = self.suppressed.add(n);
+        self.suppressed.extend_warnings(&mut self.warnings, ws);
+        self.suppressed.extend_errors(&mut self.errors, es);
     }
 }
 
@@ -932,13 +1257,8 @@ impl<W, E> DeferredFailure<(), W, E> {
     }
 
     pub fn mappend(mut self, other: Self) -> Self {
-        self.warnings.extend(other.warnings);
-        self.errors.extend(*other.errors);
-        Self {
-            passthru: (),
-            warnings: self.warnings,
-            errors: self.errors,
-        }
+        self.append_issues(other.warnings, *other.errors, other.suppressed);
+        self
     }
 
     pub fn mconcat(es: NonEmpty<Self>) -> Self {
@@ -950,6 +1270,7 @@ impl<W, E> DeferredFailure<(), W, E> {
             warnings: self.warnings,
             errors: self.errors,
             reason,
+            suppressed: self.suppressed,
         }
     }
 
@@ -957,16 +1278,18 @@ impl<W, E> DeferredFailure<(), W, E> {
     where
         F: Fn(W) -> E,
     {
-        self.errors.extend(self.warnings.into_iter().map(f));
+        let mut n = self.suppressed.warnings_to_errors();
+        n.extend_errors(&mut self.errors, self.warnings.into_iter().map(f));
         TerminalFailure {
             warnings: vec![],
             errors: self.errors,
             reason,
+            suppressed: n,
         }
     }
 
     pub fn unfail_with<V>(self, value: V) -> Tentative<V, W, E> {
-        Tentative::new(value, self.warnings, self.errors.into_iter().collect())
+        self.map_passthru(|_| value).unfail()
     }
 }
 
@@ -1470,6 +1793,25 @@ pub trait DeferredExt: Sized + PassthruExt {
         }
     }
 
+    /// Terminate according to [`SharedConfig`].
+    ///
+    /// Warnings will become errors if `warnings_are_errors` is set. Warnings
+    /// and errors should have already been limited to `max_warnings` while
+    /// they were collected (see [`IssueLimit`]); this only ensures the limit
+    /// still holds after converting warnings to errors.
+    fn def_terminate_shared<T, F>(
+        self,
+        reason: T,
+        conf: &SharedConfig,
+        f: F,
+    ) -> TerminalResult<Self::V, Self::W, Self::E, T>
+    where
+        F: Fn(Self::W) -> Self::E,
+    {
+        let _limit = IssueLimit::new(conf);
+        self.def_terminate_maybe_warn(reason, conf.warnings_are_errors, f)
+    }
+
     fn def_unfail(self) -> Tentative<Option<Self::V>, Self::W, Self::E>;
 
     fn def_unfail_default(self) -> Tentative<Self::V, Self::W, Self::E>
@@ -1622,3 +1964,62 @@ impl<E> From<io::Error> for ImpureError<E> {
         ImpureError::IO(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limited(max: usize, warnings_are_errors: bool) -> IssueLimit {
+        IssueLimit::new(&SharedConfig {
+            warnings_are_errors,
+            max_warnings: Some(max),
+        })
+    }
+
+    #[test]
+    fn test_limit_push() {
+        let _limit = limited(2, false);
+        let mut t = BiTentative::<(), u8>::new((), vec![1, 2, 3], vec![]);
+        t.push_warning(4);
+        t.extend_errors([5, 6, 7].into_iter());
+        assert_eq!(&[1, 2], t.warnings());
+        assert_eq!(&[5, 6], t.errors());
+        let f = t.terminate(()).err().unwrap();
+        assert_eq!(Some(SuppressedWarnings(2)), f.suppressed_warnings());
+        assert_eq!(Some(SuppressedErrors(1)), f.suppressed_errors());
+    }
+
+    #[test]
+    fn test_limit_merge() {
+        let _limit = limited(2, false);
+        let xs = (0..3)
+            .map(|i| BiTentative::<(), u8>::new((), vec![i], vec![i]))
+            .collect();
+        let t = Tentative::mconcat(xs);
+        assert_eq!(&[0, 1], t.warnings());
+        assert_eq!(&[0, 1], t.errors());
+        let f = DeferredFailure::<(), u8, u8>::new(vec![], (0, vec![1, 2, 3]).into(), ())
+            .mappend(DeferredFailure::new1(4))
+            .terminate(());
+        assert_eq!(Some(SuppressedErrors(3)), f.suppressed_errors());
+        assert_eq!(2, f.resolve(|_| (), |es, _| es.len()).1);
+    }
+
+    #[test]
+    fn test_limit_keeps_one_error() {
+        let _limit = limited(0, true);
+        let t = BiTentative::<(), u8>::new((), vec![1, 2], vec![]);
+        let f = t.terminate_nowarn((), |w| w).err().unwrap();
+        assert_eq!(Some(SuppressedErrors(1)), f.suppressed_errors());
+        assert_eq!(1, f.resolve(|_| (), |es, _| es.len()).1);
+    }
+
+    #[test]
+    fn test_limit_restored() {
+        {
+            let _limit = limited(1, false);
+        }
+        let t = BiTentative::<(), u8>::new((), vec![1, 2], vec![]);
+        assert_eq!(&[1, 2], t.warnings());
+    }
+}
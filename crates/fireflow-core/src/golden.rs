@@ -0,0 +1,298 @@
+//! Tiny deterministic FCS files for conformance testing.
+//!
+//! Each case covers one version and data layout. The files (and a JSON
+//! description of what a reader should get from them) are checked in under
+//! `tests/golden` in this crate so that other implementations (bindings in
+//! other languages, for instance) can be tested against the same files
+//! without linking to this crate.
+//!
+//! Each case is built with the layout templates (ie
+//! [`DataLayout3_1::standard_float32`]) where one exists and written with
+//! [`AnyCoreDataset::h_write_dataset`], so the files are exactly what this
+//! library would write.
+//!
+//! Every file has two measurements (`FL1` and `FL2`) and three events with
+//! values small enough to be represented exactly in all datatypes. There are
+//! no optional keywords (such as $DATE) which might make the output depend on
+//! when or where it was generated.
+//!
+//! If the cases here change, regenerate the checked-in files with:
+//!
+//! ```text
+//! FIREFLOW_BLESS_GOLDEN=1 cargo test -p fireflow-core --test golden
+//! ```
+
+use crate::config::WriteConfig;
+use crate::core::*;
+use crate::data::*;
+use crate::header::Version;
+use crate::text::byteord::{Endian, SizedByteOrd};
+use crate::text::float_decimal::FloatDecimal;
+use crate::validated::ascii_range::AsciiRange;
+use crate::validated::bitmask::Bitmask16;
+use crate::validated::dataframe::{
+    AnyFCSColumn, F32Column, F64Column, FCSDataFrame, U16Column, U64Column,
+};
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::BufWriter;
+use std::num::NonZeroU8;
+
+const SHORTNAMES: [&str; 2] = ["FL1", "FL2"];
+
+const COLUMNS: [[u16; 3]; 2] = [[1, 2, 3], [256, 512, 1000]];
+
+/// $PnR for all integer and ASCII columns.
+const RANGE: u64 = 1024;
+
+/// Byte order for [`GoldenLayout::Float32Mixed`].
+const MIXED_BYTEORD: [u8; 4] = [3, 4, 1, 2];
+
+/// One golden FCS file.
+///
+/// Obtain these with [`iter`], which only yields layouts that are valid for
+/// each version.
+#[derive(Clone, Copy, PartialEq)]
+pub struct GoldenCase {
+    version: Version,
+    layout: GoldenLayout,
+}
+
+/// The layout of DATA in a golden file.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GoldenLayout {
+    /// Little-endian 32-bit floats (ie [`DataLayout3_1::standard_float32`])
+    Float32,
+
+    /// Little-endian 64-bit floats (ie [`DataLayout3_1::standard_float64`])
+    Float64,
+
+    /// 16-bit integers (ie [`DataLayout3_1::uint16_big_endian`])
+    Uint16(Endian),
+
+    /// Fixed-width ASCII with 4 characters per value
+    Ascii,
+
+    /// 32-bit floats with byte order 3,4,1,2 (2.0 and 3.0 only)
+    Float32Mixed,
+
+    /// A 32-bit float column and a 16-bit integer column (3.2 only)
+    MixedDatatype,
+}
+
+/// What a reader should get from a golden file.
+///
+/// This is what is written to each JSON file.
+#[derive(Clone, Serialize)]
+pub struct GoldenExpected {
+    pub name: String,
+    pub version: String,
+
+    /// All keywords in TEXT, including offsets
+    pub keywords: BTreeMap<String, String>,
+
+    pub shortnames: Vec<String>,
+
+    /// Values of each column
+    pub columns: Vec<Vec<f64>>,
+}
+
+/// Return all golden cases.
+///
+/// All versions have 32 and 64-bit floats, big and little-endian 16-bit
+/// integers, and fixed ASCII. 2.0 and 3.0 also have floats with a mixed byte
+/// order, and 3.2 also has mixed datatypes.
+pub fn iter() -> impl Iterator<Item = GoldenCase> {
+    let common = [
+        GoldenLayout::Float32,
+        GoldenLayout::Float64,
+        GoldenLayout::Uint16(Endian::Little),
+        GoldenLayout::Uint16(Endian::Big),
+        GoldenLayout::Ascii,
+    ];
+    [
+        Version::FCS2_0,
+        Version::FCS3_0,
+        Version::FCS3_1,
+        Version::FCS3_2,
+    ]
+    .into_iter()
+    .flat_map(move |version| {
+        let extra = match version {
+            Version::FCS2_0 | Version::FCS3_0 => Some(GoldenLayout::Float32Mixed),
+            Version::FCS3_1 => None,
+            Version::FCS3_2 => Some(GoldenLayout::MixedDatatype),
+        };
+        common
+            .into_iter()
+            .chain(extra)
+            .map(move |layout| GoldenCase { version, layout })
+    })
+}
+
+impl GoldenCase {
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn layout(&self) -> GoldenLayout {
+        self.layout
+    }
+
+    /// Unique name for this case, which is also the stem of its files.
+    pub fn name(&self) -> String {
+        let l = match self.layout {
+            GoldenLayout::Float32 => "float32",
+            GoldenLayout::Float64 => "float64",
+            GoldenLayout::Uint16(Endian::Little) => "uint16_little",
+            GoldenLayout::Uint16(Endian::Big) => "uint16_big",
+            GoldenLayout::Ascii => "ascii",
+            GoldenLayout::Float32Mixed => "float32_3412",
+            GoldenLayout::MixedDatatype => "mixed",
+        };
+        format!("fcs{}_{l}", self.version.short_underscore())
+    }
+
+    /// Shortnames ($PnN) for each measurement.
+    pub fn shortnames(&self) -> Vec<String> {
+        SHORTNAMES.map(String::from).to_vec()
+    }
+
+    /// Values of DATA for each measurement.
+    pub fn columns(&self) -> Vec<Vec<f64>> {
+        COLUMNS
+            .iter()
+            .map(|c| c.iter().copied().map(f64::from).collect())
+            .collect()
+    }
+
+    /// Return the dataset for this case.
+    pub fn dataset(&self) -> AnyCoreDataset {
+        let df = self.dataframe();
+        let n = SHORTNAMES.len();
+        // ASSUME none of these will fail since the layout has one column for
+        // each measurement and the dataframe has the same number of columns
+        macro_rules! go {
+            ($text:ident, $layout:ident, $other:expr) => {{
+                let layout = match self.layout {
+                    GoldenLayout::Float32 => $layout::standard_float32(n),
+                    GoldenLayout::Float64 => $layout::standard_float64(n),
+                    GoldenLayout::Uint16(Endian::Big) => $layout::uint16_big_endian(n, RANGE),
+                    GoldenLayout::Uint16(Endian::Little) => $layout::uint16_little_endian(n, RANGE),
+                    _ => $other,
+                };
+                let mut core = $text::example(n);
+                core.set_layout(layout).ok().unwrap();
+                core.into_coredataset(df, Analysis::default(), Others::default())
+                    .ok()
+                    .unwrap()
+                    .into()
+            }};
+        }
+        match self.version {
+            Version::FCS2_0 => go!(CoreTEXT2_0, DataLayout2_0, self.ordered_layout().into()),
+            Version::FCS3_0 => go!(CoreTEXT3_0, DataLayout3_0, self.ordered_layout().into()),
+            Version::FCS3_1 => go!(CoreTEXT3_1, DataLayout3_1, self.endian_layout().into()),
+            Version::FCS3_2 => go!(CoreTEXT3_2, DataLayout3_2, {
+                if self.layout == GoldenLayout::MixedDatatype {
+                    let (b, _) = Bitmask16::from_u64(RANGE - 1);
+                    DataLayout3_2::new_mixed(
+                        vec![float_range::<f32, 4>().into(), b.into()],
+                        Endian::Little,
+                    )
+                } else {
+                    self.endian_layout().into()
+                }
+            }),
+        }
+    }
+
+    /// Keywords in TEXT in the order they are written.
+    pub fn keywords(&self) -> Vec<(String, String)> {
+        // ASSUME this won't fail since the dataset is valid
+        self.dataset()
+            .plan_write(&WriteConfig::default())
+            .ok()
+            .unwrap()
+            .resolve(|_| ())
+            .0
+            .keywords
+    }
+
+    /// Return the FCS file.
+    pub fn bytes(&self) -> Vec<u8> {
+        let mut h = BufWriter::new(vec![]);
+        // ASSUME this won't fail since the dataset is valid and writing to a
+        // vector can't fail
+        self.dataset()
+            .h_write_dataset(&mut h, &WriteConfig::default())
+            .ok()
+            .unwrap();
+        h.into_inner().ok().unwrap()
+    }
+
+    /// Return what should be read from the FCS file.
+    pub fn expected(&self) -> GoldenExpected {
+        GoldenExpected {
+            name: self.name(),
+            version: self.version.to_string(),
+            keywords: self.keywords().into_iter().collect(),
+            shortnames: self.shortnames(),
+            columns: self.columns(),
+        }
+    }
+
+    /// Return [`Self::expected`] as JSON.
+    pub fn json(&self) -> String {
+        // ASSUME this won't fail since everything is a string or number
+        let mut s = serde_json::to_string_pretty(&self.expected()).unwrap();
+        s.push('\n');
+        s
+    }
+
+    fn dataframe(&self) -> FCSDataFrame {
+        let f32s = |xs: [u16; 3]| F32Column::from(xs.map(f32::from).to_vec()).into();
+        let column = |xs: [u16; 3], i: usize| -> AnyFCSColumn {
+            match self.layout {
+                GoldenLayout::Float32 | GoldenLayout::Float32Mixed => f32s(xs),
+                GoldenLayout::Float64 => F64Column::from(xs.map(f64::from).to_vec()).into(),
+                GoldenLayout::Uint16(_) => U16Column::from(xs.to_vec()).into(),
+                GoldenLayout::Ascii => U64Column::from(xs.map(u64::from).to_vec()).into(),
+                GoldenLayout::MixedDatatype if i == 0 => f32s(xs),
+                GoldenLayout::MixedDatatype => U16Column::from(xs.to_vec()).into(),
+            }
+        };
+        let cs = (0..).zip(COLUMNS).map(|(i, xs)| column(xs, i)).collect();
+        // ASSUME this won't fail since all columns are the same length
+        FCSDataFrame::try_new(cs).ok().unwrap()
+    }
+
+    /// Return layout for 2.0 and 3.0 which isn't covered by a template.
+    fn ordered_layout<T>(&self) -> AnyOrderedLayout<T> {
+        let n = SHORTNAMES.len();
+        match self.layout {
+            GoldenLayout::Ascii => AnyOrderedLayout::new_ascii_fixed(ascii_ranges()),
+            _ => {
+                // ASSUME this won't fail since the byte order has 4 unique
+                // bytes from 1 to 4
+                let order = MIXED_BYTEORD.map(|x| NonZeroU8::new(x).unwrap());
+                let b = SizedByteOrd::try_from(order).ok().unwrap();
+                AnyOrderedLayout::new_f32(vec![float_range(); n], b)
+            }
+        }
+    }
+
+    /// Return layout for 3.1 and 3.2 which isn't covered by a template.
+    fn endian_layout<D>(&self) -> NonMixedEndianLayout<D> {
+        NonMixedEndianLayout::new_ascii_fixed(ascii_ranges())
+    }
+}
+
+fn ascii_ranges() -> Vec<AsciiRange> {
+    vec![AsciiRange::from(RANGE); SHORTNAMES.len()]
+}
+
+fn float_range<T, const LEN: usize>() -> FloatRange<T, LEN> {
+    FloatRange::new(FloatDecimal::from_u32(RANGE as u32))
+}
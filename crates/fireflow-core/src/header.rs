@@ -1,4 +1,4 @@
-use crate::config::{HeaderConfigInner, ReadState};
+use crate::config::{ControlCharPolicy, FloatRounding, HeaderConfigInner, ReadState};
 use crate::error::*;
 use crate::segment::*;
 use crate::text::keywords::*;
@@ -25,16 +25,20 @@ use serde::Serialize;
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 
-/// The length of the HEADER.
+pub use fireflow_header as raw;
+pub use raw::parse_header;
+
+/// The length of the HEADER without any OTHER offsets.
 ///
-/// This should always be the same. This also assumes that there are no OTHER
-/// segments (which for now are not supported).
+/// This is 6 bytes for the version, 4 spaces, and three pairs of 8-byte offsets
+/// for TEXT, DATA, and ANALYSIS. Each OTHER offset pair will add to this.
 pub const HEADER_LEN: u8 = 58;
 
 /// All FCS versions this library supports.
 ///
 /// This appears as the first 6 bytes of any valid FCS file.
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(test, derive(Debug))]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Version {
     FCS2_0,
@@ -77,7 +81,9 @@ impl<T> HeaderSegments<T> {
     where
         T: HeaderString + Zero,
     {
-        // ASSUME this is a total of 58 bytes long (sans OTHER)
+        // ASSUME this is a total of 58 bytes long (sans OTHER); each offset is
+        // right-aligned and padded with spaces, and empty segments (including
+        // those too big for HEADER in 3.0+) are written as 0,0
         for s in [
             version.to_string(),           // 6 bytes
             "    ".to_string(),            // 4 bytes
@@ -86,8 +92,7 @@ impl<T> HeaderSegments<T> {
             self.analysis.header_string(), // 16 bytes
         ]
         .into_iter()
-        // TODO the other segments will each be 20 chars wide and padded with 0,
-        // which is probably overkill
+        // OTHER offsets are either 8 or 20 bytes wide depending on T
         .chain(self.other.iter().map(|x| x.header_string()))
         {
             h.write_all(s.as_bytes())?;
@@ -218,7 +223,23 @@ impl Header {
         C: AsRef<HeaderConfigInner>,
         R: Read,
     {
-        h_read_required_header(h, st).and_then(|(version, text, data, analysis)| {
+        Self::h_read_as(h, st, None)
+    }
+
+    /// Read HEADER using `version_override` (if given) as the version.
+    ///
+    /// The version in HEADER will not be parsed if `version_override` is
+    /// given, so this may be used to read files whose version is invalid.
+    pub(crate) fn h_read_as<C, R>(
+        h: &mut BufReader<R>,
+        st: &ReadState<C>,
+        version_override: Option<Version>,
+    ) -> MultiResult<Self, ImpureError<HeaderError>>
+    where
+        C: AsRef<HeaderConfigInner>,
+        R: Read,
+    {
+        h_read_required_header(h, st, version_override).and_then(|(version, text, data, analysis)| {
             [
                 text.inner.try_coords(),
                 data.inner.try_coords(),
@@ -255,6 +276,7 @@ impl Header {
 fn h_read_required_header<C, R>(
     h: &mut BufReader<R>,
     st: &ReadState<C>,
+    version_override: Option<Version>,
 ) -> MultiResult<
     (
         Version,
@@ -269,39 +291,37 @@ where
     C: AsRef<HeaderConfigInner>,
 {
     let conf = &st.conf.as_ref();
-    let vers_res = Version::h_read(h)
-        .map_err(NonEmpty::new)
-        .mult_map_errors(|e| e.map_inner(HeaderError::Version));
-    let space_res = h_read_spaces(h).map_err(NonEmpty::new);
-    let text_res = h_read_primary_segment(h, false, conf.text_correction, st);
-    let data_res = h_read_primary_segment(h, true, conf.data_correction, st);
-    let anal_res = h_read_primary_segment(h, true, conf.analysis_correction, st);
+    let mut buf = [0_u8; raw::REQ_LEN];
+    h.read_exact(&mut buf).into_mult()?;
+    let fields = raw::split_header(&buf);
+    // if the version is overridden, it doesn't matter what HEADER says
+    let vers_res = Version::from_bytes(fields.version)
+        .map(|v| version_override.unwrap_or(v))
+        .or_else(|e| version_override.ok_or(e))
+        .map_err(|e| NonEmpty::new(ImpureError::Pure(HeaderError::Version(e))));
+    let space_res = if raw::is_blank(fields.spaces) {
+        Ok(())
+    } else {
+        Err(NonEmpty::new(ImpureError::Pure(HeaderError::Space)))
+    };
+    let text_res = parse_primary_segment(fields.text, false, conf.text_correction, st);
+    let data_res = parse_primary_segment(fields.data, true, conf.data_correction, st);
+    let anal_res = parse_primary_segment(fields.analysis, true, conf.analysis_correction, st);
     let offset_res = text_res
         .mult_zip3(data_res, anal_res)
-        .mult_map_errors(|e| e.map_inner(HeaderError::Segment));
+        .mult_map_errors(|e| ImpureError::Pure(HeaderError::Segment(e)));
     vers_res
         .mult_zip3(space_res, offset_res)
         .map(|(version, (), (text, data, analysis))| (version, text, data, analysis))
 }
 
-fn h_read_spaces<R: Read>(h: &mut BufReader<R>) -> Result<(), ImpureError<HeaderError>> {
-    let mut buf = [0_u8; 4];
-    h.read_exact(&mut buf)?;
-    if buf.iter().all(|x| *x == 32) {
-        Ok(())
-    } else {
-        Err(ImpureError::Pure(HeaderError::Space))
-    }
-}
-
-fn h_read_primary_segment<C, R, I>(
-    h: &mut BufReader<R>,
+fn parse_primary_segment<C, I>(
+    pair: [&[u8; raw::WIDTH]; 2],
     allow_blank: bool,
     corr: HeaderCorrection<I>,
     st: &ReadState<C>,
-) -> MultiResult<HeaderSegment<I>, ImpureError<HeaderSegmentError>>
+) -> MultiResult<HeaderSegment<I>, HeaderSegmentError>
 where
-    R: Read,
     C: AsRef<HeaderConfigInner>,
     I: HasRegion + Copy,
 {
@@ -311,8 +331,9 @@ where
         file_len: st.file_len.try_into().ok(),
         truncate_offsets: conf.truncate_offsets,
     };
-    HeaderSegment::<I>::h_read_offsets(
-        h,
+    HeaderSegment::<I>::parse(
+        pair[0],
+        pair[1],
         allow_blank,
         conf.allow_negative,
         conf.squish_offsets,
@@ -334,30 +355,29 @@ where
     let conf = st.conf.as_ref();
     let n = u64::from(text_begin) - u64::from(HEADER_LEN);
     let w = u8::from(conf.other_width);
-    let mut buf0 = vec![];
-    let mut buf1 = vec![];
     let n_segs = (n / (u64::from(w) * 2)) as usize;
+    let n_take = conf.max_other.map(|x| x.min(n_segs)).unwrap_or(n_segs);
+    let mut buf = vec![];
+    h.take(n_take as u64 * u64::from(w) * 2)
+        .read_to_end(&mut buf)
+        .into_mult()?;
 
     conf.other_corrections
         .iter()
         .copied()
         .chain(repeat(OffsetCorrection::default()))
-        .take(conf.max_other.map(|x| x.min(n_segs)).unwrap_or(n_segs))
-        .map(|corr| {
-            buf0.clear();
-            buf1.clear();
-            h.take(u64::from(w)).read_to_end(&mut buf0).into_mult()?;
-            h.take(u64::from(w)).read_to_end(&mut buf1).into_mult()?;
+        .zip(raw::split_other(&buf, usize::from(w)))
+        .map(|(corr, (_, buf0, buf1))| {
             let seg_conf = NewSegmentConfig {
                 corr,
                 file_len: Some(UintSpacePad20(st.file_len)),
                 truncate_offsets: conf.truncate_offsets,
             };
             // If any regions are entirely blank, just ignore them
-            if buf0.iter().chain(buf1.iter()).all(|x| *x == 32) {
+            if raw::is_blank(buf0) && raw::is_blank(buf1) {
                 Ok(None)
             } else {
-                OtherSegment::parse_other(&buf0, &buf1, conf.allow_negative, &seg_conf)
+                OtherSegment::parse_other(buf0, buf1, conf.allow_negative, &seg_conf)
                     .map(Some)
                     .mult_map_errors(HeaderError::Segment)
                     .mult_map_errors(ImpureError::Pure)
@@ -369,15 +389,16 @@ where
 }
 
 impl Version {
-    fn h_read<R: Read>(h: &mut BufReader<R>) -> Result<Self, ImpureError<VersionError>> {
+    pub(crate) fn h_read<R: Read>(h: &mut BufReader<R>) -> Result<Self, ImpureError<VersionError>> {
         let mut buf = [0; 6];
         h.read_exact(&mut buf)?;
-        if buf.is_ascii() {
-            let s = unsafe { str::from_utf8_unchecked(&buf) };
-            s.parse().map_err(ImpureError::Pure)
-        } else {
-            Err(ImpureError::Pure(VersionError(buf.to_vec())))
-        }
+        Self::from_bytes(&buf).map_err(ImpureError::Pure)
+    }
+
+    fn from_bytes(buf: &[u8; 6]) -> Result<Self, VersionError> {
+        str::from_utf8(buf)
+            .map_err(|_| VersionError(buf.to_vec()))
+            .and_then(str::parse)
     }
 
     pub fn short(&self) -> &'static str {
@@ -419,6 +440,14 @@ impl Version {
     }
 }
 
+impl TryFrom<&raw::RawHeader<'_>> for Version {
+    type Error = VersionError;
+
+    fn try_from(value: &raw::RawHeader<'_>) -> Result<Self, Self::Error> {
+        Self::from_bytes(&value.version)
+    }
+}
+
 impl str::FromStr for Version {
     type Err = VersionError;
 
@@ -494,14 +523,128 @@ impl fmt::Display for VersionError {
     }
 }
 
+/// Error when there are more OTHER segments than OTHER offsets to write
+pub struct TooManyOtherError {
+    pub segments: usize,
+    pub pairs: usize,
+}
+
+impl fmt::Display for TooManyOtherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "cannot write {} OTHER segments with only {} offset pairs in HEADER",
+            self.segments, self.pairs
+        )
+    }
+}
+
+/// Location of one segment in a file which has yet to be written.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct SegmentPlan {
+    /// Offset of the first byte
+    pub begin: u64,
+
+    /// Number of bytes, which will be 0 if nothing will be written
+    pub length: u64,
+}
+
+/// Everything that would be written for a dataset, without writing it.
+///
+/// Offsets are those of the file as a whole, so these will be shifted if the
+/// dataset is written after other bytes (ie in a multi-dataset file).
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct WritePlan {
+    pub version: Version,
+
+    /// HEADER, including OTHER offsets
+    pub header: SegmentPlan,
+
+    /// Primary TEXT
+    pub text: SegmentPlan,
+
+    /// Each OTHER segment, including those which are empty
+    pub other: Vec<SegmentPlan>,
+
+    /// Supplemental TEXT, which is only used if primary TEXT is too long
+    pub supp_text: SegmentPlan,
+
+    pub data: SegmentPlan,
+
+    pub analysis: SegmentPlan,
+
+    /// Total number of bytes which would be written
+    pub nbytes: u64,
+
+    /// Keywords in the order they will be written.
+    ///
+    /// This includes those in both primary and supplemental TEXT.
+    pub keywords: Vec<(String, String)>,
+
+    /// Rounding to be applied to each column of floats written as integers.
+    ///
+    /// Same as [`WriteReport::rounding`](crate::data::WriteReport::rounding).
+    pub rounding: Vec<Option<FloatRounding>>,
+}
+
 pub(crate) struct HeaderKeywordsToWrite<T> {
     pub(crate) header: HeaderSegments<T>,
     pub(crate) primary: KeywordsWriter,
     pub(crate) supplemental: KeywordsWriter,
+    pub(crate) gaps: SegmentGaps,
     // TODO do something useful with this
     pub(crate) _nextdata: Nextdata,
 }
 
+/// Number of padding bytes to write before each segment.
+///
+/// These will all be zero unless segments are aligned.
+#[derive(Default)]
+pub(crate) struct SegmentGaps {
+    pub(crate) text: u64,
+    pub(crate) other: Vec<u64>,
+    pub(crate) supp_text: u64,
+    pub(crate) data: u64,
+    pub(crate) analysis: u64,
+}
+
+impl SegmentGaps {
+    /// Write gap before DATA (which is written separately from TEXT)
+    pub(crate) fn h_write_data<W: Write>(&self, h: &mut BufWriter<W>, pad: u8) -> io::Result<()> {
+        h_write_padding(h, self.data, pad)
+    }
+
+    /// Write gap before ANALYSIS (which is written separately from TEXT)
+    pub(crate) fn h_write_analysis<W: Write>(
+        &self,
+        h: &mut BufWriter<W>,
+        pad: u8,
+    ) -> io::Result<()> {
+        h_write_padding(h, self.analysis, pad)
+    }
+}
+
+fn h_write_padding<W: Write>(h: &mut BufWriter<W>, n: u64, pad: u8) -> io::Result<()> {
+    io::copy(&mut io::repeat(pad).take(n), h).map(|_| ())
+}
+
+/// Move the beginning of a segment to the next multiple of `align`.
+///
+/// Empty segments are not moved since nothing will be written for them.
+fn align_begin(begin: u64, length: u64, align: u64) -> u64 {
+    if length == 0 {
+        begin
+    } else {
+        begin.next_multiple_of(align)
+    }
+}
+
 impl<T> HeaderKeywordsToWrite<T> {
     /// Create HEADER+TEXT+OTHER offsets for FCS 2.0
     pub(crate) fn new_2_0(
@@ -511,26 +654,39 @@ impl<T> HeaderKeywordsToWrite<T> {
         analysis_len: u64,
         other_lens: Vec<u64>,
         has_nextdata: bool,
+        align: u64,
     ) -> Result<HeaderKeywordsToWrite<T>, Uint8DigitOverflow>
     where
         T: TryFrom<u64, Error = Uint8DigitOverflow> + HeaderString,
     {
         let other_header_len = Self::other_header_len(&other_lens[..]);
 
-        let text_begin = u64::from(HEADER_LEN) + other_header_len;
+        let header_end = u64::from(HEADER_LEN) + other_header_len;
         // +1 at end accounts for first delimiter
         let text_len: u64 =
             raw_keywords_length(&req[..]) + raw_keywords_length(&opt[..]) + nextdata_len() + 1;
+        let text_begin = align_begin(header_end, text_len, align);
         let text_seg = PrimaryTextSegment::try_new_with_len(text_begin, text_len)?;
 
         let other_begin = text_seg.inner.try_next_byte().map_or(text_begin, u64::from);
-        let (other_segs, data_begin) = Self::other_segments(other_begin, &other_lens[..])?;
+        let (other_segs, other_gaps, other_end) =
+            Self::other_segments(other_begin, &other_lens[..], align)?;
 
+        let data_begin = align_begin(other_end, data_len, align);
         let data_seg = HeaderDataSegment::try_new_with_len(data_begin, data_len)?;
 
-        let analysis_begin = data_seg.inner.try_next_byte().map_or(text_begin, u64::from);
+        let data_end = data_seg.inner.try_next_byte().map_or(data_begin, u64::from);
+        let analysis_begin = align_begin(data_end, analysis_len, align);
         let analysis_seg = HeaderAnalysisSegment::try_new_with_len(analysis_begin, analysis_len)?;
 
+        let gaps = SegmentGaps {
+            text: text_begin - header_end,
+            other: other_gaps,
+            supp_text: 0,
+            data: data_begin - other_end,
+            analysis: analysis_begin - data_end,
+        };
+
         let nextdata = Nextdata(if !has_nextdata {
             UintZeroPad20(0)
         } else {
@@ -562,6 +718,7 @@ impl<T> HeaderKeywordsToWrite<T> {
             header,
             primary,
             supplemental: KeywordsWriter::default(),
+            gaps,
             _nextdata: nextdata,
         })
     }
@@ -577,12 +734,15 @@ impl<T> HeaderKeywordsToWrite<T> {
         analysis_len: u64,
         other_lens: Vec<u64>,
         has_nextdata: bool,
+        align: u64,
     ) -> Result<HeaderKeywordsToWrite<T>, Uint8DigitOverflow>
     where
         T: TryFrom<u64, Error = Uint8DigitOverflow> + HeaderString,
     {
         let other_header_len = Self::other_header_len(&other_lens[..]);
-        let prim_text_begin = u64::from(HEADER_LEN) + other_header_len;
+        let header_end = u64::from(HEADER_LEN) + other_header_len;
+        // TEXT is never empty so the length here doesn't matter
+        let prim_text_begin = align_begin(header_end, 1, align);
 
         let nooffset_req_text_len = raw_keywords_length(&req[..]);
         let opt_text_len = raw_keywords_length(&opt[..]);
@@ -605,40 +765,62 @@ impl<T> HeaderKeywordsToWrite<T> {
         // include STEXT only if the optional keywords don't fit within the first
         // 99,999,999 bytes
         let prim_text_res = make_text_seg(all_text_len);
-        let (prim_text_seg, other_segs, supp_text_seg, data_begin) = match prim_text_res {
-            Ok((prim_text_seg, other_begin)) => {
-                let (other_segs, next_begin) = Self::other_segments(other_begin, &other_lens[..])?;
-                (
-                    prim_text_seg,
-                    other_segs,
-                    SupplementalTextSegment::default(),
-                    next_begin,
-                )
-            }
-            Err(_) => {
-                let (prim_text_seg, other_begin) = make_text_seg(nosupp_text_len)?;
-                let (other_segs, supp_text_begin) =
-                    Self::other_segments(other_begin, &other_lens[..])?;
-                let supp_text_seg =
-                    SupplementalTextSegment::new_with_len(supp_text_begin, supp_text_len);
-                let data_begin = supp_text_seg
-                    .inner
-                    .try_next_byte()
-                    .map(u64::from)
-                    .unwrap_or(supp_text_begin);
-                (prim_text_seg, other_segs, supp_text_seg, data_begin)
-            }
-        };
+        let (prim_text_seg, other_segs, other_gaps, supp_text_seg, supp_text_gap, prev_end) =
+            match prim_text_res {
+                Ok((prim_text_seg, other_begin)) => {
+                    let (other_segs, other_gaps, other_end) =
+                        Self::other_segments(other_begin, &other_lens[..], align)?;
+                    (
+                        prim_text_seg,
+                        other_segs,
+                        other_gaps,
+                        SupplementalTextSegment::default(),
+                        0,
+                        other_end,
+                    )
+                }
+                Err(_) => {
+                    let (prim_text_seg, other_begin) = make_text_seg(nosupp_text_len)?;
+                    let (other_segs, other_gaps, other_end) =
+                        Self::other_segments(other_begin, &other_lens[..], align)?;
+                    let supp_text_begin = align_begin(other_end, supp_text_len, align);
+                    let supp_text_seg =
+                        SupplementalTextSegment::new_with_len(supp_text_begin, supp_text_len);
+                    let supp_text_end = supp_text_seg
+                        .inner
+                        .try_next_byte()
+                        .map(u64::from)
+                        .unwrap_or(supp_text_begin);
+                    (
+                        prim_text_seg,
+                        other_segs,
+                        other_gaps,
+                        supp_text_seg,
+                        supp_text_begin - other_end,
+                        supp_text_end,
+                    )
+                }
+            };
 
+        let data_begin = align_begin(prev_end, data_len, align);
         let data_seg = TEXTDataSegment::new_with_len(data_begin, data_len);
 
-        let analysis_begin = data_seg
+        let data_end = data_seg
             .inner
             .try_next_byte()
             .map(u64::from)
             .unwrap_or(data_begin);
+        let analysis_begin = align_begin(data_end, analysis_len, align);
         let analysis_seg = TEXTAnalysisSegment::new_with_len(analysis_begin, analysis_len);
 
+        let gaps = SegmentGaps {
+            text: prim_text_begin - header_end,
+            other: other_gaps,
+            supp_text: supp_text_gap,
+            data: data_begin - prev_end,
+            analysis: analysis_begin - data_end,
+        };
+
         let h_analysis_seg = analysis_seg.as_header();
         let h_data_seg = data_seg.as_header();
 
@@ -681,16 +863,72 @@ impl<T> HeaderKeywordsToWrite<T> {
             header,
             primary: KeywordsWriter(primary),
             supplemental: KeywordsWriter(supplemental),
+            gaps,
             _nextdata: nextdata,
         })
     }
 
+    /// Return where each segment will be and the keywords to be written.
+    ///
+    /// This follows the same order as [`Self::h_write`] followed by DATA and
+    /// ANALYSIS.
+    pub(crate) fn into_plan(
+        self,
+        version: Version,
+        data_len: u64,
+        analysis_len: u64,
+        rounding: Vec<Option<FloatRounding>>,
+    ) -> WritePlan
+    where
+        T: HeaderString,
+    {
+        let mut next = 0;
+        let mut seg = |gap: u64, length: u64| {
+            let begin = next + gap;
+            next = begin + length;
+            SegmentPlan { begin, length }
+        };
+        let other_lens: Vec<_> = self.header.other.iter().map(|o| o.inner.len()).collect();
+        let header = seg(0, u64::from(HEADER_LEN) + Self::other_header_len(&other_lens));
+        let text = seg(self.gaps.text, self.primary.len());
+        let other = other_lens
+            .iter()
+            .zip(&self.gaps.other)
+            .map(|(l, g)| seg(*g, *l))
+            .collect();
+        let supp_text = if self.supplemental.0.is_empty() {
+            seg(0, 0)
+        } else {
+            seg(self.gaps.supp_text, self.supplemental.len())
+        };
+        let data = seg(self.gaps.data, data_len);
+        let analysis = seg(self.gaps.analysis, analysis_len);
+        WritePlan {
+            version,
+            header,
+            text,
+            other,
+            supp_text,
+            data,
+            analysis,
+            nbytes: next,
+            keywords: self
+                .primary
+                .0
+                .into_iter()
+                .chain(self.supplemental.0)
+                .collect(),
+            rounding,
+        }
+    }
+
     pub(crate) fn h_write<W: Write>(
         &self,
         h: &mut BufWriter<W>,
         version: Version,
         delim: TEXTDelim,
         other_segs: &[Other],
+        pad: u8,
     ) -> io::Result<()>
     where
         T: Zero + HeaderString,
@@ -698,16 +936,20 @@ impl<T> HeaderKeywordsToWrite<T> {
         // write HEADER
         self.header.h_write(h, version)?;
 
-        // write primary TEXT
+        // write primary TEXT; the gap after HEADER must be spaces or else it
+        // may be interpreted as OTHER offsets
+        h_write_padding(h, self.gaps.text, b' ')?;
         self.primary.h_write(h, delim.into())?;
 
         // write OTHER
-        for o in other_segs.iter() {
+        for (o, g) in other_segs.iter().zip(self.gaps.other.iter()) {
+            h_write_padding(h, *g, pad)?;
             h.write_all(&o.0)?;
         }
 
         // write supplemental TEXT
         if !self.supplemental.0.is_empty() {
+            h_write_padding(h, self.gaps.supp_text, pad)?;
             self.supplemental.h_write(h, delim.into())?;
         }
         Ok(())
@@ -720,29 +962,26 @@ impl<T> HeaderKeywordsToWrite<T> {
         (other_lens.len() as u64) * u64::from(T::WIDTH) * 2
     }
 
+    /// Return OTHER segments, the gap before each, and the next free byte.
     #[allow(clippy::type_complexity)]
     fn other_segments(
         begin: u64,
         other_lens: &[u64],
-    ) -> Result<(Vec<OtherSegment<T>>, u64), <T as TryFrom<u64>>::Error>
+        align: u64,
+    ) -> Result<(Vec<OtherSegment<T>>, Vec<u64>, u64), <T as TryFrom<u64>>::Error>
     where
         T: Copy + TryFrom<u64> + Into<u64>,
     {
-        let ret = other_lens
-            .iter()
-            .scan(begin, |b, &length| {
-                let s = OtherSegment::try_new_with_len(*b, length);
-                *b += length;
-                Some(s)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let next = ret
-            .iter()
-            .flat_map(|x| x.inner.try_next_byte())
-            .last()
-            .map(|x| x.into())
-            .unwrap_or(begin);
-        Ok((ret, next))
+        let mut next = begin;
+        let mut segs = vec![];
+        let mut gaps = vec![];
+        for &length in other_lens {
+            let b = align_begin(next, length, align);
+            segs.push(OtherSegment::try_new_with_len(b, length)?);
+            gaps.push(b - next);
+            next = b + length;
+        }
+        Ok((segs, gaps, next))
     }
 }
 
@@ -750,6 +989,11 @@ impl<T> HeaderKeywordsToWrite<T> {
 pub(crate) struct KeywordsWriter(pub Vec<(String, String)>);
 
 impl KeywordsWriter {
+    /// Number of bytes including delimiters
+    fn len(&self) -> u64 {
+        raw_keywords_length(&self.0[..]) + 1
+    }
+
     pub(crate) fn h_write<W: Write>(&self, h: &mut BufWriter<W>, delim: u8) -> io::Result<()> {
         h.write_all(&[delim])?; // write first delim
         for s in self.0.iter().flat_map(|(k, v)| [k, v]) {
@@ -760,6 +1004,23 @@ impl KeywordsWriter {
     }
 }
 
+/// Apply control character policy to a value and escape the delimiter.
+///
+/// Values which would become empty are left unchanged since they cannot be
+/// blank. Any delimiter remaining in the value will be doubled.
+pub(crate) fn encode_value(value: String, delim: TEXTDelim, policy: ControlCharPolicy) -> String {
+    let v = policy
+        .apply(&value)
+        .filter(|x| !x.is_empty())
+        .unwrap_or(value);
+    let d = char::from(u8::from(delim));
+    if v.contains(d) {
+        v.replace(d, &format!("{d}{d}"))
+    } else {
+        v
+    }
+}
+
 fn raw_keywords_length(ks: &[(String, String)]) -> u64 {
     ks.iter().map(|(k, v)| k.len() + v.len() + 2).sum::<usize>() as u64
 }
@@ -775,6 +1036,12 @@ pub(crate) const OFFSET_VAL_LEN: u64 = 20;
 /// The maximum value that may be stored in a HEADER offset.
 pub(crate) const MAX_HEADER_OFFSET: u32 = 99_999_999;
 
+/// The position of the end offset for primary TEXT in HEADER.
+///
+/// This comes after the version (6 bytes), spaces (4 bytes), and begin offset
+/// (8 bytes).
+pub(crate) const HEADER_TEXT_END_POS: u64 = 18;
+
 /// Number of bytes consumed by $NEXTDATA keyword + value + delimiters
 fn nextdata_len() -> u64 {
     Nextdata::len() + OFFSET_VAL_LEN + 2
@@ -830,3 +1097,71 @@ mod python {
     impl_from_py_via_fromstr!(Version);
     impl_value_err!(VersionError);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_other_segments_aligned() {
+        let lens = [3, 0, 5];
+        let (segs, gaps, next) =
+            HeaderKeywordsToWrite::<UintSpacePad8>::other_segments(100, &lens, 16)
+                .ok()
+                .unwrap();
+        assert_eq!(segs.len(), 3);
+        assert_eq!(gaps, [12, 0, 13]);
+        assert_eq!(next, 133);
+        let (_, gaps1, next1) =
+            HeaderKeywordsToWrite::<UintSpacePad8>::other_segments(100, &lens, 1)
+                .ok()
+                .unwrap();
+        assert_eq!(gaps1, [0, 0, 0]);
+        assert_eq!(next1, 108);
+    }
+
+    #[test]
+    fn test_header_fixed_width() {
+        let (other, _, _) = HeaderKeywordsToWrite::<UintSpacePad8>::other_segments(200, &[7, 0], 1)
+            .ok()
+            .unwrap();
+        let hdr = HeaderSegments {
+            text: PrimaryTextSegment::try_new_with_len(58 + 32, 100)
+                .ok()
+                .unwrap(),
+            // too big for HEADER, so this should be 0,0
+            data: TEXTDataSegment::new_with_len(300, 100_000_000).as_header(),
+            analysis: HeaderAnalysisSegment::default(),
+            other,
+        };
+        let mut h = BufWriter::new(vec![]);
+        hdr.h_write(&mut h, Version::FCS3_1).unwrap();
+        let buf = h.into_inner().ok().unwrap();
+        assert_eq!(
+            "FCS3.1          90     189       0       0       0       0     200     206       0       0",
+            str::from_utf8(&buf).unwrap()
+        );
+        assert_eq!(buf.len(), usize::from(HEADER_LEN) + 32);
+        // minimal parser should agree with what was written
+        let raw = parse_header(&buf).unwrap();
+        assert_eq!(Some(Version::FCS3_1), Version::try_from(&raw).ok());
+        assert_eq!((90, 189), raw.text);
+        assert_eq!(buf.len(), raw.nbytes());
+        assert_eq!(
+            vec![Ok((200, 206)), Ok((0, 0))],
+            raw.other().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_encode_value() {
+        let delim = TEXTDelim::try_from(b'/').ok().unwrap();
+        let go = |v: &str, p| encode_value(v.into(), delim, p);
+        assert_eq!("a//b", go("a/b", ControlCharPolicy::Preserve));
+        assert_eq!("a\nb", go("a\nb", ControlCharPolicy::Preserve));
+        assert_eq!("ab//", go("a\nb/", ControlCharPolicy::Strip));
+        assert_eq!("a\\nb", go("a\nb", ControlCharPolicy::Escape));
+        // values cannot be blank
+        assert_eq!("\n", go("\n", ControlCharPolicy::Strip));
+    }
+}
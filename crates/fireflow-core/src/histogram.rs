@@ -0,0 +1,160 @@
+//! Per-channel histograms computed directly over DATA.
+//!
+//! These are meant for rendering distributions in a UI, where copying an
+//! entire column (possibly millions of events) just to bin it would be
+//! wasteful.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Counts of values in equally-spaced bins.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct Histogram {
+    /// Edges of each bin, which will have one more element than `counts`.
+    ///
+    /// Each bin includes its left edge and excludes its right edge, except for
+    /// the last bin which includes both.
+    pub edges: Vec<f64>,
+
+    /// Number of values in each bin
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Bin values between their minimum and maximum.
+    ///
+    /// Values which are not finite will be skipped. If all values are equal,
+    /// the range will be widened by 0.5 on either side. If there are no
+    /// values, the range will be 0 to 1.
+    ///
+    /// `xs` will be iterated twice (once for the range and once to count) so
+    /// that values never need to be collected.
+    pub(crate) fn from_values<I>(xs: I, bins: NonZeroUsize) -> Self
+    where
+        I: Iterator<Item = f64> + Clone,
+    {
+        let n = bins.get();
+        let (lo, hi) = xs
+            .clone()
+            .filter(|x| x.is_finite())
+            .fold(None, |acc: Option<(f64, f64)>, x| {
+                Some(acc.map_or((x, x), |(a, b)| (a.min(x), b.max(x))))
+            })
+            .map_or(
+                (0.0, 1.0),
+                |(a, b)| {
+                    if a == b {
+                        (a - 0.5, b + 0.5)
+                    } else {
+                        (a, b)
+                    }
+                },
+            );
+        let width = (hi - lo) / n as f64;
+        let edges = (0..n).map(|i| lo + width * i as f64).chain([hi]).collect();
+        let mut counts = vec![0; n];
+        for x in xs.filter(|x| x.is_finite()) {
+            // float to int casts saturate, so this can't go below zero
+            let i = (((x - lo) / width) as usize).min(n - 1);
+            counts[i] += 1;
+        }
+        Self { edges, counts }
+    }
+
+    /// Total number of values in all bins
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Estimate a percentile from the binned counts.
+    ///
+    /// `q` must be between 0 and 100. The value is linearly interpolated
+    /// within the bin that contains it, so it will be within one bin width of
+    /// the exact value. Return None if there are no values.
+    pub fn percentile(&self, q: f64) -> Result<Option<f64>, PercentileError> {
+        if !(0.0..=100.0).contains(&q) {
+            return Err(PercentileError(q));
+        }
+        let total = self.total();
+        if total == 0 {
+            return Ok(None);
+        }
+        let target = q / 100.0 * total as f64;
+        let mut seen = 0.0;
+        for (i, &c) in self.counts.iter().enumerate() {
+            let next = seen + c as f64;
+            if c > 0 && next >= target {
+                let frac = (target - seen) / c as f64;
+                let (l, r) = (self.edges[i], self.edges[i + 1]);
+                return Ok(Some(l + frac * (r - l)));
+            }
+            seen = next;
+        }
+        Ok(self.edges.last().copied())
+    }
+}
+
+/// Error when a percentile is not between 0 and 100
+pub struct PercentileError(f64);
+
+impl fmt::Display for PercentileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "percentile must be between 0 and 100, got {}", self.0)
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::PercentileError;
+    use crate::python::macros::impl_value_err;
+
+    impl_value_err!(PercentileError);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hist(xs: &[f64], bins: usize) -> Histogram {
+        Histogram::from_values(xs.iter().copied(), NonZeroUsize::new(bins).unwrap())
+    }
+
+    #[test]
+    fn test_histogram() {
+        let h = hist(&[0.0, 1.0, 2.0, 3.0, 4.0, f64::NAN], 4);
+        assert_eq!(h.edges, [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(h.counts, [1, 1, 1, 2]);
+        assert_eq!(h.total(), 5);
+    }
+
+    #[test]
+    fn test_histogram_degenerate() {
+        let h = hist(&[2.0, 2.0], 2);
+        assert_eq!(h.edges, [1.5, 2.0, 2.5]);
+        assert_eq!(h.counts, [0, 2]);
+        let e = hist(&[], 2);
+        assert_eq!(e.edges, [0.0, 0.5, 1.0]);
+        assert_eq!(e.counts, [0, 0]);
+        assert_eq!(e.percentile(50.0).ok(), Some(None));
+    }
+
+    #[test]
+    fn test_percentile() {
+        let xs: Vec<_> = (0..100).map(f64::from).collect();
+        let h = hist(&xs, 10);
+        let p = |q| h.percentile(q).ok().flatten().unwrap();
+        assert_eq!(p(0.0), 0.0);
+        assert!((p(50.0) - 49.5).abs() < 9.9);
+        assert_eq!(p(100.0), 99.0);
+        assert!(h.percentile(101.0).is_err());
+    }
+}
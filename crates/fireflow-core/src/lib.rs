@@ -2,15 +2,26 @@
 #![warn(clippy::shadow_unrelated)]
 
 pub mod api;
+pub mod channels;
+pub mod compat;
 pub mod config;
 pub mod core;
 pub mod data;
+pub mod demux;
+pub mod duplicates;
 pub mod error;
+#[cfg(feature = "golden")]
+pub mod golden;
 pub mod header;
+pub mod histogram;
 mod macros;
+pub mod manifest;
 pub mod nonempty;
+pub mod policy;
+pub mod prelude;
 #[cfg(feature = "python")]
 pub mod python;
+pub mod scan;
 pub mod segment;
 #[cfg(test)]
 mod test;
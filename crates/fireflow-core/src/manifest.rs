@@ -0,0 +1,246 @@
+//! Summarize many FCS files at once, ie for data deposits.
+//!
+//! Each file is fully read and validated, and its size, hash, and a few
+//! keywords are recorded so that the archive may later be checked for
+//! missing or modified files.
+
+use crate::api::read_std_dataset_from_bytes;
+use crate::compat::{CompatReport, Software};
+use crate::config::ReadStdDatasetConfig;
+use crate::error::SuppressedWarnings;
+use crate::header::Version;
+use crate::scan::{find_fcs_files, scan_files, ScanConfig};
+use crate::text::provenance::hash_bytes;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Standard keywords to include in each entry if present.
+pub const MANIFEST_KEYWORDS: [&str; 7] =
+    ["$CYT", "$CYTSN", "$DATE", "$BTIM", "$ETIM", "$FIL", "$OP"];
+
+/// Summary of many FCS files.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Manifest {
+    /// Software and version which made this manifest
+    pub software: String,
+
+    /// One entry for each file in the order they were found
+    pub files: Vec<ManifestEntry>,
+}
+
+/// Summary of one FCS file.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ManifestEntry {
+    pub path: PathBuf,
+
+    /// Size of the file in bytes; None if the file could not be opened
+    pub size: Option<u64>,
+
+    /// Hash of the entire file (see [`crate::text::provenance::hash_bytes`])
+    pub hash: Option<String>,
+
+    /// Version from HEADER; None if the file could not be read
+    pub version: Option<Version>,
+
+    /// Number of events in DATA
+    pub events: Option<usize>,
+
+    /// Number of measurements (ie $PAR)
+    pub measurements: Option<usize>,
+
+    /// Values for any keys in [`MANIFEST_KEYWORDS`] which are present
+    pub keywords: BTreeMap<String, String>,
+
+    pub status: ManifestStatus,
+
+    /// Compatibility with each of [`Software::ALL`]; empty if invalid.
+    ///
+    /// This does not affect `status`.
+    pub compat: Vec<CompatReport>,
+
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Outcome of validating a file for a [`ManifestEntry`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum ManifestStatus {
+    Valid,
+    Warnings,
+    Invalid,
+}
+
+impl ManifestEntry {
+    /// Read, hash, and validate one file.
+    pub fn new(p: &Path, conf: &ReadStdDatasetConfig) -> Self {
+        let mut ret = Self {
+            path: p.to_path_buf(),
+            size: None,
+            hash: None,
+            version: None,
+            events: None,
+            measurements: None,
+            keywords: BTreeMap::new(),
+            status: ManifestStatus::Invalid,
+            compat: vec![],
+            warnings: vec![],
+            errors: vec![],
+        };
+        // read the file once and use the same bytes for both the hash and
+        // the dataset
+        let bytes = match fs::read(p) {
+            Ok(bs) => bs,
+            Err(e) => {
+                ret.errors.push(e.to_string());
+                return ret;
+            }
+        };
+        ret.size = Some(bytes.len() as u64);
+        ret.hash = Some(hash_bytes(&bytes));
+        match read_std_dataset_from_bytes(&bytes, conf) {
+            Ok(t) => {
+                let n = t.suppressed_warnings();
+                let ((core, _), ws) = t.resolve(|ws| warning_strings(&ws, n));
+                let kws = core.standard_keywords();
+                ret.version = Some(core.version());
+                ret.events = Some(core.as_data().nrows());
+                ret.measurements = Some(core.shortnames().len());
+                ret.keywords = MANIFEST_KEYWORDS
+                    .iter()
+                    .filter_map(|k| kws.get(*k).map(|v| (k.to_string(), v.clone())))
+                    .collect();
+                ret.compat = Software::ALL.map(|s| core.check_compat(s)).into();
+                ret.warnings = ws;
+            }
+            Err(f) => {
+                let n = f.suppressed_warnings();
+                let m = f.suppressed_errors();
+                let (ws, es) = f.resolve(
+                    |ws| warning_strings(&ws, n),
+                    |es, r| {
+                        [r.to_string()]
+                            .into_iter()
+                            .chain(es.iter().map(|e| e.to_string()))
+                            .chain(m.map(|x| x.to_string()))
+                            .collect()
+                    },
+                );
+                ret.warnings = ws;
+                ret.errors.extend::<Vec<_>>(es);
+            }
+        }
+        ret.status = if !ret.errors.is_empty() {
+            ManifestStatus::Invalid
+        } else if !ret.warnings.is_empty() {
+            ManifestStatus::Warnings
+        } else {
+            ManifestStatus::Valid
+        };
+        ret
+    }
+}
+
+impl Manifest {
+    /// Make manifest for all FCS files in `paths`.
+    ///
+    /// Directories are searched recursively (see [`find_fcs_files`]) and files
+    /// are read concurrently according to `scan_conf`.
+    pub fn new(paths: &[PathBuf], conf: &ReadStdDatasetConfig, scan_conf: &ScanConfig) -> Self {
+        let fcs_paths = find_fcs_files(paths.iter());
+        let c = conf.clone();
+        let files = scan_files(fcs_paths, scan_conf, move |p| ManifestEntry::new(p, &c))
+            .map(|(_, e)| e)
+            .collect();
+        Self {
+            software: format!("fireflow {}", env!("CARGO_PKG_VERSION")),
+            files,
+        }
+    }
+
+    /// Return true if all files were read without errors.
+    pub fn is_valid(&self) -> bool {
+        self.files
+            .iter()
+            .all(|e| e.status != ManifestStatus::Invalid)
+    }
+}
+
+/// Return warnings as strings with a final summary of any which were dropped.
+fn warning_strings<W: std::fmt::Display>(ws: &[W], n: Option<SuppressedWarnings>) -> Vec<String> {
+    ws.iter()
+        .map(|w| w.to_string())
+        .chain(n.map(|s| s.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_unreadable() {
+        let p = PathBuf::from("/this/does/not/exist.fcs");
+        let m = Manifest::new(
+            std::slice::from_ref(&p),
+            &Default::default(),
+            &Default::default(),
+        );
+        assert_eq!(1, m.files.len());
+        let e = &m.files[0];
+        assert_eq!(p, e.path);
+        assert_eq!(ManifestStatus::Invalid, e.status);
+        assert_eq!((None, None), (e.size, e.hash.as_ref()));
+        assert_eq!(1, e.errors.len());
+        assert!(!m.is_valid());
+    }
+
+    #[test]
+    fn test_manifest_dir() {
+        let golden = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+        let good = fs::read(golden.join("fcs3_1_float32.fcs")).unwrap();
+        let dir = std::env::temp_dir().join("fireflow_manifest_dir");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/good.fcs"), &good).unwrap();
+        fs::write(dir.join("bad.FCS"), &good[..40]).unwrap();
+        fs::write(dir.join("notes.txt"), b"not an FCS file").unwrap();
+
+        let m = Manifest::new(
+            std::slice::from_ref(&dir),
+            &Default::default(),
+            &Default::default(),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        let paths: Vec<_> = m.files.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(vec![dir.join("bad.FCS"), dir.join("sub/good.fcs")], paths);
+        assert!(!m.is_valid());
+
+        let bad = &m.files[0];
+        assert_eq!(ManifestStatus::Invalid, bad.status);
+        assert_eq!(Some(40), bad.size);
+        assert_eq!(Some(hash_bytes(&good[..40])), bad.hash);
+        assert_eq!(None, bad.version);
+        assert!(!bad.errors.is_empty());
+        assert!(bad.compat.is_empty());
+
+        let e = &m.files[1];
+        assert_eq!(ManifestStatus::Valid, e.status);
+        assert_eq!(Some(good.len() as u64), e.size);
+        assert_eq!(Some(hash_bytes(&good)), e.hash);
+        assert!(e.version == Some(Version::FCS3_1));
+        assert_eq!((Some(3), Some(2)), (e.events, e.measurements));
+        assert!(e.errors.is_empty() && e.warnings.is_empty());
+        assert!(e.compat.iter().all(|c| c.is_compatible()));
+        assert_eq!(Software::ALL.len(), e.compat.len());
+    }
+}
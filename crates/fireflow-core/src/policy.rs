@@ -0,0 +1,189 @@
+//! Site-specific rules which go beyond what the FCS standard requires.
+//!
+//! Facilities often have their own conventions for which keywords must be
+//! present, how measurements are named, which versions are accepted, etc. None
+//! of these make a file invalid per the standard, so they are checked
+//! separately from reading and reported as violations rather than errors.
+
+use crate::header::Version;
+use crate::text::index::MeasIndex;
+use crate::validated::shortname::Shortname;
+
+use derive_more::{Display, FromStr};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Rules to be checked against a dataset.
+///
+/// Each rule is only checked if it is set (or non-empty).
+#[derive(Clone, Default)]
+pub struct SitePolicy {
+    /// Versions which are allowed
+    pub versions: Vec<Version>,
+
+    /// Keywords which must be present, standard or nonstandard.
+    ///
+    /// These are compared case-insensitively. Keywords which are written
+    /// automatically (offsets, $NEXTDATA, and $TOT) are not included in the
+    /// comparison.
+    pub required_keywords: Vec<String>,
+
+    /// Pattern which must match all $PnN
+    pub shortname_pattern: Option<ShortnamePattern>,
+
+    /// Maximum number of events
+    pub max_events: Option<usize>,
+}
+
+/// A pattern to match the $PnN of all measurements.
+#[derive(Clone, FromStr, Display)]
+pub struct ShortnamePattern(pub Regex);
+
+/// A rule in a [`SitePolicy`] which was not satisfied
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PolicyViolation {
+    /// Version is not one of those allowed
+    Version {
+        version: Version,
+        allowed: Vec<Version>,
+    },
+
+    /// Keyword is required but not present
+    MissingKeyword(String),
+
+    /// $PnN does not match the required pattern
+    Shortname { index: MeasIndex, name: Shortname },
+
+    /// Number of events exceeds the maximum
+    TooManyEvents { events: usize, max: usize },
+}
+
+impl SitePolicy {
+    /// Check policy against keywords and measurement names.
+    ///
+    /// Keywords should include both standard and nonstandard keys. Number of
+    /// events will only be checked if given.
+    pub(crate) fn check(
+        &self,
+        version: Version,
+        kws: &HashMap<String, String>,
+        shortnames: &[Shortname],
+        events: Option<usize>,
+    ) -> Vec<PolicyViolation> {
+        let mut ret = vec![];
+        if !self.versions.is_empty() && !self.versions.contains(&version) {
+            ret.push(PolicyViolation::Version {
+                version,
+                allowed: self.versions.clone(),
+            });
+        }
+        for k in &self.required_keywords {
+            if !kws.keys().any(|x| x.eq_ignore_ascii_case(k)) {
+                ret.push(PolicyViolation::MissingKeyword(k.clone()));
+            }
+        }
+        if let Some(p) = &self.shortname_pattern {
+            for (i, n) in shortnames.iter().enumerate() {
+                if !p.0.is_match(n.as_ref()) {
+                    ret.push(PolicyViolation::Shortname {
+                        index: i.into(),
+                        name: n.clone(),
+                    });
+                }
+            }
+        }
+        if let (Some(max), Some(n)) = (self.max_events, events)
+            && n > max
+        {
+            ret.push(PolicyViolation::TooManyEvents { events: n, max });
+        }
+        ret
+    }
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Version { version, allowed } => {
+                let xs: Vec<_> = allowed.iter().map(|x| x.to_string()).collect();
+                write!(
+                    f,
+                    "{version} is not allowed, must be one of {}",
+                    xs.join(", ")
+                )
+            }
+            Self::MissingKeyword(k) => write!(f, "required keyword {k} is missing"),
+            Self::Shortname { index, name } => {
+                write!(
+                    f,
+                    "$PnN for measurement {index} ('{name}') does not match pattern"
+                )
+            }
+            Self::TooManyEvents { events, max } => {
+                write!(f, "{events} events exceeds maximum of {max}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kws(xs: &[(&str, &str)]) -> HashMap<String, String> {
+        xs.iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    fn names(xs: &[&str]) -> Vec<Shortname> {
+        xs.iter().map(|x| x.parse().ok().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_empty_policy() {
+        let p = SitePolicy::default();
+        let r = p.check(Version::FCS2_0, &kws(&[]), &names(&["x"]), Some(1_000_000));
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn test_policy() {
+        let p = SitePolicy {
+            versions: vec![Version::FCS3_1, Version::FCS3_2],
+            required_keywords: vec!["$OP".into(), "site_id".into()],
+            shortname_pattern: Some("^[A-Z]+-[AHW]$".parse().ok().unwrap()),
+            max_events: Some(100),
+        };
+        let k = kws(&[("$OP", "me"), ("SITE_ID", "1")]);
+        let ns = names(&["FSC-A", "SSC-A"]);
+        assert!(p.check(Version::FCS3_1, &k, &ns, Some(100)).is_empty());
+        assert!(p.check(Version::FCS3_1, &k, &ns, None).is_empty());
+        let bad_k = kws(&[("$OP", "me")]);
+        let bad_ns = names(&["FSC-A", "fl1"]);
+        assert_eq!(
+            p.check(Version::FCS3_0, &bad_k, &bad_ns, Some(101)),
+            [
+                PolicyViolation::Version {
+                    version: Version::FCS3_0,
+                    allowed: vec![Version::FCS3_1, Version::FCS3_2],
+                },
+                PolicyViolation::MissingKeyword("site_id".into()),
+                PolicyViolation::Shortname {
+                    index: 1.into(),
+                    name: bad_ns[1].clone(),
+                },
+                PolicyViolation::TooManyEvents {
+                    events: 101,
+                    max: 100
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Commonly-used types and functions.
+//!
+//! This is meant to be glob-imported to cover most of what is needed to read,
+//! inspect, and write FCS files:
+//!
+//! ```
+//! use fireflow_core::prelude::*;
+//!
+//! let conf = ReadStdDatasetConfig::default();
+//! # let _ = conf;
+//! ```
+//!
+//! Anything more specialized (individual keyword types, layouts, etc) should
+//! be imported from its own module.
+
+pub use crate::api::{
+    fcs_patch_text, fcs_patch_version, fcs_read_header, fcs_read_raw_dataset,
+    fcs_read_raw_dataset_with_keywords, fcs_read_raw_text, fcs_read_std_dataset,
+    fcs_read_std_dataset_with_keywords, fcs_read_std_datasets, fcs_read_std_datasets_concatenated,
+    fcs_read_std_text, KeywordChange, RawDatasetOutput, RawTEXTOutput, StdDatasetOutput,
+    StdTEXTOutput,
+};
+pub use crate::config::{
+    ReadHeaderConfig, ReadLayoutConfig, ReadRawDatasetConfig, ReadRawTEXTConfig,
+    ReadStdDatasetConfig, ReadStdTEXTConfig, ReaderConfig, SharedConfig, StdTextReadConfig,
+    TimeMeasNamePattern, WriteConfig,
+};
+pub use crate::core::{
+    Analysis, AnyCoreDataset, AnyCoreTEXT, CommonMetadata, CoreDataset2_0, CoreDataset3_0,
+    CoreDataset3_1, CoreDataset3_2, CoreTEXT2_0, CoreTEXT3_0, CoreTEXT3_1, CoreTEXT3_2, Others,
+};
+pub use crate::data::WriteReport;
+pub use crate::error::{
+    IOTerminalResult, SuppressedErrors, SuppressedWarnings, Terminal, TerminalFailure,
+    TerminalResult,
+};
+pub use crate::duplicates::DuplicateReport;
+pub use crate::header::{Version, WritePlan};
+pub use crate::histogram::Histogram;
+pub use crate::text::diff::KeywordDiff;
+pub use crate::validated::dataframe::{AnyFCSColumn, FCSDataFrame};
+pub use crate::validated::shortname::Shortname;
@@ -1,4 +1,6 @@
-use crate::error::{Terminal, TerminalFailure, TerminalResult};
+use crate::error::{
+    SuppressedErrors, SuppressedWarnings, Terminal, TerminalFailure, TerminalResult,
+};
 
 use nonempty::NonEmpty;
 use pyo3::create_exception;
@@ -72,7 +74,8 @@ fn handle_warnings<X, W>(t: Terminal<X, W>) -> PyResult<X>
 where
     W: fmt::Display,
 {
-    let (x, warn_res) = t.resolve(emit_warnings);
+    let n = t.suppressed_warnings();
+    let (x, warn_res) = t.resolve(|ws| emit_warnings(ws, n));
     warn_res?;
     Ok(x)
 }
@@ -85,7 +88,9 @@ where
     T: fmt::Display,
     W: fmt::Display,
 {
-    let (warn_res, e) = f.resolve(emit_warnings, emit_failure);
+    let n = f.suppressed_warnings();
+    let m = f.suppressed_errors();
+    let (warn_res, e) = f.resolve(|ws| emit_warnings(ws, n), |es, r| emit_failure(es, r, m));
     if let Err(w) = warn_res {
         w
     } else {
@@ -93,14 +98,14 @@ where
     }
 }
 
-fn emit_warnings<W>(ws: Vec<W>) -> PyResult<()>
+fn emit_warnings<W>(ws: Vec<W>, suppressed: Option<SuppressedWarnings>) -> PyResult<()>
 where
     W: fmt::Display,
 {
     Python::with_gil(|py| -> PyResult<()> {
         let wt = py.get_type::<PyreflowWarning>();
-        for w in ws {
-            let s = CString::new(w.to_string())?;
+        for w in ws.iter().map(|w| w.to_string()).chain(suppressed.map(|s| s.to_string())) {
+            let s = CString::new(w)?;
             PyErr::warn(py, &wt, &s, 0)?;
         }
         Ok(())
@@ -112,10 +117,11 @@ where
     E: fmt::Display,
     T: fmt::Display,
 {
-    f.resolve(|_| (), emit_failure).1
+    let m = f.suppressed_errors();
+    f.resolve(|_| (), |es, r| emit_failure(es, r, m)).1
 }
 
-fn emit_failure<E, T>(es: NonEmpty<E>, r: T) -> PyErr
+fn emit_failure<E, T>(es: NonEmpty<E>, r: T, suppressed: Option<SuppressedErrors>) -> PyErr
 where
     E: fmt::Display,
     T: fmt::Display,
@@ -124,6 +130,7 @@ where
         let xs: Vec<_> = [format!("Toplevel Error: {r}")]
             .into_iter()
             .chain(es.into_iter().map(|x| x.to_string()))
+            .chain(suppressed.map(|x| x.to_string()))
             .collect();
         xs[..].join("\n").to_string()
     };
@@ -76,7 +76,7 @@ macro_rules! impl_to_py_via_display {
             fn into_pyobject(
                 self,
                 py: pyo3::marker::Python<'py>,
-            ) -> Result<Self::Output, Self::Error> {
+            ) -> Result<pyo3::Bound<'py, pyo3::types::PyString>, std::convert::Infallible> {
                 self.to_string().into_pyobject(py)
             }
         }
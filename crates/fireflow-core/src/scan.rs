@@ -0,0 +1,271 @@
+//! Apply a function to many files concurrently with bounded resource usage.
+//!
+//! This is meant for scanning large directory trees, possibly on network
+//! filesystems, where opening every file at once would exhaust file handles
+//! or overwhelm the storage backend. Results are streamed back in the same
+//! order as the input paths.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Limits for scanning many files concurrently
+#[derive(Clone)]
+pub struct ScanConfig {
+    /// Maximum number of worker threads.
+    pub max_threads: NonZeroUsize,
+
+    /// Maximum number of files which may be open at once.
+    ///
+    /// This assumes the scanning function opens at most one file at a time.
+    pub max_open_files: NonZeroUsize,
+
+    /// Maximum number of finished results to hold in memory.
+    ///
+    /// Since results are returned in order, one slow file will cause results
+    /// for subsequent files to accumulate. Workers will wait rather than
+    /// start new files once this many results are waiting to be consumed.
+    pub max_pending: NonZeroUsize,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        let n = thread::available_parallelism().map_or(NonZeroUsize::MIN, |x| x.min(EIGHT));
+        Self {
+            max_threads: n,
+            max_open_files: n,
+            max_pending: n.saturating_mul(EIGHT),
+        }
+    }
+}
+
+const EIGHT: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+
+/// Iterator over results from [`scan_files`] in the order of the input paths.
+///
+/// Dropping this will stop workers from starting any new files.
+pub struct ScanIter<T> {
+    rx: Receiver<(usize, T)>,
+    paths: Arc<Vec<PathBuf>>,
+    pending: BTreeMap<usize, T>,
+    next: usize,
+    state: Arc<ScanState>,
+}
+
+struct ScanState {
+    /// Index of the next path to be claimed by a worker
+    claimed: AtomicUsize,
+
+    /// Index of the next result to be yielded by the iterator
+    consumed: Mutex<usize>,
+    consumed_cv: Condvar,
+
+    /// Number of files currently open
+    open: Mutex<usize>,
+    open_cv: Condvar,
+
+    cancelled: AtomicBool,
+}
+
+/// Run a function on many files concurrently.
+///
+/// Return an iterator which yields each path with the result of `f` in the
+/// same order as `paths`, regardless of the order in which they finished.
+pub fn scan_files<F, T>(paths: Vec<PathBuf>, conf: &ScanConfig, f: F) -> ScanIter<T>
+where
+    F: Fn(&Path) -> T + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = channel();
+    let shared_paths = Arc::new(paths);
+    let state = Arc::new(ScanState {
+        claimed: AtomicUsize::new(0),
+        consumed: Mutex::new(0),
+        consumed_cv: Condvar::new(),
+        open: Mutex::new(0),
+        open_cv: Condvar::new(),
+        cancelled: AtomicBool::new(false),
+    });
+    let shared_f = Arc::new(f);
+    let nthreads = conf.max_threads.get().min(shared_paths.len());
+    let max_open = conf.max_open_files.get();
+    let max_pending = conf.max_pending.get();
+    for _ in 0..nthreads {
+        let worker_tx = tx.clone();
+        let worker_paths = shared_paths.clone();
+        let worker_state = state.clone();
+        let worker_f = shared_f.clone();
+        thread::spawn(move || {
+            loop {
+                let i = worker_state.claimed.fetch_add(1, Ordering::SeqCst);
+                if i >= worker_paths.len() {
+                    break;
+                }
+                // wait until the consumer has caught up
+                {
+                    let mut c = worker_state.consumed.lock().unwrap();
+                    while i >= *c + max_pending && !worker_state.cancelled.load(Ordering::SeqCst) {
+                        c = worker_state.consumed_cv.wait(c).unwrap();
+                    }
+                }
+                // wait for a free file handle
+                {
+                    let mut o = worker_state.open.lock().unwrap();
+                    while *o >= max_open && !worker_state.cancelled.load(Ordering::SeqCst) {
+                        o = worker_state.open_cv.wait(o).unwrap();
+                    }
+                    *o += 1;
+                }
+                if worker_state.cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+                let res = worker_f(&worker_paths[i]);
+                {
+                    let mut o = worker_state.open.lock().unwrap();
+                    *o -= 1;
+                    worker_state.open_cv.notify_one();
+                }
+                if worker_tx.send((i, res)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    ScanIter {
+        rx,
+        paths: shared_paths,
+        pending: BTreeMap::new(),
+        next: 0,
+        state,
+    }
+}
+
+impl<T> Iterator for ScanIter<T> {
+    type Item = (PathBuf, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.paths.len() {
+            return None;
+        }
+        let res = loop {
+            if let Some(x) = self.pending.remove(&self.next) {
+                break x;
+            }
+            // ASSUME this will only fail if a worker panicked, in which case
+            // the result for this path will never arrive
+            let (i, x) = self.rx.recv().ok()?;
+            self.pending.insert(i, x);
+        };
+        let p = self.paths[self.next].clone();
+        self.next += 1;
+        *self.state.consumed.lock().unwrap() = self.next;
+        self.state.consumed_cv.notify_all();
+        Some((p, res))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.paths.len() - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<T> Drop for ScanIter<T> {
+    fn drop(&mut self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.consumed_cv.notify_all();
+        self.state.open_cv.notify_all();
+    }
+}
+
+/// Return all FCS files from paths, searching directories recursively.
+///
+/// Paths which are not directories are always returned regardless of
+/// extension. Files found in directories must end in ".fcs" (case
+/// insensitive). Files within each directory are returned in sorted order.
+pub fn find_fcs_files<'a>(ps: impl Iterator<Item = &'a PathBuf>) -> Vec<PathBuf> {
+    fn go(p: PathBuf, acc: &mut Vec<PathBuf>) {
+        if p.is_dir() {
+            if let Ok(ds) = fs::read_dir(&p) {
+                let mut xs: Vec<_> = ds.flatten().map(|d| d.path()).collect();
+                xs.sort();
+                for x in xs {
+                    go(x, acc);
+                }
+            }
+        } else if p.extension().is_some_and(|e| e.eq_ignore_ascii_case("fcs")) {
+            acc.push(p);
+        }
+    }
+    let mut acc = vec![];
+    for p in ps {
+        if p.is_dir() {
+            go(p.clone(), &mut acc);
+        } else {
+            acc.push(p.clone());
+        }
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_scan_ordered_and_limited() {
+        let paths: Vec<_> = (0..50).map(|i| PathBuf::from(i.to_string())).collect();
+        let conf = ScanConfig {
+            max_threads: NonZeroUsize::new(6).unwrap(),
+            max_open_files: NonZeroUsize::new(3).unwrap(),
+            max_pending: NonZeroUsize::new(4).unwrap(),
+        };
+        let open = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (o, k) = (open.clone(), peak.clone());
+        let xs: Vec<_> = scan_files(paths.clone(), &conf, move |p| {
+            let n = o.fetch_add(1, Ordering::SeqCst) + 1;
+            k.fetch_max(n, Ordering::SeqCst);
+            let i: u64 = p.to_str().unwrap().parse().unwrap();
+            // make later files finish first sometimes
+            thread::sleep(Duration::from_millis((7 * i) % 5));
+            o.fetch_sub(1, Ordering::SeqCst);
+            i
+        })
+        .collect();
+        assert_eq!(xs.len(), 50);
+        for (i, (p, x)) in xs.iter().enumerate() {
+            assert_eq!(&paths[i], p);
+            assert_eq!(i as u64, *x);
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn test_scan_drop_early() {
+        let paths: Vec<_> = (0..100).map(|i| PathBuf::from(i.to_string())).collect();
+        let conf = ScanConfig::default();
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = count.clone();
+        let mut it = scan_files(paths, &conf, move |_| {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(it.next().is_some());
+        drop(it);
+        thread::sleep(Duration::from_millis(50));
+        // workers must not have processed much beyond the pending window
+        let limit = 1 + conf.max_pending.get() + conf.max_threads.get();
+        assert!(count.load(Ordering::SeqCst) <= limit);
+    }
+
+    #[test]
+    fn test_scan_empty() {
+        let mut it = scan_files(vec![], &ScanConfig::default(), |_| ());
+        assert!(it.next().is_none());
+    }
+}
@@ -261,7 +261,7 @@ where
             },
             |tnt| {
                 Ok(tnt.and_tentatively(|other| {
-                    default.unless(other).map_or_else(
+                    default.unless(other, allow_mismatch).map_or_else(
                         |(s, w)| Tentative::new_either(s, vec![w], !allow_mismatch),
                         Tentative::new1,
                     )
@@ -377,7 +377,7 @@ where
     {
         res.and_tentatively(|other| {
             other.map_or(Tentative::new1(default.into_any()), |o| {
-                default.unless(o).map_or_else(
+                default.unless(o, allow_mismatch).map_or_else(
                     |(s, w)| Tentative::new_either(s, vec![w], !allow_mismatch),
                     Tentative::new1,
                 )
@@ -632,31 +632,6 @@ impl<I, T> SpecificSegment<I, SegmentFromHeader, T> {
 }
 
 impl<I: Copy> HeaderSegment<I> {
-    pub(crate) fn h_read_offsets<R: Read>(
-        h: &mut BufReader<R>,
-        allow_blank: bool,
-        allow_negative: bool,
-        squish_offsets: bool,
-        conf: &NewSegmentConfig<UintSpacePad8, I, SegmentFromHeader>,
-    ) -> MultiResult<Self, ImpureError<HeaderSegmentError>>
-    where
-        I: HasRegion,
-    {
-        let mut buf0 = [0_u8; 8];
-        let mut buf1 = [0_u8; 8];
-        h.read_exact(&mut buf0).into_mult()?;
-        h.read_exact(&mut buf1).into_mult()?;
-        Self::parse(
-            &buf0,
-            &buf1,
-            allow_blank,
-            allow_negative,
-            squish_offsets,
-            conf,
-        )
-        .mult_map_errors(ImpureError::Pure)
-    }
-
     pub(crate) fn parse(
         bs0: &[u8; 8],
         bs1: &[u8; 8],
@@ -692,6 +667,7 @@ impl<I: Copy> HeaderSegment<I> {
     pub(crate) fn unless(
         self,
         other: TEXTSegment<I>,
+        allow_mismatch: bool,
     ) -> Result<AnySegment<I>, (AnySegment<I>, SegmentMismatchWarning<I>)> {
         if other.inner.as_u64() != self.inner.as_u64() && !self.inner.is_empty() {
             Err((
@@ -699,6 +675,7 @@ impl<I: Copy> HeaderSegment<I> {
                 SegmentMismatchWarning {
                     header: self,
                     text: other,
+                    used_header: allow_mismatch,
                 },
             ))
         } else {
@@ -1090,6 +1067,19 @@ where
 pub struct SegmentMismatchWarning<S> {
     header: HeaderSegment<S>,
     text: TEXTSegment<S>,
+    used_header: bool,
+}
+
+impl<S> SegmentMismatchWarning<S> {
+    /// Offsets for this segment as they appear in HEADER
+    pub fn header(&self) -> Segment<u64> {
+        self.header.inner.as_u64()
+    }
+
+    /// Offsets for this segment as they appear in TEXT
+    pub fn text(&self) -> Segment<u64> {
+        self.text.inner.as_u64()
+    }
 }
 
 impl<I> fmt::Display for SegmentMismatchWarning<I>
@@ -1097,13 +1087,43 @@ where
     I: HasRegion,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let h = self.header();
+        let t = self.text();
+        let show = |x: Segment<u64>| {
+            if x.is_empty() {
+                "empty".into()
+            } else {
+                format!("{} ({} bytes)", x.fmt_pair(), x.len())
+            }
+        };
         write!(
             f,
-            "segments differ in HEADER ({}) and TEXT ({}) for {}, using TEXT",
-            self.header.inner.as_u64().fmt_pair(),
-            self.text.inner.as_u64().fmt_pair(),
+            "{} offsets differ between HEADER and TEXT; HEADER: {}, TEXT: {}",
             I::REGION,
-        )
+            show(h),
+            show(t),
+        )?;
+        if let (Some((hb, he)), Some((tb, te))) = (h.try_coords(), t.try_coords()) {
+            let diff = |x: u64, y: u64| i128::from(y) - i128::from(x);
+            write!(
+                f,
+                "; TEXT differs by {:+} bytes at begin and {:+} bytes at end",
+                diff(hb, tb),
+                diff(he, te),
+            )?;
+        }
+        if self.used_header {
+            write!(
+                f,
+                "; using HEADER since its offsets are nonzero and \
+                 take precedence over TEXT"
+            )
+        } else {
+            write!(
+                f,
+                "; refusing to choose one since mismatched offsets are not allowed"
+            )
+        }
     }
 }
 
@@ -307,6 +307,12 @@ impl From<NoByteOrd<false>> for ByteOrd3_1 {
     }
 }
 
+impl<const LEN: usize> SizedByteOrd<LEN> {
+    pub fn is_mixed(&self) -> bool {
+        matches!(self, Self::Order(_))
+    }
+}
+
 impl SizedByteOrd<2> {
     pub fn endian(&self) -> Endian {
         let [x, y] = (*self).into();
@@ -328,6 +334,20 @@ impl ByteOrd2_0 {
         }
     }
 
+    /// Return true if byte order is neither big nor little endian.
+    pub fn is_mixed(&self) -> bool {
+        match self {
+            Self::O1(x) => x.is_mixed(),
+            Self::O2(x) => x.is_mixed(),
+            Self::O3(x) => x.is_mixed(),
+            Self::O4(x) => x.is_mixed(),
+            Self::O5(x) => x.is_mixed(),
+            Self::O6(x) => x.is_mixed(),
+            Self::O7(x) => x.is_mixed(),
+            Self::O8(x) => x.is_mixed(),
+        }
+    }
+
     pub fn as_vec(&self) -> Vec<NonZeroU8> {
         match self {
             Self::O1(x) => <[NonZeroU8; 1]>::from(*x).to_vec(),
@@ -690,6 +710,15 @@ mod tests {
         assert_from_to_str::<ByteOrd2_0>("1,2,3,4,5,6,7,8");
     }
 
+    #[test]
+    fn test_byteord_is_mixed() {
+        let f = |s: &str| s.parse::<ByteOrd2_0>().unwrap().is_mixed();
+        assert!(!f("1,2,3,4"));
+        assert!(!f("4,3,2,1"));
+        assert!(f("2,1,4,3"));
+        assert!(f("3,4,1,2"));
+    }
+
     #[test]
     fn test_str_to_byteord_tolong() {
         assert!("1,2,3,4,5,6,7,8,9".parse::<ByteOrd2_0>().is_err());
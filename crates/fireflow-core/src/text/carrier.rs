@@ -0,0 +1,273 @@
+//! Typed access to the carrier keywords (3.2+).
+//!
+//! $CARRIERID, $CARRIERTYPE, and $LOCATIONID describe where a sample came
+//! from, ie a plate or tube rack and the well or position within it. These
+//! are stored as free-form strings, so nothing stops a file from having a
+//! location without a carrier or values which are only whitespace. This
+//! provides a validated view of these keywords along with a check that no two
+//! datasets from the same experiment claim the same carrier location.
+
+use derive_more::Display;
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Carrier and location from which a sample was taken.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "python",
+    derive(IntoPyObject, FromPyObject),
+    pyo3(from_item_all)
+)]
+pub struct Carrier {
+    /// Value of $CARRIERID
+    pub carrierid: String,
+
+    /// Value of $CARRIERTYPE
+    pub carriertype: Option<String>,
+
+    /// Value of $LOCATIONID
+    pub locationid: Option<String>,
+}
+
+/// Error when carrier keywords are not consistent.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum CarrierError {
+    /// Keyword has a value which is only whitespace
+    Blank(&'static str),
+
+    /// $CARRIERTYPE or $LOCATIONID is present without $CARRIERID
+    MissingId,
+}
+
+/// Several datasets which have the same carrier and location.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct DuplicateCarrier {
+    /// Shared value of $CARRIERID
+    pub carrierid: String,
+
+    /// Shared value of $LOCATIONID
+    pub locationid: Option<String>,
+
+    /// Positions of the datasets which share this carrier location
+    pub indices: Vec<usize>,
+}
+
+/// A problem with the carriers of several datasets.
+#[derive(Clone, PartialEq, Display)]
+#[cfg_attr(test, derive(Debug))]
+pub enum CarrierIssue {
+    #[display("dataset {_0}: {_1}")]
+    Invalid(usize, CarrierError),
+    Duplicate(DuplicateCarrier),
+}
+
+impl Carrier {
+    /// Make a new carrier.
+    ///
+    /// Return error if any value is only whitespace.
+    pub fn try_new(
+        carrierid: String,
+        carriertype: Option<String>,
+        locationid: Option<String>,
+    ) -> Result<Self, CarrierError> {
+        let ret = Self {
+            carrierid,
+            carriertype,
+            locationid,
+        };
+        ret.validate().map(|()| ret)
+    }
+
+    /// Return error if any value is only whitespace.
+    pub fn validate(&self) -> Result<(), CarrierError> {
+        check_blank("$CARRIERID", Some(&self.carrierid))?;
+        check_blank("$CARRIERTYPE", self.carriertype.as_ref())?;
+        check_blank("$LOCATIONID", self.locationid.as_ref())
+    }
+
+    /// Make a carrier from the values of each keyword.
+    ///
+    /// Return None if all are missing, and error if $CARRIERID is missing but
+    /// any other is present or if any value is only whitespace.
+    pub(crate) fn from_keywords(
+        carrierid: Option<String>,
+        carriertype: Option<String>,
+        locationid: Option<String>,
+    ) -> Result<Option<Self>, CarrierError> {
+        match carrierid {
+            Some(i) => Self::try_new(i, carriertype, locationid).map(Some),
+            None if carriertype.is_none() && locationid.is_none() => Ok(None),
+            None => Err(CarrierError::MissingId),
+        }
+    }
+
+    /// Find datasets which share the same carrier and location.
+    ///
+    /// `carriers` should have one entry for each dataset in an experiment
+    /// (or whatever the group is). Those without a carrier are ignored.
+    /// Return each group of duplicates in order of first appearance.
+    pub fn find_duplicates(carriers: &[Option<Self>]) -> Vec<DuplicateCarrier> {
+        let mut groups: Vec<DuplicateCarrier> = vec![];
+        let mut seen: HashMap<(&str, Option<&str>), usize> = HashMap::new();
+        for (i, c) in carriers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| c.as_ref().map(|x| (i, x)))
+        {
+            let k = (c.carrierid.as_str(), c.locationid.as_deref());
+            if let Some(&g) = seen.get(&k) {
+                groups[g].indices.push(i);
+            } else {
+                seen.insert(k, groups.len());
+                groups.push(DuplicateCarrier {
+                    carrierid: c.carrierid.clone(),
+                    locationid: c.locationid.clone(),
+                    indices: vec![i],
+                });
+            }
+        }
+        groups.retain(|g| g.indices.len() > 1);
+        groups
+    }
+
+    /// Check that each carrier is valid and that none are duplicated.
+    ///
+    /// Invalid carriers are reported and then ignored when finding
+    /// duplicates.
+    pub fn check_all(carriers: Vec<Result<Option<Self>, CarrierError>>) -> Vec<CarrierIssue> {
+        let mut issues = vec![];
+        let valid: Vec<_> = carriers
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| {
+                c.unwrap_or_else(|e| {
+                    issues.push(CarrierIssue::Invalid(i, e));
+                    None
+                })
+            })
+            .collect();
+        issues.extend(
+            Self::find_duplicates(&valid)
+                .into_iter()
+                .map(CarrierIssue::Duplicate),
+        );
+        issues
+    }
+}
+
+fn check_blank(key: &'static str, x: Option<&String>) -> Result<(), CarrierError> {
+    if x.is_some_and(|s| s.trim().is_empty()) {
+        Err(CarrierError::Blank(key))
+    } else {
+        Ok(())
+    }
+}
+
+impl fmt::Display for CarrierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Blank(k) => write!(f, "{k} must not be blank"),
+            Self::MissingId => f.write_str("$CARRIERTYPE and $LOCATIONID require $CARRIERID"),
+        }
+    }
+}
+
+impl fmt::Display for DuplicateCarrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let is: Vec<_> = self.indices.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "datasets {} share $CARRIERID '{}'",
+            is.join(", "),
+            self.carrierid
+        )?;
+        if let Some(l) = &self.locationid {
+            write!(f, " and $LOCATIONID '{l}'")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::CarrierError;
+    use crate::python::macros::impl_pyreflow_err;
+
+    impl_pyreflow_err!(CarrierError);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carrier(id: &str, loc: Option<&str>) -> Option<Carrier> {
+        Some(Carrier {
+            carrierid: id.into(),
+            carriertype: None,
+            locationid: loc.map(String::from),
+        })
+    }
+
+    #[test]
+    fn test_from_keywords() {
+        assert_eq!(Carrier::from_keywords(None, None, None), Ok(None));
+        assert_eq!(
+            Carrier::from_keywords(None, None, Some("A1".into())),
+            Err(CarrierError::MissingId)
+        );
+        assert_eq!(
+            Carrier::from_keywords(Some("P1".into()), Some(" ".into()), None),
+            Err(CarrierError::Blank("$CARRIERTYPE"))
+        );
+        assert_eq!(
+            Carrier::from_keywords(Some("P1".into()), None, Some("A1".into())),
+            Ok(carrier("P1", Some("A1")))
+        );
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let cs = [
+            carrier("P1", Some("A1")),
+            carrier("P1", Some("A2")),
+            None,
+            carrier("P2", Some("A1")),
+            carrier("P1", Some("A1")),
+            carrier("P3", None),
+            carrier("P3", None),
+        ];
+        let ds = Carrier::find_duplicates(&cs);
+        assert_eq!(2, ds.len());
+        assert_eq!(vec![0, 4], ds[0].indices);
+        assert_eq!(vec![5, 6], ds[1].indices);
+        assert_eq!(
+            "datasets 0, 4 share $CARRIERID 'P1' and $LOCATIONID 'A1'",
+            ds[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_check_all() {
+        let cs = vec![
+            Ok(carrier("P1", Some("A1"))),
+            Err(CarrierError::MissingId),
+            Ok(carrier("P1", Some("A1"))),
+        ];
+        let is = Carrier::check_all(cs);
+        assert_eq!(2, is.len());
+        assert_eq!(CarrierIssue::Invalid(1, CarrierError::MissingId), is[0]);
+        assert!(matches!(&is[1], CarrierIssue::Duplicate(d) if d.indices == [0, 2]));
+    }
+}
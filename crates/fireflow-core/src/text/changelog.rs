@@ -0,0 +1,184 @@
+use crate::header::Version;
+use crate::validated::keys::*;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Key for the JSON summary of the last version conversion
+pub const CONVERSION_LOG: &str = "FIREFLOW_CONVERSION";
+
+/// Summary of keywords changed when converting between FCS versions.
+///
+/// This is stored as a compact JSON object in a single nonstandard keyword
+/// (see [`CONVERSION_LOG`]) so that readers of the converted file can see how
+/// it was produced. Only standard keywords are tracked; changes to the data
+/// layout will appear as changes to $DATATYPE, $BYTEORD, $PnB, etc.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(
+    feature = "python",
+    derive(IntoPyObject, FromPyObject),
+    pyo3(from_item_all)
+)]
+pub struct ConversionLog {
+    /// Version before conversion
+    #[serde(serialize_with = "serialize_version")]
+    #[serde(deserialize_with = "deserialize_version")]
+    pub from: Version,
+
+    /// Version after conversion
+    #[serde(serialize_with = "serialize_version")]
+    #[serde(deserialize_with = "deserialize_version")]
+    pub to: Version,
+
+    /// Standard keywords present before but not after conversion
+    #[serde(default)]
+    pub removed: Vec<String>,
+
+    /// Standard keywords present after but not before conversion
+    #[serde(default)]
+    pub added: Vec<String>,
+
+    /// Standard keywords present in both but with different values
+    #[serde(default)]
+    pub changed: Vec<String>,
+}
+
+impl ConversionLog {
+    /// Make new log by comparing standard keywords before and after conversion.
+    pub(crate) fn new(
+        from: Version,
+        to: Version,
+        before: &HashMap<String, String>,
+        after: &HashMap<String, String>,
+    ) -> Self {
+        let mut removed: Vec<_> = before
+            .keys()
+            .filter(|k| !after.contains_key(*k))
+            .cloned()
+            .collect();
+        let mut added: Vec<_> = after
+            .keys()
+            .filter(|k| !before.contains_key(*k))
+            .cloned()
+            .collect();
+        let mut changed: Vec<_> = before
+            .iter()
+            .filter(|(k, v)| after.get(*k).is_some_and(|w| w != *v))
+            .map(|(k, _)| k.clone())
+            .collect();
+        removed.sort();
+        added.sort();
+        changed.sort();
+        Self {
+            from,
+            to,
+            removed,
+            added,
+            changed,
+        }
+    }
+
+    /// Encode as compact JSON.
+    pub fn to_json(&self) -> String {
+        // ASSUME this won't fail since everything is a string
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Decode from JSON as written by [`Self::to_json`].
+    ///
+    /// Unknown fields will be ignored.
+    pub fn from_json(s: &str) -> Result<Self, ParseConversionLogError> {
+        serde_json::from_str(s).map_err(|e| ParseConversionLogError(e.to_string()))
+    }
+
+    /// Read log from nonstandard keywords.
+    ///
+    /// Return None if key is not present.
+    pub(crate) fn from_keywords(
+        kws: &NonStdKeywords,
+    ) -> Option<Result<Self, ParseConversionLogError>> {
+        CONVERSION_LOG
+            .parse::<NonStdKey>()
+            .ok()
+            .and_then(|k| kws.get(&k))
+            .map(|v| Self::from_json(v))
+    }
+
+    /// Replace log in nonstandard keywords, or remove it if None.
+    pub(crate) fn replace_keywords(this: Option<&Self>, kws: &mut NonStdKeywords) {
+        // ASSUME this will never fail since the key is a valid constant
+        if let Ok(k) = CONVERSION_LOG.parse::<NonStdKey>() {
+            kws.remove(&k);
+            if let Some(x) = this {
+                kws.insert(k, x.to_json());
+            }
+        }
+    }
+}
+
+fn serialize_version<S: Serializer>(v: &Version, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(v)
+}
+
+fn deserialize_version<'de, D: Deserializer<'de>>(d: D) -> Result<Version, D::Error> {
+    let s = String::deserialize(d)?;
+    s.parse()
+        .map_err(|_| de::Error::custom(format!("invalid version '{s}'")))
+}
+
+pub struct ParseConversionLogError(String);
+
+impl fmt::Display for ParseConversionLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "could not parse {CONVERSION_LOG}: {}", self.0)
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::ParseConversionLogError;
+    use crate::python::macros::impl_pyreflow_err;
+
+    impl_pyreflow_err!(ParseConversionLogError);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_json() {
+        let before = HashMap::from([
+            ("$MODE".into(), "L".into()),
+            ("$BYTEORD".into(), "1,2,3,4".into()),
+            ("$PAR".into(), "1".into()),
+        ]);
+        let after = HashMap::from([
+            ("$BYTEORD".into(), "1,2,3,4".into()),
+            ("$PAR".into(), "2".into()),
+            ("$CARRIERID".into(), "a \"quoted\"\\ value".into()),
+        ]);
+        let log = ConversionLog::new(Version::FCS3_0, Version::FCS3_2, &before, &after);
+        assert_eq!(log.removed, ["$MODE"]);
+        assert_eq!(log.added, ["$CARRIERID"]);
+        assert_eq!(log.changed, ["$PAR"]);
+        let s = log.to_json();
+        assert_eq!(ConversionLog::from_json(&s).ok(), Some(log));
+    }
+
+    #[test]
+    fn test_from_json_bad() {
+        assert!(ConversionLog::from_json("").is_err());
+        assert!(ConversionLog::from_json("{}").is_err());
+        assert!(ConversionLog::from_json("{\"from\":\"FCS3.0\"}").is_err());
+        assert!(ConversionLog::from_json("{\"from\":\"FCS3.0\",\"to\":[]}").is_err());
+        assert!(
+            ConversionLog::from_json("{\"from\":\"FCS3.0\",\"to\":\"FCS3.1\",\"x\":\"y\"}").is_ok()
+        );
+    }
+}
@@ -48,16 +48,25 @@ impl Compensation2_0 {
         // row = target measurement
         // These are "flipped" in 2.0, where "column" goes TO the "row"
         let n = par.0;
-        let (xs, warnings): (Vec<_>, Vec<_>) = (0..n)
-            .cartesian_product(0..n)
-            .map(|(r, c)| {
-                let k = Dfc::std(c.into(), r.into());
-                match lookup_dfc(kws, k) {
-                    Ok(x) => (x, None),
-                    Err(w) => (None, Some(LookupKeysWarning::Parse(w.inner_into()))),
-                }
+        // Find keys which are present rather than looking up all n^2 possible
+        // keys, which is slow for large $PAR
+        let mut found: Vec<_> = kws
+            .keys()
+            .filter_map(|k| {
+                let (c, r) = Dfc::indices(k)?;
+                let (ci, ri) = (usize::from(c), usize::from(r));
+                (ci < n && ri < n).then(|| (ri * n + ci, k.clone()))
             })
-            .unzip();
+            .collect();
+        found.sort_by_key(|(i, _)| *i);
+        let mut xs = vec![None; n * n];
+        let mut warnings = vec![];
+        for (i, k) in found {
+            match lookup_dfc(kws, k) {
+                Ok(x) => xs[i] = x,
+                Err(w) => warnings.push(LookupKeysWarning::Parse(w.inner_into())),
+            }
+        }
         let mut tnt = if xs.iter().all(|x| x.is_none()) || xs.is_empty() {
             Tentative::default()
         } else {
@@ -68,7 +77,7 @@ impl Compensation2_0 {
                 .map_err(|e| LookupKeysWarning::Relation(e.into()))
                 .map_or(Tentative::default(), Tentative::new1)
         };
-        tnt.extend_warnings(warnings.into_iter().flatten());
+        tnt.extend_warnings(warnings.into_iter());
         tnt.map(MaybeValue)
     }
 
@@ -124,6 +133,22 @@ impl Compensation {
         new[(i, i)] = 1.0;
         self.matrix = new;
     }
+
+    /// Remove the row/column for a measurement which is being removed.
+    ///
+    /// Return true if the matrix would be too small to be valid, in which case
+    /// it should be removed entirely and this will not be changed.
+    ///
+    /// Index is assumed to be valid. Will panic otherwise.
+    pub(crate) fn remove_by_index_unchecked(&mut self, index: MeasIndex) -> bool {
+        if self.matrix.ncols() < 3 {
+            true
+        } else {
+            let i = index.into();
+            self.matrix = self.matrix.clone().remove_row(i).remove_column(i);
+            false
+        }
+    }
 }
 
 impl FromStr for Compensation3_0 {
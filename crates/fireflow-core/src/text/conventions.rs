@@ -0,0 +1,333 @@
+//! Conventions for environmental conditions stored in nonstandard keywords.
+//!
+//! The standard has no keywords for things like sheath pressure or lab
+//! temperature, but many instruments record them anyways using their own
+//! keys. This provides typed access to these on a best-effort basis without
+//! changing the keywords themselves.
+//!
+//! Since no vendor documents the keys it uses for these, none are built in.
+//! Conventions must be given by implementing [`EnvironmentConvention`] or by
+//! making a [`KeyConvention`].
+//!
+//! Likewise, some instruments record the start of acquisition as an epoch
+//! timestamp (often with sub-second precision) rather than or in addition to
+//! the standard date/time keywords. These may be found with
+//! [`TimestampConvention`] or [`EpochConvention`].
+
+use crate::validated::keys::NonStdKeywords;
+
+use chrono::{DateTime, FixedOffset};
+
+use super::units::{parse_temperature, parse_with_units, PRESSURE_UNITS};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Environmental conditions recorded during acquisition.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct Environment {
+    /// Sheath pressure in psi
+    pub sheath_pressure: Option<f32>,
+
+    /// Ambient (room) temperature in degrees Celsius
+    pub ambient_temperature: Option<f32>,
+
+    /// Temperature inside the instrument (optics, lasers, etc) in degrees
+    /// Celsius
+    pub instrument_temperature: Option<f32>,
+
+    /// Relative humidity in percent
+    pub humidity: Option<f32>,
+}
+
+/// A way to find environmental conditions in nonstandard keywords.
+pub trait EnvironmentConvention: Send + Sync {
+    /// Name of this convention (usually a vendor family)
+    fn name(&self) -> &str;
+
+    /// Return whichever conditions can be found in the given keywords.
+    fn recognize(&self, kws: &NonStdKeywords) -> Environment;
+}
+
+/// A convention which looks up each condition from a list of keys.
+///
+/// Keys are compared case-insensitively and the first one which is present
+/// with a parsable value will be used. Values may have a unit suffix (ie
+/// "70 psi", "21.5 °C", or "45%").
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "python", derive(FromPyObject), pyo3(from_item_all))]
+pub struct KeyConvention {
+    pub name: String,
+    pub sheath_pressure: Vec<String>,
+    pub ambient_temperature: Vec<String>,
+    pub instrument_temperature: Vec<String>,
+    pub humidity: Vec<String>,
+}
+
+impl EnvironmentConvention for KeyConvention {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn recognize(&self, kws: &NonStdKeywords) -> Environment {
+        let find = |keys: &[String], parse: &dyn Fn(&str) -> Option<f32>| {
+            keys.iter().find_map(|k| {
+                kws.iter()
+                    .find(|(kk, _)| AsRef::<str>::as_ref(kk).eq_ignore_ascii_case(k))
+                    .and_then(|(_, v)| parse(v))
+            })
+        };
+        let pressure = |s: &str| parse_with_units(s, &PRESSURE_UNITS).ok();
+        let temperature = |s: &str| parse_temperature(s).ok();
+        let percent = |s: &str| s.trim().trim_end_matches('%').trim_end().parse().ok();
+        Environment {
+            sheath_pressure: find(&self.sheath_pressure, &pressure),
+            ambient_temperature: find(&self.ambient_temperature, &temperature),
+            instrument_temperature: find(&self.instrument_temperature, &temperature),
+            humidity: find(&self.humidity, &percent),
+        }
+    }
+}
+
+impl Environment {
+    /// Find conditions in keywords using the given conventions.
+    ///
+    /// Each condition will be taken from the first convention which finds it.
+    pub fn from_keywords(kws: &NonStdKeywords, conventions: &[&dyn EnvironmentConvention]) -> Self {
+        conventions
+            .iter()
+            .fold(Self::default(), |acc, c| acc.or(c.recognize(kws)))
+    }
+
+    /// Return true if no conditions were found.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn or(self, other: Self) -> Self {
+        Self {
+            sheath_pressure: self.sheath_pressure.or(other.sheath_pressure),
+            ambient_temperature: self.ambient_temperature.or(other.ambient_temperature),
+            instrument_temperature: self.instrument_temperature.or(other.instrument_temperature),
+            humidity: self.humidity.or(other.humidity),
+        }
+    }
+}
+
+/// Start of acquisition found in a vendor-specific epoch timestamp.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+pub struct VendorTimestamp {
+    /// Name of the convention which found this timestamp
+    pub convention: String,
+
+    /// Key from which this timestamp was read
+    pub key: String,
+
+    /// Start of acquisition in UTC
+    pub datetime: DateTime<FixedOffset>,
+}
+
+/// A way to find the start of acquisition in nonstandard keywords.
+pub trait TimestampConvention: Send + Sync {
+    /// Name of this convention (usually a vendor family)
+    fn name(&self) -> &str;
+
+    /// Return the start of acquisition if it can be found in the keywords.
+    fn recognize(&self, kws: &NonStdKeywords) -> Option<VendorTimestamp>;
+}
+
+/// Unit of an integer epoch timestamp.
+#[derive(Clone, Copy, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum EpochUnit {
+    Seconds,
+    Millis,
+    Micros,
+    /// Guess the unit from the magnitude of the value.
+    ///
+    /// Vendors are not always consistent with the unit implied by the key
+    /// (ie a key ending in "MILLIS" holding microseconds), so this is usually
+    /// the safer choice. Anything with at least 15 digits is assumed to be
+    /// microseconds, at least 12 digits milliseconds, and seconds otherwise,
+    /// which is unambiguous for any date between 1973 and 5138.
+    #[default]
+    Auto,
+}
+
+impl EpochUnit {
+    fn to_datetime(self, x: i64) -> Option<DateTime<FixedOffset>> {
+        let n = x.unsigned_abs();
+        let ret = match self {
+            Self::Micros => DateTime::from_timestamp_micros(x),
+            Self::Auto if n >= 100_000_000_000_000 => DateTime::from_timestamp_micros(x),
+            Self::Millis => DateTime::from_timestamp_millis(x),
+            Self::Auto if n >= 100_000_000_000 => DateTime::from_timestamp_millis(x),
+            Self::Seconds | Self::Auto => DateTime::from_timestamp(x, 0),
+        };
+        ret.map(|t| t.fixed_offset())
+    }
+}
+
+/// A convention which reads an epoch timestamp from a list of keys.
+///
+/// Keys are compared case-insensitively and the first one which is present
+/// with an integer value will be used.
+#[derive(Clone, Default)]
+pub struct EpochConvention {
+    pub name: String,
+    pub keys: Vec<(String, EpochUnit)>,
+}
+
+impl EpochConvention {
+    fn new_static(name: &str, keys: &[(&str, EpochUnit)]) -> Self {
+        Self {
+            name: name.into(),
+            keys: keys.iter().map(|(k, u)| ((*k).to_string(), *u)).collect(),
+        }
+    }
+}
+
+impl TimestampConvention for EpochConvention {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn recognize(&self, kws: &NonStdKeywords) -> Option<VendorTimestamp> {
+        self.keys.iter().find_map(|(k, unit)| {
+            kws.iter()
+                .find(|(kk, _)| AsRef::<str>::as_ref(kk).eq_ignore_ascii_case(k))
+                .and_then(|(kk, v)| {
+                    let t = v.trim().parse().ok().and_then(|x| unit.to_datetime(x))?;
+                    Some(VendorTimestamp {
+                        convention: self.name.clone(),
+                        key: kk.to_string(),
+                        datetime: t,
+                    })
+                })
+        })
+    }
+}
+
+/// Return the built-in timestamp conventions in the order they are checked.
+pub fn builtin_timestamp_conventions() -> Vec<EpochConvention> {
+    vec![EpochConvention::new_static(
+        "epoch",
+        &[
+            ("#ACQUISITIONTIMEMILLIS", EpochUnit::Auto),
+            ("ACQUISITIONTIMEMILLIS", EpochUnit::Auto),
+            ("#ACQUISITIONTIMEMICROS", EpochUnit::Auto),
+            ("ACQUISITIONTIMEMICROS", EpochUnit::Auto),
+        ],
+    )]
+}
+
+impl VendorTimestamp {
+    /// Find the start of acquisition in keywords using the given conventions.
+    ///
+    /// The timestamp will be taken from the first convention which finds it.
+    pub fn from_keywords(
+        kws: &NonStdKeywords,
+        conventions: &[&dyn TimestampConvention],
+    ) -> Option<Self> {
+        conventions.iter().find_map(|c| c.recognize(kws))
+    }
+
+    /// Find the start of acquisition using only the built-in conventions.
+    pub fn from_keywords_builtin(kws: &NonStdKeywords) -> Option<Self> {
+        let cs = builtin_timestamp_conventions();
+        let xs: Vec<_> = cs.iter().map(|c| c as &dyn TimestampConvention).collect();
+        Self::from_keywords(kws, &xs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kws(xs: &[(&str, &str)]) -> NonStdKeywords {
+        xs.iter()
+            .map(|(k, v)| (k.parse().ok().unwrap(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_key_conventions() {
+        let f = |xs: &[&str]| xs.iter().map(|x| (*x).to_string()).collect();
+        let c0 = KeyConvention {
+            name: "first".into(),
+            sheath_pressure: f(&["SHEATH PRESSURE"]),
+            ambient_temperature: f(&["AMBIENTTEMP"]),
+            ..KeyConvention::default()
+        };
+        let c1 = KeyConvention {
+            name: "second".into(),
+            ambient_temperature: f(&["ROOM_TEMPERATURE"]),
+            instrument_temperature: f(&["OpticsTemperature"]),
+            humidity: f(&["HUMIDITY"]),
+            ..KeyConvention::default()
+        };
+        let k = kws(&[
+            ("sheath pressure", "70 psi"),
+            ("OpticsTemperature", "30.5C"),
+            ("AMBIENTTEMP", "bogus"),
+            ("ROOM_TEMPERATURE", "68 F"),
+            ("HUMIDITY", "40 %"),
+        ]);
+        let e = Environment::from_keywords(&k, &[&c0, &c1]);
+        assert_eq!(
+            e,
+            Environment {
+                sheath_pressure: Some(70.0),
+                ambient_temperature: Some(20.0),
+                instrument_temperature: Some(30.5),
+                humidity: Some(40.0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom() {
+        let c = KeyConvention {
+            name: "ours".into(),
+            sheath_pressure: vec!["P_SHEATH".into()],
+            ..KeyConvention::default()
+        };
+        let k = kws(&[("P_SHEATH", "5 bar"), ("SHEATH PRESSURE", "70")]);
+        let e = Environment::from_keywords(&k, &[&c]);
+        assert!(e.sheath_pressure.is_some_and(|p| (p - 72.519).abs() < 1e-3));
+        assert_eq!(e.ambient_temperature, None);
+        assert!(Environment::from_keywords(&kws(&[]), &[&c]).is_empty());
+    }
+
+    #[test]
+    fn test_epoch_units() {
+        let t = |s: &str| {
+            VendorTimestamp::from_keywords_builtin(&kws(&[("#AcquisitionTimeMillis", s)]))
+                .map(|x| x.datetime.to_rfc3339())
+        };
+        let expected = Some("2023-09-01T10:00:00.123+00:00".into());
+        assert_eq!(t("1693562400123"), expected);
+        // microseconds in a key which claims to be milliseconds
+        assert_eq!(t("1693562400123000"), expected);
+        assert_eq!(t("1693562400"), Some("2023-09-01T10:00:00+00:00".into()));
+        assert_eq!(t("yesterday"), None);
+        let c = EpochConvention {
+            name: "ours".into(),
+            keys: vec![("STARTED".into(), EpochUnit::Millis)],
+        };
+        let ts = VendorTimestamp::from_keywords(&kws(&[("STARTED", " 1000 ")]), &[&c]);
+        assert_eq!(
+            ts.map(|x| (x.key, x.datetime.timestamp())),
+            Some(("STARTED".into(), 1))
+        );
+    }
+}
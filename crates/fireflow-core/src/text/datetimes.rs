@@ -5,7 +5,7 @@ use crate::validated::keys::*;
 use super::optional::*;
 use super::parser::*;
 
-use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeDelta, TimeZone};
 use derive_more::{AsRef, Display, From, FromStr, Into};
 use std::fmt;
 use std::mem;
@@ -83,6 +83,22 @@ impl Datetimes {
         }
     }
 
+    /// Return datetimes for one slice of the acquisition.
+    ///
+    /// `begin` and `end` are the number of seconds after $BEGINDATETIME at
+    /// which the slice starts and ends. Return None if $BEGINDATETIME is
+    /// missing.
+    pub(crate) fn slice(&self, begin: f64, end: f64) -> Option<Self> {
+        let t0 = (self.begin?.0).0;
+        let shift = |secs: f64| {
+            let d = TimeDelta::milliseconds((secs * 1000.0).round() as i64);
+            t0.checked_add_signed(d).map(FCSDateTime)
+        };
+        let b = shift(begin).map(BeginDateTime);
+        let e = shift(end).map(EndDateTime);
+        Self::try_new(b, e).or_else(|_| Self::try_new(b, None)).ok()
+    }
+
     pub(crate) fn lookup<E>(kws: &mut StdKeywords) -> LookupTentative<Self, E> {
         let b = BeginDateTime::lookup_opt(kws);
         let e = EndDateTime::lookup_opt(kws);
@@ -221,6 +237,18 @@ mod tests {
             "2112-01-01T00:00:00+00:01",
         );
     }
+
+    #[test]
+    fn test_slice() {
+        let begin: FCSDateTime = "2112-01-01T23:59:00+00:00".parse().ok().unwrap();
+        let dt = Datetimes::try_new(Some(begin.into()), None).ok().unwrap();
+        let s = dt.slice(30.0, 90.0).unwrap();
+        let b: &Option<BeginDateTime> = s.as_ref();
+        let e: &Option<EndDateTime> = s.as_ref();
+        assert_eq!("2112-01-01T23:59:30+00:00", b.unwrap().to_string());
+        assert_eq!("2112-01-02T00:00:30+00:00", e.unwrap().to_string());
+        assert!(Datetimes::default().slice(0.0, 1.0).is_none());
+    }
 }
 
 #[cfg(feature = "python")]
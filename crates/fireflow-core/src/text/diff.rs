@@ -0,0 +1,239 @@
+//! Differences between two sets of keywords.
+//!
+//! These are meant for keeping metadata in sync with some external source (ie
+//! a database), where the same changes may need to be applied in either
+//! direction and recorded for auditing.
+
+use crate::error::MultiResult;
+
+use nonempty::NonEmpty;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Change to the value of one keyword
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "python",
+    derive(IntoPyObject, FromPyObject),
+    pyo3(from_item_all)
+)]
+pub struct ValueChange {
+    pub old: String,
+    pub new: String,
+}
+
+/// Keywords which were added, removed, or changed between two sets.
+///
+/// Keys are compared case-insensitively since this is how they are compared
+/// in TEXT. Added and changed keys are spelled as in the newer set, and
+/// removed keys as in the older set.
+#[derive(Clone, Default, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "python",
+    derive(IntoPyObject, FromPyObject),
+    pyo3(from_item_all)
+)]
+pub struct KeywordDiff {
+    /// Keywords only in the newer set
+    pub added: BTreeMap<String, String>,
+
+    /// Keywords only in the older set
+    pub removed: BTreeMap<String, String>,
+
+    /// Keywords in both sets with different values
+    pub changed: BTreeMap<String, ValueChange>,
+}
+
+/// Error when a diff does not agree with the keywords it is applied to
+pub enum DiffConflict {
+    /// Key to be added is already present
+    Present { key: String, value: String },
+    /// Key to be removed or changed is not present
+    Missing(String),
+    /// Key to be removed or changed has a different value than expected
+    Mismatch {
+        key: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl KeywordDiff {
+    /// Return the changes needed to turn `old` into `new`.
+    pub fn new(old: &HashMap<String, String>, new: &HashMap<String, String>) -> Self {
+        let old_keys = upper_index(old);
+        let new_keys = upper_index(new);
+        let mut ret = Self::default();
+        for (k, v) in new {
+            match old_keys.get(&k.to_ascii_uppercase()) {
+                None => {
+                    let _ = ret.added.insert(k.clone(), v.clone());
+                }
+                Some(ok) => {
+                    let ov = &old[ok];
+                    if ov != v {
+                        let c = ValueChange {
+                            old: ov.clone(),
+                            new: v.clone(),
+                        };
+                        let _ = ret.changed.insert(k.clone(), c);
+                    }
+                }
+            }
+        }
+        for (k, v) in old {
+            if !new_keys.contains_key(&k.to_ascii_uppercase()) {
+                let _ = ret.removed.insert(k.clone(), v.clone());
+            }
+        }
+        ret
+    }
+
+    /// Return true if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Return the total number of differences.
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    /// Return the diff which undoes this one.
+    pub fn invert(self) -> Self {
+        Self {
+            added: self.removed,
+            removed: self.added,
+            changed: self
+                .changed
+                .into_iter()
+                .map(|(k, c)| {
+                    let i = ValueChange {
+                        old: c.new,
+                        new: c.old,
+                    };
+                    (k, i)
+                })
+                .collect(),
+        }
+    }
+
+    /// Apply changes to a set of keywords.
+    ///
+    /// Every key to be removed or changed must be present with its old value,
+    /// and every key to be added must not be present. If any of these do not
+    /// hold, return all conflicts and leave `kws` untouched.
+    pub fn apply(&self, kws: &mut HashMap<String, String>) -> MultiResult<(), DiffConflict> {
+        let index = upper_index(kws);
+        let find = |k: &str| index.get(&k.to_ascii_uppercase()).cloned();
+        let check = |k: &str, expected: &str| {
+            find(k).map_or(Some(DiffConflict::Missing(k.to_string())), |ok| {
+                let actual = &kws[&ok];
+                (actual != expected).then(|| DiffConflict::Mismatch {
+                    key: k.to_string(),
+                    expected: expected.to_string(),
+                    actual: actual.clone(),
+                })
+            })
+        };
+        let conflicts = self
+            .added
+            .keys()
+            .flat_map(|k| {
+                find(k).map(|ok| DiffConflict::Present {
+                    key: k.clone(),
+                    value: kws[&ok].clone(),
+                })
+            })
+            .chain(self.removed.iter().flat_map(|(k, v)| check(k, v)))
+            .chain(self.changed.iter().flat_map(|(k, c)| check(k, &c.old)));
+        if let Some(es) = NonEmpty::collect(conflicts) {
+            return Err(es);
+        }
+        for k in self.removed.keys() {
+            if let Some(ok) = find(k) {
+                let _ = kws.remove(&ok);
+            }
+        }
+        for (k, c) in &self.changed {
+            if let Some(ok) = find(k) {
+                let _ = kws.insert(ok, c.new.clone());
+            }
+        }
+        for (k, v) in &self.added {
+            let _ = kws.insert(k.clone(), v.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Map uppercase keys to their original spelling.
+fn upper_index(kws: &HashMap<String, String>) -> HashMap<String, String> {
+    kws.keys()
+        .map(|k| (k.to_ascii_uppercase(), k.clone()))
+        .collect()
+}
+
+impl fmt::Display for DiffConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Present { key, value } => {
+                write!(f, "cannot add {key}, already present with value '{value}'")
+            }
+            Self::Missing(key) => write!(f, "cannot remove or change {key}, not present"),
+            Self::Mismatch {
+                key,
+                expected,
+                actual,
+            } => write!(f, "expected {key} to be '{expected}', got '{actual}'"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kws(xs: &[(&str, &str)]) -> HashMap<String, String> {
+        xs.iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_roundtrip() {
+        let old = kws(&[("$PROJ", "x"), ("$OP", "me"), ("site", "A")]);
+        let new = kws(&[("$PROJ", "y"), ("$op", "me"), ("$CELLS", "T")]);
+        let d = KeywordDiff::new(&old, &new);
+        assert_eq!(3, d.len());
+        assert_eq!(Some(&"T".to_string()), d.added.get("$CELLS"));
+        assert_eq!(Some(&"A".to_string()), d.removed.get("site"));
+        assert_eq!("y", d.changed["$PROJ"].new);
+        let mut x = old.clone();
+        assert!(d.apply(&mut x).is_ok());
+        assert!(KeywordDiff::new(&x, &new).is_empty());
+        assert!(d.invert().apply(&mut x).is_ok());
+        assert_eq!(old, x);
+    }
+
+    #[test]
+    fn test_diff_conflicts() {
+        let old = kws(&[("$PROJ", "x")]);
+        let new = kws(&[("$PROJ", "y"), ("$CELLS", "T")]);
+        let d = KeywordDiff::new(&old, &new);
+        let mut x = kws(&[("$proj", "z"), ("$CELLS", "B")]);
+        let es = d.apply(&mut x).err().unwrap();
+        assert_eq!(2, es.len());
+        assert_eq!("z", x["$proj"]);
+    }
+}
@@ -27,6 +27,11 @@ impl<T> FloatDecimal<T> {
             _t: PhantomData,
         }
     }
+
+    /// Make a new decimal from an integer, which is always within float bounds.
+    pub(crate) fn from_u32(x: u32) -> Self {
+        Self::new(BigDecimal::from(x))
+    }
 }
 
 impl TryFrom<f32> for FloatDecimal<f32> {
@@ -60,25 +65,34 @@ impl<T: HasFloatBounds> TryFrom<BigDecimal> for FloatDecimal<T> {
             Sign::Minus => false,
             Sign::Plus => true,
         };
-        let (bi, s) = value.normalized().into_bigint_and_scale();
-        let (_, n) = bi.into_parts();
-        u64::try_from(n)
-            .ok()
-            .and_then(|x| {
-                if x > T::DIGITS && s <= -i64::from(T::ZEROS) {
-                    None
-                } else {
-                    Some(x)
-                }
+        // Compare magnitudes rather than digits and exponents separately since
+        // the latter will miss values like 1e39 which have few digits but are
+        // still out of bounds. Precision is not considered here.
+        if value.abs() > T::max_decimal().value {
+            Err(DecimalToFloatError {
+                src: value,
+                over,
+                typename: type_name::<T>(),
             })
-            .map_or(
-                Err(DecimalToFloatError {
-                    src: value,
-                    over,
-                    typename: type_name::<T>(),
-                }),
-                |range| Ok(Self::new(BigDecimal::from_biguint(BigUint::from(range), s))),
-            )
+        } else {
+            Ok(Self::new(value.normalized()))
+        }
+    }
+}
+
+impl From<&FloatDecimal<f32>> for f32 {
+    fn from(value: &FloatDecimal<f32>) -> Self {
+        // Go through a string since this is correctly rounded, whereas the
+        // direct conversion may be off by a few ULPs for large exponents.
+        // ASSUME this will never fail since value is within the bounds of f32
+        value.value.to_string().parse().unwrap_or_default()
+    }
+}
+
+impl From<&FloatDecimal<f64>> for f64 {
+    fn from(value: &FloatDecimal<f64>) -> Self {
+        // ASSUME this will never fail since value is within the bounds of f64
+        value.value.to_string().parse().unwrap_or_default()
     }
 }
 
@@ -210,4 +224,25 @@ mod tests {
             .unwrap();
         assert!(FloatDecimal::<f64>::try_from(d).is_err());
     }
+
+    #[test]
+    fn test_str_to_f32_sci_hypermax() {
+        let d = "1e39".parse::<BigDecimal>().unwrap();
+        assert!(FloatDecimal::<f32>::try_from(d.clone()).is_err());
+        assert!(FloatDecimal::<f64>::try_from(d).is_ok());
+    }
+
+    #[test]
+    fn test_str_to_f32_sci_hypermin() {
+        let d = "-3.5e38".parse::<BigDecimal>().unwrap();
+        let e = FloatDecimal::<f32>::try_from(d).err().unwrap();
+        assert!(!e.over);
+    }
+
+    #[test]
+    fn test_str_to_f32_keeps_sign() {
+        let d = "-1.5".parse::<BigDecimal>().unwrap();
+        let x = FloatDecimal::<f32>::try_from(d).ok().unwrap();
+        assert_eq!(f32::from(&x), -1.5);
+    }
 }
@@ -239,8 +239,8 @@ impl AppliedGates2_0 {
         if let Some(xs) = NonEmpty::collect(
             scheme
                 .regions
-                .iter()
-                .flat_map(|(_, r)| r.indices())
+                .values()
+                .flat_map(|r| r.indices())
                 .filter(|i| usize::from(*i) >= n),
         ) {
             Err(GateMeasurementLinkError(xs))
@@ -316,8 +316,8 @@ impl AppliedGates3_0 {
         if let Some(xs) = NonEmpty::collect(
             scheme
                 .regions
-                .iter()
-                .flat_map(|(_, r)| r.indices())
+                .values()
+                .flat_map(|r| r.indices())
                 .flat_map(GateIndex::try_from)
                 .filter(|&i| usize::from(i) >= n),
         ) {
@@ -361,6 +361,13 @@ impl AppliedGates3_0 {
         self.scheme.shift_meas_indices_after_insert(i);
     }
 
+    /// Remove regions which refer to a measurement which is being removed.
+    ///
+    /// See [`GatingScheme::remove_meas_index`].
+    pub(crate) fn remove_meas_index(&mut self, i: MeasIndex) {
+        self.scheme.remove_meas_index(i);
+    }
+
     pub(crate) fn indices_difference(
         &self,
         indices: &HashSet<MeasIndex>,
@@ -494,6 +501,13 @@ impl AppliedGates3_2 {
         self.0.shift_meas_indices_after_insert(i);
     }
 
+    /// Remove regions which refer to a measurement which is being removed.
+    ///
+    /// See [`GatingScheme::remove_meas_index`].
+    pub(crate) fn remove_meas_index(&mut self, i: MeasIndex) {
+        self.0.remove_meas_index(i);
+    }
+
     pub(crate) fn indices_difference(
         &self,
         indices: &HashSet<MeasIndex>,
@@ -680,6 +694,34 @@ impl<I> GatingScheme<I> {
         }
     }
 
+    /// Remove regions which refer to a measurement which is being removed.
+    ///
+    /// All regions with measurement indices greater than `i` will be
+    /// decremented by one. $GATING will also be removed if it refers to any
+    /// removed region since it would no longer make sense.
+    pub(crate) fn remove_meas_index(&mut self, i: MeasIndex)
+    where
+        I: LinkedMeasIndex,
+    {
+        let removed: HashSet<_> = self
+            .regions
+            .iter()
+            .filter(|(_, r)| r.meas_indices().any(|j| j == i))
+            .map(|(ri, _)| *ri)
+            .collect();
+        self.regions.retain(|ri, _| !removed.contains(ri));
+        if self
+            .gating
+            .as_ref()
+            .is_some_and(|g| g.region_indices().iter().any(|ri| removed.contains(ri)))
+        {
+            self.gating = None;
+        }
+        for (_, r) in self.regions.iter_mut() {
+            r.shift_after_remove(i)
+        }
+    }
+
     fn indices_difference(&self, indices: &HashSet<MeasIndex>) -> impl Iterator<Item = MeasIndex>
     where
         I: LinkedMeasIndex,
@@ -691,7 +733,7 @@ impl<I> GatingScheme<I> {
     where
         I: LinkedMeasIndex,
     {
-        self.regions.iter().flat_map(|(_, v)| v.meas_indices())
+        self.regions.values().flat_map(|v| v.meas_indices())
     }
 
     fn lookup<F0, F1, E>(
@@ -930,6 +972,27 @@ impl<I> Region<I> {
             }
         };
     }
+
+    /// Shift indices after the measurement at `i` is removed.
+    ///
+    /// Assume this region does not refer to `i`.
+    fn shift_after_remove(&mut self, i: MeasIndex)
+    where
+        I: LinkedMeasIndex,
+    {
+        let ix = usize::from(i);
+        let go = |j: &mut MeasIndex| {
+            let jx = usize::from(*j);
+            *j = if jx > ix { jx - 1 } else { jx }.into();
+        };
+        match self {
+            Self::Univariate(r) => r.index.meas_index_mut().map(go),
+            Self::Bivariate(r) => {
+                r.index.x.meas_index_mut().map(go);
+                r.index.y.meas_index_mut().map(go)
+            }
+        };
+    }
 }
 
 impl TryFrom<MeasOrGateIndex> for PrefixedMeasIndex {
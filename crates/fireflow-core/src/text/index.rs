@@ -79,6 +79,24 @@ newtype_index!(
     RegionIndex
 );
 
+/// A warning or error which may pertain to a single measurement.
+pub trait MeasIndexed {
+    /// Return index of the measurement to which this pertains, if any.
+    fn meas_index(&self) -> Option<MeasIndex>;
+}
+
+/// Reorder a vector such that the ith element is `xs[order[i]]`.
+///
+/// `order` should be a permutation of the indices in `xs`; indices which are
+/// out of bounds or repeated will be skipped.
+pub(crate) fn permute<T>(xs: Vec<T>, order: &[MeasIndex]) -> Vec<T> {
+    let mut ys: Vec<_> = xs.into_iter().map(Some).collect();
+    order
+        .iter()
+        .flat_map(|i| ys.get_mut(usize::from(*i)).and_then(Option::take))
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct IndexError {
     pub index: IndexFromOne, // refers to index of element
@@ -126,6 +144,12 @@ mod tests {
         assert_eq!(i, i1);
         assert_eq!(i0, IndexFromOne(NonZero::new(1).unwrap()));
     }
+
+    #[test]
+    fn test_permute() {
+        let order: Vec<MeasIndex> = [2_usize, 0, 1].map(MeasIndex::from).into();
+        assert_eq!(permute(vec!['a', 'b', 'c'], &order), vec!['c', 'a', 'b']);
+    }
 }
 
 #[cfg(feature = "python")]
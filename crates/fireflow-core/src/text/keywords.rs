@@ -19,6 +19,7 @@ use super::ranged_float::*;
 use super::scale::*;
 use super::spillover::*;
 use super::timestamps::*;
+use super::units::*;
 use super::unstainedcenters::*;
 
 use bigdecimal::{BigDecimal, ParseBigDecimalError};
@@ -27,7 +28,7 @@ use derive_more::{Add, AsMut, Display, From, FromStr, Into, Sub};
 use itertools::Itertools;
 use nonempty::NonEmpty;
 use num_traits::cast::ToPrimitive;
-use num_traits::PrimInt;
+use num_traits::{PrimInt, Signed};
 use std::any::type_name;
 use std::collections::HashSet;
 use std::fmt;
@@ -91,7 +92,10 @@ impl fmt::Display for TimestepLossError {
 }
 
 /// The value of the $VOL keyword
-#[derive(Clone, Copy, From, Display, FromStr, Into, PartialEq)]
+///
+/// This is in nanoliters. When parsing, a unit suffix (nL, uL, or mL) will be
+/// accepted and converted, but only the number will be written.
+#[derive(Clone, Copy, From, Display, Into, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[into(NonNegFloat, f32)]
@@ -99,6 +103,36 @@ pub struct Vol(pub NonNegFloat);
 
 impl_newtype_try_from!(Vol, NonNegFloat, f32, RangedFloatError);
 
+impl FromStr for Vol {
+    type Err = RangedFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_with_units(s, &VOLUME_UNITS)
+            .map_err(RangedFloatError::Parse)
+            .and_then(Self::try_from)
+    }
+}
+
+impl Vol {
+    /// Make new volume from the given units.
+    pub fn from_units(x: f32, unit: VolumeUnit) -> Result<Self, RangedFloatError> {
+        Self::try_from(x * unit.nanoliters())
+    }
+
+    /// Return volume in the given units.
+    pub fn to_units(self, unit: VolumeUnit) -> f32 {
+        f32::from(self.0) / unit.nanoliters()
+    }
+
+    pub fn nanoliters(self) -> f32 {
+        self.to_units(VolumeUnit::Nanoliter)
+    }
+
+    pub fn microliters(self) -> f32 {
+        self.to_units(VolumeUnit::Microliter)
+    }
+}
+
 /// The value of the $TR field (all versions)
 ///
 /// This is formatted as 'string,f' where 'string' is a measurement name.
@@ -1532,30 +1566,57 @@ impl fmt::Display for GatingError {
 pub struct Range(pub BigDecimal);
 
 impl Range {
-    pub(crate) fn into_uint<T>(self, notrunc: bool) -> BiTentative<T, IntRangeError<()>>
+    /// Convert to an unsigned integer.
+    ///
+    /// Return error if value is negative, too large for `T`, or has a
+    /// fractional part.
+    pub fn try_to_uint<T>(&self) -> Result<T, IntRangeError<T>>
+    where
+        T: TryFrom<Self, Error = IntRangeError<T>>,
+    {
+        self.clone().try_into()
+    }
+
+    /// Convert to an unsigned integer, saturating at the bounds of `T`.
+    ///
+    /// Any fractional part will be truncated. Return an error alongside the
+    /// converted value if the value was changed in any way.
+    pub fn to_uint_saturating<T>(&self) -> (T, Option<IntRangeError<()>>)
     where
         T: TryFrom<Self, Error = IntRangeError<T>> + PrimInt,
     {
-        let (b, e) = self.try_into().map_or_else(
+        self.try_to_uint().map_or_else(
             |e: IntRangeError<T>| match e.error_kind {
                 IntRangeErrorKind::Overrange => (T::max_value(), Some(e.void())),
                 IntRangeErrorKind::Underrange => (T::zero(), Some(e.void())),
                 IntRangeErrorKind::PrecisionLoss(y) => (y, Some(e.void())),
             },
             |x| (x, None),
-        );
-        BiTentative::new_either1(b, e, notrunc)
+        )
     }
 
-    pub(crate) fn into_float<T>(
-        self,
-        notrunc: bool,
-    ) -> BiTentative<FloatDecimal<T>, DecimalToFloatError>
+    /// Convert to a decimal within the bounds of float type `T`.
+    ///
+    /// Return error if value is beyond the bounds of `T`. Precision is not
+    /// considered.
+    pub fn try_to_float<T>(&self) -> Result<FloatDecimal<T>, DecimalToFloatError>
+    where
+        FloatDecimal<T>: TryFrom<BigDecimal, Error = DecimalToFloatError>,
+    {
+        FloatDecimal::try_from(self.0.clone())
+    }
+
+    /// Convert to a decimal within bounds of float type `T`, saturating at
+    /// the bounds of `T`.
+    ///
+    /// Return an error alongside the converted value if the value was beyond
+    /// the bounds of `T`.
+    pub fn to_float_saturating<T>(&self) -> (FloatDecimal<T>, Option<DecimalToFloatError>)
     where
         FloatDecimal<T>: TryFrom<BigDecimal, Error = DecimalToFloatError>,
         T: HasFloatBounds,
     {
-        let (x, e) = FloatDecimal::try_from(self.0).map_or_else(
+        self.try_to_float().map_or_else(
             |e| {
                 let m = if e.over {
                     T::max_decimal()
@@ -1565,7 +1626,26 @@ impl Range {
                 (m, Some(e))
             },
             |x| (x, None),
-        );
+        )
+    }
+
+    pub(crate) fn into_uint<T>(self, notrunc: bool) -> BiTentative<T, IntRangeError<()>>
+    where
+        T: TryFrom<Self, Error = IntRangeError<T>> + PrimInt,
+    {
+        let (b, e) = self.to_uint_saturating();
+        BiTentative::new_either1(b, e, notrunc)
+    }
+
+    pub(crate) fn into_float<T>(
+        self,
+        notrunc: bool,
+    ) -> BiTentative<FloatDecimal<T>, DecimalToFloatError>
+    where
+        FloatDecimal<T>: TryFrom<BigDecimal, Error = DecimalToFloatError>,
+        T: HasFloatBounds,
+    {
+        let (x, e) = self.to_float_saturating();
         BiTentative::new_either1(x, e, notrunc)
     }
 }
@@ -1589,7 +1669,7 @@ macro_rules! try_from_range_int {
                         Err(err(IntRangeErrorKind::PrecisionLoss(y)))
                     }
                 } else {
-                    if BigDecimal::from($inttype::MAX) < *x {
+                    if x.is_positive() {
                         Err(err(IntRangeErrorKind::Overrange))
                     } else {
                         Err(err(IntRangeErrorKind::Underrange))
@@ -1686,7 +1766,10 @@ pub struct Power(pub NonNegFloat);
 impl_newtype_try_from!(Power, NonNegFloat, f32, RangedFloatError);
 
 /// The value of the $PnV key
-#[derive(Clone, Copy, From, Display, FromStr, Into, PartialEq)]
+///
+/// This is in volts. When parsing, a unit suffix (V, mV, or kV) will be
+/// accepted and converted, but only the number will be written.
+#[derive(Clone, Copy, From, Display, Into, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
 #[into(NonNegFloat, f32)]
@@ -1694,6 +1777,83 @@ pub struct DetectorVoltage(pub NonNegFloat);
 
 impl_newtype_try_from!(DetectorVoltage, NonNegFloat, f32, RangedFloatError);
 
+impl FromStr for DetectorVoltage {
+    type Err = RangedFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_with_units(s, &VOLTAGE_UNITS)
+            .map_err(RangedFloatError::Parse)
+            .and_then(Self::try_from)
+    }
+}
+
+impl DetectorVoltage {
+    pub fn volts(self) -> f32 {
+        self.0.into()
+    }
+
+    pub fn millivolts(self) -> f32 {
+        self.volts() * 1e3
+    }
+
+    pub fn kilovolts(self) -> f32 {
+        self.volts() / 1e3
+    }
+}
+
+/// The value of the $PnP key
+///
+/// This is the percentage of emitted light collected by the detector, and
+/// thus must be between 0 and 100. A trailing '%' will be accepted when
+/// parsing but not written. Deprecated in 3.2.
+#[derive(Clone, Copy, Display, Into, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "python", derive(IntoPyObject))]
+#[into(NonNegFloat, f32)]
+pub struct PercentEmitted(NonNegFloat);
+
+impl TryFrom<f32> for PercentEmitted {
+    type Error = PercentEmittedError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        let x = NonNegFloat::try_from(value)?;
+        if value > 100.0 {
+            Err(PercentEmittedError::Over100(value))
+        } else {
+            Ok(Self(x))
+        }
+    }
+}
+
+impl FromStr for PercentEmitted {
+    type Err = PercentEmittedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let t = s.trim();
+        t.strip_suffix('%')
+            .unwrap_or(t)
+            .trim_end()
+            .parse::<f32>()
+            .map_err(|e| PercentEmittedError::Float(RangedFloatError::Parse(e)))
+            .and_then(Self::try_from)
+    }
+}
+
+#[derive(From)]
+pub enum PercentEmittedError {
+    Float(RangedFloatError),
+    Over100(f32),
+}
+
+impl fmt::Display for PercentEmittedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Float(e) => e.fmt(f),
+            Self::Over100(x) => write!(f, "percent must be 100 or less, got {x}"),
+        }
+    }
+}
+
 /// The value of the $GmV key
 #[derive(Clone, Copy, Display, FromStr, Into, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -2021,6 +2181,16 @@ kw_opt_meta_string!(UnstainedInfo, "UNSTAINEDINFO");
 
 kw_opt_meta_string!(Flowrate, "FLOWRATE");
 
+impl Flowrate {
+    /// Interpret this as a volumetric rate like "60 uL/min".
+    ///
+    /// $FLOWRATE is free text, so this will fail for values which don't have
+    /// an explicit volume and time unit (ie "high").
+    pub fn rate(&self) -> Result<VolumeRate, VolumeRateError> {
+        self.0.parse()
+    }
+}
+
 // version-specific
 kw_opt_meta_int!(Tot, usize, "TOT"); // optional in 2.0
 req_meta!(Tot); // required in 3.0+
@@ -2038,8 +2208,7 @@ kw_req_meta!(ByteOrd3_1, "BYTEORD"); // 3.1+
 kw_req_meas!(Width, "B");
 kw_opt_meas_string!(Filter, "F");
 kw_opt_meas!(Power, "O");
-// TODO why is this a string?
-kw_opt_meas_string!(PercentEmitted, "P");
+kw_opt_meas!(PercentEmitted, "P"); // deprecated in 3.2
 kw_req_meas!(Range, "R");
 kw_opt_meas_string!(Longname, "S");
 kw_opt_meas_string!(DetectorType, "T");
@@ -2190,6 +2359,16 @@ mod tests {
         assert_from_to_str::<Mode3_2>("L");
     }
 
+    #[test]
+    fn test_range_to_int() {
+        let f = |s: &str| u8::try_from(s.parse::<Range>().unwrap()).map_err(|e| e.error_kind);
+        assert!(matches!(f("255"), Ok(255)));
+        assert!(matches!(f("256"), Err(IntRangeErrorKind::Overrange)));
+        assert!(matches!(f("1e39"), Err(IntRangeErrorKind::Overrange)));
+        assert!(matches!(f("-1"), Err(IntRangeErrorKind::Underrange)));
+        assert!(matches!(f("-1e39"), Err(IntRangeErrorKind::Underrange)));
+    }
+
     #[test]
     fn test_pnd() {
         assert_from_to_str::<Display>("Linear,0,1");
@@ -2317,12 +2496,53 @@ mod tests {
         assert_from_to_str_almost::<Gating>("R1 AND (R2.OR.R3)", "(R1 AND (R2 OR R3))");
         assert_from_to_str::<Gating>("((NOT R1) AND R2)");
     }
+
+    #[test]
+    fn test_range_huge() {
+        let r = "1e39".parse::<Range>().unwrap();
+        assert!(r.to_string().parse::<Range>().ok() == Some(r.clone()));
+        assert!(r.try_to_uint::<u64>().is_err());
+        let (x, e) = r.to_uint_saturating::<u64>();
+        assert_eq!(x, u64::MAX);
+        assert!(e.is_some());
+        assert!(r.try_to_float::<f32>().is_err());
+        let (y, f) = r.to_float_saturating::<f32>();
+        assert_eq!(f32::from(&y), f32::MAX);
+        assert!(f.is_some());
+        let z = r.try_to_float::<f64>().ok().unwrap();
+        assert_eq!(f64::from(&z), 1e39);
+    }
+
+    #[test]
+    fn test_unit_suffixes() {
+        assert_from_to_str::<DetectorVoltage>("500");
+        assert_from_to_str_almost::<DetectorVoltage>("500 V", "500");
+        assert_from_to_str_almost::<DetectorVoltage>("0.5kV", "500");
+        assert_from_to_str_almost::<Vol>("2uL", "2000");
+        assert!("-2 uL".parse::<Vol>().is_err());
+        let v = "1500".parse::<Vol>().ok().unwrap();
+        assert_eq!(v.microliters(), 1.5);
+        let f = Flowrate("30 uL/min".into());
+        assert_eq!(f.rate().ok().map(VolumeRate::nl_per_s), Some(500.0));
+    }
+
+    #[test]
+    fn test_percent_emitted() {
+        assert_from_to_str::<PercentEmitted>("50");
+        assert_from_to_str::<PercentEmitted>("12.5");
+        assert_from_to_str_almost::<PercentEmitted>("50%", "50");
+        assert!("100".parse::<PercentEmitted>().is_ok());
+        assert!("100.5".parse::<PercentEmitted>().is_err());
+        assert!("-1".parse::<PercentEmitted>().is_err());
+        assert!("most".parse::<PercentEmitted>().is_err());
+    }
 }
 
 #[cfg(feature = "python")]
 mod python {
     use crate::python::macros::{
-        impl_from_py_transparent, impl_from_py_via_fromstr, impl_to_py_via_display, impl_value_err,
+        impl_from_py_transparent, impl_from_py_via_fromstr, impl_to_py_via_display,
+        impl_try_from_py, impl_value_err,
     };
     use crate::text::ranged_float::PositiveFloat;
     use crate::validated::shortname::Shortname;
@@ -2331,9 +2551,9 @@ mod python {
         AlphaNumType, AlphaNumTypeError, CSVFlag, Calibration3_1, Calibration3_2, DetectorVoltage,
         Display, Feature, FeatureError, GateDetectorVoltage, GateRange, GateScale, GateShortname,
         IndexPair, LastModified, Mode, Mode3_2, Mode3_2Error, ModeError, NumType, NumTypeError,
-        OpticalType, OpticalTypeError, Originality, OriginalityError, PeakBin, PeakNumber, Power,
-        PrefixedMeasIndex, Range, Timestep, Trigger, UniGate, Unicode, Vertex, Vol, Wavelength,
-        Wavelengths,
+        OpticalType, OpticalTypeError, Originality, OriginalityError, PeakBin, PeakNumber,
+        PercentEmitted, PercentEmittedError, Power, PrefixedMeasIndex, Range, Timestep, Trigger,
+        UniGate, Unicode, Vertex, Vol, Wavelength, Wavelengths,
     };
 
     use pyo3::prelude::*;
@@ -2373,6 +2593,10 @@ mod python {
     impl_from_py_transparent!(Wavelengths);
     impl_from_py_transparent!(CSVFlag);
 
+    // $PnP must be validated beyond being non-negative
+    impl_value_err!(PercentEmittedError);
+    impl_try_from_py!(PercentEmitted, f32);
+
     // $PnCALIBRATION (3.1) as (f32, String) tuple in python
     impl<'py> FromPyObject<'py> for Calibration3_1 {
         fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
@@ -0,0 +1,127 @@
+//! Keywords from earlier versions which were dropped in 3.2.
+//!
+//! Files labeled as 3.2 are often written by software which was updated from
+//! 3.0 or 3.1 and still emits keywords like $COMP, $UNICODE, or $GATE. These
+//! are not part of 3.2, so without special handling they would be reported as
+//! pseudostandard along with any other unknown key starting with '$'. Keeping
+//! them separate lets migration tooling decide what to do with each (ie move
+//! $COMP to $SPILLOVER) rather than treating them as noise.
+
+use crate::error::Tentative;
+use crate::text::compensation::Compensation3_0;
+use crate::text::keywords::*;
+use crate::text::parser::*;
+use crate::validated::keys::{BiIndexedKey, IndexedKey, Key, StdKey, StdKeywords};
+
+use derive_more::{AsRef, From};
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Keywords from 2.0-3.1 which are not part of 3.2.
+///
+/// These are kept as their original strings since they cannot be written in
+/// 3.2 and thus are only useful for inspection.
+#[derive(Clone, Default, PartialEq, AsRef, From)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LegacyKeywords(StdKeywords);
+
+impl LegacyKeywords {
+    /// Return true if `key` is from a previous version and not in 3.2.
+    pub fn matches(key: &StdKey) -> bool {
+        let s: &str = key.as_ref();
+        [
+            Compensation3_0::C,
+            Unicode::C,
+            CSMode::C,
+            CSVBits::C,
+            CSTot::C,
+            Gate::C,
+        ]
+        .iter()
+        .any(|c| s.eq_ignore_ascii_case(c))
+            || CSVFlag::matches(key)
+            || PeakBin::matches(key)
+            || PeakNumber::matches(key)
+            || Dfc::matches(key)
+            || GateScale::matches(key)
+            || GateFilter::matches(key)
+            || GatePercentEmitted::matches(key)
+            || GateRange::matches(key)
+            || GateShortname::matches(key)
+            || GateLongname::matches(key)
+            || GateDetectorType::matches(key)
+            || GateDetectorVoltage::matches(key)
+    }
+
+    /// Remove all legacy keywords and flag each as deprecated.
+    pub(crate) fn lookup(
+        kws: &mut StdKeywords,
+        disallow_dep: bool,
+    ) -> LookupTentative<Self, DeprecatedError> {
+        let legacy: HashMap<_, _> = kws.extract_if(|k, _| Self::matches(k)).collect();
+        let mut keys: Vec<_> = legacy.keys().cloned().collect();
+        keys.sort();
+        let mut tnt = Tentative::new1(Self(legacy));
+        for k in keys {
+            let e = DeprecatedError::Key(DepKeyWarning(k));
+            if disallow_dep {
+                tnt.push_error(e);
+            } else {
+                tnt.push_warning(e.into());
+            }
+        }
+        tnt
+    }
+
+    /// Return value for a key (case-insensitive, with or without '$').
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let k = key.strip_prefix('$').unwrap_or(key);
+        self.0
+            .iter()
+            .find(|(x, _)| AsRef::<str>::as_ref(x).eq_ignore_ascii_case(k))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Return all pairs with keys including the leading '$'.
+    pub fn to_pairs(&self) -> HashMap<String, String> {
+        self.0
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut kws: StdKeywords = [
+            ("$COMP", "2,1,0,0,1"),
+            ("$G1E", "0,0"),
+            ("$DFC1TO2", "0.1"),
+            ("$SPILLOVER", "x"),
+            ("$P1N", "FSC"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.parse::<StdKey>().unwrap(), v.into()))
+        .collect();
+        let tnt = LegacyKeywords::lookup(&mut kws, false);
+        assert_eq!(3, tnt.warnings().len());
+        assert!(tnt.errors().is_empty());
+        let l = tnt.value();
+        assert_eq!(Some("0,0"), l.get("$g1e"));
+        assert_eq!(Some("0.1"), l.get("DFC1TO2"));
+        assert_eq!(None, l.get("$SPILLOVER"));
+        assert_eq!(2, kws.len());
+        let mut kws1: StdKeywords = [("$UNICODE".parse().unwrap(), "x".into())].into();
+        assert_eq!(1, LegacyKeywords::lookup(&mut kws1, true).errors().len());
+    }
+}
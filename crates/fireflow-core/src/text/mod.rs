@@ -1,15 +1,25 @@
 pub mod byteord;
+pub mod carrier;
+#[cfg(feature = "serde")]
+pub mod changelog;
 pub mod compensation;
+pub mod conventions;
 pub mod datetimes;
+pub mod diff;
 pub mod float_decimal;
 pub mod gating;
 pub mod index;
 pub mod keywords;
+pub mod legacy;
 pub mod named_vec;
+pub mod namespace;
 pub mod optional;
 pub mod parser;
+pub mod provenance;
 pub mod ranged_float;
 pub mod scale;
 pub mod spillover;
 pub mod timestamps;
+pub mod tokenizer;
+pub mod units;
 pub mod unstainedcenters;
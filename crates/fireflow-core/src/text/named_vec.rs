@@ -2,7 +2,7 @@ use crate::error::*;
 use crate::text::optional::MightHave;
 use crate::validated::shortname::Shortname;
 
-use super::index::{BoundaryIndexError, IndexError, IndexFromOne, MeasIndex};
+use super::index::{permute, BoundaryIndexError, IndexError, IndexFromOne, MeasIndex};
 
 use derive_more::{From, Into};
 use std::cmp::Ordering;
@@ -551,7 +551,7 @@ impl<K: MightHave, U, V> WrappedNamedVec<K, U, V> {
                 match i.cmp(&left_len) {
                     Less => Ok(Element::NonCenter(&s.left[i])),
                     Equal => Ok(Element::Center(&s.center)),
-                    Greater => Ok(Element::NonCenter(&s.left[i - left_len - 1])),
+                    Greater => Ok(Element::NonCenter(&s.right[i - left_len - 1])),
                 }
             }
             NamedVec::Unsplit(u) => Ok(Element::NonCenter(&u.members[i])),
@@ -559,6 +559,21 @@ impl<K: MightHave, U, V> WrappedNamedVec<K, U, V> {
         .map(|x| x.bimap(|p| (&p.key, &p.value), |p| (&p.key, &p.value)))
     }
 
+    /// Get reference to non-center value at position.
+    ///
+    /// Return error if index is out of bounds or points to the center.
+    pub(crate) fn get_non_center(&self, index: MeasIndex) -> Result<&V, ElementIndexError> {
+        self.check_element_index(index, false)?;
+        self.get(index).map(|e| {
+            e.both(
+                // ASSUME this is unreachable since the index was checked
+                // above to not point to the center
+                |_| unreachable!(),
+                |(_, v)| v,
+            )
+        })
+    }
+
     /// Get mutable reference at position.
     #[allow(clippy::type_complexity)]
     pub fn get_mut(
@@ -573,7 +588,7 @@ impl<K: MightHave, U, V> WrappedNamedVec<K, U, V> {
                 match i.cmp(&left_len) {
                     Less => Ok(Element::NonCenter(&mut s.left[i])),
                     Equal => Ok(Element::Center(&mut s.center)),
-                    Greater => Ok(Element::NonCenter(&mut s.left[i - left_len - 1])),
+                    Greater => Ok(Element::NonCenter(&mut s.right[i - left_len - 1])),
                 }
             }
             NamedVec::Unsplit(u) => Ok(Element::NonCenter(&mut u.members[i])),
@@ -848,6 +863,62 @@ impl<K: MightHave, U, V> WrappedNamedVec<K, U, V> {
         ret
     }
 
+    /// Reorder elements such that the ith element is currently at `order[i]`.
+    ///
+    /// `order` must be a permutation of all indices. Elements without a name
+    /// will be given their current default name, since this depends on
+    /// position and would otherwise change.
+    pub(crate) fn reorder_nocheck(&mut self, order: &[MeasIndex]) {
+        let go = |xs: WrappedPairedVec<K, V>, offset: usize| {
+            xs.into_iter().enumerate().map(move |(i, p)| {
+                let key = if K::as_opt(&p.key).is_some() {
+                    p.key
+                } else {
+                    K::wrap(Shortname::from(MeasIndex::from(i + offset)))
+                };
+                Element::NonCenter(Pair {
+                    key,
+                    value: p.value,
+                })
+            })
+        };
+        let xs: Vec<_> = match mem::replace(self, dummy()) {
+            NamedVec::Split(s, _) => {
+                let offset = s.left.len() + 1;
+                go(s.left, 0)
+                    .chain([Element::Center(*s.center)])
+                    .chain(go(s.right, offset))
+                    .collect()
+            }
+            NamedVec::Unsplit(u) => go(u.members, 0).collect(),
+        };
+        let mut left = vec![];
+        let mut center = None;
+        let mut right = vec![];
+        for x in permute(xs, order) {
+            match x {
+                Element::NonCenter(p) if center.is_none() => left.push(p),
+                Element::NonCenter(p) => right.push(p),
+                Element::Center(c) => center = Some(c),
+            }
+        }
+        *self = if let Some(c) = center {
+            Self::new_split(left, c, right)
+        } else {
+            Self::new_unsplit(left)
+        };
+    }
+
+    /// Return index of key/value pair by name of key.
+    ///
+    /// Return error if name not found.
+    pub(crate) fn find_name(&self, n: &Shortname) -> Result<MeasIndex, KeyNotFoundError> {
+        self.indexed_names()
+            .find(|(_, x)| *x == n)
+            .map(|(i, _)| i)
+            .ok_or_else(|| KeyNotFoundError(n.clone()))
+    }
+
     /// Remove key/value pair by name of key.
     ///
     /// Return error if name not found.
@@ -2148,3 +2219,61 @@ mod python {
     impl_pyreflow_err!(SetKeysError);
     impl_pyreflow_err!(SetCenterError);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::optional::{AlwaysFamily, AlwaysValue};
+
+    type TestVec = WrappedNamedVec<AlwaysFamily, char, u32>;
+
+    // [a, T (center), b, c]
+    fn split_vec() -> TestVec {
+        let name = |s: &str| Shortname::new_unchecked(s);
+        let xs = vec![
+            Element::NonCenter((AlwaysValue(name("a")), 1)),
+            Element::Center((name("T"), 't')),
+            Element::NonCenter((AlwaysValue(name("b")), 2)),
+            Element::NonCenter((AlwaysValue(name("c")), 3)),
+        ];
+        TestVec::try_new(xs.into()).ok().unwrap()
+    }
+
+    fn value(x: Element<(&Shortname, &char), (&AlwaysValue<Shortname>, &u32)>) -> String {
+        x.both(|(k, v)| format!("{k}={v}"), |(k, v)| format!("{}={v}", k.0))
+    }
+
+    #[test]
+    fn test_get_split() {
+        let v = split_vec();
+        let xs: Vec<_> = (0..4)
+            .map(|i| value(v.get(i.into()).ok().unwrap()))
+            .collect();
+        assert_eq!(vec!["a=1", "T=t", "b=2", "c=3"], xs);
+        assert!(v.get(4.into()).is_err());
+    }
+
+    #[test]
+    fn test_get_mut_split() {
+        let mut v = split_vec();
+        for i in [0_usize, 2, 3] {
+            v.get_mut(i.into())
+                .ok()
+                .unwrap()
+                .both(|_| (), |(_, x)| *x *= 10);
+        }
+        let xs: Vec<_> = (0..4)
+            .map(|i| value(v.get(i.into()).ok().unwrap()))
+            .collect();
+        assert_eq!(vec!["a=10", "T=t", "b=20", "c=30"], xs);
+    }
+
+    #[test]
+    fn test_get_non_center() {
+        let v = split_vec();
+        assert_eq!(Some(&1), v.get_non_center(0.into()).ok());
+        assert!(v.get_non_center(1.into()).is_err());
+        assert_eq!(Some(&3), v.get_non_center(3.into()).ok());
+        assert!(v.get_non_center(4.into()).is_err());
+    }
+}
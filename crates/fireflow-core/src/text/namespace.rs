@@ -0,0 +1,189 @@
+//! Prefixed namespaces for nonstandard keywords.
+//!
+//! Vendors, analysis software, and in-house pipelines all store their own
+//! metadata in nonstandard keywords, usually with some prefix to tell them
+//! apart (ie "FJ_" or "MYLAB_"). A [`KeyNamespace`] makes this prefix explicit
+//! so that each party can read and replace only its own keywords, and so that
+//! collisions can be caught before writing.
+
+use crate::error::MultiResult;
+use crate::validated::keys::{NonStdKey, NonStdKeyError, NonStdKeywords};
+
+use derive_more::Display;
+use nonempty::NonEmpty;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A prefix shared by a group of nonstandard keywords.
+///
+/// Like all keys, prefixes are compared case-insensitively.
+#[derive(Clone, PartialEq, Eq, Hash, Display)]
+#[cfg_attr(test, derive(Debug))]
+pub struct KeyNamespace(NonStdKey);
+
+/// A reason why keywords in namespaces cannot be written
+pub enum NamespaceCollision {
+    /// Two namespaces where one prefix starts with the other
+    Overlap(KeyNamespace, KeyNamespace),
+
+    /// Key in a namespace which appears more than once in TEXT
+    Duplicate(KeyNamespace, NonStdKey),
+}
+
+impl FromStr for KeyNamespace {
+    type Err = NonStdKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl KeyNamespace {
+    pub fn prefix(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    /// Return true if key starts with this prefix and has a non-empty name.
+    pub fn contains(&self, key: &NonStdKey) -> bool {
+        self.strip(key).is_some()
+    }
+
+    /// Return true if either prefix starts with the other.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let (a, b) = (self.prefix(), other.prefix());
+        let n = a.len().min(b.len());
+        // ASSUME keys are ASCII so slicing is always at a char boundary
+        a[..n].eq_ignore_ascii_case(&b[..n])
+    }
+
+    /// Return the name of a key in this namespace without its prefix.
+    pub fn strip<'a>(&self, key: &'a NonStdKey) -> Option<&'a str> {
+        let p = self.prefix();
+        let k: &str = key.as_ref();
+        (k.len() > p.len() && k[..p.len()].eq_ignore_ascii_case(p)).then(|| &k[p.len()..])
+    }
+
+    /// Return key for a name in this namespace.
+    pub fn key(&self, name: &str) -> Result<NonStdKey, NonStdKeyError> {
+        format!("{}{name}", self.prefix()).parse()
+    }
+
+    /// Return all keywords in this namespace with their prefix removed.
+    pub fn select(&self, kws: &NonStdKeywords) -> HashMap<String, String> {
+        kws.iter()
+            .flat_map(|(k, v)| self.strip(k).map(|n| (n.to_string(), v.clone())))
+            .collect()
+    }
+
+    /// Replace all keywords in this namespace with the given names and values.
+    ///
+    /// Keywords outside this namespace are not touched. Return error and do
+    /// nothing if any name would not make a valid key.
+    pub fn replace(
+        &self,
+        kws: &mut NonStdKeywords,
+        xs: HashMap<String, String>,
+    ) -> Result<(), NonStdKeyError> {
+        let new = xs
+            .into_iter()
+            .map(|(n, v)| self.key(&n).map(|k| (k, v)))
+            .collect::<Result<Vec<_>, _>>()?;
+        kws.retain(|k, _| !self.contains(k));
+        kws.extend(new);
+        Ok(())
+    }
+}
+
+/// Check namespaces and the keys to be written in them for collisions.
+///
+/// `keys` should include every nonstandard key which will be written to TEXT,
+/// including those belonging to measurements.
+pub fn check_collisions<'a>(
+    namespaces: &[KeyNamespace],
+    keys: impl IntoIterator<Item = &'a NonStdKey>,
+) -> MultiResult<(), NamespaceCollision> {
+    let overlaps = namespaces.iter().enumerate().flat_map(|(i, a)| {
+        namespaces[i + 1..]
+            .iter()
+            .filter(|b| a.overlaps(b))
+            .map(|b| NamespaceCollision::Overlap(a.clone(), b.clone()))
+    });
+    let mut counts: HashMap<&NonStdKey, (usize, &KeyNamespace)> = HashMap::new();
+    for k in keys {
+        if let Some(ns) = namespaces.iter().find(|ns| ns.contains(k)) {
+            counts.entry(k).or_insert((0, ns)).0 += 1;
+        }
+    }
+    let mut dups: Vec<_> = counts
+        .into_iter()
+        .filter(|(_, (n, _))| *n > 1)
+        .map(|(k, (_, ns))| NamespaceCollision::Duplicate(ns.clone(), k.clone()))
+        .collect();
+    // sort so errors come out the same every time
+    dups.sort_by_key(ToString::to_string);
+    NonEmpty::collect(overlaps.chain(dups)).map_or(Ok(()), Err)
+}
+
+impl fmt::Display for NamespaceCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Overlap(a, b) => write!(f, "namespaces '{a}' and '{b}' overlap"),
+            Self::Duplicate(ns, k) => {
+                write!(f, "key {k} in namespace '{ns}' appears more than once")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+mod python {
+    use super::KeyNamespace;
+    use crate::python::macros::{impl_from_py_via_fromstr, impl_to_py_via_display};
+
+    impl_from_py_via_fromstr!(KeyNamespace);
+    impl_to_py_via_display!(KeyNamespace);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ns(s: &str) -> KeyNamespace {
+        s.parse().ok().unwrap()
+    }
+
+    fn key(s: &str) -> NonStdKey {
+        s.parse().ok().unwrap()
+    }
+
+    #[test]
+    fn test_select_replace() {
+        let lab = ns("MYLAB_");
+        let mut kws: NonStdKeywords = [
+            (key("mylab_batch"), "1".to_string()),
+            (key("FJ_BATCH"), "2".to_string()),
+            (key("MYLAB_"), "3".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let xs = lab.select(&kws);
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs["batch"], "1");
+        let ys = [("SITE".to_string(), "A".to_string())].into();
+        assert!(lab.replace(&mut kws, ys).is_ok());
+        assert_eq!(kws.len(), 3);
+        assert_eq!(kws[&key("MYLAB_SITE")], "A");
+        assert_eq!(kws[&key("FJ_BATCH")], "2");
+    }
+
+    #[test]
+    fn test_collisions() {
+        let nss = [ns("FJ_"), ns("MYLAB_"), ns("fj_x")];
+        let ks = [key("FJ_A"), key("MYLAB_A"), key("mylab_a"), key("OTHER")];
+        let es = check_collisions(&nss, &ks).err().unwrap();
+        assert_eq!(es.len(), 2);
+        assert!(matches!(es.head, NamespaceCollision::Overlap(_, _)));
+        assert!(check_collisions(&nss[..2], &ks[..2]).is_ok());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::config::{StdTextReadConfig, TimeMeasNamePattern};
+use crate::config::{
+    InstrumentRegistryError, KeywordExtensionError, StdTextReadConfig, TimeMeasNamePattern,
+};
 use crate::core::*;
 use crate::error::*;
 use crate::validated::keys::*;
@@ -550,6 +552,22 @@ where
 
 pub(crate) type RawKeywords = HashMap<String, String>;
 
+/// Values of optional standard keys which could not be parsed.
+///
+/// These are kept as-is so that writing a file back will not silently drop
+/// them.
+pub type RawValues = HashMap<StdKey, String>;
+
+/// Return key and value of each optional key which failed to parse.
+pub(crate) fn unparsed_values<V, E>(
+    tnt: &LookupTentative<V, E>,
+) -> impl Iterator<Item = (&StdKey, &String)> {
+    tnt.warnings().iter().filter_map(|w| match w {
+        LookupKeysWarning::Parse(e) => Some((&e.key, &e.value)),
+        _ => None,
+    })
+}
+
 pub(crate) type ReqResult<T> = Result<T, ReqKeyError<<T as FromStr>::Err>>;
 pub(crate) type ReqStResult<T> = Result<T, ReqKeyError<<T as FromStrStateful>::Err>>;
 pub(crate) type OptResult<T> = Result<Option<T>, ParseKeyError<<T as FromStr>::Err>>;
@@ -574,6 +592,8 @@ pub enum LookupKeysWarning {
     LinkedName(LinkedNameError),
     LinkedIndex(RegionIndexError),
     Dep(DeprecatedError),
+    Registry(InstrumentRegistryError),
+    Extension(KeywordExtensionError),
 }
 
 #[derive(From, Display)]
@@ -607,6 +627,7 @@ pub enum ParseOptKeyWarning {
     TemporalScale(TemporalScaleError),
     Float(ParseFloatError),
     RangedFloat(RangedFloatError),
+    PercentEmitted(PercentEmittedError),
     Feature(FeatureError),
     Wavelengths(WavelengthsError),
     Calibration3_1(CalibrationError<CalibrationFormat3_1>),
@@ -670,6 +691,17 @@ pub enum LookupRelationalWarning {
     Spillover(SpilloverIndexError),
 }
 
+impl MeasIndexed for LookupKeysWarning {
+    fn meas_index(&self) -> Option<MeasIndex> {
+        match self {
+            Self::Parse(x) => x.key.meas_index(),
+            Self::Dep(DeprecatedError::Key(x)) => x.0.meas_index(),
+            Self::LinkedName(x) => x.key.meas_index(),
+            _ => None,
+        }
+    }
+}
+
 /// Error/warning triggered when encountering a key which is deprecated
 pub struct DepKeyWarning(pub StdKey);
 
@@ -721,6 +753,67 @@ pub struct PseudostandardError(pub StdKey);
 /// Error denoting that unused standard keyword was found.
 pub struct UnusedStandardError(pub StdKey);
 
+/// Error denoting that a $Pn* keyword does not refer to a measurement.
+pub struct OrphanMeasKeyError {
+    pub key: StdKey,
+    pub index: usize,
+    pub par: usize,
+    pub duplicate_of: Option<StdKey>,
+}
+
+impl OrphanMeasKeyError {
+    /// Check if an unused standard key is an orphaned measurement keyword.
+    ///
+    /// This is true if the key looks like $PnX and n is either zero, greater
+    /// than $PAR, or written with leading zeros (in which case it would never
+    /// be found when looking up measurements). Return None for anything else.
+    pub(crate) fn from_key(key: &StdKey, par: Par, kws: &StdKeywords) -> Option<Self> {
+        let (digits, suffix) = key.split_meas()?;
+        let index = digits.parse::<usize>().ok()?;
+        if index == 0 || index > par.0 {
+            Some(Self {
+                key: key.clone(),
+                index,
+                par: par.0,
+                duplicate_of: None,
+            })
+        } else if digits.starts_with('0') {
+            // ASSUME this will never fail since it is the same as key with
+            // zeros removed
+            let c = format!("$P{index}{suffix}").parse::<StdKey>().ok()?;
+            let duplicate_of = if kws.contains_key(&c) { Some(c) } else { None };
+            Some(Self {
+                key: key.clone(),
+                index,
+                par: par.0,
+                duplicate_of,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for OrphanMeasKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if self.index == 0 || self.index > self.par {
+            write!(
+                f,
+                "{} refers to measurement {} but $PAR is {}",
+                self.key, self.index, self.par
+            )
+        } else if let Some(d) = &self.duplicate_of {
+            write!(f, "{} duplicates {d} and will be ignored", self.key)
+        } else {
+            write!(
+                f,
+                "{} has zero-padded index and will not be used for measurement {}",
+                self.key, self.index
+            )
+        }
+    }
+}
+
 impl fmt::Display for PseudostandardError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "pseudostandard keyword found: {}", self.0)
@@ -0,0 +1,239 @@
+use crate::validated::keys::*;
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// Key for the filename of the parent dataset
+pub const PROVENANCE_PARENT: &str = "FIREFLOW_PARENT";
+
+/// Key for the hash of the parent file
+pub const PROVENANCE_PARENT_HASH: &str = "FIREFLOW_PARENT_HASH";
+
+/// Key for the description of the operation which produced this dataset
+pub const PROVENANCE_OPERATION: &str = "FIREFLOW_OPERATION";
+
+/// Key for the software which produced this dataset
+pub const PROVENANCE_SOFTWARE: &str = "FIREFLOW_SOFTWARE";
+
+/// Key for the nonstandard key from which the start of acquisition was filled
+///
+/// This is not part of [`Provenance`] since it describes the TEXT of this
+/// dataset rather than how it was derived.
+pub const PROVENANCE_TIMESTAMP_SOURCE: &str = "FIREFLOW_TIMESTAMP_SOURCE";
+
+/// Provenance for a dataset derived from another FCS file.
+///
+/// This is stored as nonstandard keywords in the TEXT segment (all starting
+/// with "FIREFLOW_"). Since each parent may itself have provenance, these can
+/// be followed back to the original acquisition by matching the parent hash.
+#[derive(Clone, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(
+    feature = "python",
+    derive(IntoPyObject, FromPyObject),
+    pyo3(from_item_all)
+)]
+pub struct Provenance {
+    /// Filename of the parent dataset (not including directory)
+    pub parent: Option<String>,
+
+    /// Hash of the parent file as 64 hex characters (SHA-256)
+    pub parent_hash: Option<String>,
+
+    /// Free-form description of how this dataset was derived
+    pub operation: Option<String>,
+
+    /// Software and version which wrote this dataset
+    pub software: Option<String>,
+}
+
+/// A file from which datasets were derived.
+///
+/// This may be made once and given to each operation which derives datasets
+/// from the same file, so that the file is only hashed once.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParentFile {
+    /// Filename (not including directory)
+    pub name: Option<String>,
+
+    /// Hash of the file as 64 hex characters (SHA-256)
+    pub hash: String,
+}
+
+impl ParentFile {
+    /// Identify a file by its name and hash.
+    ///
+    /// This will read the entire file (in chunks) in order to compute its hash.
+    pub fn from_path(p: &Path) -> io::Result<Self> {
+        Ok(Self {
+            name: p.file_name().map(|x| x.to_string_lossy().into_owned()),
+            hash: hash_reader(fs::File::open(p)?)?,
+        })
+    }
+}
+
+impl Provenance {
+    /// Make new provenance with operation and this library as the software.
+    pub fn new(operation: String) -> Self {
+        Self {
+            parent: None,
+            parent_hash: None,
+            operation: Some(operation),
+            software: Some(Self::software_name()),
+        }
+    }
+
+    /// Return the name and version of this library.
+    pub(crate) fn software_name() -> String {
+        format!("fireflow {}", env!("CARGO_PKG_VERSION"))
+    }
+
+    /// Return the software keyword to be written with each dataset.
+    pub(crate) fn software_pair() -> (String, String) {
+        (PROVENANCE_SOFTWARE.into(), Self::software_name())
+    }
+
+    /// Make new provenance with a parent file given by a path.
+    ///
+    /// This will read the entire file (in chunks) in order to compute its hash.
+    pub fn new_with_parent(operation: String, parent: &Path) -> io::Result<Self> {
+        ParentFile::from_path(parent).map(|x| Self::derived(operation, Some(&x)))
+    }
+
+    /// Make new provenance for a dataset derived from a parent file if known.
+    pub fn derived(operation: String, parent: Option<&ParentFile>) -> Self {
+        Self {
+            parent: parent.and_then(|x| x.name.clone()),
+            parent_hash: parent.map(|x| x.hash.clone()),
+            ..Self::new(operation)
+        }
+    }
+
+    /// Return true if no fields are set.
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_none()
+            && self.parent_hash.is_none()
+            && self.operation.is_none()
+            && self.software.is_none()
+    }
+
+    /// Read provenance from nonstandard keywords.
+    ///
+    /// Return None if none of the provenance keys are present.
+    pub(crate) fn from_keywords(kws: &NonStdKeywords) -> Option<Self> {
+        let get = |k: &str| {
+            k.parse::<NonStdKey>()
+                .ok()
+                .and_then(|kk| kws.get(&kk).cloned())
+        };
+        let ret = Self {
+            parent: get(PROVENANCE_PARENT),
+            parent_hash: get(PROVENANCE_PARENT_HASH),
+            operation: get(PROVENANCE_OPERATION),
+            software: get(PROVENANCE_SOFTWARE),
+        };
+        if ret.is_empty() {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+
+    /// Replace provenance in nonstandard keywords.
+    ///
+    /// Any existing provenance keys will be removed first. Empty values will
+    /// not be written since these are not allowed in TEXT.
+    pub(crate) fn replace_keywords(this: Option<&Self>, kws: &mut NonStdKeywords) {
+        let fields = [
+            (PROVENANCE_PARENT, this.and_then(|x| x.parent.as_ref())),
+            (
+                PROVENANCE_PARENT_HASH,
+                this.and_then(|x| x.parent_hash.as_ref()),
+            ),
+            (
+                PROVENANCE_OPERATION,
+                this.and_then(|x| x.operation.as_ref()),
+            ),
+            (PROVENANCE_SOFTWARE, this.and_then(|x| x.software.as_ref())),
+        ];
+        for (k, v) in fields {
+            // ASSUME these will never fail since the keys are constants which
+            // are valid nonstandard keys
+            if let Ok(kk) = k.parse::<NonStdKey>() {
+                kws.remove(&kk);
+                if let Some(vv) = v.filter(|x| !x.is_empty()) {
+                    kws.insert(kk, vv.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Compute SHA-256 hash of a byte string, formatted as hex.
+pub fn hash_bytes(bs: &[u8]) -> String {
+    to_hex(&Sha256::digest(bs))
+}
+
+/// Compute the same hash as [`hash_bytes`] without reading everything at once.
+pub fn hash_reader<R: Read>(mut r: R) -> io::Result<String> {
+    let mut buf = [0_u8; 65536];
+    let mut h = Sha256::new();
+    loop {
+        let n = r.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        h.update(&buf[..n]);
+    }
+    Ok(to_hex(&h.finalize()))
+}
+
+fn to_hex(bs: &[u8]) -> String {
+    bs.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_hash_bytes() {
+        assert_eq!(
+            hash_bytes(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_bytes(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let bs = vec![7_u8; 100_000];
+        assert_eq!(hash_bytes(&bs), hash_reader(&bs[..]).unwrap());
+    }
+
+    #[test]
+    fn test_roundtrip_keywords() {
+        let mut kws = HashMap::new();
+        assert_eq!(Provenance::from_keywords(&kws), None);
+        let p = Provenance {
+            parent: Some("parent.fcs".into()),
+            parent_hash: Some(hash_bytes(b"parent")),
+            ..Provenance::new("subsample".into())
+        };
+        Provenance::replace_keywords(Some(&p), &mut kws);
+        assert_eq!(kws.len(), 4);
+        assert_eq!(Provenance::from_keywords(&kws), Some(p));
+        Provenance::replace_keywords(None, &mut kws);
+        assert!(kws.is_empty());
+    }
+}
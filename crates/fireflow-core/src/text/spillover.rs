@@ -37,22 +37,27 @@ pub struct GenericSpillover<T> {
 }
 
 impl Spillover {
-    // pub(crate) fn remove_by_name(&mut self, n: &Shortname) -> ClearMaybe<bool> {
-    //     if let Some(i) = self.measurements.iter().position(|m| m == n) {
-    //         if self.measurements.len() < 3 {
-    //             ClearMaybe::clear(true)
-    //         } else {
-    //             // TODO this looks expensive; it copies almost everything 3x;
-    //             // good thing these matrices aren't that big (usually). The
-    //             // alternative is to iterate over the matrix and populate a new
-    //             // one while skipping certain elements.
-    //             self.matrix = self.matrix.clone().remove_row(i).remove_column(i);
-    //             ClearMaybe::new(true)
-    //         }
-    //     } else {
-    //         ClearMaybe::new(false)
-    //     }
-    // }
+    /// Remove a measurement and its row/column if present.
+    ///
+    /// Return true if the matrix would be too small to be valid, in which case
+    /// it should be removed entirely and this will not be changed.
+    pub(crate) fn remove_by_name(&mut self, n: &Shortname) -> bool {
+        if let Some(i) = self.measurements.iter().position(|m| m == n) {
+            if self.measurements.len() < 3 {
+                true
+            } else {
+                // TODO this looks expensive; it copies almost everything 3x;
+                // good thing these matrices aren't that big (usually). The
+                // alternative is to iterate over the matrix and populate a new
+                // one while skipping certain elements.
+                let _ = self.measurements.remove(i);
+                self.matrix = self.matrix.clone().remove_row(i).remove_column(i);
+                false
+            }
+        } else {
+            false
+        }
+    }
 
     // pub(crate) fn table(&self, delim: &str) -> Vec<String> {
     //     let header0 = vec!["[-]"];
@@ -3,10 +3,11 @@ use crate::error::*;
 use crate::validated::keys::*;
 use crate::validated::timepattern::ParseWithTimePatternError;
 
+use super::datetimes::{Datetimes, FCSDateTime};
 use super::optional::*;
 use super::parser::*;
 
-use chrono::{NaiveDate, NaiveTime, Timelike};
+use chrono::{Days, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
 use derive_more::{AsRef, Display, From, FromStr, Into};
 use regex::Regex;
 use std::fmt;
@@ -156,6 +157,91 @@ impl<X> Timestamps<X> {
         }
     }
 
+    /// Combine $DATE with $BTIM/$ETIM to make $BEGINDATETIME/$ENDDATETIME.
+    ///
+    /// Return None if $DATE or $BTIM is missing. Since FCS does not store a
+    /// timezone with these keywords, the caller must supply the offset at
+    /// which they were recorded. If $ETIM is before $BTIM, acquisition is
+    /// assumed to have crossed midnight and $ENDDATETIME will be on the next
+    /// day.
+    pub(crate) fn to_datetimes(&self, offset: FixedOffset) -> Option<Datetimes>
+    where
+        X: Copy + Into<NaiveTime>,
+    {
+        let date = self.date?.0;
+        let to_dt = |d: NaiveDate, t: NaiveTime| {
+            d.and_time(t)
+                .and_local_timezone(offset)
+                .single()
+                .map(FCSDateTime)
+        };
+        let btim: NaiveTime = self.btim?.0.into();
+        let begin = to_dt(date, btim)?;
+        let end = self.etim.map(|e| e.0.into()).and_then(|etim: NaiveTime| {
+            let d = if etim < btim {
+                date.checked_add_days(Days::new(1))?
+            } else {
+                date
+            };
+            to_dt(d, etim)
+        });
+        Datetimes::try_new(Some(begin.into()), end.map(|e| e.into())).ok()
+    }
+
+    /// Return true if either $BTIM or $DATE is present.
+    pub(crate) fn has_start(&self) -> bool {
+        self.btim.is_some() || self.date.is_some()
+    }
+
+    /// Set $BTIM and $DATE from a datetime if both are missing.
+    ///
+    /// Return true if these were set, which will not happen if either is
+    /// already present or if the new $BTIM would be after $ETIM.
+    pub(crate) fn fill_start(&mut self, start: NaiveDateTime) -> bool
+    where
+        X: Copy + PartialOrd + From<NaiveTime>,
+    {
+        if self.has_start() {
+            return false;
+        }
+        let btim = Some(Xtim(start.time().into()));
+        let date = Some(FCSDate(start.date()));
+        Self::try_new(btim, self.etim, date)
+            .map(|ts| *self = ts)
+            .is_ok()
+    }
+
+    /// Return timestamps for one slice of the acquisition.
+    ///
+    /// `begin` and `end` are the number of seconds after $BTIM at which the
+    /// slice starts and ends. $DATE will be advanced if the new $BTIM falls
+    /// after midnight, and $ETIM will be dropped if it would fall on a later
+    /// day than the new $BTIM. Return None if $BTIM is missing.
+    pub(crate) fn slice(&self, begin: f64, end: f64) -> Option<Self>
+    where
+        X: Copy + PartialOrd + Into<NaiveTime> + From<NaiveTime>,
+    {
+        let t0: NaiveTime = self.btim?.0.into();
+        let shift = |secs: f64| {
+            let (t, overflow) =
+                t0.overflowing_add_signed(TimeDelta::milliseconds((secs * 1000.0).round() as i64));
+            (t, overflow / 86_400)
+        };
+        let (b, bdays) = shift(begin);
+        let (e, edays) = shift(end);
+        let date = self.date.map(|d| {
+            u64::try_from(bdays)
+                .ok()
+                .and_then(|n| d.0.checked_add_days(Days::new(n)))
+                .map_or(d, FCSDate)
+        });
+        let btim = Some(Xtim(b.into()));
+        let etim = Some(Xtim(e.into()));
+        Self::try_new(btim, etim.filter(|_| edays == bdays), date)
+            .or_else(|_| Self::try_new(btim, None, date))
+            .ok()
+    }
+
     pub(crate) fn lookup<E>(
         kws: &mut StdKeywords,
         conf: &StdTextReadConfig,
@@ -422,6 +508,7 @@ impl fmt::Display for FCSTime100Error {
 mod tests {
     use super::*;
     use crate::test::*;
+    use crate::text::datetimes::{BeginDateTime, EndDateTime};
 
     #[test]
     fn test_str_timestamps2_0() {
@@ -445,6 +532,86 @@ mod tests {
         // this is an overflow
         assert!("23:58:00.100".parse::<FCSTime100>().is_err())
     }
+
+    #[test]
+    fn test_to_datetimes() {
+        let t = |h, m| FCSTime100(NaiveTime::from_hms_opt(h, m, 0).unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2).map(FCSDate);
+        let ts = Timestamps {
+            btim: Some(Xtim(t(12, 0))),
+            etim: Some(Xtim(t(13, 30))),
+            date,
+        };
+        let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+        let dt = ts.to_datetimes(tz).unwrap();
+        let b: &Option<BeginDateTime> = dt.as_ref();
+        let e: &Option<EndDateTime> = dt.as_ref();
+        assert_eq!("2025-01-02T12:00:00-05:00", b.unwrap().to_string());
+        assert_eq!("2025-01-02T13:30:00-05:00", e.unwrap().to_string());
+        // no date means no datetimes
+        let no_date = Timestamps { date: None, ..ts };
+        assert!(no_date.to_datetimes(tz).is_none());
+        // acquisition crossing midnight should end on the next day
+        let overnight = Timestamps {
+            btim: Some(Xtim(t(23, 0))),
+            etim: Some(Xtim(t(1, 0))),
+            ..ts
+        };
+        let overnight_dt = overnight.to_datetimes(tz).unwrap();
+        let overnight_e: &Option<EndDateTime> = overnight_dt.as_ref();
+        assert_eq!(
+            "2025-01-03T01:00:00-05:00",
+            overnight_e.unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_slice() {
+        let t = |h, m, s| FCSTime100(NaiveTime::from_hms_opt(h, m, s).unwrap());
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2).map(FCSDate);
+        let ts = Timestamps {
+            btim: Some(Xtim(t(23, 59, 0))),
+            etim: Some(Xtim(t(23, 59, 50))),
+            date,
+        };
+        let s0 = ts.slice(10.0, 40.0).unwrap();
+        assert!(s0.btim == Some(Xtim(t(23, 59, 10))));
+        assert!(s0.etim == Some(Xtim(t(23, 59, 40))));
+        assert!(s0.date == date);
+        // end is past midnight so $ETIM is dropped
+        let s1 = ts.slice(40.0, 70.0).unwrap();
+        assert!(s1.btim == Some(Xtim(t(23, 59, 40))));
+        assert!(s1.etim.is_none());
+        // both past midnight so $DATE is advanced
+        let s2 = ts.slice(70.0, 80.0).unwrap();
+        assert!(s2.btim == Some(Xtim(t(0, 0, 10))));
+        assert!(s2.etim == Some(Xtim(t(0, 0, 20))));
+        assert!(s2.date == NaiveDate::from_ymd_opt(2025, 1, 3).map(FCSDate));
+        assert!(Timestamps::<FCSTime100>::default()
+            .slice(0.0, 1.0)
+            .is_none());
+    }
+
+    #[test]
+    fn test_fill_start() {
+        let t = |h, m, s| FCSTime100(NaiveTime::from_hms_opt(h, m, s).unwrap());
+        let d = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let start = d.and_hms_opt(12, 0, 0).unwrap();
+        let mut ts = Timestamps::<FCSTime100>::default();
+        assert!(ts.fill_start(start));
+        assert!(ts.btim == Some(Xtim(t(12, 0, 0))));
+        assert!(ts.date == Some(FCSDate(d)));
+        // already filled
+        assert!(!ts.fill_start(start));
+        // $ETIM is before the new $BTIM
+        let mut ts_rev = Timestamps {
+            btim: None,
+            etim: Some(Xtim(t(11, 0, 0))),
+            date: None,
+        };
+        assert!(!ts_rev.fill_start(start));
+        assert!(ts_rev.btim.is_none() && ts_rev.etim.is_some());
+    }
 }
 
 #[cfg(feature = "python")]
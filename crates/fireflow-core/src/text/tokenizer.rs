@@ -0,0 +1,466 @@
+//! Low-level splitting of TEXT into keys and values.
+//!
+//! Words in TEXT are separated by a single delimiter, and any delimiter within
+//! a word is escaped by doubling it (unless delimiters are treated literally,
+//! in which case they may not appear in words at all). The readers in
+//! [`crate::api`] use this tokenizer and then immediately parse the result,
+//! which leaves no room to recover files that are broken in ways the config
+//! cannot express. This exposes the splitting step on its own so the words may
+//! be inspected and fixed before anything is parsed.
+//!
+//! Each pair is yielded as slices into the original bytes along with their
+//! positions, and is still escaped. Use [`TextWord::unescape`] to get the value
+//! as it would appear after reading. Anything unusual found while splitting is
+//! yielded in line as a [`TextTokenIssue`] so that it may be handled the same
+//! way the reader would.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// One word from TEXT.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct TextWord<'a> {
+    /// Offset of the first byte of this word
+    pub offset: usize,
+
+    /// Bytes of this word with delimiters still escaped
+    pub raw: &'a [u8],
+}
+
+/// One key and its value from TEXT.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct TextPair<'a> {
+    pub key: TextWord<'a>,
+    pub value: TextWord<'a>,
+}
+
+/// Something unusual found while splitting TEXT.
+///
+/// None of these stop the tokenizer. The reader will treat each as either a
+/// warning or an error depending on its config.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum TextTokenIssue<'a> {
+    /// A word started or ended with an escaped delimiter.
+    ///
+    /// Only emitted when delimiters are escaped. Delimiters on a boundary
+    /// between two words are dropped, and those at the end of TEXT are kept
+    /// with the last word.
+    DelimAtBoundary,
+
+    /// A key was blank; its value will be skipped.
+    ///
+    /// Only emitted when delimiters are literal.
+    BlankKey,
+
+    /// A value was blank; the pair will be skipped.
+    ///
+    /// Only emitted when delimiters are literal.
+    BlankValue(TextWord<'a>),
+
+    /// TEXT had an odd number of words.
+    Uneven,
+
+    /// TEXT did not end with a delimiter.
+    MissingFinalDelim,
+}
+
+/// One item from TEXT.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum TextToken<'a> {
+    Pair(TextPair<'a>),
+    Issue(TextTokenIssue<'a>),
+}
+
+/// Iterator over each key/value pair in TEXT.
+#[derive(Clone)]
+pub struct TextTokenizer<'a> {
+    bytes: &'a [u8],
+    delim: u8,
+    base: usize,
+    /// Offset of the next segment, or None if all have been consumed
+    pos: Option<usize>,
+    pending: VecDeque<TextToken<'a>>,
+    state: State<'a>,
+}
+
+#[derive(Clone)]
+enum State<'a> {
+    Escaped(EscapedState<'a>),
+    Literal(LiteralState),
+    Done,
+}
+
+#[derive(Clone, Default)]
+struct EscapedState<'a> {
+    /// Number of blank segments since the last non-blank segment
+    consec_blanks: usize,
+    /// Start and end of the word currently being read
+    word: Option<(usize, usize)>,
+    /// The key if the current word is a value
+    key: Option<TextWord<'a>>,
+}
+
+#[derive(Clone, Default)]
+struct LiteralState {
+    prev_was_key: bool,
+    prev_was_blank: bool,
+}
+
+impl<'a> TextWord<'a> {
+    /// Offset one past the last byte of this word.
+    pub fn end(&self) -> usize {
+        self.offset + self.raw.len()
+    }
+
+    /// Return this word with escaped delimiters collapsed.
+    ///
+    /// This will only allocate if the word contains a delimiter.
+    pub fn unescape(&self, delim: u8) -> Cow<'a, [u8]> {
+        if self.raw.contains(&delim) {
+            Cow::Owned(unescape(delim, self.raw))
+        } else {
+            Cow::Borrowed(self.raw)
+        }
+    }
+
+    /// Return true if this word starts or ends with a delimiter.
+    ///
+    /// Such words are ambiguous since the delimiter could equally belong to
+    /// the neighboring word.
+    pub fn on_boundary(&self, delim: u8) -> bool {
+        self.raw.first() == Some(&delim) || self.raw.last() == Some(&delim)
+    }
+}
+
+impl<'a> TextTokenizer<'a> {
+    /// Split pairs from `bytes` which do not include the first delimiter.
+    ///
+    /// Delimiters within words are assumed to be escaped by doubling them.
+    pub fn new(bytes: &'a [u8], delim: u8) -> Self {
+        Self::new_inner(bytes, delim, State::Escaped(EscapedState::default()))
+    }
+
+    /// Like [`TextTokenizer::new`] but treat every delimiter as a boundary.
+    ///
+    /// This is what the reader does when `use_literal_delims` is set.
+    pub fn new_literal(bytes: &'a [u8], delim: u8) -> Self {
+        Self::new_inner(bytes, delim, State::Literal(LiteralState::default()))
+    }
+
+    /// Split pairs from `bytes` which start with the delimiter.
+    ///
+    /// Offsets will be relative to the start of `bytes`. Return None if
+    /// `bytes` is empty.
+    pub fn from_text(bytes: &'a [u8]) -> Option<Self> {
+        let (delim, rest) = bytes.split_first()?;
+        let mut ret = Self::new(rest, *delim);
+        ret.base = 1;
+        Some(ret)
+    }
+
+    pub fn delim(&self) -> u8 {
+        self.delim
+    }
+
+    /// Return only the pairs, skipping any issues.
+    pub fn pairs(self) -> impl Iterator<Item = TextPair<'a>> {
+        self.filter_map(|t| match t {
+            TextToken::Pair(p) => Some(p),
+            TextToken::Issue(_) => None,
+        })
+    }
+
+    fn new_inner(bytes: &'a [u8], delim: u8, state: State<'a>) -> Self {
+        Self {
+            bytes,
+            delim,
+            base: 0,
+            pos: Some(0),
+            pending: VecDeque::new(),
+            state,
+        }
+    }
+
+    fn word(&self, start: usize, end: usize) -> TextWord<'a> {
+        TextWord {
+            offset: self.base + start,
+            raw: &self.bytes[start..end],
+        }
+    }
+
+    /// Return the next segment between delimiters and its offset.
+    ///
+    /// This behaves like [`slice::split`], so a delimiter at the end will be
+    /// followed by an empty segment.
+    fn next_segment(&mut self) -> Option<(usize, &'a [u8])> {
+        let off = self.pos?;
+        let rest = &self.bytes[off..];
+        let seg = if let Some(i) = rest.iter().position(|x| *x == self.delim) {
+            self.pos = Some(off + i + 1);
+            &rest[..i]
+        } else {
+            self.pos = None;
+            rest
+        };
+        Some((off, seg))
+    }
+
+    fn push_issue(&mut self, i: TextTokenIssue<'a>) {
+        self.pending.push_back(TextToken::Issue(i));
+    }
+
+    fn push_pair(&mut self, key: TextWord<'a>, value: TextWord<'a>) {
+        self.pending
+            .push_back(TextToken::Pair(TextPair { key, value }));
+    }
+
+    /// Advance until at least one token is pending or everything is consumed.
+    fn step(&mut self) {
+        match std::mem::replace(&mut self.state, State::Done) {
+            State::Escaped(s) => self.step_escaped(s),
+            State::Literal(s) => self.step_literal(s),
+            State::Done => (),
+        }
+    }
+
+    fn step_escaped(&mut self, mut s: EscapedState<'a>) {
+        // Each blank segment is one more delimiter in a run. An odd number of
+        // delimiters (even number of blanks) is a boundary, and an even number
+        // is one or more escaped delimiters within a word.
+        while let Some((off, seg)) = self.next_segment() {
+            if seg.is_empty() {
+                s.consec_blanks += 1;
+                continue;
+            }
+            let end = off + seg.len();
+            if s.consec_blanks & 1 == 0 {
+                match (s.key, s.word) {
+                    (Some(key), Some((ws, we))) => {
+                        let value = self.word(ws, we);
+                        self.push_pair(key, value);
+                        s.key = None;
+                    }
+                    (None, Some((ws, we))) => s.key = Some(self.word(ws, we)),
+                    // this should only be reached on first iteration
+                    (_, None) => (),
+                }
+                s.word = Some((off, end));
+                if s.consec_blanks > 0 {
+                    self.push_issue(TextTokenIssue::DelimAtBoundary);
+                }
+            } else {
+                // Keep the escaped delimiters in the current word. If this is
+                // the first word, the delimiters start at the beginning.
+                let start = s.word.map_or(off - s.consec_blanks, |(ws, _)| ws);
+                s.word = Some((start, end));
+            }
+            s.consec_blanks = 0;
+            if !self.pending.is_empty() {
+                self.state = State::Escaped(s);
+                return;
+            }
+        }
+
+        // If all went perfectly, we should have one consecutive blank at this
+        // point since the space between the last delim and the end will show
+        // up as a blank.
+        //
+        // If we have 0, then there was no delim at the end, which is an error.
+        //
+        // If more than 1, then the last word ended with one or more escaped
+        // delimiters. Here, blanks = number of literal delimiters, whereas in
+        // the loop, this corresponded to blanks + 1 delimiters. All of these
+        // are kept with the last word.
+        if s.consec_blanks == 0 {
+            self.push_issue(TextTokenIssue::MissingFinalDelim);
+        } else if s.consec_blanks > 1 {
+            self.push_issue(TextTokenIssue::DelimAtBoundary);
+            if let Some((ws, _)) = s.word {
+                s.word = Some((ws, self.bytes.len()));
+            }
+            if s.consec_blanks & 1 == 1 {
+                self.push_issue(TextTokenIssue::MissingFinalDelim);
+            }
+        }
+
+        match (s.key, s.word) {
+            (Some(key), Some((ws, we))) => {
+                let value = self.word(ws, we);
+                self.push_pair(key, value);
+            }
+            _ => self.push_issue(TextTokenIssue::Uneven),
+        }
+    }
+
+    fn step_literal(&mut self, mut s: LiteralState) {
+        let kseg = self.next_segment();
+        if let Some((_, key)) = kseg {
+            s.prev_was_key = true;
+            s.prev_was_blank = key.is_empty();
+        }
+        // If everything is correct, we should run out of segments after the
+        // blank slice after the final delim. Otherwise we found a key without a
+        // value and also didn't end with a delim.
+        let vseg = kseg.and_then(|_| self.next_segment());
+        if let (Some((koff, key)), Some((voff, value))) = (kseg, vseg) {
+            s.prev_was_key = false;
+            s.prev_was_blank = value.is_empty();
+            let k = self.word(koff, koff + key.len());
+            if key.is_empty() {
+                self.push_issue(TextTokenIssue::BlankKey);
+            } else if value.is_empty() {
+                self.push_issue(TextTokenIssue::BlankValue(k));
+            } else {
+                let v = self.word(voff, voff + value.len());
+                self.push_pair(k, v);
+            }
+            self.state = State::Literal(s);
+            return;
+        }
+
+        if !s.prev_was_key {
+            self.push_issue(TextTokenIssue::Uneven);
+        }
+
+        if !s.prev_was_blank {
+            self.push_issue(TextTokenIssue::MissingFinalDelim);
+        }
+    }
+}
+
+impl<'a> Iterator for TextTokenizer<'a> {
+    type Item = TextToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !matches!(self.state, State::Done) {
+            self.step();
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Collapse each escaped (doubled) delimiter into one.
+pub fn unescape(delim: u8, word: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(word.len());
+    let mut it = word.iter().peekable();
+    while let Some(x) = it.next() {
+        out.push(*x);
+        if *x == delim {
+            it.next_if_eq(&&delim);
+        }
+    }
+    out
+}
+
+/// Escape each delimiter by doubling it.
+pub fn escape(delim: u8, word: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(word.len());
+    for x in word {
+        out.push(*x);
+        if *x == delim {
+            out.push(delim);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(it: TextTokenizer<'_>) -> Vec<TextTokenIssue<'_>> {
+        it.filter_map(|t| match t {
+            TextToken::Issue(i) => Some(i),
+            TextToken::Pair(_) => None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_tokenize_escaped() {
+        let bytes = "/$P4F/700//75 BP/$PROJ/x/".as_bytes();
+        let mut it = TextTokenizer::from_text(bytes).unwrap().pairs();
+        let p0 = it.next().unwrap();
+        assert_eq!((1, &b"$P4F"[..]), (p0.key.offset, p0.key.raw));
+        assert_eq!((6, &b"700//75 BP"[..]), (p0.value.offset, p0.value.raw));
+        assert_eq!(&b"700/75 BP"[..], &p0.value.unescape(b'/')[..]);
+        assert!(matches!(p0.key.unescape(b'/'), Cow::Borrowed(_)));
+        let p1 = it.next().unwrap();
+        assert_eq!(&b"x"[..], p1.value.raw);
+        assert_eq!(None, it.next());
+        assert!(issues(TextTokenizer::from_text(bytes).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_odd() {
+        let mut it = TextTokenizer::new(b"A/1/B", b'/');
+        assert!(matches!(it.next(), Some(TextToken::Pair(_))));
+        assert_eq!(
+            Some(TextToken::Issue(TextTokenIssue::MissingFinalDelim)),
+            it.next()
+        );
+        assert_eq!(Some(TextToken::Issue(TextTokenIssue::Uneven)), it.next());
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_tokenize_boundary() {
+        // the run of three delimiters is a boundary and is dropped, and the
+        // trailing escaped delimiter is kept with the last value
+        let bytes = b"A/1///B/2///";
+        let ps: Vec<_> = TextTokenizer::new(bytes, b'/')
+            .pairs()
+            .map(|p| (p.key.unescape(b'/'), p.value.unescape(b'/')))
+            .collect();
+        assert_eq!(
+            vec![
+                (Cow::from(&b"A"[..]), Cow::from(&b"1"[..])),
+                (Cow::from(&b"B"[..]), Cow::from(&b"2//"[..]))
+            ],
+            ps
+        );
+        assert_eq!(
+            vec![
+                TextTokenIssue::DelimAtBoundary,
+                TextTokenIssue::DelimAtBoundary,
+                TextTokenIssue::MissingFinalDelim
+            ],
+            issues(TextTokenizer::new(bytes, b'/'))
+        );
+    }
+
+    #[test]
+    fn test_tokenize_literal() {
+        let bytes = b"A/1//x/B//C/2/";
+        let mut it = TextTokenizer::new_literal(bytes, b'/');
+        let Some(TextToken::Pair(p)) = it.next() else {
+            panic!("expected pair");
+        };
+        assert_eq!((&b"A"[..], &b"1"[..]), (p.key.raw, p.value.raw));
+        assert_eq!(Some(TextToken::Issue(TextTokenIssue::BlankKey)), it.next());
+        let Some(TextToken::Issue(TextTokenIssue::BlankValue(k))) = it.next() else {
+            panic!("expected blank value");
+        };
+        assert_eq!((7, &b"B"[..]), (k.offset, k.raw));
+        assert!(matches!(it.next(), Some(TextToken::Pair(_))));
+        assert_eq!(None, it.next());
+    }
+
+    #[test]
+    fn test_escape_roundtrip() {
+        let w = b"a/b//c";
+        assert_eq!(&w[..], &unescape(b'/', &escape(b'/', w))[..]);
+        assert!(TextTokenizer::from_text(b"").is_none());
+        assert!(
+            TextWord {
+                offset: 0,
+                raw: b"//x"
+            }
+            .on_boundary(b'/')
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Units for keywords whose values are physical quantities.
+//!
+//! The standard specifies the unit for some of these ($PnV is in volts, $VOL
+//! is in nanoliters), but some vendors append the unit anyways, or use a
+//! different one. $FLOWRATE is free text but usually has the form "<number>
+//! <volume>/<time>".
+//!
+//! Pressure and temperature are not part of the standard but are commonly
+//! recorded by instruments in nonstandard keywords.
+
+use std::fmt;
+use std::num::ParseFloatError;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// A unit given as its SI prefix, its base unit, and a conversion factor.
+///
+/// The base is matched case-insensitively but the prefix is not, since (for
+/// example) "m" and "M" are milli and mega.
+pub(crate) type Unit = (&'static str, &'static str, f32);
+
+/// Units which may follow the value of $PnV, as multiples of volts
+///
+/// Note prefixed units must come before unprefixed units with the same base
+/// since units are matched by suffix.
+pub(crate) const VOLTAGE_UNITS: [Unit; 3] = [("m", "V", 1e-3), ("k", "V", 1e3), ("", "V", 1.0)];
+
+/// Units which may follow the value of $VOL, as multiples of nanoliters
+pub(crate) const VOLUME_UNITS: [Unit; 5] = [
+    ("n", "L", 1.0),
+    ("u", "L", 1e3),
+    ("µ", "L", 1e3),
+    ("μ", "L", 1e3),
+    ("m", "L", 1e6),
+];
+
+/// Units which may follow a pressure, as multiples of psi.
+pub(crate) const PRESSURE_UNITS: [Unit; 5] = [
+    ("", "psi", 1.0),
+    ("k", "Pa", 0.145_038),
+    ("m", "bar", 0.014_503_8),
+    ("", "bar", 14.503_8),
+    ("", "atm", 14.695_9),
+];
+
+/// Parse a float which may have a unit suffix.
+///
+/// Units may be separated from the number by whitespace. The number will be
+/// multiplied by the factor for the matched unit, or returned as-is if no unit
+/// is present. See [`Unit`] for how units are matched.
+pub(crate) fn parse_with_units(s: &str, units: &[Unit]) -> Result<f32, ParseFloatError> {
+    let t = s.trim();
+    units
+        .iter()
+        .find_map(|u| strip_unit(t, u).map(|x| (x, u.2)))
+        .map_or_else(
+            || t.parse(),
+            |(x, f)| x.trim_end().parse().map(|y: f32| y * f),
+        )
+}
+
+fn strip_unit<'a>(s: &'a str, (prefix, base, _): &Unit) -> Option<&'a str> {
+    strip_suffix_ignore_case(s, base).and_then(|x| x.strip_suffix(prefix))
+}
+
+/// Parse a temperature which may have a unit suffix, returning Celsius.
+///
+/// Fahrenheit and Kelvin ("F" and "K") will be converted, and Celsius may be
+/// written as "C", "°C", or "degC". A bare number is assumed to be Celsius.
+pub(crate) fn parse_temperature(s: &str) -> Result<f32, ParseFloatError> {
+    let t = s.trim();
+    let strip = |us: &[&str]| {
+        us.iter()
+            .find_map(|u| strip_suffix_ignore_case(t, u))
+            .map(|x| x.trim_end().parse::<f32>())
+    };
+    if let Some(x) = strip(&["°F", "degF", "F"]) {
+        x.map(|y| (y - 32.0) * 5.0 / 9.0)
+    } else if let Some(x) = strip(&["K"]) {
+        x.map(|y| y - 273.15)
+    } else if let Some(x) = strip(&["°C", "degC", "C"]) {
+        x
+    } else {
+        t.parse()
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let n = s.len().checked_sub(suffix.len())?;
+    if s.is_char_boundary(n) && s[n..].to_lowercase() == suffix.to_lowercase() {
+        Some(&s[..n])
+    } else {
+        None
+    }
+}
+
+/// A unit of volume
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum VolumeUnit {
+    Nanoliter,
+    Microliter,
+    Milliliter,
+}
+
+impl VolumeUnit {
+    /// Number of nanoliters in this unit
+    pub fn nanoliters(self) -> f32 {
+        match self {
+            Self::Nanoliter => 1.0,
+            Self::Microliter => 1e3,
+            Self::Milliliter => 1e6,
+        }
+    }
+}
+
+impl fmt::Display for VolumeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = match self {
+            Self::Nanoliter => "nL",
+            Self::Microliter => "uL",
+            Self::Milliliter => "mL",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A unit of time
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+}
+
+impl TimeUnit {
+    /// Number of seconds in this unit
+    pub fn seconds(self) -> f32 {
+        match self {
+            Self::Second => 1.0,
+            Self::Minute => 60.0,
+            Self::Hour => 3600.0,
+        }
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let s = match self {
+            Self::Second => "s",
+            Self::Minute => "min",
+            Self::Hour => "h",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A volumetric flow rate, as might be given by $FLOWRATE
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct VolumeRate {
+    nl_per_s: f32,
+}
+
+impl VolumeRate {
+    pub fn new(x: f32, v: VolumeUnit, t: TimeUnit) -> Self {
+        Self {
+            nl_per_s: x * v.nanoliters() / t.seconds(),
+        }
+    }
+
+    /// Return this rate in the given units.
+    pub fn to_units(self, v: VolumeUnit, t: TimeUnit) -> f32 {
+        self.nl_per_s * t.seconds() / v.nanoliters()
+    }
+
+    pub fn nl_per_s(self) -> f32 {
+        self.nl_per_s
+    }
+
+    pub fn ul_per_min(self) -> f32 {
+        self.to_units(VolumeUnit::Microliter, TimeUnit::Minute)
+    }
+}
+
+impl fmt::Display for VolumeRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} uL/min", self.ul_per_min())
+    }
+}
+
+/// Parse a rate like "60 uL/min" or "1nL/s".
+///
+/// A unit is required since there is no standard default.
+impl FromStr for VolumeRate {
+    type Err = VolumeRateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num, denom) = s.split_once('/').ok_or(VolumeRateError::NoUnit)?;
+        let t = denom.trim();
+        let time = [
+            ("s", TimeUnit::Second),
+            ("sec", TimeUnit::Second),
+            ("min", TimeUnit::Minute),
+            ("h", TimeUnit::Hour),
+            ("hr", TimeUnit::Hour),
+        ]
+        .into_iter()
+        .find(|(u, _)| u.eq_ignore_ascii_case(t))
+        .map(|(_, u)| u)
+        .ok_or_else(|| VolumeRateError::TimeUnit(t.to_string()))?;
+        let n = num.trim();
+        if !VOLUME_UNITS.iter().any(|u| strip_unit(n, u).is_some()) {
+            return Err(VolumeRateError::NoUnit);
+        }
+        let nl = parse_with_units(n, &VOLUME_UNITS).map_err(VolumeRateError::Float)?;
+        Ok(Self::new(nl, VolumeUnit::Nanoliter, time))
+    }
+}
+
+pub enum VolumeRateError {
+    NoUnit,
+    TimeUnit(String),
+    Float(ParseFloatError),
+}
+
+impl fmt::Display for VolumeRateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::NoUnit => f.write_str(
+                "flow rate must be like '<number> <volume unit>/<time unit>' \
+                 where volume unit is one of nL, uL, or mL",
+            ),
+            Self::TimeUnit(u) => {
+                write!(f, "time unit must be one of s, min, or h, got '{u}'")
+            }
+            Self::Float(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_units() {
+        let p = |s| parse_with_units(s, &VOLTAGE_UNITS).ok();
+        assert_eq!(p("500"), Some(500.0));
+        assert_eq!(p("500V"), Some(500.0));
+        assert_eq!(p(" 500 v "), Some(500.0));
+        assert_eq!(p("1.5 kV"), Some(1500.0));
+        assert_eq!(p("250mV"), Some(0.25));
+        assert_eq!(p("V"), None);
+        assert_eq!(p("500 W"), None);
+        let q = |s| parse_with_units(s, &VOLUME_UNITS).ok();
+        assert_eq!(q("20"), Some(20.0));
+        assert_eq!(q("20 µL"), Some(20_000.0));
+        assert_eq!(q("0.5ml"), Some(500_000.0));
+    }
+
+    #[test]
+    fn test_parse_with_units_prefix_case() {
+        let p = |s| parse_with_units(s, &VOLTAGE_UNITS).ok();
+        // the base unit may be any case but the prefix may not
+        assert_eq!(p("250mv"), Some(0.25));
+        assert_eq!(p("250 MV"), None);
+        assert_eq!(p("1.5 KV"), None);
+        let q = |s| parse_with_units(s, &VOLUME_UNITS).ok();
+        assert_eq!(q("2 ML"), None);
+        assert_eq!(q("2 Nl"), None);
+        let r = |s| parse_with_units(s, &PRESSURE_UNITS).ok();
+        assert_eq!(r("1000 MBAR"), None);
+        assert_eq!(r("1 BAR"), Some(14.503_8));
+    }
+
+    #[test]
+    fn test_parse_pressure_and_temperature() {
+        let p = |s| parse_with_units(s, &PRESSURE_UNITS).ok();
+        assert_eq!(p("70 psi"), Some(70.0));
+        assert_eq!(p("1 bar"), Some(14.503_8));
+        assert_eq!(p("1000mbar"), Some(14.503_8));
+        let t = |s| parse_temperature(s).ok();
+        assert_eq!(t("21.5"), Some(21.5));
+        assert_eq!(t("21.5 °C"), Some(21.5));
+        assert_eq!(t("212F"), Some(100.0));
+        assert_eq!(t("273.15 K"), Some(0.0));
+        assert_eq!(t("warm"), None);
+    }
+
+    #[test]
+    fn test_volume_rate() {
+        let r = "60 uL/min".parse::<VolumeRate>().ok().unwrap();
+        assert_eq!(r.nl_per_s(), 1000.0);
+        assert_eq!(r.ul_per_min(), 60.0);
+        let s = "1nl/s".parse::<VolumeRate>().ok().unwrap();
+        assert_eq!(s.to_units(VolumeUnit::Nanoliter, TimeUnit::Hour), 3600.0);
+        assert!("60".parse::<VolumeRate>().is_err());
+        assert!("60/min".parse::<VolumeRate>().is_err());
+        assert!("60 uL/fortnight".parse::<VolumeRate>().is_err());
+        assert!("high".parse::<VolumeRate>().is_err());
+    }
+}
@@ -57,6 +57,19 @@ impl UnstainedCenters {
         &self.0
     }
 
+    /// Remove the center for a measurement if present.
+    ///
+    /// Return true if this would be empty, in which case it should be removed
+    /// entirely and this will not be changed.
+    pub(crate) fn remove_by_name(&mut self, n: &Shortname) -> bool {
+        if self.0.len() == 1 && self.0.contains_key(n) {
+            true
+        } else {
+            let _ = self.0.remove(n);
+            false
+        }
+    }
+
     pub(crate) fn names_difference(
         &self,
         names: &HashSet<&Shortname>,
@@ -100,6 +100,12 @@ impl AsciiRange {
             })
     }
 
+    /// Make new AsciiRange wide enough to hold both `value` and `other`.
+    pub(crate) fn new_covering(value: u64, other: u64) -> Self {
+        let chars = Chars::from_u64(value.max(other));
+        Self { value, chars }
+    }
+
     pub(crate) fn chars(&self) -> Chars {
         self.chars
     }
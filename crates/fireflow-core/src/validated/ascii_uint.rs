@@ -97,10 +97,12 @@ impl CheckedSub for UintSpacePad20 {
 impl UintSpacePad20 {
     /// Parse from a buffer that contains up to 20 bytes.
     ///
-    /// Will panic if parsed digit is more than 20 digits long.
+    /// Will return error if parsed digit is more than 20 digits long.
     pub(crate) fn from_bytes(bs: &[u8], allow_negative: bool) -> Result<Self, ParseFixedUintError> {
         if bs.len() > 20 {
-            panic!("cannot parse more than 20 bytes")
+            return Err(ParseFixedUintError::TooLong(FixedUintTooLongError(
+                bs.len(),
+            )));
         }
         let x = ascii_str_from_bytes(bs)?.trim_start().parse::<i32>()?;
         if x < 0 {
@@ -206,6 +208,7 @@ pub enum ParseFixedUintError {
     Int(ParseIntError),
     NotAscii(BytesNotAscii),
     Negative(NegativeOffsetError),
+    TooLong(FixedUintTooLongError),
 }
 
 impl TryFrom<u64> for UintSpacePad8 {
@@ -265,6 +268,18 @@ impl fmt::Display for BytesNotAscii {
 
 pub struct NegativeOffsetError(pub i32);
 
+pub struct FixedUintTooLongError(pub usize);
+
+impl fmt::Display for FixedUintTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "cannot parse more than 20 bytes as integer, got {}",
+            self.0
+        )
+    }
+}
+
 impl fmt::Display for NegativeOffsetError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "HEADER offset is negative: {}", self.0)
@@ -289,6 +304,12 @@ mod tests {
         assert!("99999999".parse::<UintSpacePad8>().is_ok());
         assert!("100000000".parse::<UintSpacePad8>().is_err());
     }
+
+    #[test]
+    fn test_uint20_from_bytes() {
+        assert!(UintSpacePad20::from_bytes(b"                   1", false).is_ok());
+        assert!(UintSpacePad20::from_bytes(b"                    1", false).is_err());
+    }
 }
 
 #[cfg(feature = "python")]
@@ -1,5 +1,6 @@
+use crate::histogram::Histogram;
 use crate::macros::match_many_to_one;
-use crate::text::index::BoundaryIndexError;
+use crate::text::index::{permute, BoundaryIndexError, MeasIndex};
 use crate::validated::ascii_range::Chars;
 
 use derive_more::{Display, From};
@@ -9,6 +10,9 @@ use polars_arrow::datatypes::ArrowDataType;
 use std::any::type_name;
 use std::fmt;
 use std::iter;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::slice::Iter;
 
 #[cfg(feature = "python")]
@@ -38,6 +42,20 @@ pub enum AnyFCSColumn {
 #[derive(Clone, PartialEq)]
 pub struct FCSColumn<T>(pub Buffer<T>);
 
+/// Iterator over one column, converting each value to a float on the fly.
+#[derive(Clone)]
+pub struct ColumnIter<'a> {
+    column: Option<&'a AnyFCSColumn>,
+    rows: Range<usize>,
+}
+
+/// Iterator over the rows of a dataframe.
+#[derive(Clone)]
+pub struct RowIter<'a> {
+    df: &'a FCSDataFrame,
+    rows: Range<usize>,
+}
+
 pub type U08Column = FCSColumn<u8>;
 pub type U16Column = FCSColumn<u16>;
 pub type U32Column = FCSColumn<u32>;
@@ -109,12 +127,88 @@ impl PartialEq for AnyFCSColumn {
     }
 }
 
+impl<T> FCSColumn<T> {
+    /// Return a new empty column of the same type.
+    fn empty_like(&self) -> Self {
+        Self(Vec::new().into())
+    }
+
+    /// Return a new column with only the rows in `rows`.
+    fn slice(&self, rows: Range<usize>) -> Self
+    where
+        T: Clone,
+    {
+        Self(self.0.clone().sliced(rows.start, rows.len()))
+    }
+
+    /// Return a new column with only the rows at `rows` (in the given order).
+    fn take(&self, rows: &[usize]) -> Self
+    where
+        T: Copy,
+    {
+        rows.iter().map(|&i| self.0[i]).collect::<Vec<_>>().into()
+    }
+}
+
+impl<T: Copy> FCSColumn<T> {
+    /// Add the rows of `other` after those in this one.
+    ///
+    /// This will only copy `other` unless this column shares its buffer with
+    /// another column, in which case this column will be copied first.
+    fn append(&mut self, other: &Self) {
+        let mut xs = mem::take(&mut self.0).make_mut();
+        xs.extend_from_slice(&other.0);
+        self.0 = xs.into();
+    }
+}
+
 impl<T> From<Vec<T>> for FCSColumn<T> {
     fn from(value: Vec<T>) -> Self {
         FCSColumn(value.into())
     }
 }
 
+impl Iterator for ColumnIter<'_> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = self.column?;
+        self.rows.next().map(|i| c.get_as(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ColumnIter<'_> {}
+
+impl ColumnIter<'_> {
+    /// Return iterator which yields nothing.
+    pub(crate) fn empty() -> Self {
+        Self {
+            column: None,
+            rows: 0..0,
+        }
+    }
+}
+
+impl Iterator for RowIter<'_> {
+    type Item = Vec<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows
+            .next()
+            .map(|i| self.df.columns.iter().map(|c| c.get_as(i)).collect())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl ExactSizeIterator for RowIter<'_> {}
+
 impl AnyFCSColumn {
     pub fn len(&self) -> usize {
         match_many_to_one!(self, AnyFCSColumn, [U08, U16, U32, U64, F32, F64], x, {
@@ -136,6 +230,145 @@ impl AnyFCSColumn {
         self.len() == 0
     }
 
+    /// Return a new empty column of the same type.
+    pub fn empty_like(&self) -> Self {
+        match_many_to_one!(self, Self, [U08, U16, U32, U64, F32, F64], x, {
+            x.empty_like().into()
+        })
+    }
+
+    /// Return a new column with only the rows in `rows`.
+    ///
+    /// Panics if `rows` is out of bounds.
+    pub fn slice(&self, rows: Range<usize>) -> Self {
+        match_many_to_one!(self, Self, [U08, U16, U32, U64, F32, F64], x, {
+            x.slice(rows).into()
+        })
+    }
+
+    /// Return a new column with only the rows at `rows` (in the given order).
+    ///
+    /// Panics if any index is out of bounds.
+    pub fn take(&self, rows: &[usize]) -> Self {
+        match_many_to_one!(self, Self, [U08, U16, U32, U64, F32, F64], x, {
+            x.take(rows).into()
+        })
+    }
+
+    /// Return the name of the type of this column.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::U08(_) => "u8",
+            Self::U16(_) => "u16",
+            Self::U32(_) => "u32",
+            Self::U64(_) => "u64",
+            Self::F32(_) => "f32",
+            Self::F64(_) => "f64",
+        }
+    }
+
+    /// Add the rows of `other` after those in this column.
+    ///
+    /// Return false and do nothing if the columns are not the same type.
+    pub fn append(&mut self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::U08(xs), Self::U08(ys)) => xs.append(ys),
+            (Self::U16(xs), Self::U16(ys)) => xs.append(ys),
+            (Self::U32(xs), Self::U32(ys)) => xs.append(ys),
+            (Self::U64(xs), Self::U64(ys)) => xs.append(ys),
+            (Self::F32(xs), Self::F32(ys)) => xs.append(ys),
+            (Self::F64(xs), Self::F64(ys)) => xs.append(ys),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Return true if this column stores floats.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::F32(_) | Self::F64(_))
+    }
+
+    /// Return a new column with `f` applied to each value if this stores
+    /// floats, or a copy of this column otherwise.
+    pub(crate) fn map_floats<F: Fn(f64) -> f64>(&self, f: F) -> Self {
+        match self {
+            // ASSUME rounding an f32 via f64 is exact
+            Self::F32(xs) => FCSColumn::from(
+                xs.0.iter()
+                    .map(|&x| f(f64::from(x)) as f32)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Self::F64(xs) => FCSColumn::from(xs.0.iter().map(|&x| f(x)).collect::<Vec<_>>()).into(),
+            _ => self.clone(),
+        }
+    }
+
+    /// Return value at index converted to `T`.
+    ///
+    /// Values which cannot be exactly represented as `T` are truncated as they
+    /// would be when writing. Panics if `i` is out of bounds.
+    pub(crate) fn get_as<T: AllFCSCast>(&self, i: usize) -> T {
+        match_many_to_one!(self, AnyFCSColumn, [U08, U16, U32, U64, F32, F64], x, {
+            T::from_truncated(x.0[i]).new
+        })
+    }
+
+    /// Return iterator over this column, converting each value to a float.
+    pub fn iter_f64(&self) -> ColumnIter<'_> {
+        ColumnIter {
+            column: Some(self),
+            rows: 0..self.len(),
+        }
+    }
+
+    /// Return all values in this column as floats.
+    pub(crate) fn to_f64s(&self) -> Vec<f64> {
+        match self {
+            Self::U08(xs) => u8::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+            Self::U16(xs) => u16::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+            Self::U32(xs) => u32::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+            Self::U64(xs) => u64::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+            Self::F32(xs) => f32::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+            Self::F64(xs) => f64::as_col_iter::<f64>(xs).map(|x| x.new).collect(),
+        }
+    }
+
+    /// Return row ranges which split this column into windows of equal width.
+    ///
+    /// Each value will be multiplied by `scale` before comparing it to
+    /// `window`. A new range will be started when a value is at least `window`
+    /// greater than the first value in the current range, or when a value is
+    /// less than the one before it (as would happen if a counter rolled over).
+    pub fn window_bounds(&self, window: f64, scale: f64) -> Vec<Range<usize>> {
+        let xs = self.to_f64s();
+        let mut ret = vec![];
+        let mut start = 0;
+        for i in 1..xs.len() {
+            let x = xs[i] * scale;
+            if x < xs[i - 1] * scale || x - xs[start] * scale >= window {
+                ret.push(start..i);
+                start = i;
+            }
+        }
+        if !xs.is_empty() {
+            ret.push(start..xs.len());
+        }
+        ret
+    }
+
+    /// Compute a histogram of this column after applying `f` to each value.
+    // the cast is a noop for the f64 arm but needed for all others
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn histogram<F>(&self, bins: NonZeroUsize, f: F) -> Histogram
+    where
+        F: Fn(f64) -> f64,
+    {
+        match_many_to_one!(self, AnyFCSColumn, [U08, U16, U32, U64, F32, F64], x, {
+            Histogram::from_values(x.0.iter().map(|&y| f(y as f64)), bins)
+        })
+    }
+
     /// Convert number at index to string
     pub fn pos_to_string(&self, i: usize) -> String {
         match_many_to_one!(self, AnyFCSColumn, [U08, U16, U32, U64, F32, F64], x, {
@@ -155,6 +388,19 @@ impl AnyFCSColumn {
         }
     }
 
+    /// The largest value in the column if written as ASCII (0 if empty)
+    pub fn ascii_max(&self) -> u64 {
+        match self {
+            Self::U08(xs) => u8::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+            Self::U16(xs) => u16::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+            Self::U32(xs) => u32::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+            Self::U64(xs) => u64::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+            Self::F32(xs) => f32::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+            Self::F64(xs) => f64::as_col_iter::<u64>(xs).map(|x| x.new).max(),
+        }
+        .unwrap_or(0)
+    }
+
     pub fn as_array(&self) -> Box<dyn Array> {
         match self.clone() {
             Self::U08(xs) => Box::new(PrimitiveArray::new(ArrowDataType::UInt8, xs.0, None)),
@@ -190,6 +436,21 @@ pub struct ColumnLengthError {
     col_len: usize,
 }
 
+/// Error when appending rows from one dataframe to another.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum AppendRowsError {
+    /// Dataframes do not have the same number of columns
+    ColumnCount { this: usize, other: usize },
+
+    /// Column at the given index is not the same type in both dataframes
+    ColumnType {
+        index: usize,
+        this: &'static str,
+        other: &'static str,
+    },
+}
+
 #[derive(From, Display)]
 pub enum InsertColumnError {
     Index(BoundaryIndexError),
@@ -206,6 +467,23 @@ impl fmt::Display for ColumnLengthError {
     }
 }
 
+impl fmt::Display for AppendRowsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::ColumnCount { this, other } => write!(
+                f,
+                "cannot append rows from dataframe with {other} columns \
+                 to dataframe with {this} columns"
+            ),
+            Self::ColumnType { index, this, other } => write!(
+                f,
+                "cannot append rows to column {index}: type is {this} \
+                 but appended type is {other}"
+            ),
+        }
+    }
+}
+
 impl FCSDataFrame {
     pub fn try_new(columns: Vec<AnyFCSColumn>) -> Result<Self, NewDataframeError> {
         if let Some(nrows) = columns.first().map(|c| c.len()) {
@@ -219,6 +497,14 @@ impl FCSDataFrame {
         }
     }
 
+    /// Make a dataframe from columns which all have `nrows` rows.
+    ///
+    /// This is for columns converted element-wise from another dataframe,
+    /// whose lengths therefore cannot differ.
+    pub(crate) fn new_nocheck(columns: Vec<AnyFCSColumn>, nrows: usize) -> Self {
+        Self { columns, nrows }
+    }
+
     pub fn new1(column: AnyFCSColumn) -> Self {
         Self {
             nrows: column.len(),
@@ -231,10 +517,112 @@ impl FCSDataFrame {
         self.nrows = 0;
     }
 
+    /// Remove all rows but keep all columns (and their types).
+    pub fn clear_rows(&mut self) {
+        for c in &mut self.columns {
+            *c = c.empty_like();
+        }
+        self.nrows = 0;
+    }
+
+    /// Return a new dataframe with only the rows in `rows`.
+    ///
+    /// Panics if `rows` is out of bounds.
+    pub fn slice_rows(&self, rows: Range<usize>) -> Self {
+        assert!(rows.end <= self.nrows(), "row range out of bounds");
+        Self {
+            nrows: rows.len(),
+            columns: self.columns.iter().map(|c| c.slice(rows.clone())).collect(),
+        }
+    }
+
+    /// Return a new dataframe with only the rows at `rows` (in the given order).
+    ///
+    /// Panics if any index is out of bounds.
+    pub fn take_rows(&self, rows: &[usize]) -> Self {
+        Self {
+            nrows: rows.len(),
+            columns: self.columns.iter().map(|c| c.take(rows)).collect(),
+        }
+    }
+
+    /// Add the rows of `other` after the rows in this dataframe.
+    ///
+    /// Both must have the same number of columns and each column must have
+    /// the same type as its counterpart. If this dataframe has no columns, it
+    /// will become a copy of `other`. If `other` has no columns, nothing will
+    /// be added. Nothing will be changed on error.
+    ///
+    /// Columns are extended in place, so appending many dataframes one after
+    /// another only copies each once (unless the columns in this dataframe are
+    /// shared with another, in which case they will be copied the first time).
+    ///
+    /// Return the range of the rows which were added.
+    pub fn append_rows(&mut self, other: &Self) -> Result<Range<usize>, AppendRowsError> {
+        let start = self.nrows();
+        if self.is_empty() {
+            *self = other.clone();
+            return Ok(0..self.nrows());
+        }
+        if other.is_empty() {
+            return Ok(start..start);
+        }
+        if self.ncols() != other.ncols() {
+            return Err(AppendRowsError::ColumnCount {
+                this: self.ncols(),
+                other: other.ncols(),
+            });
+        }
+        // check all types before changing anything
+        if let Some((index, (x, y))) = self
+            .columns
+            .iter()
+            .zip(other.columns.iter())
+            .enumerate()
+            .find(|(_, (x, y))| x.type_name() != y.type_name())
+        {
+            return Err(AppendRowsError::ColumnType {
+                index,
+                this: x.type_name(),
+                other: y.type_name(),
+            });
+        }
+        for (x, y) in self.columns.iter_mut().zip(other.columns.iter()) {
+            x.append(y);
+        }
+        self.nrows += other.nrows();
+        Ok(start..self.nrows)
+    }
+
+    /// Stack the rows of each dataframe in order.
+    ///
+    /// All must have the same column types as with [`Self::append_rows`].
+    /// Return the new dataframe and the range of rows which came from each
+    /// input.
+    pub fn concat_rows(dfs: &[Self]) -> Result<(Self, Vec<Range<usize>>), AppendRowsError> {
+        let mut ret = Self::default();
+        let rs = dfs
+            .iter()
+            .map(|df| ret.append_rows(df))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((ret, rs))
+    }
+
     pub fn iter_columns(&self) -> Iter<'_, AnyFCSColumn> {
         self.columns.iter()
     }
 
+    /// Return iterator over each row, with values converted to `f64`.
+    ///
+    /// Each row is built from the columns as it is requested, so only one row
+    /// is allocated at a time.
+    pub fn iter_rows(&self) -> RowIter<'_> {
+        RowIter {
+            df: self,
+            rows: 0..self.nrows(),
+        }
+    }
+
     pub fn nrows(&self) -> usize {
         if self.is_empty() {
             0
@@ -278,6 +666,13 @@ impl FCSDataFrame {
         }
     }
 
+    /// Reorder columns such that the ith column is currently at `order[i]`.
+    ///
+    /// `order` must be a permutation of all column indices.
+    pub(crate) fn reorder_columns_nocheck(&mut self, order: &[MeasIndex]) {
+        self.columns = permute(mem::take(&mut self.columns), order);
+    }
+
     // will panic if index is out of bounds
     pub(crate) fn insert_column_nocheck(
         &mut self,
@@ -599,6 +994,135 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_iter_rows_and_columns() {
+        let df = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U16(vec![1_u16, 300].into()),
+            AnyFCSColumn::F32(vec![0.5_f32, 2.0].into()),
+        ])
+        .unwrap();
+        let rows: Vec<_> = df.iter_rows().collect();
+        assert_eq!(vec![vec![1.0, 0.5], vec![300.0, 2.0]], rows);
+        let cs = df.iter_columns().collect::<Vec<_>>();
+        assert_eq!(vec![1.0, 300.0], cs[0].iter_f64().collect::<Vec<_>>());
+        assert_eq!(vec![0.5, 2.0], cs[1].iter_f64().collect::<Vec<_>>());
+        assert_eq!(2, cs[1].iter_f64().len());
+    }
+
+    #[test]
+    fn test_window_bounds() {
+        let c = AnyFCSColumn::U32(vec![0_u32, 1, 5, 9, 10, 25, 3, 4].into());
+        assert_eq!(vec![0..4, 4..5, 5..6, 6..8], c.window_bounds(1.0, 0.1));
+        assert!(AnyFCSColumn::U32(vec![].into())
+            .window_bounds(1.0, 1.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_clear_rows() {
+        let mut df = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U08(vec![1_u8, 2].into()),
+            AnyFCSColumn::F64(vec![1.0_f64, 2.0].into()),
+        ])
+        .ok()
+        .unwrap();
+        df.clear_rows();
+        assert_eq!(df.ncols(), 2);
+        assert_eq!(df.nrows(), 0);
+        assert!(matches!(
+            df.iter_columns().nth(1),
+            Some(AnyFCSColumn::F64(_))
+        ));
+    }
+
+    #[test]
+    fn test_slice_rows() {
+        let df = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U08(vec![1_u8, 2, 3, 4].into()),
+            AnyFCSColumn::F64(vec![1.0_f64, 2.0, 3.0, 4.0].into()),
+        ])
+        .ok()
+        .unwrap();
+        let s = df.slice_rows(1..3);
+        assert_eq!(s.nrows(), 2);
+        assert!(s.iter_columns().nth(1).unwrap() == &AnyFCSColumn::F64(vec![2.0_f64, 3.0].into()));
+        assert_eq!(df.slice_rows(4..4).nrows(), 0);
+    }
+
+    #[test]
+    fn test_take_rows() {
+        let df = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U16(vec![1_u16, 2, 3, 4].into()),
+            AnyFCSColumn::F32(vec![1.0_f32, 2.0, 3.0, 4.0].into()),
+        ])
+        .ok()
+        .unwrap();
+        let t = df.take_rows(&[3, 0]);
+        assert_eq!(t.nrows(), 2);
+        assert!(t.iter_columns().next().unwrap() == &AnyFCSColumn::U16(vec![4_u16, 1].into()));
+        assert_eq!(df.take_rows(&[]).nrows(), 0);
+    }
+
+    #[test]
+    fn test_append_rows() {
+        let mut df = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U08(vec![1_u8, 2].into()),
+            AnyFCSColumn::F64(vec![1.0_f64, 2.0].into()),
+        ])
+        .ok()
+        .unwrap();
+        let other = df.slice_rows(1..2);
+        assert_eq!(Ok(2..3), df.append_rows(&other));
+        assert_eq!(df.nrows(), 3);
+        assert!(df.iter_columns().next().unwrap() == &AnyFCSColumn::U08(vec![1_u8, 2, 2].into()));
+        assert_eq!(Ok(3..3), df.append_rows(&FCSDataFrame::default()));
+
+        let wrong_type = FCSDataFrame::try_new(vec![
+            AnyFCSColumn::U08(vec![1_u8].into()),
+            AnyFCSColumn::F32(vec![1.0_f32].into()),
+        ])
+        .ok()
+        .unwrap();
+        assert_eq!(
+            Err(AppendRowsError::ColumnType {
+                index: 1,
+                this: "f64",
+                other: "f32"
+            }),
+            df.append_rows(&wrong_type)
+        );
+        let wrong_count = FCSDataFrame::new1(AnyFCSColumn::U08(vec![1_u8].into()));
+        assert_eq!(
+            Err(AppendRowsError::ColumnCount { this: 2, other: 1 }),
+            df.append_rows(&wrong_count)
+        );
+        assert_eq!(df.nrows(), 3);
+    }
+
+    #[test]
+    fn test_append_rows_shared() {
+        let mut df = FCSDataFrame::new1(AnyFCSColumn::U16(vec![1_u16, 2].into()));
+        let copy = df.clone();
+        let other = FCSDataFrame::new1(AnyFCSColumn::U16(vec![3_u16].into()));
+        assert_eq!(Ok(2..3), df.append_rows(&other));
+        assert_eq!(Ok(3..4), df.append_rows(&other));
+        assert!(
+            df.iter_columns().next().unwrap() == &AnyFCSColumn::U16(vec![1_u16, 2, 3, 3].into())
+        );
+        assert!(copy.iter_columns().next().unwrap() == &AnyFCSColumn::U16(vec![1_u16, 2].into()));
+    }
+
+    #[test]
+    fn test_concat_rows() {
+        let df = FCSDataFrame::new1(AnyFCSColumn::U32(vec![1_u32, 2].into()));
+        let (out, rs) = FCSDataFrame::concat_rows(&[df.clone(), df]).unwrap();
+        assert_eq!(vec![0..2, 2..4], rs);
+        assert!(
+            out.iter_columns().next().unwrap() == &AnyFCSColumn::U32(vec![1_u32, 2, 1, 2].into())
+        );
+        assert_eq!(0, FCSDataFrame::concat_rows(&[]).unwrap().0.ncols());
+    }
+
     // only test lossy cases, assume the others will simply noop
 
     #[test]
@@ -837,10 +1361,48 @@ pub(crate) mod python {
 
     use polars::prelude::*;
     use polars_arrow::array::PrimitiveArray;
+    use polars_arrow::ffi;
     use pyo3::prelude::*;
     use pyo3_polars::{PyDataFrame, PySeries};
     use std::fmt;
 
+    impl FCSDataFrame {
+        /// Convert to a pyarrow Table using the Arrow C data interface.
+        ///
+        /// Unlike the polars conversion, this only depends on pyarrow and
+        /// therefore works regardless of which polars version is installed.
+        /// If `names` is not given, columns will be named like "X0", "X1", etc.
+        pub fn into_pyarrow<'py>(
+            self,
+            py: Python<'py>,
+            names: Option<Vec<String>>,
+        ) -> PyResult<Bound<'py, PyAny>> {
+            let pa = py.import("pyarrow")?;
+            let ns = names.unwrap_or_else(|| (0..self.ncols()).map(|i| format!("X{i}")).collect());
+            let arrays = self
+                .iter_columns()
+                .map(|c| c.as_pyarrow_array(&pa))
+                .collect::<PyResult<Vec<_>>>()?;
+            pa.getattr("Table")?
+                .call_method1("from_arrays", (arrays, ns))
+        }
+    }
+
+    impl AnyFCSColumn {
+        fn as_pyarrow_array<'py>(&self, pa: &Bound<'py, PyModule>) -> PyResult<Bound<'py, PyAny>> {
+            let xs = self.as_array();
+            let field = ArrowField::new("".into(), xs.dtype().clone(), false);
+            let schema = Box::new(ffi::export_field_to_c(&field));
+            let array = Box::new(ffi::export_array_to_c(xs));
+            let schema_ptr: *const ffi::ArrowSchema = &*schema;
+            let array_ptr: *const ffi::ArrowArray = &*array;
+            // pyarrow will move both structs and null out their release
+            // callbacks, so dropping the boxes afterward is a no-op
+            pa.getattr("Array")?
+                .call_method1("_import_from_c", (array_ptr as usize, schema_ptr as usize))
+        }
+    }
+
     impl<'py> IntoPyObject<'py> for FCSDataFrame {
         type Target = PyAny;
         type Output = Bound<'py, PyAny>;
@@ -1,6 +1,6 @@
-use crate::config::ReadHeaderAndTEXTConfig;
+use crate::config::{ControlCharPolicy, ReadHeaderAndTEXTConfig};
 use crate::error::*;
-use crate::text::index::IndexFromOne;
+use crate::text::index::{IndexFromOne, MeasIndex};
 
 use derive_more::{AsRef, Display, From};
 use itertools::Itertools;
@@ -12,7 +12,6 @@ use std::fmt;
 use std::hash::Hash;
 use std::str;
 use std::str::FromStr;
-use std::sync::OnceLock;
 use unicase::Ascii;
 
 #[cfg(feature = "serde")]
@@ -167,13 +166,8 @@ pub(crate) trait IndexedKey {
     const SUFFIX: &'static str;
 
     fn std(i: IndexFromOne) -> StdKey {
-        // reserve enough space for prefix, suffix, and a number with 3 digits
-        let n = Self::PREFIX.len() + 3 + Self::SUFFIX.len();
-        let mut s = String::with_capacity(n);
-        s.push_str(Self::PREFIX);
-        s.push_str(i.to_string().as_str());
-        s.push_str(Self::SUFFIX);
-        StdKey::new(s)
+        // index may have any number of digits (ie $P1000N)
+        StdKey::new(format!("{}{i}{}", Self::PREFIX, Self::SUFFIX))
     }
 
     fn std_blank() -> MeasHeader {
@@ -188,19 +182,10 @@ pub(crate) trait IndexedKey {
         MeasHeader(s)
     }
 
-    /// Build regexp matching "<PREFIX>n<SUFFIX>"
-    fn regexp() -> CaseInsRegex {
-        let mut s = String::new();
-        s.push_str(Self::PREFIX);
-        s.push_str("[0-9]+");
-        s.push_str(Self::SUFFIX);
-        // ASSUME this will never fail because pre/suffix should only be letters
-        CaseInsRegex::from_str(s.as_str()).unwrap()
-    }
-
+    /// Return true if key is "<PREFIX>n<SUFFIX>" (case-insensitive).
     fn matches(other: &StdKey) -> bool {
-        static RE: OnceLock<CaseInsRegex> = OnceLock::new();
-        RE.get_or_init(|| Self::regexp()).0.is_match(other.as_ref())
+        strip_index(other.as_ref(), Self::PREFIX)
+            .is_some_and(|rest| rest.eq_ignore_ascii_case(Self::SUFFIX))
     }
 }
 
@@ -213,33 +198,33 @@ pub(crate) trait BiIndexedKey {
     const SUFFIX: &'static str;
 
     fn std(i: IndexFromOne, j: IndexFromOne) -> StdKey {
-        // reserve enough space for prefix, middle, suffix, and two numbers with
-        // 2 digits
-        let n = Self::PREFIX.len() + Self::MIDDLE.len() + Self::SUFFIX.len() + 4;
-        let mut s = String::with_capacity(n);
-        s.push_str(Self::PREFIX);
-        s.push_str(i.to_string().as_str());
-        s.push_str(Self::MIDDLE);
-        s.push_str(j.to_string().as_str());
-        s.push_str(Self::SUFFIX);
-        StdKey::new(s)
+        StdKey::new(format!(
+            "{}{i}{}{j}{}",
+            Self::PREFIX,
+            Self::MIDDLE,
+            Self::SUFFIX
+        ))
     }
 
-    /// Build regexp matching "<PREFIX>m<MIDDLE>n<SUFFIX>"
-    fn regexp() -> CaseInsRegex {
-        let mut s = String::new();
-        s.push_str(Self::PREFIX);
-        s.push_str("[0-9]+");
-        s.push_str(Self::MIDDLE);
-        s.push_str("[0-9]+");
-        s.push_str(Self::SUFFIX);
-        // ASSUME this will never fail because pre/suffix should only be letters
-        CaseInsRegex::from_str(s.as_str()).unwrap()
+    /// Return true if key is "<PREFIX>m<MIDDLE>n<SUFFIX>" (case-insensitive).
+    fn matches(other: &StdKey) -> bool {
+        strip_index(other.as_ref(), Self::PREFIX)
+            .and_then(|rest| strip_index(rest, Self::MIDDLE))
+            .is_some_and(|rest| rest.eq_ignore_ascii_case(Self::SUFFIX))
     }
 
-    fn matches(other: &StdKey) -> bool {
-        static RE: OnceLock<CaseInsRegex> = OnceLock::new();
-        RE.get_or_init(|| Self::regexp()).0.is_match(other.as_ref())
+    /// Return m and n if key is "<PREFIX>m<MIDDLE>n<SUFFIX>".
+    ///
+    /// This is the inverse of [`Self::std`], so indices with leading zeros
+    /// will not match.
+    fn indices(other: &StdKey) -> Option<(IndexFromOne, IndexFromOne)> {
+        let (m, rest0) = split_index(other.as_ref(), Self::PREFIX)?;
+        let (n, rest1) = split_index(rest0, Self::MIDDLE)?;
+        if rest1.eq_ignore_ascii_case(Self::SUFFIX) {
+            Some((parse_index(m)?, parse_index(n)?))
+        } else {
+            None
+        }
     }
 
     // fn std_blank() -> String {
@@ -295,10 +280,65 @@ impl TryFrom<HashMap<KeyString, KeyString>> for KeyStringPairs {
     }
 }
 
+/// Strip a case-insensitive prefix followed by at least one digit.
+///
+/// Each key type needs its own pattern, so this cannot be done with a cached
+/// regexp in a default trait method (which would be shared among all types).
+fn strip_index<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    split_index(s, prefix).map(|(_, rest)| rest)
+}
+
+/// Split "<prefix><digits><rest>" into digits and rest.
+fn split_index<'a>(s: &'a str, prefix: &str) -> Option<(&'a str, &'a str)> {
+    let n = prefix.len();
+    if s.len() <= n || !s.as_bytes()[..n].eq_ignore_ascii_case(prefix.as_bytes()) {
+        return None;
+    }
+    let rest = &s[n..];
+    let d = rest.bytes().take_while(u8::is_ascii_digit).count();
+    (d > 0).then(|| rest.split_at(d))
+}
+
+/// Parse index from digits, which may not have leading zeros.
+fn parse_index(digits: &str) -> Option<IndexFromOne> {
+    if digits.starts_with('0') {
+        return None;
+    }
+    let i = digits.parse::<usize>().ok()?;
+    Some(IndexFromOne::from(i - 1))
+}
+
 impl StdKey {
     fn new(s: String) -> Self {
         Self(KeyString::new(s))
     }
+
+    /// Split key into index and suffix if it looks like $PnX.
+    ///
+    /// The index will be returned exactly as written, so it may be zero or
+    /// have leading zeros.
+    pub(crate) fn split_meas(&self) -> Option<(&str, &str)> {
+        let s: &str = self.as_ref();
+        let rest = s.strip_prefix(['P', 'p'])?;
+        let n = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let (digits, suffix) = rest.split_at(n);
+        if digits.is_empty()
+            || suffix.is_empty()
+            || !suffix.bytes().all(|b| b.is_ascii_alphabetic())
+        {
+            None
+        } else {
+            Some((digits, suffix))
+        }
+    }
+
+    /// Return measurement index if key looks like $PnX.
+    pub fn meas_index(&self) -> Option<MeasIndex> {
+        self.split_meas()
+            .and_then(|(digits, _)| digits.parse::<usize>().ok())
+            .and_then(|n| n.checked_sub(1))
+            .map(MeasIndex::from)
+    }
 }
 
 impl NonStdKey {
@@ -447,6 +487,60 @@ impl KeyMatcher<'_> {
     }
 }
 
+/// Progress towards finding every key in an allowlist while splitting TEXT.
+///
+/// If there is no allowlist, every key is allowed and the search is never
+/// done.
+pub(crate) struct AllowlistProgress<'a> {
+    keys: Option<Vec<&'a [u8]>>,
+    found: Vec<bool>,
+    remaining: usize,
+}
+
+impl<'a> AllowlistProgress<'a> {
+    /// Make a new search from an allowlist and the keys needed to parse TEXT.
+    ///
+    /// `required` is ignored if there is no allowlist.
+    pub(crate) fn new(allowlist: Option<&'a [KeyString]>, required: &[&'static str]) -> Self {
+        let keys: Option<Vec<&[u8]>> = allowlist.map(|xs| {
+            xs.iter()
+                .map(|x| x.as_ref().as_bytes())
+                .chain(required.iter().map(|x| x.as_bytes()))
+                .unique_by(|x| x.to_ascii_uppercase())
+                .collect()
+        });
+        let n = keys.as_ref().map_or(0, Vec::len);
+        Self {
+            keys,
+            found: vec![false; n],
+            remaining: n,
+        }
+    }
+
+    /// Return true if key (case-insensitive) should be kept.
+    ///
+    /// This also marks the key as found.
+    pub(crate) fn check(&mut self, k: &[u8]) -> bool {
+        let Some(keys) = &self.keys else {
+            return true;
+        };
+        if let Some(i) = keys.iter().position(|x| x.eq_ignore_ascii_case(k)) {
+            if !self.found[i] {
+                self.found[i] = true;
+                self.remaining -= 1;
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return true if every key in the allowlist has been found.
+    pub(crate) fn is_done(&self) -> bool {
+        self.keys.is_some() && self.remaining == 0
+    }
+}
+
 impl ParsedKeywords {
     pub(crate) fn insert(
         &mut self,
@@ -517,6 +611,37 @@ impl ParsedKeywords {
         }
     }
 
+    /// Apply a policy to control characters in all values.
+    ///
+    /// Return a warning for each value which was changed, sorted by key.
+    /// Values which become empty will be removed.
+    pub(crate) fn apply_control_chars(
+        &mut self,
+        policy: ControlCharPolicy,
+    ) -> Vec<ControlCharWarning> {
+        let mut ws = vec![];
+        let mut go = |k: String, v: &mut String| {
+            if let Some(x) = policy.apply(v) {
+                *v = x;
+                ws.push(ControlCharWarning { key: k, policy });
+            }
+        };
+        for (k, v) in self.std.iter_mut() {
+            go(k.to_string(), v);
+        }
+        for (k, v) in self.nonstd.iter_mut() {
+            go(k.to_string(), v);
+        }
+        for (k, v) in self.non_ascii.iter_mut() {
+            go(k.clone(), v);
+        }
+        self.std.retain(|_, v| !v.is_empty());
+        self.nonstd.retain(|_, v| !v.is_empty());
+        self.non_ascii.retain(|(_, v)| !v.is_empty());
+        ws.sort_by(|a, b| a.key.cmp(&b.key));
+        ws
+    }
+
     pub(crate) fn append_std(
         &mut self,
         new: &HashMap<KeyString, String>,
@@ -550,6 +675,13 @@ pub enum KeywordInsertError {
 #[derive(Debug, PartialEq)]
 pub struct BlankValueError(pub Vec<u8>);
 
+/// A value which had control characters escaped or stripped.
+#[derive(Debug, PartialEq)]
+pub struct ControlCharWarning {
+    pub key: String,
+    pub policy: ControlCharPolicy,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct KeyPresent<T> {
     pub key: T,
@@ -590,6 +722,21 @@ pub struct NonStdMeasRegexError {
 
 pub struct KeyStringPairsError(NonEmpty<KeyString>);
 
+impl fmt::Display for ControlCharWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let action = match self.policy {
+            ControlCharPolicy::Escape => "escaped",
+            ControlCharPolicy::Strip => "stripped",
+            ControlCharPolicy::Preserve => "preserved",
+        };
+        write!(
+            f,
+            "control characters in value for key '{}' were {action}",
+            self.key
+        )
+    }
+}
+
 impl<T: fmt::Display> fmt::Display for KeyPresent<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(
@@ -796,6 +943,17 @@ mod serialize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn std_key_meas_index() {
+        let go = |s: &str| s.parse::<StdKey>().unwrap().meas_index().map(usize::from);
+        assert_eq!(go("$P1B"), Some(0));
+        assert_eq!(go("$P012N"), Some(11));
+        assert_eq!(go("$P0N"), None);
+        assert_eq!(go("$PAR"), None);
+        assert_eq!(go("$P1"), None);
+        assert_eq!(go("$PLATEID"), None);
+    }
+
     #[test]
     fn fromstr_std_key() {
         let s = "$MAJESTY";
@@ -900,4 +1058,32 @@ mod tests {
         let k = s.parse::<NonStdKey>();
         assert_eq!(Err(NonStdKeyError::Ascii(AsciiStringError::Empty)), k);
     }
+
+    #[test]
+    fn matches_indexed_keys() {
+        use crate::text::keywords::{Dfc, Gain, PeakBin};
+        let k = |s: &str| s.parse::<StdKey>().unwrap();
+        // each key type must use its own pattern
+        assert!(Gain::matches(&k("$P12G")));
+        assert!(PeakBin::matches(&k("$pk3")));
+        assert!(!Gain::matches(&k("$PK3")));
+        assert!(!PeakBin::matches(&k("$P1G")));
+        assert!(!Gain::matches(&k("$PG")));
+        assert!(!Gain::matches(&k("$P1GX")));
+        assert!(!Gain::matches(&k("$XP1G")));
+        assert!(Dfc::matches(&k("$DFC1TO20")));
+        assert!(!Dfc::matches(&k("$DFC1TO")));
+        // indices are not limited to 3 digits
+        assert!(Gain::matches(&k("$P1500G")));
+        assert!(Dfc::matches(&k("$DFC1000TO1500")));
+        // NOTE usize is converted from zero-based index
+        assert_eq!(k("$P1500G"), Gain::std(1499.into()));
+        assert_eq!(k("$DFC1000TO1500"), Dfc::std(999.into(), 1499.into()));
+        assert_eq!(
+            Some((999.into(), 1499.into())),
+            Dfc::indices(&k("$DFC1000TO1500"))
+        );
+        assert_eq!(None, Dfc::indices(&k("$DFC01TO2")));
+        assert_eq!(None, Dfc::indices(&k("$DFC1TO2X")));
+    }
 }
@@ -12,7 +12,10 @@ use pyo3::prelude::*;
 
 /// The value for the $PnN key (all versions).
 ///
-/// This cannot contain commas.
+/// This cannot be empty and cannot contain commas, since commas are used to
+/// delimit names in keywords such as $SPILLOVER and $UNSTAINEDCENTERS. See
+/// [`Shortname::validate`] for a way to check these rules without building a
+/// new value.
 #[derive(Clone, Eq, PartialEq, Hash, Debug, AsRef, Display, Into)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[cfg_attr(feature = "python", derive(IntoPyObject))]
@@ -28,20 +31,41 @@ pub struct Shortname(String);
 // pub struct ShortnamePrefix(Shortname);
 
 impl Shortname {
+    /// Characters which may not appear anywhere in a shortname.
+    pub const ILLEGAL_CHARS: [char; 1] = [','];
+
     pub fn new_unchecked<T: AsRef<str>>(s: T) -> Self {
         Shortname(s.as_ref().to_owned())
     }
+
+    /// Check that a string may be used as a shortname.
+    ///
+    /// The first rule violation is returned, in the order they are listed in
+    /// [`ShortnameError`].
+    pub fn validate(s: &str) -> Result<(), ShortnameError> {
+        if s.is_empty() {
+            Err(ShortnameError::Empty)
+        } else if s.contains(Self::ILLEGAL_CHARS) {
+            Err(ShortnameError::Comma(s.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Make a shortname by replacing all illegal characters with a substitute.
+    ///
+    /// This will still fail if the result is empty or if the substitute itself
+    /// contains an illegal character.
+    pub fn normalize(s: &str, sub: &str) -> Result<Self, ShortnameError> {
+        s.replace(Self::ILLEGAL_CHARS, sub).parse()
+    }
 }
 
 impl FromStr for Shortname {
     type Err = ShortnameError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.contains(',') {
-            Err(ShortnameError(s.to_string()))
-        } else {
-            Ok(Shortname(s.to_string()))
-        }
+        Self::validate(s).map(|_| Shortname(s.to_string()))
     }
 }
 
@@ -63,11 +87,21 @@ impl From<MeasIndex> for Shortname {
 //     }
 // }
 
-pub struct ShortnameError(String);
+/// A violated shortname rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShortnameError {
+    /// Name is an empty string
+    Empty,
+    /// Name contains at least one comma
+    Comma(String),
+}
 
 impl fmt::Display for ShortnameError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "commas are not allowed in name '{}'", self.0)
+        match self {
+            Self::Empty => f.write_str("name must not be empty"),
+            Self::Comma(s) => write!(f, "commas are not allowed in name '{s}'"),
+        }
     }
 }
 
@@ -80,6 +114,59 @@ mod tests {
         assert!("Thunderfist Chronicles".parse::<Shortname>().is_ok());
         assert!("Thunderfist,Chronicles".parse::<Shortname>().is_err());
     }
+
+    #[test]
+    fn test_validate_ok() {
+        assert_eq!(Ok(()), Shortname::validate("FSC-A"));
+        assert_eq!(Ok(()), Shortname::validate(" "));
+        assert_eq!(Ok(()), Shortname::validate("CD4 (PE-Cy7)"));
+        assert_eq!(Ok(()), Shortname::validate("$P1"));
+        assert_eq!(Ok(()), Shortname::validate("花冷え"));
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        assert_eq!(Err(ShortnameError::Empty), Shortname::validate(""));
+        assert_eq!(Err(ShortnameError::Empty), "".parse::<Shortname>());
+    }
+
+    #[test]
+    fn test_validate_comma() {
+        for s in [",", "a,", ",a", "a,b", "a,,b"] {
+            assert_eq!(
+                Err(ShortnameError::Comma(s.to_string())),
+                Shortname::validate(s)
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            Ok(Shortname::new_unchecked("FSC_A_B")),
+            Shortname::normalize("FSC,A,B", "_")
+        );
+        assert_eq!(
+            Ok(Shortname::new_unchecked("FSC")),
+            Shortname::normalize("FSC", "_")
+        );
+        assert_eq!(
+            Ok(Shortname::new_unchecked("FSCA")),
+            Shortname::normalize("FSC,A", "")
+        );
+        assert_eq!(Err(ShortnameError::Empty), Shortname::normalize(",", ""));
+        assert_eq!(
+            Err(ShortnameError::Comma("a,b".into())),
+            Shortname::normalize("a,b", ",")
+        );
+    }
+
+    #[test]
+    fn test_meas_index_to_shortname() {
+        let s = Shortname::from(MeasIndex::from(0));
+        assert_eq!("P1", s.to_string());
+        assert!(Shortname::validate(s.as_ref()).is_ok());
+    }
 }
 
 #[cfg(feature = "python")]
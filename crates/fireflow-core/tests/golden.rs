@@ -0,0 +1,98 @@
+//! Check the checked-in golden files against the cases in
+//! `fireflow_core::golden`, and check that each can be read as described.
+//!
+//! If the cases change on purpose, regenerate the files with:
+//!
+//! ```text
+//! FIREFLOW_BLESS_GOLDEN=1 cargo test -p fireflow-core --test golden
+//! ```
+
+use fireflow_core::api::{fcs_read_raw_text, fcs_read_std_dataset};
+use fireflow_core::config::{ReadRawTEXTConfig, ReadStdDatasetConfig};
+use fireflow_core::golden::{self, GoldenLayout};
+use fireflow_core::header::Version;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+#[test]
+fn test_golden_files() {
+    let bless = std::env::var_os("FIREFLOW_BLESS_GOLDEN").is_some();
+    for case in golden::iter() {
+        let name = case.name();
+        let fcs_path = golden_dir().join(format!("{name}.fcs"));
+        let json_path = golden_dir().join(format!("{name}.json"));
+        if bless {
+            fs::write(fcs_path, case.bytes()).unwrap();
+            fs::write(json_path, case.json()).unwrap();
+            continue;
+        }
+        let fcs = fs::read(fcs_path).unwrap_or_default();
+        let json = fs::read_to_string(json_path).unwrap_or_default();
+        assert!(
+            fcs == case.bytes() && json == case.json(),
+            "golden files for {name} are missing or stale; \
+             rerun with FIREFLOW_BLESS_GOLDEN=1 if this is intended"
+        );
+    }
+}
+
+#[test]
+fn test_golden_read() {
+    for case in golden::iter() {
+        let name = case.name();
+        let p = golden_dir().join(format!("{name}.fcs"));
+
+        let (raw, clean) = fcs_read_raw_text(&p, &ReadRawTEXTConfig::default())
+            .ok()
+            .unwrap_or_else(|| panic!("could not read TEXT for {name}"))
+            .resolve(|ws| ws.is_empty());
+        assert!(clean, "warnings when reading TEXT for {name}");
+        let kws: BTreeMap<_, _> = raw
+            .keywords
+            .std
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        assert!(
+            kws == case.expected().keywords,
+            "keywords differ for {name}"
+        );
+        assert!(
+            raw.keywords.nonstd.is_empty(),
+            "nonstandard keywords for {name}"
+        );
+
+        let ((core, _), clean) = fcs_read_std_dataset(&p, &ReadStdDatasetConfig::default())
+            .ok()
+            .unwrap_or_else(|| panic!("could not read dataset for {name}"))
+            .resolve(|ws| ws.is_empty());
+        // ASCII is deprecated in 3.1+ and floats with mixed byte order are
+        // unusual, but both should still be readable
+        let expect_warning = match case.layout() {
+            GoldenLayout::Ascii => case.version() >= Version::FCS3_1,
+            GoldenLayout::Float32Mixed => true,
+            _ => false,
+        };
+        assert!(
+            clean || expect_warning,
+            "warnings when reading dataset for {name}"
+        );
+        assert!(
+            core.version() == case.version(),
+            "version differs for {name}"
+        );
+        let names = core.shortnames();
+        let shortnames: Vec<_> = names.iter().map(ToString::to_string).collect();
+        assert!(shortnames == case.shortnames(), "shortnames differ for {name}");
+        for (n, col) in names.iter().zip(case.columns()) {
+            let xs: Vec<f64> = core.iter_column(n).ok().unwrap().collect();
+            assert!(xs == col, "values of {n} differ for {name}");
+        }
+    }
+}
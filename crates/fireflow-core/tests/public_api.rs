@@ -0,0 +1,459 @@
+//! Snapshot of the public API of this crate.
+//!
+//! Starting from `lib.rs`, every item reachable from outside the crate is
+//! recorded along with its module path and compared to `public_api.txt`. This
+//! includes functions, types, fields, variants, trait items, re-exports, and
+//! the headers (with generics and where clauses) of each impl for a public
+//! type, including trait impls and derives. Items are only recorded if all
+//! their enclosing modules are public, except for impls (and macro
+//! invocations) which apply wherever they are written.
+//!
+//! Macros cannot be expanded here, so items generated by a macro are
+//! represented by the macro invocation itself.
+//!
+//! Each entry is formatted with prettyplease and flattened onto one line, so
+//! whitespace, line breaks, and trailing commas do not matter. Items and
+//! attributes which only apply to tests (ie `#[cfg_attr(test, derive(Debug))]`)
+//! are ignored since they are not part of the API.
+//!
+//! Any difference fails, including additions, so that the snapshot always
+//! lists the entire API. If the API changes on purpose, update it with:
+//!
+//! ```text
+//! FIREFLOW_BLESS_API=1 cargo test -p fireflow-core --test public_api
+//! ```
+//!
+//! See "API stability" in the README for how to remove or change items.
+
+use syn::{
+    Attribute, Fields, Generics, Ident, ImplItem, Item, ItemImpl, ItemMod, Meta, TraitItem, Type,
+    Visibility, parse_quote,
+};
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+const SNAPSHOT: &str = "tests/public_api.txt";
+
+#[test]
+fn test_public_api() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let current = public_items(&root.join("src"));
+    let snapshot_path = root.join(SNAPSHOT);
+    if std::env::var_os("FIREFLOW_BLESS_API").is_some() {
+        let mut out = current.into_iter().collect::<Vec<_>>().join("\n");
+        out.push('\n');
+        fs::write(&snapshot_path, out).unwrap();
+        return;
+    }
+    let snapshot: BTreeSet<String> = fs::read_to_string(&snapshot_path)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect();
+    let removed: Vec<_> = snapshot.difference(&current).collect();
+    let added: Vec<_> = current.difference(&snapshot).collect();
+    let mut msg = String::new();
+    if !removed.is_empty() {
+        msg.push_str("removed or changed (breaking):\n");
+        for x in &removed {
+            msg.push_str(&format!("  - {x}\n"));
+        }
+    }
+    if !added.is_empty() {
+        msg.push_str("added (not breaking):\n");
+        for x in &added {
+            msg.push_str(&format!("  + {x}\n"));
+        }
+    }
+    assert!(
+        msg.is_empty(),
+        "public API differs from {SNAPSHOT}\n{msg}\
+         rerun with FIREFLOW_BLESS_API=1 if this is intended"
+    );
+}
+
+/// A module and everything needed to interpret its items.
+struct Module {
+    path: String,
+    public: bool,
+    cfg: Vec<String>,
+    items: Vec<Item>,
+}
+
+/// Return all public items in the crate rooted at `src/lib.rs`.
+fn public_items(src: &Path) -> BTreeSet<String> {
+    let mut modules = vec![];
+    let lib = parse_file(&src.join("lib.rs"));
+    collect_modules("crate".into(), true, vec![], lib.items, src, &mut modules);
+
+    // impls are only interesting if their type can be named from outside
+    let names: BTreeSet<_> = modules
+        .iter()
+        .filter(|m| m.public)
+        .flat_map(|m| m.items.iter().filter_map(public_name))
+        .collect();
+
+    let mut ret = BTreeSet::new();
+    for m in &modules {
+        for item in m.items.iter().filter(|i| !is_test(item_attrs(i))) {
+            for (context, sig) in item_entries(item, m.public, &names) {
+                let cfg: String = m
+                    .cfg
+                    .iter()
+                    .chain(&cfgs(item_attrs(item)))
+                    .cloned()
+                    .collect();
+                ret.insert(format!("{} | {cfg}{context} | {sig}", m.path));
+            }
+        }
+    }
+    ret
+}
+
+fn parse_file(p: &Path) -> syn::File {
+    let s = fs::read_to_string(p).unwrap_or_else(|e| panic!("{}: {e}", p.display()));
+    syn::parse_file(&s).unwrap_or_else(|e| panic!("{}: {e}", p.display()))
+}
+
+/// Find all modules under a module, following `mod foo;` to its file.
+///
+/// `dir` is the directory in which child modules declared without a body
+/// are found. Unit tests are skipped.
+fn collect_modules(
+    path: String,
+    public: bool,
+    cfg: Vec<String>,
+    items: Vec<Item>,
+    dir: &Path,
+    acc: &mut Vec<Module>,
+) {
+    let children: Vec<_> = items
+        .iter()
+        .filter_map(|i| match i {
+            Item::Mod(m) if !is_test(&m.attrs) => Some(m.clone()),
+            _ => None,
+        })
+        .collect();
+    acc.push(Module {
+        path: path.clone(),
+        public,
+        cfg: cfg.clone(),
+        items,
+    });
+    for m in children {
+        let ItemMod {
+            attrs,
+            vis,
+            ident,
+            content,
+            ..
+        } = m;
+        let child_path = format!("{path}::{ident}");
+        let child_public = public && is_pub(&vis);
+        let child_cfg: Vec<_> = cfg.iter().cloned().chain(cfgs(&attrs)).collect();
+        let child_dir = dir.join(ident.to_string());
+        let child_items = match content {
+            Some((_, xs)) => xs,
+            None => {
+                let f = dir.join(format!("{ident}.rs"));
+                let p = if f.exists() {
+                    f
+                } else {
+                    child_dir.join("mod.rs")
+                };
+                parse_file(&p).items
+            }
+        };
+        collect_modules(
+            child_path,
+            child_public,
+            child_cfg,
+            child_items,
+            &child_dir,
+            acc,
+        );
+    }
+}
+
+/// Return the name under which an item may be referred to outside the crate.
+fn public_name(item: &Item) -> Option<String> {
+    let (vis, ident) = match item {
+        Item::Struct(x) => (&x.vis, &x.ident),
+        Item::Enum(x) => (&x.vis, &x.ident),
+        Item::Type(x) => (&x.vis, &x.ident),
+        Item::Trait(x) => (&x.vis, &x.ident),
+        Item::Union(x) => (&x.vis, &x.ident),
+        _ => return None,
+    };
+    is_pub(vis).then(|| ident.to_string())
+}
+
+/// Return context and signature for each public part of an item.
+fn item_entries(item: &Item, public: bool, names: &BTreeSet<String>) -> Vec<(String, String)> {
+    let mut ret = vec![];
+    match item {
+        Item::Impl(x) if impl_is_public(x, names) => {
+            let context = impl_header(x);
+            if x.trait_.is_some() {
+                ret.push((context, "impl".into()));
+            } else {
+                for i in &x.items {
+                    match i {
+                        ImplItem::Fn(f) if is_pub(&f.vis) && !is_test(&f.attrs) => {
+                            ret.push((context.clone(), fn_sig(&f.sig)));
+                        }
+                        ImplItem::Const(c) if is_pub(&c.vis) && !is_test(&c.attrs) => {
+                            ret.push((context.clone(), const_sig(&c.ident, &c.ty)));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        // like impls, these may generate items wherever they are invoked
+        Item::Macro(x) if x.ident.is_none() => {
+            let mut m = x.clone();
+            m.attrs.clear();
+            ret.push((String::new(), pretty(Item::Macro(m), "", ";")));
+        }
+        _ if !public => (),
+        Item::Fn(x) if is_pub(&x.vis) => ret.push((String::new(), fn_sig(&x.sig))),
+        Item::Const(x) if is_pub(&x.vis) => ret.push((String::new(), const_sig(&x.ident, &x.ty))),
+        Item::Static(x) if is_pub(&x.vis) => {
+            ret.push((
+                String::new(),
+                format!("static {}: {}", x.ident, type_sig(&x.ty)),
+            ));
+        }
+        Item::Type(x) if is_pub(&x.vis) => ret.push((String::new(), strip_attrs(x))),
+        Item::Use(x) if is_pub(&x.vis) => {
+            let tree = &x.tree;
+            ret.push((String::new(), pretty(parse_quote!(use #tree;), "use ", ";")));
+        }
+        Item::Mod(x) if is_pub(&x.vis) => ret.push((String::new(), format!("mod {}", x.ident))),
+        Item::Struct(x) if is_pub(&x.vis) => {
+            let context = type_header(&x.attrs, "struct", &x.ident, &x.generics);
+            ret.push((String::new(), context.clone()));
+            let fields = match &x.fields {
+                Fields::Named(fs) => fs.named.iter().collect(),
+                Fields::Unnamed(fs) => fs.unnamed.iter().collect(),
+                Fields::Unit => vec![],
+            };
+            for (i, f) in fields.into_iter().enumerate() {
+                if is_pub(&f.vis) && !is_test(&f.attrs) {
+                    let name = f.ident.as_ref().map_or(i.to_string(), ToString::to_string);
+                    ret.push((context.clone(), format!("{name}: {}", type_sig(&f.ty))));
+                }
+            }
+        }
+        Item::Enum(x) if is_pub(&x.vis) => {
+            let context = type_header(&x.attrs, "enum", &x.ident, &x.generics);
+            ret.push((String::new(), context.clone()));
+            for v in x.variants.iter().filter(|v| !is_test(&v.attrs)) {
+                let mut v = v.clone();
+                v.attrs.retain(|a| !is_doc(a) && !is_test_attr(a));
+                let e = pretty(parse_quote!(enum E { #v }), "enum E {", "}");
+                ret.push((context.clone(), e));
+            }
+        }
+        Item::Trait(x) if is_pub(&x.vis) => {
+            let mut header = x.clone();
+            header.attrs.clear();
+            header.vis = Visibility::Inherited;
+            header.items.clear();
+            let context = pretty(Item::Trait(header), "", "{}");
+            ret.push((String::new(), context.clone()));
+            for i in &x.items {
+                let sig = match i {
+                    TraitItem::Fn(f) => fn_sig(&f.sig),
+                    TraitItem::Const(c) => const_sig(&c.ident, &c.ty),
+                    TraitItem::Type(t) => {
+                        let mut t = t.clone();
+                        t.attrs.clear();
+                        t.default = None;
+                        pretty(parse_quote!(trait T { #t }), "trait T {", "}")
+                    }
+                    _ => continue,
+                };
+                ret.push((context.clone(), sig));
+            }
+        }
+        _ => (),
+    }
+    ret
+}
+
+/// Return true if an impl is for a type which may be named outside the crate.
+fn impl_is_public(x: &ItemImpl, names: &BTreeSet<String>) -> bool {
+    let ty = match &*x.self_ty {
+        Type::Reference(r) => &*r.elem,
+        t => t,
+    };
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| names.contains(&s.ident.to_string())),
+        // ie impls on tuples or arrays of public types
+        _ => true,
+    }
+}
+
+fn impl_header(x: &ItemImpl) -> String {
+    let mut header = x.clone();
+    header.attrs.clear();
+    header.items.clear();
+    pretty(Item::Impl(header), "", "{}")
+}
+
+fn fn_sig(sig: &syn::Signature) -> String {
+    pretty(parse_quote!(#sig {}), "", "{}")
+}
+
+fn type_sig(ty: &Type) -> String {
+    pretty(parse_quote!(type T = #ty;), "type T =", ";")
+}
+
+fn const_sig(ident: &Ident, ty: &Type) -> String {
+    // values of constants may change freely
+    format!("const {ident}: {}", type_sig(ty))
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(x) => &x.attrs,
+        Item::Enum(x) => &x.attrs,
+        Item::Fn(x) => &x.attrs,
+        Item::Impl(x) => &x.attrs,
+        Item::Macro(x) => &x.attrs,
+        Item::Mod(x) => &x.attrs,
+        Item::Static(x) => &x.attrs,
+        Item::Struct(x) => &x.attrs,
+        Item::Trait(x) => &x.attrs,
+        Item::Type(x) => &x.attrs,
+        Item::Use(x) => &x.attrs,
+        _ => &[],
+    }
+}
+
+/// Return each `cfg` attribute, which will be prepended to the context.
+fn cfgs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .map(|a| format!("{} ", pretty(parse_quote!(#a struct S;), "", "struct S;")))
+        .collect()
+}
+
+/// Return the header of a struct or enum, including derives.
+fn type_header(attrs: &[Attribute], kind: &str, ident: &Ident, g: &Generics) -> String {
+    let derives: Vec<_> = attrs.iter().filter(|a| is_derive(a)).collect();
+    let wc = &g.where_clause;
+    let item = if kind == "struct" {
+        parse_quote!(#(#derives)* struct #ident #g #wc {})
+    } else {
+        parse_quote!(#(#derives)* enum #ident #g #wc {})
+    };
+    pretty(item, "", "{}")
+}
+
+/// Return true if an attribute is a derive, including those behind `cfg_attr`.
+///
+/// Derives which only apply to tests are not included.
+fn is_derive(a: &Attribute) -> bool {
+    let p = a.path();
+    p.is_ident("derive") || (p.is_ident("cfg_attr") && !is_test_attr(a) && has_derive(a))
+}
+
+fn has_derive(a: &Attribute) -> bool {
+    match &a.meta {
+        Meta::List(l) => l
+            .tokens
+            .clone()
+            .into_iter()
+            .any(|t| t.to_string() == "derive"),
+        _ => false,
+    }
+}
+
+fn strip_attrs(x: &syn::ItemType) -> String {
+    let mut x = x.clone();
+    x.attrs.clear();
+    pretty(Item::Type(x), "", "")
+}
+
+/// Format an item with prettyplease and flatten it onto one line.
+///
+/// `prefix` and `suffix` are removed afterward, which is useful for fragments
+/// (ie a field type) which must be wrapped in an item to be formatted.
+fn pretty(item: Item, prefix: &str, suffix: &str) -> String {
+    let file = syn::File {
+        shebang: None,
+        attrs: vec![],
+        items: vec![item],
+    };
+    let s = flatten(&prettyplease::unparse(&file));
+    let s = s.strip_prefix(prefix).unwrap_or(&s);
+    let s = s.strip_suffix(suffix).unwrap_or(s);
+    s.trim().trim_end_matches(',').into()
+}
+
+/// Collapse whitespace and remove trailing commas.
+///
+/// Whether prettyplease breaks an item over multiple lines (and therefore
+/// whether it adds trailing commas) depends on its length, so this makes the
+/// same item look the same regardless of what is around it.
+fn flatten(s: &str) -> String {
+    let mut ret = String::new();
+    for w in s.split_whitespace() {
+        if !ret.is_empty() && !ret.ends_with(['(', '[']) && !w.starts_with([')', ']']) {
+            ret.push(' ');
+        }
+        ret.push_str(w);
+    }
+    // ASSUME commas are never followed by a closing delimiter except when
+    // trailing, since string literals are not recorded
+    let mut out = String::new();
+    for c in ret.chars() {
+        if matches!(c, ')' | ']' | '}' | '>') {
+            let t = out.trim_end();
+            if let Some(x) = t.strip_suffix(',') {
+                out = x.to_string();
+                if c == '}' {
+                    out.push(' ');
+                }
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_pub(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+fn is_doc(a: &Attribute) -> bool {
+    a.path().is_ident("doc")
+}
+
+fn is_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| match &a.meta {
+        Meta::List(l) => l.path.is_ident("cfg") && l.tokens.to_string() == "test",
+        _ => false,
+    })
+}
+
+/// Return true if an attribute is `#[cfg_attr(test, ...)]`.
+fn is_test_attr(a: &Attribute) -> bool {
+    match &a.meta {
+        Meta::List(l) => {
+            l.path.is_ident("cfg_attr")
+                && l.tokens.clone().into_iter().next().map(|t| t.to_string()) == Some("test".into())
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,316 @@
+//! Minimal HEADER parser.
+//!
+//! This has no dependencies (not even `std`) so it may be vendored into
+//! constrained environments such as instrument firmware which needs to check
+//! its own output.
+//!
+//! There are two layers. [`split_header`] and [`split_other`] only cut HEADER
+//! into its fixed-width fields without interpreting them; `fireflow-core` uses
+//! these and then parses each field leniently with its own error correction.
+//! [`parse_header`] is built on top of these and is strict; it does no error
+//! correction and is meant for checking files rather than reading files in the
+//! wild.
+//!
+//! Offsets are returned exactly as written. In particular, DATA and ANALYSIS
+//! may be 0,0 in 3.0+ if they are too big for HEADER, in which case the real
+//! offsets are in TEXT.
+
+#![no_std]
+#![warn(clippy::shadow_reuse)]
+#![warn(clippy::shadow_unrelated)]
+
+use core::fmt;
+use core::str;
+
+/// Length of HEADER without any OTHER offsets.
+pub const REQ_LEN: usize = 58;
+
+/// Width of each offset in HEADER.
+pub const WIDTH: usize = 8;
+
+/// The required fields of HEADER before they are interpreted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeaderFields<'a> {
+    /// The first 6 bytes (ie "FCS3.1")
+    pub version: &'a [u8; 6],
+
+    /// The 4 bytes after the version which should be spaces
+    pub spaces: &'a [u8; 4],
+
+    /// Begin and end offsets for TEXT, DATA, and ANALYSIS respectively
+    pub text: [&'a [u8; WIDTH]; 2],
+    pub data: [&'a [u8; WIDTH]; 2],
+    pub analysis: [&'a [u8; WIDTH]; 2],
+}
+
+/// HEADER as it appears in the first bytes of an FCS file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RawHeader<'a> {
+    /// The first 6 bytes (ie "FCS3.1")
+    pub version: [u8; 6],
+
+    pub text: (u64, u64),
+    pub data: (u64, u64),
+    pub analysis: (u64, u64),
+
+    /// Bytes after the required offsets and before the first segment
+    other: &'a [u8],
+}
+
+/// Error encountered when parsing HEADER.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RawHeaderError {
+    /// Input is shorter than the required 58 bytes
+    TooShort(usize),
+
+    /// Version does not look like "FCSn.n"
+    Version([u8; 6]),
+
+    /// Version is not followed by 4 spaces
+    Space,
+
+    /// Offset at this position is not a right-aligned unsigned integer
+    Offset(usize),
+
+    /// Begin offset at this position is greater than its end offset
+    Order(usize),
+}
+
+/// Iterator over OTHER offsets in HEADER.
+///
+/// Pairs which are entirely blank are skipped.
+#[derive(Clone)]
+pub struct RawOtherOffsets<'a> {
+    fields: OtherFields<'a>,
+}
+
+/// Iterator over begin and end fields of each OTHER offset in HEADER.
+///
+/// Any trailing bytes which are not enough to make a full pair are ignored.
+#[derive(Clone)]
+pub struct OtherFields<'a> {
+    bytes: &'a [u8],
+    width: usize,
+    pos: usize,
+}
+
+/// Split the required part of HEADER into its fields.
+pub fn split_header(bytes: &[u8; REQ_LEN]) -> HeaderFields<'_> {
+    // ASSUME none of these will fail since the input is exactly 58 bytes
+    let field = |i: usize| -> &[u8; WIDTH] { bytes[i..i + WIDTH].try_into().unwrap() };
+    HeaderFields {
+        version: bytes[..6].try_into().unwrap(),
+        spaces: bytes[6..10].try_into().unwrap(),
+        text: [field(10), field(18)],
+        data: [field(26), field(34)],
+        analysis: [field(42), field(50)],
+    }
+}
+
+/// Split OTHER offsets into their begin and end fields.
+///
+/// `bytes` should start immediately after the required 58 bytes and each
+/// offset is assumed to be `width` bytes.
+pub fn split_other(bytes: &[u8], width: usize) -> OtherFields<'_> {
+    OtherFields {
+        bytes,
+        width,
+        pos: 0,
+    }
+}
+
+/// Return true if `version` looks like "FCSn.n".
+pub fn is_version(version: &[u8; 6]) -> bool {
+    version.starts_with(b"FCS")
+        && version[3].is_ascii_digit()
+        && version[4] == b'.'
+        && version[5].is_ascii_digit()
+}
+
+/// Return true if all bytes are spaces.
+pub fn is_blank(bytes: &[u8]) -> bool {
+    bytes.iter().all(|x| *x == b' ')
+}
+
+/// Parse HEADER from the start of an FCS file.
+///
+/// `bytes` must contain at least the first 58 bytes. If more are given, OTHER
+/// offsets may be obtained with [`RawHeader::other`].
+pub fn parse_header(bytes: &[u8]) -> Result<RawHeader<'_>, RawHeaderError> {
+    let fields = bytes
+        .first_chunk()
+        .map(split_header)
+        .ok_or(RawHeaderError::TooShort(bytes.len()))?;
+    if !is_version(fields.version) {
+        return Err(RawHeaderError::Version(*fields.version));
+    }
+    if !is_blank(fields.spaces) {
+        return Err(RawHeaderError::Space);
+    }
+    let text = parse_pair(fields.text, 10)?;
+    let data = parse_pair(fields.data, 26)?;
+    let analysis = parse_pair(fields.analysis, 42)?;
+    // OTHER offsets end wherever the first segment begins
+    let other_end = [text, data, analysis]
+        .iter()
+        .map(|(b, _)| *b)
+        .filter(|b| *b >= REQ_LEN as u64)
+        .min()
+        .map_or(REQ_LEN, |b| usize::try_from(b).unwrap_or(usize::MAX))
+        .min(bytes.len());
+    Ok(RawHeader {
+        version: *fields.version,
+        text,
+        data,
+        analysis,
+        other: &bytes[REQ_LEN..other_end],
+    })
+}
+
+impl<'a> RawHeader<'a> {
+    /// Return the version as a string (ie "FCS3.1").
+    pub fn version_str(&self) -> &str {
+        // ASSUME this will not fail since the version was checked to be ASCII
+        str::from_utf8(&self.version).unwrap_or_default()
+    }
+
+    /// Return offsets for each OTHER segment.
+    ///
+    /// This assumes OTHER offsets are 8 bytes wide like the others in HEADER.
+    /// Only the bytes passed to [`parse_header`] will be considered.
+    pub fn other(&self) -> RawOtherOffsets<'a> {
+        RawOtherOffsets {
+            fields: split_other(self.other, WIDTH),
+        }
+    }
+
+    /// Return number of bytes in HEADER including OTHER offsets.
+    pub fn nbytes(&self) -> usize {
+        REQ_LEN + self.other.len()
+    }
+}
+
+impl<'a> Iterator for OtherFields<'a> {
+    /// Position of the pair relative to the start of OTHER, and the begin and
+    /// end fields.
+    type Item = (usize, &'a [u8], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = self.pos;
+        let w = self.width;
+        if w == 0 || i + w * 2 > self.bytes.len() {
+            return None;
+        }
+        self.pos += w * 2;
+        Some((i, &self.bytes[i..i + w], &self.bytes[i + w..i + w * 2]))
+    }
+}
+
+impl Iterator for RawOtherOffsets<'_> {
+    type Item = Result<(u64, u64), RawHeaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.fields
+            .by_ref()
+            .find(|(_, b, e)| !(is_blank(b) && is_blank(e)))
+            .map(|(i, b, e)| {
+                // ASSUME these won't fail since fields are WIDTH bytes
+                let pair = [b.try_into().unwrap(), e.try_into().unwrap()];
+                parse_pair(pair, REQ_LEN + i)
+            })
+    }
+}
+
+fn parse_pair(pair: [&[u8; WIDTH]; 2], pos: usize) -> Result<(u64, u64), RawHeaderError> {
+    let begin = parse_offset(pair[0], pos)?;
+    let end = parse_offset(pair[1], pos + WIDTH)?;
+    // 0,0 is an empty segment; otherwise end is inclusive
+    if begin > end {
+        Err(RawHeaderError::Order(pos))
+    } else {
+        Ok((begin, end))
+    }
+}
+
+/// Parse a right-aligned, space-padded integer, where all spaces is zero.
+fn parse_offset(buf: &[u8; WIDTH], pos: usize) -> Result<u64, RawHeaderError> {
+    let err = RawHeaderError::Offset(pos);
+    let n_spaces = buf.iter().take_while(|x| **x == b' ').count();
+    buf[n_spaces..].iter().try_fold(0_u64, |acc, x| {
+        if x.is_ascii_digit() {
+            Ok(acc * 10 + u64::from(x - b'0'))
+        } else {
+            Err(err)
+        }
+    })
+}
+
+impl fmt::Display for RawHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::TooShort(n) => write!(f, "HEADER must be at least {REQ_LEN} bytes, got {n}"),
+            Self::Version(v) => match str::from_utf8(v) {
+                Ok(s) => write!(f, "'{s}' is not a valid FCS version"),
+                Err(_) => write!(f, "FCS version is not ASCII, got bytes {v:?}"),
+            },
+            Self::Space => f.write_str("version must be followed by 4 spaces"),
+            Self::Offset(i) => write!(f, "HEADER offset at byte {i} is not a valid integer"),
+            Self::Order(i) => write!(
+                f,
+                "HEADER begin offset at byte {i} is greater than its end offset"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDR: &[u8] =
+        b"FCS3.1          90     189       0       0       0       0     200     206        ";
+
+    #[test]
+    fn test_split_header() {
+        let fs = split_header(HDR.first_chunk().unwrap());
+        assert_eq!(b"FCS3.1", fs.version);
+        assert!(is_blank(fs.spaces));
+        assert_eq!([b"      90", b"     189"], fs.text);
+        assert_eq!([b"       0", b"       0"], fs.analysis);
+        let mut os = split_other(&HDR[REQ_LEN..], 4);
+        assert_eq!(Some((0, &b"    "[..], &b" 200"[..])), os.next());
+        assert_eq!(Some((8, &b"    "[..], &b" 206"[..])), os.next());
+        assert_eq!(Some((16, &b"    "[..], &b"    "[..])), os.next());
+        assert_eq!(None, os.next());
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let h = parse_header(HDR).unwrap();
+        assert_eq!("FCS3.1", h.version_str());
+        assert_eq!((90, 189), h.text);
+        assert_eq!((0, 0), h.data);
+        assert_eq!((0, 0), h.analysis);
+        let mut os = h.other();
+        assert_eq!(Some(Ok((200, 206))), os.next());
+        // only part of the second pair is present
+        assert_eq!(None, os.next());
+        assert_eq!(HDR.len(), h.nbytes());
+    }
+
+    #[test]
+    fn test_parse_header_errors() {
+        assert_eq!(Err(RawHeaderError::TooShort(10)), parse_header(&HDR[..10]));
+        let mut bad = [0; 58];
+        bad.copy_from_slice(&HDR[..58]);
+        bad[17] = b'x';
+        assert_eq!(Err(RawHeaderError::Offset(10)), parse_header(&bad));
+        bad[10..26].copy_from_slice(b"      90      10");
+        assert_eq!(Err(RawHeaderError::Order(10)), parse_header(&bad));
+        bad[3] = b'X';
+        assert!(matches!(
+            parse_header(&bad),
+            Err(RawHeaderError::Version(_))
+        ));
+    }
+}
@@ -63,6 +63,7 @@ pub(crate) enum PyType {
     Decimal,
     Date,
     Time,
+    Timezone,
     Option(Box<PyType>),
     Dict(Box<PyType>, Box<PyType>),
     Union(Box<PyType>, Box<PyType>, Vec<PyType>),
@@ -383,6 +384,7 @@ impl fmt::Display for PyType {
             Self::None => f.write_str("None"),
             Self::Date => f.write_str(":py:class:`~datetime.date`"),
             Self::Time => f.write_str(":py:class:`~datetime.time`"),
+            Self::Timezone => f.write_str(":py:class:`~datetime.timezone`"),
             Self::Datetime => f.write_str(":py:class:`~datetime.datetime`"),
             Self::Decimal => f.write_str(":py:class:`~decimal.Decimal`"),
             Self::Union(x, y, zs) => {
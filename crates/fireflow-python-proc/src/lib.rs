@@ -349,6 +349,47 @@ pub fn impl_core_all_meas_nonstandard_keywords(input: TokenStream) -> TokenStrea
 }
 
 // TODO make this return $TOT, $NEXTDATA, etc
+#[proc_macro]
+pub fn impl_core_grouped_keywords(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+
+    let doc = DocString::new(
+        "Return keywords with measurement non-standard keywords grouped.".into(),
+        vec![
+            "This is like ``standard_keywords`` except that non-standard \
+             keywords linked to a measurement (ie those which matched \
+             ``nonstandard_measurement_pattern`` when reading) are returned \
+             separately for each measurement, including the time measurement."
+                .into(),
+            "This will not include *$TOT*, *$NEXTDATA* or any of the \
+             offset keywords since these are not encoded in this class."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::PyClass("pyreflow.typing.GroupedKeywords".into()),
+            Some(
+                "All other keywords under ``keywords`` and non-standard \
+                 keywords for each measurement under ``measurements``."
+                    .into(),
+            ),
+        )),
+    );
+
+    quote! {
+        #[pymethods]
+        impl #t {
+            #doc
+            fn grouped_keywords(&self) -> fireflow_core::core::GroupedKeywords {
+                self.0.grouped_keywords()
+            }
+        }
+    }
+    .into()
+}
+
 #[proc_macro]
 pub fn impl_core_standard_keywords(input: TokenStream) -> TokenStream {
     let t = parse_macro_input!(input as Ident);
@@ -449,188 +490,124 @@ pub fn impl_core_set_tr_threshold(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_write_text(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-    let textdelim_path = textdelim_path();
-
-    let write_2_0_warning = if version == Version::FCS2_0 {
-        Some("Will raise exception if file cannot fit within 99,999,999 bytes.".into())
-    } else {
-        None
-    };
+pub fn impl_core_provenance(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let prov_type = PyType::new_opt(PyType::PyClass("pyreflow.typing.Provenance".into()));
 
-    let write_text_doc = DocString::new(
-        "Write data to path.".into(),
-        ["Resulting FCS file will include *HEADER* and *TEXT*.".into()]
-            .into_iter()
-            .chain(write_2_0_warning.clone())
-            .collect(),
+    let s0 = "This is stored in nonstandard keywords starting with \
+              *FIREFLOW_* and includes the filename and hash of the parent \
+              file, the operation used to derive this dataset, and the \
+              software which wrote it.";
+    let get_doc = DocString::new(
+        "Provenance for a derived dataset.".into(),
+        vec![s0.into()],
         DocSelf::PySelf,
-        vec![path_param(false), textdelim_param(), big_other_param()],
-        None,
-    );
-
-    quote! {
-        #[pymethods]
-        impl #i {
-            #write_text_doc
-            fn write_text(
-                &self,
-                path: std::path::PathBuf,
-                delim: #textdelim_path,
-                big_other: bool
-            ) -> PyResult<()> {
-                let f = std::fs::File::options().write(true).create(true).open(path)?;
-                let mut h = std::io::BufWriter::new(f);
-                self.0.h_write_text(&mut h, delim, big_other).py_termfail_resolve_nowarn()
-            }
-        }
-    }
-    .into()
-}
-
-#[proc_macro]
-pub fn impl_core_write_dataset(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-    let textdelim_path = textdelim_path();
-
-    let write_2_0_warning = if version == Version::FCS2_0 {
-        Some("Will raise exception if file cannot fit within 99,999,999 bytes.".into())
-    } else {
-        None
-    };
+        vec![],
+        Some(DocReturn::new(prov_type, None)),
+    )
+    .doc();
 
-    let doc = DocString::new(
-        "Write data as an FCS file.".into(),
-        ["The resulting file will include *HEADER*, *TEXT*, *DATA*, \
-            *ANALYSIS*, and *OTHER* as they present from this class."
-            .into()]
-        .into_iter()
-        .chain(write_2_0_warning)
-        .collect(),
+    let record_doc = DocString::new(
+        "Record provenance for a dataset derived from another file.".into(),
+        vec!["This will replace any existing provenance keywords.".into()],
         DocSelf::PySelf,
         vec![
-            path_param(false),
-            textdelim_param(),
-            big_other_param(),
+            DocArg::new_param(
+                "operation".into(),
+                PyType::Str,
+                "Description of how this dataset was derived.".into(),
+            ),
             DocArg::new_param_def(
-                "skip_conversion_check".into(),
-                PyType::Bool,
-                "Skip check to ensure that types of the dataframe match the \
-                 columns (*$PnB*, *$DATATYPE*, etc). If this is ``False``, \
-                 perform this check before writing, and raise exception on \
-                 failure. If ``True``, raise warnings as file is being \
-                 written. Skipping this is faster since the data needs to be \
-                 traversed twice to perform the conversion check, but may \
-                 result in loss of precision and/or truncation."
+                "parent".into(),
+                PyType::new_opt(PyType::PyClass("~pathlib.Path".into())),
+                "Path to the parent file. If given, its filename and a hash \
+                 of its contents will be recorded."
                     .into(),
-                DocDefault::Bool(false),
+                DocDefault::Option,
             ),
         ],
         None,
     );
 
+    let prov = quote!(fireflow_core::text::provenance::Provenance);
+
     quote! {
         #[pymethods]
-        impl #i {
-            #doc
-            fn write_dataset(
-                &self,
-                path: std::path::PathBuf,
-                delim: #textdelim_path,
-                big_other: bool,
-                skip_conversion_check: bool,
-            ) -> PyResult<()> {
-                let f = std::fs::File::options().write(true).create(true).open(path)?;
-                let mut h = std::io::BufWriter::new(f);
-                let conf = fireflow_core::config::WriteConfig {
-                    delim,
-                    skip_conversion_check,
-                    big_other,
-                };
-                self.0.h_write_dataset(&mut h, &conf).py_termfail_resolve()
-            }
-        }
-    }
-    .into()
-}
-
-#[proc_macro]
-pub fn impl_core_all_peak_attrs(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_pycore(&i).1;
-
-    let go = |k: &str, i: &str, name: &str| {
-        let p = keyword_path(i);
-        let doc = DocString::new(
-            format!("The value of *$P{k}n* for all measurements."),
-            vec![],
-            DocSelf::PySelf,
-            vec![],
-            Some(DocReturn::new(PyType::new_list(PyType::Int), None)),
-        )
-        .doc();
-        let get = format_ident!("get_all_{name}");
-        let set = format_ident!("set_all_{name}");
-        quote! {
-            #doc
+        impl #t {
+            #get_doc
             #[getter]
-            fn #get(&self) -> Vec<Option<#p>> {
-                self.0
-                    .get_temporal_optical::<Option<#p>>()
-                    .map(|x| x.as_ref().copied())
-                    .collect()
+            fn get_provenance(&self) -> Option<#prov> {
+                self.0.provenance()
             }
 
             #[setter]
-            fn #set(&mut self, xs: Vec<Option<#p>>) -> PyResult<()> {
-                Ok(self.0.set_temporal_optical(xs)?)
+            fn set_provenance(&mut self, p: Option<#prov>) {
+                self.0.set_provenance(p)
             }
-        }
-    };
-
-    let pkn = go("K", "PeakBin", "peak_bins");
-    let pknn = go("KN", "PeakNumber", "peak_sizes");
 
-    quote! {
-        #[pymethods]
-        impl #i {
-            #pkn
-            #pknn
+            #record_doc
+            fn record_provenance(
+                &mut self,
+                operation: String,
+                parent: Option<std::path::PathBuf>,
+            ) -> PyResult<()> {
+                let p = if let Some(pp) = parent {
+                    #prov::new_with_parent(operation, &pp)?
+                } else {
+                    #prov::new(operation)
+                };
+                self.0.set_provenance(Some(p));
+                Ok(())
+            }
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_all_shortnames_attr(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_pycore(&i).1;
-    let shortname_path = shortname_path();
-
+pub fn impl_core_environment(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let env_type = PyType::PyClass("pyreflow.typing.Environment".into());
+    let conv_type = PyType::PyClass("pyreflow.typing.KeyConvention".into());
+
+    let s0 = "These are not part of the standard but are recorded by many \
+              instruments in vendor-specific nonstandard keywords. Only the \
+              keys in the given conventions are recognized, so any condition \
+              may be ``None`` even if present under some other key. The \
+              keywords themselves are not changed.";
+    let s1 = "Pressure is in psi, temperatures are in degrees Celsius, and \
+              humidity is in percent.";
     let doc = DocString::new(
-        "Value of *$PnN* for all measurements.".into(),
-        vec!["Strings are unique and cannot contain commas.".into()],
+        "Environmental conditions during acquisition.".into(),
+        vec![s0.into(), s1.into()],
         DocSelf::PySelf,
-        vec![],
-        Some(DocReturn::new(PyType::new_list(PyType::Str), None)),
+        vec![DocArg::new_param(
+            "conventions".into(),
+            PyType::new_list(conv_type),
+            "Keys to check for each condition. Keys are compared \
+             case-insensitively, and each condition will be taken from the \
+             first convention which has it."
+                .into(),
+        )],
+        Some(DocReturn::new(env_type, None)),
     )
     .doc();
 
     quote! {
         #[pymethods]
-        impl #i {
+        impl #t {
             #doc
-            #[getter]
-            fn get_all_shortnames(&self) -> Vec<#shortname_path> {
-                self.0.all_shortnames()
-            }
-
-            #[setter]
-            fn set_all_shortnames(&mut self, names: Vec<#shortname_path>) -> PyResult<()> {
-                Ok(self.0.set_all_shortnames(names).void()?)
+            fn environment(
+                &self,
+                conventions: Vec<fireflow_core::text::conventions::KeyConvention>,
+            ) -> fireflow_core::text::conventions::Environment {
+                let xs: Vec<_> = conventions
+                    .iter()
+                    .map(|c| c as &dyn fireflow_core::text::conventions::EnvironmentConvention)
+                    .collect();
+                self.0.environment(&xs)
             }
         }
     }
@@ -638,39 +615,76 @@ pub fn impl_core_all_shortnames_attr(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_all_shortnames_maybe_attr(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_pycore(&i).1;
-    let shortname_path = shortname_path();
+pub fn impl_core_vendor_timestamp(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let ts_type = PyType::new_opt(PyType::PyClass("pyreflow.typing.VendorTimestamp".into()));
+    let path = quote!(fireflow_core::text::conventions::VendorTimestamp);
 
-    let doc = DocString::new(
-        "The possibly-empty values of *$PnN* for all measurements.".into(),
-        vec!["*$PnN* is optional for this FCS version so values may be ``None``.".into()],
+    let get_doc = DocString::new(
+        "Start of acquisition stored in vendor epoch keywords.".into(),
+        vec![
+            "Some instruments store this as an epoch timestamp (seconds, \
+             milliseconds, or microseconds) in a nonstandard keyword such as \
+             *#ACQUISITIONTIMEMILLIS*. Only common key names are recognized. \
+             The keywords themselves are not changed."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(ts_type.clone(), None)),
+    )
+    .doc();
+
+    let fill_doc = DocString::new(
+        "Fill in start of acquisition from vendor epoch keywords.".into(),
+        vec![
+            "*$BTIM* and *$DATE* (or *$BEGINDATETIME* for FCS 3.2) will only \
+             be set if none of these are present already. Filled values will \
+             be in UTC. The key from which these were filled will be stored \
+             in *FIREFLOW_TIMESTAMP_SOURCE*."
+                .into(),
+        ],
         DocSelf::PySelf,
         vec![],
         Some(DocReturn::new(
-            PyType::new_list(PyType::new_opt(PyType::Str)),
-            None,
+            ts_type,
+            Some("The timestamp used or ``None`` if nothing was filled.".into()),
         )),
     )
     .doc();
 
+    let source_doc = DocString::new(
+        "Key from which start of acquisition was filled.".into(),
+        vec![
+            "Will be ``None`` unless start of acquisition was filled from a \
+             vendor epoch keyword."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(PyType::new_opt(PyType::Str), None)),
+    )
+    .doc();
+
     quote! {
         #[pymethods]
-        impl #i {
-            #doc
+        impl #t {
+            #get_doc
             #[getter]
-            fn get_all_shortnames_maybe(&self) -> Vec<Option<#shortname_path>> {
-                self.0
-                    .shortnames_maybe()
-                    .into_iter()
-                    .map(|x| x.cloned())
-                    .collect()
+            fn get_vendor_timestamp(&self) -> Option<#path> {
+                self.0.vendor_timestamp()
             }
 
-            #[setter]
-            fn set_all_shortnames_maybe(&mut self, names: Vec<Option<#shortname_path>>) -> PyResult<()> {
-                Ok(self.0.set_measurement_shortnames_maybe(names).void()?)
+            #fill_doc
+            fn fill_timestamps(&mut self) -> Option<#path> {
+                self.0.fill_timestamps()
+            }
+
+            #source_doc
+            #[getter]
+            fn get_timestamp_source(&self) -> Option<String> {
+                self.0.timestamp_source().map(String::from)
             }
         }
     }
@@ -678,392 +692,348 @@ pub fn impl_core_all_shortnames_maybe_attr(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_get_set_timestep(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_pycore(&i).1;
-    let timestep_path = keyword_path("Timestep");
+pub fn impl_core_namespace(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let dict = PyType::new_dict(PyType::Str, PyType::Str);
+    let prefix = DocArg::new_param(
+        "prefix".into(),
+        PyType::Str,
+        "Prefix shared by all keys in the namespace (ie ``\"MYLAB_\"``).".into(),
+    );
 
-    let t = PyType::new_opt(PyType::Float);
     let get_doc = DocString::new(
-        "The value of *$TIMESTEP*".into(),
-        vec![],
+        "Return nonstandard keywords in a namespace.".into(),
+        vec![
+            "Keys are compared case-insensitively and returned without their \
+             prefix. This does not include keywords belonging to measurements."
+                .into(),
+        ],
         DocSelf::PySelf,
-        vec![],
-        Some(DocReturn::new(t.clone(), None)),
-    )
-    .doc();
+        vec![prefix.clone()],
+        Some(DocReturn::new(dict.clone(), None)),
+    );
+
     let set_doc = DocString::new(
-        "Set the *$TIMESTEP* if time measurement is present.".into(),
-        vec![],
+        "Replace all nonstandard keywords in a namespace.".into(),
+        vec![
+            "Each name will be prefixed before being stored. Nonstandard \
+             keywords outside the namespace will not be changed."
+                .into(),
+        ],
         DocSelf::PySelf,
-        vec![DocArg::new_param(
-            "timestep".into(),
-            PyType::Float,
-            "The timestep to set. Must be greater than zero.".into(),
-        )],
-        Some(DocReturn::new(
-            t,
-            Some("Previous *$TIMESTEP* if present.".into()),
-        )),
+        vec![
+            prefix,
+            DocArg::new_param(
+                "keywords".into(),
+                dict,
+                "Names and values to store in the namespace.".into(),
+            ),
+        ],
+        None,
     );
-    let q = quote! {
-        #get_doc
-        #[getter]
-        fn get_timestep(&self) -> Option<#timestep_path> {
-            self.0.timestep().copied()
-        }
 
-        #set_doc
-        fn set_timestep(&mut self, timestep: #timestep_path) -> Option<#timestep_path> {
-            self.0.set_timestep(timestep)
-        }
-    };
+    let ns = quote!(fireflow_core::text::namespace::KeyNamespace);
 
     quote! {
         #[pymethods]
-        impl #i {
-            #q
+        impl #t {
+            #get_doc
+            fn namespace_keywords(
+                &self,
+                prefix: #ns,
+            ) -> std::collections::HashMap<String, String> {
+                self.0.namespace_keywords(&prefix)
+            }
+
+            #set_doc
+            fn set_namespace_keywords(
+                &mut self,
+                prefix: #ns,
+                keywords: std::collections::HashMap<String, String>,
+            ) -> PyResult<()> {
+                Ok(self.0.set_namespace_keywords(&prefix, keywords)?)
+            }
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_set_temporal(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-    let shortname_path = shortname_path();
-    let timestep_path = keyword_path("Timestep");
-    let meas_index_path = meas_index_path();
+pub fn impl_core_keyword_origins(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let origin_type = PyType::PyClass("pyreflow.typing.KeywordOrigin".into());
+
+    let origins_doc = DocString::new(
+        "Origin of each standard keyword which has a value.".into(),
+        vec!["Keywords which were in *TEXT* when this was read will be \
+             ``\"present\"``. All others (filled in by this library, ie when \
+             converting versions, or set after reading) will be \
+             ``\"defaulted\"``. This covers the same keywords as \
+             :py:meth:`standard_keywords`."
+            .into()],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_dict(PyType::Str, origin_type.clone()),
+            None,
+        )),
+    )
+    .doc();
 
-    let make_doc = |has_timestep: bool, has_index: bool| {
-        let name = param_name("Name to set to temporal.");
-        let index = DocArg::new_param("index".into(), PyType::Int, "Index to set".into());
-        let (i, p) = if has_index {
-            ("index", index)
-        } else {
-            ("name", name)
-        };
-        let timestep = if has_timestep {
-            Some(DocArg::new_param(
-                "timestep".into(),
-                PyType::Float,
-                "The value of *$TIMESTEP* to use.".into(),
-            ))
-        } else {
-            None
-        };
-        let force = DocArg::new_param_def(
-            "force".into(),
-            PyType::Bool,
-            "If ``True`` remove any optical-specific metadata (detectors, \
-             lasers, etc) without raising an exception. Defauls to ``False``."
+    let origin_doc = DocString::new(
+        "Return origin of one standard keyword.".into(),
+        vec![
+            "Same as :py:attr:`keyword_origins` except keywords with no \
+              value will be ``\"absent\"``."
                 .into(),
-            DocDefault::Bool(false),
-        );
-        let ps = [p].into_iter().chain(timestep).chain([force]).collect();
-        DocString::new(
-            format!("Set the temporal measurement to a given {i}."),
-            vec![],
-            DocSelf::PySelf,
-            ps,
-            Some(DocReturn::new(
-                PyType::Bool,
-                Some(format!(
-                    "``True`` if temporal measurement was set, which will \
-                     happen for all cases except when the time measurement is \
-                     already set to ``{i}``."
-                )),
-            )),
-        )
-    };
+        ],
+        DocSelf::PySelf,
+        vec![DocArg::new_param(
+            "key".into(),
+            PyType::Str,
+            "Standard key including the leading *$* (case-insensitive).".into(),
+        )],
+        Some(DocReturn::new(origin_type, None)),
+    );
 
-    let q = if version == Version::FCS2_0 {
-        let name_doc = make_doc(false, false);
-        let index_doc = make_doc(false, true);
-        quote! {
-            #name_doc
-            fn set_temporal(&mut self, name: #shortname_path, force: bool) -> PyResult<bool> {
-                self.0.set_temporal(&name, (), force).py_termfail_resolve()
-            }
+    let origin = quote!(fireflow_core::core::KeywordOrigin);
 
-            #index_doc
-            fn set_temporal_at(&mut self, index: #meas_index_path, force: bool) -> PyResult<bool> {
-                self.0.set_temporal_at(index, (), force).py_termfail_resolve()
-            }
-        }
-    } else {
-        let name_doc = make_doc(true, false);
-        let index_doc = make_doc(true, true);
-        quote! {
-            #name_doc
-            fn set_temporal(
-                &mut self,
-                name: #shortname_path,
-                timestep: #timestep_path,
-                force: bool,
-            ) -> PyResult<bool> {
-                self.0
-                    .set_temporal(&name, timestep, force)
-                    .py_termfail_resolve()
+    quote! {
+        #[pymethods]
+        impl #t {
+            #origins_doc
+            #[getter]
+            fn get_keyword_origins(&self) -> std::collections::HashMap<String, #origin> {
+                self.0.keyword_origins()
             }
 
-            #index_doc
-            fn set_temporal_at(
-                &mut self,
-                index: #meas_index_path,
-                timestep: #timestep_path,
-                force: bool,
-            ) -> PyResult<bool> {
-                self.0
-                    .set_temporal_at(index, timestep, force)
-                    .py_termfail_resolve()
+            #origin_doc
+            fn keyword_origin(&self, key: &str) -> #origin {
+                self.0.keyword_origin(key)
             }
         }
-    };
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_extension_keywords(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+
+    let doc = DocString::new(
+        "Keywords defined by extensions to the standard.".into(),
+        vec![
+            "These were matched by ``keyword_extensions`` when read and \
+             will be written as standard keywords. Keys include the \
+             leading *$*."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_dict(PyType::Str, PyType::Str),
+            None,
+        )),
+    )
+    .doc();
 
     quote! {
         #[pymethods]
-        impl #i {
-            #q
+        impl #t {
+            #doc
+            #[getter]
+            fn extension_keywords(&self) -> std::collections::HashMap<String, String> {
+                self.0
+                    .extension_keywords()
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.clone()))
+                    .collect()
+            }
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_unset_temporal(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-    let timestep_path = keyword_path("Timestep");
+pub fn impl_core_display_names(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let format_path = quote!(fireflow_core::channels::DisplayNameFormat);
 
-    let make_doc = |has_timestep: bool, has_force: bool| {
-        let s = "Convert the temporal measurement to an optical measurement.".into();
-        let p = if has_force {
-            Some(DocArg::new_param_def(
-                "force".into(),
-                PyType::Bool,
-                "If ``True`` and current time measurement has data which cannot \
-                 be converted to optical, force the conversion anyways. \
-                 Otherwise raise an exception."
+    let doc = DocString::new(
+        "Return names of all measurements for display.".into(),
+        vec![
+            "This is meant for plot axes and legends. *$PnS* will be ignored \
+             if it is blank or the same as *$PnN*. Names will be in the same \
+             order as the measurements."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![
+            DocArg::new_param_def(
+                "format".into(),
+                PyType::PyClass("pyreflow.typing.DisplayNameFormat".into()),
+                "Which keywords to use. ``\"longname\"`` uses *$PnS* if \
+                 present and *$PnN* otherwise, ``\"shortname\"`` always uses \
+                 *$PnN*, and ``\"both\"`` uses *$PnN* followed by *$PnS* if \
+                 present."
                     .into(),
-                DocDefault::Bool(false),
-            ))
-        } else {
-            None
-        }
-        .into_iter()
-        .collect();
-        let (rt, rd) = if has_timestep {
-            (
-                PyType::new_opt(PyType::Float),
-                "Value of *$TIMESTEP* if time measurement was present.".into(),
-            )
-        } else {
-            (
-                PyType::Bool,
-                "``True`` if temporal measurement was present and converted, \
-                 ``False`` if there was not a temporal measurement."
+                DocDefault::Other(quote!(#format_path::default()), "\"longname\"".into()),
+            ),
+            DocArg::new_param_def(
+                "separator".into(),
+                PyType::Str,
+                "Text between *$PnN* and *$PnS* when ``format`` is \
+                 ``\"both\"``."
                     .into(),
-            )
-        };
-        DocString::new(
-            s,
-            vec![],
-            DocSelf::PySelf,
-            p,
-            Some(DocReturn::new(rt, Some(rd))),
-        )
-    };
+                DocDefault::Other(quote!(" :: ".into()), "\" :: \"".into()),
+            ),
+        ],
+        Some(DocReturn::new(PyType::new_list(PyType::Str), None)),
+    );
 
-    let q = if version == Version::FCS2_0 {
-        let doc = make_doc(false, false);
-        quote! {
-            #doc
-            fn unset_temporal(&mut self) -> bool {
-                self.0.unset_temporal().is_some()
-            }
-        }
-    } else if version < Version::FCS3_2 {
-        let doc = make_doc(true, false);
-        quote! {
+    quote! {
+        #[pymethods]
+        impl #t {
             #doc
-            fn unset_temporal(&mut self) -> Option<#timestep_path> {
-                self.0.unset_temporal()
+            fn display_names(&self, format: #format_path, separator: String) -> Vec<String> {
+                let conf = fireflow_core::channels::DisplayNameConfig { format, separator };
+                self.0.display_names(&conf)
             }
         }
-    } else {
-        let doc = make_doc(true, true);
-        quote! {
-            #doc
-            fn unset_temporal(&mut self, force: bool) -> PyResult<Option<#timestep_path>> {
-                self.0.unset_temporal_lossy(force).py_termfail_resolve()
-            }
-        }
-    };
-
-    quote! {
-        #[pymethods]
-        impl #i {
-            #q
-        }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_rename_temporal(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_pycore(&i).1;
-    let shortname_path = shortname_path();
+pub fn impl_core_keyword_diff(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let is_dataset = split_ident_version_pycore(&t).0;
+    let diff_type = PyType::PyClass("pyreflow.typing.KeywordDiff".into());
 
-    let doc = DocString::new(
-        "Rename temporal measurement if present.".into(),
-        vec![],
+    let diff_doc = DocString::new(
+        "Return changes needed to turn keywords in this into ``other``.".into(),
+        vec![
+            "This considers all keywords which would be written to *TEXT* \
+             (standard and nonstandard) except for *$TOT* and offsets. Keys \
+             are compared case-insensitively."
+                .into(),
+        ],
         DocSelf::PySelf,
-        vec![param_name("New name to assign.")],
-        Some(DocReturn::new(
-            PyType::new_opt(PyType::Bool),
-            Some("Previous name if present".into()),
-        )),
+        vec![DocArg::new_param(
+            "other".into(),
+            PyType::new_dict(PyType::Str, PyType::Str),
+            "Keywords to compare against.".into(),
+        )],
+        Some(DocReturn::new(diff_type.clone(), None)),
     );
 
-    quote! {
-        #[pymethods]
-        impl #i {
-            #doc
-            fn rename_temporal(&mut self, name: #shortname_path) -> Option<#shortname_path> {
-                self.0.rename_temporal(name)
-            }
-        }
-    }
-    .into()
-}
-
-#[proc_macro]
-pub fn impl_core_all_transforms_attr(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-    let scale_path = quote!(fireflow_core::text::scale::Scale);
-    let xform_path = quote!(fireflow_core::core::ScaleTransform);
-
-    let log_pytype = PyType::Tuple(vec![PyType::Float, PyType::Float]);
-    let q = if version == Version::FCS2_0 {
-        let s0 = "Will be ``()`` for linear scaling (``0,0`` in FCS encoding), \
-                   a 2-tuple for log scaling, or ``None`` if missing."
-            .into();
-        let s1 = "The temporal measurement must always be ``()``. Setting it \
-                  to another value will raise an exception."
-            .into();
-        // TODO this will probably end up not being DRY
-        let doc = DocString::new(
-            "The value for *$PnE* for all measurements.".into(),
-            vec![s0, s1],
-            DocSelf::PySelf,
-            vec![],
-            Some(DocReturn::new(
-                PyType::new_list(PyType::new_union(vec![
-                    PyType::new_unit(),
-                    log_pytype,
-                    PyType::None,
-                ])),
-                None,
-            )),
-        )
-        .doc();
-        quote! {
-            #doc
-            #[getter]
-            fn get_all_scales(&self) -> Vec<Option<#scale_path>> {
-                self.0.scales().collect()
-            }
-
-            #[setter]
-            fn set_all_scales(&mut self, scales: Vec<Option<#scale_path>>) -> PyResult<()> {
-                self.0.set_scales(scales).py_termfail_resolve_nowarn()
-            }
-        }
+    let s1 = if is_dataset {
+        "The result must have the same number of measurements as there are \
+         columns in *DATA*, which will not be changed."
     } else {
-        let sum = "The value for *$PnE* and/or *$PnG* for all measurements.";
-        let s0 = "Collectively these keywords correspond to scale transforms.";
-        let s1 = "If scaling is linear, return a float which corresponds to the \
-                  value of *$PnG* when *$PnE* is ``0,0``. If scaling is logarithmic, \
-                  return a pair of floats, corresponding to unset *$PnG* and the \
-                  non-``0,0`` value of *$PnE*.";
-        let s2 = "The FCS standards disallow any other combinations.";
-        let s3 = "The temporal measurement will always be ``1.0``, corresponding \
-                  to an identity transform. Setting it to another value will \
-                  raise an exception.";
-        let doc = DocString::new(
-            sum.into(),
-            vec![s0.into(), s1.into(), s2.into(), s3.into()],
-            DocSelf::PySelf,
-            vec![],
-            Some(DocReturn::new(
-                PyType::new_list(PyType::new_union2(PyType::Float, log_pytype)),
-                None,
-            )),
-        )
-        .doc();
-        quote! {
-            #doc
-            #[getter]
-            fn get_all_scale_transforms(&self) -> Vec<#xform_path> {
-                self.0.transforms().collect()
-            }
-
-            #[setter]
-            fn set_all_scale_transforms(&mut self, transforms: Vec<#xform_path>) -> PyResult<()> {
-                self.0.set_transforms(transforms).py_termfail_resolve_nowarn()
-            }
-        }
+        "Nonstandard keywords belonging to measurements will stay with their \
+         measurements."
     };
+    let apply_doc = DocString::new(
+        "Apply changes to keywords.".into(),
+        vec![
+            "Keywords will be changed as if read from a file of the same \
+             version. If the diff conflicts with the current keywords or \
+             the result cannot be standardized, raise exception and leave \
+             this unchanged."
+                .into(),
+            s1.into(),
+        ],
+        DocSelf::PySelf,
+        vec![DocArg::new_param(
+            "diff".into(),
+            diff_type,
+            "Changes to apply, usually from :py:meth:`keyword_diff`.".into(),
+        )],
+        None,
+    );
+
+    let diff = quote!(fireflow_core::text::diff::KeywordDiff);
 
     quote! {
         #[pymethods]
-        impl #i {
-            #q
+        impl #t {
+            #diff_doc
+            fn keyword_diff(&self, other: std::collections::HashMap<String, String>) -> #diff {
+                self.0.keyword_diff(&other)
+            }
+
+            #apply_doc
+            fn apply_diff(&mut self, diff: #diff) -> PyResult<()> {
+                self.0.apply_diff(&diff).py_termfail_resolve()
+            }
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_get_measurements(input: TokenStream) -> TokenStream {
+pub fn impl_core_align_to_panel(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
+    let is_dataset = split_ident_version_pycore(&i).0;
+    let shortname_path = shortname_path();
 
-    let element_path = element_path(version);
-    let named_vec_path = quote!(fireflow_core::text::named_vec::NamedVec);
+    let s0 = "Measurements will be reordered such that their names match \
+              ``reference``. Keywords which refer to measurements by name \
+              will be unaffected.";
+    let s1 = if is_dataset {
+        "Columns in the dataframe will be reordered to match."
+    } else {
+        "The data layout will be reordered to match."
+    };
+    let s2 = "Raise exception if *$COMP* or gating regions refer to \
+              measurements by index and any of these would be moved.";
 
     let doc = DocString::new(
-        "Get all measurements.".into(),
-        vec![],
+        "Reorder measurements to match a reference panel.".into(),
+        vec![s0.into(), s1.into(), s2.into()],
         DocSelf::PySelf,
-        vec![],
-        Some(DocReturn::new(
-            PyType::new_list(measurement_pytype(version)),
-            None,
-        )),
-    )
-    .doc();
+        vec![
+            DocArg::new_param(
+                "reference".into(),
+                PyType::new_list(PyType::Str),
+                "Measurement names in the desired order.".into(),
+            ),
+            DocArg::new_param_def(
+                "allow_missing".into(),
+                PyType::Bool,
+                "If ``True``, skip names in ``reference`` which are not \
+                 present, otherwise raise exception."
+                    .into(),
+                DocDefault::Bool(false),
+            ),
+            DocArg::new_param_def(
+                "allow_extra".into(),
+                PyType::Bool,
+                "If ``True``, append measurements not in ``reference`` in \
+                 their original order, otherwise raise exception."
+                    .into(),
+                DocDefault::Bool(false),
+            ),
+        ],
+        None,
+    );
 
     quote! {
         #[pymethods]
         impl #i {
             #doc
-            #[getter]
-            fn measurements(&self) -> Vec<#element_path> {
-                // This might seem inefficient since we are cloning
-                // everything, but if we want to map a python lambda
-                // function over the measurements we would need to to do
-                // this anyways, so simply returnig a copied list doesn't
-                // lose anything and keeps this API simpler.
-                let ms: &#named_vec_path<_, _, _, _> = self.0.as_ref();
-                ms.iter()
-                    .map(|(_, e)| e.bimap(|t| t.value.clone(), |o| o.value.clone()))
-                    .map(|v| v.inner_into())
-                    .collect()
+            fn align_to_panel(
+                &mut self,
+                reference: Vec<#shortname_path>,
+                allow_missing: bool,
+                allow_extra: bool,
+            ) -> PyResult<()> {
+                Ok(self.0.align_to_panel(&reference, allow_missing, allow_extra)?)
             }
         }
     }
@@ -1071,39 +1041,40 @@ pub fn impl_core_get_measurements(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_get_temporal(input: TokenStream) -> TokenStream {
-    let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-
-    let ttype = pytemporal(version);
-    let meas_index_path = meas_index_path();
-    let shortname_path = shortname_path();
-
+pub fn impl_core_conversion_log(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let log_type = PyType::new_opt(PyType::PyClass("pyreflow.typing.ConversionLog".into()));
+
+    let s0 = "This is stored as JSON in the nonstandard keyword \
+              *FIREFLOW_CONVERSION* and includes the source and target \
+              versions as well as the standard keywords which were added, \
+              removed, or changed. It is written when converting with \
+              ``record_conversion=True``.";
+    let s1 = "Raise exception if keyword is present but cannot be parsed.";
     let doc = DocString::new(
-        "Get the temporal measurement if it exists.".into(),
-        vec![],
+        "Summary of the last version conversion.".into(),
+        vec![s0.into(), s1.into()],
         DocSelf::PySelf,
         vec![],
-        Some(DocReturn::new(
-            PyType::new_opt(PyType::Tuple(vec![
-                PyType::Int,
-                PyType::Str,
-                temporal_pytype(version),
-            ])),
-            Some("Index, name, and measurement or ``None``".into()),
-        )),
+        Some(DocReturn::new(log_type, None)),
     )
     .doc();
 
+    let log = quote!(fireflow_core::text::changelog::ConversionLog);
+
     quote! {
         #[pymethods]
-        impl #i {
+        impl #t {
             #doc
             #[getter]
-            fn get_temporal(&self) -> Option<(#meas_index_path, #shortname_path, #ttype)> {
-                self.0
-                    .temporal()
-                    .map(|t| (t.index, t.key.clone(), t.value.clone().into()))
+            fn get_conversion_log(&self) -> PyResult<Option<#log>> {
+                Ok(self.0.conversion_log().transpose()?)
+            }
+
+            #[setter]
+            fn set_conversion_log(&mut self, log: Option<#log>) {
+                self.0.set_conversion_log(log)
             }
         }
     }
@@ -1111,83 +1082,217 @@ pub fn impl_core_get_temporal(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_get_measurement(input: TokenStream) -> TokenStream {
+pub fn impl_core_write_text(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
     let version = split_ident_version_pycore(&i).1;
+    let textdelim_path = textdelim_path();
 
-    let element_path = element_path(version);
-    let named_vec_path = quote!(fireflow_core::text::named_vec::NamedVec);
-    let meas_index_path = meas_index_path();
+    let write_2_0_warning = if version == Version::FCS2_0 {
+        Some("Will raise exception if file cannot fit within 99,999,999 bytes.".into())
+    } else {
+        None
+    };
 
-    let doc = DocString::new(
-        "Return measurement at index.".into(),
-        vec!["Raise exception if ``index`` not found.".into()],
+    let write_text_doc = DocString::new(
+        "Write data to path.".into(),
+        ["Resulting FCS file will include *HEADER* and *TEXT*.".into()]
+            .into_iter()
+            .chain(write_2_0_warning.clone())
+            .collect(),
         DocSelf::PySelf,
-        vec![param_index("Index to retrieve.")],
-        Some(DocReturn::new(measurement_pytype(version), None)),
+        vec![path_param(false), textdelim_param(), big_other_param()],
+        None,
     );
 
     quote! {
         #[pymethods]
         impl #i {
-            // TODO this should return name as well
-            #doc
-            fn measurement_at(&self, index: #meas_index_path) -> PyResult<#element_path> {
-                let ms: &#named_vec_path<_, _, _, _> = self.0.as_ref();
-                let m = ms.get(index)?;
-                Ok(m.bimap(|x| x.1.clone(), |x| x.1.clone()).inner_into())
+            #write_text_doc
+            fn write_text(
+                &self,
+                path: std::path::PathBuf,
+                delim: #textdelim_path,
+                big_other: bool
+            ) -> PyResult<()> {
+                let f = std::fs::File::options().write(true).create(true).open(path)?;
+                let mut h = std::io::BufWriter::new(f);
+                self.0.h_write_text(&mut h, delim, big_other).py_termfail_resolve_nowarn()
             }
-
-            // TODO return measurement with name
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_set_measurements(input: TokenStream) -> TokenStream {
+pub fn impl_core_write_dataset(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let (is_dataset, version) = split_ident_version_pycore(&i);
-
-    let meas_argtype = ArgData::new_measurements_arg(version).rstype;
+    let version = split_ident_version_pycore(&i).1;
+    let textdelim_path = textdelim_path();
 
-    let s = if is_dataset {
-        "layout and dataframe"
+    let write_2_0_warning = if version == Version::FCS2_0 {
+        Some("Will raise exception if file cannot fit within 99,999,999 bytes.".into())
     } else {
-        "layout"
+        None
     };
-    let ps = vec![format!(
-        "Length of ``measurements`` must match number of columns in existing {s}."
-    )];
+
+    let rounding_path = quote!(fireflow_core::config::FloatRounding);
+    let rounding_type = PyType::PyClass("pyreflow.typing.FloatRounding".into());
+    let control_path = quote!(fireflow_core::config::ControlCharPolicy);
+    let control_type = PyType::PyClass("pyreflow.typing.ControlCharPolicy".into());
+
     let doc = DocString::new(
-        "Set all measurements at once.".into(),
-        ps,
+        "Write data as an FCS file.".into(),
+        ["The resulting file will include *HEADER*, *TEXT*, *DATA*, \
+            *ANALYSIS*, and *OTHER* as they present from this class."
+            .into()]
+        .into_iter()
+        .chain(write_2_0_warning)
+        .collect(),
         DocSelf::PySelf,
         vec![
-            param_type_set_meas(version),
-            param_allow_shared_names(),
-            param_skip_index_check(),
+            path_param(false),
+            textdelim_param(),
+            big_other_param(),
+            DocArg::new_param_def(
+                "other_pairs".into(),
+                PyType::new_opt(PyType::Int),
+                "If given, write exactly this many *OTHER* offset pairs in \
+                 *HEADER*. Extra pairs will be written as 0,0, and raise \
+                 exception if there are more *OTHER* segments than this. \
+                 If ``None``, write one pair for each *OTHER* segment."
+                    .into(),
+                DocDefault::Option,
+            ),
+            DocArg::new_param_def(
+                "skip_conversion_check".into(),
+                PyType::Bool,
+                "Skip check to ensure that types of the dataframe match the \
+                 columns (*$PnB*, *$DATATYPE*, etc). If this is ``False``, \
+                 perform this check before writing, and raise exception on \
+                 failure. If ``True``, raise warnings as file is being \
+                 written. Skipping this is faster since the data needs to be \
+                 traversed twice to perform the conversion check, but may \
+                 result in loss of precision and/or truncation."
+                    .into(),
+                DocDefault::Bool(false),
+            ),
+            DocArg::new_param_def(
+                "segment_alignment".into(),
+                PyType::new_opt(PyType::Int),
+                "If given, start each segment at an offset which is a \
+                 multiple of this. Empty segments will not be moved."
+                    .into(),
+                DocDefault::Option,
+            ),
+            DocArg::new_param_def(
+                "padding".into(),
+                PyType::Int,
+                "Byte used to fill gaps between aligned segments. The gap \
+                 between *HEADER* and *TEXT* will always be filled with \
+                 spaces."
+                    .into(),
+                DocDefault::Other(quote!(0), "0".into()),
+            ),
+            DocArg::new_param_def(
+                "namespaces".into(),
+                PyType::new_list(PyType::Str),
+                "Prefixes of nonstandard keyword namespaces to check before \
+                 writing. Raise exception if any two overlap or if any key \
+                 in a namespace appears more than once in *TEXT*."
+                    .into(),
+                DocDefault::EmptyList,
+            ),
+            DocArg::new_param_def(
+                "float_rounding".into(),
+                rounding_type.clone(),
+                "How to handle fractional values in float columns whose \
+                 measurements are stored as integers. ``\"truncate\"`` \
+                 rounds toward zero and ``\"round\"`` rounds to the nearest \
+                 integer (halves away from zero). ``\"error\"`` leaves \
+                 values as-is, so any fractional value is a conversion loss \
+                 and will raise unless ``skip_conversion_check`` is ``True``."
+                    .into(),
+                DocDefault::Other(quote!(#rounding_path::default()), "\"error\"".into()),
+            ),
+            DocArg::new_param_def(
+                "float_rounding_overrides".into(),
+                PyType::new_dict(PyType::Int, rounding_type),
+                "Rounding to use for specific measurements (by index) instead \
+                 of ``float_rounding``."
+                    .into(),
+                DocDefault::EmptyDict,
+            ),
+            DocArg::new_param_def(
+                "control_chars".into(),
+                control_type,
+                "How to handle control characters (ie newlines) in keyword \
+                 values. ``\"preserve\"`` writes values as-is (doubling any \
+                 delimiters), while ``\"escape\"`` and ``\"strip\"`` replace \
+                 them with escape sequences or remove them with a warning."
+                    .into(),
+                DocDefault::Other(quote!(#control_path::default()), "\"preserve\"".into()),
+            ),
+            DocArg::new_param_def(
+                "record_provenance".into(),
+                PyType::Bool,
+                "If ``True``, record this library and its version as \
+                 *FIREFLOW_SOFTWARE*, replacing any existing value."
+                    .into(),
+                DocDefault::Bool(false),
+            ),
         ],
-        None,
+        Some(DocReturn::new(
+            PyType::PyClass("pyreflow.typing.WriteReport".into()),
+            Some(
+                "Number of values in each column which were truncated or \
+                 otherwise changed to fit their measurement type, and the \
+                 rounding applied to each float column written as integers. \
+                 Lossy counts will all be 0 unless ``skip_conversion_check`` \
+                 is ``True``."
+                    .into(),
+            ),
+        )),
     );
 
     quote! {
         #[pymethods]
         impl #i {
             #doc
-            fn set_measurements(
-                &mut self,
-                measurements: #meas_argtype,
-                allow_shared_names: bool,
-                skip_index_check: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .set_measurements(
-                        measurements.0.inner_into(),
-                        allow_shared_names,
-                        skip_index_check,
-                    )
-                    .py_termfail_resolve_nowarn()
+            #[allow(clippy::too_many_arguments)]
+            fn write_dataset(
+                &self,
+                path: std::path::PathBuf,
+                delim: #textdelim_path,
+                big_other: bool,
+                other_pairs: Option<usize>,
+                skip_conversion_check: bool,
+                segment_alignment: Option<std::num::NonZeroU64>,
+                padding: u8,
+                namespaces: Vec<fireflow_core::text::namespace::KeyNamespace>,
+                float_rounding: #rounding_path,
+                float_rounding_overrides: std::collections::HashMap<
+                    fireflow_core::text::index::MeasIndex,
+                    #rounding_path,
+                >,
+                control_chars: #control_path,
+                record_provenance: bool,
+            ) -> PyResult<fireflow_core::data::WriteReport> {
+                let f = std::fs::File::options().write(true).create(true).open(path)?;
+                let mut h = std::io::BufWriter::new(f);
+                let conf = fireflow_core::config::WriteConfig {
+                    delim,
+                    skip_conversion_check,
+                    big_other,
+                    other_pairs,
+                    segment_alignment,
+                    padding,
+                    namespaces,
+                    float_rounding,
+                    float_rounding_overrides,
+                    control_chars,
+                    record_provenance,
+                };
+                self.0.h_write_dataset(&mut h, &conf).py_termfail_resolve()
             }
         }
     }
@@ -1195,176 +1300,119 @@ pub fn impl_core_set_measurements(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_push_measurement(input: TokenStream) -> TokenStream {
+pub fn impl_core_all_peak_attrs(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let (is_dataset, version) = split_ident_version_pycore(&i);
-
-    let otype = pyoptical(version);
-    let ttype = pytemporal(version);
-
-    let range_path = keyword_path("Range");
-    let ver_shortname_path = versioned_shortname_path(version);
-    let any_fcs_col_path = quote!(fireflow_core::validated::dataframe::AnyFCSColumn);
-    let shortname_path = shortname_path();
-
-    let push_meas_doc = |is_optical: bool, hasdata: bool| {
-        let (meas_type, what) = if is_optical {
-            (optical_pytype(version), "optical")
-        } else {
-            (temporal_pytype(version), "temporal")
-        };
-        let param_meas = DocArg::new_param(
-            "meas".into(),
-            meas_type.clone(),
-            "The measurement to push.".into(),
-        );
-        let _param_col = if hasdata { Some(param_col()) } else { None };
-        let ps: Vec<_> = [param_meas]
-            .into_iter()
-            .chain(_param_col)
-            .chain([
-                param_name("Name of new measurement."),
-                param_range(),
-                param_notrunc(),
-            ])
-            .collect();
-        let summary = format!("Push {what} measurement to end of measurement vector.");
-        DocString::new(summary, vec![], DocSelf::PySelf, ps, None)
-    };
-
-    let push_opt_doc = push_meas_doc(true, false);
-    let push_tmp_doc = push_meas_doc(false, false);
-    let push_opt_data_doc = push_meas_doc(true, true);
-    let push_tmp_data_doc = push_meas_doc(false, true);
-
-    let q = if is_dataset {
-        quote! {
-            #push_opt_data_doc
-            fn push_optical(
-                &mut self,
-                meas: #otype,
-                col: #any_fcs_col_path,
-                name: #ver_shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .push_optical(name.into(), meas.into(), col, range, notrunc)
-                    .py_termfail_resolve()
-                    .void()
-            }
+    let _ = split_ident_version_pycore(&i).1;
 
-            #push_tmp_data_doc
-            fn push_temporal(
-                &mut self,
-                meas: #ttype,
-                col: #any_fcs_col_path,
-                name: #shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .push_temporal(name, meas.into(), col, range, notrunc)
-                    .py_termfail_resolve()
-            }
-        }
-    } else {
+    let go = |k: &str, i: &str, name: &str| {
+        let p = keyword_path(i);
+        let doc = DocString::new(
+            format!("The value of *$P{k}n* for all measurements."),
+            vec![],
+            DocSelf::PySelf,
+            vec![],
+            Some(DocReturn::new(PyType::new_list(PyType::Int), None)),
+        )
+        .doc();
+        let get = format_ident!("get_all_{name}");
+        let set = format_ident!("set_all_{name}");
         quote! {
-            #push_opt_doc
-            fn push_optical(
-                &mut self,
-                meas: #otype,
-                name: #ver_shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
+            #doc
+            #[getter]
+            fn #get(&self) -> Vec<Option<#p>> {
                 self.0
-                    .push_optical(name.into(), meas.into(), range, notrunc)
-                    .py_termfail_resolve()
-                    .void()
+                    .get_temporal_optical::<Option<#p>>()
+                    .map(|x| x.as_ref().copied())
+                    .collect()
             }
 
-            #push_tmp_doc
-            fn push_temporal(
-                &mut self,
-                meas: #ttype,
-                name: #shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .push_temporal(name, meas.into(), range, notrunc)
-                    .py_termfail_resolve()
+            #[setter]
+            fn #set(&mut self, xs: Vec<Option<#p>>) -> PyResult<()> {
+                Ok(self.0.set_temporal_optical(xs)?)
             }
         }
     };
 
+    let pkn = go("K", "PeakBin", "peak_bins");
+    let pknn = go("KN", "PeakNumber", "peak_sizes");
+
     quote! {
         #[pymethods]
         impl #i {
-            #q
+            #pkn
+            #pknn
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_remove_measurement(input: TokenStream) -> TokenStream {
+pub fn impl_core_all_shortnames_attr(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-
-    let element_path = element_path(version);
-    let ver_shortname_path = versioned_shortname_path(version);
+    let _ = split_ident_version_pycore(&i).1;
     let shortname_path = shortname_path();
-    let family_path = versioned_family_path(version);
-    let meas_index_path = meas_index_path();
 
-    let by_name_doc = DocString::new(
-        "Remove a measurement with a given name.".into(),
-        vec!["Raise exception if ``name`` not found.".into()],
+    let doc = DocString::new(
+        "Value of *$PnN* for all measurements.".into(),
+        vec!["Strings are unique and cannot contain commas.".into()],
         DocSelf::PySelf,
-        vec![param_name("Name to remove")],
-        Some(DocReturn::new(
-            PyType::Tuple(vec![PyType::Int, measurement_pytype(version)]),
-            Some("Index and measurement object".into()),
-        )),
-    );
+        vec![],
+        Some(DocReturn::new(PyType::new_list(PyType::Str), None)),
+    )
+    .doc();
 
-    let by_index_doc = DocString::new(
-        "Remove a measurement with a given index.".into(),
-        vec!["Raise exception if ``index`` not found.".into()],
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            #[getter]
+            fn get_all_shortnames(&self) -> Vec<#shortname_path> {
+                self.0.all_shortnames()
+            }
+
+            #[setter]
+            fn set_all_shortnames(&mut self, names: Vec<#shortname_path>) -> PyResult<()> {
+                Ok(self.0.set_all_shortnames(names).void()?)
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_all_shortnames_maybe_attr(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_pycore(&i).1;
+    let shortname_path = shortname_path();
+
+    let doc = DocString::new(
+        "The possibly-empty values of *$PnN* for all measurements.".into(),
+        vec!["*$PnN* is optional for this FCS version so values may be ``None``.".into()],
         DocSelf::PySelf,
-        vec![param_index("Index to remove")],
+        vec![],
         Some(DocReturn::new(
-            PyType::Tuple(vec![PyType::Str, measurement_pytype(version)]),
-            Some("Name and measurement object".into()),
+            PyType::new_list(PyType::new_opt(PyType::Str)),
+            None,
         )),
-    );
-
-    let bare_element_path = quote!(fireflow_core::text::named_vec::Element);
+    )
+    .doc();
 
     quote! {
         #[pymethods]
         impl #i {
-            #by_name_doc
-            fn remove_measurement_by_name(
-                &mut self,
-                name: #shortname_path,
-            ) -> PyResult<(#meas_index_path, #element_path)> {
-                Ok(self
-                   .0
-                   .remove_measurement_by_name(&name)
-                   .map(|(i, x)| (i, x.inner_into()))?)
+            #doc
+            #[getter]
+            fn get_all_shortnames_maybe(&self) -> Vec<Option<#shortname_path>> {
+                self.0
+                    .shortnames_maybe()
+                    .into_iter()
+                    .map(|x| x.cloned())
+                    .collect()
             }
 
-            #by_index_doc
-            fn remove_measurement_by_index(
-                &mut self,
-                index: #meas_index_path,
-            ) -> PyResult<(#ver_shortname_path, #element_path)> {
-                let r = self.0.remove_measurement_by_index(index)?;
-                let (n, v) = #bare_element_path::unzip::<#family_path>(r);
-                Ok((n.0, v.inner_into()))
+            #[setter]
+            fn set_all_shortnames_maybe(&mut self, names: Vec<Option<#shortname_path>>) -> PyResult<()> {
+                Ok(self.0.set_measurement_shortnames_maybe(names).void()?)
             }
         }
     }
@@ -1372,188 +1420,1570 @@ pub fn impl_core_remove_measurement(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_insert_measurement(input: TokenStream) -> TokenStream {
+pub fn impl_core_get_set_timestep(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let (is_dataset, version) = split_ident_version_pycore(&i);
+    let _ = split_ident_version_pycore(&i).1;
+    let timestep_path = keyword_path("Timestep");
 
-    let otype = pyoptical(version);
-    let ttype = pytemporal(version);
-    let meas_index_path = meas_index_path();
+    let t = PyType::new_opt(PyType::Float);
+    let get_doc = DocString::new(
+        "The value of *$TIMESTEP*".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(t.clone(), None)),
+    )
+    .doc();
+    let set_doc = DocString::new(
+        "Set the *$TIMESTEP* if time measurement is present.".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![DocArg::new_param(
+            "timestep".into(),
+            PyType::Float,
+            "The timestep to set. Must be greater than zero.".into(),
+        )],
+        Some(DocReturn::new(
+            t,
+            Some("Previous *$TIMESTEP* if present.".into()),
+        )),
+    );
+    let q = quote! {
+        #get_doc
+        #[getter]
+        fn get_timestep(&self) -> Option<#timestep_path> {
+            self.0.timestep().copied()
+        }
+
+        #set_doc
+        fn set_timestep(&mut self, timestep: #timestep_path) -> Option<#timestep_path> {
+            self.0.set_timestep(timestep)
+        }
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_set_temporal(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
     let shortname_path = shortname_path();
-    let ver_shortname_path = versioned_shortname_path(version);
-    let range_path = keyword_path("Range");
-    let any_fcs_col_path = quote!(fireflow_core::validated::dataframe::AnyFCSColumn);
+    let timestep_path = keyword_path("Timestep");
+    let meas_index_path = meas_index_path();
 
-    let insert_meas_doc = |is_optical: bool, hasdata: bool| {
-        let (meas_type, what) = if is_optical {
-            (optical_pytype(version), "optical")
+    let make_doc = |has_timestep: bool, has_index: bool| {
+        let name = param_name("Name to set to temporal.");
+        let index = DocArg::new_param("index".into(), PyType::Int, "Index to set".into());
+        let (i, p) = if has_index {
+            ("index", index)
         } else {
-            (temporal_pytype(version), "temporal")
+            ("name", name)
         };
-        let param_meas = DocArg::new_param(
-            "meas".into(),
-            meas_type.clone(),
-            "The measurement to insert.".into(),
+        let timestep = if has_timestep {
+            Some(DocArg::new_param(
+                "timestep".into(),
+                PyType::Float,
+                "The value of *$TIMESTEP* to use.".into(),
+            ))
+        } else {
+            None
+        };
+        let force = DocArg::new_param_def(
+            "force".into(),
+            PyType::Bool,
+            "If ``True`` remove any optical-specific metadata (detectors, \
+             lasers, etc) without raising an exception. Defauls to ``False``."
+                .into(),
+            DocDefault::Bool(false),
         );
-        let _param_col = if hasdata { Some(param_col()) } else { None };
-        let summary = format!("Insert {what} measurement at position in measurement vector.");
-        let ps: Vec<_> = [
-            param_index("Position at which to insert new measurement."),
-            param_meas,
-        ]
-        .into_iter()
-        .chain(_param_col)
-        .chain([
-            param_name("Name of new measurement."),
-            param_range(),
-            param_notrunc(),
-        ])
-        .collect();
-        DocString::new(summary, vec![], DocSelf::PySelf, ps, None)
+        let ps = [p].into_iter().chain(timestep).chain([force]).collect();
+        DocString::new(
+            format!("Set the temporal measurement to a given {i}."),
+            vec![],
+            DocSelf::PySelf,
+            ps,
+            Some(DocReturn::new(
+                PyType::Bool,
+                Some(format!(
+                    "``True`` if temporal measurement was set, which will \
+                     happen for all cases except when the time measurement is \
+                     already set to ``{i}``."
+                )),
+            )),
+        )
     };
 
-    let insert_opt_doc = insert_meas_doc(true, false);
-    let insert_tmp_doc = insert_meas_doc(false, false);
-    let insert_opt_data_doc = insert_meas_doc(true, true);
-    let insert_tmp_data_doc = insert_meas_doc(false, true);
+    let q = if version == Version::FCS2_0 {
+        let name_doc = make_doc(false, false);
+        let index_doc = make_doc(false, true);
+        quote! {
+            #name_doc
+            fn set_temporal(&mut self, name: #shortname_path, force: bool) -> PyResult<bool> {
+                self.0.set_temporal(&name, (), force).py_termfail_resolve()
+            }
 
-    let q = if is_dataset {
+            #index_doc
+            fn set_temporal_at(&mut self, index: #meas_index_path, force: bool) -> PyResult<bool> {
+                self.0.set_temporal_at(index, (), force).py_termfail_resolve()
+            }
+        }
+    } else {
+        let name_doc = make_doc(true, false);
+        let index_doc = make_doc(true, true);
         quote! {
-            #insert_opt_data_doc
-            fn insert_optical(
+            #name_doc
+            fn set_temporal(
                 &mut self,
-                index: #meas_index_path,
-                meas: #otype,
-                col: #any_fcs_col_path,
-                name: #ver_shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
+                name: #shortname_path,
+                timestep: #timestep_path,
+                force: bool,
+            ) -> PyResult<bool> {
                 self.0
-                    .insert_optical(index, name.into(), meas.into(), col, range, notrunc)
+                    .set_temporal(&name, timestep, force)
                     .py_termfail_resolve()
-                    .void()
             }
 
-            #insert_tmp_data_doc
-            fn insert_temporal(
+            #index_doc
+            fn set_temporal_at(
                 &mut self,
                 index: #meas_index_path,
-                meas: #ttype,
-                col: #any_fcs_col_path,
-                name: #shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
+                timestep: #timestep_path,
+                force: bool,
+            ) -> PyResult<bool> {
                 self.0
-                    .insert_temporal(index, name, meas.into(), col, range, notrunc)
+                    .set_temporal_at(index, timestep, force)
                     .py_termfail_resolve()
             }
         }
-    } else {
-        quote! {
-            #insert_opt_doc
-            fn insert_optical(
-                &mut self,
-                index: #meas_index_path,
-                meas: #otype,
-                name: #ver_shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .insert_optical(index, name.into(), meas.into(), range, notrunc)
-                    .py_termfail_resolve()
-                    .void()
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_unset_temporal(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+    let timestep_path = keyword_path("Timestep");
+
+    let make_doc = |has_timestep: bool, has_force: bool| {
+        let s = "Convert the temporal measurement to an optical measurement.".into();
+        let p = if has_force {
+            Some(DocArg::new_param_def(
+                "force".into(),
+                PyType::Bool,
+                "If ``True`` and current time measurement has data which cannot \
+                 be converted to optical, force the conversion anyways. \
+                 Otherwise raise an exception."
+                    .into(),
+                DocDefault::Bool(false),
+            ))
+        } else {
+            None
+        }
+        .into_iter()
+        .collect();
+        let (rt, rd) = if has_timestep {
+            (
+                PyType::new_opt(PyType::Float),
+                "Value of *$TIMESTEP* if time measurement was present.".into(),
+            )
+        } else {
+            (
+                PyType::Bool,
+                "``True`` if temporal measurement was present and converted, \
+                 ``False`` if there was not a temporal measurement."
+                    .into(),
+            )
+        };
+        DocString::new(
+            s,
+            vec![],
+            DocSelf::PySelf,
+            p,
+            Some(DocReturn::new(rt, Some(rd))),
+        )
+    };
+
+    let q = if version == Version::FCS2_0 {
+        let doc = make_doc(false, false);
+        quote! {
+            #doc
+            fn unset_temporal(&mut self) -> bool {
+                self.0.unset_temporal().is_some()
+            }
+        }
+    } else if version < Version::FCS3_2 {
+        let doc = make_doc(true, false);
+        quote! {
+            #doc
+            fn unset_temporal(&mut self) -> Option<#timestep_path> {
+                self.0.unset_temporal()
+            }
+        }
+    } else {
+        let doc = make_doc(true, true);
+        quote! {
+            #doc
+            fn unset_temporal(&mut self, force: bool) -> PyResult<Option<#timestep_path>> {
+                self.0.unset_temporal_lossy(force).py_termfail_resolve()
+            }
+        }
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_rename_temporal(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_pycore(&i).1;
+    let shortname_path = shortname_path();
+
+    let doc = DocString::new(
+        "Rename temporal measurement if present.".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![param_name("New name to assign.")],
+        Some(DocReturn::new(
+            PyType::new_opt(PyType::Bool),
+            Some("Previous name if present".into()),
+        )),
+    );
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn rename_temporal(&mut self, name: #shortname_path) -> Option<#shortname_path> {
+                self.0.rename_temporal(name)
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_all_transforms_attr(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+    let scale_path = quote!(fireflow_core::text::scale::Scale);
+    let xform_path = quote!(fireflow_core::core::ScaleTransform);
+
+    let log_pytype = PyType::Tuple(vec![PyType::Float, PyType::Float]);
+    let q = if version == Version::FCS2_0 {
+        let s0 = "Will be ``()`` for linear scaling (``0,0`` in FCS encoding), \
+                   a 2-tuple for log scaling, or ``None`` if missing."
+            .into();
+        let s1 = "The temporal measurement must always be ``()``. Setting it \
+                  to another value will raise an exception."
+            .into();
+        // TODO this will probably end up not being DRY
+        let doc = DocString::new(
+            "The value for *$PnE* for all measurements.".into(),
+            vec![s0, s1],
+            DocSelf::PySelf,
+            vec![],
+            Some(DocReturn::new(
+                PyType::new_list(PyType::new_union(vec![
+                    PyType::new_unit(),
+                    log_pytype.clone(),
+                    PyType::None,
+                ])),
+                None,
+            )),
+        )
+        .doc();
+        let xs0 = "This is equivalent to :py:attr:`all_scales` except that \
+                   missing *$PnE* will be returned as linear (``1.0``), which \
+                   is consistent with the value for later FCS versions."
+            .into();
+        let xs1 = "Since FCS 2.0 has no *$PnG*, linear transforms must be \
+                   ``1.0``. Setting any other gain will raise an exception."
+            .into();
+        let xform_doc = DocString::new(
+            "The value for *$PnE* for all measurements as scale transforms.".into(),
+            vec![xs0, xs1],
+            DocSelf::PySelf,
+            vec![],
+            Some(DocReturn::new(
+                PyType::new_list(PyType::new_union2(PyType::Float, log_pytype)),
+                None,
+            )),
+        )
+        .doc();
+        quote! {
+            #doc
+            #[getter]
+            fn get_all_scales(&self) -> Vec<Option<#scale_path>> {
+                self.0.scales().collect()
+            }
+
+            #[setter]
+            fn set_all_scales(&mut self, scales: Vec<Option<#scale_path>>) -> PyResult<()> {
+                self.0.set_scales(scales).py_termfail_resolve_nowarn()
+            }
+
+            #xform_doc
+            #[getter]
+            fn get_all_scale_transforms(&self) -> Vec<#xform_path> {
+                self.0.transforms().collect()
+            }
+
+            #[setter]
+            fn set_all_scale_transforms(&mut self, transforms: Vec<#xform_path>) -> PyResult<()> {
+                self.0
+                    .set_scales_from_transforms(transforms)
+                    .py_termfail_resolve_nowarn()
+            }
+        }
+    } else {
+        let sum = "The value for *$PnE* and/or *$PnG* for all measurements.";
+        let s0 = "Collectively these keywords correspond to scale transforms.";
+        let s1 = "If scaling is linear, return a float which corresponds to the \
+                  value of *$PnG* when *$PnE* is ``0,0``. If scaling is logarithmic, \
+                  return a pair of floats, corresponding to unset *$PnG* and the \
+                  non-``0,0`` value of *$PnE*.";
+        let s2 = "The FCS standards disallow any other combinations.";
+        let s3 = "The temporal measurement will always be ``1.0``, corresponding \
+                  to an identity transform. Setting it to another value will \
+                  raise an exception.";
+        let doc = DocString::new(
+            sum.into(),
+            vec![s0.into(), s1.into(), s2.into(), s3.into()],
+            DocSelf::PySelf,
+            vec![],
+            Some(DocReturn::new(
+                PyType::new_list(PyType::new_union2(PyType::Float, log_pytype)),
+                None,
+            )),
+        )
+        .doc();
+        quote! {
+            #doc
+            #[getter]
+            fn get_all_scale_transforms(&self) -> Vec<#xform_path> {
+                self.0.transforms().collect()
+            }
+
+            #[setter]
+            fn set_all_scale_transforms(&mut self, transforms: Vec<#xform_path>) -> PyResult<()> {
+                self.0.set_transforms(transforms).py_termfail_resolve_nowarn()
+            }
+        }
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_get_measurements(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let element_path = element_path(version);
+    let named_vec_path = quote!(fireflow_core::text::named_vec::NamedVec);
+
+    let doc = DocString::new(
+        "Get all measurements.".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_list(measurement_pytype(version)),
+            None,
+        )),
+    )
+    .doc();
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            #[getter]
+            fn measurements(&self) -> Vec<#element_path> {
+                // This might seem inefficient since we are cloning
+                // everything, but if we want to map a python lambda
+                // function over the measurements we would need to to do
+                // this anyways, so simply returnig a copied list doesn't
+                // lose anything and keeps this API simpler.
+                let ms: &#named_vec_path<_, _, _, _> = self.0.as_ref();
+                ms.iter()
+                    .map(|(_, e)| e.bimap(|t| t.value.clone(), |o| o.value.clone()))
+                    .map(|v| v.inner_into())
+                    .collect()
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_get_temporal(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let ttype = pytemporal(version);
+    let meas_index_path = meas_index_path();
+    let shortname_path = shortname_path();
+
+    let doc = DocString::new(
+        "Get the temporal measurement if it exists.".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_opt(PyType::Tuple(vec![
+                PyType::Int,
+                PyType::Str,
+                temporal_pytype(version),
+            ])),
+            Some("Index, name, and measurement or ``None``".into()),
+        )),
+    )
+    .doc();
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            #[getter]
+            fn get_temporal(&self) -> Option<(#meas_index_path, #shortname_path, #ttype)> {
+                self.0
+                    .temporal()
+                    .map(|t| (t.index, t.key.clone(), t.value.clone().into()))
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_get_measurement(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let element_path = element_path(version);
+    let named_vec_path = quote!(fireflow_core::text::named_vec::NamedVec);
+    let meas_index_path = meas_index_path();
+
+    let doc = DocString::new(
+        "Return measurement at index.".into(),
+        vec!["Raise exception if ``index`` not found.".into()],
+        DocSelf::PySelf,
+        vec![param_index("Index to retrieve.")],
+        Some(DocReturn::new(measurement_pytype(version), None)),
+    );
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            // TODO this should return name as well
+            #doc
+            fn measurement_at(&self, index: #meas_index_path) -> PyResult<#element_path> {
+                let ms: &#named_vec_path<_, _, _, _> = self.0.as_ref();
+                let m = ms.get(index)?;
+                Ok(m.bimap(|x| x.1.clone(), |x| x.1.clone()).inner_into())
+            }
+
+            // TODO return measurement with name
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_set_measurements(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let (is_dataset, version) = split_ident_version_pycore(&i);
+
+    let meas_argtype = ArgData::new_measurements_arg(version).rstype;
+
+    let s = if is_dataset {
+        "layout and dataframe"
+    } else {
+        "layout"
+    };
+    let ps = vec![format!(
+        "Length of ``measurements`` must match number of columns in existing {s}."
+    )];
+    let doc = DocString::new(
+        "Set all measurements at once.".into(),
+        ps,
+        DocSelf::PySelf,
+        vec![
+            param_type_set_meas(version),
+            param_allow_shared_names(),
+            param_skip_index_check(),
+        ],
+        None,
+    );
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn set_measurements(
+                &mut self,
+                measurements: #meas_argtype,
+                allow_shared_names: bool,
+                skip_index_check: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .set_measurements(
+                        measurements.0.inner_into(),
+                        allow_shared_names,
+                        skip_index_check,
+                    )
+                    .py_termfail_resolve_nowarn()
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_push_measurement(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let (is_dataset, version) = split_ident_version_pycore(&i);
+
+    let otype = pyoptical(version);
+    let ttype = pytemporal(version);
+
+    let range_path = keyword_path("Range");
+    let ver_shortname_path = versioned_shortname_path(version);
+    let any_fcs_col_path = quote!(fireflow_core::validated::dataframe::AnyFCSColumn);
+    let shortname_path = shortname_path();
+
+    let push_meas_doc = |is_optical: bool, hasdata: bool| {
+        let (meas_type, what) = if is_optical {
+            (optical_pytype(version), "optical")
+        } else {
+            (temporal_pytype(version), "temporal")
+        };
+        let param_meas = DocArg::new_param(
+            "meas".into(),
+            meas_type.clone(),
+            "The measurement to push.".into(),
+        );
+        let _param_col = if hasdata { Some(param_col()) } else { None };
+        let ps: Vec<_> = [param_meas]
+            .into_iter()
+            .chain(_param_col)
+            .chain([
+                param_name("Name of new measurement."),
+                param_range(),
+                param_notrunc(),
+            ])
+            .collect();
+        let summary = format!("Push {what} measurement to end of measurement vector.");
+        DocString::new(summary, vec![], DocSelf::PySelf, ps, None)
+    };
+
+    let push_opt_doc = push_meas_doc(true, false);
+    let push_tmp_doc = push_meas_doc(false, false);
+    let push_opt_data_doc = push_meas_doc(true, true);
+    let push_tmp_data_doc = push_meas_doc(false, true);
+
+    let push_like_doc = |hasdata: bool| {
+        let _param_col = if hasdata { Some(param_col()) } else { None };
+        let ps: Vec<_> = [param_index(
+            "Index of optical measurement to use as a template.",
+        )]
+        .into_iter()
+        .chain(_param_col)
+        .chain([
+            param_name("Name of new measurement."),
+            param_range(),
+            param_notrunc(),
+        ])
+        .collect();
+        DocString::new(
+            "Push optical measurement to end using another as a template.".into(),
+            vec![
+                "The new measurement will be a copy of the optical measurement \
+                 at ``index`` except for *$PnS* and non-standard keywords."
+                    .into(),
+                "Raise exception if ``index`` is out of bounds or refers to \
+                 the time measurement."
+                    .into(),
+            ],
+            DocSelf::PySelf,
+            ps,
+            None,
+        )
+    };
+
+    let push_like_text_doc = push_like_doc(false);
+    let push_like_data_doc = push_like_doc(true);
+    let meas_index_path = meas_index_path();
+
+    let q = if is_dataset {
+        quote! {
+            #push_opt_data_doc
+            fn push_optical(
+                &mut self,
+                meas: #otype,
+                col: #any_fcs_col_path,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_optical(name.into(), meas.into(), col, range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #push_like_data_doc
+            fn push_optical_like(
+                &mut self,
+                index: #meas_index_path,
+                col: #any_fcs_col_path,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_optical_like(name.into(), index, col, range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #push_tmp_data_doc
+            fn push_temporal(
+                &mut self,
+                meas: #ttype,
+                col: #any_fcs_col_path,
+                name: #shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_temporal(name, meas.into(), col, range, notrunc)
+                    .py_termfail_resolve()
+            }
+        }
+    } else {
+        quote! {
+            #push_opt_doc
+            fn push_optical(
+                &mut self,
+                meas: #otype,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_optical(name.into(), meas.into(), range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #push_like_text_doc
+            fn push_optical_like(
+                &mut self,
+                index: #meas_index_path,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_optical_like(name.into(), index, range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #push_tmp_doc
+            fn push_temporal(
+                &mut self,
+                meas: #ttype,
+                name: #shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .push_temporal(name, meas.into(), range, notrunc)
+                    .py_termfail_resolve()
+            }
+        }
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_remove_measurement(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let element_path = element_path(version);
+    let ver_shortname_path = versioned_shortname_path(version);
+    let shortname_path = shortname_path();
+    let family_path = versioned_family_path(version);
+    let meas_index_path = meas_index_path();
+
+    let by_name_doc = DocString::new(
+        "Remove a measurement with a given name.".into(),
+        vec!["Raise exception if ``name`` not found.".into()],
+        DocSelf::PySelf,
+        vec![param_name("Name to remove")],
+        Some(DocReturn::new(
+            PyType::Tuple(vec![PyType::Int, measurement_pytype(version)]),
+            Some("Index and measurement object".into()),
+        )),
+    );
+
+    let by_index_doc = DocString::new(
+        "Remove a measurement with a given index.".into(),
+        vec!["Raise exception if ``index`` not found.".into()],
+        DocSelf::PySelf,
+        vec![param_index("Index to remove")],
+        Some(DocReturn::new(
+            PyType::Tuple(vec![PyType::Str, measurement_pytype(version)]),
+            Some("Name and measurement object".into()),
+        )),
+    );
+
+    let links_doc = DocString::new(
+        "Remove a measurement and update everything which refers to it.".into(),
+        vec![
+            "Unlike :py:meth:`remove_measurement_by_name`, this will not fail \
+             if other keywords refer to the measurement. It will be dropped \
+             from *$SPILLOVER*/*$COMP* (removing these if they would have \
+             less than two measurements), *$UNSTAINEDCENTERS*, and *$TR*. \
+             Gating regions which refer to it will be removed (along with \
+             *$GATING* if it refers to these regions) and all other regions \
+             will be shifted to point to the same measurements as before."
+                .into(),
+            "Raise exception if ``name`` not found, in which case nothing \
+             will be changed."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![param_name("Name to remove")],
+        Some(DocReturn::new(
+            PyType::PyClass("pyreflow.typing.MeasRemoval".into()),
+            Some("Index of removed measurement and changes to other keywords".into()),
+        )),
+    );
+
+    let bare_element_path = quote!(fireflow_core::text::named_vec::Element);
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #by_name_doc
+            fn remove_measurement_by_name(
+                &mut self,
+                name: #shortname_path,
+            ) -> PyResult<(#meas_index_path, #element_path)> {
+                Ok(self
+                   .0
+                   .remove_measurement_by_name(&name)
+                   .map(|(i, x)| (i, x.inner_into()))?)
+            }
+
+            #by_index_doc
+            fn remove_measurement_by_index(
+                &mut self,
+                index: #meas_index_path,
+            ) -> PyResult<(#ver_shortname_path, #element_path)> {
+                let r = self.0.remove_measurement_by_index(index)?;
+                let (n, v) = #bare_element_path::unzip::<#family_path>(r);
+                Ok((n.0, v.inner_into()))
+            }
+
+            #links_doc
+            fn remove_measurement_and_links(
+                &mut self,
+                name: #shortname_path,
+            ) -> PyResult<fireflow_core::core::MeasRemoval> {
+                Ok(self.0.remove_measurement_and_links(&name)?)
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_insert_measurement(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let (is_dataset, version) = split_ident_version_pycore(&i);
+
+    let otype = pyoptical(version);
+    let ttype = pytemporal(version);
+    let meas_index_path = meas_index_path();
+    let shortname_path = shortname_path();
+    let ver_shortname_path = versioned_shortname_path(version);
+    let range_path = keyword_path("Range");
+    let any_fcs_col_path = quote!(fireflow_core::validated::dataframe::AnyFCSColumn);
+
+    let insert_meas_doc = |is_optical: bool, hasdata: bool| {
+        let (meas_type, what) = if is_optical {
+            (optical_pytype(version), "optical")
+        } else {
+            (temporal_pytype(version), "temporal")
+        };
+        let param_meas = DocArg::new_param(
+            "meas".into(),
+            meas_type.clone(),
+            "The measurement to insert.".into(),
+        );
+        let _param_col = if hasdata { Some(param_col()) } else { None };
+        let summary = format!("Insert {what} measurement at position in measurement vector.");
+        let ps: Vec<_> = [
+            param_index("Position at which to insert new measurement."),
+            param_meas,
+        ]
+        .into_iter()
+        .chain(_param_col)
+        .chain([
+            param_name("Name of new measurement."),
+            param_range(),
+            param_notrunc(),
+        ])
+        .collect();
+        DocString::new(summary, vec![], DocSelf::PySelf, ps, None)
+    };
+
+    let insert_opt_doc = insert_meas_doc(true, false);
+    let insert_tmp_doc = insert_meas_doc(false, false);
+    let insert_opt_data_doc = insert_meas_doc(true, true);
+    let insert_tmp_data_doc = insert_meas_doc(false, true);
+
+    let q = if is_dataset {
+        quote! {
+            #insert_opt_data_doc
+            fn insert_optical(
+                &mut self,
+                index: #meas_index_path,
+                meas: #otype,
+                col: #any_fcs_col_path,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .insert_optical(index, name.into(), meas.into(), col, range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #insert_tmp_data_doc
+            fn insert_temporal(
+                &mut self,
+                index: #meas_index_path,
+                meas: #ttype,
+                col: #any_fcs_col_path,
+                name: #shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .insert_temporal(index, name, meas.into(), col, range, notrunc)
+                    .py_termfail_resolve()
+            }
+        }
+    } else {
+        quote! {
+            #insert_opt_doc
+            fn insert_optical(
+                &mut self,
+                index: #meas_index_path,
+                meas: #otype,
+                name: #ver_shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .insert_optical(index, name.into(), meas.into(), range, notrunc)
+                    .py_termfail_resolve()
+                    .void()
+            }
+
+            #insert_tmp_doc
+            fn insert_temporal(
+                &mut self,
+                index: #meas_index_path,
+                meas: #ttype,
+                name: #shortname_path,
+                range: #range_path,
+                notrunc: bool,
+            ) -> PyResult<()> {
+                self.0
+                    .insert_temporal(index, name, meas.into(), range, notrunc)
+                    .py_termfail_resolve()
+            }
+        }
+    };
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #q
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_replace_optical(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let otype = pyoptical(version);
+    let meas_index_path = meas_index_path();
+    let element_path = element_path(version);
+    let shortname_path = shortname_path();
+
+    let make_replace_doc = |is_index: bool| {
+        let (i, i_param, m) = if is_index {
+            (
+                "index",
+                param_index("Index to replace."),
+                "measurement at index",
+            )
+        } else {
+            ("name", param_name("Name to replace."), "named measurement")
+        };
+        let meas_desc = format!("Optical measurement to replace measurement at ``{i}``.");
+        let sub = format!("Raise exception if ``{i}`` does not exist.");
+        DocString::new(
+            format!("Replace {m} with given optical measurement."),
+            vec![sub],
+            DocSelf::PySelf,
+            vec![
+                i_param,
+                DocArg::new_param("meas".into(), optical_pytype(version), meas_desc),
+            ],
+            Some(DocReturn::new(
+                measurement_pytype(version),
+                Some("Replaced measurement object".into()),
+            )),
+        )
+    };
+
+    let replace_at_doc = make_replace_doc(true);
+    let replace_named_doc = make_replace_doc(false);
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #replace_at_doc
+            fn replace_optical_at(
+                &mut self,
+                index: #meas_index_path,
+                meas: #otype,
+            ) -> PyResult<#element_path> {
+                let ret = self.0.replace_optical_at(index, meas.into())?;
+                Ok(ret.inner_into())
+            }
+
+            #replace_named_doc
+            fn replace_optical_named(
+                &mut self,
+                name: #shortname_path,
+                meas: #otype,
+            ) -> Option<#element_path> {
+                self.0
+                    .replace_optical_named(&name, meas.into())
+                    .map(|r| r.inner_into())
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_replace_temporal(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_pycore(&i).1;
+
+    let ttype = pytemporal(version);
+    let meas_index_path = meas_index_path();
+    let element_path = element_path(version);
+    let shortname_path = shortname_path();
+
+    let force_param = DocArg::new_param_def(
+        "force".into(),
+        PyType::Bool,
+        "If ``True``, do not raise exception if existing temporal measurement \
+         cannot be converted to optical measurement."
+            .into(),
+        DocDefault::Bool(false),
+    );
+
+    // the temporal replacement functions for 3.2 are different because they
+    // can fail if $PnTYPE is set
+    let (replace_tmp_args, replace_tmp_at_body, replace_tmp_named_body, force) =
+        if version == Version::FCS3_2 {
+            let go = |fun, x| quote!(self.0.#fun(#x, meas.into(), force).py_termfail_resolve()?);
+            (
+                quote! {force: bool},
+                go(quote! {replace_temporal_at_lossy}, quote! {index}),
+                go(quote! {replace_temporal_named_lossy}, quote! {&name}),
+                Some(force_param),
+            )
+        } else {
+            (
+                quote! {},
+                quote! {self.0.replace_temporal_at(index, meas.into())?},
+                quote! {self.0.replace_temporal_named(&name, meas.into())},
+                None,
+            )
+        };
+
+    let make_replace_doc = |is_index: bool| {
+        let (i, i_param, m) = if is_index {
+            (
+                "index",
+                param_index("Index to replace."),
+                "measurement at index",
+            )
+        } else {
+            ("name", param_name("Name to replace."), "named measurement")
+        };
+        let meas_desc = format!("Temporal measurement to replace measurement at ``{i}``.");
+        let sub = format!(
+            "Raise exception if ``{i}`` does not exist  or there \
+             is already a temporal measurement in a different position."
+        );
+        let args = [
+            i_param,
+            DocArg::new_param("meas".into(), temporal_pytype(version), meas_desc),
+        ];
+        DocString::new(
+            format!("Replace {m} with given temporal measurement."),
+            vec![sub],
+            DocSelf::PySelf,
+            args.into_iter().chain(force.clone()).collect(),
+            Some(DocReturn::new(
+                measurement_pytype(version),
+                Some("Replaced measurement object".into()),
+            )),
+        )
+    };
+
+    let replace_at_doc = make_replace_doc(true);
+    let replace_named_doc = make_replace_doc(false);
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #replace_at_doc
+            fn replace_temporal_at(
+                &mut self,
+                index: #meas_index_path,
+                meas: #ttype,
+                #replace_tmp_args
+            ) -> PyResult<#element_path> {
+                let ret = #replace_tmp_at_body;
+                Ok(ret.inner_into())
+            }
+
+            #replace_named_doc
+            fn replace_temporal_named(
+                &mut self,
+                name: #shortname_path,
+                meas: #ttype,
+                #replace_tmp_args
+            ) -> PyResult<Option<#element_path>> {
+                let ret = #replace_tmp_named_body;
+                Ok(ret.map(|r| r.inner_into()))
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_coretext_from_kws(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as Path);
+    let ident = path.segments.last().unwrap().ident.clone();
+    let version = split_ident_version_checked("CoreTEXT", &ident);
+    let pyname = format_ident!("Py{ident}");
+
+    let std_args = ArgData::std_config_args(version);
+    let layout_args = ArgData::layout_config_args(version);
+    let shared_args = ArgData::shared_config_args();
+
+    let sk = quote!(fireflow_core::validated::keys::StdKey);
+    let nsk = quote!(fireflow_core::validated::keys::NonStdKey);
+    let std = quote!(std::collections::HashMap<#sk, String>);
+    let nonstd = quote!(std::collections::HashMap<#nsk, String>);
+
+    let fun_args: Vec<_> = std_args
+        .iter()
+        .chain(layout_args.iter())
+        .chain(shared_args.iter())
+        .map(|a| a.constr_arg())
+        .collect();
+
+    let std_inner_args: Vec<_> = std_args.iter().map(|a| a.inner_arg1()).collect();
+    let layout_inner_args: Vec<_> = layout_args.iter().map(|a| a.inner_arg1()).collect();
+    let shared_inner_args: Vec<_> = shared_args.iter().map(|a| a.inner_arg1()).collect();
+
+    let std_conf = quote!(fireflow_core::config::StdTextReadConfig);
+    let layout_conf = quote!(fireflow_core::config::ReadLayoutConfig);
+    let shared_conf = quote!(fireflow_core::config::SharedConfig);
+    let core_conf = quote!(fireflow_core::config::NewCoreTEXTConfig);
+
+    let other_kws = if version == Version::FCS2_0 {
+        "*$TOT*"
+    } else {
+        "*$TOT*, *$BEGINDATA*, *$ENDDATA*, *$BEGINANALYSIS*, *$ENDANALYSIS*, \
+         or *$TIMESTEP* (if time measurement not included)"
+    };
+    let no_kws = format!(
+        "Must not contain any *$Pn\\** keywords not indexed by \
+         *$PAR* or {other_kws}."
+    );
+
+    let std_param = DocArg::new_param(
+        "std".into(),
+        PyType::new_dict(PyType::Str, PyType::Str),
+        format!("Standard keywords. {no_kws}"),
+    );
+
+    let nonstd_param = DocArg::new_param(
+        "nonstd".into(),
+        PyType::new_dict(PyType::Str, PyType::Str),
+        "Non-Standard keywords.".into(),
+    );
+
+    let config_params = std_args
+        .iter()
+        .chain(layout_args.iter())
+        .chain(shared_args.iter())
+        .map(|a| a.doc.clone());
+
+    let params = [std_param, nonstd_param]
+        .into_iter()
+        .chain(config_params)
+        .collect();
+
+    let doc = DocString::new(
+        "Make new instance from keywords.".into(),
+        vec![],
+        // NOTE no need to write "cls" in sig (apparently)
+        DocSelf::NoSelf,
+        params,
+        Some(DocReturn::new(PyType::PyClass(ident.to_string()), None)),
+    );
+
+    quote! {
+        #[pymethods]
+        impl #pyname {
+            #[classmethod]
+            #[allow(clippy::too_many_arguments)]
+            #doc
+            fn from_kws(
+                _: &Bound<'_, pyo3::types::PyType>,
+                std: #std,
+                nonstd: #nonstd,
+                #(#fun_args),*
+            ) -> PyResult<Self> {
+                let kws = fireflow_core::validated::keys::ValidKeywords { std, nonstd };
+                #[allow(clippy::needless_update)]
+                let standard = #std_conf {
+                    #(#std_inner_args,)*
+                    ..#std_conf::default()
+                };
+                #[allow(clippy::needless_update)]
+                let layout = #layout_conf {
+                    #(#layout_inner_args,)*
+                    ..#layout_conf::default()
+                };
+                let shared = #shared_conf { #(#shared_inner_args),* };
+                let conf = #core_conf { standard, layout, shared };
+                Ok(Self(#path::new_from_keywords(kws, &conf).py_termfail_resolve()?))
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_coredataset_from_kws(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as Path);
+    let ident = path.segments.last().unwrap().ident.clone();
+    let version = split_ident_version_checked("CoreDataset", &ident);
+    let pyname = format_ident!("Py{ident}");
+
+    let std_args = ArgData::std_config_args(version);
+    let layout_args = ArgData::layout_config_args(version);
+    let offsets_args = ArgData::offsets_config_args(version);
+    let reader_args = ArgData::reader_config_args();
+    let shared_args = ArgData::shared_config_args();
+
+    let config_args: Vec<_> = std_args
+        .iter()
+        .chain(layout_args.iter())
+        .chain(offsets_args.iter())
+        .chain(reader_args.iter())
+        .chain(shared_args.iter())
+        .collect();
+
+    let std_inner_args: Vec<_> = std_args.iter().map(|a| a.inner_arg1()).collect();
+    let layout_inner_args: Vec<_> = layout_args.iter().map(|a| a.inner_arg1()).collect();
+    let offsets_inner_args: Vec<_> = offsets_args.iter().map(|a| a.inner_arg1()).collect();
+    let reader_inner_args: Vec<_> = reader_args.iter().map(|a| a.inner_arg1()).collect();
+    let shared_inner_args: Vec<_> = shared_args.iter().map(|a| a.inner_arg1()).collect();
+
+    let std_conf = quote!(fireflow_core::config::StdTextReadConfig);
+    let layout_conf = quote!(fireflow_core::config::ReadLayoutConfig);
+    let offsets_conf = quote!(fireflow_core::config::ReadTEXTOffsetsConfig);
+    let reader_conf = quote!(fireflow_core::config::ReaderConfig);
+    let shared_conf = quote!(fireflow_core::config::SharedConfig);
+    let core_conf = quote!(fireflow_core::config::ReadStdDatasetFromKeywordsConfig);
+
+    let sk_path = quote!(fireflow_core::validated::keys::StdKey);
+    let nsk_path = quote!(fireflow_core::validated::keys::NonStdKey);
+    let std_path = quote!(std::collections::HashMap<#sk_path, String>);
+    let nonstd_path = quote!(std::collections::HashMap<#nsk_path, String>);
+
+    let data_seg_path = quote!(fireflow_core::segment::HeaderDataSegment);
+    let analysis_seg_path = quote!(fireflow_core::segment::HeaderAnalysisSegment);
+    let other_segs_path = quote!(Vec<fireflow_core::segment::OtherSegment20>);
+
+    let path_param = path_param(true);
+
+    let std_param = DocArg::new_param(
+        "std".into(),
+        PyType::new_dict(PyType::Str, PyType::Str),
+        "Standard keywords.".into(),
+    );
+
+    let nonstd_param = DocArg::new_param(
+        "nonstd".into(),
+        PyType::new_dict(PyType::Str, PyType::Str),
+        "Non-Standard keywords.".into(),
+    );
+
+    let data_seg_param = DocArg::new_param(
+        "data_seg".into(),
+        segment_pytype(),
+        "The *DATA* segment from *HEADER*.".into(),
+    );
+
+    let analysis_seg_param = DocArg::new_param_def(
+        "analysis_seg".into(),
+        segment_pytype(),
+        "The *ANALYSIS* segment from *HEADER*.".into(),
+        DocDefault::Other(quote!(#analysis_seg_path::default()), "(0, 0)".into()),
+    );
+
+    let other_segs_param = DocArg::new_param_def(
+        "other_segs".into(),
+        PyType::new_list(segment_pytype()),
+        "The *OTHER* segments from *HEADER*.".into(),
+        DocDefault::EmptyList,
+    );
+
+    let config_params = config_args.iter().map(|a| a.doc.clone());
+
+    let params = [
+        path_param,
+        std_param,
+        nonstd_param,
+        data_seg_param,
+        analysis_seg_param,
+        other_segs_param,
+    ]
+    .into_iter()
+    .chain(config_params)
+    .collect();
+
+    let doc = DocString::new(
+        "Make new instance from keywords.".into(),
+        vec![],
+        DocSelf::NoSelf,
+        params,
+        Some(DocReturn::new(PyType::PyClass(ident.to_string()), None)),
+    );
+
+    let fun_args: Vec<_> = config_args.iter().map(|a| a.constr_arg()).collect();
+
+    quote! {
+        #[pymethods]
+        impl #pyname {
+            #[classmethod]
+            #[allow(clippy::too_many_arguments)]
+            #doc
+            fn from_kws(
+                _: &Bound<'_, pyo3::types::PyType>,
+                path: std::path::PathBuf,
+                std: #std_path,
+                nonstd: #nonstd_path,
+                data_seg: #data_seg_path,
+                analysis_seg: #analysis_seg_path,
+                other_segs: #other_segs_path,
+                #(#fun_args),*
+            // ) -> PyResult<(Self, fireflow_core::core::StdDatasetWithKwsOutput)> {
+            ) -> PyResult<Self> {
+                let kws = fireflow_core::validated::keys::ValidKeywords { std, nonstd };
+                #[allow(clippy::needless_update)]
+                let standard = #std_conf {
+                    #(#std_inner_args,)*
+                    ..#std_conf::default()
+                };
+                #[allow(clippy::needless_update)]
+                let layout = #layout_conf {
+                    #(#layout_inner_args,)*
+                    ..#layout_conf::default()
+                };
+                #[allow(clippy::needless_update)]
+                let offsets = #offsets_conf {
+                    #(#offsets_inner_args,)*
+                    ..#offsets_conf::default()
+                };
+                #[allow(clippy::needless_update)]
+                let data = #reader_conf {
+                    #(#reader_inner_args,)*
+                    ..#reader_conf::default()
+                };
+                #[allow(clippy::needless_update)]
+                let shared = #shared_conf {
+                    #(#shared_inner_args,)*
+                    ..#shared_conf::default()
+                };
+                let conf = #core_conf { standard, layout, offsets, data, shared };
+                let (core, uncore) = #path::new_from_keywords(
+                    path, kws, data_seg, analysis_seg, &other_segs[..], &conf
+                ).py_termfail_resolve()?;
+                // Ok((Self(core), uncore))
+                // TODO return more stuff from this
+                Ok(core.into())
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_coretext_example(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as Path);
+    let ident = path.segments.last().unwrap().ident.clone();
+    let _ = split_ident_version_checked("CoreTEXT", &ident);
+    let pyname = format_ident!("Py{ident}");
+
+    let doc = DocString::new(
+        "Make a minimal valid instance with dummy measurements.".into(),
+        vec![
+            "Measurements will be named ``FL1``, ``FL2``, etc and will each \
+             have a range of 1024 with a float layout."
+                .into(),
+            "This is meant for unit tests and documentation examples where \
+             reading an actual FCS file is unnecessary."
+                .into(),
+        ],
+        DocSelf::NoSelf,
+        vec![DocArg::new_param_def(
+            "n".into(),
+            PyType::Int,
+            "Number of optical measurements to add.".into(),
+            DocDefault::Other(quote!(2), "2".into()),
+        )],
+        Some(DocReturn::new(PyType::PyClass(ident.to_string()), None)),
+    );
+
+    quote! {
+        #[pymethods]
+        impl #pyname {
+            #[classmethod]
+            #doc
+            fn example(_: &Bound<'_, pyo3::types::PyType>, n: usize) -> Self {
+                Self(#path::example(n))
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_coretext_unset_measurements(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_checked("PyCoreTEXT", &i);
+
+    let doc = DocString::new(
+        "Remove measurements and clear the layout.".into(),
+        vec![
+            "This is equivalent to deleting all *$Pn\\** keywords and setting *$PAR* to 0.".into(),
+            "Will raise exception if other keywords (such as *$TR*) reference a measurement."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        None,
+    );
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn unset_measurements(&mut self) -> PyResult<()> {
+                Ok(self.0.unset_measurements()?)
             }
+        }
+    }
+    .into()
+}
 
-            #insert_tmp_doc
-            fn insert_temporal(
-                &mut self,
-                index: #meas_index_path,
-                meas: #ttype,
-                name: #shortname_path,
-                range: #range_path,
-                notrunc: bool,
-            ) -> PyResult<()> {
-                self.0
-                    .insert_temporal(index, name, meas.into(), range, notrunc)
-                    .py_termfail_resolve()
+#[proc_macro]
+pub fn impl_coredataset_unset_data(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
+
+    let doc = DocString::new(
+        "Remove all measurements and their data.".into(),
+        vec!["Raise exception if any keywords (such as *$TR*) reference a measurement.".into()],
+        DocSelf::PySelf,
+        vec![],
+        None,
+    );
+
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn unset_data(&mut self) -> PyResult<()> {
+                Ok(self.0.unset_data()?)
             }
         }
-    };
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_coredataset_truncate_data(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
+
+    let p = DocArg::new_param_def(
+        "skip_conv_check".into(),
+        PyType::Bool,
+        "If ``True``, silently truncate data; otherwise return warnings when \
+         truncation is performed."
+            .into(),
+        DocDefault::Bool(false),
+    );
+
+    let doc = DocString::new(
+        "Coerce all values in DATA to fit within types specified in layout.".into(),
+        vec!["This will always create a new copy of DATA in-place.".into()],
+        DocSelf::PySelf,
+        vec![p],
+        None,
+    );
 
     quote! {
         #[pymethods]
         impl #i {
-            #q
+            #doc
+            fn truncate_data(&mut self, skip_conv_check: bool) -> PyResult<()> {
+                self.0.truncate_data(skip_conv_check).py_term_resolve_noerror()
+            }
         }
     }
     .into()
 }
 
 #[proc_macro]
-pub fn impl_core_replace_optical(input: TokenStream) -> TokenStream {
+pub fn impl_coredataset_solidify_delimited(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
 
-    let otype = pyoptical(version);
-    let meas_index_path = meas_index_path();
-    let element_path = element_path(version);
-    let shortname_path = shortname_path();
+    let widths_doc = DocString::new(
+        "Number of digits in the widest value of each column.".into(),
+        vec![
+            "Only applies to delimited ASCII layouts (*$PnB* is ``\"*\"``), \
+             where column widths are not stored in *TEXT*. Will be ``None`` \
+             for all other layouts, and empty if *DATA* has no columns."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_opt(PyType::new_list(PyType::Int)),
+            None,
+        )),
+    )
+    .doc();
 
-    let make_replace_doc = |is_index: bool| {
-        let (i, i_param, m) = if is_index {
-            (
-                "index",
-                param_index("Index to replace."),
-                "measurement at index",
-            )
-        } else {
-            ("name", param_name("Name to replace."), "named measurement")
-        };
-        let meas_desc = format!("Optical measurement to replace measurement at ``{i}``.");
-        let sub = format!("Raise exception if ``{i}`` does not exist.");
-        DocString::new(
-            format!("Replace {m} with given optical measurement."),
-            vec![sub],
-            DocSelf::PySelf,
-            vec![
-                i_param,
-                DocArg::new_param("meas".into(), optical_pytype(version), meas_desc),
-            ],
-            Some(DocReturn::new(
-                measurement_pytype(version),
-                Some("Replaced measurement object".into()),
-            )),
-        )
-    };
+    let endian = DocArg::new_param_def(
+        "endian".into(),
+        PyType::new_opt(PyType::new_lit(&["big", "little"])),
+        "If given, convert to integers with this byte order; otherwise \
+         convert to fixed-width ASCII."
+            .into(),
+        DocDefault::Option,
+    );
 
-    let replace_at_doc = make_replace_doc(true);
-    let replace_named_doc = make_replace_doc(false);
+    let solidify_doc = DocString::new(
+        "Convert a delimited ASCII layout to a fixed-width layout.".into(),
+        vec![
+            "Each column will be given the smallest width which can hold both \
+             its *$PnR* and every value in *DATA*. For 2.0 and 3.0, all \
+             integer columns will have the width of the widest column."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![endian],
+        Some(DocReturn::new(
+            PyType::Bool,
+            Some("``False`` if layout was not delimited ASCII, ``True`` otherwise.".into()),
+        )),
+    );
 
     quote! {
         #[pymethods]
         impl #i {
-            #replace_at_doc
-            fn replace_optical_at(
-                &mut self,
-                index: #meas_index_path,
-                meas: #otype,
-            ) -> PyResult<#element_path> {
-                let ret = self.0.replace_optical_at(index, meas.into())?;
-                Ok(ret.inner_into())
+            #widths_doc
+            #[getter]
+            fn get_delimited_widths(&self) -> Option<Vec<u8>> {
+                self.0
+                    .delimited_widths()
+                    .map(|ws| ws.into_iter().map(u8::from).collect())
             }
 
-            #replace_named_doc
-            fn replace_optical_named(
+            #solidify_doc
+            fn solidify_delimited(
                 &mut self,
-                name: #shortname_path,
-                meas: #otype,
-            ) -> Option<#element_path> {
-                self.0
-                    .replace_optical_named(&name, meas.into())
-                    .map(|r| r.inner_into())
+                endian: Option<fireflow_core::text::byteord::Endian>,
+            ) -> bool {
+                self.0.solidify_delimited(endian)
             }
         }
     }
@@ -1561,101 +2991,50 @@ pub fn impl_core_replace_optical(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_core_replace_temporal(input: TokenStream) -> TokenStream {
+pub fn impl_coredataset_metadata_only(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let version = split_ident_version_pycore(&i).1;
-
-    let ttype = pytemporal(version);
-    let meas_index_path = meas_index_path();
-    let element_path = element_path(version);
-    let shortname_path = shortname_path();
+    let version = split_ident_version_checked("PyCoreDataset", &i);
+    let name = format!("CoreDataset{}", version.short_underscore());
 
-    let force_param = DocArg::new_param_def(
-        "force".into(),
+    let p = DocArg::new_param_def(
+        "keep_analysis".into(),
         PyType::Bool,
-        "If ``True``, do not raise exception if existing temporal measurement \
-         cannot be converted to optical measurement."
-            .into(),
+        "If ``True``, keep *ANALYSIS*, otherwise remove it.".into(),
         DocDefault::Bool(false),
     );
 
-    // the temporal replacement functions for 3.2 are different because they
-    // can fail if $PnTYPE is set
-    let (replace_tmp_args, replace_tmp_at_body, replace_tmp_named_body, force) =
-        if version == Version::FCS3_2 {
-            let go = |fun, x| quote!(self.0.#fun(#x, meas.into(), force).py_termfail_resolve()?);
-            (
-                quote! {force: bool},
-                go(quote! {replace_temporal_at_lossy}, quote! {index}),
-                go(quote! {replace_temporal_named_lossy}, quote! {&name}),
-                Some(force_param),
-            )
-        } else {
-            (
-                quote! {},
-                quote! {self.0.replace_temporal_at(index, meas.into())?},
-                quote! {self.0.replace_temporal_named(&name, meas.into())},
-                None,
-            )
-        };
-
-    let make_replace_doc = |is_index: bool| {
-        let (i, i_param, m) = if is_index {
-            (
-                "index",
-                param_index("Index to replace."),
-                "measurement at index",
-            )
-        } else {
-            ("name", param_name("Name to replace."), "named measurement")
-        };
-        let meas_desc = format!("Temporal measurement to replace measurement at ``{i}``.");
-        let sub = format!(
-            "Raise exception if ``{i}`` does not exist  or there \
-             is already a temporal measurement in a different position."
-        );
-        let args = [
-            i_param,
-            DocArg::new_param("meas".into(), temporal_pytype(version), meas_desc),
-        ];
-        DocString::new(
-            format!("Replace {m} with given temporal measurement."),
-            vec![sub],
-            DocSelf::PySelf,
-            args.into_iter().chain(force.clone()).collect(),
-            Some(DocReturn::new(
-                measurement_pytype(version),
-                Some("Replaced measurement object".into()),
-            )),
-        )
-    };
+    let to_doc = DocString::new(
+        "Return copy of this dataset without any events.".into(),
+        vec![
+            "All measurements and *TEXT* keywords will be kept, and *$TOT* \
+              will be 0 when written. This is useful for sharing panel or \
+              instrument setup without sharing measurement data."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![p],
+        Some(DocReturn::new(PyType::PyClass(name), None)),
+    );
 
-    let replace_at_doc = make_replace_doc(true);
-    let replace_named_doc = make_replace_doc(false);
+    let is_doc = DocString::new(
+        "Return ``True`` if this dataset has measurements but no events.".into(),
+        vec![],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(PyType::Bool, None)),
+    );
 
     quote! {
         #[pymethods]
         impl #i {
-            #replace_at_doc
-            fn replace_temporal_at(
-                &mut self,
-                index: #meas_index_path,
-                meas: #ttype,
-                #replace_tmp_args
-            ) -> PyResult<#element_path> {
-                let ret = #replace_tmp_at_body;
-                Ok(ret.inner_into())
+            #to_doc
+            fn to_metadata_only(&self, keep_analysis: bool) -> Self {
+                self.0.to_metadata_only(keep_analysis).into()
             }
 
-            #replace_named_doc
-            fn replace_temporal_named(
-                &mut self,
-                name: #shortname_path,
-                meas: #ttype,
-                #replace_tmp_args
-            ) -> PyResult<Option<#element_path>> {
-                let ret = #replace_tmp_named_body;
-                Ok(ret.map(|r| r.inner_into()))
+            #is_doc
+            fn is_metadata_only(&self) -> bool {
+                self.0.is_metadata_only()
             }
         }
     }
@@ -1663,106 +3042,52 @@ pub fn impl_core_replace_temporal(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_coretext_from_kws(input: TokenStream) -> TokenStream {
-    let path = parse_macro_input!(input as Path);
-    let ident = path.segments.last().unwrap().ident.clone();
-    let version = split_ident_version_checked("CoreTEXT", &ident);
-    let pyname = format_ident!("Py{ident}");
-
-    let std_args = ArgData::std_config_args(version);
-    let layout_args = ArgData::layout_config_args(version);
-    let shared_args = ArgData::shared_config_args();
-
-    let sk = quote!(fireflow_core::validated::keys::StdKey);
-    let nsk = quote!(fireflow_core::validated::keys::NonStdKey);
-    let std = quote!(std::collections::HashMap<#sk, String>);
-    let nonstd = quote!(std::collections::HashMap<#nsk, String>);
-
-    let fun_args: Vec<_> = std_args
-        .iter()
-        .chain(layout_args.iter())
-        .chain(shared_args.iter())
-        .map(|a| a.constr_arg())
-        .collect();
-
-    let std_inner_args: Vec<_> = std_args.iter().map(|a| a.inner_arg1()).collect();
-    let layout_inner_args: Vec<_> = layout_args.iter().map(|a| a.inner_arg1()).collect();
-    let shared_inner_args: Vec<_> = shared_args.iter().map(|a| a.inner_arg1()).collect();
-
-    let std_conf = quote!(fireflow_core::config::StdTextReadConfig);
-    let layout_conf = quote!(fireflow_core::config::ReadLayoutConfig);
-    let shared_conf = quote!(fireflow_core::config::SharedConfig);
-    let core_conf = quote!(fireflow_core::config::NewCoreTEXTConfig);
-
-    let other_kws = if version == Version::FCS2_0 {
-        "*$TOT*"
-    } else {
-        "*$TOT*, *$BEGINDATA*, *$ENDDATA*, *$BEGINANALYSIS*, *$ENDANALYSIS*, \
-         or *$TIMESTEP* (if time measurement not included)"
-    };
-    let no_kws = format!(
-        "Must not contain any *$Pn\\** keywords not indexed by \
-         *$PAR* or {other_kws}."
-    );
-
-    let std_param = DocArg::new_param(
-        "std".into(),
-        PyType::new_dict(PyType::Str, PyType::Str),
-        format!("Standard keywords. {no_kws}"),
-    );
+pub fn impl_coredataset_time_chunks(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
 
-    let nonstd_param = DocArg::new_param(
-        "nonstd".into(),
-        PyType::new_dict(PyType::Str, PyType::Str),
-        "Non-Standard keywords.".into(),
+    let p = DocArg::new_param(
+        "window".into(),
+        PyType::Float,
+        "Width of each window in seconds.".into(),
     );
 
-    let config_params = std_args
-        .iter()
-        .chain(layout_args.iter())
-        .chain(shared_args.iter())
-        .map(|a| a.doc.clone());
-
-    let params = [std_param, nonstd_param]
-        .into_iter()
-        .chain(config_params)
-        .collect();
-
     let doc = DocString::new(
-        "Make new instance from keywords.".into(),
-        vec![],
-        // NOTE no need to write "cls" in sig (apparently)
-        DocSelf::NoSelf,
-        params,
-        Some(DocReturn::new(PyType::PyClass(ident.to_string()), None)),
+        "Return rows of *DATA* in each window of acquisition time.".into(),
+        vec![
+            "Time is computed from the time measurement multiplied by \
+             *$TIMESTEP* (which is assumed to be 1 for FCS 2.0). A new \
+             window will be started after ``window`` seconds, and also \
+             whenever time decreases as would happen if the time counter \
+             rolled over."
+                .into(),
+            "These are the same windows used to split datasets with \
+             ``split_by_time``."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![p],
+        Some(DocReturn::new(
+            PyType::new_opt(PyType::new_list(PyType::Tuple(vec![
+                PyType::Int,
+                PyType::Int,
+            ]))),
+            Some(
+                "Start (inclusive) and end (exclusive) row of each window, or \
+                 ``None`` if there is no time measurement."
+                    .into(),
+            ),
+        )),
     );
 
     quote! {
         #[pymethods]
-        impl #pyname {
-            #[classmethod]
-            #[allow(clippy::too_many_arguments)]
+        impl #i {
             #doc
-            fn from_kws(
-                _: &Bound<'_, pyo3::types::PyType>,
-                std: #std,
-                nonstd: #nonstd,
-                #(#fun_args),*
-            ) -> PyResult<Self> {
-                let kws = fireflow_core::validated::keys::ValidKeywords { std, nonstd };
-                #[allow(clippy::needless_update)]
-                let standard = #std_conf {
-                    #(#std_inner_args,)*
-                    ..#std_conf::default()
-                };
-                #[allow(clippy::needless_update)]
-                let layout = #layout_conf {
-                    #(#layout_inner_args,)*
-                    ..#layout_conf::default()
-                };
-                let shared = #shared_conf { #(#shared_inner_args),* };
-                let conf = #core_conf { standard, layout, shared };
-                Ok(Self(#path::new_from_keywords(kws, &conf).py_termfail_resolve()?))
+            fn time_chunks(&self, window: f64) -> Option<Vec<(usize, usize)>> {
+                self.0
+                    .time_chunks(window)
+                    .map(|xs| xs.into_iter().map(|r| (r.start, r.end)).collect())
             }
         }
     }
@@ -1770,156 +3095,174 @@ pub fn impl_coretext_from_kws(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_coredataset_from_kws(input: TokenStream) -> TokenStream {
-    let path = parse_macro_input!(input as Path);
-    let ident = path.segments.last().unwrap().ident.clone();
-    let version = split_ident_version_checked("CoreDataset", &ident);
-    let pyname = format_ident!("Py{ident}");
-
-    let std_args = ArgData::std_config_args(version);
-    let layout_args = ArgData::layout_config_args(version);
-    let offsets_args = ArgData::offsets_config_args(version);
-    let reader_args = ArgData::reader_config_args();
-    let shared_args = ArgData::shared_config_args();
-
-    let config_args: Vec<_> = std_args
-        .iter()
-        .chain(layout_args.iter())
-        .chain(offsets_args.iter())
-        .chain(reader_args.iter())
-        .chain(shared_args.iter())
-        .collect();
-
-    let std_inner_args: Vec<_> = std_args.iter().map(|a| a.inner_arg1()).collect();
-    let layout_inner_args: Vec<_> = layout_args.iter().map(|a| a.inner_arg1()).collect();
-    let offsets_inner_args: Vec<_> = offsets_args.iter().map(|a| a.inner_arg1()).collect();
-    let reader_inner_args: Vec<_> = reader_args.iter().map(|a| a.inner_arg1()).collect();
-    let shared_inner_args: Vec<_> = shared_args.iter().map(|a| a.inner_arg1()).collect();
+pub fn impl_coredataset_split_by_time(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_checked("PyCoreDataset", &i);
+    let name = format!("CoreDataset{}", version.short_underscore());
 
-    let std_conf = quote!(fireflow_core::config::StdTextReadConfig);
-    let layout_conf = quote!(fireflow_core::config::ReadLayoutConfig);
-    let offsets_conf = quote!(fireflow_core::config::ReadTEXTOffsetsConfig);
-    let reader_conf = quote!(fireflow_core::config::ReaderConfig);
-    let shared_conf = quote!(fireflow_core::config::SharedConfig);
-    let core_conf = quote!(fireflow_core::config::ReadStdDatasetFromKeywordsConfig);
+    let p = DocArg::new_param(
+        "interval".into(),
+        PyType::Float,
+        "Width of each window in seconds.".into(),
+    );
 
-    let sk_path = quote!(fireflow_core::validated::keys::StdKey);
-    let nsk_path = quote!(fireflow_core::validated::keys::NonStdKey);
-    let std_path = quote!(std::collections::HashMap<#sk_path, String>);
-    let nonstd_path = quote!(std::collections::HashMap<#nsk_path, String>);
+    let parent = param_parent_file();
 
-    let data_seg_path = quote!(fireflow_core::segment::HeaderDataSegment);
-    let analysis_seg_path = quote!(fireflow_core::segment::HeaderAnalysisSegment);
-    let other_segs_path = quote!(Vec<fireflow_core::segment::OtherSegment20>);
+    let doc = DocString::new(
+        "Split this dataset into several datasets by acquisition time.".into(),
+        vec![
+            "Time is computed from the time measurement multiplied by \
+             *$TIMESTEP* (which is assumed to be 1 for FCS 2.0). A new \
+             dataset will be started for each window of ``interval`` \
+             seconds, and also whenever time decreases as would happen if \
+             the time counter rolled over."
+                .into(),
+            "Each new dataset will otherwise be a copy of this one, except \
+             that *$BTIM*, *$ETIM*, and *$DATE* (and *$BEGINDATETIME* and \
+             *$ENDDATETIME* for FCS 3.2) will be shifted to match the times \
+             of the first and last event in each window. Each will also have \
+             provenance recording ``parent`` (if given), ``interval``, and its \
+             position."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![p, parent],
+        Some(DocReturn::new(
+            PyType::new_opt(PyType::new_list(PyType::PyClass(name))),
+            Some("New datasets or ``None`` if there is no time measurement.".into()),
+        )),
+    );
 
-    let path_param = path_param(true);
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn split_by_time(
+                &self,
+                interval: f64,
+                parent: Option<std::path::PathBuf>,
+            ) -> PyResult<Option<Vec<Self>>> {
+                let pf = parent
+                    .map(|p| fireflow_core::text::provenance::ParentFile::from_path(&p))
+                    .transpose()?;
+                Ok(self
+                    .0
+                    .split_by_time(interval, pf.as_ref())
+                    .map(|xs| xs.into_iter().map(Self::from).collect()))
+            }
+        }
+    }
+    .into()
+}
 
-    let std_param = DocArg::new_param(
-        "std".into(),
-        PyType::new_dict(PyType::Str, PyType::Str),
-        "Standard keywords.".into(),
-    );
+#[proc_macro]
+pub fn impl_coredataset_demultiplex(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let version = split_ident_version_checked("PyCoreDataset", &i);
+    let name = format!("CoreDataset{}", version.short_underscore());
 
-    let nonstd_param = DocArg::new_param(
-        "nonstd".into(),
-        PyType::new_dict(PyType::Str, PyType::Str),
-        "Non-Standard keywords.".into(),
-    );
+    let shortname_path = shortname_path();
 
-    let data_seg_param = DocArg::new_param(
-        "data_seg".into(),
-        segment_pytype(),
-        "The *DATA* segment from *HEADER*.".into(),
+    let p = DocArg::new_param(
+        "rules".into(),
+        PyType::new_list(PyType::Tuple(vec![PyType::Str, PyType::Float])),
+        "Pairs of barcode measurement names and the minimum raw value at \
+         which an event is positive for that measurement."
+            .into(),
     );
 
-    let analysis_seg_param = DocArg::new_param_def(
-        "analysis_seg".into(),
-        segment_pytype(),
-        "The *ANALYSIS* segment from *HEADER*.".into(),
-        DocDefault::Other(quote!(#analysis_seg_path::default()), "(0, 0)".into()),
-    );
+    let parent = param_parent_file();
 
-    let other_segs_param = DocArg::new_param_def(
-        "other_segs".into(),
-        PyType::new_list(segment_pytype()),
-        "The *OTHER* segments from *HEADER*.".into(),
-        DocDefault::EmptyList,
+    let doc = DocString::new(
+        "Split events into one dataset per barcode measurement.".into(),
+        vec![
+            "Each event will be assigned to the dataset for the only rule it \
+             is positive for; events which are positive for no rules or more \
+             than one rule will be dropped."
+                .into(),
+            "Each new dataset will otherwise be a copy of this one, except \
+             that the measurement name will be appended to *$SMNO* and \
+             *$SRC* (or used as *$SMNO* if it is missing), and provenance \
+             will record ``parent`` (if given) and the measurement name."
+                .into(),
+            "Raise exception if any name is not found.".into(),
+        ],
+        DocSelf::PySelf,
+        vec![p, parent],
+        Some(DocReturn::new(
+            PyType::new_list(PyType::PyClass(name)),
+            Some("One dataset for each rule, in the same order.".into()),
+        )),
     );
 
-    let config_params = config_args.iter().map(|a| a.doc.clone());
-
-    let params = [
-        path_param,
-        std_param,
-        nonstd_param,
-        data_seg_param,
-        analysis_seg_param,
-        other_segs_param,
-    ]
-    .into_iter()
-    .chain(config_params)
-    .collect();
+    quote! {
+        #[pymethods]
+        impl #i {
+            #doc
+            fn demultiplex(
+                &self,
+                rules: Vec<(#shortname_path, fireflow_core::demux::Threshold)>,
+                parent: Option<std::path::PathBuf>,
+            ) -> PyResult<Vec<Self>> {
+                let pf = parent
+                    .map(|p| fireflow_core::text::provenance::ParentFile::from_path(&p))
+                    .transpose()?;
+                Ok(self
+                    .0
+                    .demultiplex(&rules, pf.as_ref())?
+                    .into_iter()
+                    .map(Self::from)
+                    .collect())
+            }
+        }
+    }
+    .into()
+}
 
-    let doc = DocString::new(
-        "Make new instance from keywords.".into(),
-        vec![],
-        DocSelf::NoSelf,
-        params,
-        Some(DocReturn::new(PyType::PyClass(ident.to_string()), None)),
+#[proc_macro]
+pub fn impl_coredataset_duplicates(input: TokenStream) -> TokenStream {
+    let i: Ident = syn::parse(input).unwrap();
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
+
+    let shortname_path = shortname_path();
+
+    let p = DocArg::new_param_def(
+        "channels".into(),
+        PyType::new_list(PyType::Str),
+        "Names of measurements to compare. If empty, compare all measurements."
+            .into(),
+        DocDefault::EmptyList,
     );
 
-    let fun_args: Vec<_> = config_args.iter().map(|a| a.constr_arg()).collect();
+    let doc = DocString::new(
+        "Find events which repeat earlier events in *DATA*.".into(),
+        vec![
+            "Two events are duplicates if their raw values are identical for \
+             every measurement in ``channels``. Runs of consecutive duplicates \
+             usually indicate an aborted and restarted acquisition."
+                .into(),
+            "Events are hashed without copying *DATA*, so this is much \
+             cheaper than :py:attr:`data` for large files."
+                .into(),
+            "Raise exception if any name is not found.".into(),
+        ],
+        DocSelf::PySelf,
+        vec![p],
+        Some(DocReturn::new(
+            PyType::PyClass("pyreflow.typing.DuplicateReport".into()),
+            Some("Each duplicated event and runs of duplicated events".into()),
+        )),
+    );
 
     quote! {
         #[pymethods]
-        impl #pyname {
-            #[classmethod]
-            #[allow(clippy::too_many_arguments)]
+        impl #i {
             #doc
-            fn from_kws(
-                _: &Bound<'_, pyo3::types::PyType>,
-                path: std::path::PathBuf,
-                std: #std_path,
-                nonstd: #nonstd_path,
-                data_seg: #data_seg_path,
-                analysis_seg: #analysis_seg_path,
-                other_segs: #other_segs_path,
-                #(#fun_args),*
-            // ) -> PyResult<(Self, fireflow_core::core::StdDatasetWithKwsOutput)> {
-            ) -> PyResult<Self> {
-                let kws = fireflow_core::validated::keys::ValidKeywords { std, nonstd };
-                #[allow(clippy::needless_update)]
-                let standard = #std_conf {
-                    #(#std_inner_args,)*
-                    ..#std_conf::default()
-                };
-                #[allow(clippy::needless_update)]
-                let layout = #layout_conf {
-                    #(#layout_inner_args,)*
-                    ..#layout_conf::default()
-                };
-                #[allow(clippy::needless_update)]
-                let offsets = #offsets_conf {
-                    #(#offsets_inner_args,)*
-                    ..#offsets_conf::default()
-                };
-                #[allow(clippy::needless_update)]
-                let data = #reader_conf {
-                    #(#reader_inner_args,)*
-                    ..#reader_conf::default()
-                };
-                #[allow(clippy::needless_update)]
-                let shared = #shared_conf {
-                    #(#shared_inner_args,)*
-                    ..#shared_conf::default()
-                };
-                let conf = #core_conf { standard, layout, offsets, data, shared };
-                let (core, uncore) = #path::new_from_keywords(
-                    path, kws, data_seg, analysis_seg, &other_segs[..], &conf
-                ).py_termfail_resolve()?;
-                // Ok((Self(core), uncore))
-                // TODO return more stuff from this
-                Ok(core.into())
+            fn find_duplicate_events(
+                &self,
+                channels: Vec<#shortname_path>,
+            ) -> PyResult<fireflow_core::duplicates::DuplicateReport> {
+                Ok(self.0.find_duplicate_events(&channels)?)
             }
         }
     }
@@ -1927,28 +3270,109 @@ pub fn impl_coredataset_from_kws(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_coretext_unset_measurements(input: TokenStream) -> TokenStream {
+pub fn impl_coredataset_histogram(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    let _ = split_ident_version_checked("PyCoreTEXT", &i);
+    let _ = split_ident_version_checked("PyCoreDataset", &i);
 
-    let doc = DocString::new(
-        "Remove measurements and clear the layout.".into(),
+    let shortname_path = shortname_path();
+
+    let make_bins = |n: usize| {
+        DocArg::new_param_def(
+            "bins".into(),
+            PyType::Int,
+            "Number of equally-spaced bins. Must be at least 1.".into(),
+            DocDefault::Other(
+                quote!(std::num::NonZeroUsize::new(#n).unwrap()),
+                n.to_string(),
+            ),
+        )
+    };
+
+    let scaled = DocArg::new_param_def(
+        "scaled".into(),
+        PyType::Bool,
+        "If ``True``, apply *$PnE* and *$PnG* to each value before binning. \
+         This will only change integer measurements with a log scale or gain."
+            .into(),
+        DocDefault::Bool(false),
+    );
+
+    let hist_doc = DocString::new(
+        "Compute a histogram of one measurement in *DATA*.".into(),
         vec![
-            "This is equivalent to deleting all *$Pn\\** keywords and setting *$PAR* to 0.".into(),
-            "Will raise exception if other keywords (such as *$TR*) reference a measurement."
+            "Bins will be evenly spaced between the minimum and maximum of \
+              the column. This is computed without copying the column, so it \
+              is much cheaper than :py:attr:`data` for large files."
                 .into(),
+            "Raise exception if ``name`` not found.".into(),
         ],
         DocSelf::PySelf,
-        vec![],
-        None,
+        vec![
+            param_name("Name of measurement"),
+            make_bins(256),
+            scaled.clone(),
+        ],
+        Some(DocReturn::new(
+            PyType::PyClass("pyreflow.typing.Histogram".into()),
+            Some("Bin edges and counts".into()),
+        )),
+    );
+
+    let qs = DocArg::new_param(
+        "percentiles".into(),
+        PyType::new_list(PyType::Float),
+        "Percentiles to estimate, each between 0 and 100.".into(),
+    );
+
+    let pct_doc = DocString::new(
+        "Estimate percentiles of one measurement in *DATA*.".into(),
+        vec![
+            "Each value is interpolated from a histogram with ``bins`` bins \
+              and will be within one bin width of the exact value."
+                .into(),
+            "Raise exception if ``name`` not found or any percentile is out \
+              of range."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![
+            param_name("Name of measurement"),
+            qs,
+            make_bins(1024),
+            scaled,
+        ],
+        Some(DocReturn::new(
+            PyType::new_list(PyType::new_opt(PyType::Float)),
+            Some("Estimated values, which will be ``None`` if there are no events".into()),
+        )),
     );
 
     quote! {
         #[pymethods]
         impl #i {
-            #doc
-            fn unset_measurements(&mut self) -> PyResult<()> {
-                Ok(self.0.unset_measurements()?)
+            #hist_doc
+            fn channel_histogram(
+                &self,
+                name: #shortname_path,
+                bins: std::num::NonZeroUsize,
+                scaled: bool,
+            ) -> PyResult<fireflow_core::histogram::Histogram> {
+                Ok(self.0.channel_histogram(&name, bins, scaled)?)
+            }
+
+            #pct_doc
+            fn channel_percentiles(
+                &self,
+                name: #shortname_path,
+                percentiles: Vec<f64>,
+                bins: std::num::NonZeroUsize,
+                scaled: bool,
+            ) -> PyResult<Vec<Option<f64>>> {
+                let h = self.0.channel_histogram(&name, bins, scaled)?;
+                Ok(percentiles
+                    .into_iter()
+                    .map(|q| h.percentile(q))
+                    .collect::<Result<_, _>>()?)
             }
         }
     }
@@ -1956,24 +3380,47 @@ pub fn impl_coretext_unset_measurements(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_coredataset_unset_data(input: TokenStream) -> TokenStream {
+pub fn impl_coredataset_column_metadata(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
     let _ = split_ident_version_checked("PyCoreDataset", &i);
 
+    let s0 = "Keys will be the *$PnN* of each column in the same order as \
+              *DATA*. Each value is a dictionary with *longname* (*$PnS*), \
+              *datatype* (*$DATATYPE* or *$PnDATATYPE*), *range* (*$PnR*), \
+              and *transform* (*$PnE* and/or *$PnG*) for the column.";
+    let s1 = "This is meant to be carried alongside :py:attr:`data` so \
+              downstream consumers do not need to look up each measurement.";
+
     let doc = DocString::new(
-        "Remove all measurements and their data.".into(),
-        vec!["Raise exception if any keywords (such as *$TR*) reference a measurement.".into()],
+        "Metadata for each column in *DATA*.".into(),
+        vec![s0.into(), s1.into()],
         DocSelf::PySelf,
         vec![],
-        None,
-    );
+        Some(DocReturn::new(
+            PyType::new_dict(
+                PyType::Str,
+                PyType::PyClass("pyreflow.typing.ColumnMetadata".into()),
+            ),
+            None,
+        )),
+    )
+    .doc();
 
     quote! {
         #[pymethods]
         impl #i {
             #doc
-            fn unset_data(&mut self) -> PyResult<()> {
-                Ok(self.0.unset_data()?)
+            #[getter]
+            fn column_metadata<'py>(
+                &self,
+                py: Python<'py>,
+            ) -> PyResult<Bound<'py, pyo3::types::PyDict>> {
+                let d = pyo3::types::PyDict::new(py);
+                let ns = self.0.all_shortnames();
+                for (n, m) in ns.into_iter().zip(self.0.column_metadata()) {
+                    d.set_item(n, m)?;
+                }
+                Ok(d)
             }
         }
     }
@@ -1981,33 +3428,34 @@ pub fn impl_coredataset_unset_data(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn impl_coredataset_truncate_data(input: TokenStream) -> TokenStream {
+pub fn impl_coredataset_data_arrow(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
     let _ = split_ident_version_checked("PyCoreDataset", &i);
 
-    let p = DocArg::new_param_def(
-        "skip_conv_check".into(),
-        PyType::Bool,
-        "If ``True``, silently truncate data; otherwise return warnings when \
-         truncation is performed."
-            .into(),
-        DocDefault::Bool(false),
-    );
+    let s0 = "Columns will be named using *$PnN* as with :py:attr:`data`.";
+    let s1 = "This uses the Arrow C data interface and does not depend on \
+              polars, so it may be used if the installed polars version is \
+              incompatible with this library.";
 
     let doc = DocString::new(
-        "Coerce all values in DATA to fit within types specified in layout.".into(),
-        vec!["This will always create a new copy of DATA in-place.".into()],
+        "Return *DATA* as a pyarrow Table.".into(),
+        vec![s0.into(), s1.into()],
         DocSelf::PySelf,
-        vec![p],
-        None,
-    );
+        vec![],
+        Some(DocReturn::new(
+            PyType::PyClass("pyarrow.Table".into()),
+            None,
+        )),
+    )
+    .doc();
 
     quote! {
         #[pymethods]
         impl #i {
             #doc
-            fn truncate_data(&mut self, skip_conv_check: bool) -> PyResult<()> {
-                self.0.truncate_data(skip_conv_check).py_term_resolve_noerror()
+            fn data_arrow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+                let ns = self.0.all_shortnames().iter().map(|n| n.to_string()).collect();
+                self.0.data().clone().into_pyarrow(py, Some(ns))
             }
         }
     }
@@ -2233,7 +3681,7 @@ pub fn impl_new_meas(input: TokenStream) -> TokenStream {
         ArgData::new_meas_kw_opt_arg("DetectorType", "detector_type", "T", PyType::Str);
 
     let percent_emitted =
-        ArgData::new_meas_kw_opt_arg("PercentEmitted", "percent_emitted", "P", PyType::Str);
+        ArgData::new_meas_kw_opt_arg("PercentEmitted", "percent_emitted", "P", PyType::Float);
 
     let detector_voltage =
         ArgData::new_meas_kw_opt_arg("DetectorVoltage", "detector_voltage", "V", PyType::Float);
@@ -2758,7 +4206,8 @@ impl ArgData {
             "A dataframe encoding the contents of *DATA*. Number of columns must \
              match number of measurements. May be empty. Types do not necessarily \
              need to correspond to those in the data layout but mismatches may \
-             result in truncation."
+             result in truncation. *$TOT* is always computed from the number of \
+             rows when writing."
                 .into(),
         );
         // use polars df here because we need to manually add names
@@ -3286,6 +4735,8 @@ impl ArgData {
         let time_pattern = ArgData::time_pattern_arg();
         let allow_pseudostandard = ArgData::allow_pseudostandard_arg();
         let allow_unused_standard = ArgData::allow_unused_standard_arg();
+        let absorb_orphan_meas = ArgData::absorb_orphan_measurement_keywords_arg();
+        let disallow_orphan_meas = ArgData::disallow_orphan_measurement_keywords_arg();
         let disallow_deprecated = ArgData::disallow_deprecated_arg();
         let fix_log_scale_offsets = ArgData::fix_log_scale_offsets_arg();
         let nonstandard_measurement_pattern = ArgData::nonstandard_measurement_pattern_arg();
@@ -3299,6 +4750,8 @@ impl ArgData {
             time_pattern,
             allow_pseudostandard,
             allow_unused_standard,
+            absorb_orphan_meas,
+            disallow_orphan_meas,
             disallow_deprecated,
             fix_log_scale_offsets,
             nonstandard_measurement_pattern,
@@ -3318,12 +4771,14 @@ impl ArgData {
         let integer_widths_from_byteord = ArgData::integer_widths_from_byteord_arg();
         let integer_byteord_override = ArgData::integer_byteord_override_arg();
         let disallow_range_truncation = ArgData::disallow_range_truncation_arg();
+        let disallow_mixed_float_byteord = ArgData::disallow_mixed_float_byteord_arg();
 
         match version {
             Version::FCS2_0 | Version::FCS3_0 => [
                 integer_widths_from_byteord,
                 integer_byteord_override,
                 disallow_range_truncation,
+                disallow_mixed_float_byteord,
             ]
             .into_iter()
             .collect(),
@@ -3363,8 +4818,9 @@ impl ArgData {
 
     fn shared_config_args() -> Vec<Self> {
         let warnings_are_errors = ArgData::warnings_are_errors_arg();
+        let max_warnings = ArgData::max_warnings_arg();
 
-        vec![warnings_are_errors]
+        vec![warnings_are_errors, max_warnings]
     }
 
     fn time_meas_pattern_arg() -> Self {
@@ -3479,6 +4935,26 @@ impl ArgData {
         )
     }
 
+    fn absorb_orphan_measurement_keywords_arg() -> Self {
+        ArgData::new_config_bool_arg(
+            "absorb_orphan_measurement_keywords".into(),
+            "If ``True`` move *$Pn\\** keywords whose index is 0, exceeds \
+             *$PAR*, or has leading zeros into nonstandard keywords (without \
+             the leading *$*)."
+                .into(),
+        )
+    }
+
+    fn disallow_orphan_measurement_keywords_arg() -> Self {
+        ArgData::new_config_bool_arg(
+            "disallow_orphan_measurement_keywords".into(),
+            "If ``True`` throw error if *$Pn\\** keywords are found whose \
+             index is 0, exceeds *$PAR*, or has leading zeros. Otherwise \
+             throw a warning."
+                .into(),
+        )
+    }
+
     fn disallow_deprecated_arg() -> Self {
         ArgData::new_config_bool_arg(
             "disallow_deprecated".into(),
@@ -3537,6 +5013,16 @@ impl ArgData {
         )
     }
 
+    fn disallow_mixed_float_byteord_arg() -> Self {
+        ArgData::new_config_bool_arg(
+            "disallow_mixed_float_byteord".into(),
+            "If ``True`` throw error if *$BYTEORD* is neither big nor little \
+             endian when *$DATATYPE* is ``F`` or ``D``. Only has an effect \
+             for FCS 2.0/3.0."
+                .into(),
+        )
+    }
+
     fn new_config_correction_arg(name: &str, what: &str, location: &str, rstype: Path) -> Self {
         ArgData::new_config_arg(
             name.into(),
@@ -3636,6 +5122,23 @@ impl ArgData {
             "If ``True`` all warnings will be regarded as errors.".into(),
         )
     }
+
+    fn max_warnings_arg() -> Self {
+        ArgData::new_config_arg(
+            "max_warnings".into(),
+            PyType::new_opt(PyType::Int),
+            "Maximum number of warnings to emit. Any further warnings will be \
+             dropped and summarized with one final warning. Errors are limited \
+             the same way, although at least one is always kept. If ``None``, \
+             emit all warnings and errors."
+                .into(),
+            DocDefault::Other(
+                quote!(Some(fireflow_core::config::DEFAULT_MAX_WARNINGS)),
+                "10000".into(),
+            ),
+            parse_quote!(Option<usize>),
+        )
+    }
 }
 
 #[proc_macro]
@@ -3659,7 +5162,7 @@ pub fn impl_core_all_pno(input: TokenStream) -> TokenStream {
 #[proc_macro]
 pub fn impl_core_all_pnp(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
-    core_all_optical_attr(&i, "PercentEmitted", "percents_emitted", "P", PyType::Str)
+    core_all_optical_attr(&i, "PercentEmitted", "percents_emitted", "P", PyType::Float)
 }
 
 #[proc_macro]
@@ -3698,6 +5201,81 @@ pub fn impl_core_all_pnl_new(input: TokenStream) -> TokenStream {
     )
 }
 
+#[proc_macro]
+pub fn impl_core_legacy(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+
+    let doc = DocString::new(
+        "Keywords from previous versions which were found in *TEXT*.".into(),
+        vec!["These are not part of FCS 3.2 (ie *$COMP*, *$UNICODE*, \
+             *$GATE*, *$Gn\\**, *$PKn*, etc) and will not be written. Each \
+             triggers a deprecation warning when read (or an error if \
+             ``disallow_deprecated`` is ``True``). They are kept here rather \
+             than being treated as pseudostandard so they may be migrated \
+             explicitly."
+            .into()],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(
+            PyType::new_dict(PyType::Str, PyType::Str),
+            None,
+        )),
+    )
+    .doc();
+
+    quote! {
+        #[pymethods]
+        impl #t {
+            #doc
+            #[getter]
+            fn legacy(&self) -> std::collections::HashMap<String, String> {
+                self.0.legacy().to_pairs()
+            }
+        }
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn impl_core_carrier(input: TokenStream) -> TokenStream {
+    let t = parse_macro_input!(input as Ident);
+    let _ = split_ident_version_pycore(&t);
+    let carrier_type = PyType::new_opt(PyType::PyClass("pyreflow.typing.Carrier".into()));
+    let path = quote!(fireflow_core::text::carrier::Carrier);
+
+    let doc = DocString::new(
+        "Value of *$CARRIERID*, *$CARRIERTYPE*, and *$LOCATIONID*.".into(),
+        vec![
+            "Will be ``None`` if all are missing. Raise exception if any \
+             value is blank or if *$CARRIERTYPE* or *$LOCATIONID* is present \
+             without *$CARRIERID*. Setting to ``None`` removes all three."
+                .into(),
+        ],
+        DocSelf::PySelf,
+        vec![],
+        Some(DocReturn::new(carrier_type, None)),
+    )
+    .doc();
+
+    quote! {
+        #[pymethods]
+        impl #t {
+            #doc
+            #[getter]
+            fn get_carrier(&self) -> PyResult<Option<#path>> {
+                Ok(self.0.carrier()?)
+            }
+
+            #[setter]
+            fn set_carrier(&mut self, c: Option<#path>) -> PyResult<()> {
+                Ok(self.0.set_carrier(c)?)
+            }
+        }
+    }
+    .into()
+}
+
 #[proc_macro]
 pub fn impl_core_all_pnd(input: TokenStream) -> TokenStream {
     let i: Ident = syn::parse(input).unwrap();
@@ -3891,6 +5469,19 @@ pub fn impl_core_to_version_x_y(input: TokenStream) -> TokenStream {
                       they must be discarded. Set to ``True`` to perform the \
                       conversion with such discarding; otherwise, remove the \
                       keywords manually before converting.";
+    let strip_desc = "If ``True``, remove or replace keywords which are \
+                      deprecated in FCS 3.2. ``$PLATEID`` and ``$WELLID`` \
+                      will be moved to ``$CARRIERID`` and ``$LOCATIONID``. \
+                      ``$DATE``/``$BTIM``/``$ETIM`` will be removed if \
+                      ``$BEGINDATETIME`` is set, or otherwise used to make \
+                      ``$BEGINDATETIME``/``$ENDDATETIME`` if \
+                      ``timestamp_offset`` is given. ``$MODE``, ``$PLATENAME``, \
+                      ``$PnP``, and all gating keywords will be removed. \
+                      Each change will be emitted as a warning.";
+    let record_desc = "If ``True``, store a JSON summary of standard keywords \
+                       which were added, removed, or changed in the \
+                       nonstandard keyword ``FIREFLOW_CONVERSION``. See \
+                       :py:attr:`conversion_log`.";
     let base = if is_dataset {
         "CoreDataset"
     } else {
@@ -3911,20 +5502,90 @@ pub fn impl_core_to_version_x_y(input: TokenStream) -> TokenStream {
                 param_desc.into(),
                 DocDefault::Bool(false),
             );
-            let doc = DocString::new(
-                format!("Convert to FCS {vs}."),
-                vec![sub.into()],
-                DocSelf::PySelf,
-                vec![param],
-                Some(DocReturn::new(
-                    PyType::PyClass(target_type.to_string()),
-                    Some(format!("A new class conforming to FCS {vs}")),
-                )),
+            let record_param = DocArg::new_param_def(
+                "record_conversion".into(),
+                PyType::Bool,
+                record_desc.into(),
+                DocDefault::Bool(false),
             );
-            quote! {
-                #doc
-                fn #fn_name(&self, force: bool) -> PyResult<#target_pytype> {
-                    self.0.clone().try_convert(force).py_termfail_resolve().map(|x| x.into())
+            // only 3.2 has deprecated keywords that can be stripped
+            if *v == Version::FCS3_2 {
+                let strip_param = DocArg::new_param_def(
+                    "strip_deprecated".into(),
+                    PyType::Bool,
+                    strip_desc.into(),
+                    DocDefault::Bool(false),
+                );
+                let offset_param = DocArg::new_param_def(
+                    "timestamp_offset".into(),
+                    PyType::new_opt(PyType::Timezone),
+                    "The timezone in which ``$DATE``, ``$BTIM``, and \
+                     ``$ETIM`` were recorded. These have no timezone, so \
+                     they will be kept as-is when ``strip_deprecated`` is \
+                     ``True`` unless this is given."
+                        .into(),
+                    DocDefault::Option,
+                );
+                let doc = DocString::new(
+                    format!("Convert to FCS {vs}."),
+                    vec![sub.into()],
+                    DocSelf::PySelf,
+                    vec![param, strip_param, offset_param, record_param],
+                    Some(DocReturn::new(
+                        PyType::PyClass(target_type.to_string()),
+                        Some(format!("A new class conforming to FCS {vs}")),
+                    )),
+                );
+                quote! {
+                    #doc
+                    fn #fn_name(
+                        &self,
+                        force: bool,
+                        strip_deprecated: bool,
+                        timestamp_offset: Option<chrono::FixedOffset>,
+                        record_conversion: bool,
+                    ) -> PyResult<#target_pytype> {
+                        self.0
+                            .clone()
+                            .try_convert(&fireflow_core::config::ConvertConfig {
+                                force,
+                                strip_deprecated,
+                                timestamp_offset,
+                                record_conversion,
+                            })
+                            .py_termfail_resolve()
+                            .map(|x| x.into())
+                    }
+                }
+            } else {
+                let doc = DocString::new(
+                    format!("Convert to FCS {vs}."),
+                    vec![sub.into()],
+                    DocSelf::PySelf,
+                    vec![param, record_param],
+                    Some(DocReturn::new(
+                        PyType::PyClass(target_type.to_string()),
+                        Some(format!("A new class conforming to FCS {vs}")),
+                    )),
+                );
+                quote! {
+                    #doc
+                    fn #fn_name(
+                        &self,
+                        force: bool,
+                        record_conversion: bool,
+                    ) -> PyResult<#target_pytype> {
+                        self.0
+                            .clone()
+                            .try_convert(&fireflow_core::config::ConvertConfig {
+                                force,
+                                strip_deprecated: false,
+                                timestamp_offset: None,
+                                record_conversion,
+                            })
+                            .py_termfail_resolve()
+                            .map(|x| x.into())
+                    }
                 }
             }
         })
@@ -4874,6 +6535,17 @@ fn param_index(desc: &str) -> DocArg {
     DocArg::new_param("index".into(), PyType::Int, desc.into())
 }
 
+fn param_parent_file() -> DocArg {
+    DocArg::new_param_def(
+        "parent".into(),
+        PyType::new_opt(PyType::PyClass("~pathlib.Path".into())),
+        "Path to the file this dataset was read from. If given, its filename \
+         and a hash of its contents will be recorded in each new dataset."
+            .into(),
+        DocDefault::Option,
+    )
+}
+
 fn param_col() -> DocArg {
     DocArg::new_param(
         "col".into(),
@@ -81,20 +81,28 @@ use fireflow_core::validated::keys::{StdKeywords, ValidKeywords};
 use fireflow_core::validated::shortname::Shortname;
 
 use fireflow_python_proc::{
-    impl_core_all_meas_nonstandard_keywords, impl_core_all_peak_attrs, impl_core_all_pnanalyte,
-    impl_core_all_pncal3_1, impl_core_all_pncal3_2, impl_core_all_pnd, impl_core_all_pndet,
-    impl_core_all_pnf, impl_core_all_pnfeature, impl_core_all_pnl_new, impl_core_all_pnl_old,
-    impl_core_all_pno, impl_core_all_pnp, impl_core_all_pns, impl_core_all_pnt,
-    impl_core_all_pntag, impl_core_all_pntype, impl_core_all_pnv, impl_core_all_shortnames_attr,
-    impl_core_all_shortnames_maybe_attr, impl_core_all_transforms_attr, impl_core_get_measurement,
-    impl_core_get_measurements, impl_core_get_set_timestep, impl_core_get_temporal,
-    impl_core_insert_measurement, impl_core_par, impl_core_push_measurement,
-    impl_core_remove_measurement, impl_core_rename_temporal, impl_core_replace_optical,
-    impl_core_replace_temporal, impl_core_set_measurements, impl_core_set_measurements_and_layout,
-    impl_core_set_temporal, impl_core_set_tr_threshold, impl_core_standard_keywords,
-    impl_core_to_version_x_y, impl_core_unset_temporal, impl_core_version, impl_core_write_dataset,
-    impl_core_write_text, impl_coredataset_from_kws, impl_coredataset_set_measurements_and_data,
-    impl_coredataset_truncate_data, impl_coredataset_unset_data, impl_coretext_from_kws,
+    impl_core_align_to_panel, impl_core_all_meas_nonstandard_keywords, impl_core_all_peak_attrs,
+    impl_core_all_pnanalyte, impl_core_all_pncal3_1, impl_core_all_pncal3_2, impl_core_all_pnd,
+    impl_core_all_pndet, impl_core_all_pnf, impl_core_all_pnfeature, impl_core_all_pnl_new,
+    impl_core_all_pnl_old, impl_core_all_pno, impl_core_all_pnp, impl_core_all_pns,
+    impl_core_all_pnt, impl_core_all_pntag, impl_core_all_pntype, impl_core_all_pnv,
+    impl_core_all_shortnames_attr, impl_core_all_shortnames_maybe_attr,
+    impl_core_all_transforms_attr, impl_core_carrier, impl_core_conversion_log,
+    impl_core_display_names, impl_core_environment, impl_core_extension_keywords,
+    impl_core_get_measurement, impl_core_get_measurements, impl_core_get_set_timestep,
+    impl_core_get_temporal, impl_core_grouped_keywords, impl_core_insert_measurement,
+    impl_core_keyword_diff, impl_core_keyword_origins, impl_core_legacy, impl_core_namespace,
+    impl_core_par, impl_core_provenance, impl_core_push_measurement, impl_core_remove_measurement,
+    impl_core_rename_temporal, impl_core_replace_optical, impl_core_replace_temporal,
+    impl_core_set_measurements, impl_core_set_measurements_and_layout, impl_core_set_temporal,
+    impl_core_set_tr_threshold, impl_core_standard_keywords, impl_core_to_version_x_y,
+    impl_core_unset_temporal, impl_core_vendor_timestamp, impl_core_version,
+    impl_core_write_dataset, impl_core_write_text, impl_coredataset_column_metadata,
+    impl_coredataset_data_arrow, impl_coredataset_demultiplex, impl_coredataset_duplicates,
+    impl_coredataset_from_kws, impl_coredataset_histogram, impl_coredataset_metadata_only,
+    impl_coredataset_set_measurements_and_data, impl_coredataset_solidify_delimited,
+    impl_coredataset_split_by_time, impl_coredataset_time_chunks, impl_coredataset_truncate_data,
+    impl_coredataset_unset_data, impl_coretext_example, impl_coretext_from_kws,
     impl_coretext_to_dataset, impl_coretext_unset_measurements, impl_gated_meas,
     impl_layout_byte_widths, impl_new_core, impl_new_delim_ascii_layout,
     impl_new_endian_float_layout, impl_new_endian_uint_layout, impl_new_fixed_ascii_layout,
@@ -104,7 +112,7 @@ use fireflow_python_proc::{
 
 use derive_more::{From, Into};
 use pyo3::prelude::*;
-use pyo3::types::PyTuple;
+use pyo3::types::{PyDict, PyTuple};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -135,11 +143,28 @@ pub fn py_fcs_read_std_text(
 
 #[pyfunction]
 #[pyo3(name = "_fcs_read_raw_dataset")]
+#[pyo3(signature = (p, conf, as_arrow=false))]
 pub fn py_fcs_read_raw_dataset(
+    py: Python<'_>,
     p: PathBuf,
     conf: cfg::ReadRawDatasetConfig,
-) -> PyResult<api::RawDatasetOutput> {
-    api::fcs_read_raw_dataset(&p, &conf).py_termfail_resolve()
+    as_arrow: bool,
+) -> PyResult<Bound<'_, PyDict>> {
+    let ret = api::fcs_read_raw_dataset(&p, &conf).py_termfail_resolve()?;
+    if as_arrow {
+        ret.into_pyobject_arrow(py)
+    } else {
+        ret.into_pyobject(py)
+    }
+}
+
+#[pyfunction]
+#[pyo3(name = "_fcs_read_raw_dataset_passthru")]
+pub fn py_fcs_read_raw_dataset_passthru(
+    p: PathBuf,
+    conf: cfg::ReadRawDatasetConfig,
+) -> PyResult<api::RawDatasetPassthruOutput> {
+    api::fcs_read_raw_dataset_passthru(&p, &conf).py_termfail_resolve()
 }
 
 #[pyfunction]
@@ -152,9 +177,53 @@ pub fn py_fcs_read_std_dataset(
     Ok((core.into(), data))
 }
 
+#[pyfunction]
+#[pyo3(name = "_fcs_read_std_datasets")]
+pub fn py_fcs_read_std_datasets(
+    py: Python<'_>,
+    p: PathBuf,
+    conf: cfg::ReadStdDatasetConfig,
+) -> PyResult<Vec<Bound<'_, PyAny>>> {
+    api::fcs_read_std_datasets(&p, &conf)
+        .into_iter()
+        .map(|res| match res.py_termfail_resolve() {
+            Ok((core, data)) => (PyAnyCoreDataset::from(core), data)
+                .into_pyobject(py)
+                .map(|x| x.into_any()),
+            // return the exception rather than raising it so that the other
+            // datasets are still accessible
+            Err(e) => Ok(e.into_value(py).into_bound(py).into_any()),
+        })
+        .collect()
+}
+
+#[pyfunction]
+#[pyo3(name = "_fcs_read_std_datasets_concatenated")]
+pub fn py_fcs_read_std_datasets_concatenated(
+    py: Python<'_>,
+    p: PathBuf,
+    conf: cfg::ReadStdDatasetConfig,
+) -> PyResult<Vec<(u64, Bound<'_, PyAny>)>> {
+    api::fcs_read_std_datasets_concatenated(&p, &conf)
+        .into_iter()
+        .map(|(offset, res)| {
+            let x = match res.py_termfail_resolve() {
+                Ok((core, data)) => (PyAnyCoreDataset::from(core), data)
+                    .into_pyobject(py)
+                    .map(|x| x.into_any())?,
+                Err(e) => e.into_value(py).into_bound(py).into_any(),
+            };
+            Ok((offset, x))
+        })
+        .collect()
+}
+
 #[pyfunction]
 #[pyo3(name = "_fcs_read_raw_dataset_with_keywords")]
+#[pyo3(signature = (p, version, std, data_seg, analysis_seg, other_segs, conf, as_arrow=false))]
+#[allow(clippy::too_many_arguments)]
 pub fn py_fcs_read_raw_dataset_with_keywords(
+    py: Python<'_>,
     p: PathBuf,
     version: Version,
     std: StdKeywords,
@@ -162,8 +231,9 @@ pub fn py_fcs_read_raw_dataset_with_keywords(
     analysis_seg: HeaderAnalysisSegment,
     other_segs: Vec<OtherSegment20>,
     conf: cfg::ReadRawDatasetFromKeywordsConfig,
-) -> PyResult<api::RawDatasetWithKwsOutput> {
-    api::fcs_read_raw_dataset_with_keywords(
+    as_arrow: bool,
+) -> PyResult<Bound<'_, PyDict>> {
+    let ret = api::fcs_read_raw_dataset_with_keywords(
         &p,
         version,
         &std,
@@ -172,7 +242,12 @@ pub fn py_fcs_read_raw_dataset_with_keywords(
         other_segs,
         &conf,
     )
-    .py_termfail_resolve()
+    .py_termfail_resolve()?;
+    if as_arrow {
+        ret.into_pyobject_arrow(py)
+    } else {
+        ret.into_pyobject(py)
+    }
 }
 
 #[pyfunction]
@@ -320,7 +395,38 @@ macro_rules! impl_common {
         impl_core_all_meas_nonstandard_keywords!($pytype);
 
         // method to return all standard keywords as read-only dict
+        impl_core_grouped_keywords!($pytype);
         impl_core_standard_keywords!($pytype);
+
+        // attribute to get/set provenance and method to record it
+        impl_core_provenance!($pytype);
+
+        // attribute to get environmental conditions from vendor keywords
+        impl_core_environment!($pytype);
+
+        // attributes/method to get and fill start time from vendor keywords
+        impl_core_vendor_timestamp!($pytype);
+
+        // method to name measurements for plotting
+        impl_core_display_names!($pytype);
+
+        // methods to compute and apply keyword diffs
+        impl_core_keyword_diff!($pytype);
+
+        // attribute/method to tell keywords read from TEXT from those filled in
+        impl_core_keyword_origins!($pytype);
+
+        // attribute to get keywords defined by extensions to the standard
+        impl_core_extension_keywords!($pytype);
+
+        // methods to get/set nonstandard keywords by namespace
+        impl_core_namespace!($pytype);
+
+        // attribute to get/set summary of last version conversion
+        impl_core_conversion_log!($pytype);
+
+        // method to reorder measurements to match a reference panel
+        impl_core_align_to_panel!($pytype);
     };
 }
 
@@ -339,6 +445,12 @@ impl_coretext_from_kws!(core::CoreTEXT3_0);
 impl_coretext_from_kws!(core::CoreTEXT3_1);
 impl_coretext_from_kws!(core::CoreTEXT3_2);
 
+// impl example for all CoreTEXT*
+impl_coretext_example!(core::CoreTEXT2_0);
+impl_coretext_example!(core::CoreTEXT3_0);
+impl_coretext_example!(core::CoreTEXT3_1);
+impl_coretext_example!(core::CoreTEXT3_2);
+
 // impl from_kws for all CoreTEXT*
 impl_coredataset_from_kws!(core::CoreDataset2_0);
 impl_coredataset_from_kws!(core::CoreDataset3_0);
@@ -365,6 +477,15 @@ macro_rules! impl_coredataset_common {
         impl_core_write_dataset!($pytype);
         impl_coredataset_unset_data!($pytype);
         impl_coredataset_truncate_data!($pytype);
+        impl_coredataset_solidify_delimited!($pytype);
+        impl_coredataset_metadata_only!($pytype);
+        impl_coredataset_split_by_time!($pytype);
+        impl_coredataset_time_chunks!($pytype);
+        impl_coredataset_demultiplex!($pytype);
+        impl_coredataset_duplicates!($pytype);
+        impl_coredataset_column_metadata!($pytype);
+        impl_coredataset_histogram!($pytype);
+        impl_coredataset_data_arrow!($pytype);
     };
 }
 
@@ -444,6 +565,14 @@ impl_core_all_pnfeature!(PyCoreDataset3_2);
 impl_core_all_pnanalyte!(PyCoreTEXT3_2);
 impl_core_all_pnanalyte!(PyCoreDataset3_2);
 
+// Get/set $CARRIERID/$CARRIERTYPE/$LOCATIONID together (3.2)
+impl_core_carrier!(PyCoreTEXT3_2);
+impl_core_carrier!(PyCoreDataset3_2);
+
+// Keywords from previous versions (3.2)
+impl_core_legacy!(PyCoreTEXT3_2);
+impl_core_legacy!(PyCoreDataset3_2);
+
 #[derive(From, Into, Default)]
 struct PyAppliedGates2_0(AppliedGates2_0);
 
@@ -47,17 +47,25 @@ from .api import (
     fcs_read_raw_text,
     fcs_read_std_text,
     fcs_read_raw_dataset,
+    fcs_read_raw_dataset_passthru,
     fcs_read_std_dataset,
+    fcs_read_std_datasets,
+    fcs_read_std_datasets_concatenated,
     fcs_read_raw_dataset_with_keywords,
     fcs_read_std_dataset_with_keywords,
+    rewrite_fcs_file,
     ReadHeaderOutput,
     ReadRawTEXTOutput,
     ReadStdTEXTOutput,
     ReadRawDatasetOutput,
+    ReadRawDatasetPassthruOutput,
+    PassthruLayout,
+    PartialPassthruLayout,
     ReadStdDatasetOutput,
     ReadRawDatasetFromKwsOutput,
     ReadStdDatasetFromKwsOutput,
 )
+from .interop import to_anndata, to_flowkit_sample
 
 
 __all__ = [
@@ -105,15 +113,24 @@ __all__ = [
     "fcs_read_raw_text",
     "fcs_read_std_text",
     "fcs_read_raw_dataset",
+    "fcs_read_raw_dataset_passthru",
     "fcs_read_std_dataset",
+    "fcs_read_std_datasets",
+    "fcs_read_std_datasets_concatenated",
     "fcs_read_raw_dataset_with_keywords",
     "fcs_read_std_dataset_with_keywords",
+    "rewrite_fcs_file",
+    "to_anndata",
+    "to_flowkit_sample",
     "PyreflowWarning",
     "PyreflowException",
     "ReadHeaderOutput",
     "ReadRawTEXTOutput",
     "ReadStdTEXTOutput",
     "ReadRawDatasetOutput",
+    "ReadRawDatasetPassthruOutput",
+    "PassthruLayout",
+    "PartialPassthruLayout",
     "ReadStdDatasetOutput",
     "ReadRawDatasetFromKwsOutput",
     "ReadStdDatasetFromKwsOutput",
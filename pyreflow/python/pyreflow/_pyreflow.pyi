@@ -1,10 +1,11 @@
 from __future__ import annotations
 from pathlib import Path
-from datetime import time, date, datetime
+from datetime import time, date, datetime, timezone
 from typing import TypeVar, Self, Generic, Union, final
 
 from polars import Series, DataFrame
 import numpy as np
+import pyarrow as pa
 import numpy.typing as npt
 
 # TODO not sure why mypy complains about this
@@ -45,6 +46,23 @@ from pyreflow.typing import (
     FCSVersion,
     TemporalOpticalKey,
     Segment,
+    ColumnMetadata,
+    GroupedKeywords,
+    Histogram,
+    DuplicateReport,
+    WriteReport,
+    Provenance,
+    Environment,
+    KeyConvention,
+    VendorTimestamp,
+    Carrier,
+    KeywordDiff,
+    MeasRemoval,
+    KeywordOrigin,
+    ControlCharPolicy,
+    DisplayNameFormat,
+    FloatRounding,
+    ConversionLog,
 )
 
 _X = TypeVar("_X")
@@ -232,7 +250,7 @@ class _OpticalCommon:
     detector_type: str | None
     detector_voltage: float | None
     power: float | None
-    percent_emitted: str | None
+    percent_emitted: float | None
 
 class _OpticalScaleTransform:
     transform: ScaleTransform
@@ -253,7 +271,7 @@ class Optical2_0(_MeasCommon, _OpticalCommon, _OpticalWavelength, _PeakCommon):
         filter: str | None = None,
         power: float | None = None,
         detector_type: str | None = None,
-        percent_emitted: str | None = None,
+        percent_emitted: float | None = None,
         detector_voltage: float | None = None,
         longname: str | None = None,
         nonstandard_keywords: NonStdKeywords = {},
@@ -272,7 +290,7 @@ class Optical3_0(
         filter: str | None = None,
         power: float | None = None,
         detector_type: str | None = None,
-        percent_emitted: str | None = None,
+        percent_emitted: float | None = None,
         detector_voltage: float | None = None,
         longname: str | None = None,
         nonstandard_keywords: NonStdKeywords = {},
@@ -300,7 +318,7 @@ class Optical3_1(
         filter: str | None = None,
         power: float | None = None,
         detector_type: str | None = None,
-        percent_emitted: str | None = None,
+        percent_emitted: float | None = None,
         detector_voltage: float | None = None,
         longname: str | None = None,
         nonstandard_keywords: NonStdKeywords = {},
@@ -336,7 +354,7 @@ class Optical3_2(
         filter: str | None = None,
         power: float | None = None,
         detector_type: str | None = None,
-        percent_emitted: str | None = None,
+        percent_emitted: float | None = None,
         detector_voltage: float | None = None,
         longname: str | None = None,
         nonstandard_keywords: NonStdKeywords = {},
@@ -487,12 +505,36 @@ class _CoreCommon:
 
     all_filters: _OpticalKeyVals[str]
     all_powers: _OpticalKeyVals[float]
-    all_percents_emitted: _OpticalKeyVals[int]
+    all_percents_emitted: _OpticalKeyVals[float]
     all_detector_types: _OpticalKeyVals[str]
     all_detector_voltages: _OpticalKeyVals[float]
     all_meas_nonstandard_keywords: list[NonStdKeywords]
 
     nonstandard_keywords: NonStdKeywords
+    provenance: Provenance | None
+    def record_provenance(self, operation: str, parent: Path | None = None) -> None: ...
+    def environment(self, conventions: list[KeyConvention]) -> Environment: ...
+    @property
+    def vendor_timestamp(self) -> VendorTimestamp | None: ...
+    def fill_timestamps(self) -> VendorTimestamp | None: ...
+    @property
+    def timestamp_source(self) -> str | None: ...
+    def display_names(
+        self, format: DisplayNameFormat = "longname", separator: str = " :: "
+    ) -> list[str]: ...
+    def keyword_diff(self, other: dict[str, str]) -> KeywordDiff: ...
+    def apply_diff(self, diff: KeywordDiff) -> None: ...
+    def namespace_keywords(self, prefix: str) -> dict[str, str]: ...
+    def set_namespace_keywords(
+        self, prefix: str, keywords: dict[str, str]
+    ) -> None: ...
+    conversion_log: ConversionLog | None
+    def align_to_panel(
+        self,
+        reference: list[str],
+        allow_missing: bool = False,
+        allow_extra: bool = False,
+    ) -> None: ...
     def standard_keywords(
         self,
         exclude_req_root: bool = False,
@@ -500,6 +542,12 @@ class _CoreCommon:
         exclude_req_meas: bool = False,
         exclude_opt_meas: bool = False,
     ) -> dict[str, str]: ...
+    def grouped_keywords(self) -> GroupedKeywords: ...
+    @property
+    def keyword_origins(self) -> dict[str, KeywordOrigin]: ...
+    def keyword_origin(self, key: str) -> KeywordOrigin: ...
+    @property
+    def extension_keywords(self) -> dict[str, str]: ...
     @property
     def par(self) -> int: ...
     def set_trigger_threshold(self, threshold: int) -> bool: ...
@@ -510,13 +558,34 @@ class _CoreCommon:
     def version(self) -> FCSVersion: ...
 
 class _CoreDatasetCommon:
+    @property
+    def column_metadata(self) -> dict[Shortname, ColumnMetadata]: ...
+    def data_arrow(self) -> pa.Table: ...
+    def channel_histogram(
+        self, name: Shortname, bins: int = 256, scaled: bool = False
+    ) -> Histogram: ...
+    def channel_percentiles(
+        self,
+        name: Shortname,
+        percentiles: list[float],
+        bins: int = 1024,
+        scaled: bool = False,
+    ) -> list[float | None]: ...
     def write_dataset(
         self,
         path: Path,
         delim: int = 30,
         big_other: bool = False,
+        other_pairs: int | None = None,
         skip_conversion_check: bool = False,
-    ) -> None: ...
+        segment_alignment: int | None = None,
+        padding: int = 0,
+        namespaces: list[str] = [],
+        float_rounding: FloatRounding = "error",
+        float_rounding_overrides: dict[int, FloatRounding] = {},
+        control_chars: ControlCharPolicy = "preserve",
+        record_provenance: bool = False,
+    ) -> WriteReport: ...
 
 class _CoreShortnamesMaybe:
     all_shortnames_maybe: list[Shortname | None]
@@ -562,6 +631,7 @@ class _CoreGetSetMeas(Generic[_N, _O, _T]):
         self, name: Shortname
     ) -> tuple[MeasIndex, _O | _T]: ...
     def remove_measurement_by_index(self, index: MeasIndex) -> tuple[_N, _O | _T]: ...
+    def remove_measurement_and_links(self, name: Shortname) -> MeasRemoval: ...
     def measurement_at(self, index: MeasIndex) -> _O | _T: ...
     def replace_optical_at(self, index: MeasIndex, meas: _O) -> _O | _T: ...
     def replace_optical_named(self, name: Shortname, meas: _O) -> _O | _T | None: ...
@@ -592,6 +662,9 @@ class _CoreTEXTGetSetMeas(Generic[_N, _T, _O]):
     def insert_optical(
         self, index: MeasIndex, meas: _O, name: _N, range: Range, notrunc: bool = False
     ) -> None: ...
+    def push_optical_like(
+        self, index: MeasIndex, name: _N, range: Range, notrunc: bool = False
+    ) -> None: ...
     def push_temporal(
         self, meas: _T, name: Shortname, range: Range, notrunc: bool = False
     ) -> None: ...
@@ -612,6 +685,14 @@ class _CoreDatasetGetSetMeas(Generic[_N, _T, _O]):
     def push_optical(
         self, meas: _O, col: Series, name: _N, range: Range, notrunc: bool = False
     ) -> None: ...
+    def push_optical_like(
+        self,
+        index: MeasIndex,
+        col: Series,
+        name: _N,
+        range: Range,
+        notrunc: bool = False,
+    ) -> None: ...
     def insert_optical(
         self,
         index: MeasIndex,
@@ -640,6 +721,21 @@ class _CoreDatasetGetSetMeas(Generic[_N, _T, _O]):
     ) -> None: ...
     def unset_data(self) -> None: ...
     def truncate_data(self, skip_conv_check: bool = False) -> None: ...
+    @property
+    def delimited_widths(self) -> list[int] | None: ...
+    def solidify_delimited(self, endian: Endian | None = None) -> bool: ...
+    def to_metadata_only(self, keep_analysis: bool = False) -> Self: ...
+    def is_metadata_only(self) -> bool: ...
+    def time_chunks(self, window: float) -> list[tuple[int, int]] | None: ...
+    def split_by_time(
+        self, interval: float, parent: Path | None = None
+    ) -> list[Self] | None: ...
+    def demultiplex(
+        self, rules: list[tuple[Shortname, float]], parent: Path | None = None
+    ) -> list[Self]: ...
+    def find_duplicate_events(
+        self, channels: list[Shortname] = []
+    ) -> DuplicateReport: ...
     data: DataFrame
 
 class _CoreGetSetMeasOrdered(Generic[_O, _T]):
@@ -772,6 +868,9 @@ class _Core3_2:
     carriertype: str | None
     carrierid: str | None
     locationid: str | None
+    carrier: Carrier | None
+    @property
+    def legacy(self) -> dict[str, str]: ...
     begindatetime: datetime | None
     enddatetime: datetime | None
 
@@ -795,16 +894,28 @@ class _CoreToDataset(Generic[_X]):
     ) -> _X: ...
 
 class _CoreTo2_0(Generic[_X]):
-    def to_version_2_0(self, force: bool = False) -> _X: ...
+    def to_version_2_0(
+        self, force: bool = False, record_conversion: bool = False
+    ) -> _X: ...
 
 class _CoreTo3_0(Generic[_X]):
-    def to_version_3_0(self, force: bool = False) -> _X: ...
+    def to_version_3_0(
+        self, force: bool = False, record_conversion: bool = False
+    ) -> _X: ...
 
 class _CoreTo3_1(Generic[_X]):
-    def to_version_3_1(self, force: bool = False) -> _X: ...
+    def to_version_3_1(
+        self, force: bool = False, record_conversion: bool = False
+    ) -> _X: ...
 
 class _CoreTo3_2(Generic[_X]):
-    def to_version_3_2(self, force: bool = False) -> _X: ...
+    def to_version_3_2(
+        self,
+        force: bool = False,
+        strip_deprecated: bool = False,
+        timestamp_offset: timezone | None = None,
+        record_conversion: bool = False,
+    ) -> _X: ...
 
 @final
 class CoreTEXT2_0(
@@ -818,6 +929,7 @@ class CoreTEXT2_0(
     _CoreGetSetMeasOrdered[Optical2_0, Temporal2_0],
     _CoreSetShortnamesMaybe,
     _CoreScaleMethods,
+    _CoreScaleTransformMethods,
     _CoreToDataset[CoreDataset2_0],
     _CoreCompensation,
     _CoreMeasWavelength,
@@ -854,6 +966,8 @@ class CoreTEXT2_0(
         nonstandard_keywords: NonStdKeywords = {},
     ) -> Self: ...
     @classmethod
+    def example(cls, n: int = 2) -> Self: ...
+    @classmethod
     def from_kws(
         cls,
         std: StdKeywords,
@@ -866,13 +980,17 @@ class CoreTEXT2_0(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
         integer_widths_from_byteord: bool = False,
         integer_byteord_override: list[int] | None = None,
         disallow_range_truncation: bool = False,
+        disallow_mixed_float_byteord: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -932,6 +1050,8 @@ class CoreTEXT3_0(
         nonstandard_keywords: NonStdKeywords = {},
     ) -> Self: ...
     @classmethod
+    def example(cls, n: int = 2) -> Self: ...
+    @classmethod
     def from_kws(
         cls,
         std: StdKeywords,
@@ -944,6 +1064,8 @@ class CoreTEXT3_0(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -951,7 +1073,9 @@ class CoreTEXT3_0(
         integer_widths_from_byteord: bool = False,
         integer_byteord_override: list[int] | None = None,
         disallow_range_truncation: bool = False,
+        disallow_mixed_float_byteord: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1020,6 +1144,8 @@ class CoreTEXT3_1(
         nonstandard_keywords: NonStdKeywords = {},
     ) -> Self: ...
     @classmethod
+    def example(cls, n: int = 2) -> Self: ...
+    @classmethod
     def from_kws(
         cls,
         std: StdKeywords,
@@ -1032,6 +1158,8 @@ class CoreTEXT3_1(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -1039,6 +1167,7 @@ class CoreTEXT3_1(
         parse_indexed_spillover: bool = False,
         disallow_range_truncation: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1110,6 +1239,8 @@ class CoreTEXT3_2(
         nonstandard_keywords: NonStdKeywords = {},
     ) -> Self: ...
     @classmethod
+    def example(cls, n: int = 2) -> Self: ...
+    @classmethod
     def from_kws(
         cls,
         std: StdKeywords,
@@ -1122,6 +1253,8 @@ class CoreTEXT3_2(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -1129,6 +1262,7 @@ class CoreTEXT3_2(
         parse_indexed_spillover: bool = False,
         disallow_range_truncation: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1143,6 +1277,7 @@ class CoreDataset2_0(
     _CoreGetSetMeasOrdered[Optical2_0, Temporal2_0],
     _CoreDatasetGetSetMeasOrdered[Optical2_0, Temporal2_0],
     _CoreScaleMethods,
+    _CoreScaleTransformMethods,
     _CoreSetShortnamesMaybe,
     _CoreCompensation,
     _CoreMeasWavelength,
@@ -1199,15 +1334,19 @@ class CoreDataset2_0(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
         integer_widths_from_byteord: bool = False,
         integer_byteord_override: list[int] | None = None,
         disallow_range_truncation: bool = False,
+        disallow_mixed_float_byteord: bool = False,
         allow_uneven_event_width: bool = False,
         allow_tot_mismatch: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1287,6 +1426,8 @@ class CoreDataset3_0(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -1294,6 +1435,7 @@ class CoreDataset3_0(
         integer_widths_from_byteord: bool = False,
         integer_byteord_override: list[int] | None = None,
         disallow_range_truncation: bool = False,
+        disallow_mixed_float_byteord: bool = False,
         text_data_correction: tuple[int, int] = (0, 0),
         text_analysis_correction: tuple[int, int] = (0, 0),
         ignore_text_data_offsets: bool = False,
@@ -1304,6 +1446,7 @@ class CoreDataset3_0(
         allow_uneven_event_width: bool = False,
         allow_tot_mismatch: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1392,6 +1535,8 @@ class CoreDataset3_1(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -1408,6 +1553,7 @@ class CoreDataset3_1(
         allow_uneven_event_width: bool = False,
         allow_tot_mismatch: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 @final
@@ -1499,6 +1645,8 @@ class CoreDataset3_2(
         time_pattern: str | None = None,
         allow_pseudostandard: bool = False,
         allow_unused_standard: bool = False,
+        absorb_orphan_measurement_keywords: bool = False,
+        disallow_orphan_measurement_keywords: bool = False,
         disallow_deprecated: bool = False,
         fix_log_scale_offsets: bool = False,
         nonstandard_measurement_pattern: str | None = None,
@@ -1515,6 +1663,7 @@ class CoreDataset3_2(
         allow_uneven_event_width: bool = False,
         allow_tot_mismatch: bool = False,
         warnings_are_errors: bool = False,
+        max_warnings: int | None = 10000,
     ) -> Self: ...
 
 __version__: str
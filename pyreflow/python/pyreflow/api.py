@@ -1,4 +1,5 @@
 from ._pyreflow import _api  # type: ignore
+from ._pyreflow import PyreflowException
 from pyreflow.typing import (
     ByteOrd,
     StdKey,
@@ -12,10 +13,18 @@ from pyreflow.typing import (
     AnalysisBytes,
     OtherBytes,
     TemporalOpticalKey,
+    InstrumentRegistry,
+    KeywordExtensions,
+    ControlCharPolicy,
+    Datatype,
+    FloatRange,
+    KeywordDiff,
+    WriteReport,
 )
 from pathlib import Path
 from typing import Any, NamedTuple
 import polars as pl
+import pyarrow as pa
 import textwrap
 
 
@@ -90,6 +99,21 @@ class ExtraStdKeywords(NamedTuple):
     """
 
 
+class MeasWarnings(NamedTuple):
+    """
+    Warnings which pertain to one measurement.
+    """
+
+    index: int
+    """Index of the measurement (starting at 0)."""
+
+    shortname: str
+    """Name of the measurement (*$PnN* or its default)."""
+
+    warnings: list[str]
+    """Warning messages in the order they were encountered."""
+
+
 class StdTEXTData(NamedTuple):
     """
     Return data from reading standardized TEXT.
@@ -121,6 +145,24 @@ class StdTEXTData(NamedTuple):
 
     extra: ExtraStdKeywords
 
+    estimated_memory_bytes: int | None
+    """
+    Estimated number of bytes needed to read *DATA* into a dataframe.
+
+    This is the number of events multiplied by the size of the native type
+    for each column. It may be used to decide whether to read all of *DATA*
+    at once. This will be ``None`` if the number of events cannot be
+    determined, which may happen for delimited ASCII without *$TOT*.
+    """
+
+    meas_warnings: list[MeasWarnings]
+    """
+    Warnings which pertain to specific measurements, sorted by index.
+
+    These are also emitted as normal warnings; this is meant to make it easy
+    to show which channels were affected without parsing messages.
+    """
+
     parse: ParseData
 
 
@@ -133,6 +175,14 @@ class StdDatasetData(NamedTuple):
 
     extra: ExtraStdKeywords
 
+    meas_warnings: list[MeasWarnings]
+    """
+    Warnings which pertain to specific measurements, sorted by index.
+
+    These are also emitted as normal warnings; this is meant to make it easy
+    to show which channels were affected without parsing messages.
+    """
+
     data_seg: Segment
     """
     Segment used to read to *DATA*.
@@ -192,8 +242,82 @@ class ReadStdTEXTOutput(NamedTuple):
 class ReadRawDatasetOutput(NamedTuple):
     """Return value when reading raw dataset."""
 
-    data: pl.DataFrame
-    """The *DATA* segment as a polars dataframe."""
+    data: pl.DataFrame | pa.Table
+    """
+    The *DATA* segment as a polars dataframe.
+
+    This will be a pyarrow Table if ``as_arrow`` was ``True``.
+    """
+
+    analysis: AnalysisBytes
+    """The *ANALYSIS* segment as a byte sequence."""
+
+    others: list[OtherBytes]
+    """*OTHER* segments in the order defined in *HEADER* as a byte sequence."""
+
+    data_seg: Segment
+    """
+    Segment used to read *DATA*.
+
+    This will be the segment in *TEXT* if present and valid, otherwise it will
+    be the segment from *HEADER*.
+    """
+
+    analysis_seg: Segment
+    """
+    Segment used to read *ANALYSIS*.
+
+    This will be the segment in *TEXT* if present and valid, otherwise it will
+    be the segment from *HEADER*.
+    """
+
+    text: ReadRawTEXTOutput
+    """Other data from reading raw *TEXT*."""
+
+
+class PassthruLayout(NamedTuple):
+    """Layout which may be used to interpret raw *DATA*."""
+
+    datatype: Datatype
+    """Value for *$DATATYPE*."""
+
+    byteord: str
+    """Value for *$BYTEORD*."""
+
+    widths: list[str]
+    """Values for *$PnB*."""
+
+    ranges: list[FloatRange]
+    """Values for *$PnR*."""
+
+    datatypes: list[Datatype]
+    """Datatype for each column (*$PnDATATYPE* or *$DATATYPE*)."""
+
+
+class PartialPassthruLayout(NamedTuple):
+    """Layout keywords which could be read when the layout is invalid."""
+
+    datatype: Datatype | None
+    """Value for *$DATATYPE* if present and valid."""
+
+    byteord: str | None
+    """Value for *$BYTEORD* if present."""
+
+    widths: list[str | None]
+    """Values for *$PnB* for each measurement in *$PAR* (if valid)."""
+
+
+class ReadRawDatasetPassthruOutput(NamedTuple):
+    """Return value when reading raw dataset without parsing *DATA*."""
+
+    data: bytes
+    """The *DATA* segment exactly as it appears in the file."""
+
+    layout: PassthruLayout | None
+    """The layout of *DATA* or ``None`` if it could not be determined."""
+
+    partial_layout: PartialPassthruLayout | None
+    """Layout keywords which could be read if ``layout`` is ``None``."""
 
     analysis: AnalysisBytes
     """The *ANALYSIS* segment as a byte sequence."""
@@ -234,8 +358,12 @@ class ReadStdDatasetOutput(NamedTuple):
 class ReadRawDatasetFromKwsOutput(NamedTuple):
     """Return value from reading raw dataset using known keywords."""
 
-    data: pl.DataFrame
-    """The *DATA* segment as a polars dataframe."""
+    data: pl.DataFrame | pa.Table
+    """
+    The *DATA* segment as a polars dataframe.
+
+    This will be a pyarrow Table if ``as_arrow`` was ``True``.
+    """
 
     analysis: AnalysisBytes
     """The *ANALYSIS* segment as a byte sequence."""
@@ -293,13 +421,35 @@ def _to_parse_data(xs: dict[str, Any]) -> ParseData:
 
 
 def _to_std_text_data(xs: dict[str, Any]) -> StdTEXTData:
-    args: dict[str, Any] = {
-        k: _to_parse_data(v)
-        if k == "parse"
-        else (ExtraStdKeywords(**v) if k == "extra" else v)
-        for k, v in xs.items()
-    }
-    return StdTEXTData(**args)
+    def go(k: str, v: Any) -> Any:
+        if k == "parse":
+            return _to_parse_data(v)
+        if k == "extra":
+            return ExtraStdKeywords(**v)
+        if k == "meas_warnings":
+            return _to_meas_warnings(v)
+        return v
+
+    return StdTEXTData(**{k: go(k, v) for k, v in xs.items()})
+
+
+def _to_meas_warnings(xs: list[dict[str, Any]]) -> list[MeasWarnings]:
+    return [MeasWarnings(**x) for x in xs]
+
+
+def _to_std_dataset_output(
+    core: AnyCoreDataset, uncore: dict[str, Any]
+) -> ReadStdDatasetOutput:
+    return ReadStdDatasetOutput(
+        core=core,
+        uncore=StdDatasetData(
+            parse=_to_parse_data(uncore["parse"]),
+            extra=ExtraStdKeywords(**uncore["dataset"]["extra"]),
+            meas_warnings=_to_meas_warnings(uncore["meas_warnings"]),
+            data_seg=uncore["dataset"]["standardized"]["data_seg"],
+            analysis_seg=uncore["dataset"]["standardized"]["analysis_seg"],
+        ),
+    )
 
 
 def _to_raw_output(xs: dict[str, Any]) -> ReadRawTEXTOutput:
@@ -455,6 +605,27 @@ _RAW_ARGS: dict[str, list[str]] = {
             "it may also be sensible to enable ``allow_empty``."
         )
     ],
+    "control_chars": [
+        (
+            "How to handle control characters (ie newlines) in values. "
+            "``\"preserve\"`` keeps values as-is, ``\"escape\"`` replaces them "
+            "with escape sequences (ie ``\\n``), and ``\"strip\"`` removes them. "
+            "A warning will be emitted for each value which is escaped or stripped, "
+            "and values which are entirely control characters will be removed if "
+            "stripped."
+        )
+    ],
+    "keyword_allowlist": [
+        (
+            "If given, only keep these keys and stop reading *TEXT* once all "
+            "are found. Keys are matched case-insensitively as they appear in "
+            "*TEXT*, so standard keys must include the leading ``$``. "
+            "*$NEXTDATA* and the *STEXT* offsets (if required) are always kept. "
+            "This is meant for quickly pulling a few keywords from many files "
+            "with :py:func:`fcs_read_raw_text`; other readers will almost "
+            "certainly fail since required keywords will be missing."
+        )
+    ],
     "ignore_standard_keys": [
         (
             "Remove standard keys from *TEXT*. "
@@ -550,6 +721,20 @@ _STD_ARGS: dict[str, list[str]] = {
     "allow_unused_standard": [
         "If ``True`` allow unused standard keywords to be present."
     ],
+    "absorb_orphan_measurement_keywords": [
+        (
+            "If ``True`` move *$Pn\\** keywords whose index is 0, exceeds *$PAR*, "
+            "or has leading zeros into nonstandard keywords (without the leading *$*). "
+            "Keywords which collide with an existing nonstandard keyword will "
+            "not be moved."
+        )
+    ],
+    "disallow_orphan_measurement_keywords": [
+        (
+            "If ``True`` throw error if *$Pn\\** keywords are found whose index "
+            "is 0, exceeds *$PAR*, or has leading zeros. Otherwise throw a warning."
+        )
+    ],
     "disallow_deprecated": [
         "If ``True`` throw error if a deprecated key is encountered."
     ],
@@ -564,6 +749,40 @@ _STD_ARGS: dict[str, list[str]] = {
             "`regexp-syntax <https://docs.rs/regex-syntax/latest/regex_syntax/>`__. "
         )
     ],
+    "exclude_measurement_pattern": [
+        (
+            "If given, drop all measurements whose *$PnN* matches this regular "
+            "expression. Their columns will not be read from *DATA*. "
+            "Measurements referenced by other keywords (such as *$TR* or "
+            "*$SPILLOVER*) will not be dropped and will trigger a warning."
+        )
+    ],
+    "instrument_registry": [
+        (
+            "If given, a function which takes the values of *$CYT* and *$CYTSN* "
+            "(or ``None`` if missing) and returns a dict of nonstandard keywords "
+            "to add to the result. Keywords already in *TEXT* take precedence. "
+            "Exceptions raised by this function will be reported as warnings."
+        )
+    ],
+    "keyword_extensions": [
+        (
+            "If given, a dict of keys (with or without *$*) to functions which "
+            "take a value and return it as it should be stored, raising an "
+            "exception if invalid. Keys starting with *$* which are not part of "
+            "the version being read and match one of these will be treated as "
+            "standard rather than pseudostandard. Invalid values will be "
+            "reported as warnings."
+        )
+    ],
+    "timestamps_from_conventions": [
+        (
+            "If ``True`` fill in *$BTIM* and *$DATE* (or *$BEGINDATETIME* for "
+            "FCS 3.2) from vendor epoch timestamps in nonstandard keywords "
+            "(such as *#ACQUISITIONTIMEMILLIS*) if these are all missing. The "
+            "key used will be stored in *FIREFLOW_TIMESTAMP_SOURCE*."
+        )
+    ],
 }
 
 _OFFSET_ARGS: dict[str, list[str]] = {
@@ -598,6 +817,13 @@ _LAYOUT_ARGS: dict[str, list[str]] = {
             "to match the number of bytes specified by *$PnB* and *$DATATYPE*."
         )
     ],
+    "disallow_mixed_float_byteord": [
+        (
+            "If ``True`` throw error if *$BYTEORD* is neither big nor little "
+            "endian when *$DATATYPE* is ``F`` or ``D``. Only has an effect for "
+            "FCS 2.0/3.0."
+        )
+    ],
 }
 
 _DATA_ARGS: dict[str, list[str]] = {
@@ -618,6 +844,22 @@ _DATA_ARGS: dict[str, list[str]] = {
 
 _SHARED_ARGS: dict[str, list[str]] = {
     "warnings_are_errors": ["If ``True`` all warnings will be regarded as errors."],
+    "max_warnings": [
+        "Maximum number of warnings to emit. Any further warnings will be "
+        "dropped and summarized with one final warning. Errors are limited "
+        "the same way, although at least one is always kept. If ``None``, "
+        "emit all warnings and errors."
+    ],
+}
+
+_OUTPUT_ARGS: dict[str, list[str]] = {
+    "as_arrow": [
+        (
+            "If ``True`` return *DATA* as a pyarrow Table rather than a polars "
+            "DataFrame. Use this if the installed polars version is incompatible "
+            "with this library."
+        )
+    ],
 }
 
 
@@ -707,6 +949,8 @@ def fcs_read_raw_text(
     allow_stext_own_delim: bool = False,
     allow_missing_nextdata: bool = False,
     trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
     ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
     rename_standard_keys: dict[str, str] = {},
     promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
@@ -715,6 +959,7 @@ def fcs_read_raw_text(
     append_standard_keywords: dict[str, str] = {},
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
 ) -> ReadRawTEXTOutput:
     """
     Read the HEADER and TEXT of an FCS file.
@@ -764,6 +1009,8 @@ def fcs_read_std_text(
     allow_stext_own_delim: bool = False,
     allow_missing_nextdata: bool = False,
     trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
     ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
     rename_standard_keys: dict[str, str] = {},
     promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
@@ -781,9 +1028,15 @@ def fcs_read_std_text(
     time_pattern: str | None = None,
     allow_pseudostandard: bool = False,
     allow_unused_standard: bool = False,
+    absorb_orphan_measurement_keywords: bool = False,
+    disallow_orphan_measurement_keywords: bool = False,
     disallow_deprecated: bool = False,
     fix_log_scale_offsets: bool = False,
     nonstandard_measurement_pattern: str | None = None,
+    exclude_measurement_pattern: str | None = None,
+    instrument_registry: InstrumentRegistry | None = None,
+    keyword_extensions: KeywordExtensions | None = None,
+    timestamps_from_conventions: bool = False,
     # offset args
     text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
     text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
@@ -796,8 +1049,10 @@ def fcs_read_std_text(
     integer_widths_from_byteord: bool = False,
     integer_byteord_override: ByteOrd | None = None,
     disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
 ) -> ReadStdTEXTOutput:
     """
     Read the HEADER and standardized TEXT of an FCS file.
@@ -845,6 +1100,8 @@ def fcs_read_raw_dataset(
     allow_stext_own_delim: bool = False,
     allow_missing_nextdata: bool = False,
     trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
     ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
     rename_standard_keys: dict[str, str] = {},
     promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
@@ -863,17 +1120,21 @@ def fcs_read_raw_dataset(
     integer_widths_from_byteord: bool = False,
     integer_byteord_override: ByteOrd | None = None,
     disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
     # data args
     allow_uneven_event_width: bool = False,
     allow_tot_mismatch: bool = False,
     allow_data_par_mismatch: bool = False,
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
+    # output args
+    as_arrow: bool = False,
 ) -> ReadRawDatasetOutput:
     """
     Read an FCS file with standardized TEXT.
     """
-    args = {k: v for k, v in locals().items() if k != "p"}
+    args = {k: v for k, v in locals().items() if k not in ["p", "as_arrow"]}
     conf = {
         "raw": _assign_raw_args(args),
         "offsets": _assign_args(list(_OFFSET_ARGS), args),
@@ -882,7 +1143,7 @@ def fcs_read_raw_dataset(
         "shared": _assign_args(list(_SHARED_ARGS), args),
     }
     assert len(args) == 0, False
-    ret = _api._fcs_read_raw_dataset(p, conf)
+    ret = _api._fcs_read_raw_dataset(p, conf, as_arrow)
     text = ret["text"]
     return ReadRawDatasetOutput(
         text=_to_raw_output(ret["text"]),
@@ -890,6 +1151,90 @@ def fcs_read_raw_dataset(
     )
 
 
+def fcs_read_raw_dataset_passthru(
+    p: Path,
+    # header args
+    version_override: FCSVersion | None = None,
+    text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    other_corrections: list[OffsetCorrection] = [],
+    max_other: int | None = None,
+    other_width: int = DEFAULT_OTHER_WIDTH,
+    squish_offsets: bool = False,
+    allow_negative: bool = False,
+    truncate_offsets: bool = False,
+    # raw text args
+    supp_text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    allow_duplicated_stext: bool = False,
+    ignore_supp_text: bool = False,
+    use_literal_delims: bool = False,
+    allow_non_ascii_delim: bool = False,
+    allow_missing_final_delim: bool = False,
+    allow_nonunique: bool = False,
+    allow_odd: bool = False,
+    allow_empty: bool = False,
+    allow_delim_at_boundary: bool = False,
+    allow_non_utf8: bool = False,
+    allow_non_ascii_keywords: bool = False,
+    allow_missing_stext: bool = False,
+    allow_stext_own_delim: bool = False,
+    allow_missing_nextdata: bool = False,
+    trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
+    ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    rename_standard_keys: dict[str, str] = {},
+    promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    demote_from_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    replace_standard_key_values: dict[str, str] = {},
+    append_standard_keywords: dict[str, str] = {},
+    # offset args
+    text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    ignore_text_data_offsets: bool = False,
+    ignore_text_analysis_offsets: bool = False,
+    allow_header_text_offset_mismatch: bool = False,
+    allow_missing_required_offsets: bool = False,
+    truncate_text_offsets: bool = False,
+    # layout args
+    integer_widths_from_byteord: bool = False,
+    integer_byteord_override: ByteOrd | None = None,
+    disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
+    # data args
+    allow_uneven_event_width: bool = False,
+    allow_tot_mismatch: bool = False,
+    allow_data_par_mismatch: bool = False,
+    # shared args
+    warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
+) -> ReadRawDatasetPassthruOutput:
+    """
+    Read an FCS file without parsing DATA.
+    """
+    args = {k: v for k, v in locals().items() if k != "p"}
+    conf = {
+        "raw": _assign_raw_args(args),
+        "offsets": _assign_args(list(_OFFSET_ARGS), args),
+        "layout": _assign_args(list(_LAYOUT_ARGS), args),
+        "data": _assign_args(list(_DATA_ARGS), args),
+        "shared": _assign_args(list(_SHARED_ARGS), args),
+    }
+    assert len(args) == 0, False
+    ret = _api._fcs_read_raw_dataset_passthru(p, conf)
+    data = ret.pop("data")
+    layout = data["layout"]
+    partial = data["partial_layout"]
+    return ReadRawDatasetPassthruOutput(
+        data=data["bytes"],
+        layout=None if layout is None else PassthruLayout(**layout),
+        partial_layout=None if partial is None else PartialPassthruLayout(**partial),
+        text=_to_raw_output(ret.pop("text")),
+        **ret,
+    )
+
+
 def fcs_read_std_dataset(
     p: Path,
     # header args
@@ -920,6 +1265,8 @@ def fcs_read_std_dataset(
     allow_stext_own_delim: bool = False,
     allow_missing_nextdata: bool = False,
     trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
     ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
     rename_standard_keys: dict[str, str] = {},
     promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
@@ -937,9 +1284,15 @@ def fcs_read_std_dataset(
     time_pattern: str | None = None,
     allow_pseudostandard: bool = False,
     allow_unused_standard: bool = False,
+    absorb_orphan_measurement_keywords: bool = False,
+    disallow_orphan_measurement_keywords: bool = False,
     disallow_deprecated: bool = False,
     fix_log_scale_offsets: bool = False,
     nonstandard_measurement_pattern: str | None = None,
+    exclude_measurement_pattern: str | None = None,
+    instrument_registry: InstrumentRegistry | None = None,
+    keyword_extensions: KeywordExtensions | None = None,
+    timestamps_from_conventions: bool = False,
     # offset args
     text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
     text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
@@ -952,12 +1305,14 @@ def fcs_read_std_dataset(
     integer_widths_from_byteord: bool = False,
     integer_byteord_override: ByteOrd | None = None,
     disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
     # data args
     allow_uneven_event_width: bool = False,
     allow_tot_mismatch: bool = False,
     allow_data_par_mismatch: bool = False,
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
 ) -> ReadStdDatasetOutput:
     """
     Read an FCS file with standardized TEXT.
@@ -973,15 +1328,203 @@ def fcs_read_std_dataset(
     }
     assert len(args) == 0, False
     core, uncore = _api._fcs_read_std_dataset(p, conf)
-    return ReadStdDatasetOutput(
-        core=core,
-        uncore=StdDatasetData(
-            parse=_to_parse_data(uncore["parse"]),
-            extra=ExtraStdKeywords(**uncore["dataset"]["extra"]),
-            data_seg=uncore["dataset"]["standardized"]["data_seg"],
-            analysis_seg=uncore["dataset"]["standardized"]["analysis_seg"],
-        ),
-    )
+    return _to_std_dataset_output(core, uncore)
+
+
+def fcs_read_std_datasets(
+    p: Path,
+    # header args
+    version_override: FCSVersion | None = None,
+    text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    other_corrections: list[OffsetCorrection] = [],
+    max_other: int | None = None,
+    other_width: int = DEFAULT_OTHER_WIDTH,
+    squish_offsets: bool = False,
+    allow_negative: bool = False,
+    truncate_offsets: bool = False,
+    # raw text args
+    supp_text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    allow_duplicated_stext: bool = False,
+    ignore_supp_text: bool = False,
+    use_literal_delims: bool = False,
+    allow_non_ascii_delim: bool = False,
+    allow_missing_final_delim: bool = False,
+    allow_nonunique: bool = False,
+    allow_odd: bool = False,
+    allow_empty: bool = False,
+    allow_delim_at_boundary: bool = False,
+    allow_non_utf8: bool = False,
+    allow_non_ascii_keywords: bool = False,
+    allow_missing_stext: bool = False,
+    allow_stext_own_delim: bool = False,
+    allow_missing_nextdata: bool = False,
+    trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
+    ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    rename_standard_keys: dict[str, str] = {},
+    promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    demote_from_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    replace_standard_key_values: dict[str, str] = {},
+    append_standard_keywords: dict[str, str] = {},
+    # standard args
+    time_meas_pattern: str | None = DEFAULT_TIME_MEAS_PATTERN,
+    allow_missing_time: bool = False,
+    force_time_linear: bool = False,
+    ignore_time_gain: bool = False,
+    ignore_time_optical_keys: set[TemporalOpticalKey] = set(),
+    parse_indexed_spillover: bool = False,
+    date_pattern: str | None = None,
+    time_pattern: str | None = None,
+    allow_pseudostandard: bool = False,
+    allow_unused_standard: bool = False,
+    absorb_orphan_measurement_keywords: bool = False,
+    disallow_orphan_measurement_keywords: bool = False,
+    disallow_deprecated: bool = False,
+    fix_log_scale_offsets: bool = False,
+    nonstandard_measurement_pattern: str | None = None,
+    exclude_measurement_pattern: str | None = None,
+    instrument_registry: InstrumentRegistry | None = None,
+    keyword_extensions: KeywordExtensions | None = None,
+    timestamps_from_conventions: bool = False,
+    # offset args
+    text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    ignore_text_data_offsets: bool = False,
+    ignore_text_analysis_offsets: bool = False,
+    allow_header_text_offset_mismatch: bool = False,
+    allow_missing_required_offsets: bool = False,
+    truncate_text_offsets: bool = False,
+    # layout args
+    integer_widths_from_byteord: bool = False,
+    integer_byteord_override: ByteOrd | None = None,
+    disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
+    # data args
+    allow_uneven_event_width: bool = False,
+    allow_tot_mismatch: bool = False,
+    allow_data_par_mismatch: bool = False,
+    # shared args
+    warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
+) -> list[ReadStdDatasetOutput | PyreflowException]:
+    """
+    Read all datasets in an FCS file with standardized TEXT.
+    """
+    args = {k: v for k, v in locals().items() if k != "p"}
+    conf = {
+        "raw": _assign_raw_args(args),
+        "offsets": _assign_args(list(_OFFSET_ARGS), args),
+        "layout": _assign_args(list(_LAYOUT_ARGS), args),
+        "standard": _assign_args(list(_STD_ARGS), args),
+        "data": _assign_args(list(_DATA_ARGS), args),
+        "shared": _assign_args(list(_SHARED_ARGS), args),
+    }
+    assert len(args) == 0, False
+    return [
+        x if isinstance(x, PyreflowException) else _to_std_dataset_output(*x)
+        for x in _api._fcs_read_std_datasets(p, conf)
+    ]
+
+
+def fcs_read_std_datasets_concatenated(
+    p: Path,
+    # header args
+    version_override: FCSVersion | None = None,
+    text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    other_corrections: list[OffsetCorrection] = [],
+    max_other: int | None = None,
+    other_width: int = DEFAULT_OTHER_WIDTH,
+    squish_offsets: bool = False,
+    allow_negative: bool = False,
+    truncate_offsets: bool = False,
+    # raw text args
+    supp_text_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    allow_duplicated_stext: bool = False,
+    ignore_supp_text: bool = False,
+    use_literal_delims: bool = False,
+    allow_non_ascii_delim: bool = False,
+    allow_missing_final_delim: bool = False,
+    allow_nonunique: bool = False,
+    allow_odd: bool = False,
+    allow_empty: bool = False,
+    allow_delim_at_boundary: bool = False,
+    allow_non_utf8: bool = False,
+    allow_non_ascii_keywords: bool = False,
+    allow_missing_stext: bool = False,
+    allow_stext_own_delim: bool = False,
+    allow_missing_nextdata: bool = False,
+    trim_value_whitespace: bool = False,
+    control_chars: ControlCharPolicy = "preserve",
+    keyword_allowlist: list[str] | None = None,
+    ignore_standard_keys: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    rename_standard_keys: dict[str, str] = {},
+    promote_to_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    demote_from_standard: KeyPatterns = DEFAULT_KEY_PATTERNS,
+    replace_standard_key_values: dict[str, str] = {},
+    append_standard_keywords: dict[str, str] = {},
+    # standard args
+    time_meas_pattern: str | None = DEFAULT_TIME_MEAS_PATTERN,
+    allow_missing_time: bool = False,
+    force_time_linear: bool = False,
+    ignore_time_gain: bool = False,
+    ignore_time_optical_keys: set[TemporalOpticalKey] = set(),
+    parse_indexed_spillover: bool = False,
+    date_pattern: str | None = None,
+    time_pattern: str | None = None,
+    allow_pseudostandard: bool = False,
+    allow_unused_standard: bool = False,
+    absorb_orphan_measurement_keywords: bool = False,
+    disallow_orphan_measurement_keywords: bool = False,
+    disallow_deprecated: bool = False,
+    fix_log_scale_offsets: bool = False,
+    nonstandard_measurement_pattern: str | None = None,
+    exclude_measurement_pattern: str | None = None,
+    instrument_registry: InstrumentRegistry | None = None,
+    keyword_extensions: KeywordExtensions | None = None,
+    timestamps_from_conventions: bool = False,
+    # offset args
+    text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
+    ignore_text_data_offsets: bool = False,
+    ignore_text_analysis_offsets: bool = False,
+    allow_header_text_offset_mismatch: bool = False,
+    allow_missing_required_offsets: bool = False,
+    truncate_text_offsets: bool = False,
+    # layout args
+    integer_widths_from_byteord: bool = False,
+    integer_byteord_override: ByteOrd | None = None,
+    disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
+    # data args
+    allow_uneven_event_width: bool = False,
+    allow_tot_mismatch: bool = False,
+    allow_data_par_mismatch: bool = False,
+    # shared args
+    warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
+) -> list[tuple[int, ReadStdDatasetOutput | PyreflowException]]:
+    """
+    Read all datasets in concatenated FCS files with standardized TEXT.
+    """
+    args = {k: v for k, v in locals().items() if k != "p"}
+    conf = {
+        "raw": _assign_raw_args(args),
+        "offsets": _assign_args(list(_OFFSET_ARGS), args),
+        "layout": _assign_args(list(_LAYOUT_ARGS), args),
+        "standard": _assign_args(list(_STD_ARGS), args),
+        "data": _assign_args(list(_DATA_ARGS), args),
+        "shared": _assign_args(list(_SHARED_ARGS), args),
+    }
+    assert len(args) == 0, False
+    return [
+        (o, x if isinstance(x, PyreflowException) else _to_std_dataset_output(*x))
+        for o, x in _api._fcs_read_std_datasets_concatenated(p, conf)
+    ]
 
 
 def fcs_read_raw_dataset_with_keywords(
@@ -1003,17 +1546,21 @@ def fcs_read_raw_dataset_with_keywords(
     integer_widths_from_byteord: bool = False,
     integer_byteord_override: ByteOrd | None = None,
     disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
     # data args
     allow_uneven_event_width: bool = False,
     allow_tot_mismatch: bool = False,
     allow_data_par_mismatch: bool = False,
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
+    # output args
+    as_arrow: bool = False,
 ) -> ReadRawDatasetFromKwsOutput:
     """
     Read raw data from FCS file using a given set of keywords.
     """
-    omit = ["p", "version", "std", "data_seg", "analysis_seg", "other_segs"]
+    omit = ["p", "version", "std", "data_seg", "analysis_seg", "other_segs", "as_arrow"]
     args = {k: v for k, v in locals().items() if k not in omit}
     conf = {
         "offsets": _assign_args(list(_OFFSET_ARGS), args),
@@ -1023,7 +1570,7 @@ def fcs_read_raw_dataset_with_keywords(
     }
     assert len(args) == 0, False
     ret = _api._fcs_read_raw_dataset_with_keywords(
-        p, version, std, data_seg, analysis_seg, other_segs, conf
+        p, version, std, data_seg, analysis_seg, other_segs, conf, as_arrow
     )
     return ReadRawDatasetFromKwsOutput(**ret)
 
@@ -1047,9 +1594,15 @@ def fcs_read_std_dataset_with_keywords(
     time_pattern: str | None = None,
     allow_pseudostandard: bool = False,
     allow_unused_standard: bool = False,
+    absorb_orphan_measurement_keywords: bool = False,
+    disallow_orphan_measurement_keywords: bool = False,
     disallow_deprecated: bool = False,
     fix_log_scale_offsets: bool = False,
     nonstandard_measurement_pattern: str | None = None,
+    exclude_measurement_pattern: str | None = None,
+    instrument_registry: InstrumentRegistry | None = None,
+    keyword_extensions: KeywordExtensions | None = None,
+    timestamps_from_conventions: bool = False,
     # offset args
     text_data_correction: OffsetCorrection = DEFAULT_CORRECTION,
     text_analysis_correction: OffsetCorrection = DEFAULT_CORRECTION,
@@ -1062,12 +1615,14 @@ def fcs_read_std_dataset_with_keywords(
     integer_widths_from_byteord: bool = False,
     integer_byteord_override: ByteOrd | None = None,
     disallow_range_truncation: bool = False,
+    disallow_mixed_float_byteord: bool = False,
     # data args
     allow_uneven_event_width: bool = False,
     allow_tot_mismatch: bool = False,
     allow_data_par_mismatch: bool = False,
     # shared args
     warnings_are_errors: bool = False,
+    max_warnings: int | None = 10000,
 ) -> ReadStdDatasetFromKwsOutput:
     """
     Read standardized data from FCS file using a given set of keywords.
@@ -1099,6 +1654,74 @@ def fcs_read_std_dataset_with_keywords(
     )
 
 
+# parameters of CoreDataset*.write_dataset
+_WRITE_ARGS = {
+    "delim",
+    "big_other",
+    "other_pairs",
+    "skip_conversion_check",
+    "segment_alignment",
+    "padding",
+    "namespaces",
+    "float_rounding",
+    "float_rounding_overrides",
+    "control_chars",
+    "record_provenance",
+}
+
+
+def _edits_to_diff(kws: dict[str, str], edits: dict[str, str | None]) -> KeywordDiff:
+    # keys are case-insensitive, so edit whichever spelling is already present
+    present = {k.upper(): k for k in kws}
+    diff: KeywordDiff = {"added": {}, "removed": {}, "changed": {}}
+    for k, v in edits.items():
+        old_k = present.get(k.upper())
+        if old_k is None:
+            if v is not None:
+                diff["added"][k] = v
+        elif v is None:
+            diff["removed"][old_k] = kws[old_k]
+        elif v != kws[old_k]:
+            diff["changed"][old_k] = {"old": kws[old_k], "new": v}
+    return diff
+
+
+def rewrite_fcs_file(
+    in_path: Path,
+    out_path: Path,
+    edits: dict[str, str | None],
+    **opts: Any,
+) -> WriteReport:
+    """Read an FCS file, change some keywords, and write it to a new file.
+
+    This is a shortcut for reading with :py:func:`fcs_read_std_dataset`,
+    applying ``edits`` with ``apply_diff``, and writing with
+    ``write_dataset``.
+
+    :param in_path: path to FCS file to read
+
+    :param out_path: path to FCS file to write
+
+    :param edits: Keywords to change. Each value will replace the current
+        value, or add the keyword if not present. Values of ``None`` remove the
+        keyword if present. Keys may be standard (starting with ``$``) or
+        nonstandard and are matched case-insensitively. *$TOT* and offsets
+        cannot be changed since these are computed when writing.
+
+    :param opts: Any keyword argument of :py:func:`fcs_read_std_dataset` or
+        ``write_dataset``.
+
+    :return: the output of ``write_dataset``
+
+    :raises PyreflowException: if the file cannot be read, the edits do not
+        result in valid keywords, or the file cannot be written
+    """
+    write_opts = {k: opts.pop(k) for k in _WRITE_ARGS & opts.keys()}
+    core = fcs_read_std_dataset(in_path, **opts).core
+    core.apply_diff(_edits_to_diff(core.standard_keywords(), edits))
+    return core.write_dataset(out_path, **write_opts)
+
+
 def _format_docstring(front: str, params: list[tuple[str, list[str]]]) -> str:
     # TODO actually indent these appropriately
     width = 76
@@ -1154,6 +1777,22 @@ fcs_read_std_text.__doc__ = _format_docstring(
 
 fcs_read_raw_dataset.__doc__ = _format_docstring(
     "Read dataset from FCS file without standardization.",
+    [
+        ("p", ["path to FCS file"]),
+        *_HEADER_ARGS.items(),
+        *_RAW_ARGS.items(),
+        *_OFFSET_ARGS.items(),
+        *_LAYOUT_ARGS.items(),
+        *_DATA_ARGS.items(),
+        *_SHARED_ARGS.items(),
+        *_OUTPUT_ARGS.items(),
+    ],
+)
+
+fcs_read_raw_dataset_passthru.__doc__ = _format_docstring(
+    "Read dataset from FCS file without parsing *DATA*. If the layout cannot "
+    "be determined, this will be reported as a warning rather than an error, "
+    "and whichever layout keywords could be read will be returned instead.",
     [
         ("p", ["path to FCS file"]),
         *_HEADER_ARGS.items(),
@@ -1179,6 +1818,43 @@ fcs_read_std_dataset.__doc__ = _format_docstring(
     ],
 )
 
+fcs_read_std_datasets.__doc__ = _format_docstring(
+    "Read all datasets from FCS file with standardization. Datasets after the "
+    "first are found using *$NEXTDATA*. Each dataset is read independently; if "
+    "one cannot be read, its exception will be returned in place of its output "
+    "rather than raised. If *TEXT* cannot be read for a dataset, *$NEXTDATA* "
+    "is unknown and its exception will be the last item returned.",
+    [
+        ("p", ["path to FCS file"]),
+        *_HEADER_ARGS.items(),
+        *_RAW_ARGS.items(),
+        *_STD_ARGS.items(),
+        *_OFFSET_ARGS.items(),
+        *_LAYOUT_ARGS.items(),
+        *_DATA_ARGS.items(),
+        *_SHARED_ARGS.items(),
+    ],
+)
+
+fcs_read_std_datasets_concatenated.__doc__ = _format_docstring(
+    "Read all datasets from a file made by joining complete FCS files "
+    "end-to-end, with standardization. After each dataset is read, the file is "
+    "searched for the next valid *HEADER* starting after the last byte of any "
+    "of its segments, so datasets linked by *$NEXTDATA* will also be found. "
+    "Each item is a tuple of the dataset's offset in the file and its output, "
+    "or its exception if it could not be read.",
+    [
+        ("p", ["path to FCS file"]),
+        *_HEADER_ARGS.items(),
+        *_RAW_ARGS.items(),
+        *_STD_ARGS.items(),
+        *_OFFSET_ARGS.items(),
+        *_LAYOUT_ARGS.items(),
+        *_DATA_ARGS.items(),
+        *_SHARED_ARGS.items(),
+    ],
+)
+
 fcs_read_raw_dataset_with_keywords.__doc__ = _format_docstring(
     "Read dataset from FCS file using given keywords without standardization.",
     [
@@ -1192,6 +1868,7 @@ fcs_read_raw_dataset_with_keywords.__doc__ = _format_docstring(
         *_LAYOUT_ARGS.items(),
         *_DATA_ARGS.items(),
         *_SHARED_ARGS.items(),
+        *_OUTPUT_ARGS.items(),
     ],
 )
 
@@ -0,0 +1,82 @@
+from pyreflow.typing import AnyCoreDataset
+from types import ModuleType
+from typing import Any
+import importlib
+
+
+def _import_optional(name: str, extra: str) -> ModuleType:
+    try:
+        return importlib.import_module(name)
+    except ImportError as e:
+        raise ImportError(
+            f"{name} is required for this conversion; "
+            f"install it with 'pip install pyreflow[{extra}]'"
+        ) from e
+
+
+def to_anndata(core: AnyCoreDataset) -> Any:
+    """Convert a dataset to an ``AnnData`` object.
+
+    Events will be observations and measurements will be variables. Variables
+    will be indexed by *$PnN* and will have the columns ``longname`` (*$PnS*),
+    ``datatype``, ``range`` (*$PnR*), and ``transform`` (*$PnE* or *$PnG*).
+    Standard and nonstandard keywords will be stored in ``uns`` under
+    ``keywords`` and ``nonstandard_keywords`` along with the FCS version under
+    ``version``.
+
+    :param core: dataset to convert
+
+    :return: an ``anndata.AnnData`` object
+
+    :raises ImportError: if ``anndata`` is not installed
+    """
+    anndata = _import_optional("anndata", "anndata")
+    pd = _import_optional("pandas", "anndata")
+    names = core.data.columns
+    meta = core.column_metadata
+    var = pd.DataFrame(
+        {
+            "longname": [meta[n]["longname"] for n in names],
+            "datatype": [meta[n]["datatype"] for n in names],
+            "range": [float(meta[n]["range"]) for n in names],
+            "transform": [str(meta[n]["transform"]) for n in names],
+        },
+        index=pd.Index(names, dtype=str),
+    )
+    uns = {
+        "version": core.version,
+        "keywords": core.standard_keywords(),
+        "nonstandard_keywords": dict(core.nonstandard_keywords),
+    }
+    return anndata.AnnData(X=core.data.to_numpy(), var=var, uns=uns)
+
+
+def to_flowkit_sample(
+    core: AnyCoreDataset, sample_id: str | None = None, **kwargs: Any
+) -> Any:
+    """Convert a dataset to a FlowKit ``Sample``.
+
+    Channels will be labeled using *$PnN* and *$PnS* (or an empty string if
+    *$PnS* is missing).
+
+    :param core: dataset to convert
+
+    :param sample_id: ID for the sample. If ``None``, use *$FIL* if present.
+
+    :param kwargs: Any other keyword argument to ``flowkit.Sample``.
+
+    :return: a ``flowkit.Sample`` object
+
+    :raises ImportError: if ``flowkit`` is not installed
+    """
+    flowkit = _import_optional("flowkit", "flowkit")
+    pd = _import_optional("pandas", "flowkit")
+    names = core.data.columns
+    meta = core.column_metadata
+    columns = pd.MultiIndex.from_tuples(
+        [(n, meta[n]["longname"] or "") for n in names],
+        names=["pnn", "pns"],
+    )
+    df = pd.DataFrame(core.data.to_numpy(), columns=columns)
+    sid = core.fil if sample_id is None else sample_id
+    return flowkit.Sample(df, sample_id=sid, **kwargs)
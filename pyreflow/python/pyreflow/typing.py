@@ -1,6 +1,7 @@
 import pyreflow._pyreflow as pf
-from typing import Literal, TypeAlias
+from typing import Callable, Literal, TypeAlias, TypedDict
 from decimal import Decimal
+from datetime import datetime
 import numpy as np
 import numpy.typing as npt
 
@@ -24,6 +25,10 @@ StdKey: TypeAlias = str
 
 NonStdKey: TypeAlias = str
 
+InstrumentRegistry: TypeAlias = Callable[[str | None, str | None], dict[NonStdKey, str]]
+
+KeywordExtensions: TypeAlias = dict[StdKey, Callable[[str], str]]
+
 AnalysisBytes: TypeAlias = bytes
 
 OtherBytes: TypeAlias = bytes
@@ -70,11 +75,154 @@ DoubleType = Literal["D"]
 IntegerType = Literal["I"]
 AsciiType = Literal["A"]
 
+FloatRounding = Literal["truncate", "round", "error"]
+
+ControlCharPolicy = Literal["preserve", "escape", "strip"]
+
+DisplayNameFormat = Literal["longname", "shortname", "both"]
+
 Datatype: TypeAlias = FloatType | DoubleType | IntegerType | AsciiType
 MixedType: TypeAlias = (
     tuple[FloatType | DoubleType, FloatRange] | tuple[AsciiType | IntegerType, IntRange]
 )
 
+
+class GroupedKeywords(TypedDict):
+    """Keywords with measurement non-standard keywords grouped separately."""
+
+    keywords: dict[str, str]
+    measurements: list[dict[str, str]]
+
+
+class ColumnMetadata(TypedDict):
+    """Metadata for one column in *DATA*."""
+
+    longname: str | None
+    datatype: Datatype
+    range: FloatRange
+    transform: ScaleTransform
+
+
+class Histogram(TypedDict):
+    """Counts of values in equally-spaced bins."""
+
+    edges: list[float]
+    counts: list[int]
+
+
+class DuplicateEvent(TypedDict):
+    """An event which repeats an earlier event."""
+
+    index: int
+    original: int
+
+
+class DuplicateRun(TypedDict):
+    """Consecutive events which repeat consecutive earlier events."""
+
+    start: int
+    original_start: int
+    len: int
+
+
+class DuplicateReport(TypedDict):
+    """Duplicated events found in *DATA*."""
+
+    events: int
+    duplicates: list[DuplicateEvent]
+    runs: list[DuplicateRun]
+
+
+class WriteReport(TypedDict):
+    """Summary of values which were changed when writing *DATA*."""
+
+    lossy_values: list[int]
+    rounding: list[FloatRounding | None]
+
+
+class Provenance(TypedDict):
+    """Provenance for a dataset derived from another FCS file."""
+
+    parent: str | None
+    parent_hash: str | None
+    operation: str | None
+    software: str | None
+
+
+class Environment(TypedDict):
+    """Environmental conditions found in vendor-specific keywords."""
+
+    sheath_pressure: float | None
+    ambient_temperature: float | None
+    instrument_temperature: float | None
+    humidity: float | None
+
+
+class KeyConvention(TypedDict):
+    """Nonstandard keys in which to find each environmental condition."""
+
+    name: str
+    sheath_pressure: list[str]
+    ambient_temperature: list[str]
+    instrument_temperature: list[str]
+    humidity: list[str]
+
+
+class VendorTimestamp(TypedDict):
+    """Start of acquisition found in a vendor-specific epoch keyword."""
+
+    convention: str
+    key: str
+    datetime: datetime
+
+
+class Carrier(TypedDict):
+    """Carrier and location from which a sample was taken."""
+
+    carrierid: str
+    carriertype: str | None
+    locationid: str | None
+
+
+class ValueChange(TypedDict):
+    """Old and new value of a changed keyword."""
+
+    old: str
+    new: str
+
+
+KeywordOrigin = Literal["present", "defaulted", "absent"]
+
+
+class KeywordDiff(TypedDict):
+    """Keywords which were added, removed, or changed between two sets."""
+
+    added: dict[str, str]
+    removed: dict[str, str]
+    changed: dict[str, ValueChange]
+
+
+class MeasRemoval(TypedDict):
+    """A removed measurement and changes to keywords which referred to it."""
+
+    index: MeasIndex
+    links: KeywordDiff
+
+
+# summary of standard keywords changed when converting FCS versions; use
+# functional syntax since "from" is a reserved word
+ConversionLog = TypedDict(
+    "ConversionLog",
+    {
+        "from": FCSVersion,
+        "to": FCSVersion,
+        "removed": list[str],
+        "added": list[str],
+        "changed": list[str],
+    },
+)
+
+
 TemporalOpticalKey = Literal[
     "F",
     "L",
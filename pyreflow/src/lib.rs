@@ -69,7 +69,13 @@ fn _api(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(ff::py_fcs_read_raw_text, m)?)?;
     m.add_function(wrap_pyfunction!(ff::py_fcs_read_std_text, m)?)?;
     m.add_function(wrap_pyfunction!(ff::py_fcs_read_std_dataset, m)?)?;
+    m.add_function(wrap_pyfunction!(ff::py_fcs_read_std_datasets, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        ff::py_fcs_read_std_datasets_concatenated,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(ff::py_fcs_read_raw_dataset, m)?)?;
+    m.add_function(wrap_pyfunction!(ff::py_fcs_read_raw_dataset_passthru, m)?)?;
     m.add_function(wrap_pyfunction!(
         ff::py_fcs_read_raw_dataset_with_keywords,
         m
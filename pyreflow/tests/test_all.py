@@ -2,6 +2,7 @@ from typing import cast, Any
 from datetime import date, datetime, time, timezone, timedelta
 from decimal import Decimal
 from pathlib import Path
+import json
 
 import pytest
 
@@ -17,9 +18,13 @@ from pyreflow.typing import (
     AppliedGates2_0,
     AppliedGates3_0,
     AppliedGates3_2,
+    Carrier,
+    DisplayNameFormat,
+    KeyConvention,
 )
 import pyreflow as pf
 import polars as pl
+import pyarrow as pa
 
 from .conftest import lazy_fixture
 
@@ -400,6 +405,15 @@ class TestCore:
         with pytest.raises(ValueError):
             core.all_shortnames = ["I,can,haz,IP"]
 
+    @pytest.mark.parametrize(
+        "cls", [pf.CoreTEXT2_0, pf.CoreTEXT3_0, pf.CoreTEXT3_1, pf.CoreTEXT3_2]
+    )
+    def test_example(self, cls: type) -> None:
+        core = cls.example(3)
+        assert core.par == 3
+        assert core.all_shortnames == ["FL1", "FL2", "FL3"]
+        assert cls.example().par == 2
+
     @parameterize_versions("core", ["2_0", "3_0", "3_1"], ["text2", "dataset2"])
     @pytest.mark.parametrize("attr", ["all_peak_bins", "all_peak_sizes"])
     def test_peak(
@@ -438,6 +452,16 @@ class TestCore:
         with pytest.raises(TypeError):
             core.all_longnames = [cast(str, 42)]
 
+    @all_core
+    def test_display_names(self, core: AnyCore) -> None:
+        assert core.display_names() == [LINK_NAME1]
+        core.all_longnames = ["CD4"]
+        assert core.display_names() == ["CD4"]
+        assert core.display_names(format="shortname") == [LINK_NAME1]
+        assert core.display_names("both", " / ") == [f"{LINK_NAME1} / CD4"]
+        with pytest.raises(ValueError):
+            core.display_names(format=cast(DisplayNameFormat, "neither"))
+
     # TODO add raw_keywords test
 
     @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["text2", "dataset2"])
@@ -662,6 +686,16 @@ class TestCore:
         core.all_scales = [(), ()]
         assert core.all_scales == [(), ()]
 
+    @parameterize_versions("core", ["2_0"], ["text2", "dataset2"])
+    def test_meas_all_transforms_2_0(
+        self, core: pf.CoreTEXT2_0 | pf.CoreDataset2_0
+    ) -> None:
+        assert core.all_scale_transforms == [1.0, 1.0]
+        core.all_scale_transforms = [(2.0, 1.0), 1.0]
+        assert core.all_scales == [(2.0, 1.0), ()]
+        with pytest.raises(pf.PyreflowException):
+            core.all_scale_transforms = [2.0, 1.0]
+
     @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["text2", "dataset2"])
     def test_meas_all_transforms(
         self,
@@ -677,7 +711,7 @@ class TestCore:
     # each of these should be strings or None
     @all_core2
     @pytest.mark.parametrize(
-        "attr", [f"all_{x}" for x in ["filters", "percents_emitted", "detector_types"]]
+        "attr", [f"all_{x}" for x in ["filters", "detector_types"]]
     )
     def test_meas_opt_strs(self, attr: str, core: AnyCore) -> None:
         assert getattr(core, attr) == [None, ()]
@@ -690,7 +724,7 @@ class TestCore:
     # each of these should be a non-negative float
     @all_core2
     @pytest.mark.parametrize(
-        "attr", [f"all_{x}" for x in ["powers", "detector_voltages"]]
+        "attr", [f"all_{x}" for x in ["powers", "percents_emitted", "detector_voltages"]]
     )
     def test_meas_opt_floats(self, attr: str, core: AnyCore) -> None:
         assert getattr(core, attr) == [None, ()]
@@ -705,6 +739,13 @@ class TestCore:
         with pytest.raises(TypeError):
             setattr(core, attr, ["pickle rick", ()])
 
+    @all_core2
+    def test_percent_emitted_max(self, core: AnyCore) -> None:
+        core.all_percents_emitted = [100.0, ()]
+        assert core.all_percents_emitted == [100.0, ()]
+        with pytest.raises(ValueError):
+            core.all_percents_emitted = [100.5, ()]
+
     @pytest.mark.parametrize(
         "core, optical, temporal",
         [
@@ -837,6 +878,100 @@ class TestCore:
         # # and it shouldn't return anything if we try to remove it a 2nd time
         # assert core.remove_nonstandard(k) is None
 
+    @all_core
+    def test_provenance(self, tmp_path: Path, core: AnyCore) -> None:
+        assert core.provenance is None
+        parent = tmp_path / "parent.fcs"
+        parent.write_bytes(b"parent")
+        core.record_provenance("subsample", parent)
+        p = core.provenance
+        assert p is not None
+        assert p["parent"] == "parent.fcs"
+        assert p["parent_hash"] is not None
+        assert p["operation"] == "subsample"
+        assert "FIREFLOW_OPERATION" in core.nonstandard_keywords
+        core.provenance = None
+        assert core.provenance is None
+        assert core.nonstandard_keywords == {}
+
+    @all_core
+    def test_environment(self, core: AnyCore) -> None:
+        c: KeyConvention = {
+            "name": "ours",
+            "sheath_pressure": ["SHEATH PRESSURE"],
+            "ambient_temperature": [],
+            "instrument_temperature": [],
+            "humidity": ["HUMIDITY"],
+        }
+        assert core.environment([c]) == {
+            "sheath_pressure": None,
+            "ambient_temperature": None,
+            "instrument_temperature": None,
+            "humidity": None,
+        }
+        core.nonstandard_keywords = {"SHEATH PRESSURE": "1 bar", "HUMIDITY": "40%"}
+        assert core.environment([])["sheath_pressure"] is None
+        e = core.environment([c])
+        assert e["sheath_pressure"] is not None
+        assert abs(e["sheath_pressure"] - 14.5038) < 1e-3
+        assert e["humidity"] == 40.0
+        assert e["ambient_temperature"] is None
+        assert "SHEATH PRESSURE" in core.nonstandard_keywords
+
+    @all_core
+    def test_vendor_timestamp(self, core: AnyCore) -> None:
+        assert core.vendor_timestamp is None
+        assert core.fill_timestamps() is None
+        k = "#ACQUISITIONTIMEMILLIS"
+        core.nonstandard_keywords = {k: "1693562400123"}
+        ts = core.vendor_timestamp
+        assert ts is not None
+        assert ts["key"] == k
+        assert ts["datetime"] == datetime(
+            2023, 9, 1, 10, 0, 0, 123000, tzinfo=timezone.utc
+        )
+        assert core.timestamp_source is None
+        assert core.fill_timestamps() == ts
+        assert core.timestamp_source == k
+        # start of acquisition is now present so this should do nothing
+        assert core.fill_timestamps() is None
+
+    @all_core
+    def test_keyword_diff(self, core: AnyCore) -> None:
+        kws = core.keyword_diff({})["removed"]
+        assert core.keyword_diff(kws) == {"added": {}, "removed": {}, "changed": {}}
+        kws["$PROJ"] = "sync"
+        kws["SITE"] = "A"
+        d = core.keyword_diff(kws)
+        assert d["added"] == {"$PROJ": "sync", "SITE": "A"}
+        core.apply_diff(d)
+        assert core.proj == "sync"
+        assert core.nonstandard_keywords == {"SITE": "A"}
+        assert core.keyword_diff(kws) == {"added": {}, "removed": {}, "changed": {}}
+        with pytest.raises(pf.PyreflowException):
+            core.apply_diff(d)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_keyword_origins(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        assert set(core.keyword_origins.values()) == {"defaulted"}
+        p = tmp_path / "keyword_origins.fcs"
+        core.write_dataset(p)
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert set(nu_core.keyword_origins.values()) == {"present"}
+        assert nu_core.keyword_origin("$p1n") == "present"
+        assert nu_core.keyword_origin("$PROJ") == "absent"
+        nu_core.proj = "sync"
+        assert nu_core.keyword_origin("$PROJ") == "defaulted"
+
+    @all_core
+    def test_namespace_keywords(self, core: AnyCore) -> None:
+        core.nonstandard_keywords = {"FJ_BATCH": "1", "mylab_batch": "2"}
+        assert core.namespace_keywords("MYLAB_") == {"batch": "2"}
+        core.set_namespace_keywords("MYLAB_", {"SITE": "A"})
+        assert core.nonstandard_keywords == {"FJ_BATCH": "1", "MYLAB_SITE": "A"}
+        with pytest.raises(ValueError):
+            core.set_namespace_keywords("$LAB_", {})
+
     @parameterize_versions("core", ["2_0"], ["text", "dataset"])
     def test_temporal_no_timestep(
         self, core: pf.CoreTEXT2_0 | pf.CoreDataset2_0
@@ -901,6 +1036,15 @@ class TestCore:
         with pytest.raises(IndexError):
             core.remove_measurement_by_name(LINK_NAME1)
 
+    @all_core
+    def test_remove_meas_and_links(self, core: AnyCore) -> None:
+        core.trigger = (LINK_NAME1, 0)
+        r = core.remove_measurement_and_links(LINK_NAME1)
+        assert r["index"] == 0
+        assert "$TR" in r["links"]["removed"]
+        assert core.trigger is None
+        assert len(core.measurements) == 0
+
     @all_core
     def test_remove_meas_by_index(self, core: AnyCore) -> None:
         assert len(core.measurements) == 1
@@ -908,6 +1052,22 @@ class TestCore:
         with pytest.raises(IndexError):
             core.remove_measurement_by_index(0)
 
+    @all_core2
+    def test_align_to_panel(self, core: AnyCore) -> None:
+        assert core.all_shortnames == [LINK_NAME1, LINK_NAME2]
+        core.align_to_panel([LINK_NAME2, LINK_NAME1])
+        assert core.all_shortnames == [LINK_NAME2, LINK_NAME1]
+        with pytest.raises(pf.PyreflowException):
+            core.align_to_panel([LINK_NAME1])
+        with pytest.raises(pf.PyreflowException):
+            core.align_to_panel([LINK_NAME1, LINK_NAME2, "rogue"])
+        core.align_to_panel([LINK_NAME1], allow_extra=True)
+        assert core.all_shortnames == [LINK_NAME1, LINK_NAME2]
+        core.align_to_panel(
+            ["rogue", LINK_NAME2, LINK_NAME1], allow_missing=True
+        )
+        assert core.all_shortnames == [LINK_NAME2, LINK_NAME1]
+
     @pytest.mark.parametrize(
         "core, optical",
         [
@@ -1087,6 +1247,40 @@ class TestCore:
         assert df1.width == 0
         assert len(core.measurements) == 0
 
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_column_metadata(self, core: AnyCoreDataset) -> None:
+        md = core.column_metadata
+        assert list(md) == core.data.columns
+        assert all(m["datatype"] == "I" for m in md.values())
+        assert all(m["range"] == 9001 for m in md.values())
+        assert all(m["transform"] == 1.0 for m in md.values())
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_data_arrow(self, core: AnyCoreDataset) -> None:
+        df = core.data
+        tbl = core.data_arrow()
+        assert tbl.column_names == df.columns
+        assert tbl.num_rows == df.height
+        assert pl.from_arrow(tbl).equals(df)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_to_anndata(self, core: AnyCoreDataset) -> None:
+        pytest.importorskip("anndata")
+        adata = pf.to_anndata(core)
+        assert adata.shape == core.data.shape
+        assert list(adata.var_names) == core.data.columns
+        assert list(adata.var["range"]) == [9001.0] * core.data.width
+        assert adata.uns["version"] == core.version
+        assert adata.uns["keywords"] == core.standard_keywords()
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_to_flowkit_sample(self, core: AnyCoreDataset) -> None:
+        pytest.importorskip("flowkit")
+        sample = pf.to_flowkit_sample(core, sample_id="test")
+        assert sample.id == "test"
+        assert sample.event_count == core.data.height
+        assert list(sample.pnn_labels) == core.data.columns
+
     @parameterize_versions("core", ["2_0", "3_0"], ["text2", "dataset2"])
     def test_ordered_layout(
         self,
@@ -1290,6 +1484,15 @@ class TestCore:
         core.all_scales = [(), ()]
         new = core.to_version_3_1()
         assert isinstance(new, target)
+        assert new.conversion_log is None
+        logged = core.to_version_3_1(record_conversion=True)
+        log = logged.conversion_log
+        assert log is not None
+        assert log["from"] == "FCS2.0"
+        assert log["to"] == "FCS3.1"
+        assert "FIREFLOW_CONVERSION" in logged.nonstandard_keywords
+        logged.conversion_log = None
+        assert logged.conversion_log is None
 
     @pytest.mark.parametrize(
         "core, target",
@@ -1426,6 +1629,71 @@ class TestCore:
         new = core.to_version_3_2()
         assert isinstance(new, target)
 
+    @parameterize_versions("core", ["3_2"], ["dataset"])
+    def test_carrier(self, tmp_path: Path, core: pf.CoreDataset3_2) -> None:
+        assert core.carrier is None
+        c: Carrier = {"carrierid": "plate9", "carriertype": None, "locationid": "A1"}
+        core.carrier = c
+        assert core.carrierid == "plate9"
+        assert core.locationid == "A1"
+        p = tmp_path / "carrier.fcs"
+        core.write_dataset(p)
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.carrier == c
+        with pytest.raises(pf.PyreflowException):
+            nu_core.carrier = {**c, "carrierid": " "}
+        nu_core.carrierid = None
+        with pytest.raises(pf.PyreflowException):
+            _ = nu_core.carrier
+        nu_core.carrier = None
+        assert nu_core.locationid is None
+
+    @parameterize_versions("core", ["3_2"], ["dataset"])
+    def test_legacy(self, tmp_path: Path, core: pf.CoreDataset3_2) -> None:
+        assert core.legacy == {}
+        p = tmp_path / "legacy.fcs"
+        core.write_dataset(p)
+        legacy = {"UNICODE": "UTF-8,$CYT", "GATE": "0"}
+        with pytest.warns(pf.PyreflowWarning):
+            nu_core, uncore = pf.fcs_read_std_dataset(
+                p, append_standard_keywords=legacy
+            )
+        assert nu_core.legacy == {"$UNICODE": "UTF-8,$CYT", "$GATE": "0"}
+        assert len(uncore.extra.pseudostandard) == 0
+        with pytest.raises(pf.PyreflowException):
+            pf.fcs_read_std_dataset(
+                p, append_standard_keywords=legacy, disallow_deprecated=True
+            )
+
+    @pytest.mark.parametrize(
+        "core",
+        [lazy_fixture(c) for c in ["text2_3_1", "dataset2_3_1"]],
+    )
+    def test_3_1_to_3_2_strip_deprecated(
+        self, core: pf.CoreTEXT3_1 | pf.CoreDataset3_1
+    ) -> None:
+        core.cyt = "Cygnus X-1"
+        core.plateid = "plate9"
+        core.platename = "my plate"
+        core.date = date(2025, 1, 2)
+        core.btim = time(12, 0)
+        with pytest.warns(pf.PyreflowWarning):
+            new = core.to_version_3_2(strip_deprecated=True)
+        assert new.carrierid == "plate9"
+        assert new.plateid is None
+        assert new.platename is None
+        assert new.mode is None
+        # timestamps cannot be converted without a timezone
+        assert new.date == date(2025, 1, 2)
+        assert new.begindatetime is None
+
+        tz = timezone(timedelta(hours=-5))
+        with pytest.warns(pf.PyreflowWarning):
+            new = core.to_version_3_2(strip_deprecated=True, timestamp_offset=tz)
+        assert new.date is None
+        assert new.btim is None
+        assert new.begindatetime == datetime(2025, 1, 2, 12, tzinfo=tz)
+
     @pytest.mark.parametrize(
         "core, target",
         [
@@ -1791,6 +2059,9 @@ class TestLayouts:
         assert new.typed_ranges == types
 
 
+GOLDEN_DIR = Path(__file__).parents[2] / "crates" / "fireflow-core" / "tests" / "golden"
+
+
 class TestReadWrite:
     @staticmethod
     def _assert_uncore_empty(
@@ -1833,6 +2104,72 @@ class TestReadWrite:
         self._assert_uncore_empty(un_core)
         assert core == nu_core
 
+    @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["text"])
+    def test_text_instrument_registry(self, tmp_path: Path, core: AnyCoreTEXT) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "text_registry.fcs"
+        core.cyt = "meat grinder"
+        core.cytsn = "MG-42"
+        core.nonstandard_keywords = {"SITE_NAME": "basement"}
+        core.write_text(p)
+
+        def registry(cyt: str | None, cytsn: str | None) -> dict[str, str]:
+            assert cyt == "meat grinder"
+            assert cytsn == "MG-42"
+            return {"SITE_NAME": "attic", "SITE_INSTRUMENT": "Grinder #2"}
+
+        nu_core, _ = pf.fcs_read_std_text(
+            p, time_meas_pattern=None, instrument_registry=registry
+        )
+        assert nu_core.nonstandard_keywords == {
+            "SITE_NAME": "basement",
+            "SITE_INSTRUMENT": "Grinder #2",
+        }
+
+        def broken(cyt: str | None, cytsn: str | None) -> dict[str, str]:
+            raise ValueError("no such instrument")
+
+        with pytest.raises(pf.PyreflowException):
+            pf.fcs_read_std_text(
+                p,
+                time_meas_pattern=None,
+                instrument_registry=broken,
+                warnings_are_errors=True,
+            )
+
+    @parameterize_versions("core", ["3_1", "3_2"], ["text"])
+    def test_text_keyword_extensions(self, tmp_path: Path, core: AnyCoreTEXT) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "text_extensions.fcs"
+        core.write_text(p)
+        extra = {"PLATEROWS": "008"}
+
+        def rows(x: str) -> str:
+            return str(int(x))
+
+        nu_core, uncore = pf.fcs_read_std_text(
+            p,
+            time_meas_pattern=None,
+            append_standard_keywords=extra,
+            keyword_extensions={"$PLATEROWS": rows},
+        )
+        assert nu_core.extension_keywords == {"$PLATEROWS": "8"}
+        assert len(uncore.extra.pseudostandard) == 0
+
+        def broken(x: str) -> str:
+            raise ValueError("not a plate")
+
+        with pytest.raises(pf.PyreflowException):
+            pf.fcs_read_std_text(
+                p,
+                time_meas_pattern=None,
+                append_standard_keywords=extra,
+                keyword_extensions={"PLATEROWS": broken},
+                warnings_are_errors=True,
+            )
+
     @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["blank_dataset"])
     def test_dataset_empty(self, tmp_path: Path, core: AnyCoreDataset) -> None:
         d = tmp_path
@@ -1842,13 +2179,22 @@ class TestReadWrite:
         nu_core, un_core = pf.fcs_read_std_dataset(p)
         self._assert_uncore_empty(un_core)
         assert core == nu_core
+        # if asked, the software which wrote the file is recorded
+        core.write_dataset(p, record_provenance=True)
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        prov = nu_core.provenance
+        assert prov is not None
+        assert prov["software"] is not None
+        assert prov["software"].startswith("fireflow ")
+        assert prov["parent"] is None
+        assert prov["operation"] is None
 
     @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
     def test_dataset_non_empty_1(self, tmp_path: Path, core: AnyCoreDataset) -> None:
         d = tmp_path
         d.mkdir(exist_ok=True)
         p = d / "dataset1.fcs"
-        core.write_dataset(p)
+        assert core.write_dataset(p) == {"lossy_values": [0], "rounding": [None]}
         nu_core, un_core = pf.fcs_read_std_dataset(
             p, time_meas_pattern=None, warnings_are_errors=True
         )
@@ -1869,6 +2215,128 @@ class TestReadWrite:
         self._assert_uncore_empty(un_core)
         assert core == nu_core
 
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_exclude(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_exclude.fcs"
+        core.write_dataset(p)
+        nu_core, _ = pf.fcs_read_std_dataset(
+            p,
+            time_meas_pattern=None,
+            exclude_measurement_pattern="^maple",
+        )
+        assert nu_core.par == core.par - 1
+        assert LINK_NAME2 not in nu_core.all_shortnames
+        assert nu_core.data.width == core.data.width - 1
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_estimated_memory(
+        self, tmp_path: Path, core: AnyCoreDataset
+    ) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_memory.fcs"
+        core.write_dataset(p)
+        _, un_core = pf.fcs_read_std_text(p, time_meas_pattern=None)
+        nu_core, _ = pf.fcs_read_std_dataset(p, time_meas_pattern=None)
+        assert un_core.estimated_memory_bytes == nu_core.data.estimated_size()
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_meas_warnings(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_meas_warnings.fcs"
+        core.write_dataset(p)
+        bad = {"P1V": "not a voltage"}
+        with pytest.warns(pf.PyreflowWarning):
+            _, uncore = pf.fcs_read_std_dataset(
+                p, time_meas_pattern=None, append_standard_keywords=bad
+            )
+        assert len(uncore.meas_warnings) == 1
+        mw = uncore.meas_warnings[0]
+        assert mw.index == 0
+        assert mw.shortname == core.all_shortnames[0]
+        assert len(mw.warnings) == 1
+        with pytest.warns(pf.PyreflowWarning):
+            _, un_text = pf.fcs_read_std_text(
+                p, time_meas_pattern=None, append_standard_keywords=bad
+            )
+        assert un_text.meas_warnings == uncore.meas_warnings
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_unparsed_roundtrip(
+        self, tmp_path: Path, core: AnyCoreDataset
+    ) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_unparsed.fcs"
+        core.write_dataset(p)
+        bad = {"P1V": "not a voltage", "LOST": "lots"}
+        with pytest.warns(pf.PyreflowWarning):
+            nu_core, _ = pf.fcs_read_std_dataset(
+                p, time_meas_pattern=None, append_standard_keywords=bad
+            )
+        q = d / "dataset_unparsed_out.fcs"
+        nu_core.write_dataset(q)
+        out = pf.fcs_read_raw_text(q)
+        assert out.std["$P1V"] == "not a voltage"
+        assert out.std["$LOST"] == "lots"
+
+    @pytest.mark.parametrize(
+        "golden", sorted(GOLDEN_DIR.glob("*.json")), ids=lambda p: p.stem
+    )
+    def test_golden(self, golden: Path) -> None:
+        expected = json.loads(golden.read_text())
+        p = golden.with_suffix(".fcs")
+        out = pf.fcs_read_raw_text(p)
+        assert out.version == expected["version"]
+        assert dict(out.std) == expected["keywords"]
+        core, _ = pf.fcs_read_std_dataset(p)
+        assert core.version == expected["version"]
+        assert core.data.columns == expected["shortnames"]
+        for i, xs in enumerate(expected["columns"]):
+            assert core.data[:, i].to_list() == xs
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_max_warnings(
+        self, tmp_path: Path, core: AnyCoreDataset
+    ) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_max_warnings.fcs"
+        core.write_dataset(p)
+        bad = {"P1V": "not a voltage", "LOST": "lots"}
+        with pytest.warns(pf.PyreflowWarning) as ws:
+            pf.fcs_read_std_dataset(
+                p, time_meas_pattern=None, append_standard_keywords=bad, max_warnings=1
+            )
+        assert len(ws) == 2
+        assert str(ws[1].message).endswith("further warnings suppressed")
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_dataset_orphan_meas(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_orphan_meas.fcs"
+        core.write_dataset(p)
+        orphans = {"P99N": "ghost", "P01S": "padded"}
+        nu_core, _ = pf.fcs_read_std_dataset(
+            p,
+            time_meas_pattern=None,
+            append_standard_keywords=orphans,
+            absorb_orphan_measurement_keywords=True,
+        )
+        assert nu_core.nonstandard_keywords["P99N"] == "ghost"
+        assert nu_core.nonstandard_keywords["P01S"] == "padded"
+        with pytest.raises(pf.PyreflowException):
+            pf.fcs_read_std_dataset(
+                p,
+                time_meas_pattern=None,
+                append_standard_keywords=orphans,
+                disallow_orphan_measurement_keywords=True,
+            )
+
     @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["dataset2"])
     def test_dataset_supp_text(self, tmp_path: Path, core: AnyCoreDataset) -> None:
         d = tmp_path
@@ -1888,6 +2356,46 @@ class TestReadWrite:
         self._assert_uncore_empty(un_core)
         assert core == nu_core
 
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_raw_dataset_arrow(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_arrow.fcs"
+        core.write_dataset(p)
+        polars_out = pf.fcs_read_raw_dataset(p)
+        arrow_out = pf.fcs_read_raw_dataset(p, as_arrow=True)
+        assert isinstance(arrow_out.data, pa.Table)
+        assert pl.from_arrow(arrow_out.data).equals(polars_out.data)
+        assert arrow_out.data_seg == polars_out.data_seg
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset2"])
+    def test_raw_dataset_passthru(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_passthru.fcs"
+        core.write_dataset(p)
+        out = pf.fcs_read_raw_dataset_passthru(p)
+        begin, end = out.data_seg
+        assert len(out.data) == end - begin + 1
+        assert out.layout is not None
+        assert out.layout.datatype == "I"
+        assert len(out.layout.widths) == len(core.measurements)
+        assert out.partial_layout is None
+
+        # corrupt $DATATYPE such that the layout cannot be determined
+        bad = p.read_bytes().replace(b"$DATATYPE\x1eI\x1e", b"$DATATYPE\x1eQ\x1e")
+        p.write_bytes(bad)
+        with pytest.raises(pf.PyreflowException):
+            pf.fcs_read_raw_dataset(p)
+        with pytest.warns(pf.PyreflowWarning):
+            bad_out = pf.fcs_read_raw_dataset_passthru(p)
+        assert bad_out.layout is None
+        assert bad_out.data == out.data
+        assert bad_out.partial_layout is not None
+        assert bad_out.partial_layout.datatype is None
+        assert bad_out.partial_layout.byteord is not None
+        assert bad_out.partial_layout.widths == out.layout.widths
+
     @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
     def test_dataset_conversion(self, tmp_path: Path, core: AnyCoreDataset) -> None:
         d = tmp_path
@@ -1900,4 +2408,258 @@ class TestReadWrite:
         with pytest.raises(pf.PyreflowException):
             core.write_dataset(p)
         # TODO shouldn't this emit a warning?
-        core.write_dataset(p, skip_conversion_check=True)
+        report = core.write_dataset(p, skip_conversion_check=True)
+        assert report["lossy_values"] == [3]
+        assert report["rounding"] == ["error"]
+        report = core.write_dataset(p, float_rounding="round")
+        assert report == {"lossy_values": [0], "rounding": ["round"]}
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.data[:, 0].to_list() == [2, 3, 4]
+        report = core.write_dataset(
+            p, float_rounding="round", float_rounding_overrides={0: "truncate"}
+        )
+        assert report["rounding"] == ["truncate"]
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.data[:, 0].to_list() == [1, 2, 3]
+        with pytest.raises(ValueError):
+            core.write_dataset(p, float_rounding="ceiling")  # type: ignore
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_namespaces(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        p = tmp_path / "namespaces.fcs"
+        core.nonstandard_keywords = {"MYLAB_SITE": "A"}
+        core.write_dataset(p, namespaces=["FJ_", "MYLAB_"])
+        with pytest.raises(pf.PyreflowException):
+            core.write_dataset(p, namespaces=["MYLAB_", "MYLAB_X"])
+        core.all_meas_nonstandard_keywords = [{"mylab_site": "B"}]
+        with pytest.raises(pf.PyreflowException):
+            core.write_dataset(p, namespaces=["MYLAB_"])
+        core.write_dataset(p)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_grouped_keywords(self, core: AnyCoreDataset) -> None:
+        core.nonstandard_keywords = {"NOTE": "a"}
+        core.all_meas_nonstandard_keywords = [{"P1FOO": "x"}]
+        g = core.grouped_keywords()
+        assert g["keywords"]["NOTE"] == "a"
+        assert "P1FOO" not in g["keywords"]
+        assert g["measurements"] == [{"P1FOO": "x"}]
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_control_chars(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        p = tmp_path / "control_chars.fcs"
+        core.nonstandard_keywords = {"NOTE": "line1\nline2\x1eend"}
+        core.write_dataset(p)
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.nonstandard_keywords["NOTE"] == "line1\nline2\x1eend"
+        with pytest.warns(pf.PyreflowWarning):
+            nu_core, _ = pf.fcs_read_std_dataset(p, control_chars="strip")
+        assert nu_core.nonstandard_keywords["NOTE"] == "line1line2end"
+        with pytest.warns(pf.PyreflowWarning):
+            core.write_dataset(p, control_chars="escape")
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.nonstandard_keywords["NOTE"] == "line1\\nline2\\x1Eend"
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_raw_text_allowlist(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        p = tmp_path / "allowlist.fcs"
+        core.nonstandard_keywords = {"NOTE": "hi", "OTHER": "skipped"}
+        core.write_dataset(p)
+        out = pf.fcs_read_raw_text(p, keyword_allowlist=["$par", "note"])
+        assert out.std["$PAR"] == str(core.par)
+        assert "$NEXTDATA" in out.std
+        assert "$TOT" not in out.std
+        assert out.nonstd == {"NOTE": "hi"}
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_rewrite_fcs_file(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        p = tmp_path / "in.fcs"
+        out = tmp_path / "out.fcs"
+        core.proj = "old"
+        core.nonstandard_keywords = {"SITE": "A", "DROPME": "x"}
+        core.write_dataset(p)
+        edits = {"$proj": "new", "site": "B", "DROPME": None, "$COM": "fixed"}
+        report = pf.rewrite_fcs_file(p, out, edits, allow_odd=True, delim=47)
+        assert report["lossy_values"] == []
+        nu_core, _ = pf.fcs_read_std_dataset(out)
+        assert nu_core.proj == "new"
+        assert nu_core.com == "fixed"
+        assert nu_core.nonstandard_keywords == {"SITE": "B"}
+        with pytest.raises(TypeError):
+            pf.rewrite_fcs_file(p, out, {}, not_an_option=True)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_solidify_delimited(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        assert core.delimited_widths is None
+        assert core.solidify_delimited() is False
+        core.layout = pf.DelimAsciiLayout([9])
+        core.data = pl.DataFrame([pl.Series("blub", [1, 20, 300], dtype=pl.UInt64)])
+        assert core.delimited_widths == [3]
+        assert core.solidify_delimited() is True
+        assert core.delimited_widths is None
+        assert core.layout.datatype == "A"
+        assert core.layout.char_widths == [3]
+        core.layout = pf.DelimAsciiLayout([9])
+        assert core.solidify_delimited("big") is True
+        assert core.layout.datatype == "I"
+        report = core.write_dataset(tmp_path / "solid.fcs")
+        assert report["lossy_values"] == [0]
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_aligned(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_aligned.fcs"
+        core.write_dataset(p, segment_alignment=512)
+        hdr = pf.fcs_read_header(p)
+        assert hdr.segments.text[0] % 512 == 0
+        assert hdr.segments.data[0] % 512 == 0
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert core == nu_core
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_other_pairs(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_other_pairs.fcs"
+        core.others = [b"other"]
+        core.write_dataset(p, other_pairs=2)
+        raw = p.read_bytes()
+        # the unused pair should be zeros right-aligned in 8-byte fields
+        assert raw[74:90] == b"       0       0"
+        hdr = pf.fcs_read_header(p)
+        assert hdr.segments.text[0] == 90
+        with pytest.raises(pf.PyreflowException):
+            core.write_dataset(p, other_pairs=0)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_read_std_datasets(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "one.fcs"
+        core.write_dataset(p)
+        one = p.read_bytes()
+        # point $NEXTDATA in the first dataset to the beginning of the second
+        i = one.index(b"$NEXTDATA") + len("$NEXTDATA") + 1
+        first = one[:i] + str(len(one)).zfill(20).encode() + one[i + 20 :]
+        q = d / "multi.fcs"
+        q.write_bytes(first + one)
+        res = pf.fcs_read_std_datasets(q)
+        assert len(res) == 2
+        for r in res:
+            assert not isinstance(r, pf.PyreflowException)
+            assert r.core == core
+        # a truncated second dataset should not affect the first
+        q.write_bytes(first + one[:-1])
+        res = pf.fcs_read_std_datasets(q)
+        assert len(res) == 2
+        assert not isinstance(res[0], pf.PyreflowException)
+        assert isinstance(res[1], pf.PyreflowException)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_read_std_datasets_concatenated(
+        self, tmp_path: Path, core: AnyCoreDataset
+    ) -> None:
+        p = tmp_path / "one.fcs"
+        core.write_dataset(p)
+        one = p.read_bytes()
+        q = tmp_path / "concat.fcs"
+        q.write_bytes(one + b"\0" + one)
+        assert len(pf.fcs_read_std_datasets(q)) == 1
+        res = pf.fcs_read_std_datasets_concatenated(q)
+        assert [o for o, _ in res] == [0, len(one) + 1]
+        for _, r in res:
+            assert not isinstance(r, pf.PyreflowException)
+            assert r.core == core
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_histogram(self, core: AnyCoreDataset) -> None:
+        n = core.all_shortnames[0]
+        h = core.channel_histogram(n, bins=4)
+        assert len(h["edges"]) == 5
+        assert sum(h["counts"]) == len(core.data)
+        lo, hi = core.channel_percentiles(n, [0, 100])
+        assert lo == h["edges"][0]
+        assert hi == h["edges"][-1]
+        with pytest.raises(IndexError):
+            core.channel_histogram("nope")
+        with pytest.raises(ValueError):
+            core.channel_percentiles(n, [101])
+
+    @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["dataset"])
+    def test_split_by_time(
+        self, core: pf.CoreDataset3_0 | pf.CoreDataset3_1 | pf.CoreDataset3_2
+    ) -> None:
+        assert core.split_by_time(2.0) is None
+        assert core.time_chunks(2.0) is None
+        core.set_temporal(LINK_NAME1, 1.0)
+        assert core.time_chunks(2.0) == [(0, 2), (2, 3)]
+        core.btim = time(12, 0, 0)
+        core.date = date(1991, 8, 25)
+        xs = core.split_by_time(2.0)
+        assert xs is not None
+        assert [len(x.data) for x in xs] == [2, 1]
+        assert xs[0].btim == time(12, 0, 0)
+        assert xs[0].etim == time(12, 0, 1)
+        assert xs[1].btim == time(12, 0, 2)
+        assert xs[1].etim is None
+        assert all(x.date == date(1991, 8, 25) for x in xs)
+        p = xs[1].provenance
+        assert p is not None
+        assert p["operation"] == "split_by_time interval=2 slice=1"
+        assert p["parent"] is None
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_demultiplex(self, core: AnyCoreDataset) -> None:
+        core.smno = "pool"
+        xs = core.demultiplex([(LINK_NAME1, 2)])
+        assert len(xs) == 1
+        assert len(xs[0].data) == 2
+        assert xs[0].smno == f"pool-{LINK_NAME1}"
+        assert xs[0].src is None
+        p = xs[0].provenance
+        assert p is not None
+        assert p["operation"] == f"demultiplex channel={LINK_NAME1}"
+        assert core.demultiplex([]) == []
+        with pytest.raises(IndexError):
+            core.demultiplex([("nope", 1)])
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_find_duplicate_events(self, core: AnyCoreDataset) -> None:
+        r = core.find_duplicate_events([LINK_NAME1])
+        assert r["events"] == len(core.data)
+        assert len(r["duplicates"]) == sum(x["len"] for x in r["runs"])
+        assert core.find_duplicate_events()["events"] == len(core.data)
+        with pytest.raises(IndexError):
+            core.find_duplicate_events(["nope"])
+
+    @parameterize_versions("core", ["3_0", "3_1", "3_2"], ["dataset"])
+    def test_push_optical_like(
+        self,
+        core: pf.CoreDataset3_0 | pf.CoreDataset3_1 | pf.CoreDataset3_2,
+        series1: pl.Series,
+    ) -> None:
+        core.all_filters = ["red"]
+        core.all_longnames = ["template"]
+        core.push_optical_like(0, series1, "copy", 100)
+        assert core.all_shortnames == [LINK_NAME1, "copy"]
+        assert core.all_filters == ["red", "red"]
+        assert core.all_longnames == ["template", None]
+        with pytest.raises(pf.PyreflowException):
+            core.push_optical_like(2, series1, "oob", 100)
+        core.set_temporal(LINK_NAME1, 1.0)
+        with pytest.raises(pf.PyreflowException):
+            core.push_optical_like(0, series1, "oops", 100)
+
+    @parameterize_versions("core", ["2_0", "3_0", "3_1", "3_2"], ["dataset"])
+    def test_dataset_metadata_only(self, tmp_path: Path, core: AnyCoreDataset) -> None:
+        d = tmp_path
+        d.mkdir(exist_ok=True)
+        p = d / "dataset_metadata_only.fcs"
+        stub = core.to_metadata_only()
+        assert stub.is_metadata_only()
+        assert stub.par == core.par
+        stub.write_dataset(p)
+        nu_core, _ = pf.fcs_read_std_dataset(p)
+        assert nu_core.is_metadata_only()
+        assert nu_core.par == core.par